@@ -68,7 +68,7 @@ fn main() {
         1
     );
     assert!(module.start().is_some());
-    assert_eq!(module.function_types().unwrap().len(), 2);
+    assert_eq!(module.types().unwrap().len(), 2);
     assert_eq!(module.imports().unwrap().len(), 1);
     assert_eq!(module.functions().unwrap().len(), 1);
     assert_eq!(module.memories().unwrap().len(), 1);
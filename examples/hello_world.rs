@@ -1,13 +1,13 @@
 use wasm_ast::{
     ControlInstruction, Custom, Data, Export, Expression, Function, FunctionType, Import, Limit,
-    Memory, Module, ModuleSection, ResultType, Start, ValueType,
+    Memory, Module, ModuleSection, RecursiveType, ResultType, Start, ValueType,
 };
 
 fn main() {
     let message = "Hello, World!";
-    let function_types = vec![
-        FunctionType::side_effect(vec![ValueType::I32, ValueType::I32].into()),
-        FunctionType::runnable(),
+    let types: Vec<RecursiveType> = vec![
+        FunctionType::side_effect(vec![ValueType::I32, ValueType::I32].into()).into(),
+        FunctionType::runnable().into(),
     ];
     let imports = vec![Import::function("console".into(), "log".into(), 0)];
     let start = Start::new(1);
@@ -28,7 +28,7 @@ fn main() {
     let footer_custom = vec![Custom::new("footer".into(), Vec::from("foot"))];
 
     let mut builder = Module::builder();
-    builder.set_function_types(Some(function_types.clone()));
+    builder.set_types(Some(types.clone()));
     builder.set_functions(Some(functions.clone()));
     builder.set_memories(Some(memories.clone()));
     builder.set_data(Some(data.clone()));
@@ -41,7 +41,7 @@ fn main() {
 
     let module = builder.build();
 
-    assert_eq!(module.function_types(), Some(function_types.as_slice()));
+    assert_eq!(module.types(), Some(types.as_slice()));
     assert_eq!(module.functions(), Some(functions.as_slice()));
     assert_eq!(module.tables(), None);
     assert_eq!(module.memories(), Some(memories.as_slice()));
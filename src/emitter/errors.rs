@@ -1,6 +1,6 @@
 use thiserror::Error;
 
-/// An error in emitting a WebAssembly module in the binary format.
+/// An error in emitting a WebAssembly module in the binary or text format.
 #[derive(Error, Debug)]
 pub enum EmitError {
     #[error("The given integer type does not have sufficient capacity to store the parsed integer without overflow.")]
@@ -9,4 +9,13 @@ pub enum EmitError {
     IO(#[from] std::io::Error),
     #[error("An error occurred encoding a number into LEB-128.")]
     Encode(#[from] crate::leb128::LEB128Error),
+    #[error("An error occurred formatting the text format.")]
+    Format(#[from] std::fmt::Error),
+    #[error("The vector instruction {0:?} is not defined for its shape.")]
+    UndefinedVectorInstruction(crate::model::VectorInstruction),
+    #[error("The GC instruction {0:?} casts to or from a type that is not a reference type.")]
+    UndefinedGcInstruction(crate::model::GcInstruction),
+    #[cfg(feature = "parser")]
+    #[error("A lazily parsed function body is not a valid expression: {0}")]
+    MalformedBody(#[from] crate::parser::ParseError),
 }
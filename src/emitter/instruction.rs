@@ -1,15 +1,21 @@
 use crate::emitter::errors::EmitError;
-use crate::emitter::types::{emit_reference_type, emit_value_type};
+use crate::emitter::types::{emit_heap_type, emit_value_type};
 use crate::emitter::values::{
-    emit_byte, emit_f32, emit_f64, emit_i32, emit_i64, emit_repeated, emit_u32, emit_vector,
+    emit_byte, emit_bytes, emit_f32, emit_f64, emit_i32, emit_i64, emit_repeated, emit_u32,
+    emit_u64, emit_vector,
 };
 use crate::model::{
-    BlockType, ControlInstruction, Expression, FloatType, Instruction, IntegerType, MemoryArgument,
-    MemoryInstruction, NumberType, NumericInstruction, ParametricInstruction, ReferenceInstruction,
-    SignExtension, TableInstruction, VariableInstruction,
+    AtomicInstruction, AtomicOperation, BlockType, Catch, ControlInstruction, Expression,
+    FloatType, FloatVectorShape, GcInstruction, Instruction, IntegerType, IntegerVectorShape,
+    MemoryArgument, MemoryInstruction, NumberType, NumericInstruction, ParametricInstruction,
+    ReferenceInstruction, SignExtension, TableInstruction, VariableInstruction, VectorHalf,
+    VectorInstruction, VectorShape,
 };
 use std::io::Write;
 
+/// Bit 6 of the alignment in a memarg marks the presence of an explicit memory index.
+const MEMORY_INDEX_FLAG: u32 = 0x40;
+
 /// Emit an expression to the output.
 ///
 /// See https://webassembly.github.io/spec/core/binary/instructions.html#expressions
@@ -18,7 +24,6 @@ pub fn emit_expression<O: Write + ?Sized>(
     output: &mut O,
 ) -> Result<usize, EmitError> {
     let mut bytes = 0;
-    let expression = expression;
 
     for instruction in expression.instructions() {
         bytes += emit_instruction(instruction, output)?;
@@ -38,11 +43,14 @@ pub fn emit_instruction<O: Write + ?Sized>(
 ) -> Result<usize, EmitError> {
     match instruction {
         Instruction::Numeric(instruction) => emit_numeric_instruction(instruction, output),
+        Instruction::Vector(instruction) => emit_vector_instruction(instruction, output),
         Instruction::Reference(instruction) => emit_reference_instruction(instruction, output),
         Instruction::Parametric(instruction) => emit_parametric_instruction(instruction, output),
         Instruction::Variable(instruction) => emit_variable_instruction(instruction, output),
         Instruction::Table(instruction) => emit_table_instruction(instruction, output),
         Instruction::Memory(instruction) => emit_memory_instruction(instruction, output),
+        Instruction::Atomic(instruction) => emit_atomic_instruction(instruction, output),
+        Instruction::Gc(instruction) => emit_gc_instruction(instruction, output),
         Instruction::Control(instruction) => emit_control_instruction(instruction, output),
     }
 }
@@ -586,6 +594,248 @@ fn emit_numeric_instruction<O: Write + ?Sized>(
     Ok(bytes)
 }
 
+/// Emit a vector instruction to the output.
+/// Vector instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+///
+/// See https://webassembly.github.io/spec/core/binary/instructions.html#vector-instructions
+fn emit_vector_instruction<O: Write + ?Sized>(
+    instruction: &VectorInstruction,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let opcode =
+        vector_opcode(instruction).ok_or(EmitError::UndefinedVectorInstruction(*instruction))?;
+    let mut bytes = 0;
+
+    bytes += emit_byte(0xFDu8, output)?;
+    bytes += emit_u32(opcode, output)?;
+
+    match instruction {
+        VectorInstruction::Constant(value) => {
+            bytes += emit_bytes(&value.to_le_bytes(), output, false)?;
+        }
+        VectorInstruction::Shuffle(lanes) => {
+            bytes += emit_bytes(lanes, output, false)?;
+        }
+        VectorInstruction::ExtractLane(.., lane) | VectorInstruction::ReplaceLane(_, lane) => {
+            bytes += emit_byte(lane, output)?;
+        }
+        VectorInstruction::Load(argument)
+        | VectorInstruction::Store(argument)
+        | VectorInstruction::LoadExtend(.., argument)
+        | VectorInstruction::LoadSplat(_, argument)
+        | VectorInstruction::LoadZero(_, argument) => {
+            bytes += emit_memory_argument(argument, output)?;
+        }
+        VectorInstruction::LoadLane(_, argument, lane)
+        | VectorInstruction::StoreLane(_, argument, lane) => {
+            bytes += emit_memory_argument(argument, output)?;
+            bytes += emit_byte(lane, output)?;
+        }
+        _ => {}
+    }
+
+    Ok(bytes)
+}
+
+/// The opcode of a vector instruction following the vector prefix byte.
+/// Instructions that are not defined for their shape do not have an opcode.
+///
+/// See https://webassembly.github.io/spec/core/binary/instructions.html#vector-instructions
+fn vector_opcode(instruction: &VectorInstruction) -> Option<u32> {
+    use FloatVectorShape::{F32x4, F64x2};
+    use IntegerVectorShape::{I16x8, I32x4, I64x2, I8x16};
+
+    if !instruction.is_defined() {
+        return None;
+    }
+
+    let opcode = match *instruction {
+        VectorInstruction::Load(_) => 0,
+        VectorInstruction::LoadExtend(I8x16, sign, _) => 1 + sign_index(sign),
+        VectorInstruction::LoadExtend(I16x8, sign, _) => 3 + sign_index(sign),
+        VectorInstruction::LoadExtend(I32x4, sign, _) => 5 + sign_index(sign),
+        VectorInstruction::LoadSplat(shape, _) => 7 + integer_shape_index(shape),
+        VectorInstruction::Store(_) => 11,
+        VectorInstruction::Constant(_) => 12,
+        VectorInstruction::Shuffle(_) => 13,
+        VectorInstruction::Swizzle => 14,
+        VectorInstruction::Splat(shape) => 15 + shape_index(shape),
+        VectorInstruction::ExtractLane(VectorShape::I8x16, Some(sign), _) => 21 + sign_index(sign),
+        VectorInstruction::ReplaceLane(VectorShape::I8x16, _) => 23,
+        VectorInstruction::ExtractLane(VectorShape::I16x8, Some(sign), _) => 24 + sign_index(sign),
+        VectorInstruction::ReplaceLane(VectorShape::I16x8, _) => 26,
+        VectorInstruction::ExtractLane(VectorShape::I32x4, None, _) => 27,
+        VectorInstruction::ReplaceLane(VectorShape::I32x4, _) => 28,
+        VectorInstruction::ExtractLane(VectorShape::I64x2, None, _) => 29,
+        VectorInstruction::ReplaceLane(VectorShape::I64x2, _) => 30,
+        VectorInstruction::ExtractLane(VectorShape::F32x4, None, _) => 31,
+        VectorInstruction::ReplaceLane(VectorShape::F32x4, _) => 32,
+        VectorInstruction::ExtractLane(VectorShape::F64x2, None, _) => 33,
+        VectorInstruction::ReplaceLane(VectorShape::F64x2, _) => 34,
+        VectorInstruction::Equal(VectorShape::I64x2) => 214,
+        VectorInstruction::NotEqual(VectorShape::I64x2) => 215,
+        VectorInstruction::LessThanInteger(I64x2, _) => 216,
+        VectorInstruction::GreaterThanInteger(I64x2, _) => 217,
+        VectorInstruction::LessThanOrEqualToInteger(I64x2, _) => 218,
+        VectorInstruction::GreaterThanOrEqualToInteger(I64x2, _) => 219,
+        VectorInstruction::Equal(VectorShape::F32x4) => 65,
+        VectorInstruction::NotEqual(VectorShape::F32x4) => 66,
+        VectorInstruction::Equal(VectorShape::F64x2) => 71,
+        VectorInstruction::NotEqual(VectorShape::F64x2) => 72,
+        VectorInstruction::Equal(shape) => 35 + 10 * shape_index(shape),
+        VectorInstruction::NotEqual(shape) => 36 + 10 * shape_index(shape),
+        VectorInstruction::LessThanInteger(shape, sign) => {
+            37 + 10 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::GreaterThanInteger(shape, sign) => {
+            39 + 10 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::LessThanOrEqualToInteger(shape, sign) => {
+            41 + 10 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::GreaterThanOrEqualToInteger(shape, sign) => {
+            43 + 10 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::LessThanFloat(shape) => 67 + 6 * float_shape_index(shape),
+        VectorInstruction::GreaterThanFloat(shape) => 68 + 6 * float_shape_index(shape),
+        VectorInstruction::LessThanOrEqualToFloat(shape) => 69 + 6 * float_shape_index(shape),
+        VectorInstruction::GreaterThanOrEqualToFloat(shape) => 70 + 6 * float_shape_index(shape),
+        VectorInstruction::Not => 77,
+        VectorInstruction::And => 78,
+        VectorInstruction::AndNot => 79,
+        VectorInstruction::Or => 80,
+        VectorInstruction::Xor => 81,
+        VectorInstruction::BitSelect => 82,
+        VectorInstruction::AnyTrue => 83,
+        VectorInstruction::LoadLane(shape, ..) => 84 + integer_shape_index(shape),
+        VectorInstruction::StoreLane(shape, ..) => 88 + integer_shape_index(shape),
+        VectorInstruction::LoadZero(I32x4, _) => 92,
+        VectorInstruction::LoadZero(I64x2, _) => 93,
+        VectorInstruction::Demote => 94,
+        VectorInstruction::Promote => 95,
+        VectorInstruction::CountOnes => 98,
+        VectorInstruction::Ceiling(F32x4) => 103,
+        VectorInstruction::Floor(F32x4) => 104,
+        VectorInstruction::Truncate(F32x4) => 105,
+        VectorInstruction::Nearest(F32x4) => 106,
+        VectorInstruction::Ceiling(F64x2) => 116,
+        VectorInstruction::Floor(F64x2) => 117,
+        VectorInstruction::Truncate(F64x2) => 122,
+        VectorInstruction::Nearest(F64x2) => 148,
+        VectorInstruction::ExtendedAddPairwise(shape, sign) => {
+            124 + 2 * (integer_shape_index(shape) - 1) + sign_index(sign)
+        }
+        VectorInstruction::Q15MultiplyRoundSaturate => 130,
+        VectorInstruction::DotProduct => 186,
+        VectorInstruction::AbsoluteValue(VectorShape::F32x4) => 224,
+        VectorInstruction::Negate(VectorShape::F32x4) => 225,
+        VectorInstruction::AbsoluteValue(VectorShape::F64x2) => 236,
+        VectorInstruction::Negate(VectorShape::F64x2) => 237,
+        VectorInstruction::AbsoluteValue(shape) => 96 + 32 * shape_index(shape),
+        VectorInstruction::Negate(shape) => 97 + 32 * shape_index(shape),
+        VectorInstruction::AllTrue(shape) => 99 + 32 * integer_shape_index(shape),
+        VectorInstruction::Bitmask(shape) => 100 + 32 * integer_shape_index(shape),
+        VectorInstruction::Narrow(shape, sign) => {
+            101 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::Extend(shape, half, sign) => {
+            135 + 32 * (integer_shape_index(shape) - 1) + half_index(half) + 2 * sign_index(sign)
+        }
+        VectorInstruction::ShiftLeft(shape) => 107 + 32 * integer_shape_index(shape),
+        VectorInstruction::ShiftRight(shape, sign) => {
+            108 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::Add(VectorShape::F32x4) => 228,
+        VectorInstruction::Subtract(VectorShape::F32x4) => 229,
+        VectorInstruction::Multiply(VectorShape::F32x4) => 230,
+        VectorInstruction::Add(VectorShape::F64x2) => 240,
+        VectorInstruction::Subtract(VectorShape::F64x2) => 241,
+        VectorInstruction::Multiply(VectorShape::F64x2) => 242,
+        VectorInstruction::Add(shape) => 110 + 32 * shape_index(shape),
+        VectorInstruction::AddSaturate(shape, sign) => {
+            111 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::Subtract(shape) => 113 + 32 * shape_index(shape),
+        VectorInstruction::SubtractSaturate(shape, sign) => {
+            114 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::Multiply(shape) => 117 + 32 * shape_index(shape),
+        VectorInstruction::MinimumInteger(shape, sign) => {
+            118 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::MaximumInteger(shape, sign) => {
+            120 + 32 * integer_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::AverageRounded(shape) => 123 + 32 * integer_shape_index(shape),
+        VectorInstruction::ExtendedMultiply(shape, half, sign) => {
+            156 + 32 * (integer_shape_index(shape) - 1) + half_index(half) + 2 * sign_index(sign)
+        }
+        VectorInstruction::SquareRoot(shape) => 227 + 12 * float_shape_index(shape),
+        VectorInstruction::DivideFloat(shape) => 231 + 12 * float_shape_index(shape),
+        VectorInstruction::MinimumFloat(shape) => 232 + 12 * float_shape_index(shape),
+        VectorInstruction::MaximumFloat(shape) => 233 + 12 * float_shape_index(shape),
+        VectorInstruction::PseudoMinimum(shape) => 234 + 12 * float_shape_index(shape),
+        VectorInstruction::PseudoMaximum(shape) => 235 + 12 * float_shape_index(shape),
+        VectorInstruction::ConvertAndTruncateWithSaturation(shape, sign) => {
+            248 + 4 * float_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::Convert(shape, sign) => {
+            250 + 4 * float_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::RelaxedSwizzle => 256,
+        VectorInstruction::RelaxedTruncate(shape, sign) => {
+            257 + 2 * float_shape_index(shape) + sign_index(sign)
+        }
+        VectorInstruction::RelaxedMultiplyAdd(shape) => 261 + 2 * float_shape_index(shape),
+        VectorInstruction::RelaxedNegativeMultiplyAdd(shape) => 262 + 2 * float_shape_index(shape),
+        VectorInstruction::RelaxedLaneSelect(shape) => 265 + integer_shape_index(shape),
+        VectorInstruction::RelaxedMinimum(shape) => 269 + 2 * float_shape_index(shape),
+        VectorInstruction::RelaxedMaximum(shape) => 270 + 2 * float_shape_index(shape),
+        VectorInstruction::RelaxedQ15MultiplyRound => 273,
+        VectorInstruction::RelaxedDotProduct => 274,
+        VectorInstruction::RelaxedDotProductAdd => 275,
+        _ => return None,
+    };
+
+    Some(opcode)
+}
+
+/// The offset of a sign extension within a group of vector or GC opcodes.
+fn sign_index(sign: SignExtension) -> u32 {
+    match sign {
+        SignExtension::Signed => 0,
+        SignExtension::Unsigned => 1,
+    }
+}
+
+/// The offset of a vector half within a group of vector opcodes.
+fn half_index(half: VectorHalf) -> u32 {
+    match half {
+        VectorHalf::Low => 0,
+        VectorHalf::High => 1,
+    }
+}
+
+/// The position of a shape in the order the vector opcodes assign to shapes.
+fn shape_index(shape: VectorShape) -> u32 {
+    match shape {
+        VectorShape::I8x16 => 0,
+        VectorShape::I16x8 => 1,
+        VectorShape::I32x4 => 2,
+        VectorShape::I64x2 => 3,
+        VectorShape::F32x4 => 4,
+        VectorShape::F64x2 => 5,
+    }
+}
+
+fn integer_shape_index(shape: IntegerVectorShape) -> u32 {
+    shape_index(shape.into())
+}
+
+fn float_shape_index(shape: FloatVectorShape) -> u32 {
+    shape_index(shape.into()) - 4
+}
+
 /// Emit a reference instruction to the output.
 ///
 /// See https://webassembly.github.io/spec/core/binary/instructions.html#reference-instructions
@@ -598,7 +848,7 @@ pub fn emit_reference_instruction<O: Write + ?Sized>(
     match instruction {
         ReferenceInstruction::Null(kind) => {
             bytes += emit_byte(0xD0u8, output)?;
-            bytes += emit_reference_type(*kind, output)?;
+            bytes += emit_heap_type(*kind, output)?;
         }
         ReferenceInstruction::IsNull => {
             bytes += emit_byte(0xD1u8, output)?;
@@ -607,6 +857,12 @@ pub fn emit_reference_instruction<O: Write + ?Sized>(
             bytes += emit_byte(0xD2u8, output)?;
             bytes += emit_u32(index, output)?;
         }
+        ReferenceInstruction::Equal => {
+            bytes += emit_byte(0xD3u8, output)?;
+        }
+        ReferenceInstruction::AsNonNull => {
+            bytes += emit_byte(0xD4u8, output)?;
+        }
     }
 
     Ok(bytes)
@@ -829,41 +1085,237 @@ pub fn emit_memory_instruction<O: Write + ?Sized>(
             bytes += emit_byte(0x3Eu8, output)?;
             bytes += emit_memory_argument(memory_argument, output)?;
         }
-        MemoryInstruction::Size => {
+        MemoryInstruction::Size(memory) => {
             bytes += emit_byte(0x3Fu8, output)?;
-            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(memory, output)?;
         }
-        MemoryInstruction::Grow => {
+        MemoryInstruction::Grow(memory) => {
             bytes += emit_byte(0x40u8, output)?;
-            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(memory, output)?;
         }
-        MemoryInstruction::Init(index) => {
+        MemoryInstruction::Init(data, memory) => {
             bytes += emit_byte(0xFCu8, output)?;
             bytes += emit_u32(8u32, output)?;
-            bytes += emit_u32(index, output)?;
-            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(data, output)?;
+            bytes += emit_u32(memory, output)?;
         }
         MemoryInstruction::DataDrop(index) => {
             bytes += emit_byte(0xFCu8, output)?;
             bytes += emit_u32(9u32, output)?;
             bytes += emit_u32(index, output)?;
         }
-        MemoryInstruction::Copy => {
+        MemoryInstruction::Copy(destination, source) => {
             bytes += emit_byte(0xFCu8, output)?;
             bytes += emit_u32(10u32, output)?;
-            bytes += emit_byte(0x00u8, output)?;
-            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(destination, output)?;
+            bytes += emit_u32(source, output)?;
         }
-        MemoryInstruction::Fill => {
+        MemoryInstruction::Fill(memory) => {
             bytes += emit_byte(0xFCu8, output)?;
             bytes += emit_u32(11u32, output)?;
+            bytes += emit_u32(memory, output)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Emit an atomic memory instruction to the output.
+/// Atomic instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+///
+/// See https://webassembly.github.io/threads/core/binary/instructions.html#atomic-memory-instructions
+fn emit_atomic_instruction<O: Write + ?Sized>(
+    instruction: &AtomicInstruction,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+
+    bytes += emit_byte(0xFEu8, output)?;
+    bytes += emit_u32(atomic_opcode(instruction), output)?;
+
+    match instruction {
+        AtomicInstruction::Fence => {
             bytes += emit_byte(0x00u8, output)?;
         }
+        AtomicInstruction::Notify(argument)
+        | AtomicInstruction::Wait(_, argument)
+        | AtomicInstruction::Load(_, argument)
+        | AtomicInstruction::Load8(_, argument)
+        | AtomicInstruction::Load16(_, argument)
+        | AtomicInstruction::Load32(argument)
+        | AtomicInstruction::Store(_, argument)
+        | AtomicInstruction::Store8(_, argument)
+        | AtomicInstruction::Store16(_, argument)
+        | AtomicInstruction::Store32(argument)
+        | AtomicInstruction::ReadModifyWrite(_, _, argument)
+        | AtomicInstruction::ReadModifyWrite8(_, _, argument)
+        | AtomicInstruction::ReadModifyWrite16(_, _, argument)
+        | AtomicInstruction::ReadModifyWrite32(_, argument) => {
+            bytes += emit_memory_argument(argument, output)?;
+        }
     }
 
     Ok(bytes)
 }
 
+/// Emit a garbage collection instruction to the output.
+/// GC instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+/// Casts encode the nullability of their target type in the opcode,
+/// while branches on casts encode the nullability of both types in a flags byte.
+///
+/// See https://webassembly.github.io/gc/core/binary/instructions.html#aggregate-instructions
+fn emit_gc_instruction<O: Write + ?Sized>(
+    instruction: &GcInstruction,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+    let undefined = || EmitError::UndefinedGcInstruction(*instruction);
+
+    bytes += emit_byte(0xFBu8, output)?;
+    bytes += emit_u32(gc_opcode(instruction), output)?;
+
+    match *instruction {
+        GcInstruction::StructNew(kind)
+        | GcInstruction::StructNewDefault(kind)
+        | GcInstruction::ArrayNew(kind)
+        | GcInstruction::ArrayNewDefault(kind)
+        | GcInstruction::ArrayGet(kind, _)
+        | GcInstruction::ArraySet(kind)
+        | GcInstruction::ArrayFill(kind) => {
+            bytes += emit_u32(kind, output)?;
+        }
+        GcInstruction::StructGet(kind, index, _)
+        | GcInstruction::StructSet(kind, index)
+        | GcInstruction::ArrayNewFixed(kind, index)
+        | GcInstruction::ArrayNewData(kind, index)
+        | GcInstruction::ArrayNewElement(kind, index)
+        | GcInstruction::ArrayCopy(kind, index)
+        | GcInstruction::ArrayInitData(kind, index)
+        | GcInstruction::ArrayInitElement(kind, index) => {
+            bytes += emit_u32(kind, output)?;
+            bytes += emit_u32(index, output)?;
+        }
+        GcInstruction::Test(kind) | GcInstruction::Cast(kind) => {
+            bytes += emit_heap_type(kind.heap_type().ok_or_else(undefined)?, output)?;
+        }
+        GcInstruction::BranchOnCast(label, source, target)
+        | GcInstruction::BranchOnCastFail(label, source, target) => {
+            let source_heap = source.heap_type().ok_or_else(undefined)?;
+            let target_heap = target.heap_type().ok_or_else(undefined)?;
+            let flags = u8::from(source.is_nullable()) | (u8::from(target.is_nullable()) << 1);
+
+            bytes += emit_byte(flags, output)?;
+            bytes += emit_u32(label, output)?;
+            bytes += emit_heap_type(source_heap, output)?;
+            bytes += emit_heap_type(target_heap, output)?;
+        }
+        GcInstruction::ArrayLength
+        | GcInstruction::AnyConvertExtern
+        | GcInstruction::ExternConvertAny
+        | GcInstruction::ReferenceI31
+        | GcInstruction::I31Get(_) => {}
+    }
+
+    Ok(bytes)
+}
+
+/// The opcode of a GC instruction following the GC prefix byte.
+///
+/// See https://webassembly.github.io/gc/core/binary/instructions.html#aggregate-instructions
+fn gc_opcode(instruction: &GcInstruction) -> u32 {
+    match *instruction {
+        GcInstruction::StructNew(_) => 0x00,
+        GcInstruction::StructNewDefault(_) => 0x01,
+        GcInstruction::StructGet(_, _, None) => 0x02,
+        GcInstruction::StructGet(_, _, Some(extension)) => 0x03 + sign_index(extension),
+        GcInstruction::StructSet(_, _) => 0x05,
+        GcInstruction::ArrayNew(_) => 0x06,
+        GcInstruction::ArrayNewDefault(_) => 0x07,
+        GcInstruction::ArrayNewFixed(_, _) => 0x08,
+        GcInstruction::ArrayNewData(_, _) => 0x09,
+        GcInstruction::ArrayNewElement(_, _) => 0x0A,
+        GcInstruction::ArrayGet(_, None) => 0x0B,
+        GcInstruction::ArrayGet(_, Some(extension)) => 0x0C + sign_index(extension),
+        GcInstruction::ArraySet(_) => 0x0E,
+        GcInstruction::ArrayLength => 0x0F,
+        GcInstruction::ArrayFill(_) => 0x10,
+        GcInstruction::ArrayCopy(_, _) => 0x11,
+        GcInstruction::ArrayInitData(_, _) => 0x12,
+        GcInstruction::ArrayInitElement(_, _) => 0x13,
+        GcInstruction::Test(kind) => 0x14 + u32::from(kind.is_nullable()),
+        GcInstruction::Cast(kind) => 0x16 + u32::from(kind.is_nullable()),
+        GcInstruction::BranchOnCast(_, _, _) => 0x18,
+        GcInstruction::BranchOnCastFail(_, _, _) => 0x19,
+        GcInstruction::AnyConvertExtern => 0x1A,
+        GcInstruction::ExternConvertAny => 0x1B,
+        GcInstruction::ReferenceI31 => 0x1C,
+        GcInstruction::I31Get(extension) => 0x1D + sign_index(extension),
+    }
+}
+
+/// The opcode of an atomic instruction following the atomic prefix byte.
+/// Each group of accesses is ordered by the full width accesses of i32 and i64,
+/// followed by the narrow accesses of i32 and then i64.
+///
+/// See https://webassembly.github.io/threads/core/binary/instructions.html#atomic-memory-instructions
+fn atomic_opcode(instruction: &AtomicInstruction) -> u32 {
+    match *instruction {
+        AtomicInstruction::Notify(_) => 0x00,
+        AtomicInstruction::Wait(IntegerType::I32, _) => 0x01,
+        AtomicInstruction::Wait(IntegerType::I64, _) => 0x02,
+        AtomicInstruction::Fence => 0x03,
+        AtomicInstruction::Load(kind, _) => 0x10 + integer_index(kind),
+        AtomicInstruction::Load8(kind, _) => 0x12 + narrow_index(kind),
+        AtomicInstruction::Load16(kind, _) => 0x13 + narrow_index(kind),
+        AtomicInstruction::Load32(_) => 0x16,
+        AtomicInstruction::Store(kind, _) => 0x17 + integer_index(kind),
+        AtomicInstruction::Store8(kind, _) => 0x19 + narrow_index(kind),
+        AtomicInstruction::Store16(kind, _) => 0x1A + narrow_index(kind),
+        AtomicInstruction::Store32(_) => 0x1D,
+        AtomicInstruction::ReadModifyWrite(operation, kind, _) => {
+            0x1E + operation_index(operation) + integer_index(kind)
+        }
+        AtomicInstruction::ReadModifyWrite8(operation, kind, _) => {
+            0x20 + operation_index(operation) + narrow_index(kind)
+        }
+        AtomicInstruction::ReadModifyWrite16(operation, kind, _) => {
+            0x21 + operation_index(operation) + narrow_index(kind)
+        }
+        AtomicInstruction::ReadModifyWrite32(operation, _) => 0x24 + operation_index(operation),
+    }
+}
+
+/// The offset of a full width atomic access within a group of atomic opcodes.
+fn integer_index(kind: IntegerType) -> u32 {
+    match kind {
+        IntegerType::I32 => 0,
+        IntegerType::I64 => 1,
+    }
+}
+
+/// The offset of a narrow atomic access within a group of atomic opcodes.
+fn narrow_index(kind: IntegerType) -> u32 {
+    match kind {
+        IntegerType::I32 => 0,
+        IntegerType::I64 => 2,
+    }
+}
+
+/// The offset of the group of opcodes for a read-modify-write operation.
+fn operation_index(operation: AtomicOperation) -> u32 {
+    let group = match operation {
+        AtomicOperation::Add => 0,
+        AtomicOperation::Subtract => 1,
+        AtomicOperation::And => 2,
+        AtomicOperation::Or => 3,
+        AtomicOperation::Xor => 4,
+        AtomicOperation::Exchange => 5,
+        AtomicOperation::CompareExchange => 6,
+    };
+
+    group * 7
+}
+
 /// Emit a control instruction to the output.
 ///
 /// See https://webassembly.github.io/spec/core/binary/instructions.html#control-instructions
@@ -922,11 +1374,79 @@ fn emit_control_instruction<O: Write + ?Sized>(
             bytes += emit_byte(0x10u8, output)?;
             bytes += emit_u32(index, output)?;
         }
-        ControlInstruction::CallIndirect(table, kind) => {
+        ControlInstruction::CallIndirect(kind, table) => {
             bytes += emit_byte(0x11u8, output)?;
             bytes += emit_u32(kind, output)?;
             bytes += emit_u32(table, output)?;
         }
+        ControlInstruction::ReturnCall(index) => {
+            bytes += emit_byte(0x12u8, output)?;
+            bytes += emit_u32(index, output)?;
+        }
+        ControlInstruction::ReturnCallIndirect(kind, table) => {
+            bytes += emit_byte(0x13u8, output)?;
+            bytes += emit_u32(kind, output)?;
+            bytes += emit_u32(table, output)?;
+        }
+        ControlInstruction::CallReference(kind) => {
+            bytes += emit_byte(0x14u8, output)?;
+            bytes += emit_u32(kind, output)?;
+        }
+        ControlInstruction::ReturnCallReference(kind) => {
+            bytes += emit_byte(0x15u8, output)?;
+            bytes += emit_u32(kind, output)?;
+        }
+        ControlInstruction::BranchOnNull(index) => {
+            bytes += emit_byte(0xD5u8, output)?;
+            bytes += emit_u32(index, output)?;
+        }
+        ControlInstruction::BranchOnNonNull(index) => {
+            bytes += emit_byte(0xD6u8, output)?;
+            bytes += emit_u32(index, output)?;
+        }
+        ControlInstruction::TryTable(kind, catches, expression) => {
+            bytes += emit_byte(0x1Fu8, output)?;
+            bytes += emit_block_type(kind, output)?;
+            bytes += emit_vector(catches, output, emit_catch)?;
+            bytes += emit_expression(expression, output)?;
+        }
+        ControlInstruction::Throw(tag) => {
+            bytes += emit_byte(0x08u8, output)?;
+            bytes += emit_u32(tag, output)?;
+        }
+        ControlInstruction::ThrowReference => {
+            bytes += emit_byte(0x0Au8, output)?;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Emit a catch clause of a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction to the output.
+///
+/// See https://webassembly.github.io/exception-handling/core/binary/instructions.html#control-instructions
+fn emit_catch<O: Write + ?Sized>(catch: &Catch, output: &mut O) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+
+    match catch {
+        Catch::Catch(tag, label) => {
+            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(tag, output)?;
+            bytes += emit_u32(label, output)?;
+        }
+        Catch::CatchReference(tag, label) => {
+            bytes += emit_byte(0x01u8, output)?;
+            bytes += emit_u32(tag, output)?;
+            bytes += emit_u32(label, output)?;
+        }
+        Catch::CatchAll(label) => {
+            bytes += emit_byte(0x02u8, output)?;
+            bytes += emit_u32(label, output)?;
+        }
+        Catch::CatchAllReference(label) => {
+            bytes += emit_byte(0x03u8, output)?;
+            bytes += emit_u32(label, output)?;
+        }
     }
 
     Ok(bytes)
@@ -955,8 +1475,14 @@ pub fn emit_memory_argument<O: Write + ?Sized>(
 ) -> Result<usize, EmitError> {
     let mut bytes = 0;
 
-    bytes += emit_u32(argument.align(), output)?;
-    bytes += emit_u32(argument.offset(), output)?;
+    if argument.memory() == 0 {
+        bytes += emit_u32(argument.align(), output)?;
+    } else {
+        bytes += emit_u32(argument.align() | MEMORY_INDEX_FLAG, output)?;
+        bytes += emit_u32(argument.memory(), output)?;
+    }
+
+    bytes += emit_u64(argument.offset(), output)?;
 
     Ok(bytes)
 }
@@ -4,14 +4,17 @@ mod errors;
 mod instruction;
 mod module;
 mod sections;
+mod text;
 mod types;
 mod values;
 
-use crate::model::Module;
+use crate::model::{Expression, Function, Module};
 use sections::emit_module;
 use std::fmt::Debug;
 use std::io::Write;
 
+pub use text::{emit_text, emit_text_with, InstructionSyntax};
+
 /// Emits a binary representation of a WebAssembly Abstract Syntax Tree (AST) to a `Write` output.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/index.html>
@@ -31,6 +34,18 @@ pub fn emit_binary<O: Write>(module: &Module, output: &mut O) -> Result<usize, e
     emit_module(module, output)
 }
 
+/// The body of the function, or an error if a lazily parsed body is not a valid expression.
+#[cfg(feature = "parser")]
+fn function_body(function: &Function) -> Result<&Expression, errors::EmitError> {
+    Ok(function.try_body()?)
+}
+
+/// The body of the function, which is always decoded without the parser.
+#[cfg(not(feature = "parser"))]
+fn function_body(function: &Function) -> Result<&Expression, errors::EmitError> {
+    Ok(function.body())
+}
+
 /// Counts the number of bytes written, but does else nothing with the bytes.
 #[derive(Copy, Clone, Debug, Ord, PartialOrd, Eq, PartialEq)]
 struct CountingWrite {
@@ -73,15 +88,16 @@ impl Write for CountingWrite {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "parser"))]
 mod tests {
     use super::*;
     use crate::emitter::errors::EmitError;
     use crate::model::{
-        ControlInstruction, Custom, Data, DataMode, Element, ElementInitializer, ElementMode,
-        Export, ExportDescription, Expression, Function, FunctionType, Global, GlobalType, Import,
-        ImportDescription, Instruction, Limit, Memory, MemoryType, Module, ModuleSection, Name,
-        NumericInstruction, ReferenceType, ResultType, Start, Table, TableType, ValueType,
+        BlockType, ControlInstruction, Custom, Data, DataMode, Element, ElementInitializer,
+        ElementMode, Export, ExportDescription, Expression, Function, FunctionType, Global,
+        GlobalType, HeapType, Import, ImportDescription, Instruction, Limit, Memory, MemoryType,
+        Module, ModuleSection, Name, NumericInstruction, ParametricInstruction,
+        ReferenceInstruction, ReferenceType, ResultType, Start, Table, TableType, ValueType,
     };
     use crate::parser::parse_binary;
     use wasmtime::{Engine, Extern, Func, Instance, Store};
@@ -89,7 +105,7 @@ mod tests {
     fn validate(target: &Module) -> Result<(), EmitError> {
         let mut bytes = Vec::new();
 
-        emit_binary(&target, &mut bytes)?;
+        emit_binary(target, &mut bytes)?;
 
         let parsed = parse_binary(bytes.as_slice())
             .map_err(|_| EmitError::IO(std::io::Error::from(std::io::ErrorKind::NotFound)))?;
@@ -397,6 +413,43 @@ mod tests {
         validate(&module.build()).unwrap();
     }
 
+    #[test]
+    fn signed_immediates_round_trip() {
+        let mut module = Module::builder();
+
+        for parameters in 0..101 {
+            let kind = FunctionType::new(
+                ResultType::new(vec![ValueType::I32; parameters]),
+                ResultType::empty(),
+            );
+
+            module.add_function_type(kind).unwrap();
+        }
+
+        let function = Function::new(
+            0,
+            ResultType::new(vec![ValueType::NullableReference(HeapType::Index(100))]),
+            Expression::new(vec![
+                ReferenceInstruction::Null(HeapType::Index(64)).into(),
+                ParametricInstruction::Drop.into(),
+                ControlInstruction::Block(BlockType::Index(100), Expression::empty()).into(),
+                (-1i32).into(),
+                (i32::MIN).into(),
+                (-65i64).into(),
+                (i64::MIN).into(),
+                64i32.into(),
+            ]),
+        );
+        module.add_function(function).unwrap();
+
+        let module = module.build();
+        let mut bytes = Vec::new();
+
+        emit_binary(&module, &mut bytes).unwrap();
+
+        assert_eq!(parse_binary(&bytes).unwrap(), module);
+    }
+
     #[test]
     fn invalid_module() {
         let mut module = Module::builder();
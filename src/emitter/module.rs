@@ -4,11 +4,11 @@ use crate::emitter::types::{
     emit_global_type, emit_memory_type, emit_reference_type, emit_table_type, emit_value_type,
 };
 use crate::emitter::values::{emit_byte, emit_bytes, emit_name, emit_u32, emit_usize, emit_vector};
-use crate::emitter::CountingWrite;
+use crate::emitter::{function_body, CountingWrite};
 use crate::model::{
     Custom, Data, DataMode, Element, ElementMode, Export, ExportDescription, Expression, Function,
     Global, Import, ImportDescription, Instruction, Memory, ReferenceInstruction, ReferenceType,
-    Start, Table,
+    Start, Table, Tag,
 };
 use std::io::Write;
 
@@ -42,7 +42,7 @@ fn emit_function_code<O: Write + ?Sized>(
         emit_value_type(local, output)?;
     }
 
-    emit_expression(function.body(), output)
+    emit_expression(function_body(function)?, output)
 }
 
 /// Emit an import to the output.
@@ -84,16 +84,33 @@ pub fn emit_import_description<O: Write + ?Sized>(
             bytes += emit_byte(0x03u8, output)?;
             bytes += emit_global_type(global_type, output)?;
         }
+        ImportDescription::Tag(index) => {
+            bytes += emit_byte(0x04u8, output)?;
+            bytes += emit_byte(0x00u8, output)?;
+            bytes += emit_u32(index, output)?;
+        }
     };
 
     Ok(bytes)
 }
 
 /// Emit a table to the output.
+/// Tables with an initializer are prefixed by the bytes `0x40 0x00`.
 ///
-/// See https://webassembly.github.io/spec/core/binary/modules.html#table-section
+/// See https://webassembly.github.io/function-references/core/binary/modules.html#table-section
 pub fn emit_table<O: Write + ?Sized>(table: &Table, output: &mut O) -> Result<usize, EmitError> {
-    emit_table_type(table.kind(), output)
+    match table.initializer() {
+        Some(initializer) => {
+            let mut bytes = 0;
+
+            bytes += emit_bytes(&[0x40, 0x00], output, false)?;
+            bytes += emit_table_type(table.kind(), output)?;
+            bytes += emit_expression(initializer, output)?;
+
+            Ok(bytes)
+        }
+        None => emit_table_type(table.kind(), output),
+    }
 }
 
 /// Emit a memory to the output.
@@ -103,6 +120,19 @@ pub fn emit_memory<O: Write + ?Sized>(memory: &Memory, output: &mut O) -> Result
     emit_memory_type(memory.kind(), output)
 }
 
+/// Emit a tag to the output.
+/// The tag's attribute is always 0, which denotes an exception.
+///
+/// See https://webassembly.github.io/exception-handling/core/binary/modules.html#tag-section
+pub fn emit_tag<O: Write + ?Sized>(tag: &Tag, output: &mut O) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+
+    bytes += emit_byte(0x00u8, output)?;
+    bytes += emit_u32(tag.kind(), output)?;
+
+    Ok(bytes)
+}
+
 /// Emit a global to the output.
 ///
 /// See https://webassembly.github.io/spec/core/binary/modules.html#global-section
@@ -139,6 +169,7 @@ pub fn emit_export_description<O: Write + ?Sized>(
         ExportDescription::Table(index) => (0x01, index),
         ExportDescription::Memory(index) => (0x02, index),
         ExportDescription::Global(index) => (0x03, index),
+        ExportDescription::Tag(index) => (0x04, index),
     };
     let mut bytes = 0;
 
@@ -207,22 +238,24 @@ pub fn emit_element<O: Write + ?Sized>(
                 emit_u32,
             )?;
         }
-        (expressions, ElementMode::Active(table, offset), kind)
+        (expressions, ElementMode::Active(table, offset), ReferenceType::Function)
             if is_function_indices(expressions) =>
         {
             bytes += emit_byte(0x02u8, output)?;
             bytes += emit_u32(table, output)?;
             bytes += emit_expression(offset, output)?;
-            bytes += emit_reference_type(kind, output)?;
+            bytes += emit_byte(0x00u8, output)?;
             bytes += emit_vector(
                 expressions.iter().filter_map(extract_index),
                 output,
                 emit_u32,
             )?;
         }
-        (expressions, ElementMode::Declarative, kind) if is_function_indices(expressions) => {
+        (expressions, ElementMode::Declarative, ReferenceType::Function)
+            if is_function_indices(expressions) =>
+        {
             bytes += emit_byte(0x03u8, output)?;
-            bytes += emit_reference_type(kind, output)?;
+            bytes += emit_byte(0x00u8, output)?;
             bytes += emit_vector(
                 expressions.iter().filter_map(extract_index),
                 output,
@@ -1,9 +1,9 @@
 use crate::emitter::errors::EmitError;
 use crate::emitter::module::{
     emit_custom_content, emit_data, emit_element, emit_export, emit_function, emit_global,
-    emit_import, emit_memory, emit_start, emit_table,
+    emit_import, emit_memory, emit_start, emit_table, emit_tag,
 };
-use crate::emitter::types::emit_function_type;
+use crate::emitter::types::emit_recursive_type;
 use crate::emitter::values::{
     emit_byte, emit_bytes, emit_repeated, emit_u32, emit_usize, emit_vector,
 };
@@ -36,6 +36,8 @@ pub fn emit_module<O: Write>(module: &Module, output: &mut O) -> Result<usize, E
     bytes += emit_custom_sections(module, ModuleSection::Table, output)?;
     bytes += emit_memory_section(module, output)?;
     bytes += emit_custom_sections(module, ModuleSection::Memory, output)?;
+    bytes += emit_tag_section(module, output)?;
+    bytes += emit_custom_sections(module, ModuleSection::Tag, output)?;
     bytes += emit_global_section(module, output)?;
     bytes += emit_custom_sections(module, ModuleSection::Global, output)?;
     bytes += emit_export_section(module, output)?;
@@ -81,10 +83,10 @@ pub fn emit_custom_section<O: Write>(custom: &Custom, output: &mut O) -> Result<
 ///
 /// See https://webassembly.github.io/spec/core/binary/modules.html#type-section
 pub fn emit_type_section<O: Write>(module: &Module, output: &mut O) -> Result<usize, EmitError> {
-    match module.function_types() {
+    match module.types() {
         None => Ok(0),
         Some(types) => emit_section(ModuleSection::Type, output, |o| {
-            emit_vector(types, o, emit_function_type)
+            emit_vector(types, o, emit_recursive_type)
         }),
     }
 }
@@ -144,6 +146,18 @@ pub fn emit_memory_section<O: Write>(module: &Module, output: &mut O) -> Result<
     }
 }
 
+/// Emits the tag section to the output.
+///
+/// See https://webassembly.github.io/exception-handling/core/binary/modules.html#tag-section
+pub fn emit_tag_section<O: Write>(module: &Module, output: &mut O) -> Result<usize, EmitError> {
+    match module.tags() {
+        Some(tags) => emit_section(ModuleSection::Tag, output, |o| {
+            emit_vector(tags, o, emit_tag)
+        }),
+        None => Ok(0),
+    }
+}
+
 /// Emits the global section to the output.
 ///
 /// See https://webassembly.github.io/spec/core/binary/modules.html#global-section
@@ -0,0 +1,1316 @@
+use crate::emitter::text::types::{format_block_type, format_heap_type, format_value_type};
+use crate::emitter::text::values::{format_f32, format_f64};
+use crate::emitter::text::InstructionSyntax;
+use crate::model::{
+    AtomicInstruction, AtomicOperation, BlockType, Catch, CompositeType, ControlInstruction,
+    Expression, FloatType, FloatVectorShape, FunctionType, GcInstruction, ImportDescription,
+    Instruction, IntegerType, IntegerVectorShape, MemoryArgument, MemoryIndex, MemoryInstruction,
+    Module, NumberType, NumericInstruction, ParametricInstruction, ReferenceInstruction,
+    SignExtension, SubType, TableInstruction, TypeIndex, VariableInstruction, VectorHalf,
+    VectorInstruction, VectorShape,
+};
+use std::borrow::Cow;
+
+/// A line of text nested at a given depth.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Line {
+    pub depth: usize,
+    pub text: String,
+}
+
+impl Line {
+    fn new(depth: usize, text: String) -> Self {
+        Line { depth, text }
+    }
+}
+
+/// The types of the module needed to determine the operands of instructions when folding.
+pub struct TextContext<'module> {
+    types: Vec<&'module SubType>,
+    functions: Vec<TypeIndex>,
+    tags: Vec<TypeIndex>,
+}
+
+impl<'module> TextContext<'module> {
+    /// Creates a new context for the given module.
+    pub fn new(module: &'module Module) -> Self {
+        let imports = module.imports().unwrap_or_default();
+        let imported = imports
+            .iter()
+            .filter_map(|import| match import.description() {
+                ImportDescription::Function(kind) => Some(*kind),
+                _ => None,
+            });
+        let defined = module
+            .functions()
+            .unwrap_or_default()
+            .iter()
+            .map(|function| function.kind());
+        let imported_tags = imports
+            .iter()
+            .filter_map(|import| match import.description() {
+                ImportDescription::Tag(kind) => Some(*kind),
+                _ => None,
+            });
+        let defined_tags = module
+            .tags()
+            .unwrap_or_default()
+            .iter()
+            .map(|tag| tag.kind());
+
+        TextContext {
+            types: module.sub_types().collect(),
+            functions: imported.chain(defined).collect(),
+            tags: imported_tags.chain(defined_tags).collect(),
+        }
+    }
+
+    /// The function type at the given index, if any.
+    pub fn function_type(&self, index: TypeIndex) -> Option<&FunctionType> {
+        self.types.get(index as usize)?.function_type()
+    }
+
+    /// The number of fields of a structure type.
+    fn fields(&self, index: TypeIndex) -> Option<usize> {
+        match self.types.get(index as usize)?.kind() {
+            CompositeType::Struct(kind) => Some(kind.fields().len()),
+            _ => None,
+        }
+    }
+
+    /// The number of parameters and results of a function type.
+    fn arity(&self, index: TypeIndex) -> Option<(usize, usize)> {
+        self.function_type(index)
+            .map(|kind| (kind.parameters().len(), kind.results().len()))
+    }
+
+    /// The number of parameters and results of a block type.
+    fn block_arity(&self, kind: &BlockType) -> Option<(usize, usize)> {
+        match kind {
+            BlockType::None => Some((0, 0)),
+            BlockType::ValueType(_) => Some((0, 1)),
+            BlockType::Index(index) => self.arity(*index),
+        }
+    }
+}
+
+/// Formats the instructions of an expression as lines of text, nested at the given depth.
+/// The number of results is used to fold the operands of 𝗋𝖾𝗍𝗎𝗋𝗇 instructions.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#expressions
+pub fn format_expression(
+    expression: &Expression,
+    syntax: InstructionSyntax,
+    context: &TextContext,
+    results: usize,
+    depth: usize,
+) -> Vec<Line> {
+    let mut lines = Vec::new();
+
+    match syntax {
+        InstructionSyntax::Flat => format_flat(expression.instructions(), depth, &mut lines),
+        InstructionSyntax::Folded => {
+            let mut folder = Folder {
+                context,
+                results,
+                labels: vec![Some(results)],
+            };
+
+            folder.format_sequence(expression.instructions(), depth, &mut lines);
+        }
+    }
+
+    lines
+}
+
+/// Formats the instructions of an expression on a single line.
+pub fn format_inline_expression(
+    expression: &Expression,
+    syntax: InstructionSyntax,
+    context: &TextContext,
+) -> String {
+    let lines = format_expression(expression, syntax, context, 1, 0);
+    let text: Vec<&str> = lines.iter().map(|line| line.text.as_str()).collect();
+
+    text.join(" ")
+}
+
+/// Formats a sequence of instructions in the flat (i.e. linear) syntax.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#instructions
+fn format_flat(instructions: &[Instruction], depth: usize, lines: &mut Vec<Line>) {
+    for instruction in instructions {
+        lines.push(Line::new(depth, format_instruction(instruction)));
+
+        match instruction {
+            Instruction::Control(ControlInstruction::Block(_, body))
+            | Instruction::Control(ControlInstruction::Loop(_, body))
+            | Instruction::Control(ControlInstruction::TryTable(_, _, body)) => {
+                format_flat(body.instructions(), depth + 1, lines);
+                lines.push(Line::new(depth, "end".to_string()));
+            }
+            Instruction::Control(ControlInstruction::If(_, then, otherwise)) => {
+                format_flat(then.instructions(), depth + 1, lines);
+
+                if let Some(otherwise) = otherwise {
+                    lines.push(Line::new(depth, "else".to_string()));
+                    format_flat(otherwise.instructions(), depth + 1, lines);
+                }
+
+                lines.push(Line::new(depth, "end".to_string()));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// An instruction whose operands are given by the folded instructions that precede it.
+struct Folded<'instruction> {
+    instruction: &'instruction Instruction,
+    operands: Vec<Folded<'instruction>>,
+}
+
+/// Groups sequences of instructions into folded S-expressions.
+/// Folding is purely syntactic; an instruction only takes the preceding instructions that
+/// produce a single value as operands, so the order of the instructions is always preserved.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#folded-instructions
+struct Folder<'context> {
+    context: &'context TextContext<'context>,
+    results: usize,
+    labels: Vec<Option<usize>>,
+}
+
+impl<'context> Folder<'context> {
+    /// Formats a sequence of instructions as folded instructions.
+    fn format_sequence(
+        &mut self,
+        instructions: &[Instruction],
+        depth: usize,
+        lines: &mut Vec<Line>,
+    ) {
+        for folded in self.fold(instructions) {
+            self.format_folded(&folded, depth, lines);
+        }
+    }
+
+    /// Folds the sequence of instructions into S-expressions.
+    fn fold<'instruction>(
+        &self,
+        instructions: &'instruction [Instruction],
+    ) -> Vec<Folded<'instruction>> {
+        let mut folded = Vec::new();
+        let mut pending: Vec<Folded> = Vec::new();
+
+        for instruction in instructions {
+            match self.arity(instruction) {
+                Some((parameters, results)) => {
+                    let start = pending.len() - parameters.min(pending.len());
+                    let operands = pending.split_off(start);
+                    let expression = Folded {
+                        instruction,
+                        operands,
+                    };
+
+                    if results == 1 {
+                        pending.push(expression);
+                    } else {
+                        folded.append(&mut pending);
+                        folded.push(expression);
+                    }
+                }
+                None => {
+                    folded.append(&mut pending);
+                    folded.push(Folded {
+                        instruction,
+                        operands: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        folded.append(&mut pending);
+        folded
+    }
+
+    /// The number of operands and results of an instruction, if known.
+    fn arity(&self, instruction: &Instruction) -> Option<(usize, usize)> {
+        match instruction {
+            Instruction::Numeric(instruction) => Some((numeric_arity(instruction), 1)),
+            Instruction::Vector(instruction) => Some(vector_arity(instruction)),
+            Instruction::Reference(ReferenceInstruction::IsNull)
+            | Instruction::Reference(ReferenceInstruction::AsNonNull) => Some((1, 1)),
+            Instruction::Reference(ReferenceInstruction::Equal) => Some((2, 1)),
+            Instruction::Reference(_) => Some((0, 1)),
+            Instruction::Parametric(ParametricInstruction::Drop) => Some((1, 0)),
+            Instruction::Parametric(ParametricInstruction::Select(_)) => Some((3, 1)),
+            Instruction::Variable(instruction) => Some(match instruction {
+                VariableInstruction::LocalGet(_) | VariableInstruction::GlobalGet(_) => (0, 1),
+                VariableInstruction::LocalSet(_) | VariableInstruction::GlobalSet(_) => (1, 0),
+                VariableInstruction::LocalTee(_) => (1, 1),
+            }),
+            Instruction::Table(instruction) => Some(match instruction {
+                TableInstruction::Get(_) => (1, 1),
+                TableInstruction::Set(_) => (2, 0),
+                TableInstruction::Size(_) => (0, 1),
+                TableInstruction::Grow(_) => (2, 1),
+                TableInstruction::Fill(_)
+                | TableInstruction::Copy(..)
+                | TableInstruction::Init(..) => (3, 0),
+                TableInstruction::ElementDrop(_) => (0, 0),
+            }),
+            Instruction::Memory(instruction) => Some(match instruction {
+                MemoryInstruction::Load(..)
+                | MemoryInstruction::Load8(..)
+                | MemoryInstruction::Load16(..)
+                | MemoryInstruction::Load32(..)
+                | MemoryInstruction::Grow(_) => (1, 1),
+                MemoryInstruction::Store(..)
+                | MemoryInstruction::Store8(..)
+                | MemoryInstruction::Store16(..)
+                | MemoryInstruction::Store32(..) => (2, 0),
+                MemoryInstruction::Size(_) => (0, 1),
+                MemoryInstruction::Fill(_)
+                | MemoryInstruction::Copy(..)
+                | MemoryInstruction::Init(..) => (3, 0),
+                MemoryInstruction::DataDrop(_) => (0, 0),
+            }),
+            Instruction::Atomic(instruction) => Some(atomic_arity(instruction)),
+            Instruction::Gc(instruction) => self.gc_arity(instruction),
+            Instruction::Control(instruction) => self.control_arity(instruction),
+        }
+    }
+
+    /// The number of operands and results of a GC instruction, if known.
+    fn gc_arity(&self, instruction: &GcInstruction) -> Option<(usize, usize)> {
+        match instruction {
+            GcInstruction::StructNew(kind) => Some((self.context.fields(*kind)?, 1)),
+            GcInstruction::StructNewDefault(_) => Some((0, 1)),
+            GcInstruction::StructSet(..) => Some((2, 0)),
+            GcInstruction::ArrayNewFixed(_, length) => Some((*length as usize, 1)),
+            GcInstruction::ArrayNew(_)
+            | GcInstruction::ArrayNewData(..)
+            | GcInstruction::ArrayNewElement(..)
+            | GcInstruction::ArrayGet(..) => Some((2, 1)),
+            GcInstruction::ArraySet(_) => Some((3, 0)),
+            GcInstruction::ArrayFill(_)
+            | GcInstruction::ArrayInitData(..)
+            | GcInstruction::ArrayInitElement(..) => Some((4, 0)),
+            GcInstruction::ArrayCopy(..) => Some((5, 0)),
+            GcInstruction::BranchOnCast(label, ..) | GcInstruction::BranchOnCastFail(label, ..) => {
+                let arity = self.label_arity(*label)?;
+
+                Some((arity, arity))
+            }
+            _ => Some((1, 1)),
+        }
+    }
+
+    /// The number of operands and results of a control instruction, if known.
+    fn control_arity(&self, instruction: &ControlInstruction) -> Option<(usize, usize)> {
+        match instruction {
+            ControlInstruction::Nop | ControlInstruction::Unreachable => Some((0, 0)),
+            ControlInstruction::Block(kind, _)
+            | ControlInstruction::Loop(kind, _)
+            | ControlInstruction::TryTable(kind, ..) => match self.context.block_arity(kind)? {
+                (0, results) => Some((0, results)),
+                _ => None,
+            },
+            ControlInstruction::If(kind, ..) => self
+                .context
+                .block_arity(kind)
+                .map(|(parameters, results)| (parameters + 1, results)),
+            ControlInstruction::Branch(label) => Some((self.label_arity(*label)?, 0)),
+            ControlInstruction::BranchIf(label) => {
+                let arity = self.label_arity(*label)?;
+
+                Some((arity + 1, arity))
+            }
+            ControlInstruction::BranchTable(_, label) => Some((self.label_arity(*label)? + 1, 0)),
+            ControlInstruction::Return => Some((self.results, 0)),
+            ControlInstruction::Call(function) => {
+                let kind = self.context.functions.get(*function as usize)?;
+
+                self.context.arity(*kind)
+            }
+            ControlInstruction::CallIndirect(kind, _) => self
+                .context
+                .arity(*kind)
+                .map(|(parameters, results)| (parameters + 1, results)),
+            ControlInstruction::ReturnCall(function) => {
+                let kind = self.context.functions.get(*function as usize)?;
+
+                self.context
+                    .arity(*kind)
+                    .map(|(parameters, _)| (parameters, 0))
+            }
+            ControlInstruction::ReturnCallIndirect(kind, _)
+            | ControlInstruction::ReturnCallReference(kind) => self
+                .context
+                .arity(*kind)
+                .map(|(parameters, _)| (parameters + 1, 0)),
+            ControlInstruction::CallReference(kind) => self
+                .context
+                .arity(*kind)
+                .map(|(parameters, results)| (parameters + 1, results)),
+            ControlInstruction::BranchOnNull(label) => {
+                let arity = self.label_arity(*label)?;
+
+                Some((arity + 1, arity + 1))
+            }
+            ControlInstruction::BranchOnNonNull(label) => {
+                let arity = self.label_arity(*label)?;
+
+                Some((arity + 1, arity))
+            }
+            ControlInstruction::Throw(tag) => {
+                let kind = self.context.tags.get(*tag as usize)?;
+
+                self.context
+                    .arity(*kind)
+                    .map(|(parameters, _)| (parameters, 0))
+            }
+            ControlInstruction::ThrowReference => Some((1, 0)),
+        }
+    }
+
+    /// The number of values a branch to the given label takes.
+    fn label_arity(&self, label: u32) -> Option<usize> {
+        let depth = label as usize;
+
+        if depth < self.labels.len() {
+            self.labels[self.labels.len() - 1 - depth]
+        } else {
+            None
+        }
+    }
+
+    /// Formats a folded instruction and its operands.
+    fn format_folded(&mut self, folded: &Folded, depth: usize, lines: &mut Vec<Line>) {
+        let header = format_instruction(folded.instruction);
+
+        match folded.instruction {
+            Instruction::Control(ControlInstruction::Block(kind, body))
+            | Instruction::Control(ControlInstruction::Loop(kind, body))
+            | Instruction::Control(ControlInstruction::TryTable(kind, _, body)) => {
+                self.format_operands(&folded.operands, depth, lines);
+
+                let label = match (folded.instruction, self.context.block_arity(kind)) {
+                    (Instruction::Control(ControlInstruction::Loop(..)), arity) => {
+                        arity.map(|(parameters, _)| parameters)
+                    }
+                    (_, arity) => arity.map(|(_, results)| results),
+                };
+
+                self.format_block(header, body, label, depth, lines);
+            }
+            Instruction::Control(ControlInstruction::If(kind, then, otherwise)) => {
+                let label = self.context.block_arity(kind).map(|(_, results)| results);
+
+                lines.push(Line::new(depth, format!("({}", header)));
+                self.format_operands(&folded.operands, depth + 1, lines);
+                self.format_block("then".to_string(), then, label, depth + 1, lines);
+
+                if let Some(otherwise) = otherwise {
+                    self.format_block("else".to_string(), otherwise, label, depth + 1, lines);
+                }
+
+                lines.push(Line::new(depth, ")".to_string()));
+            }
+            _ => {
+                let mut operands = Vec::new();
+
+                self.format_operands(&folded.operands, depth + 1, &mut operands);
+
+                if operands.len() == folded.operands.len() {
+                    let mut text = format!("({}", header);
+
+                    for operand in operands {
+                        text.push(' ');
+                        text.push_str(&operand.text);
+                    }
+
+                    text.push(')');
+                    lines.push(Line::new(depth, text));
+                } else {
+                    lines.push(Line::new(depth, format!("({}", header)));
+                    lines.append(&mut operands);
+                    lines.push(Line::new(depth, ")".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Formats the operands of a folded instruction.
+    fn format_operands(&mut self, operands: &[Folded], depth: usize, lines: &mut Vec<Line>) {
+        for operand in operands {
+            self.format_folded(operand, depth, lines);
+        }
+    }
+
+    /// Formats a nested sequence of instructions with the given header.
+    /// The label arity is used to fold the operands of branches that target the block.
+    fn format_block(
+        &mut self,
+        header: String,
+        body: &Expression,
+        label: Option<usize>,
+        depth: usize,
+        lines: &mut Vec<Line>,
+    ) {
+        if body.is_empty() {
+            lines.push(Line::new(depth, format!("({})", header)));
+            return;
+        }
+
+        lines.push(Line::new(depth, format!("({}", header)));
+
+        self.labels.push(label);
+        self.format_sequence(body.instructions(), depth + 1, lines);
+        self.labels.pop();
+
+        lines.push(Line::new(depth, ")".to_string()));
+    }
+}
+
+/// The number of operands of a numeric instruction.
+fn numeric_arity(instruction: &NumericInstruction) -> usize {
+    match instruction {
+        NumericInstruction::I32Constant(_)
+        | NumericInstruction::I64Constant(_)
+        | NumericInstruction::F32Constant(_)
+        | NumericInstruction::F64Constant(_) => 0,
+        NumericInstruction::Add(_)
+        | NumericInstruction::Subtract(_)
+        | NumericInstruction::Multiply(_)
+        | NumericInstruction::DivideInteger(..)
+        | NumericInstruction::DivideFloat(_)
+        | NumericInstruction::Remainder(..)
+        | NumericInstruction::And(_)
+        | NumericInstruction::Or(_)
+        | NumericInstruction::Xor(_)
+        | NumericInstruction::ShiftLeft(_)
+        | NumericInstruction::ShiftRight(..)
+        | NumericInstruction::RotateLeft(_)
+        | NumericInstruction::RotateRight(_)
+        | NumericInstruction::Minimum(_)
+        | NumericInstruction::Maximum(_)
+        | NumericInstruction::CopySign(_)
+        | NumericInstruction::Equal(_)
+        | NumericInstruction::NotEqual(_)
+        | NumericInstruction::LessThanInteger(..)
+        | NumericInstruction::LessThanFloat(_)
+        | NumericInstruction::GreaterThanInteger(..)
+        | NumericInstruction::GreaterThanFloat(_)
+        | NumericInstruction::LessThanOrEqualToInteger(..)
+        | NumericInstruction::LessThanOrEqualToFloat(_)
+        | NumericInstruction::GreaterThanOrEqualToInteger(..)
+        | NumericInstruction::GreaterThanOrEqualToFloat(_) => 2,
+        _ => 1,
+    }
+}
+
+/// The number of operands and results of a vector instruction.
+fn vector_arity(instruction: &VectorInstruction) -> (usize, usize) {
+    match instruction {
+        VectorInstruction::Constant(_) => (0, 1),
+        VectorInstruction::Store(_) | VectorInstruction::StoreLane(..) => (2, 0),
+        VectorInstruction::BitSelect
+        | VectorInstruction::RelaxedMultiplyAdd(_)
+        | VectorInstruction::RelaxedNegativeMultiplyAdd(_)
+        | VectorInstruction::RelaxedLaneSelect(_)
+        | VectorInstruction::RelaxedDotProductAdd => (3, 1),
+        VectorInstruction::Not
+        | VectorInstruction::AnyTrue
+        | VectorInstruction::Splat(_)
+        | VectorInstruction::ExtractLane(..)
+        | VectorInstruction::AbsoluteValue(_)
+        | VectorInstruction::Negate(_)
+        | VectorInstruction::CountOnes
+        | VectorInstruction::SquareRoot(_)
+        | VectorInstruction::Ceiling(_)
+        | VectorInstruction::Floor(_)
+        | VectorInstruction::Truncate(_)
+        | VectorInstruction::Nearest(_)
+        | VectorInstruction::AllTrue(_)
+        | VectorInstruction::Bitmask(_)
+        | VectorInstruction::Extend(..)
+        | VectorInstruction::ExtendedAddPairwise(..)
+        | VectorInstruction::ConvertAndTruncateWithSaturation(..)
+        | VectorInstruction::Convert(..)
+        | VectorInstruction::RelaxedTruncate(..)
+        | VectorInstruction::Demote
+        | VectorInstruction::Promote
+        | VectorInstruction::Load(_)
+        | VectorInstruction::LoadExtend(..)
+        | VectorInstruction::LoadSplat(..)
+        | VectorInstruction::LoadZero(..) => (1, 1),
+        _ => (2, 1),
+    }
+}
+
+/// Formats an instruction and its immediates in the flat syntax.
+/// Structured instructions are formatted as their opening keyword and block type.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html
+pub fn format_instruction(instruction: &Instruction) -> String {
+    match instruction {
+        Instruction::Numeric(instruction) => format_numeric_instruction(instruction),
+        Instruction::Vector(instruction) => format_vector_instruction(instruction),
+        Instruction::Reference(instruction) => format_reference_instruction(instruction),
+        Instruction::Parametric(instruction) => format_parametric_instruction(instruction),
+        Instruction::Variable(instruction) => format_variable_instruction(instruction),
+        Instruction::Table(instruction) => format_table_instruction(instruction),
+        Instruction::Memory(instruction) => format_memory_instruction(instruction),
+        Instruction::Atomic(instruction) => format_atomic_instruction(instruction),
+        Instruction::Gc(instruction) => format_gc_instruction(instruction),
+        Instruction::Control(instruction) => format_control_instruction(instruction),
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#numeric-instructions
+fn format_numeric_instruction(instruction: &NumericInstruction) -> String {
+    match *instruction {
+        NumericInstruction::I32Constant(value) => format!("i32.const {}", value),
+        NumericInstruction::I64Constant(value) => format!("i64.const {}", value),
+        NumericInstruction::F32Constant(value) => format!("f32.const {}", format_f32(value)),
+        NumericInstruction::F64Constant(value) => format!("f64.const {}", format_f64(value)),
+        NumericInstruction::CountLeadingZeros(kind) => format!("{}.clz", integer(kind)),
+        NumericInstruction::CountTrailingZeros(kind) => format!("{}.ctz", integer(kind)),
+        NumericInstruction::CountOnes(kind) => format!("{}.popcnt", integer(kind)),
+        NumericInstruction::AbsoluteValue(kind) => format!("{}.abs", float(kind)),
+        NumericInstruction::Negate(kind) => format!("{}.neg", float(kind)),
+        NumericInstruction::SquareRoot(kind) => format!("{}.sqrt", float(kind)),
+        NumericInstruction::Ceiling(kind) => format!("{}.ceil", float(kind)),
+        NumericInstruction::Floor(kind) => format!("{}.floor", float(kind)),
+        NumericInstruction::Truncate(kind) => format!("{}.trunc", float(kind)),
+        NumericInstruction::Nearest(kind) => format!("{}.nearest", float(kind)),
+        NumericInstruction::Add(kind) => format!("{}.add", number(kind)),
+        NumericInstruction::Subtract(kind) => format!("{}.sub", number(kind)),
+        NumericInstruction::Multiply(kind) => format!("{}.mul", number(kind)),
+        NumericInstruction::DivideInteger(kind, sign) => {
+            format!("{}.div_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::DivideFloat(kind) => format!("{}.div", float(kind)),
+        NumericInstruction::Remainder(kind, sign) => {
+            format!("{}.rem_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::And(kind) => format!("{}.and", integer(kind)),
+        NumericInstruction::Or(kind) => format!("{}.or", integer(kind)),
+        NumericInstruction::Xor(kind) => format!("{}.xor", integer(kind)),
+        NumericInstruction::ShiftLeft(kind) => format!("{}.shl", integer(kind)),
+        NumericInstruction::ShiftRight(kind, sign) => {
+            format!("{}.shr_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::RotateLeft(kind) => format!("{}.rotl", integer(kind)),
+        NumericInstruction::RotateRight(kind) => format!("{}.rotr", integer(kind)),
+        NumericInstruction::Minimum(kind) => format!("{}.min", float(kind)),
+        NumericInstruction::Maximum(kind) => format!("{}.max", float(kind)),
+        NumericInstruction::CopySign(kind) => format!("{}.copysign", float(kind)),
+        NumericInstruction::EqualToZero(kind) => format!("{}.eqz", integer(kind)),
+        NumericInstruction::Equal(kind) => format!("{}.eq", number(kind)),
+        NumericInstruction::NotEqual(kind) => format!("{}.ne", number(kind)),
+        NumericInstruction::LessThanInteger(kind, sign) => {
+            format!("{}.lt_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::LessThanFloat(kind) => format!("{}.lt", float(kind)),
+        NumericInstruction::GreaterThanInteger(kind, sign) => {
+            format!("{}.gt_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::GreaterThanFloat(kind) => format!("{}.gt", float(kind)),
+        NumericInstruction::LessThanOrEqualToInteger(kind, sign) => {
+            format!("{}.le_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::LessThanOrEqualToFloat(kind) => format!("{}.le", float(kind)),
+        NumericInstruction::GreaterThanOrEqualToInteger(kind, sign) => {
+            format!("{}.ge_{}", integer(kind), extension(sign))
+        }
+        NumericInstruction::GreaterThanOrEqualToFloat(kind) => format!("{}.ge", float(kind)),
+        NumericInstruction::ExtendSigned8(kind) => format!("{}.extend8_s", integer(kind)),
+        NumericInstruction::ExtendSigned16(kind) => format!("{}.extend16_s", integer(kind)),
+        NumericInstruction::ExtendSigned32 => "i64.extend32_s".to_string(),
+        NumericInstruction::Wrap => "i32.wrap_i64".to_string(),
+        NumericInstruction::ExtendWithSignExtension(sign) => {
+            format!("i64.extend_i32_{}", extension(sign))
+        }
+        NumericInstruction::ConvertAndTruncate(integer_kind, float_kind, sign) => format!(
+            "{}.trunc_{}_{}",
+            integer(integer_kind),
+            float(float_kind),
+            extension(sign)
+        ),
+        NumericInstruction::ConvertAndTruncateWithSaturation(integer_kind, float_kind, sign) => {
+            format!(
+                "{}.trunc_sat_{}_{}",
+                integer(integer_kind),
+                float(float_kind),
+                extension(sign)
+            )
+        }
+        NumericInstruction::Demote => "f32.demote_f64".to_string(),
+        NumericInstruction::Promote => "f64.promote_f32".to_string(),
+        NumericInstruction::Convert(float_kind, integer_kind, sign) => format!(
+            "{}.convert_{}_{}",
+            float(float_kind),
+            integer(integer_kind),
+            extension(sign)
+        ),
+        NumericInstruction::ReinterpretFloat(IntegerType::I32) => "i32.reinterpret_f32".to_string(),
+        NumericInstruction::ReinterpretFloat(IntegerType::I64) => "i64.reinterpret_f64".to_string(),
+        NumericInstruction::ReinterpretInteger(FloatType::F32) => "f32.reinterpret_i32".to_string(),
+        NumericInstruction::ReinterpretInteger(FloatType::F64) => "f64.reinterpret_i64".to_string(),
+    }
+}
+
+/// Formats a vector instruction.
+/// Instructions that are not defined for their shape are formatted by the same rules,
+/// even though no parser accepts the resulting keyword.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#vector-instructions
+fn format_vector_instruction(instruction: &VectorInstruction) -> String {
+    match *instruction {
+        VectorInstruction::Constant(value) => {
+            let mut text = "v128.const i32x4".to_string();
+
+            for lane in value.to_le_bytes().chunks(4) {
+                let lane = u32::from_le_bytes([lane[0], lane[1], lane[2], lane[3]]);
+
+                text.push_str(&format!(" 0x{:08x}", lane));
+            }
+
+            text
+        }
+        VectorInstruction::Not => "v128.not".to_string(),
+        VectorInstruction::And => "v128.and".to_string(),
+        VectorInstruction::AndNot => "v128.andnot".to_string(),
+        VectorInstruction::Or => "v128.or".to_string(),
+        VectorInstruction::Xor => "v128.xor".to_string(),
+        VectorInstruction::BitSelect => "v128.bitselect".to_string(),
+        VectorInstruction::AnyTrue => "v128.any_true".to_string(),
+        VectorInstruction::Shuffle(lanes) => {
+            let mut text = "i8x16.shuffle".to_string();
+
+            for lane in lanes {
+                text.push_str(&format!(" {}", lane));
+            }
+
+            text
+        }
+        VectorInstruction::Swizzle => "i8x16.swizzle".to_string(),
+        VectorInstruction::Splat(kind) => format!("{}.splat", shape(kind)),
+        VectorInstruction::ExtractLane(kind, None, lane) => {
+            format!("{}.extract_lane {}", shape(kind), lane)
+        }
+        VectorInstruction::ExtractLane(kind, Some(sign), lane) => {
+            format!("{}.extract_lane_{} {}", shape(kind), extension(sign), lane)
+        }
+        VectorInstruction::ReplaceLane(kind, lane) => {
+            format!("{}.replace_lane {}", shape(kind), lane)
+        }
+        VectorInstruction::Equal(kind) => format!("{}.eq", shape(kind)),
+        VectorInstruction::NotEqual(kind) => format!("{}.ne", shape(kind)),
+        VectorInstruction::LessThanInteger(kind, sign) => {
+            format!("{}.lt_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::LessThanFloat(kind) => format!("{}.lt", shape(kind)),
+        VectorInstruction::GreaterThanInteger(kind, sign) => {
+            format!("{}.gt_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::GreaterThanFloat(kind) => format!("{}.gt", shape(kind)),
+        VectorInstruction::LessThanOrEqualToInteger(kind, sign) => {
+            format!("{}.le_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::LessThanOrEqualToFloat(kind) => format!("{}.le", shape(kind)),
+        VectorInstruction::GreaterThanOrEqualToInteger(kind, sign) => {
+            format!("{}.ge_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::GreaterThanOrEqualToFloat(kind) => format!("{}.ge", shape(kind)),
+        VectorInstruction::AbsoluteValue(kind) => format!("{}.abs", shape(kind)),
+        VectorInstruction::Negate(kind) => format!("{}.neg", shape(kind)),
+        VectorInstruction::CountOnes => "i8x16.popcnt".to_string(),
+        VectorInstruction::SquareRoot(kind) => format!("{}.sqrt", shape(kind)),
+        VectorInstruction::Ceiling(kind) => format!("{}.ceil", shape(kind)),
+        VectorInstruction::Floor(kind) => format!("{}.floor", shape(kind)),
+        VectorInstruction::Truncate(kind) => format!("{}.trunc", shape(kind)),
+        VectorInstruction::Nearest(kind) => format!("{}.nearest", shape(kind)),
+        VectorInstruction::AllTrue(kind) => format!("{}.all_true", shape(kind)),
+        VectorInstruction::Bitmask(kind) => format!("{}.bitmask", shape(kind)),
+        VectorInstruction::Narrow(kind, sign) => format!(
+            "{}.narrow_{}_{}",
+            shape(kind),
+            wider_shape(kind),
+            extension(sign)
+        ),
+        VectorInstruction::Extend(kind, half, sign) => format!(
+            "{}.extend_{}_{}_{}",
+            shape(kind),
+            vector_half(half),
+            narrower_shape(kind),
+            extension(sign)
+        ),
+        VectorInstruction::ShiftLeft(kind) => format!("{}.shl", shape(kind)),
+        VectorInstruction::ShiftRight(kind, sign) => {
+            format!("{}.shr_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::Add(kind) => format!("{}.add", shape(kind)),
+        VectorInstruction::Subtract(kind) => format!("{}.sub", shape(kind)),
+        VectorInstruction::Multiply(kind) => format!("{}.mul", shape(kind)),
+        VectorInstruction::AddSaturate(kind, sign) => {
+            format!("{}.add_sat_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::SubtractSaturate(kind, sign) => {
+            format!("{}.sub_sat_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::MinimumInteger(kind, sign) => {
+            format!("{}.min_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::MaximumInteger(kind, sign) => {
+            format!("{}.max_{}", shape(kind), extension(sign))
+        }
+        VectorInstruction::AverageRounded(kind) => format!("{}.avgr_u", shape(kind)),
+        VectorInstruction::Q15MultiplyRoundSaturate => "i16x8.q15mulr_sat_s".to_string(),
+        VectorInstruction::DotProduct => "i32x4.dot_i16x8_s".to_string(),
+        VectorInstruction::ExtendedMultiply(kind, half, sign) => format!(
+            "{}.extmul_{}_{}_{}",
+            shape(kind),
+            vector_half(half),
+            narrower_shape(kind),
+            extension(sign)
+        ),
+        VectorInstruction::ExtendedAddPairwise(kind, sign) => format!(
+            "{}.extadd_pairwise_{}_{}",
+            shape(kind),
+            narrower_shape(kind),
+            extension(sign)
+        ),
+        VectorInstruction::DivideFloat(kind) => format!("{}.div", shape(kind)),
+        VectorInstruction::MinimumFloat(kind) => format!("{}.min", shape(kind)),
+        VectorInstruction::MaximumFloat(kind) => format!("{}.max", shape(kind)),
+        VectorInstruction::PseudoMinimum(kind) => format!("{}.pmin", shape(kind)),
+        VectorInstruction::PseudoMaximum(kind) => format!("{}.pmax", shape(kind)),
+        VectorInstruction::ConvertAndTruncateWithSaturation(FloatVectorShape::F32x4, sign) => {
+            format!("i32x4.trunc_sat_f32x4_{}", extension(sign))
+        }
+        VectorInstruction::ConvertAndTruncateWithSaturation(FloatVectorShape::F64x2, sign) => {
+            format!("i32x4.trunc_sat_f64x2_{}_zero", extension(sign))
+        }
+        VectorInstruction::Convert(FloatVectorShape::F32x4, sign) => {
+            format!("f32x4.convert_i32x4_{}", extension(sign))
+        }
+        VectorInstruction::Convert(FloatVectorShape::F64x2, sign) => {
+            format!("f64x2.convert_low_i32x4_{}", extension(sign))
+        }
+        VectorInstruction::Demote => "f32x4.demote_f64x2_zero".to_string(),
+        VectorInstruction::Promote => "f64x2.promote_low_f32x4".to_string(),
+        VectorInstruction::Load(argument) => {
+            format!("v128.load{}", format_memory_argument(&argument, 4))
+        }
+        VectorInstruction::Store(argument) => {
+            format!("v128.store{}", format_memory_argument(&argument, 4))
+        }
+        VectorInstruction::LoadExtend(kind, sign, argument) => {
+            let bits = lane_bits(kind);
+
+            format!(
+                "v128.load{}x{}_{}{}",
+                bits,
+                64 / bits,
+                extension(sign),
+                format_memory_argument(&argument, 3)
+            )
+        }
+        VectorInstruction::LoadSplat(kind, argument) => format!(
+            "v128.load{}_splat{}",
+            lane_bits(kind),
+            format_memory_argument(&argument, lane_alignment(kind))
+        ),
+        VectorInstruction::LoadZero(kind, argument) => format!(
+            "v128.load{}_zero{}",
+            lane_bits(kind),
+            format_memory_argument(&argument, lane_alignment(kind))
+        ),
+        VectorInstruction::LoadLane(kind, argument, lane) => format!(
+            "v128.load{}_lane{} {}",
+            lane_bits(kind),
+            format_memory_argument(&argument, lane_alignment(kind)),
+            lane
+        ),
+        VectorInstruction::StoreLane(kind, argument, lane) => format!(
+            "v128.store{}_lane{} {}",
+            lane_bits(kind),
+            format_memory_argument(&argument, lane_alignment(kind)),
+            lane
+        ),
+        VectorInstruction::RelaxedSwizzle => "i8x16.relaxed_swizzle".to_string(),
+        VectorInstruction::RelaxedTruncate(FloatVectorShape::F32x4, sign) => {
+            format!("i32x4.relaxed_trunc_f32x4_{}", extension(sign))
+        }
+        VectorInstruction::RelaxedTruncate(FloatVectorShape::F64x2, sign) => {
+            format!("i32x4.relaxed_trunc_f64x2_{}_zero", extension(sign))
+        }
+        VectorInstruction::RelaxedMultiplyAdd(kind) => format!("{}.relaxed_madd", shape(kind)),
+        VectorInstruction::RelaxedNegativeMultiplyAdd(kind) => {
+            format!("{}.relaxed_nmadd", shape(kind))
+        }
+        VectorInstruction::RelaxedLaneSelect(kind) => {
+            format!("{}.relaxed_laneselect", shape(kind))
+        }
+        VectorInstruction::RelaxedMinimum(kind) => format!("{}.relaxed_min", shape(kind)),
+        VectorInstruction::RelaxedMaximum(kind) => format!("{}.relaxed_max", shape(kind)),
+        VectorInstruction::RelaxedQ15MultiplyRound => "i16x8.relaxed_q15mulr_s".to_string(),
+        VectorInstruction::RelaxedDotProduct => "i16x8.relaxed_dot_i8x16_i7x16_s".to_string(),
+        VectorInstruction::RelaxedDotProductAdd => {
+            "i32x4.relaxed_dot_i8x16_i7x16_add_s".to_string()
+        }
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#reference-instructions
+fn format_reference_instruction(instruction: &ReferenceInstruction) -> String {
+    match instruction {
+        ReferenceInstruction::Null(kind) => format!("ref.null {}", format_heap_type(*kind)),
+        ReferenceInstruction::IsNull => "ref.is_null".to_string(),
+        ReferenceInstruction::Function(function) => format!("ref.func {}", function),
+        ReferenceInstruction::Equal => "ref.eq".to_string(),
+        ReferenceInstruction::AsNonNull => "ref.as_non_null".to_string(),
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#parametric-instructions
+fn format_parametric_instruction(instruction: &ParametricInstruction) -> String {
+    match instruction {
+        ParametricInstruction::Drop => "drop".to_string(),
+        ParametricInstruction::Select(None) => "select".to_string(),
+        ParametricInstruction::Select(Some(kinds)) => {
+            let kinds: Vec<Cow<str>> = kinds.iter().copied().map(format_value_type).collect();
+
+            format!("select (result {})", kinds.join(" "))
+        }
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#variable-instructions
+fn format_variable_instruction(instruction: &VariableInstruction) -> String {
+    match instruction {
+        VariableInstruction::LocalGet(local) => format!("local.get {}", local),
+        VariableInstruction::LocalSet(local) => format!("local.set {}", local),
+        VariableInstruction::LocalTee(local) => format!("local.tee {}", local),
+        VariableInstruction::GlobalGet(global) => format!("global.get {}", global),
+        VariableInstruction::GlobalSet(global) => format!("global.set {}", global),
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#table-instructions
+fn format_table_instruction(instruction: &TableInstruction) -> String {
+    match instruction {
+        TableInstruction::Get(table) => format!("table.get {}", table),
+        TableInstruction::Set(table) => format!("table.set {}", table),
+        TableInstruction::Size(table) => format!("table.size {}", table),
+        TableInstruction::Grow(table) => format!("table.grow {}", table),
+        TableInstruction::Fill(table) => format!("table.fill {}", table),
+        TableInstruction::Copy(destination, source) => {
+            format!("table.copy {} {}", destination, source)
+        }
+        TableInstruction::Init(element, table) => format!("table.init {} {}", table, element),
+        TableInstruction::ElementDrop(element) => format!("elem.drop {}", element),
+    }
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#memory-instructions
+fn format_memory_instruction(instruction: &MemoryInstruction) -> String {
+    match *instruction {
+        MemoryInstruction::Load(kind, argument) => format!(
+            "{}.load{}",
+            number(kind),
+            format_memory_argument(&argument, natural_alignment(kind))
+        ),
+        MemoryInstruction::Store(kind, argument) => format!(
+            "{}.store{}",
+            number(kind),
+            format_memory_argument(&argument, natural_alignment(kind))
+        ),
+        MemoryInstruction::Load8(kind, sign, argument) => format!(
+            "{}.load8_{}{}",
+            integer(kind),
+            extension(sign),
+            format_memory_argument(&argument, 0)
+        ),
+        MemoryInstruction::Load16(kind, sign, argument) => format!(
+            "{}.load16_{}{}",
+            integer(kind),
+            extension(sign),
+            format_memory_argument(&argument, 1)
+        ),
+        MemoryInstruction::Load32(sign, argument) => format!(
+            "i64.load32_{}{}",
+            extension(sign),
+            format_memory_argument(&argument, 2)
+        ),
+        MemoryInstruction::Store8(kind, argument) => format!(
+            "{}.store8{}",
+            integer(kind),
+            format_memory_argument(&argument, 0)
+        ),
+        MemoryInstruction::Store16(kind, argument) => format!(
+            "{}.store16{}",
+            integer(kind),
+            format_memory_argument(&argument, 1)
+        ),
+        MemoryInstruction::Store32(argument) => {
+            format!("i64.store32{}", format_memory_argument(&argument, 2))
+        }
+        MemoryInstruction::Size(memory) => format!("memory.size{}", format_memory(memory)),
+        MemoryInstruction::Grow(memory) => format!("memory.grow{}", format_memory(memory)),
+        MemoryInstruction::Fill(memory) => format!("memory.fill{}", format_memory(memory)),
+        MemoryInstruction::Copy(0, 0) => "memory.copy".to_string(),
+        MemoryInstruction::Copy(destination, source) => {
+            format!("memory.copy {} {}", destination, source)
+        }
+        MemoryInstruction::Init(data, 0) => format!("memory.init {}", data),
+        MemoryInstruction::Init(data, memory) => format!("memory.init {} {}", memory, data),
+        MemoryInstruction::DataDrop(data) => format!("data.drop {}", data),
+    }
+}
+
+/// See https://webassembly.github.io/gc/core/text/instructions.html#aggregate-instructions
+fn format_gc_instruction(instruction: &GcInstruction) -> String {
+    match *instruction {
+        GcInstruction::StructNew(kind) => format!("struct.new {}", kind),
+        GcInstruction::StructNewDefault(kind) => format!("struct.new_default {}", kind),
+        GcInstruction::StructGet(kind, field, None) => format!("struct.get {} {}", kind, field),
+        GcInstruction::StructGet(kind, field, Some(sign)) => {
+            format!("struct.get_{} {} {}", extension(sign), kind, field)
+        }
+        GcInstruction::StructSet(kind, field) => format!("struct.set {} {}", kind, field),
+        GcInstruction::ArrayNew(kind) => format!("array.new {}", kind),
+        GcInstruction::ArrayNewDefault(kind) => format!("array.new_default {}", kind),
+        GcInstruction::ArrayNewFixed(kind, length) => {
+            format!("array.new_fixed {} {}", kind, length)
+        }
+        GcInstruction::ArrayNewData(kind, data) => format!("array.new_data {} {}", kind, data),
+        GcInstruction::ArrayNewElement(kind, element) => {
+            format!("array.new_elem {} {}", kind, element)
+        }
+        GcInstruction::ArrayGet(kind, None) => format!("array.get {}", kind),
+        GcInstruction::ArrayGet(kind, Some(sign)) => {
+            format!("array.get_{} {}", extension(sign), kind)
+        }
+        GcInstruction::ArraySet(kind) => format!("array.set {}", kind),
+        GcInstruction::ArrayLength => "array.len".to_string(),
+        GcInstruction::ArrayFill(kind) => format!("array.fill {}", kind),
+        GcInstruction::ArrayCopy(target, source) => format!("array.copy {} {}", target, source),
+        GcInstruction::ArrayInitData(kind, data) => format!("array.init_data {} {}", kind, data),
+        GcInstruction::ArrayInitElement(kind, element) => {
+            format!("array.init_elem {} {}", kind, element)
+        }
+        GcInstruction::Test(kind) => format!("ref.test {}", format_value_type(kind)),
+        GcInstruction::Cast(kind) => format!("ref.cast {}", format_value_type(kind)),
+        GcInstruction::BranchOnCast(label, source, target) => format!(
+            "br_on_cast {} {} {}",
+            label,
+            format_value_type(source),
+            format_value_type(target)
+        ),
+        GcInstruction::BranchOnCastFail(label, source, target) => format!(
+            "br_on_cast_fail {} {} {}",
+            label,
+            format_value_type(source),
+            format_value_type(target)
+        ),
+        GcInstruction::AnyConvertExtern => "any.convert_extern".to_string(),
+        GcInstruction::ExternConvertAny => "extern.convert_any".to_string(),
+        GcInstruction::ReferenceI31 => "ref.i31".to_string(),
+        GcInstruction::I31Get(sign) => format!("i31.get_{}", extension(sign)),
+    }
+}
+
+/// See https://webassembly.github.io/threads/core/text/instructions.html#atomic-memory-instructions
+fn format_atomic_instruction(instruction: &AtomicInstruction) -> String {
+    match *instruction {
+        AtomicInstruction::Notify(argument) => format!(
+            "memory.atomic.notify{}",
+            format_memory_argument(&argument, 2)
+        ),
+        AtomicInstruction::Wait(IntegerType::I32, argument) => format!(
+            "memory.atomic.wait32{}",
+            format_memory_argument(&argument, 2)
+        ),
+        AtomicInstruction::Wait(IntegerType::I64, argument) => format!(
+            "memory.atomic.wait64{}",
+            format_memory_argument(&argument, 3)
+        ),
+        AtomicInstruction::Fence => "atomic.fence".to_string(),
+        AtomicInstruction::Load(kind, argument) => format!(
+            "{}.atomic.load{}",
+            integer(kind),
+            format_memory_argument(&argument, natural_alignment(kind.into()))
+        ),
+        AtomicInstruction::Load8(kind, argument) => format!(
+            "{}.atomic.load8_u{}",
+            integer(kind),
+            format_memory_argument(&argument, 0)
+        ),
+        AtomicInstruction::Load16(kind, argument) => format!(
+            "{}.atomic.load16_u{}",
+            integer(kind),
+            format_memory_argument(&argument, 1)
+        ),
+        AtomicInstruction::Load32(argument) => {
+            format!(
+                "i64.atomic.load32_u{}",
+                format_memory_argument(&argument, 2)
+            )
+        }
+        AtomicInstruction::Store(kind, argument) => format!(
+            "{}.atomic.store{}",
+            integer(kind),
+            format_memory_argument(&argument, natural_alignment(kind.into()))
+        ),
+        AtomicInstruction::Store8(kind, argument) => format!(
+            "{}.atomic.store8{}",
+            integer(kind),
+            format_memory_argument(&argument, 0)
+        ),
+        AtomicInstruction::Store16(kind, argument) => format!(
+            "{}.atomic.store16{}",
+            integer(kind),
+            format_memory_argument(&argument, 1)
+        ),
+        AtomicInstruction::Store32(argument) => {
+            format!("i64.atomic.store32{}", format_memory_argument(&argument, 2))
+        }
+        AtomicInstruction::ReadModifyWrite(operation, kind, argument) => format!(
+            "{}.atomic.rmw.{}{}",
+            integer(kind),
+            atomic_operation(operation),
+            format_memory_argument(&argument, natural_alignment(kind.into()))
+        ),
+        AtomicInstruction::ReadModifyWrite8(operation, kind, argument) => format!(
+            "{}.atomic.rmw8.{}_u{}",
+            integer(kind),
+            atomic_operation(operation),
+            format_memory_argument(&argument, 0)
+        ),
+        AtomicInstruction::ReadModifyWrite16(operation, kind, argument) => format!(
+            "{}.atomic.rmw16.{}_u{}",
+            integer(kind),
+            atomic_operation(operation),
+            format_memory_argument(&argument, 1)
+        ),
+        AtomicInstruction::ReadModifyWrite32(operation, argument) => format!(
+            "i64.atomic.rmw32.{}_u{}",
+            atomic_operation(operation),
+            format_memory_argument(&argument, 2)
+        ),
+    }
+}
+
+fn atomic_operation(operation: AtomicOperation) -> &'static str {
+    match operation {
+        AtomicOperation::Add => "add",
+        AtomicOperation::Subtract => "sub",
+        AtomicOperation::And => "and",
+        AtomicOperation::Or => "or",
+        AtomicOperation::Xor => "xor",
+        AtomicOperation::Exchange => "xchg",
+        AtomicOperation::CompareExchange => "cmpxchg",
+    }
+}
+
+/// The number of operands and results of an atomic instruction.
+fn atomic_arity(instruction: &AtomicInstruction) -> (usize, usize) {
+    match instruction {
+        AtomicInstruction::Fence => (0, 0),
+        AtomicInstruction::Notify(_) => (2, 1),
+        AtomicInstruction::Wait(..) => (3, 1),
+        AtomicInstruction::Load(..)
+        | AtomicInstruction::Load8(..)
+        | AtomicInstruction::Load16(..)
+        | AtomicInstruction::Load32(_) => (1, 1),
+        AtomicInstruction::Store(..)
+        | AtomicInstruction::Store8(..)
+        | AtomicInstruction::Store16(..)
+        | AtomicInstruction::Store32(_) => (2, 0),
+        AtomicInstruction::ReadModifyWrite(operation, ..)
+        | AtomicInstruction::ReadModifyWrite8(operation, ..)
+        | AtomicInstruction::ReadModifyWrite16(operation, ..)
+        | AtomicInstruction::ReadModifyWrite32(operation, _) => {
+            if *operation == AtomicOperation::CompareExchange {
+                (3, 1)
+            } else {
+                (2, 1)
+            }
+        }
+    }
+}
+
+/// Formats a memory index with a leading space, omitting the default memory.
+fn format_memory(memory: MemoryIndex) -> String {
+    if memory == 0 {
+        String::new()
+    } else {
+        format!(" {}", memory)
+    }
+}
+
+/// Formats the non-default fields of a memory argument, including a leading space.
+/// The text format expresses alignment in bytes rather than as an exponent.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#memory-instructions
+fn format_memory_argument(argument: &MemoryArgument, natural: u32) -> String {
+    let mut text = format_memory(argument.memory());
+
+    if argument.offset() != 0 {
+        text.push_str(&format!(" offset={}", argument.offset()));
+    }
+
+    if argument.align() != natural {
+        let align = 1u128.checked_shl(argument.align()).unwrap_or_default();
+
+        text.push_str(&format!(" align={}", align));
+    }
+
+    text
+}
+
+/// See https://webassembly.github.io/spec/core/text/instructions.html#control-instructions
+fn format_control_instruction(instruction: &ControlInstruction) -> String {
+    match instruction {
+        ControlInstruction::Nop => "nop".to_string(),
+        ControlInstruction::Unreachable => "unreachable".to_string(),
+        ControlInstruction::Block(kind, _) => format!("block{}", format_block_type(kind)),
+        ControlInstruction::Loop(kind, _) => format!("loop{}", format_block_type(kind)),
+        ControlInstruction::If(kind, ..) => format!("if{}", format_block_type(kind)),
+        ControlInstruction::Branch(label) => format!("br {}", label),
+        ControlInstruction::BranchIf(label) => format!("br_if {}", label),
+        ControlInstruction::BranchTable(labels, default) => {
+            let mut text = "br_table".to_string();
+
+            for label in labels.iter().chain(std::iter::once(default)) {
+                text.push_str(&format!(" {}", label));
+            }
+
+            text
+        }
+        ControlInstruction::Return => "return".to_string(),
+        ControlInstruction::Call(function) => format!("call {}", function),
+        ControlInstruction::CallIndirect(kind, table) => {
+            format!("call_indirect {} (type {})", table, kind)
+        }
+        ControlInstruction::ReturnCall(function) => format!("return_call {}", function),
+        ControlInstruction::ReturnCallIndirect(kind, table) => {
+            format!("return_call_indirect {} (type {})", table, kind)
+        }
+        ControlInstruction::CallReference(kind) => format!("call_ref {}", kind),
+        ControlInstruction::ReturnCallReference(kind) => format!("return_call_ref {}", kind),
+        ControlInstruction::BranchOnNull(label) => format!("br_on_null {}", label),
+        ControlInstruction::BranchOnNonNull(label) => format!("br_on_non_null {}", label),
+        ControlInstruction::TryTable(kind, catches, _) => {
+            let mut text = format!("try_table{}", format_block_type(kind));
+
+            for catch in catches {
+                text.push(' ');
+                text.push_str(&format_catch(catch));
+            }
+
+            text
+        }
+        ControlInstruction::Throw(tag) => format!("throw {}", tag),
+        ControlInstruction::ThrowReference => "throw_ref".to_string(),
+    }
+}
+
+/// Formats a catch clause of a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+///
+/// See https://webassembly.github.io/exception-handling/core/text/instructions.html#control-instructions
+fn format_catch(catch: &Catch) -> String {
+    match catch {
+        Catch::Catch(tag, label) => format!("(catch {} {})", tag, label),
+        Catch::CatchReference(tag, label) => format!("(catch_ref {} {})", tag, label),
+        Catch::CatchAll(label) => format!("(catch_all {})", label),
+        Catch::CatchAllReference(label) => format!("(catch_all_ref {})", label),
+    }
+}
+
+/// The natural alignment of a number type as the exponent of a power of 2.
+fn natural_alignment(kind: NumberType) -> u32 {
+    match kind {
+        NumberType::I32 | NumberType::F32 => 2,
+        NumberType::I64 | NumberType::F64 => 3,
+    }
+}
+
+fn number(kind: NumberType) -> &'static str {
+    match kind {
+        NumberType::I32 => "i32",
+        NumberType::I64 => "i64",
+        NumberType::F32 => "f32",
+        NumberType::F64 => "f64",
+    }
+}
+
+fn integer(kind: IntegerType) -> &'static str {
+    number(kind.into())
+}
+
+fn float(kind: FloatType) -> &'static str {
+    number(kind.into())
+}
+
+fn extension(sign: SignExtension) -> &'static str {
+    match sign {
+        SignExtension::Signed => "s",
+        SignExtension::Unsigned => "u",
+    }
+}
+
+fn shape(kind: impl Into<VectorShape>) -> &'static str {
+    match kind.into() {
+        VectorShape::I8x16 => "i8x16",
+        VectorShape::I16x8 => "i16x8",
+        VectorShape::I32x4 => "i32x4",
+        VectorShape::I64x2 => "i64x2",
+        VectorShape::F32x4 => "f32x4",
+        VectorShape::F64x2 => "f64x2",
+    }
+}
+
+/// The shape with lanes twice as wide, as used by the source of a narrowing instruction.
+fn wider_shape(kind: IntegerVectorShape) -> &'static str {
+    match kind {
+        IntegerVectorShape::I8x16 => "i16x8",
+        IntegerVectorShape::I16x8 => "i32x4",
+        IntegerVectorShape::I32x4 | IntegerVectorShape::I64x2 => "i64x2",
+    }
+}
+
+/// The shape with lanes half as wide, as used by the source of an extending instruction.
+fn narrower_shape(kind: IntegerVectorShape) -> &'static str {
+    match kind {
+        IntegerVectorShape::I8x16 | IntegerVectorShape::I16x8 => "i8x16",
+        IntegerVectorShape::I32x4 => "i16x8",
+        IntegerVectorShape::I64x2 => "i32x4",
+    }
+}
+
+fn vector_half(half: VectorHalf) -> &'static str {
+    match half {
+        VectorHalf::Low => "low",
+        VectorHalf::High => "high",
+    }
+}
+
+/// The number of bits in a lane of an integer vector shape.
+fn lane_bits(kind: IntegerVectorShape) -> u32 {
+    8 << lane_alignment(kind)
+}
+
+/// The natural alignment of a lane of an integer vector shape as the exponent of a power of 2.
+fn lane_alignment(kind: IntegerVectorShape) -> u32 {
+    match kind {
+        IntegerVectorShape::I8x16 => 0,
+        IntegerVectorShape::I16x8 => 1,
+        IntegerVectorShape::I32x4 => 2,
+        IntegerVectorShape::I64x2 => 3,
+    }
+}
@@ -0,0 +1,1249 @@
+//! Emit WebAssembly text format.
+
+mod instruction;
+mod module;
+mod types;
+mod values;
+
+use crate::emitter::errors::EmitError;
+use crate::model::Module;
+use std::fmt::{Display, Write};
+
+/// The indentation used for each level of nesting.
+const INDENTATION: &str = "  ";
+
+/// The syntax used to emit the instructions of expressions in the text format.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum InstructionSyntax {
+    /// Instructions are emitted in order, one per line, with structured instructions terminated by 𝖾𝗇𝖽.
+    #[default]
+    Flat,
+    /// Instructions are emitted as S-expressions with their operands nested inside.
+    Folded,
+}
+
+/// Emits a text representation of a WebAssembly Abstract Syntax Tree (AST) to a `Write` output,
+/// using the flat instruction syntax.
+///
+/// See https://webassembly.github.io/spec/core/text/index.html
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{emit_text, Export, Function, FunctionType, Module, NumericInstruction, NumberType, ResultType, ValueType, VariableInstruction};
+///
+/// let mut builder = Module::builder();
+/// let kind = builder
+///     .add_function_type(FunctionType::new(
+///         vec![ValueType::I32].into(),
+///         vec![ValueType::I32].into(),
+///     ))
+///     .unwrap();
+/// let function = builder
+///     .add_function(Function::new(
+///         kind,
+///         ResultType::empty(),
+///         vec![
+///             VariableInstruction::LocalGet(0).into(),
+///             1i32.into(),
+///             NumericInstruction::Add(NumberType::I32).into(),
+///         ]
+///         .into(),
+///     ))
+///     .unwrap();
+///
+/// builder.add_export(Export::function("increment".into(), function));
+///
+/// let mut text = String::new();
+/// emit_text(&builder.build(), &mut text).unwrap();
+///
+/// assert_eq!(
+///     text,
+///     r#"(module
+///   (type (;0;) (func (param i32) (result i32)))
+///   (export "increment" (func 0))
+///   (func (;0;) (type 0) (param i32) (result i32)
+///     local.get 0
+///     i32.const 1
+///     i32.add
+///   )
+/// )
+/// "#
+/// );
+/// ```
+pub fn emit_text<O: Write + ?Sized>(module: &Module, output: &mut O) -> Result<(), EmitError> {
+    emit_text_with(module, output, InstructionSyntax::Flat)
+}
+
+/// Emits a text representation of a WebAssembly Abstract Syntax Tree (AST) to a `Write` output,
+/// using the given instruction syntax.
+///
+/// See https://webassembly.github.io/spec/core/text/index.html
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{emit_text_with, Function, FunctionType, InstructionSyntax, Module, NumericInstruction, NumberType, ResultType, ValueType, VariableInstruction};
+///
+/// let mut builder = Module::builder();
+/// let kind = builder
+///     .add_function_type(FunctionType::new(
+///         vec![ValueType::F64].into(),
+///         vec![ValueType::F64].into(),
+///     ))
+///     .unwrap();
+///
+/// builder
+///     .add_function(Function::new(
+///         kind,
+///         ResultType::empty(),
+///         vec![
+///             VariableInstruction::LocalGet(0).into(),
+///             0.5f64.into(),
+///             NumericInstruction::Multiply(NumberType::F64).into(),
+///         ]
+///         .into(),
+///     ))
+///     .unwrap();
+///
+/// let mut text = String::new();
+/// emit_text_with(&builder.build(), &mut text, InstructionSyntax::Folded).unwrap();
+///
+/// assert_eq!(
+///     text,
+///     r#"(module
+///   (type (;0;) (func (param f64) (result f64)))
+///   (func (;0;) (type 0) (param f64) (result f64)
+///     (f64.mul (local.get 0) (f64.const 0x1p-1))
+///   )
+/// )
+/// "#
+/// );
+/// ```
+pub fn emit_text_with<O: Write + ?Sized>(
+    module: &Module,
+    output: &mut O,
+    syntax: InstructionSyntax,
+) -> Result<(), EmitError> {
+    let mut writer = TextWriter {
+        output,
+        syntax,
+        depth: 0,
+    };
+
+    module::emit_module(module, &mut writer)
+}
+
+/// Writes indented lines of text to an output.
+pub(crate) struct TextWriter<'output, O: Write + ?Sized> {
+    output: &'output mut O,
+    syntax: InstructionSyntax,
+    depth: usize,
+}
+
+impl<'output, O: Write + ?Sized> TextWriter<'output, O> {
+    /// The syntax to use for instructions.
+    pub fn syntax(&self) -> InstructionSyntax {
+        self.syntax
+    }
+
+    /// Writes a line at the current depth.
+    pub fn line<T: Display>(&mut self, text: T) -> Result<(), EmitError> {
+        self.nested_line(0, text)
+    }
+
+    /// Writes a line nested the given number of levels below the current depth.
+    pub fn nested_line<T: Display>(&mut self, depth: usize, text: T) -> Result<(), EmitError> {
+        for _ in 0..(self.depth + depth) {
+            self.output.write_str(INDENTATION)?;
+        }
+
+        writeln!(self.output, "{}", text)?;
+
+        Ok(())
+    }
+
+    /// Opens an S-expression with the given text and increases the depth.
+    pub fn open<T: Display>(&mut self, text: T) -> Result<(), EmitError> {
+        self.line(format!("({}", text))?;
+        self.depth += 1;
+
+        Ok(())
+    }
+
+    /// Decreases the depth and closes the S-expression.
+    pub fn close(&mut self) -> Result<(), EmitError> {
+        self.depth -= 1;
+        self.line(")")
+    }
+}
+
+#[cfg(all(test, feature = "text"))]
+mod tests {
+    use super::*;
+    use crate::model::{
+        ArrayType, AtomicInstruction, AtomicOperation, BlockType, Catch, ControlInstruction,
+        Custom, Data, Element, ElementInitializer, Export, Expression, FieldType, FloatType,
+        FloatVectorShape, Function, FunctionType, GcInstruction, Global, GlobalType, HeapType,
+        Import, Instruction, IntegerType, IntegerVectorShape, Limit, Memory, MemoryArgument,
+        MemoryInstruction, MemoryType, ModuleSection, NumberType, NumericInstruction, PackedType,
+        ParametricInstruction, RecursiveType, ReferenceInstruction, ReferenceType, ResultType,
+        SignExtension, Start, StructType, SubType, Table, TableType, Tag, ValueType,
+        VariableInstruction, VectorHalf, VectorInstruction, VectorShape,
+    };
+    use crate::parser::{parse_binary, parse_text};
+
+    fn round_trip(module: &Module) {
+        for syntax in [InstructionSyntax::Flat, InstructionSyntax::Folded] {
+            let mut text = String::new();
+
+            emit_text_with(module, &mut text, syntax).unwrap();
+
+            let parsed = parse_text(&text).unwrap();
+
+            assert_eq!(module, &parsed, "{}", text);
+        }
+    }
+
+    fn module_with_body(kind: FunctionType, body: Vec<Instruction>) -> Module {
+        let mut builder = Module::builder();
+
+        builder.add_function_type(kind).unwrap();
+        builder
+            .add_function(Function::new(0, ResultType::empty(), body.into()))
+            .unwrap();
+        builder.build()
+    }
+
+    #[test]
+    fn empty_module() {
+        let mut text = String::new();
+
+        emit_text(&Module::empty(), &mut text).unwrap();
+
+        assert_eq!(text, "(module\n)\n");
+        round_trip(&Module::empty());
+    }
+
+    #[test]
+    fn all_sections() {
+        let mut builder = Module::builder();
+        let kind = builder.add_function_type(FunctionType::runnable()).unwrap();
+
+        builder
+            .add_import(Import::function("env".into(), "start".into(), kind))
+            .unwrap();
+        builder
+            .add_import(Import::global(
+                "env".into(),
+                "base".into(),
+                GlobalType::immutable(ValueType::I32),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                kind,
+                vec![ValueType::I64, ValueType::F32].into(),
+                vec![ControlInstruction::Call(0).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_table(Table::new(TableType::new(
+                ReferenceType::Function,
+                Limit::bounded(1, 2),
+            )))
+            .unwrap();
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder
+            .add_global(Global::mutable(
+                ValueType::I32,
+                vec![VariableInstruction::GlobalGet(0).into()].into(),
+            ))
+            .unwrap();
+        builder.add_export(Export::memory("memory".into(), 0));
+        builder.add_export(Export::function("run".into(), 1));
+        builder.set_start(Some(Start::new(1)));
+        builder
+            .add_element(Element::active(
+                0,
+                vec![0i32.into()].into(),
+                ReferenceType::Function,
+                vec![0, 1].to_initializers(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::passive(
+                ReferenceType::External,
+                vec![Expression::from(vec![ReferenceInstruction::Null(
+                    HeapType::External,
+                )
+                .into()])],
+            ))
+            .unwrap();
+        builder
+            .add_data(Data::active(
+                0,
+                vec![VariableInstruction::GlobalGet(0).into()].into(),
+                b"Hello, \"World\"!\n".to_vec(),
+            ))
+            .unwrap();
+        builder.add_data(Data::passive(vec![0x00, 0xFF])).unwrap();
+        builder.add_custom_section(
+            ModuleSection::Data,
+            Custom::new("version".into(), b"1.0".to_vec()),
+        );
+
+        round_trip(&builder.build());
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let module = module_with_body(
+            FunctionType::new(vec![ValueType::I32].into(), vec![ValueType::I32].into()),
+            vec![
+                ControlInstruction::Block(
+                    BlockType::ValueType(ValueType::I32),
+                    vec![
+                        VariableInstruction::LocalGet(0).into(),
+                        ControlInstruction::If(
+                            BlockType::ValueType(ValueType::I32),
+                            vec![1i32.into()].into(),
+                            Some(vec![2i32.into(), ControlInstruction::Branch(1).into()].into()),
+                        )
+                        .into(),
+                    ]
+                    .into(),
+                )
+                .into(),
+                ControlInstruction::Loop(
+                    BlockType::None,
+                    vec![
+                        VariableInstruction::LocalGet(0).into(),
+                        ControlInstruction::BranchIf(0).into(),
+                    ]
+                    .into(),
+                )
+                .into(),
+                VariableInstruction::LocalGet(0).into(),
+                ControlInstruction::BranchTable(vec![0, 0], 0).into(),
+            ],
+        );
+
+        let mut text = String::new();
+        emit_text_with(&module, &mut text, InstructionSyntax::Folded).unwrap();
+
+        assert_eq!(
+            text,
+            r#"(module
+  (type (;0;) (func (param i32) (result i32)))
+  (func (;0;) (type 0) (param i32) (result i32)
+    (block (result i32)
+      (if (result i32)
+        (local.get 0)
+        (then
+          (i32.const 1)
+        )
+        (else
+          (br 1 (i32.const 2))
+        )
+      )
+    )
+    (loop
+      (br_if 0 (local.get 0))
+    )
+    (br_table 0 0 0 (local.get 0))
+  )
+)
+"#
+        );
+
+        round_trip(&module);
+    }
+
+    #[test]
+    fn instructions() {
+        let mut builder = Module::builder();
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    0i32.into(),
+                    0i32.into(),
+                    MemoryInstruction::Load(NumberType::I64, MemoryArgument::new(2, 8)).into(),
+                    MemoryInstruction::Store(NumberType::I64, MemoryArgument::default_offset(3))
+                        .into(),
+                    f32::from_bits(0x7FA00000).into(),
+                    f32::NEG_INFINITY.into(),
+                    NumericInstruction::Minimum(FloatType::F32).into(),
+                    ParametricInstruction::Drop.into(),
+                    (-1.0e-300f64).into(),
+                    1i64.into(),
+                    NumericInstruction::Convert(
+                        FloatType::F64,
+                        IntegerType::I64,
+                        SignExtension::Unsigned,
+                    )
+                    .into(),
+                    1i32.into(),
+                    ParametricInstruction::Select(Some(vec![ValueType::F64])).into(),
+                    ParametricInstruction::Drop.into(),
+                    ReferenceInstruction::Function(0).into(),
+                    ReferenceInstruction::IsNull.into(),
+                    ParametricInstruction::Drop.into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder.add_export(Export::function("f".into(), 0));
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text(&module, &mut text).unwrap();
+
+        assert!(text.contains("i64.load offset=8 align=4\n"));
+        assert!(text.contains("i64.store\n"));
+        assert!(text.contains("f32.const nan:0x200000\n"));
+        assert!(text.contains("f32.const -inf\n"));
+        assert!(text.contains("f64.const -0x1.56e1fc2f8f359p-997\n"));
+        assert!(text.contains("f64.convert_i64_u\n"));
+        assert!(text.contains("select (result f64)\n"));
+
+        // NaN constants are never equal, so compare the emitted text of the parsed module instead.
+        let mut emitted = String::new();
+
+        emit_text(&parse_text(&text).unwrap(), &mut emitted).unwrap();
+
+        assert_eq!(text, emitted);
+    }
+
+    /// Every vector instruction that is defined for its shape, with arbitrary immediates.
+    fn vector_instructions() -> Vec<VectorInstruction> {
+        let argument = MemoryArgument::new(0, 16);
+        let mut instructions = vec![
+            VectorInstruction::Constant(0x0102_0304_0506_0708_090A_0B0C_0D0E_0F10),
+            VectorInstruction::Not,
+            VectorInstruction::And,
+            VectorInstruction::AndNot,
+            VectorInstruction::Or,
+            VectorInstruction::Xor,
+            VectorInstruction::BitSelect,
+            VectorInstruction::AnyTrue,
+            VectorInstruction::Shuffle([0, 31, 1, 30, 2, 29, 3, 28, 4, 27, 5, 26, 6, 25, 7, 24]),
+            VectorInstruction::Swizzle,
+            VectorInstruction::CountOnes,
+            VectorInstruction::Q15MultiplyRoundSaturate,
+            VectorInstruction::DotProduct,
+            VectorInstruction::Demote,
+            VectorInstruction::Promote,
+            VectorInstruction::RelaxedSwizzle,
+            VectorInstruction::RelaxedQ15MultiplyRound,
+            VectorInstruction::RelaxedDotProduct,
+            VectorInstruction::RelaxedDotProductAdd,
+            VectorInstruction::Load(MemoryArgument::new(4, 0)),
+            VectorInstruction::Store(MemoryArgument::new(3, 1)),
+        ];
+        let signs = [SignExtension::Signed, SignExtension::Unsigned];
+
+        for shape in [
+            VectorShape::I8x16,
+            VectorShape::I16x8,
+            VectorShape::I32x4,
+            VectorShape::I64x2,
+            VectorShape::F32x4,
+            VectorShape::F64x2,
+        ] {
+            instructions.extend([
+                VectorInstruction::Splat(shape),
+                VectorInstruction::ExtractLane(shape, None, 1),
+                VectorInstruction::ExtractLane(shape, Some(SignExtension::Signed), 1),
+                VectorInstruction::ExtractLane(shape, Some(SignExtension::Unsigned), 1),
+                VectorInstruction::ReplaceLane(shape, 1),
+                VectorInstruction::Equal(shape),
+                VectorInstruction::NotEqual(shape),
+                VectorInstruction::AbsoluteValue(shape),
+                VectorInstruction::Negate(shape),
+                VectorInstruction::Add(shape),
+                VectorInstruction::Subtract(shape),
+                VectorInstruction::Multiply(shape),
+            ]);
+        }
+
+        for shape in [
+            IntegerVectorShape::I8x16,
+            IntegerVectorShape::I16x8,
+            IntegerVectorShape::I32x4,
+            IntegerVectorShape::I64x2,
+        ] {
+            instructions.extend([
+                VectorInstruction::AllTrue(shape),
+                VectorInstruction::Bitmask(shape),
+                VectorInstruction::ShiftLeft(shape),
+                VectorInstruction::AverageRounded(shape),
+                VectorInstruction::RelaxedLaneSelect(shape),
+                VectorInstruction::LoadSplat(shape, argument),
+                VectorInstruction::LoadZero(shape, argument),
+                VectorInstruction::LoadLane(shape, argument, 1),
+                VectorInstruction::StoreLane(shape, argument, 1),
+            ]);
+
+            for sign in signs {
+                instructions.extend([
+                    VectorInstruction::LessThanInteger(shape, sign),
+                    VectorInstruction::GreaterThanInteger(shape, sign),
+                    VectorInstruction::LessThanOrEqualToInteger(shape, sign),
+                    VectorInstruction::GreaterThanOrEqualToInteger(shape, sign),
+                    VectorInstruction::Narrow(shape, sign),
+                    VectorInstruction::ShiftRight(shape, sign),
+                    VectorInstruction::AddSaturate(shape, sign),
+                    VectorInstruction::SubtractSaturate(shape, sign),
+                    VectorInstruction::MinimumInteger(shape, sign),
+                    VectorInstruction::MaximumInteger(shape, sign),
+                    VectorInstruction::ExtendedAddPairwise(shape, sign),
+                    VectorInstruction::LoadExtend(shape, sign, argument),
+                ]);
+
+                for half in [VectorHalf::Low, VectorHalf::High] {
+                    instructions.extend([
+                        VectorInstruction::Extend(shape, half, sign),
+                        VectorInstruction::ExtendedMultiply(shape, half, sign),
+                    ]);
+                }
+            }
+        }
+
+        for shape in [FloatVectorShape::F32x4, FloatVectorShape::F64x2] {
+            instructions.extend([
+                VectorInstruction::LessThanFloat(shape),
+                VectorInstruction::GreaterThanFloat(shape),
+                VectorInstruction::LessThanOrEqualToFloat(shape),
+                VectorInstruction::GreaterThanOrEqualToFloat(shape),
+                VectorInstruction::SquareRoot(shape),
+                VectorInstruction::Ceiling(shape),
+                VectorInstruction::Floor(shape),
+                VectorInstruction::Truncate(shape),
+                VectorInstruction::Nearest(shape),
+                VectorInstruction::DivideFloat(shape),
+                VectorInstruction::MinimumFloat(shape),
+                VectorInstruction::MaximumFloat(shape),
+                VectorInstruction::PseudoMinimum(shape),
+                VectorInstruction::PseudoMaximum(shape),
+                VectorInstruction::RelaxedMultiplyAdd(shape),
+                VectorInstruction::RelaxedNegativeMultiplyAdd(shape),
+                VectorInstruction::RelaxedMinimum(shape),
+                VectorInstruction::RelaxedMaximum(shape),
+            ]);
+
+            for sign in signs {
+                instructions.extend([
+                    VectorInstruction::ConvertAndTruncateWithSaturation(shape, sign),
+                    VectorInstruction::Convert(shape, sign),
+                    VectorInstruction::RelaxedTruncate(shape, sign),
+                ]);
+            }
+        }
+
+        instructions.retain(VectorInstruction::is_defined);
+        instructions
+    }
+
+    #[test]
+    fn vector_instructions_match_reference_encoding() {
+        let instructions = vector_instructions();
+
+        assert_eq!(instructions.len(), 256);
+
+        for instruction in instructions {
+            let module = module_with_body(FunctionType::runnable(), vec![instruction.into()]);
+            let mut text = String::new();
+
+            emit_text(&module, &mut text).unwrap();
+
+            let bytes = wat::parse_str(&text).unwrap();
+
+            assert_eq!(module, parse_binary(&bytes).unwrap(), "{}", text);
+            round_trip(&module);
+        }
+    }
+
+    fn atomic_instructions() -> Vec<AtomicInstruction> {
+        let operations = [
+            AtomicOperation::Add,
+            AtomicOperation::Subtract,
+            AtomicOperation::And,
+            AtomicOperation::Or,
+            AtomicOperation::Xor,
+            AtomicOperation::Exchange,
+            AtomicOperation::CompareExchange,
+        ];
+        let mut instructions = vec![
+            AtomicInstruction::Notify(MemoryArgument::new(2, 4)),
+            AtomicInstruction::Wait(IntegerType::I32, MemoryArgument::default_offset(2)),
+            AtomicInstruction::Wait(IntegerType::I64, MemoryArgument::default_offset(3)),
+            AtomicInstruction::Fence,
+            AtomicInstruction::Load32(MemoryArgument::default_offset(2)),
+            AtomicInstruction::Store32(MemoryArgument::new(2, 16)),
+        ];
+
+        for (kind, natural) in [(IntegerType::I32, 2), (IntegerType::I64, 3)] {
+            instructions.extend([
+                AtomicInstruction::Load(kind, MemoryArgument::default_offset(natural)),
+                AtomicInstruction::Load8(kind, MemoryArgument::default_offset(0)),
+                AtomicInstruction::Load16(kind, MemoryArgument::default_offset(1)),
+                AtomicInstruction::Store(kind, MemoryArgument::default_offset(natural)),
+                AtomicInstruction::Store8(kind, MemoryArgument::default_offset(0)),
+                AtomicInstruction::Store16(kind, MemoryArgument::default_offset(1)),
+            ]);
+
+            for operation in operations {
+                instructions.extend([
+                    AtomicInstruction::ReadModifyWrite(
+                        operation,
+                        kind,
+                        MemoryArgument::default_offset(natural),
+                    ),
+                    AtomicInstruction::ReadModifyWrite8(
+                        operation,
+                        kind,
+                        MemoryArgument::default_offset(0),
+                    ),
+                    AtomicInstruction::ReadModifyWrite16(
+                        operation,
+                        kind,
+                        MemoryArgument::default_offset(1),
+                    ),
+                ]);
+            }
+        }
+
+        for operation in operations {
+            instructions.push(AtomicInstruction::ReadModifyWrite32(
+                operation,
+                MemoryArgument::default_offset(2),
+            ));
+        }
+
+        instructions
+    }
+
+    #[test]
+    fn atomic_instructions_match_reference_encoding() {
+        let instructions = atomic_instructions();
+
+        assert_eq!(instructions.len(), 67);
+
+        for instruction in instructions {
+            let mut builder = Module::builder();
+
+            builder.add_function_type(FunctionType::runnable()).unwrap();
+            builder
+                .add_memory(MemoryType::shared(Limit::bounded(1, 2)).into())
+                .unwrap();
+            builder
+                .add_function(Function::new(
+                    0,
+                    ResultType::empty(),
+                    vec![instruction.into()].into(),
+                ))
+                .unwrap();
+
+            let module = builder.build();
+            let mut text = String::new();
+
+            emit_text(&module, &mut text).unwrap();
+
+            let bytes = wat::parse_str(&text).unwrap();
+
+            assert_eq!(module, parse_binary(&bytes).unwrap(), "{}", text);
+            round_trip(&module);
+        }
+    }
+
+    #[test]
+    fn multiple_memories() {
+        let mut builder = Module::builder();
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder
+            .add_memory(Memory::from(Limit::bounded(1, 2)))
+            .unwrap();
+        builder.add_data(Data::passive(vec![0x00, 0xFF])).unwrap();
+        builder.include_data_count();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    0i32.into(),
+                    MemoryInstruction::Load(NumberType::I64, MemoryArgument::with_memory(1, 3, 8))
+                        .into(),
+                    MemoryInstruction::Store32(MemoryArgument::with_memory(0, 1, 0)).into(),
+                    0i32.into(),
+                    VectorInstruction::LoadLane(
+                        IntegerVectorShape::I8x16,
+                        MemoryArgument::with_memory(1, 0, 0),
+                        0,
+                    )
+                    .into(),
+                    ParametricInstruction::Drop.into(),
+                    MemoryInstruction::Size(1).into(),
+                    MemoryInstruction::Grow(0).into(),
+                    ParametricInstruction::Drop.into(),
+                    0i32.into(),
+                    0i32.into(),
+                    0i32.into(),
+                    MemoryInstruction::Fill(1).into(),
+                    0i32.into(),
+                    0i32.into(),
+                    0i32.into(),
+                    MemoryInstruction::Copy(0, 1).into(),
+                    0i32.into(),
+                    0i32.into(),
+                    0i32.into(),
+                    MemoryInstruction::Init(0, 1).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text(&module, &mut text).unwrap();
+
+        assert!(text.contains("i64.load 1 offset=8"));
+        assert!(text.contains("v128.load8_lane 1 0"));
+        assert!(text.contains("memory.copy 0 1"));
+        assert!(text.contains("memory.init 1 0"));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap(),
+            "{}",
+            text
+        );
+        round_trip(&module);
+    }
+
+    #[test]
+    fn exception_handling() {
+        let mut builder = Module::builder();
+        let kind = builder
+            .add_function_type(FunctionType::side_effect(vec![ValueType::I32].into()))
+            .unwrap();
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_import(Import::tag("env".into(), "error".into(), kind))
+            .unwrap();
+
+        let tag = builder.add_tag(Tag::new(kind)).unwrap();
+
+        builder.add_export(Export::tag("error".into(), tag));
+        builder.add_custom_section(
+            ModuleSection::Tag,
+            Custom::new("after-tags".into(), vec![0x01]),
+        );
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![
+                    ControlInstruction::Block(
+                        BlockType::ValueType(ValueType::I32),
+                        vec![
+                            ControlInstruction::Block(
+                                BlockType::ValueType(ValueType::ExceptionReference),
+                                vec![
+                                    ControlInstruction::TryTable(
+                                        BlockType::None,
+                                        vec![Catch::Catch(tag, 1), Catch::CatchAllReference(0)],
+                                        vec![1i32.into(), ControlInstruction::Throw(tag).into()]
+                                            .into(),
+                                    )
+                                    .into(),
+                                    ControlInstruction::Unreachable.into(),
+                                ]
+                                .into(),
+                            )
+                            .into(),
+                            ControlInstruction::ThrowReference.into(),
+                        ]
+                        .into(),
+                    )
+                    .into(),
+                    ParametricInstruction::Drop.into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text(&module, &mut text).unwrap();
+
+        assert!(text.contains("(import \"env\" \"error\" (tag"));
+        assert!(text.contains("try_table (catch 1 1) (catch_all_ref 0)"));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap(),
+            "{}",
+            text
+        );
+        round_trip(&module);
+    }
+
+    #[test]
+    fn tail_calls() {
+        let mut builder = Module::builder();
+        let table = TableType::new(ReferenceType::Function, Limit::unbounded(1));
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_function_type(FunctionType::new(
+                vec![ValueType::I32].into(),
+                vec![ValueType::I32].into(),
+            ))
+            .unwrap();
+        builder.add_table(Table::new(table)).unwrap();
+        builder.add_table(Table::new(table)).unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![
+                    VariableInstruction::LocalGet(0).into(),
+                    0i32.into(),
+                    ControlInstruction::CallIndirect(1, 0).into(),
+                    0i32.into(),
+                    ControlInstruction::ReturnCallIndirect(1, 1).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![
+                    VariableInstruction::LocalGet(0).into(),
+                    ControlInstruction::ReturnCall(0).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text_with(&module, &mut text, InstructionSyntax::Folded).unwrap();
+
+        assert!(text.contains("(return_call 0 (local.get 0))"));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap(),
+            "{}",
+            text
+        );
+        round_trip(&module);
+    }
+
+    #[test]
+    fn typed_function_references() {
+        let mut builder = Module::builder();
+        let reference = ValueType::NullableReference(HeapType::Index(0));
+
+        builder
+            .add_function_type(FunctionType::new(
+                vec![ValueType::I32].into(),
+                vec![ValueType::I32].into(),
+            ))
+            .unwrap();
+        builder
+            .add_function_type(FunctionType::new(
+                vec![reference].into(),
+                vec![ValueType::I32].into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                vec![ValueType::Reference(HeapType::Function)].into(),
+                vec![
+                    1i32.into(),
+                    VariableInstruction::LocalGet(0).into(),
+                    ControlInstruction::CallReference(0).into(),
+                    ParametricInstruction::Drop.into(),
+                    ControlInstruction::Block(
+                        BlockType::ValueType(ValueType::I32),
+                        vec![
+                            2i32.into(),
+                            VariableInstruction::LocalGet(0).into(),
+                            ControlInstruction::BranchOnNull(0).into(),
+                            ParametricInstruction::Drop.into(),
+                        ]
+                        .into(),
+                    )
+                    .into(),
+                    ParametricInstruction::Drop.into(),
+                    ControlInstruction::Block(
+                        BlockType::ValueType(ValueType::Reference(HeapType::Index(0))),
+                        vec![
+                            VariableInstruction::LocalGet(0).into(),
+                            ControlInstruction::BranchOnNonNull(0).into(),
+                            ControlInstruction::Unreachable.into(),
+                        ]
+                        .into(),
+                    )
+                    .into(),
+                    ReferenceInstruction::AsNonNull.into(),
+                    ParametricInstruction::Drop.into(),
+                    3i32.into(),
+                    VariableInstruction::LocalGet(0).into(),
+                    ControlInstruction::ReturnCallReference(0).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text_with(&module, &mut text, InstructionSyntax::Flat).unwrap();
+
+        assert!(text.contains("(param (ref null 0))"));
+        assert!(text.contains("(local (ref func))"));
+        assert!(text.contains("block (result (ref 0))"));
+        assert!(text.contains("br_on_non_null 0"));
+        #[cfg(feature = "validator")]
+        assert_eq!(crate::validator::validate(&module), Ok(()));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap(),
+            "{}",
+            text
+        );
+        round_trip(&module);
+    }
+
+    #[test]
+    fn typed_tables_and_elements() {
+        let mut builder = Module::builder();
+        let reference = ReferenceType::Reference(HeapType::Index(0));
+        let nullable = ReferenceType::NullableReference(HeapType::Index(0));
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_import(Import::table(
+                "env".into(),
+                "t".into(),
+                TableType::new(reference, Limit::unbounded(1)),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![0i32.into(), ControlInstruction::CallIndirect(0, 1).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_table(TableType::new(nullable, Limit::unbounded(1)).into())
+            .unwrap();
+        builder
+            .add_table(
+                TableType::new(
+                    ReferenceType::nullable_reference(HeapType::Any),
+                    Limit::unbounded(1),
+                )
+                .into(),
+            )
+            .unwrap();
+        builder
+            .add_table(Table::with_initializer(
+                TableType::new(reference, Limit::unbounded(1)),
+                vec![ReferenceInstruction::Function(0).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::active(
+                1,
+                vec![0i32.into()].into(),
+                reference,
+                vec![0].to_initializers(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::passive(
+                nullable,
+                vec![vec![ReferenceInstruction::Null(HeapType::Index(0)).into()].into()],
+            ))
+            .unwrap();
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text_with(&module, &mut text, InstructionSyntax::Flat).unwrap();
+
+        assert!(text.contains("(table (;1;) 1 (ref null 0))"));
+        assert!(text.contains("(table (;2;) 1 anyref)"));
+        assert!(text.contains("(table (;3;) 1 (ref 0) ref.func 0)"));
+        assert!(text.contains("(ref 0) (item ref.func 0)"));
+        #[cfg(feature = "validator")]
+        assert_eq!(crate::validator::validate(&module), Ok(()));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap(),
+            "{}",
+            text
+        );
+
+        let mut bytes = Vec::new();
+
+        crate::emit_binary(&module, &mut bytes).unwrap();
+
+        assert_eq!(module, parse_binary(&bytes).unwrap());
+        round_trip(&module);
+    }
+
+    #[test]
+    fn vector_types() {
+        let module = module_with_body(
+            FunctionType::new(vec![ValueType::V128].into(), vec![ValueType::V128].into()),
+            vec![
+                VariableInstruction::LocalGet(0).into(),
+                VectorInstruction::Constant(-1).into(),
+                VectorInstruction::ExtractLane(VectorShape::F64x2, None, 1).into(),
+                VectorInstruction::ReplaceLane(VectorShape::F64x2, 0).into(),
+            ],
+        );
+        let mut text = String::new();
+
+        emit_text_with(&module, &mut text, InstructionSyntax::Folded).unwrap();
+
+        assert!(text.contains("(param v128) (result v128)"));
+        assert!(text.contains(
+            "(f64x2.replace_lane 0 (local.get 0) (f64x2.extract_lane 1 (v128.const i32x4 0xffffffff"
+        ));
+        assert_eq!(
+            module,
+            parse_binary(&wat::parse_str(&text).unwrap()).unwrap()
+        );
+        round_trip(&module);
+    }
+
+    #[test]
+    fn garbage_collection() {
+        let mut builder = Module::builder();
+        let add = || NumericInstruction::Add(NumberType::I32).into();
+        let node = StructType::new(vec![
+            FieldType::mutable(ValueType::I32),
+            FieldType::immutable(ValueType::NullableReference(HeapType::Index(1))),
+        ]);
+        let leaf = StructType::new(
+            node.fields()
+                .iter()
+                .copied()
+                .chain([FieldType::immutable(PackedType::I8)])
+                .collect(),
+        );
+
+        builder
+            .add_recursive_type(RecursiveType::new(vec![
+                SubType::new(false, vec![], node.into()),
+                SubType::new(true, vec![0], leaf.into()),
+            ]))
+            .unwrap();
+        builder
+            .add_recursive_type(ArrayType::new(FieldType::mutable(PackedType::I16)).into())
+            .unwrap();
+        builder
+            .add_function_type(FunctionType::new(
+                vec![
+                    ValueType::NullableReference(HeapType::Index(0)),
+                    ValueType::I32,
+                ]
+                .into(),
+                vec![ValueType::I32].into(),
+            ))
+            .unwrap();
+        builder
+            .add_recursive_type(
+                ArrayType::new(FieldType::mutable(ValueType::FunctionReference)).into(),
+            )
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                3,
+                vec![ValueType::NullableReference(HeapType::Index(2))].into(),
+                vec![
+                    VariableInstruction::LocalGet(0).into(),
+                    GcInstruction::StructGet(0, 0, None).into(),
+                    VariableInstruction::LocalGet(1).into(),
+                    add(),
+                    7i32.into(),
+                    3i32.into(),
+                    GcInstruction::ArrayNew(2).into(),
+                    1i32.into(),
+                    GcInstruction::ArrayGet(2, Some(SignExtension::Signed)).into(),
+                    add(),
+                    VariableInstruction::LocalGet(0).into(),
+                    GcInstruction::Test(ValueType::Reference(HeapType::Index(1))).into(),
+                    add(),
+                    5i32.into(),
+                    GcInstruction::ReferenceI31.into(),
+                    GcInstruction::I31Get(SignExtension::Unsigned).into(),
+                    add(),
+                    ControlInstruction::Block(
+                        BlockType::ValueType(ValueType::NullableReference(HeapType::Index(1))),
+                        vec![
+                            VariableInstruction::LocalGet(0).into(),
+                            GcInstruction::BranchOnCast(
+                                0,
+                                ValueType::NullableReference(HeapType::Index(0)),
+                                ValueType::NullableReference(HeapType::Index(1)),
+                            )
+                            .into(),
+                            GcInstruction::Cast(ValueType::NullableReference(HeapType::Index(1)))
+                                .into(),
+                        ]
+                        .into(),
+                    )
+                    .into(),
+                    ParametricInstruction::Drop.into(),
+                    1i32.into(),
+                    ReferenceInstruction::Null(HeapType::Index(1)).into(),
+                    2i32.into(),
+                    GcInstruction::StructNew(1).into(),
+                    9i32.into(),
+                    GcInstruction::StructSet(0, 0).into(),
+                    GcInstruction::StructNewDefault(1).into(),
+                    GcInstruction::StructGet(1, 2, Some(SignExtension::Unsigned)).into(),
+                    add(),
+                    1i32.into(),
+                    2i32.into(),
+                    GcInstruction::ArrayNewFixed(2, 2).into(),
+                    GcInstruction::ArrayLength.into(),
+                    add(),
+                    VariableInstruction::LocalGet(0).into(),
+                    VariableInstruction::LocalGet(0).into(),
+                    ReferenceInstruction::Equal.into(),
+                    add(),
+                    4i32.into(),
+                    GcInstruction::ArrayNewDefault(2).into(),
+                    VariableInstruction::LocalSet(2).into(),
+                    VariableInstruction::LocalGet(2).into(),
+                    0i32.into(),
+                    9i32.into(),
+                    4i32.into(),
+                    GcInstruction::ArrayFill(2).into(),
+                    VariableInstruction::LocalGet(2).into(),
+                    0i32.into(),
+                    VariableInstruction::LocalGet(2).into(),
+                    1i32.into(),
+                    2i32.into(),
+                    GcInstruction::ArrayCopy(2, 2).into(),
+                    VariableInstruction::LocalGet(2).into(),
+                    0i32.into(),
+                    0i32.into(),
+                    2i32.into(),
+                    GcInstruction::ArrayInitData(2, 0).into(),
+                    VariableInstruction::LocalGet(2).into(),
+                    0i32.into(),
+                    3i32.into(),
+                    GcInstruction::ArraySet(2).into(),
+                    0i32.into(),
+                    2i32.into(),
+                    GcInstruction::ArrayNewData(2, 0).into(),
+                    ParametricInstruction::Drop.into(),
+                    0i32.into(),
+                    1i32.into(),
+                    GcInstruction::ArrayNewElement(4, 0).into(),
+                    0i32.into(),
+                    0i32.into(),
+                    1i32.into(),
+                    GcInstruction::ArrayInitElement(4, 0).into(),
+                    ReferenceInstruction::Null(HeapType::NoExtern).into(),
+                    GcInstruction::AnyConvertExtern.into(),
+                    GcInstruction::ExternConvertAny.into(),
+                    ParametricInstruction::Drop.into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::passive(
+                ReferenceType::Function,
+                vec![0].to_initializers(),
+            ))
+            .unwrap();
+        builder.add_data(Data::passive(vec![1, 2, 3, 4])).unwrap();
+        builder.set_data_count(Some(1));
+
+        let module = builder.build();
+        let mut text = String::new();
+
+        emit_text_with(&module, &mut text, InstructionSyntax::Folded).unwrap();
+
+        assert!(text.contains("(rec"));
+        assert!(text
+            .contains("(sub final 0 (struct (field (mut i32)) (field (ref null 1)) (field i8)))"));
+        assert!(text.contains("(array (mut i16))"));
+        assert!(text.contains("br_on_cast 0 (ref null 0) (ref null 1)"));
+        #[cfg(feature = "validator")]
+        assert_eq!(crate::validator::validate(&module), Ok(()));
+
+        let bytes = wat::parse_str(&text).unwrap();
+        let mut emitted = Vec::new();
+
+        crate::emitter::emit_binary(&module, &mut emitted).unwrap();
+
+        assert_eq!(module, parse_binary(&bytes).unwrap(), "{}", text);
+        assert_eq!(emitted, bytes);
+        round_trip(&module);
+    }
+
+    #[test]
+    fn memory64() {
+        let text = r#"(module
+  (type (func (param i64) (result i64)))
+  (func $f (type 0)
+    (i64.store offset=4294967296 (local.get 0) (i64.const 7))
+    (drop (memory.grow (i64.const 1)))
+    (memory.copy 0 1 (i64.const 0) (i32.const 0) (i32.const 4))
+    (drop (call_indirect (type 0) (i64.const 1) (i64.const 0)))
+    (drop (table.size))
+    (i64.add (i64.load offset=8 (local.get 0)) (memory.size)))
+  (table i64 funcref (elem $f))
+  (memory i64 1 2)
+  (memory 1)
+  (data (memory 0) (i64.const 16) "abc"))"#;
+        let module = parse_text(text).unwrap();
+        let bytes = wat::parse_str(text).unwrap();
+        let mut emitted = Vec::new();
+        let mut formatted = String::new();
+
+        crate::emitter::emit_binary(&module, &mut emitted).unwrap();
+        emit_text(&module, &mut formatted).unwrap();
+
+        #[cfg(feature = "validator")]
+        assert_eq!(crate::validator::validate(&module), Ok(()));
+        assert_eq!(module, parse_binary(&bytes).unwrap());
+        assert_eq!(module, parse_binary(&emitted).unwrap());
+        assert!(formatted.contains("(table (;0;) i64 1 1 funcref)"));
+        assert!(formatted.contains("(memory (;0;) i64 1 2)"));
+        assert!(formatted.contains("i64.store offset=4294967296"));
+        round_trip(&module);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::emitter::errors::EmitError;
+use crate::emitter::function_body;
+use crate::emitter::text::instruction::{format_expression, format_inline_expression, TextContext};
+use crate::emitter::text::types::{
+    format_global_type, format_memory_type, format_reference_type, format_result_type,
+    format_signature, format_sub_type, format_table_type,
+};
+use crate::emitter::text::values::format_string;
+use crate::emitter::text::{InstructionSyntax, TextWriter};
+use crate::model::{
+    Custom, Data, DataMode, Element, ElementMode, Export, ExportDescription, Function, Global,
+    Import, ImportDescription, Instruction, Module, ModuleSection, ReferenceInstruction,
+    ReferenceType, Table, TypeIndex,
+};
+use std::fmt::Write;
+
+/// Emits a module in the text format.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#modules
+pub fn emit_module<O: Write + ?Sized>(
+    module: &Module,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let context = TextContext::new(module);
+
+    writer.open("module")?;
+    emit_custom_sections(module, ModuleSection::Custom, writer)?;
+
+    let mut index = 0;
+
+    for group in module.types().unwrap_or_default() {
+        let grouped = group.len() != 1;
+
+        if grouped {
+            writer.open("rec")?;
+        }
+
+        for kind in group.types() {
+            writer.line(format!("(type (;{};) {})", index, format_sub_type(kind)))?;
+            index += 1;
+        }
+
+        if grouped {
+            writer.close()?;
+        }
+    }
+
+    emit_custom_sections(module, ModuleSection::Type, writer)?;
+
+    let mut indices = [0usize; 5];
+
+    for import in module.imports().unwrap_or_default() {
+        emit_import(import, &mut indices, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Import, writer)?;
+    emit_custom_sections(module, ModuleSection::Function, writer)?;
+
+    for (index, table) in module.tables().unwrap_or_default().iter().enumerate() {
+        emit_table(table, indices[1] + index, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Table, writer)?;
+
+    for (index, memory) in module.memories().unwrap_or_default().iter().enumerate() {
+        let index = indices[2] + index;
+
+        writer.line(format!(
+            "(memory (;{};) {})",
+            index,
+            format_memory_type(memory.kind())
+        ))?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Memory, writer)?;
+
+    for (index, tag) in module.tags().unwrap_or_default().iter().enumerate() {
+        let index = indices[4] + index;
+
+        writer.line(format!(
+            "(tag (;{};) {})",
+            index,
+            format_type_use(tag.kind(), &context)
+        ))?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Tag, writer)?;
+
+    for (index, global) in module.globals().unwrap_or_default().iter().enumerate() {
+        emit_global(global, indices[3] + index, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Global, writer)?;
+
+    for export in module.exports().unwrap_or_default() {
+        emit_export(export, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Export, writer)?;
+
+    if let Some(start) = module.start() {
+        writer.line(format!("(start {})", start.function()))?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Start, writer)?;
+
+    for (index, element) in module.elements().unwrap_or_default().iter().enumerate() {
+        emit_element(element, index, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Element, writer)?;
+    emit_custom_sections(module, ModuleSection::DataCount, writer)?;
+
+    for (index, function) in module.functions().unwrap_or_default().iter().enumerate() {
+        emit_function(function, indices[0] + index, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Code, writer)?;
+
+    for (index, data) in module.data().unwrap_or_default().iter().enumerate() {
+        emit_data(data, index, &context, writer)?;
+    }
+
+    emit_custom_sections(module, ModuleSection::Data, writer)?;
+    writer.close()
+}
+
+/// Emits the custom sections at the given insertion point as custom annotations.
+/// The text format has no data count section, so custom sections after it are placed before the code.
+///
+/// See https://webassembly.github.io/annotations/core/text/custom.html
+fn emit_custom_sections<O: Write + ?Sized>(
+    module: &Module,
+    insertion_point: ModuleSection,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let placement = match insertion_point {
+        ModuleSection::Custom => "before first",
+        ModuleSection::Type => "after type",
+        ModuleSection::Import => "after import",
+        ModuleSection::Function => "after func",
+        ModuleSection::Table => "after table",
+        ModuleSection::Memory => "after memory",
+        ModuleSection::Tag => "after tag",
+        ModuleSection::Global => "after global",
+        ModuleSection::Export => "after export",
+        ModuleSection::Start => "after start",
+        ModuleSection::Element => "after elem",
+        ModuleSection::DataCount => "before code",
+        ModuleSection::Code => "after code",
+        ModuleSection::Data => "after data",
+    };
+
+    for custom in module
+        .custom_sections_at(insertion_point)
+        .unwrap_or_default()
+    {
+        emit_custom_section(custom, placement, writer)?;
+    }
+
+    Ok(())
+}
+
+/// Emits a custom section as a custom annotation with the given placement.
+fn emit_custom_section<O: Write + ?Sized>(
+    custom: &Custom,
+    placement: &str,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    writer.line(format!(
+        "(@custom {} ({}) {})",
+        format_string(custom.name().as_bytes()),
+        placement,
+        format_string(custom.bytes())
+    ))
+}
+
+/// Formats the type use of a function, including the parameters and results of the type if known.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#type-uses
+fn format_type_use(kind: TypeIndex, context: &TextContext) -> String {
+    let signature = context
+        .function_type(kind)
+        .map(format_signature)
+        .unwrap_or_default();
+
+    if signature.is_empty() {
+        format!("(type {})", kind)
+    } else {
+        format!("(type {}) {}", kind, signature)
+    }
+}
+
+/// Emits an import, incrementing the index of the imported definition's index space.
+/// The indices are ordered as functions, tables, memories, globals, and tags.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#imports
+fn emit_import<O: Write + ?Sized>(
+    import: &Import,
+    indices: &mut [usize; 5],
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let (space, description) = match import.description() {
+        ImportDescription::Function(kind) => (
+            0,
+            format!(
+                "func (;{};) {}",
+                indices[0],
+                format_type_use(*kind, context)
+            ),
+        ),
+        ImportDescription::Table(kind) => (
+            1,
+            format!("table (;{};) {}", indices[1], format_table_type(kind)),
+        ),
+        ImportDescription::Memory(kind) => (
+            2,
+            format!("memory (;{};) {}", indices[2], format_memory_type(kind)),
+        ),
+        ImportDescription::Global(kind) => (
+            3,
+            format!("global (;{};) {}", indices[3], format_global_type(kind)),
+        ),
+        ImportDescription::Tag(kind) => (
+            4,
+            format!("tag (;{};) {}", indices[4], format_type_use(*kind, context)),
+        ),
+    };
+
+    indices[space] += 1;
+
+    writer.line(format!(
+        "(import {} {} ({}))",
+        format_string(import.module().as_bytes()),
+        format_string(import.name().as_bytes()),
+        description
+    ))
+}
+
+/// Emits a global with its initializer on a single line.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#globals
+fn emit_global<O: Write + ?Sized>(
+    global: &Global,
+    index: usize,
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    writer.line(format!(
+        "(global (;{};) {} {})",
+        index,
+        format_global_type(global.kind()),
+        format_inline_expression(global.initializer(), writer.syntax(), context)
+    ))
+}
+
+/// See https://webassembly.github.io/function-references/core/text/modules.html#tables
+fn emit_table<O: Write + ?Sized>(
+    table: &Table,
+    index: usize,
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let mut text = format!("(table (;{};) {}", index, format_table_type(table.kind()));
+
+    if let Some(initializer) = table.initializer() {
+        text.push(' ');
+        text.push_str(&format_inline_expression(
+            initializer,
+            writer.syntax(),
+            context,
+        ));
+    }
+
+    text.push(')');
+
+    writer.line(text)
+}
+
+/// See https://webassembly.github.io/spec/core/text/modules.html#exports
+fn emit_export<O: Write + ?Sized>(
+    export: &Export,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let description = match export.description() {
+        ExportDescription::Function(index) => format!("func {}", index),
+        ExportDescription::Table(index) => format!("table {}", index),
+        ExportDescription::Memory(index) => format!("memory {}", index),
+        ExportDescription::Global(index) => format!("global {}", index),
+        ExportDescription::Tag(index) => format!("tag {}", index),
+    };
+
+    writer.line(format!(
+        "(export {} ({}))",
+        format_string(export.name().as_bytes()),
+        description
+    ))
+}
+
+/// Emits an element segment.
+/// Segments of function references are abbreviated to a list of function indices.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#element-segments
+fn emit_element<O: Write + ?Sized>(
+    element: &Element,
+    index: usize,
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let mut text = format!("(elem (;{};)", index);
+
+    match element.mode() {
+        ElementMode::Passive => {}
+        ElementMode::Declarative => text.push_str(" declare"),
+        ElementMode::Active(table, offset) => text.push_str(&format!(
+            " (table {}) (offset {})",
+            table,
+            format_inline_expression(offset, InstructionSyntax::Flat, context)
+        )),
+    }
+
+    let functions: Option<Vec<String>> = element
+        .initializers()
+        .iter()
+        .map(|initializer| match initializer.instructions() {
+            [Instruction::Reference(ReferenceInstruction::Function(function))] => {
+                Some(function.to_string())
+            }
+            _ => None,
+        })
+        .collect();
+
+    match functions {
+        Some(functions) if element.kind() == ReferenceType::Function => {
+            text.push_str(" func");
+
+            for function in functions {
+                text.push(' ');
+                text.push_str(&function);
+            }
+        }
+        _ => {
+            text.push(' ');
+            text.push_str(&format_reference_type(element.kind()));
+
+            for initializer in element.initializers() {
+                text.push_str(&format!(
+                    " (item {})",
+                    format_inline_expression(initializer, InstructionSyntax::Flat, context)
+                ));
+            }
+        }
+    }
+
+    text.push(')');
+    writer.line(text)
+}
+
+/// Emits a function with its locals and body.
+///
+/// See https://webassembly.github.io/spec/core/text/modules.html#functions
+fn emit_function<O: Write + ?Sized>(
+    function: &Function,
+    index: usize,
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let mut header = format!(
+        "func (;{};) {}",
+        index,
+        format_type_use(function.kind(), context)
+    );
+    let locals = format_result_type("local", function.locals());
+
+    if !locals.is_empty() {
+        header.push(' ');
+        header.push_str(&locals);
+    }
+
+    let results = context
+        .function_type(function.kind())
+        .map(|kind| kind.results().len())
+        .unwrap_or_default();
+
+    let body = function_body(function)?;
+
+    writer.open(header)?;
+
+    for line in format_expression(body, writer.syntax(), context, results, 0) {
+        writer.nested_line(line.depth, line.text)?;
+    }
+
+    writer.close()
+}
+
+/// See https://webassembly.github.io/spec/core/text/modules.html#data-segments
+fn emit_data<O: Write + ?Sized>(
+    data: &Data,
+    index: usize,
+    context: &TextContext,
+    writer: &mut TextWriter<O>,
+) -> Result<(), EmitError> {
+    let mut text = format!("(data (;{};)", index);
+
+    if let DataMode::Active(memory, offset) = data.mode() {
+        text.push_str(&format!(
+            " (memory {}) (offset {})",
+            memory,
+            format_inline_expression(offset, InstructionSyntax::Flat, context)
+        ));
+    }
+
+    text.push(' ');
+    text.push_str(&format_string(data.initializer()));
+    text.push(')');
+
+    writer.line(text)
+}
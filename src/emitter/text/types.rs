@@ -0,0 +1,229 @@
+use crate::model::{
+    BlockType, CompositeType, FieldType, FunctionType, GlobalType, HeapType, IndexType, Limit,
+    MemoryType, Mutability, PackedType, ReferenceType, ResultType, StorageType, SubType, TableType,
+    ValueType,
+};
+use std::borrow::Cow;
+
+/// Formats a value type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#value-types
+pub fn format_value_type(kind: ValueType) -> Cow<'static, str> {
+    match kind {
+        ValueType::I32 => "i32".into(),
+        ValueType::I64 => "i64".into(),
+        ValueType::F32 => "f32".into(),
+        ValueType::F64 => "f64".into(),
+        ValueType::V128 => "v128".into(),
+        ValueType::FunctionReference => "funcref".into(),
+        ValueType::ExternalReference => "externref".into(),
+        ValueType::ExceptionReference => "exnref".into(),
+        ValueType::Reference(heap) => format!("(ref {})", format_heap_type(heap)).into(),
+        ValueType::NullableReference(HeapType::Index(index)) => {
+            format!("(ref null {})", index).into()
+        }
+        ValueType::NullableReference(heap) => match heap {
+            HeapType::None => "nullref".into(),
+            HeapType::NoExtern => "nullexternref".into(),
+            HeapType::NoFunction => "nullfuncref".into(),
+            HeapType::NoException => "nullexnref".into(),
+            heap => format!("{}ref", format_heap_type(heap)).into(),
+        },
+    }
+}
+
+/// Formats a reference type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#reference-types
+pub fn format_reference_type(kind: ReferenceType) -> Cow<'static, str> {
+    format_value_type(kind.into())
+}
+
+/// Formats a heap type, as used by typed references and the 𝗋𝖾𝖿.𝗇𝗎𝗅𝗅 instruction.
+///
+/// See https://webassembly.github.io/gc/core/text/types.html#heap-types
+pub fn format_heap_type(kind: impl Into<HeapType>) -> Cow<'static, str> {
+    match kind.into() {
+        HeapType::Function => "func".into(),
+        HeapType::External => "extern".into(),
+        HeapType::Exception => "exn".into(),
+        HeapType::Any => "any".into(),
+        HeapType::Eq => "eq".into(),
+        HeapType::I31 => "i31".into(),
+        HeapType::Struct => "struct".into(),
+        HeapType::Array => "array".into(),
+        HeapType::None => "none".into(),
+        HeapType::NoExtern => "noextern".into(),
+        HeapType::NoFunction => "nofunc".into(),
+        HeapType::NoException => "noexn".into(),
+        HeapType::Index(index) => index.to_string().into(),
+    }
+}
+
+/// Formats a result type with the given keyword (i.e. `param`, `result`, or `local`).
+/// Empty result types are formatted as an empty string.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#result-types
+pub fn format_result_type(keyword: &str, kind: &ResultType) -> String {
+    if kind.is_empty() {
+        return String::new();
+    }
+
+    let kinds: Vec<Cow<str>> = kind
+        .kinds()
+        .iter()
+        .copied()
+        .map(format_value_type)
+        .collect();
+
+    format!("({} {})", keyword, kinds.join(" "))
+}
+
+/// Formats the parameters and results of a function type, separated by a space.
+pub fn format_signature(kind: &FunctionType) -> String {
+    let parts = [
+        format_result_type("param", kind.parameters()),
+        format_result_type("result", kind.results()),
+    ];
+    let parts: Vec<&str> = parts
+        .iter()
+        .map(String::as_str)
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    parts.join(" ")
+}
+
+/// Formats a function type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#function-types
+pub fn format_function_type(kind: &FunctionType) -> String {
+    let signature = format_signature(kind);
+
+    if signature.is_empty() {
+        "(func)".to_string()
+    } else {
+        format!("(func {})", signature)
+    }
+}
+
+/// Formats a field type of a structure or array type.
+///
+/// See https://webassembly.github.io/gc/core/text/types.html#composite-types
+pub fn format_field_type(kind: &FieldType) -> String {
+    let storage = match kind.kind() {
+        StorageType::Value(kind) => format_value_type(kind),
+        StorageType::Packed(PackedType::I8) => "i8".into(),
+        StorageType::Packed(PackedType::I16) => "i16".into(),
+    };
+
+    match kind.mutability() {
+        Mutability::Mutable => format!("(mut {})", storage),
+        Mutability::Immutable => storage.to_string(),
+    }
+}
+
+/// Formats a composite type.
+///
+/// See https://webassembly.github.io/gc/core/text/types.html#composite-types
+pub fn format_composite_type(kind: &CompositeType) -> String {
+    match kind {
+        CompositeType::Function(kind) => format_function_type(kind),
+        CompositeType::Struct(kind) => {
+            let fields: Vec<String> = kind
+                .fields()
+                .iter()
+                .map(|field| format!(" (field {})", format_field_type(field)))
+                .collect();
+
+            format!("(struct{})", fields.concat())
+        }
+        CompositeType::Array(kind) => format!("(array {})", format_field_type(&kind.field())),
+    }
+}
+
+/// Formats a sub type, using the abbreviation of a bare composite type for final types without supertypes.
+///
+/// See https://webassembly.github.io/gc/core/text/types.html#recursive-types
+pub fn format_sub_type(kind: &SubType) -> String {
+    if kind.is_final() && kind.supertypes().is_empty() {
+        return format_composite_type(kind.kind());
+    }
+
+    let mut text = String::from("(sub");
+
+    if kind.is_final() {
+        text.push_str(" final");
+    }
+
+    for supertype in kind.supertypes() {
+        text.push_str(&format!(" {}", supertype));
+    }
+
+    format!("{} {})", text, format_composite_type(kind.kind()))
+}
+
+/// Formats limits.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#limits
+pub fn format_limits(limits: &Limit) -> String {
+    match limits.max() {
+        Some(max) => format!("{} {}", limits.min(), max),
+        None => limits.min().to_string(),
+    }
+}
+
+/// Formats limits prefixed by their index type, which is omitted for the default `i32`.
+///
+/// See https://webassembly.github.io/memory64/core/text/types.html#index-types
+fn format_indexed_limits(index_type: IndexType, limits: &Limit) -> String {
+    match index_type {
+        IndexType::I32 => format_limits(limits),
+        IndexType::I64 => format!("i64 {}", format_limits(limits)),
+    }
+}
+
+/// Formats a memory type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#memory-types
+pub fn format_memory_type(kind: &MemoryType) -> String {
+    let limits = format_indexed_limits(kind.index_type(), kind.limits());
+
+    if kind.is_shared() {
+        format!("{} shared", limits)
+    } else {
+        limits
+    }
+}
+
+/// Formats a table type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#table-types
+pub fn format_table_type(kind: &TableType) -> String {
+    format!(
+        "{} {}",
+        format_indexed_limits(kind.index_type(), kind.limits()),
+        format_reference_type(kind.kind())
+    )
+}
+
+/// Formats a global type.
+///
+/// See https://webassembly.github.io/spec/core/text/types.html#global-types
+pub fn format_global_type(kind: &GlobalType) -> String {
+    match kind.mutability() {
+        Mutability::Mutable => format!("(mut {})", format_value_type(kind.kind())),
+        Mutability::Immutable => format_value_type(kind.kind()).to_string(),
+    }
+}
+
+/// Formats a block type, including a leading space for non-empty block types.
+///
+/// See https://webassembly.github.io/spec/core/text/instructions.html#control-instructions
+pub fn format_block_type(kind: &BlockType) -> String {
+    match kind {
+        BlockType::None => String::new(),
+        BlockType::Index(index) => format!(" (type {})", index),
+        BlockType::ValueType(kind) => format!(" (result {})", format_value_type(*kind)),
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Write;
+
+/// Formats a 32-bit floating point as a text format literal.
+/// Finite values are formatted as exact hexadecimal floats.
+///
+/// See https://webassembly.github.io/spec/core/text/values.html#floating-point
+pub fn format_f32(value: f32) -> String {
+    format_float(value.to_bits() as u64, 23, 8)
+}
+
+/// Formats a 64-bit floating point as a text format literal.
+/// Finite values are formatted as exact hexadecimal floats.
+///
+/// See https://webassembly.github.io/spec/core/text/values.html#floating-point
+pub fn format_f64(value: f64) -> String {
+    format_float(value.to_bits(), 52, 11)
+}
+
+/// Formats the bits of an IEEE 754 floating point with the given number of mantissa and exponent bits.
+/// NaN values with the canonical payload are formatted as `nan`, while others include their payload.
+fn format_float(bits: u64, mantissa_bits: u32, exponent_bits: u32) -> String {
+    let sign = if (bits >> (mantissa_bits + exponent_bits)) & 1 == 1 {
+        "-"
+    } else {
+        ""
+    };
+    let exponent_mask = (1u64 << exponent_bits) - 1;
+    let exponent = (bits >> mantissa_bits) & exponent_mask;
+    let mantissa = bits & ((1u64 << mantissa_bits) - 1);
+    let bias = (exponent_mask >> 1) as i64;
+
+    if exponent == exponent_mask {
+        return if mantissa == 0 {
+            format!("{}inf", sign)
+        } else if mantissa == 1 << (mantissa_bits - 1) {
+            format!("{}nan", sign)
+        } else {
+            format!("{}nan:0x{:x}", sign, mantissa)
+        };
+    }
+
+    if exponent == 0 && mantissa == 0 {
+        return format!("{}0x0p+0", sign);
+    }
+
+    let digits = (mantissa_bits as usize).div_ceil(4);
+    let fraction = format!(
+        "{:0width$x}",
+        mantissa << (digits as u32 * 4 - mantissa_bits),
+        width = digits
+    );
+    let fraction = fraction.trim_end_matches('0');
+    let (leading, exponent) = if exponent == 0 {
+        (0, 1 - bias)
+    } else {
+        (1, exponent as i64 - bias)
+    };
+
+    if fraction.is_empty() {
+        format!("{}0x{}p{:+}", sign, leading, exponent)
+    } else {
+        format!("{}0x{}.{}p{:+}", sign, leading, fraction, exponent)
+    }
+}
+
+/// Formats a sequence of bytes as a text format string.
+/// Printable ASCII characters are kept as is, all other bytes are escaped as hexadecimal.
+///
+/// See https://webassembly.github.io/spec/core/text/values.html#strings
+pub fn format_string(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() + 2);
+
+    text.push('"');
+
+    for byte in bytes {
+        match byte {
+            b'"' => text.push_str("\\\""),
+            b'\\' => text.push_str("\\\\"),
+            0x20..=0x7E => text.push(*byte as char),
+            _ => {
+                let _ = write!(text, "\\{:02x}", byte);
+            }
+        }
+    }
+
+    text.push('"');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn f32_literals() {
+        assert_eq!(format_f32(0.0), "0x0p+0");
+        assert_eq!(format_f32(-0.0), "-0x0p+0");
+        assert_eq!(format_f32(1.0), "0x1p+0");
+        assert_eq!(format_f32(3.0), "0x1.8p+1");
+        assert_eq!(format_f32(-0.1), "-0x1.99999ap-4");
+        assert_eq!(format_f32(f32::MAX), "0x1.fffffep+127");
+        assert_eq!(format_f32(f32::from_bits(1)), "0x0.000002p-126");
+        assert_eq!(format_f32(f32::INFINITY), "inf");
+        assert_eq!(format_f32(f32::NEG_INFINITY), "-inf");
+        assert_eq!(format_f32(f32::from_bits(0x7FC00000)), "nan");
+        assert_eq!(format_f32(f32::from_bits(0xFFC00000)), "-nan");
+        assert_eq!(format_f32(f32::from_bits(0x7F800001)), "nan:0x1");
+        assert_eq!(format_f32(f32::from_bits(0x7FA00000)), "nan:0x200000");
+    }
+
+    #[test]
+    fn f64_literals() {
+        assert_eq!(format_f64(0.0), "0x0p+0");
+        assert_eq!(format_f64(1.5), "0x1.8p+0");
+        assert_eq!(format_f64(-1024.0), "-0x1p+10");
+        assert_eq!(format_f64(0.1), "0x1.999999999999ap-4");
+        assert_eq!(format_f64(f64::from_bits(1)), "0x0.0000000000001p-1022");
+        assert_eq!(format_f64(f64::INFINITY), "inf");
+        assert_eq!(format_f64(f64::NAN.copysign(1.0)), "nan");
+        assert_eq!(
+            format_f64(f64::from_bits(0x7FF0_0000_0000_0042)),
+            "nan:0x42"
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(format_string(b""), "\"\"");
+        assert_eq!(format_string(b"hello"), "\"hello\"");
+        assert_eq!(format_string(b"a\"b\\c"), "\"a\\\"b\\\\c\"");
+        assert_eq!(format_string(&[0x00, 0x0A, 0xFF]), "\"\\00\\0a\\ff\"");
+    }
+}
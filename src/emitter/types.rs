@@ -1,8 +1,9 @@
 use crate::emitter::errors::EmitError;
-use crate::emitter::values::{emit_byte, emit_u32, emit_vector};
+use crate::emitter::values::{emit_byte, emit_i64, emit_u32, emit_u64, emit_vector};
 use crate::model::{
-    FunctionType, GlobalType, Limit, MemoryType, Mutability, ReferenceType, ResultType, TableType,
-    ValueType,
+    CompositeType, FieldType, FunctionType, GlobalType, HeapType, IndexType, Limit, MemoryType,
+    Mutability, PackedType, RecursiveType, ReferenceType, ResultType, StorageType, SubType,
+    TableType, ValueType,
 };
 use std::borrow::Borrow;
 use std::io::Write;
@@ -23,8 +24,43 @@ pub fn emit_value_type<T: Borrow<ValueType>, O: Write + ?Sized>(
         ValueType::I64 => 0x7E,
         ValueType::F32 => 0x7D,
         ValueType::F64 => 0x7C,
+        ValueType::V128 => 0x7B,
         ValueType::FunctionReference => 0x70,
         ValueType::ExternalReference => 0x6F,
+        ValueType::ExceptionReference => 0x69,
+        ValueType::Reference(heap) => {
+            return Ok(emit_byte(0x64u8, output)? + emit_heap_type(heap, output)?);
+        }
+        ValueType::NullableReference(HeapType::Index(index)) => {
+            return Ok(emit_byte(0x63u8, output)? + emit_heap_type(HeapType::Index(index), output)?);
+        }
+        ValueType::NullableReference(heap) => return emit_heap_type(heap, output),
+    };
+
+    emit_byte(value, output)
+}
+
+/// Emits a heap type as either the byte of an abstract heap type, or a type index as a signed 33-bit integer.
+///
+/// See https://webassembly.github.io/function-references/core/binary/types.html#heap-types
+pub fn emit_heap_type<O: Write + ?Sized>(
+    kind: HeapType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let value: u8 = match kind {
+        HeapType::Function => 0x70,
+        HeapType::External => 0x6F,
+        HeapType::Exception => 0x69,
+        HeapType::Any => 0x6E,
+        HeapType::Eq => 0x6D,
+        HeapType::I31 => 0x6C,
+        HeapType::Struct => 0x6B,
+        HeapType::Array => 0x6A,
+        HeapType::None => 0x71,
+        HeapType::NoExtern => 0x72,
+        HeapType::NoFunction => 0x73,
+        HeapType::NoException => 0x74,
+        HeapType::Index(index) => return emit_i64(index as i64, output),
     };
 
     emit_byte(value, output)
@@ -50,21 +86,114 @@ pub fn emit_function_type<O: Write + ?Sized>(
     Ok(bytes)
 }
 
-pub fn emit_limit<O: Write + ?Sized>(limits: &Limit, output: &mut O) -> Result<usize, EmitError> {
+/// Emits a field type as its storage type followed by its mutability.
+///
+/// See https://webassembly.github.io/gc/core/binary/types.html#composite-types
+pub fn emit_field_type<O: Write + ?Sized>(
+    kind: &FieldType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
     let mut bytes = 0;
 
-    match limits.max() {
-        Some(max) => {
-            bytes += emit_byte(0x01u8, output)?;
-            bytes += emit_u32(limits.min(), output)?;
-            bytes += emit_u32(max, output)?;
+    bytes += match kind.kind() {
+        StorageType::Value(kind) => emit_value_type(kind, output)?,
+        StorageType::Packed(PackedType::I8) => emit_byte(0x78u8, output)?,
+        StorageType::Packed(PackedType::I16) => emit_byte(0x77u8, output)?,
+    };
+    bytes += emit_mutability(kind.mutability(), output)?;
+
+    Ok(bytes)
+}
+
+/// See https://webassembly.github.io/gc/core/binary/types.html#composite-types
+pub fn emit_composite_type<O: Write + ?Sized>(
+    kind: &CompositeType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+
+    match kind {
+        CompositeType::Function(kind) => {
+            bytes += emit_function_type(kind, output)?;
+        }
+        CompositeType::Struct(kind) => {
+            bytes += emit_byte(0x5Fu8, output)?;
+            bytes += emit_vector(kind.fields(), output, emit_field_type)?;
         }
-        None => {
-            bytes += emit_byte(0x00u8, output)?;
-            bytes += emit_u32(limits.min(), output)?;
+        CompositeType::Array(kind) => {
+            bytes += emit_byte(0x5Eu8, output)?;
+            bytes += emit_field_type(&kind.field(), output)?;
         }
+    }
+
+    Ok(bytes)
+}
+
+/// Emits a sub type, using the short form of a bare composite type for final types without supertypes.
+///
+/// See https://webassembly.github.io/gc/core/binary/types.html#recursive-types
+pub fn emit_sub_type<O: Write + ?Sized>(
+    kind: &SubType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let mut bytes = 0;
+
+    if !kind.is_final() || !kind.supertypes().is_empty() {
+        bytes += emit_byte(if kind.is_final() { 0x4Fu8 } else { 0x50u8 }, output)?;
+        bytes += emit_vector(kind.supertypes(), output, emit_u32)?;
+    }
+
+    bytes += emit_composite_type(kind.kind(), output)?;
+
+    Ok(bytes)
+}
+
+/// Emits a recursive type, using the short form of a single sub type for groups of one type.
+///
+/// See https://webassembly.github.io/gc/core/binary/types.html#recursive-types
+pub fn emit_recursive_type<O: Write + ?Sized>(
+    kind: &RecursiveType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    match kind.types() {
+        [kind] => emit_sub_type(kind, output),
+        types => Ok(emit_byte(0x4Eu8, output)? + emit_vector(types, output, emit_sub_type)?),
+    }
+}
+
+/// Emits limits whose flags byte marks the presence of a maximum in bit 0,
+/// a shared memory in bit 1 and a 64-bit index type in bit 2.
+/// Limits with a 32-bit index type must fit in 32 bits.
+///
+/// See <https://webassembly.github.io/memory64/core/binary/types.html#limits>
+pub fn emit_limit<O: Write + ?Sized>(
+    limits: &Limit,
+    shared: bool,
+    index_type: IndexType,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let mut flags = if shared { 0x02u8 } else { 0x00u8 };
+    let emit_bound = |bound: u64, output: &mut O| match index_type {
+        IndexType::I32 => emit_u32(u32::try_from(bound)?, output),
+        IndexType::I64 => emit_u64(bound, output),
     };
 
+    if limits.max().is_some() {
+        flags |= 0x01;
+    }
+
+    if index_type == IndexType::I64 {
+        flags |= 0x04;
+    }
+
+    let mut bytes = emit_byte(flags, output)?;
+
+    bytes += emit_bound(limits.min(), output)?;
+
+    if let Some(max) = limits.max() {
+        bytes += emit_bound(max, output)?;
+    }
+
     Ok(bytes)
 }
 
@@ -72,7 +201,7 @@ pub fn emit_memory_type<O: Write + ?Sized>(
     kind: &MemoryType,
     output: &mut O,
 ) -> Result<usize, EmitError> {
-    emit_limit(kind.limits(), output)
+    emit_limit(kind.limits(), kind.is_shared(), kind.index_type(), output)
 }
 
 pub fn emit_table_type<O: Write + ?Sized>(
@@ -82,7 +211,7 @@ pub fn emit_table_type<O: Write + ?Sized>(
     let mut bytes = 0;
 
     bytes += emit_reference_type(kind.kind(), output)?;
-    bytes += emit_limit(kind.limits(), output)?;
+    bytes += emit_limit(kind.limits(), false, kind.index_type(), output)?;
 
     Ok(bytes)
 }
@@ -94,13 +223,19 @@ pub fn emit_global_type<O: Write + ?Sized>(
     let mut bytes = 0;
 
     bytes += emit_value_type(kind.kind(), output)?;
+    bytes += emit_mutability(kind.mutability(), output)?;
 
-    let mutability: u8 = match kind.mutability() {
+    Ok(bytes)
+}
+
+fn emit_mutability<O: Write + ?Sized>(
+    mutability: Mutability,
+    output: &mut O,
+) -> Result<usize, EmitError> {
+    let value: u8 = match mutability {
         Mutability::Immutable => 0x00,
         Mutability::Mutable => 0x01,
     };
 
-    bytes += emit_byte(mutability, output)?;
-
-    Ok(bytes)
+    emit_byte(value, output)
 }
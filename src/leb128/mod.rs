@@ -20,6 +20,7 @@ const SIGN_BIT: usize = 6;
 /// Maximum size (in bytes) of an LEB128-encoded integer type
 ///
 /// See <https://en.wikipedia.org/wiki/LEB128>
+#[allow(clippy::manual_is_multiple_of)]
 const fn max_leb128_size<T>() -> usize {
     let bits = size_of::<T>() * 8;
 
@@ -145,6 +146,7 @@ where
 }
 
 /// Encodes a signed integer using LEB128 (Little-Endian Base 128) encoding.
+/// The encoding ends once the remaining bits are all copies of the sign bit of the last group.
 ///
 /// See <https://en.wikipedia.org/wiki/LEB128>
 pub fn encode_signed<I, O: Write>(input: I, mut output: O) -> Result<usize, LEB128Error>
@@ -158,14 +160,20 @@ where
         let mut byte = (value as u8).zero_bit_at(GROUP_BITS);
         value >>= GROUP_BITS;
 
-        if value != 0 {
+        let done = match value {
+            0 => !byte.bit_at(SIGN_BIT),
+            -1 => byte.bit_at(SIGN_BIT),
+            _ => false,
+        };
+
+        if !done {
             byte = byte.one_bit_at(GROUP_BITS);
         }
 
         output.write_all(&[byte])?;
         written += 1;
 
-        if value == 0 {
+        if done {
             break;
         }
     }
@@ -273,7 +281,7 @@ mod tests {
 
     #[test]
     fn encode_signed_leb128_small() {
-        let input = 64;
+        let input = 63;
         let mut output = Vec::new();
         let written = encode_signed(input, &mut output).unwrap();
 
@@ -281,6 +289,40 @@ mod tests {
         assert_eq!(output, vec![input]);
     }
 
+    #[test]
+    fn encode_signed_leb128_sign_bit() {
+        let mut output = Vec::new();
+        let written = encode_signed(64, &mut output).unwrap();
+
+        assert_eq!(written, 2);
+        assert_eq!(output, vec![0xC0, 0x00]);
+        assert_eq!(parse_signed::<i64>(&output).unwrap().1, 64);
+    }
+
+    #[test]
+    fn encode_signed_leb128_negative() {
+        for (input, expected) in [
+            (-1i64, vec![0x7F]),
+            (-64, vec![0x40]),
+            (-65, vec![0xBF, 0x7F]),
+            (-123456, vec![0xC0, 0xBB, 0x78]),
+        ] {
+            let mut output = Vec::new();
+            let written = encode_signed(input, &mut output).unwrap();
+
+            assert_eq!(written, expected.len());
+            assert_eq!(output, expected);
+            assert_eq!(parse_signed::<i64>(&output).unwrap().1, input);
+        }
+
+        let mut output = Vec::new();
+
+        encode_signed(i64::MIN, &mut output).unwrap();
+
+        assert_eq!(output.len(), 10);
+        assert_eq!(parse_signed::<i64>(&output).unwrap().1, i64::MIN);
+    }
+
     #[test]
     fn encode_signed_leb128_zero() {
         let input = 0;
@@ -10,6 +10,9 @@ pub mod emitter;
 #[cfg(feature = "parser")]
 pub mod parser;
 
+#[cfg(feature = "validator")]
+pub mod validator;
+
 pub use model::*;
 
 #[cfg(feature = "emitter")]
@@ -17,3 +20,6 @@ pub use emitter::*;
 
 #[cfg(feature = "parser")]
 pub use parser::*;
+
+#[cfg(feature = "validator")]
+pub use validator::*;
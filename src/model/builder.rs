@@ -0,0 +1,548 @@
+//! A builder of function bodies that emits instructions in the order of the binary format.
+
+use crate::model::{
+    BlockType, Catch, ControlInstruction, Expression, Function, FunctionType, GcInstruction,
+    Instruction, LabelIndex, LocalIndex, ModelError, Operator, ResultType, TagIndex, TypeIndex,
+    ValueType,
+};
+use std::convert::TryFrom;
+
+/// A branch target of a function being built.
+/// Each structured instruction opened by a `FunctionBuilder` has its own label,
+/// as does the body of the function itself.
+/// Labels are resolved to label indices relative to the instruction that branches to them.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#control-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Label(usize);
+
+/// A catch clause of a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction opened by a `FunctionBuilder`.
+/// Like `Catch`, but the clause targets a label instead of a label index.
+///
+/// See <https://webassembly.github.io/exception-handling/core/syntax/instructions.html#control-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum CatchLabel {
+    /// Catches exceptions with the given tag.
+    Catch(TagIndex, Label),
+    /// Catches exceptions with the given tag, along with a reference to the exception.
+    CatchReference(TagIndex, Label),
+    /// Catches all exceptions.
+    CatchAll(Label),
+    /// Catches all exceptions, along with a reference to the exception.
+    CatchAllReference(Label),
+}
+
+/// A structured instruction that is still open, or the function body itself.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Frame {
+    label: Label,
+    /// True for an 𝗂𝖿 instruction whose 𝖾𝗅𝗌𝖾 branch has not been started yet.
+    expects_else: bool,
+}
+
+/// A builder pattern for `Function`s.
+/// Instructions are emitted linearly, as in the binary format.
+/// Structured instructions are opened with `block`, `loop_`, `if_` and `try_table`, and closed with `end`;
+/// the builder nests the emitted instructions into the expressions of the structured instructions.
+/// Branches to labels are resolved to the label index of the targeted structured instruction.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{
+///     BlockType, ControlInstruction, Function, FunctionBuilder, FunctionType, NumericInstruction,
+///     NumberType, ResultType, ValueType, VariableInstruction,
+/// };
+///
+/// let kind = FunctionType::side_effect(ResultType::new(vec![ValueType::I32]));
+/// let mut builder = FunctionBuilder::new(0, &kind);
+/// let counter = builder.add_local(ValueType::I32).unwrap();
+///
+/// let exit = builder.block(BlockType::None);
+/// let repeat = builder.loop_(BlockType::None);
+/// builder
+///     .instruction(VariableInstruction::LocalGet(0))
+///     .instruction(VariableInstruction::LocalGet(counter))
+///     .instruction(NumericInstruction::Equal(NumberType::I32))
+///     .branch_if(exit)
+///     .unwrap()
+///     .instruction(VariableInstruction::LocalGet(counter))
+///     .instruction(1i32)
+///     .instruction(NumericInstruction::Add(NumberType::I32))
+///     .instruction(VariableInstruction::LocalSet(counter))
+///     .branch(repeat)
+///     .unwrap()
+///     .end()
+///     .end();
+///
+/// let function = builder.build().unwrap();
+///
+/// assert_eq!(counter, 1);
+/// assert_eq!(function.locals(), &ResultType::new(vec![ValueType::I32]));
+/// assert_eq!(
+///     function,
+///     Function::new(
+///         0,
+///         ResultType::new(vec![ValueType::I32]),
+///         vec![ControlInstruction::Block(
+///             BlockType::None,
+///             vec![ControlInstruction::Loop(
+///                 BlockType::None,
+///                 vec![
+///                     VariableInstruction::LocalGet(0).into(),
+///                     VariableInstruction::LocalGet(1).into(),
+///                     NumericInstruction::Equal(NumberType::I32).into(),
+///                     ControlInstruction::BranchIf(1).into(),
+///                     VariableInstruction::LocalGet(1).into(),
+///                     1i32.into(),
+///                     NumericInstruction::Add(NumberType::I32).into(),
+///                     VariableInstruction::LocalSet(1).into(),
+///                     ControlInstruction::Branch(0).into(),
+///                 ]
+///                 .into()
+///             )
+///             .into()]
+///             .into()
+///         )
+///         .into()]
+///         .into()
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct FunctionBuilder {
+    kind: TypeIndex,
+    parameters: usize,
+    locals: Vec<ValueType>,
+    operators: Vec<Operator>,
+    frames: Vec<Frame>,
+    next_label: usize,
+}
+
+impl FunctionBuilder {
+    /// Creates a new builder of a function with the given type.
+    /// The parameters of the function type precede the locals added to the builder in the local index space.
+    pub fn new(kind: TypeIndex, function_type: &FunctionType) -> Self {
+        FunctionBuilder {
+            kind,
+            parameters: function_type.parameters().len(),
+            locals: Vec::new(),
+            operators: Vec::new(),
+            frames: vec![Frame {
+                label: Label(0),
+                expects_else: false,
+            }],
+            next_label: 1,
+        }
+    }
+
+    /// Adds a local of the given type to the function.
+    /// Returns the index of the local, which follows the indices of the parameters.
+    pub fn add_local(&mut self, kind: ValueType) -> Result<LocalIndex, ModelError> {
+        let index = LocalIndex::try_from(self.parameters + self.locals.len())?;
+
+        self.locals.push(kind);
+
+        Ok(index)
+    }
+
+    /// Emits a non-structured instruction.
+    /// Structured instructions are emitted as a whole, including their nested expressions.
+    pub fn instruction(&mut self, instruction: impl Into<Instruction>) -> &mut Self {
+        self.operators
+            .push(Operator::Instruction(instruction.into()));
+        self
+    }
+
+    /// Opens a 𝖻𝗅𝗈𝖼𝗄 instruction. Returns the label of the block.
+    pub fn block(&mut self, kind: BlockType) -> Label {
+        self.open(Operator::Block(kind), false)
+    }
+
+    /// Opens a 𝗅𝗈𝗈𝗉 instruction. Returns the label of the loop.
+    pub fn loop_(&mut self, kind: BlockType) -> Label {
+        self.open(Operator::Loop(kind), false)
+    }
+
+    /// Opens an 𝗂𝖿 instruction. Returns the label of the 𝗂𝖿 instruction.
+    pub fn if_(&mut self, kind: BlockType) -> Label {
+        self.open(Operator::If(kind), true)
+    }
+
+    /// Opens a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction. Returns the label of the 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+    /// The labels of the catch clauses are resolved outside of the 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction,
+    /// so a clause cannot target the instruction's own label.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{BlockType, Catch, CatchLabel, ControlInstruction, FunctionBuilder, FunctionType};
+    ///
+    /// let mut builder = FunctionBuilder::new(0, &FunctionType::runnable());
+    /// let handler = builder.block(BlockType::None);
+    /// let body = builder
+    ///     .try_table(BlockType::None, &[CatchLabel::CatchAll(handler)])
+    ///     .unwrap();
+    ///
+    /// builder.branch(body).unwrap().end().end();
+    ///
+    /// assert_eq!(
+    ///     builder.build().unwrap().body(),
+    ///     &vec![ControlInstruction::Block(
+    ///         BlockType::None,
+    ///         vec![ControlInstruction::TryTable(
+    ///             BlockType::None,
+    ///             vec![Catch::CatchAll(0)],
+    ///             vec![ControlInstruction::Branch(0).into()].into()
+    ///         )
+    ///         .into()]
+    ///         .into()
+    ///     )
+    ///     .into()]
+    ///     .into()
+    /// );
+    /// ```
+    pub fn try_table(
+        &mut self,
+        kind: BlockType,
+        catches: &[CatchLabel],
+    ) -> Result<Label, ModelError> {
+        let catches = catches
+            .iter()
+            .map(|catch| {
+                Ok(match *catch {
+                    CatchLabel::Catch(tag, label) => Catch::Catch(tag, self.depth(label)?),
+                    CatchLabel::CatchReference(tag, label) => {
+                        Catch::CatchReference(tag, self.depth(label)?)
+                    }
+                    CatchLabel::CatchAll(label) => Catch::CatchAll(self.depth(label)?),
+                    CatchLabel::CatchAllReference(label) => {
+                        Catch::CatchAllReference(self.depth(label)?)
+                    }
+                })
+            })
+            .collect::<Result<Vec<Catch>, ModelError>>()?;
+
+        Ok(self.open(Operator::TryTable(kind, catches), false))
+    }
+
+    /// Starts the 𝖾𝗅𝗌𝖾 branch of the innermost open 𝗂𝖿 instruction.
+    /// Fails if the innermost open structured instruction is not an 𝗂𝖿 instruction,
+    /// or if its 𝖾𝗅𝗌𝖾 branch was already started.
+    pub fn else_(&mut self) -> Result<&mut Self, ModelError> {
+        let position = self.operators.len();
+
+        match self.frames.last_mut() {
+            Some(frame) if frame.expects_else => frame.expects_else = false,
+            _ => return Err(ModelError::UnexpectedElse(position)),
+        }
+
+        self.operators.push(Operator::Else);
+
+        Ok(self)
+    }
+
+    /// Closes the innermost open structured instruction.
+    pub fn end(&mut self) -> &mut Self {
+        if self.frames.len() > 1 {
+            self.frames.pop();
+        }
+
+        self.operators.push(Operator::End);
+        self
+    }
+
+    /// The label of the function body itself. Branching to it returns from the function.
+    pub fn function_label(&self) -> Label {
+        Label(0)
+    }
+
+    /// The label index of the given label, relative to the next emitted instruction.
+    /// The label must belong to a structured instruction that is still open, or to the function body.
+    pub fn depth(&self, label: Label) -> Result<LabelIndex, ModelError> {
+        let depth = self
+            .frames
+            .iter()
+            .rev()
+            .position(|frame| frame.label == label)
+            .ok_or(ModelError::LabelNotInScope)?;
+
+        Ok(LabelIndex::try_from(depth)?)
+    }
+
+    /// Emits an unconditional branch to the given label.
+    pub fn branch(&mut self, label: Label) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(ControlInstruction::Branch(depth)))
+    }
+
+    /// Emits a conditional branch to the given label.
+    pub fn branch_if(&mut self, label: Label) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(ControlInstruction::BranchIf(depth)))
+    }
+
+    /// Emits a branch table that branches to the label selected by an operand,
+    /// or to the default label if the operand is out of bounds.
+    pub fn branch_table(
+        &mut self,
+        labels: &[Label],
+        default: Label,
+    ) -> Result<&mut Self, ModelError> {
+        let depths = labels
+            .iter()
+            .map(|label| self.depth(*label))
+            .collect::<Result<Vec<LabelIndex>, ModelError>>()?;
+        let default = self.depth(default)?;
+
+        Ok(self.instruction(ControlInstruction::BranchTable(depths, default)))
+    }
+
+    /// Emits a branch to the given label if a reference operand is null.
+    pub fn branch_on_null(&mut self, label: Label) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(ControlInstruction::BranchOnNull(depth)))
+    }
+
+    /// Emits a branch to the given label if a reference operand is not null.
+    pub fn branch_on_non_null(&mut self, label: Label) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(ControlInstruction::BranchOnNonNull(depth)))
+    }
+
+    /// Emits a branch to the given label if a reference operand of the source type
+    /// matches the target type.
+    pub fn branch_on_cast(
+        &mut self,
+        label: Label,
+        source: ValueType,
+        target: ValueType,
+    ) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(GcInstruction::BranchOnCast(depth, source, target)))
+    }
+
+    /// Emits a branch to the given label if a reference operand of the source type
+    /// does not match the target type.
+    pub fn branch_on_cast_fail(
+        &mut self,
+        label: Label,
+        source: ValueType,
+        target: ValueType,
+    ) -> Result<&mut Self, ModelError> {
+        let depth = self.depth(label)?;
+
+        Ok(self.instruction(GcInstruction::BranchOnCastFail(depth, source, target)))
+    }
+
+    /// Builds the emitted instructions into a function.
+    /// Every structured instruction must be closed, and every 𝖾𝗅𝗌𝖾 must belong to an 𝗂𝖿 instruction.
+    pub fn build(mut self) -> Result<Function, ModelError> {
+        self.operators.push(Operator::End);
+
+        let body = Expression::from_operators(self.operators)?;
+
+        Ok(Function::new(self.kind, ResultType::new(self.locals), body))
+    }
+
+    /// Opens a structured instruction with a new label.
+    fn open(&mut self, operator: Operator, expects_else: bool) -> Label {
+        let label = Label(self.next_label);
+
+        self.next_label += 1;
+        self.frames.push(Frame {
+            label,
+            expects_else,
+        });
+        self.operators.push(operator);
+
+        label
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn if_else_labels() {
+        let mut builder = FunctionBuilder::new(1, &FunctionType::runnable());
+        let function = builder.function_label();
+        let condition = builder.add_local(ValueType::I32).unwrap();
+        let outer = builder.if_(BlockType::None);
+
+        builder.branch(function).unwrap().else_().unwrap();
+
+        let inner = builder.block(BlockType::None);
+
+        builder.branch_if(outer).unwrap().end();
+
+        assert!(matches!(
+            builder.branch(inner),
+            Err(ModelError::LabelNotInScope)
+        ));
+        assert_eq!(builder.depth(outer).unwrap(), 0);
+
+        builder.end().instruction(ControlInstruction::Nop);
+
+        assert_eq!(condition, 0);
+        assert_eq!(
+            builder.build().unwrap(),
+            Function::new(
+                1,
+                ResultType::new(vec![ValueType::I32]),
+                vec![
+                    ControlInstruction::If(
+                        BlockType::None,
+                        vec![ControlInstruction::Branch(1).into()].into(),
+                        Some(
+                            vec![ControlInstruction::Block(
+                                BlockType::None,
+                                vec![ControlInstruction::BranchIf(1).into()].into()
+                            )
+                            .into()]
+                            .into()
+                        )
+                    )
+                    .into(),
+                    ControlInstruction::Nop.into()
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn local_index_overflow() {
+        let mut builder = FunctionBuilder::new(0, &FunctionType::runnable());
+
+        builder.parameters = LocalIndex::MAX as usize;
+
+        assert_eq!(builder.add_local(ValueType::I32).unwrap(), LocalIndex::MAX);
+        assert!(matches!(
+            builder.add_local(ValueType::I64),
+            Err(ModelError::IndexOverflow(_))
+        ));
+        assert_eq!(builder.locals, vec![ValueType::I32]);
+    }
+
+    #[test]
+    fn unbalanced_blocks() {
+        let mut builder = FunctionBuilder::new(0, &FunctionType::runnable());
+
+        builder.block(BlockType::None);
+
+        assert!(matches!(
+            builder.clone().build(),
+            Err(ModelError::MissingEnd(1))
+        ));
+
+        builder.end().end();
+
+        assert!(matches!(builder.build(), Err(ModelError::UnbalancedEnd(3))));
+
+        let mut builder = FunctionBuilder::new(0, &FunctionType::runnable());
+
+        assert!(matches!(
+            builder.else_(),
+            Err(ModelError::UnexpectedElse(0))
+        ));
+
+        builder.if_(BlockType::None);
+        builder.block(BlockType::None);
+
+        assert!(matches!(
+            builder.else_(),
+            Err(ModelError::UnexpectedElse(2))
+        ));
+
+        builder.end().else_().unwrap();
+
+        assert!(matches!(
+            builder.else_(),
+            Err(ModelError::UnexpectedElse(4))
+        ));
+
+        builder.end();
+
+        assert!(builder.build().is_ok());
+    }
+
+    #[test]
+    fn label_resolved_branches() {
+        let mut builder = FunctionBuilder::new(0, &FunctionType::runnable());
+        let function = builder.function_label();
+        let outer = builder.block(BlockType::None);
+        let handler = builder.block(BlockType::None);
+        let body = builder
+            .try_table(
+                BlockType::None,
+                &[
+                    CatchLabel::Catch(0, outer),
+                    CatchLabel::CatchAllReference(handler),
+                ],
+            )
+            .unwrap();
+
+        assert!(matches!(
+            builder.try_table(BlockType::None, &[CatchLabel::CatchAll(Label(9))]),
+            Err(ModelError::LabelNotInScope)
+        ));
+
+        builder
+            .branch_table(&[body, handler], function)
+            .unwrap()
+            .branch_on_null(outer)
+            .unwrap()
+            .branch_on_non_null(body)
+            .unwrap()
+            .branch_on_cast(handler, ValueType::ExternalReference, ValueType::I32)
+            .unwrap()
+            .branch_on_cast_fail(function, ValueType::ExternalReference, ValueType::I32)
+            .unwrap()
+            .end()
+            .end()
+            .end();
+
+        assert_eq!(
+            builder.build().unwrap().body(),
+            &vec![ControlInstruction::Block(
+                BlockType::None,
+                vec![ControlInstruction::Block(
+                    BlockType::None,
+                    vec![ControlInstruction::TryTable(
+                        BlockType::None,
+                        vec![Catch::Catch(0, 1), Catch::CatchAllReference(0)],
+                        vec![
+                            ControlInstruction::BranchTable(vec![0, 1], 3).into(),
+                            ControlInstruction::BranchOnNull(2).into(),
+                            ControlInstruction::BranchOnNonNull(0).into(),
+                            GcInstruction::BranchOnCast(
+                                1,
+                                ValueType::ExternalReference,
+                                ValueType::I32
+                            )
+                            .into(),
+                            GcInstruction::BranchOnCastFail(
+                                3,
+                                ValueType::ExternalReference,
+                                ValueType::I32
+                            )
+                            .into(),
+                        ]
+                        .into()
+                    )
+                    .into()]
+                    .into()
+                )
+                .into()]
+                .into()
+            )
+            .into()]
+            .into()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::model::{FunctionIndex, GlobalIndex, MemoryIndex, TableIndex, TagIndex, TypeIndex};
 use thiserror::Error;
 
 /// An error in a WebAssembly module model.
@@ -5,4 +6,50 @@ use thiserror::Error;
 pub enum ModelError {
     #[error("The module does not have enough space to add the given component. The indices in a WebAssembly module are limited by the capacity of a u32.")]
     IndexOverflow(#[from] std::num::TryFromIntError),
+    #[error(
+        "The function {0} is not defined by the module; it is either imported or out of bounds."
+    )]
+    UndefinedFunction(FunctionIndex),
+    #[error("The function {0} cannot be removed while the module still references it.")]
+    ReferencedFunction(FunctionIndex),
+    #[error("The body of function {0} is not a valid expression: {1}")]
+    MalformedBody(FunctionIndex, String),
+    #[error("The custom section is not a valid name section: {0}")]
+    InvalidNameSection(&'static str),
+    #[error("The operator at position {0} follows the end of the expression.")]
+    UnbalancedEnd(usize),
+    #[error("The else operator at position {0} does not belong to an if instruction.")]
+    UnexpectedElse(usize),
+    #[error(
+        "The operator stream ends with {0} unclosed block(s), including the expression itself."
+    )]
+    MissingEnd(usize),
+    #[error("The type {0} is not defined by the module.")]
+    UnknownType(TypeIndex),
+    #[error("The type {0} is a structure or array type, but functions and tags require a function type.")]
+    ExpectedFunctionType(TypeIndex),
+    #[error("The function {0} is neither imported nor defined by the module.")]
+    UnknownFunction(FunctionIndex),
+    #[error("The table {0} is neither imported nor defined by the module.")]
+    UnknownTable(TableIndex),
+    #[error("The memory {0} is neither imported nor defined by the module.")]
+    UnknownMemory(MemoryIndex),
+    #[error("The global {0} is neither imported nor defined by the module.")]
+    UnknownGlobal(GlobalIndex),
+    #[error("The tag {0} is neither imported nor defined by the module.")]
+    UnknownTag(TagIndex),
+    #[error("The label does not belong to the function body or an open structured instruction.")]
+    LabelNotInScope,
+    #[error("The module already exports a component named {0:?}.")]
+    DuplicateExport(String),
+    #[error("The instruction at position {0} is not allowed in a constant expression.")]
+    NonConstantInstruction(usize),
+    #[error("The instruction at position {0} needs a store to be evaluated.")]
+    UnevaluableInstruction(usize),
+    #[error("The operands of the instruction at position {0} are missing or have the wrong type.")]
+    InvalidConstantOperands(usize),
+    #[error("The constant expression evaluates to {0} values instead of exactly one.")]
+    InvalidConstantResult(usize),
+    #[error("The offset of segment {0} is not an integer, or the segment ends beyond the largest address.")]
+    InvalidSegmentOffset(u32),
 }
@@ -2,7 +2,7 @@
 //! Each class of definition has its own index space, as distinguished by the following classes.
 //!
 //! The index space for functions, tables,
-//! memories and globals includes respective imports declared in the same module.
+//! memories, tags and globals includes respective imports declared in the same module.
 //! The indices of these imports precede the indices of other definitions in the same index space.
 //!
 //! Element indices reference element segments and data indices reference data segments.
@@ -12,6 +12,8 @@
 //!
 //! Label indices reference structured control instructions inside an instruction sequence.
 //!
+//! Field indices reference the fields of a structure type.
+//!
 //! See <https://webassembly.github.io/spec/core/syntax/modules.html#indices>
 
 pub type TypeIndex = u32;
@@ -19,7 +21,73 @@ pub type FunctionIndex = u32;
 pub type TableIndex = u32;
 pub type MemoryIndex = u32;
 pub type GlobalIndex = u32;
+pub type TagIndex = u32;
 pub type ElementIndex = u32;
 pub type DataIndex = u32;
 pub type LocalIndex = u32;
 pub type LabelIndex = u32;
+pub type FieldIndex = u32;
+
+/// The extent of an index space that includes imports (i.e. functions, tables, memories, globals and tags).
+/// The indices of the imports precede the indices of the definitions of the module.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/modules.html#indices>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::IndexSpace;
+///
+/// let space = IndexSpace::new(2, 1);
+///
+/// assert_eq!(space.len(), 3);
+/// assert!(space.is_import(1));
+/// assert!(!space.is_import(2));
+/// assert!(space.contains(2));
+/// assert!(!space.contains(3));
+/// ```
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct IndexSpace {
+    imports: usize,
+    definitions: usize,
+}
+
+impl IndexSpace {
+    /// Creates a new index space with the given number of imports and definitions.
+    pub fn new(imports: usize, definitions: usize) -> Self {
+        IndexSpace {
+            imports,
+            definitions,
+        }
+    }
+
+    /// The number of imports in the index space.
+    /// Equivalently, the index of the first definition.
+    pub fn imports(&self) -> usize {
+        self.imports
+    }
+
+    /// The number of definitions in the index space.
+    pub fn definitions(&self) -> usize {
+        self.definitions
+    }
+
+    /// The number of indices in the index space.
+    pub fn len(&self) -> usize {
+        self.imports + self.definitions
+    }
+
+    /// True if the index space has neither imports nor definitions.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// True if the index belongs to the index space.
+    pub fn contains(&self, index: u32) -> bool {
+        (index as usize) < self.len()
+    }
+
+    /// True if the index refers to an import.
+    pub fn is_import(&self, index: u32) -> bool {
+        (index as usize) < self.imports
+    }
+}
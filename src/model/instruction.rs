@@ -1,8 +1,9 @@
 //! WebAssembly instruction set.
 
 use crate::model::{
-    DataIndex, ElementIndex, FloatType, FunctionIndex, GlobalIndex, IntegerType, LabelIndex,
-    LocalIndex, NumberType, ReferenceType, TableIndex, TypeIndex, ValueType,
+    DataIndex, ElementIndex, FieldIndex, FloatType, FunctionIndex, GlobalIndex, HeapType,
+    IntegerType, LabelIndex, LocalIndex, MemoryIndex, ModelError, NumberType, TableIndex, TagIndex,
+    TypeIndex, Value, ValueType,
 };
 
 /// WebAssembly code consists of sequences of instructions.
@@ -22,14 +23,54 @@ use crate::model::{
 #[derive(Clone, Debug, PartialEq)]
 pub enum Instruction {
     Numeric(NumericInstruction),
+    Vector(VectorInstruction),
     Reference(ReferenceInstruction),
     Parametric(ParametricInstruction),
     Variable(VariableInstruction),
     Table(TableInstruction),
     Memory(MemoryInstruction),
+    Atomic(AtomicInstruction),
+    Gc(GcInstruction),
     Control(ControlInstruction),
 }
 
+impl Instruction {
+    /// Returns true if the instruction may appear in a constant expression, including the
+    /// integer arithmetic of the extended constant expressions proposal.
+    /// Whether a 𝗀𝗅𝗈𝖻𝖺𝗅.𝗀𝖾𝗍 reads an imported immutable global depends on the module,
+    /// so it is left to validation.
+    ///
+    /// See <https://webassembly.github.io/extended-const/core/valid/instructions.html#constant-expressions>
+    pub fn is_constant(&self) -> bool {
+        matches!(
+            self,
+            Instruction::Numeric(
+                NumericInstruction::I32Constant(_)
+                    | NumericInstruction::I64Constant(_)
+                    | NumericInstruction::F32Constant(_)
+                    | NumericInstruction::F64Constant(_)
+                    | NumericInstruction::Add(NumberType::I32 | NumberType::I64)
+                    | NumericInstruction::Subtract(NumberType::I32 | NumberType::I64)
+                    | NumericInstruction::Multiply(NumberType::I32 | NumberType::I64)
+            ) | Instruction::Vector(VectorInstruction::Constant(_))
+                | Instruction::Reference(
+                    ReferenceInstruction::Null(_) | ReferenceInstruction::Function(_)
+                )
+                | Instruction::Variable(VariableInstruction::GlobalGet(_))
+                | Instruction::Gc(
+                    GcInstruction::StructNew(_)
+                        | GcInstruction::StructNewDefault(_)
+                        | GcInstruction::ArrayNew(_)
+                        | GcInstruction::ArrayNewDefault(_)
+                        | GcInstruction::ArrayNewFixed(..)
+                        | GcInstruction::ReferenceI31
+                        | GcInstruction::AnyConvertExtern
+                        | GcInstruction::ExternConvertAny
+                )
+        )
+    }
+}
+
 /// Numeric instructions provide basic operations over numeric values of specific type.
 /// These operations closely match respective operations available in hardware.
 ///
@@ -452,6 +493,310 @@ impl From<f64> for Instruction {
     }
 }
 
+/// Vector instructions (also known as SIMD instructions, single data multiple value) provide
+/// basic operations over values of vector type.
+/// Vector instructions interpret the 128 bits of a vector as lanes of a given shape,
+/// which determines the number and type of the lanes.
+///
+/// Some vector instructions are only defined for some shapes (e.g., 𝗂𝟪𝗑𝟣𝟨.𝗆𝗎𝗅 does not exist).
+/// Use [`VectorInstruction::is_defined`] to determine whether an instruction exists.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+///
+/// # Examples
+/// ## Constant
+/// ```rust
+/// use wasm_ast::{VectorInstruction, Instruction};
+///
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::Constant(42)),
+///     VectorInstruction::Constant(42).into()
+/// );
+/// ```
+///
+/// ## Lanes
+/// ```rust
+/// use wasm_ast::{VectorInstruction, Instruction, VectorShape, SignExtension};
+///
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::Splat(VectorShape::F32x4)),
+///     VectorInstruction::Splat(VectorShape::F32x4).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::ExtractLane(VectorShape::I8x16, Some(SignExtension::Signed), 15)),
+///     VectorInstruction::ExtractLane(VectorShape::I8x16, Some(SignExtension::Signed), 15).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::ReplaceLane(VectorShape::I64x2, 1)),
+///     VectorInstruction::ReplaceLane(VectorShape::I64x2, 1).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::Shuffle([0; 16])),
+///     VectorInstruction::Shuffle([0; 16]).into()
+/// );
+/// ```
+///
+/// ## Arithmetic
+/// ```rust
+/// use wasm_ast::{VectorInstruction, Instruction, VectorShape, IntegerVectorShape, FloatVectorShape, SignExtension, VectorHalf};
+///
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::Add(VectorShape::I32x4)),
+///     VectorInstruction::Add(VectorShape::I32x4).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::AddSaturate(IntegerVectorShape::I8x16, SignExtension::Unsigned)),
+///     VectorInstruction::AddSaturate(IntegerVectorShape::I8x16, SignExtension::Unsigned).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::ExtendedMultiply(IntegerVectorShape::I64x2, VectorHalf::High, SignExtension::Signed)),
+///     VectorInstruction::ExtendedMultiply(IntegerVectorShape::I64x2, VectorHalf::High, SignExtension::Signed).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::PseudoMinimum(FloatVectorShape::F64x2)),
+///     VectorInstruction::PseudoMinimum(FloatVectorShape::F64x2).into()
+/// );
+/// ```
+///
+/// ## Memory
+/// ```rust
+/// use wasm_ast::{VectorInstruction, Instruction, IntegerVectorShape, MemoryArgument};
+///
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::Load(MemoryArgument::default_offset(4))),
+///     VectorInstruction::Load(MemoryArgument::default_offset(4)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Vector(VectorInstruction::LoadLane(IntegerVectorShape::I16x8, MemoryArgument::default_offset(1), 7)),
+///     VectorInstruction::LoadLane(IntegerVectorShape::I16x8, MemoryArgument::default_offset(1), 7).into()
+/// );
+/// ```
+///
+/// ## Defined
+/// ```rust
+/// use wasm_ast::{VectorInstruction, VectorShape, IntegerVectorShape, SignExtension};
+///
+/// assert!(VectorInstruction::Multiply(VectorShape::I16x8).is_defined());
+/// assert!(!VectorInstruction::Multiply(VectorShape::I8x16).is_defined());
+/// assert!(!VectorInstruction::LessThanInteger(IntegerVectorShape::I64x2, SignExtension::Unsigned).is_defined());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VectorInstruction {
+    /// v128.const i128
+    Constant(i128),
+    /// v128.not
+    Not,
+    /// v128.and
+    And,
+    /// v128.andnot
+    AndNot,
+    /// v128.or
+    Or,
+    /// v128.xor
+    Xor,
+    /// v128.bitselect
+    BitSelect,
+    /// v128.any_true
+    AnyTrue,
+    /// i8x16.shuffle laneidx^16
+    /// Selects lanes from two vectors, where indices below 16 refer to the first operand.
+    Shuffle([LaneIndex; 16]),
+    /// i8x16.swizzle
+    Swizzle,
+    /// shape.splat
+    /// Creates a vector with all lanes set to the operand.
+    Splat(VectorShape),
+    /// shape.extract_lane_sx? laneidx
+    /// Extracting a lane of an 𝗂𝟪𝗑𝟣𝟨 or 𝗂𝟣𝟨𝗑𝟪 vector requires a sign extension, other shapes must not have one.
+    ExtractLane(VectorShape, Option<SignExtension>, LaneIndex),
+    /// shape.replace_lane laneidx
+    ReplaceLane(VectorShape, LaneIndex),
+    /// shape.eq
+    Equal(VectorShape),
+    /// shape.ne
+    NotEqual(VectorShape),
+    /// ishape.lt_sx
+    /// 𝗂𝟨𝟦𝗑𝟤 only supports signed comparisons.
+    LessThanInteger(IntegerVectorShape, SignExtension),
+    /// fshape.lt
+    LessThanFloat(FloatVectorShape),
+    /// ishape.gt_sx
+    GreaterThanInteger(IntegerVectorShape, SignExtension),
+    /// fshape.gt
+    GreaterThanFloat(FloatVectorShape),
+    /// ishape.le_sx
+    LessThanOrEqualToInteger(IntegerVectorShape, SignExtension),
+    /// fshape.le
+    LessThanOrEqualToFloat(FloatVectorShape),
+    /// ishape.ge_sx
+    GreaterThanOrEqualToInteger(IntegerVectorShape, SignExtension),
+    /// fshape.ge
+    GreaterThanOrEqualToFloat(FloatVectorShape),
+    /// shape.abs
+    AbsoluteValue(VectorShape),
+    /// shape.neg
+    Negate(VectorShape),
+    /// i8x16.popcnt
+    CountOnes,
+    /// fshape.sqrt
+    SquareRoot(FloatVectorShape),
+    /// fshape.ceil
+    Ceiling(FloatVectorShape),
+    /// fshape.floor
+    Floor(FloatVectorShape),
+    /// fshape.trunc
+    Truncate(FloatVectorShape),
+    /// fshape.nearest
+    Nearest(FloatVectorShape),
+    /// ishape.all_true
+    AllTrue(IntegerVectorShape),
+    /// ishape.bitmask
+    Bitmask(IntegerVectorShape),
+    /// ishape.narrow_ishape_sx
+    /// The shape is that of the result (i.e., 𝗂𝟪𝗑𝟣𝟨 or 𝗂𝟣𝟨𝗑𝟪).
+    Narrow(IntegerVectorShape, SignExtension),
+    /// ishape.extend_half_ishape_sx
+    /// The shape is that of the result (i.e., 𝗂𝟣𝟨𝗑𝟪, 𝗂𝟥𝟤𝗑𝟦 or 𝗂𝟨𝟦𝗑𝟤).
+    Extend(IntegerVectorShape, VectorHalf, SignExtension),
+    /// ishape.shl
+    ShiftLeft(IntegerVectorShape),
+    /// ishape.shr_sx
+    ShiftRight(IntegerVectorShape, SignExtension),
+    /// shape.add
+    Add(VectorShape),
+    /// shape.sub
+    Subtract(VectorShape),
+    /// shape.mul
+    /// Not defined for 𝗂𝟪𝗑𝟣𝟨.
+    Multiply(VectorShape),
+    /// ishape.add_sat_sx
+    /// Only defined for 𝗂𝟪𝗑𝟣𝟨 and 𝗂𝟣𝟨𝗑𝟪.
+    AddSaturate(IntegerVectorShape, SignExtension),
+    /// ishape.sub_sat_sx
+    /// Only defined for 𝗂𝟪𝗑𝟣𝟨 and 𝗂𝟣𝟨𝗑𝟪.
+    SubtractSaturate(IntegerVectorShape, SignExtension),
+    /// ishape.min_sx
+    /// Not defined for 𝗂𝟨𝟦𝗑𝟤.
+    MinimumInteger(IntegerVectorShape, SignExtension),
+    /// ishape.max_sx
+    /// Not defined for 𝗂𝟨𝟦𝗑𝟤.
+    MaximumInteger(IntegerVectorShape, SignExtension),
+    /// ishape.avgr_u
+    /// Only defined for 𝗂𝟪𝗑𝟣𝟨 and 𝗂𝟣𝟨𝗑𝟪.
+    AverageRounded(IntegerVectorShape),
+    /// i16x8.q15mulr_sat_s
+    Q15MultiplyRoundSaturate,
+    /// i32x4.dot_i16x8_s
+    DotProduct,
+    /// ishape.extmul_half_ishape_sx
+    /// The shape is that of the result (i.e., 𝗂𝟣𝟨𝗑𝟪, 𝗂𝟥𝟤𝗑𝟦 or 𝗂𝟨𝟦𝗑𝟤).
+    ExtendedMultiply(IntegerVectorShape, VectorHalf, SignExtension),
+    /// ishape.extadd_pairwise_ishape_sx
+    /// The shape is that of the result (i.e., 𝗂𝟣𝟨𝗑𝟪 or 𝗂𝟥𝟤𝗑𝟦).
+    ExtendedAddPairwise(IntegerVectorShape, SignExtension),
+    /// fshape.div
+    DivideFloat(FloatVectorShape),
+    /// fshape.min
+    MinimumFloat(FloatVectorShape),
+    /// fshape.max
+    MaximumFloat(FloatVectorShape),
+    /// fshape.pmin
+    PseudoMinimum(FloatVectorShape),
+    /// fshape.pmax
+    PseudoMaximum(FloatVectorShape),
+    /// i32x4.trunc_sat_f32x4_sx or i32x4.trunc_sat_f64x2_sx_zero
+    /// The shape is that of the operand.
+    ConvertAndTruncateWithSaturation(FloatVectorShape, SignExtension),
+    /// f32x4.convert_i32x4_sx or f64x2.convert_low_i32x4_sx
+    /// The shape is that of the result.
+    Convert(FloatVectorShape, SignExtension),
+    /// f32x4.demote_f64x2_zero
+    Demote,
+    /// f64x2.promote_low_f32x4
+    Promote,
+    /// v128.load memarg
+    Load(MemoryArgument),
+    /// v128.store memarg
+    Store(MemoryArgument),
+    /// v128.loadNxM_sx memarg
+    /// Loads 64 bits and extends each lane of the given shape to twice its width
+    /// (i.e., 𝗂𝟪𝗑𝟣𝟨 for 𝗅𝗈𝖺𝖽𝟪𝗑𝟪, 𝗂𝟣𝟨𝗑𝟪 for 𝗅𝗈𝖺𝖽𝟣𝟨𝗑𝟦 and 𝗂𝟥𝟤𝗑𝟦 for 𝗅𝗈𝖺𝖽𝟥𝟤𝗑𝟤).
+    LoadExtend(IntegerVectorShape, SignExtension, MemoryArgument),
+    /// v128.loadN_splat memarg
+    /// Loads a single lane of the given shape and replicates it to all lanes.
+    LoadSplat(IntegerVectorShape, MemoryArgument),
+    /// v128.loadN_zero memarg
+    /// Loads a single lane of the given shape (i.e., 𝗂𝟥𝟤𝗑𝟦 or 𝗂𝟨𝟦𝗑𝟤), setting the other lanes to zero.
+    LoadZero(IntegerVectorShape, MemoryArgument),
+    /// v128.loadN_lane memarg laneidx
+    /// Loads a single lane of the given shape, replacing the lane of the vector operand.
+    LoadLane(IntegerVectorShape, MemoryArgument, LaneIndex),
+    /// v128.storeN_lane memarg laneidx
+    /// Stores a single lane of the given shape.
+    StoreLane(IntegerVectorShape, MemoryArgument, LaneIndex),
+    /// i8x16.relaxed_swizzle
+    /// Like 𝗌𝗐𝗂𝗓𝗓𝗅𝖾, except that the result for lane indices of 16 or more is implementation-defined.
+    RelaxedSwizzle,
+    /// i32x4.relaxed_trunc_f32x4_sx or i32x4.relaxed_trunc_f64x2_sx_zero
+    /// The shape is that of the operand.
+    RelaxedTruncate(FloatVectorShape, SignExtension),
+    /// fshape.relaxed_madd
+    RelaxedMultiplyAdd(FloatVectorShape),
+    /// fshape.relaxed_nmadd
+    RelaxedNegativeMultiplyAdd(FloatVectorShape),
+    /// ishape.relaxed_laneselect
+    RelaxedLaneSelect(IntegerVectorShape),
+    /// fshape.relaxed_min
+    RelaxedMinimum(FloatVectorShape),
+    /// fshape.relaxed_max
+    RelaxedMaximum(FloatVectorShape),
+    /// i16x8.relaxed_q15mulr_s
+    RelaxedQ15MultiplyRound,
+    /// i16x8.relaxed_dot_i8x16_i7x16_s
+    RelaxedDotProduct,
+    /// i32x4.relaxed_dot_i8x16_i7x16_add_s
+    RelaxedDotProductAdd,
+}
+
+impl VectorInstruction {
+    /// Determines whether the instruction is defined for its shape.
+    /// Only defined instructions have a representation in the binary and text formats.
+    pub fn is_defined(&self) -> bool {
+        use IntegerVectorShape::*;
+
+        match *self {
+            VectorInstruction::ExtractLane(shape, sign, _) => {
+                matches!(shape, VectorShape::I8x16 | VectorShape::I16x8) == sign.is_some()
+            }
+            VectorInstruction::LessThanInteger(I64x2, SignExtension::Unsigned)
+            | VectorInstruction::GreaterThanInteger(I64x2, SignExtension::Unsigned)
+            | VectorInstruction::LessThanOrEqualToInteger(I64x2, SignExtension::Unsigned)
+            | VectorInstruction::GreaterThanOrEqualToInteger(I64x2, SignExtension::Unsigned) => {
+                false
+            }
+            VectorInstruction::Multiply(shape) => shape != VectorShape::I8x16,
+            VectorInstruction::Narrow(shape, _)
+            | VectorInstruction::AddSaturate(shape, _)
+            | VectorInstruction::SubtractSaturate(shape, _)
+            | VectorInstruction::AverageRounded(shape) => matches!(shape, I8x16 | I16x8),
+            VectorInstruction::MinimumInteger(shape, _)
+            | VectorInstruction::MaximumInteger(shape, _) => shape != I64x2,
+            VectorInstruction::Extend(shape, ..)
+            | VectorInstruction::ExtendedMultiply(shape, ..) => shape != I8x16,
+            VectorInstruction::ExtendedAddPairwise(shape, _) => matches!(shape, I16x8 | I32x4),
+            VectorInstruction::LoadExtend(shape, ..) => shape != I64x2,
+            VectorInstruction::LoadZero(shape, _) => matches!(shape, I32x4 | I64x2),
+            _ => true,
+        }
+    }
+}
+
+impl From<VectorInstruction> for Instruction {
+    fn from(instruction: VectorInstruction) -> Self {
+        Instruction::Vector(instruction)
+    }
+}
+
 /// Instructions in this group are concerned with accessing references.
 /// These instruction produce a null value, check for a null value, or produce a reference to a given function, respectively.
 ///
@@ -459,11 +804,11 @@ impl From<f64> for Instruction {
 ///
 /// # Examples
 /// ```rust
-/// use wasm_ast::{ReferenceInstruction, Instruction, ReferenceType};
+/// use wasm_ast::{ReferenceInstruction, Instruction, HeapType};
 ///
 /// assert_eq!(
-///     Instruction::Reference(ReferenceInstruction::Null(ReferenceType::External)),
-///     ReferenceInstruction::Null(ReferenceType::External).into()
+///     Instruction::Reference(ReferenceInstruction::Null(HeapType::External)),
+///     ReferenceInstruction::Null(HeapType::External).into()
 /// );
 /// assert_eq!(
 ///     Instruction::Reference(ReferenceInstruction::IsNull),
@@ -476,15 +821,25 @@ impl From<f64> for Instruction {
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum ReferenceInstruction {
-    /// ref.null
+    /// ref.null heaptype
     /// Produce a null value.
-    Null(ReferenceType),
+    Null(HeapType),
     /// ref.is_null
     /// Check for a null value.
     IsNull,
     /// ref.func funcidx
     /// Produce a reference to a given function.
     Function(FunctionIndex),
+    /// ref.eq
+    /// Compare two references of the 𝖾𝗊 heap type for identity.
+    ///
+    /// See <https://webassembly.github.io/gc/core/syntax/instructions.html#reference-instructions>
+    Equal,
+    /// ref.as_non_null
+    /// Convert a nullable reference to a non-nullable one, trapping on null.
+    ///
+    /// See <https://webassembly.github.io/function-references/core/syntax/instructions.html#reference-instructions>
+    AsNonNull,
 }
 
 impl From<ReferenceInstruction> for Instruction {
@@ -666,8 +1021,9 @@ impl From<TableInstruction> for Instruction {
 
 /// Instructions in this group are concerned with linear memory.
 /// Memory is accessed with 𝗅𝗈𝖺𝖽 and 𝗌𝗍𝗈𝗋𝖾 instructions for the different value types.
-/// They all take a memory immediate memarg that contains an address offset and
-/// the expected alignment (expressed as the exponent of a power of 2).
+/// They all take a memory immediate memarg that contains an address offset,
+/// the expected alignment (expressed as the exponent of a power of 2) and the index of the memory to access.
+/// The other memory instructions take the indices of the memories they operate on as immediates.
 /// Integer loads and stores can optionally specify a storage size that is smaller than
 /// the bit width of the respective value type.
 /// In the case of loads, a sign extension mode sx is then required to select appropriate behavior.
@@ -717,24 +1073,24 @@ impl From<TableInstruction> for Instruction {
 ///     MemoryInstruction::Store32(MemoryArgument::default_offset(4)).into()
 /// );
 /// assert_eq!(
-///     Instruction::Memory(MemoryInstruction::Size),
-///     MemoryInstruction::Size.into()
+///     Instruction::Memory(MemoryInstruction::Size(0)),
+///     MemoryInstruction::Size(0).into()
 /// );
 /// assert_eq!(
-///     Instruction::Memory(MemoryInstruction::Grow),
-///     MemoryInstruction::Grow.into()
+///     Instruction::Memory(MemoryInstruction::Grow(1)),
+///     MemoryInstruction::Grow(1).into()
 /// );
 /// assert_eq!(
-///     Instruction::Memory(MemoryInstruction::Fill),
-///     MemoryInstruction::Fill.into()
+///     Instruction::Memory(MemoryInstruction::Fill(0)),
+///     MemoryInstruction::Fill(0).into()
 /// );
 /// assert_eq!(
-///     Instruction::Memory(MemoryInstruction::Copy),
-///     MemoryInstruction::Copy.into()
+///     Instruction::Memory(MemoryInstruction::Copy(1, 0)),
+///     MemoryInstruction::Copy(1, 0).into()
 /// );
 /// assert_eq!(
-///     Instruction::Memory(MemoryInstruction::Init(1)),
-///     MemoryInstruction::Init(1).into()
+///     Instruction::Memory(MemoryInstruction::Init(1, 0)),
+///     MemoryInstruction::Init(1, 0).into()
 /// );
 /// assert_eq!(
 ///     Instruction::Memory(MemoryInstruction::DataDrop(0)),
@@ -767,17 +1123,17 @@ pub enum MemoryInstruction {
     Store32(MemoryArgument),
     /// The 𝗆𝖾𝗆𝗈𝗋𝗒.𝗌𝗂𝗓𝖾 instruction returns the current size of a memory.
     /// Operates in units of page size.
-    Size,
+    Size(MemoryIndex),
     /// The 𝗆𝖾𝗆𝗈𝗋𝗒.𝗀𝗋𝗈𝗐 instruction grows memory by a given delta and returns the previous size,
     /// or −1 if enough memory cannot be allocated.
-    Grow,
+    Grow(MemoryIndex),
     /// The 𝗆𝖾𝗆𝗈𝗋𝗒.𝖿𝗂𝗅𝗅 instruction sets all values in a region to a given byte.
-    Fill,
+    Fill(MemoryIndex),
     /// The 𝗆𝖾𝗆𝗈𝗋𝗒.𝖼𝗈𝗉𝗒 instruction copies data from a source memory region to
-    /// a possibly overlapping destination region.
-    Copy,
+    /// a possibly overlapping destination region; the first index denotes the destination.
+    Copy(MemoryIndex, MemoryIndex),
     /// The 𝗆𝖾𝗆𝗈𝗋𝗒.𝗂𝗇𝗂𝗍 instruction copies data from a passive data segment into a memory.
-    Init(DataIndex),
+    Init(DataIndex, MemoryIndex),
     /// he 𝖽𝖺𝗍𝖺.𝖽𝗋𝗈𝗉 instruction prevents further use of a passive data segment.
     /// This instruction is intended to be used as an optimization hint.
     /// After a data segment is dropped its data can no longer be retrieved,
@@ -791,6 +1147,205 @@ impl From<MemoryInstruction> for Instruction {
     }
 }
 
+/// Atomic instructions access shared linear memory indivisibly, allowing threads to synchronize.
+/// Like other memory instructions, the loads, stores and read-modify-write operations
+/// take a memarg immediate, whose alignment must equal the natural alignment of the access.
+/// Narrow accesses zero-extend the value read from memory.
+/// The 𝗐𝖺𝗂𝗍 and 𝗇𝗈𝗍𝗂𝖿𝗒 instructions suspend and wake threads waiting on an address.
+///
+/// See <https://webassembly.github.io/threads/core/syntax/instructions.html#atomic-memory-instructions>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{AtomicInstruction, AtomicOperation, Instruction, IntegerType, MemoryArgument};
+///
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Notify(MemoryArgument::default_offset(2))),
+///     AtomicInstruction::Notify(MemoryArgument::default_offset(2)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Wait(IntegerType::I64, MemoryArgument::default_offset(3))),
+///     AtomicInstruction::Wait(IntegerType::I64, MemoryArgument::default_offset(3)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Fence),
+///     AtomicInstruction::Fence.into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Load(IntegerType::I32, MemoryArgument::default_offset(2))),
+///     AtomicInstruction::Load(IntegerType::I32, MemoryArgument::default_offset(2)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Load8(IntegerType::I64, MemoryArgument::default_offset(0))),
+///     AtomicInstruction::Load8(IntegerType::I64, MemoryArgument::default_offset(0)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::Store32(MemoryArgument::default_offset(2))),
+///     AtomicInstruction::Store32(MemoryArgument::default_offset(2)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::ReadModifyWrite(AtomicOperation::Add, IntegerType::I64, MemoryArgument::default_offset(3))),
+///     AtomicInstruction::ReadModifyWrite(AtomicOperation::Add, IntegerType::I64, MemoryArgument::default_offset(3)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Atomic(AtomicInstruction::ReadModifyWrite16(AtomicOperation::CompareExchange, IntegerType::I32, MemoryArgument::default_offset(1))),
+///     AtomicInstruction::ReadModifyWrite16(AtomicOperation::CompareExchange, IntegerType::I32, MemoryArgument::default_offset(1)).into()
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AtomicInstruction {
+    /// memory.atomic.notify memarg
+    /// Wakes up to a given number of threads waiting on an address and returns the number woken.
+    Notify(MemoryArgument),
+    /// memory.atomic.waitnn memarg
+    /// Suspends the thread until notified if the address holds the expected value.
+    Wait(IntegerType, MemoryArgument),
+    /// atomic.fence
+    /// Orders the memory accesses before and after the fence.
+    Fence,
+    /// inn.atomic.load memarg
+    Load(IntegerType, MemoryArgument),
+    /// inn.atomic.load8_u memarg
+    Load8(IntegerType, MemoryArgument),
+    /// inn.atomic.load16_u memarg
+    Load16(IntegerType, MemoryArgument),
+    /// i64.atomic.load32_u memarg
+    Load32(MemoryArgument),
+    /// inn.atomic.store memarg
+    Store(IntegerType, MemoryArgument),
+    /// inn.atomic.store8 memarg
+    Store8(IntegerType, MemoryArgument),
+    /// inn.atomic.store16 memarg
+    Store16(IntegerType, MemoryArgument),
+    /// i64.atomic.store32 memarg
+    Store32(MemoryArgument),
+    /// inn.atomic.rmw.op memarg
+    /// Atomically modifies a value in memory and returns the value read before the modification.
+    ReadModifyWrite(AtomicOperation, IntegerType, MemoryArgument),
+    /// inn.atomic.rmw8.op_u memarg
+    ReadModifyWrite8(AtomicOperation, IntegerType, MemoryArgument),
+    /// inn.atomic.rmw16.op_u memarg
+    ReadModifyWrite16(AtomicOperation, IntegerType, MemoryArgument),
+    /// i64.atomic.rmw32.op_u memarg
+    ReadModifyWrite32(AtomicOperation, MemoryArgument),
+}
+
+impl From<AtomicInstruction> for Instruction {
+    fn from(instruction: AtomicInstruction) -> Self {
+        Instruction::Atomic(instruction)
+    }
+}
+
+/// The modification applied by an atomic read-modify-write instruction.
+///
+/// See <https://webassembly.github.io/threads/core/syntax/instructions.html#atomic-memory-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum AtomicOperation {
+    Add,
+    Subtract,
+    And,
+    Or,
+    Xor,
+    /// Replaces the value in memory.
+    Exchange,
+    /// Replaces the value in memory only if it equals an expected value.
+    CompareExchange,
+}
+
+/// Instructions in this group create and access the structures, arrays and unboxed scalars
+/// introduced by garbage collection, and test and cast references against heap types.
+/// They are encoded with the 0xFB prefix.
+///
+/// Cast instructions take the reference type to test or cast to, which must be a reference type.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/instructions.html#aggregate-reference-instructions>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{GcInstruction, HeapType, Instruction, SignExtension, ValueType};
+///
+/// assert_eq!(
+///     Instruction::Gc(GcInstruction::StructGet(0, 1, Some(SignExtension::Signed))),
+///     GcInstruction::StructGet(0, 1, Some(SignExtension::Signed)).into()
+/// );
+/// assert_eq!(
+///     Instruction::Gc(GcInstruction::Cast(ValueType::Reference(HeapType::Index(2)))),
+///     GcInstruction::Cast(ValueType::Reference(HeapType::Index(2))).into()
+/// );
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GcInstruction {
+    /// struct.new typeidx
+    /// Allocates a structure with its fields initialized from operands.
+    StructNew(TypeIndex),
+    /// struct.new_default typeidx
+    /// Allocates a structure with its fields initialized to their default values.
+    StructNewDefault(TypeIndex),
+    /// struct.get_sx? typeidx fieldidx
+    /// Reads a field of a structure. Packed fields must be read with a sign extension.
+    StructGet(TypeIndex, FieldIndex, Option<SignExtension>),
+    /// struct.set typeidx fieldidx
+    StructSet(TypeIndex, FieldIndex),
+    /// array.new typeidx
+    /// Allocates an array with all elements initialized to an operand.
+    ArrayNew(TypeIndex),
+    /// array.new_default typeidx
+    ArrayNewDefault(TypeIndex),
+    /// array.new_fixed typeidx u32
+    /// Allocates an array of the given length with its elements initialized from operands.
+    ArrayNewFixed(TypeIndex, u32),
+    /// array.new_data typeidx dataidx
+    ArrayNewData(TypeIndex, DataIndex),
+    /// array.new_elem typeidx elemidx
+    ArrayNewElement(TypeIndex, ElementIndex),
+    /// array.get_sx? typeidx
+    /// Reads an element of an array. Packed elements must be read with a sign extension.
+    ArrayGet(TypeIndex, Option<SignExtension>),
+    /// array.set typeidx
+    ArraySet(TypeIndex),
+    /// array.len
+    ArrayLength,
+    /// array.fill typeidx
+    ArrayFill(TypeIndex),
+    /// array.copy typeidx typeidx
+    /// Copies elements from an array of the second type to an array of the first type.
+    ArrayCopy(TypeIndex, TypeIndex),
+    /// array.init_data typeidx dataidx
+    ArrayInitData(TypeIndex, DataIndex),
+    /// array.init_elem typeidx elemidx
+    ArrayInitElement(TypeIndex, ElementIndex),
+    /// ref.test reftype
+    /// Tests whether a reference matches the given reference type.
+    Test(ValueType),
+    /// ref.cast reftype
+    /// Casts a reference to the given reference type, trapping if it does not match.
+    Cast(ValueType),
+    /// br_on_cast labelidx reftype reftype
+    /// Branches if a reference of the first type matches the second type.
+    BranchOnCast(LabelIndex, ValueType, ValueType),
+    /// br_on_cast_fail labelidx reftype reftype
+    /// Branches if a reference of the first type does not match the second type.
+    BranchOnCastFail(LabelIndex, ValueType, ValueType),
+    /// any.convert_extern
+    /// Converts an external reference into an internal reference.
+    AnyConvertExtern,
+    /// extern.convert_any
+    /// Converts an internal reference into an external reference.
+    ExternConvertAny,
+    /// ref.i31
+    /// Converts an 𝗂𝟥𝟤 into an unboxed scalar, dropping its highest bit.
+    ReferenceI31,
+    /// i31.get_sx
+    /// Reads the value of an unboxed scalar as an 𝗂𝟥𝟤.
+    I31Get(SignExtension),
+}
+
+impl From<GcInstruction> for Instruction {
+    fn from(instruction: GcInstruction) -> Self {
+        Instruction::Gc(instruction)
+    }
+}
+
 /// Instructions in this group affect the flow of control.
 /// The 𝖻𝗅𝗈𝖼𝗄, 𝗅𝗈𝗈𝗉 and 𝗂𝖿 instructions are structured instructions.
 /// They bracket nested sequences of instructions, called blocks, terminated with, or separated by,
@@ -836,6 +1391,10 @@ impl From<MemoryInstruction> for Instruction {
 /// assert_eq!(Instruction::Control(ControlInstruction::Return), ControlInstruction::Return.into());
 /// assert_eq!(Instruction::Control(ControlInstruction::Call(1)), ControlInstruction::Call(1).into());
 /// assert_eq!(Instruction::Control(ControlInstruction::CallIndirect(0, 1)), ControlInstruction::CallIndirect(0, 1).into());
+/// assert_eq!(Instruction::Control(ControlInstruction::ReturnCall(1)), ControlInstruction::ReturnCall(1).into());
+/// assert_eq!(Instruction::Control(ControlInstruction::ReturnCallIndirect(0, 1)), ControlInstruction::ReturnCallIndirect(0, 1).into());
+/// assert_eq!(Instruction::Control(ControlInstruction::Throw(0)), ControlInstruction::Throw(0).into());
+/// assert_eq!(Instruction::Control(ControlInstruction::ThrowReference), ControlInstruction::ThrowReference.into());
 /// ```
 ///
 /// ## Block
@@ -876,6 +1435,18 @@ impl From<MemoryInstruction> for Instruction {
 ///     ControlInstruction::If(BlockType::None, expression.clone(), Some(expression.clone())).into()
 /// );
 /// ```
+///
+/// ## Try Table
+/// ```rust
+/// use wasm_ast::{ControlInstruction, Instruction, Expression, BlockType, Catch};
+/// let expression = Expression::new(vec![ControlInstruction::Throw(0).into()]);
+/// let catches = vec![Catch::Catch(0, 0), Catch::CatchAllReference(1)];
+///
+/// assert_eq!(
+///     Instruction::Control(ControlInstruction::TryTable(BlockType::None, catches.clone(), expression.clone())),
+///     ControlInstruction::TryTable(BlockType::None, catches.clone(), expression.clone()).into()
+/// );
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub enum ControlInstruction {
     /// The 𝗇𝗈𝗉 instruction does nothing.
@@ -908,6 +1479,37 @@ pub enum ControlInstruction {
     /// the callee is dynamically checked against the function type indexed by the instruction’s
     /// second immediate, and the call is aborted with a trap if it does not match.
     CallIndirect(TypeIndex, TableIndex),
+    /// The 𝗋𝖾𝗍𝗎𝗋𝗇_𝖼𝖺𝗅𝗅 instruction performs a tail call to another function,
+    /// unwinding the current frame before invoking the callee.
+    /// The callee must return the same result types as the current function.
+    ///
+    /// See <https://webassembly.github.io/tail-call/core/syntax/instructions.html#control-instructions>
+    ReturnCall(FunctionIndex),
+    /// The 𝗋𝖾𝗍𝗎𝗋𝗇_𝖼𝖺𝗅𝗅_𝗂𝗇𝖽𝗂𝗋𝖾𝖼𝗍 instruction performs a tail call through a table,
+    /// checking the callee against the function type like 𝖼𝖺𝗅𝗅_𝗂𝗇𝖽𝗂𝗋𝖾𝖼𝗍.
+    ReturnCallIndirect(TypeIndex, TableIndex),
+    /// The 𝖼𝖺𝗅𝗅_𝗋𝖾𝖿 instruction calls the function referenced by an operand of the indexed function type.
+    ///
+    /// See <https://webassembly.github.io/function-references/core/syntax/instructions.html#control-instructions>
+    CallReference(TypeIndex),
+    /// The 𝗋𝖾𝗍𝗎𝗋𝗇_𝖼𝖺𝗅𝗅_𝗋𝖾𝖿 instruction performs a tail call to the function referenced by an operand.
+    ReturnCallReference(TypeIndex),
+    /// The 𝖻𝗋_𝗈𝗇_𝗇𝗎𝗅𝗅 instruction branches if a reference operand is null,
+    /// and otherwise leaves the reference on the stack as non-nullable.
+    BranchOnNull(LabelIndex),
+    /// The 𝖻𝗋_𝗈𝗇_𝗇𝗈𝗇_𝗇𝗎𝗅𝗅 instruction branches with a reference operand if it is not null,
+    /// and otherwise drops it.
+    BranchOnNonNull(LabelIndex),
+    /// The 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction executes its expression like a block,
+    /// and transfers control to the label of the first matching catch clause when an exception is thrown.
+    ///
+    /// See <https://webassembly.github.io/exception-handling/core/syntax/instructions.html#control-instructions>
+    TryTable(BlockType, Vec<Catch>, Expression),
+    /// The 𝗍𝗁𝗋𝗈𝗐 instruction creates an exception with the given tag, consuming the tag's
+    /// parameters from the stack, and throws it.
+    Throw(TagIndex),
+    /// The 𝗍𝗁𝗋𝗈𝗐_𝗋𝖾𝖿 instruction re-throws the exception referenced by an operand of type 𝖾𝗑𝗇𝗋𝖾𝖿.
+    ThrowReference,
 }
 
 impl From<ControlInstruction> for Instruction {
@@ -916,6 +1518,35 @@ impl From<ControlInstruction> for Instruction {
     }
 }
 
+/// A catch clause of a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+/// Clauses that match a tag branch with the tag's parameters as operands,
+/// and the `_ref` variants additionally push a reference to the caught exception.
+///
+/// See <https://webassembly.github.io/exception-handling/core/syntax/instructions.html#control-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Catch {
+    /// Catches exceptions with the given tag.
+    Catch(TagIndex, LabelIndex),
+    /// Catches exceptions with the given tag, along with a reference to the exception.
+    CatchReference(TagIndex, LabelIndex),
+    /// Catches all exceptions.
+    CatchAll(LabelIndex),
+    /// Catches all exceptions, along with a reference to the exception.
+    CatchAllReference(LabelIndex),
+}
+
+impl Catch {
+    /// The label targeted by this catch clause.
+    pub fn label(&self) -> LabelIndex {
+        match self {
+            Catch::Catch(_, label)
+            | Catch::CatchReference(_, label)
+            | Catch::CatchAll(label)
+            | Catch::CatchAllReference(label) => *label,
+        }
+    }
+}
+
 /// A structured instruction can consume input and produce output on the operand stack according to
 /// its annotated block type.
 /// It is given either as a type index that refers to a suitable function type,
@@ -929,8 +1560,8 @@ pub enum BlockType {
     ValueType(ValueType),
 }
 
-/// Argument to load and store instructions that contains an address offset and
-/// the expected alignment (expressed as the exponent of a power of 2).
+/// Argument to load and store instructions that contains an address offset,
+/// the expected alignment (expressed as the exponent of a power of 2) and the index of the memory to access.
 ///
 /// The static address offset is added to the dynamic address operand,
 /// yielding a 33 bit effective address that is the zero-based index at which the memory is accessed.
@@ -976,26 +1607,67 @@ pub enum BlockType {
 ///
 /// assert_eq!(argument.offset(), 0);
 /// assert_eq!(argument.align(), 1);
+/// assert_eq!(argument.memory(), 0);
+/// ```
+///
+/// ## With Memory
+/// ```rust
+/// use wasm_ast::MemoryArgument;
+///
+/// let argument = MemoryArgument::with_memory(2, 3, 8);
+///
+/// assert_eq!(argument.memory(), 2);
+/// assert_eq!(argument.align(), 3);
+/// assert_eq!(argument.offset(), 8);
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub struct MemoryArgument {
     align: u32,
-    offset: u32,
+    offset: u64,
+    memory: MemoryIndex,
 }
 
 impl MemoryArgument {
-    /// Creates a new memory argument with the given alignment and offset.
-    pub fn new(align: u32, offset: u32) -> Self {
-        MemoryArgument { align, offset }
+    /// Creates a new memory argument with the given alignment and offset for the first memory.
+    pub fn new(align: u32, offset: u64) -> Self {
+        MemoryArgument {
+            align,
+            offset,
+            memory: 0,
+        }
     }
 
-    /// Creates a new memory argument with the default offset and the given alignment.
+    /// Creates a new memory argument with the default offset and the given alignment for the first memory.
     pub fn default_offset(align: u32) -> Self {
-        MemoryArgument { offset: 0, align }
+        MemoryArgument {
+            offset: 0,
+            align,
+            memory: 0,
+        }
+    }
+
+    /// Creates a new memory argument with the given alignment and offset for the given memory.
+    pub fn with_memory(memory: MemoryIndex, align: u32, offset: u64) -> Self {
+        MemoryArgument {
+            align,
+            offset,
+            memory,
+        }
+    }
+
+    /// The index of the memory accessed by the instruction.
+    pub fn memory(&self) -> MemoryIndex {
+        self.memory
+    }
+
+    /// Mutable reference to the index of the memory accessed by the instruction.
+    pub fn memory_mut(&mut self) -> &mut MemoryIndex {
+        &mut self.memory
     }
 
     /// The static address offset of the memory instruction.
-    pub fn offset(&self) -> u32 {
+    /// Offsets beyond 32 bits are only valid for memories with a 64-bit index type.
+    pub fn offset(&self) -> u64 {
         self.offset
     }
 
@@ -1017,6 +1689,83 @@ pub enum SignExtension {
     Unsigned,
 }
 
+/// The index of a lane in a vector instruction.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+pub type LaneIndex = u8;
+
+/// Vector instructions interpret the bits of a vector as lanes of a given shape.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{VectorShape, IntegerVectorShape, FloatVectorShape};
+///
+/// assert_eq!(VectorShape::I8x16, IntegerVectorShape::I8x16.into());
+/// assert_eq!(VectorShape::I64x2, IntegerVectorShape::I64x2.into());
+/// assert_eq!(VectorShape::F32x4, FloatVectorShape::F32x4.into());
+/// assert_eq!(VectorShape::F64x2, FloatVectorShape::F64x2.into());
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VectorShape {
+    I8x16,
+    I16x8,
+    I32x4,
+    I64x2,
+    F32x4,
+    F64x2,
+}
+
+impl From<IntegerVectorShape> for VectorShape {
+    fn from(shape: IntegerVectorShape) -> Self {
+        match shape {
+            IntegerVectorShape::I8x16 => VectorShape::I8x16,
+            IntegerVectorShape::I16x8 => VectorShape::I16x8,
+            IntegerVectorShape::I32x4 => VectorShape::I32x4,
+            IntegerVectorShape::I64x2 => VectorShape::I64x2,
+        }
+    }
+}
+
+impl From<FloatVectorShape> for VectorShape {
+    fn from(shape: FloatVectorShape) -> Self {
+        match shape {
+            FloatVectorShape::F32x4 => VectorShape::F32x4,
+            FloatVectorShape::F64x2 => VectorShape::F64x2,
+        }
+    }
+}
+
+/// The shapes of vectors with integer lanes.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum IntegerVectorShape {
+    I8x16,
+    I16x8,
+    I32x4,
+    I64x2,
+}
+
+/// The shapes of vectors with floating-point lanes.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum FloatVectorShape {
+    F32x4,
+    F64x2,
+}
+
+/// Some vector instructions only operate on the low or high half of the lanes of their operands.
+///
+/// See <https://webassembly.github.io/spec/core/syntax/instructions.html#vector-instructions>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum VectorHalf {
+    Low,
+    High,
+}
+
 /// Function bodies, initialization values for globals,
 /// and offsets of element or data segments are given as expressions, which are sequences of instructions terminated by an 𝖾𝗇𝖽 marker.
 /// In some places, validation restricts expressions to be constant,
@@ -1088,6 +1837,11 @@ impl Expression {
         &self.instructions
     }
 
+    /// Mutable reference to the instructions for this expression.
+    pub fn instructions_mut(&mut self) -> &mut Vec<Instruction> {
+        &mut self.instructions
+    }
+
     /// Returns true if this `Expression` has a length of zero, false otherwise.
     pub fn is_empty(&self) -> bool {
         self.instructions.is_empty()
@@ -1104,3 +1858,161 @@ impl From<Vec<Instruction>> for Expression {
         Expression { instructions }
     }
 }
+
+/// An expression consisting only of instructions that are allowed in constant expressions,
+/// such as the initializers of globals and the offsets of active element and data segments.
+/// Besides constants and references, the extended constant expressions proposal allows
+/// integer addition, subtraction and multiplication, and reading imported immutable globals.
+///
+/// Constant expressions can be evaluated given the values of the imported globals.
+/// Instructions that allocate or convert garbage-collected references need a store
+/// and cannot be evaluated.
+///
+/// See <https://webassembly.github.io/extended-const/core/valid/instructions.html#constant-expressions>
+///
+/// # Examples
+/// ## Extended
+/// ```rust
+/// use wasm_ast::{ConstantExpression, Expression, NumberType, NumericInstruction, Value, VariableInstruction};
+///
+/// let expression: Expression = vec![
+///     VariableInstruction::GlobalGet(0).into(),
+///     1024i32.into(),
+///     NumericInstruction::Multiply(NumberType::I32).into(),
+///     16i32.into(),
+///     NumericInstruction::Add(NumberType::I32).into(),
+/// ].into();
+/// let constant = ConstantExpression::new(expression.clone()).unwrap();
+///
+/// assert_eq!(constant.expression(), &expression);
+/// assert_eq!(constant.evaluate(&[Value::I32(2)]).unwrap(), Value::I32(2064));
+/// assert!(constant.evaluate(&[]).is_err());
+/// assert!(constant.evaluate(&[Value::I64(2)]).is_err());
+/// assert_eq!(Expression::from(constant), expression);
+/// ```
+///
+/// ## Non-Constant
+/// ```rust
+/// use wasm_ast::{ConstantExpression, Expression, IntegerType, NumericInstruction, SignExtension};
+///
+/// let expression: Expression = vec![
+///     6i32.into(),
+///     3i32.into(),
+///     NumericInstruction::DivideInteger(IntegerType::I32, SignExtension::Signed).into(),
+/// ].into();
+///
+/// assert!(ConstantExpression::try_from(expression).is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConstantExpression {
+    expression: Expression,
+}
+
+impl ConstantExpression {
+    /// Creates a new constant expression, checking that all of its instructions are constant.
+    pub fn new(expression: Expression) -> Result<Self, ModelError> {
+        match expression
+            .instructions()
+            .iter()
+            .position(|instruction| !instruction.is_constant())
+        {
+            Some(position) => Err(ModelError::NonConstantInstruction(position)),
+            None => Ok(ConstantExpression { expression }),
+        }
+    }
+
+    /// The underlying expression of this `ConstantExpression`.
+    pub fn expression(&self) -> &Expression {
+        &self.expression
+    }
+
+    /// Evaluates this `ConstantExpression` to a single value,
+    /// given the values of the imported globals in order of their global indices.
+    /// Integer arithmetic wraps around on overflow.
+    ///
+    /// See <https://webassembly.github.io/extended-const/core/exec/instructions.html#numeric-instructions>
+    pub fn evaluate(&self, globals: &[Value]) -> Result<Value, ModelError> {
+        let mut stack = Vec::new();
+
+        for (position, instruction) in self.expression.instructions().iter().enumerate() {
+            let value = match *instruction {
+                Instruction::Numeric(NumericInstruction::I32Constant(value)) => Value::I32(value),
+                Instruction::Numeric(NumericInstruction::I64Constant(value)) => Value::I64(value),
+                Instruction::Numeric(NumericInstruction::F32Constant(value)) => Value::F32(value),
+                Instruction::Numeric(NumericInstruction::F64Constant(value)) => Value::F64(value),
+                Instruction::Vector(VectorInstruction::Constant(value)) => Value::V128(value),
+                Instruction::Reference(ReferenceInstruction::Null(kind)) => Value::Null(kind),
+                Instruction::Reference(ReferenceInstruction::Function(function)) => {
+                    Value::Function(function)
+                }
+                Instruction::Variable(VariableInstruction::GlobalGet(global)) => *globals
+                    .get(global as usize)
+                    .ok_or(ModelError::UnknownGlobal(global))?,
+                Instruction::Numeric(
+                    operation @ (NumericInstruction::Add(_)
+                    | NumericInstruction::Subtract(_)
+                    | NumericInstruction::Multiply(_)),
+                ) => {
+                    let right = stack.pop();
+                    let left = stack.pop();
+
+                    match (operation, left, right) {
+                        (
+                            NumericInstruction::Add(NumberType::I32),
+                            Some(Value::I32(left)),
+                            Some(Value::I32(right)),
+                        ) => Value::I32(left.wrapping_add(right)),
+                        (
+                            NumericInstruction::Subtract(NumberType::I32),
+                            Some(Value::I32(left)),
+                            Some(Value::I32(right)),
+                        ) => Value::I32(left.wrapping_sub(right)),
+                        (
+                            NumericInstruction::Multiply(NumberType::I32),
+                            Some(Value::I32(left)),
+                            Some(Value::I32(right)),
+                        ) => Value::I32(left.wrapping_mul(right)),
+                        (
+                            NumericInstruction::Add(NumberType::I64),
+                            Some(Value::I64(left)),
+                            Some(Value::I64(right)),
+                        ) => Value::I64(left.wrapping_add(right)),
+                        (
+                            NumericInstruction::Subtract(NumberType::I64),
+                            Some(Value::I64(left)),
+                            Some(Value::I64(right)),
+                        ) => Value::I64(left.wrapping_sub(right)),
+                        (
+                            NumericInstruction::Multiply(NumberType::I64),
+                            Some(Value::I64(left)),
+                            Some(Value::I64(right)),
+                        ) => Value::I64(left.wrapping_mul(right)),
+                        _ => return Err(ModelError::InvalidConstantOperands(position)),
+                    }
+                }
+                _ => return Err(ModelError::UnevaluableInstruction(position)),
+            };
+
+            stack.push(value);
+        }
+
+        match stack[..] {
+            [value] => Ok(value),
+            _ => Err(ModelError::InvalidConstantResult(stack.len())),
+        }
+    }
+}
+
+impl TryFrom<Expression> for ConstantExpression {
+    type Error = ModelError;
+
+    fn try_from(expression: Expression) -> Result<Self, Self::Error> {
+        ConstantExpression::new(expression)
+    }
+}
+
+impl From<ConstantExpression> for Expression {
+    fn from(constant: ConstantExpression) -> Self {
+        constant.expression
+    }
+}
@@ -1,15 +1,23 @@
 //! The model of the WebAssembly syntax.
 
+pub mod builder;
 mod errors;
 pub mod indices;
 pub mod instruction;
 pub mod module;
+pub mod names;
+pub mod operators;
 pub mod types;
 pub mod values;
+pub mod visit;
 
+pub use builder::{CatchLabel, FunctionBuilder, Label};
 pub use errors::ModelError;
 pub use indices::*;
 pub use instruction::*;
 pub use module::*;
+pub use names::*;
+pub use operators::*;
 pub use types::*;
 pub use values::*;
+pub use visit::{Visit, VisitMut};
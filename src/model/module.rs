@@ -2,18 +2,62 @@
 
 use crate::model::indices::*;
 use crate::model::types::*;
-use crate::model::{Expression, Name};
+use crate::model::visit::{
+    walk_control_mut, walk_data_mut, walk_element_mut, walk_function_mut, walk_global_mut,
+    walk_table_mut,
+};
+use crate::model::{
+    AtomicInstruction, BlockType, Catch, ConstantExpression, ControlInstruction, Expression,
+    GcInstruction, MemoryInstruction, Name, NameSection, ParametricInstruction, TableInstruction,
+    Value, VariableInstruction, VectorInstruction, VisitMut,
+};
 use crate::{ModelError, ReferenceInstruction};
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::mem::discriminant;
 
 /// A builder pattern for `Module`s.
 /// The builder performs minimal validation when using the `add_*` family of methods.
 /// The builder validates that the added element would not exceed the maximum size of a u32.
-/// No other validations are performed.
+/// No other validations are performed, unless the builder is strict.
+///
+/// A strict builder also checks that the indices referenced by each added component
+/// are defined by the module at the time of insertion, and that export names are unique.
+/// This covers the functions, tables, memories, globals and tags referenced by the expressions
+/// of the component (e.g. function bodies, initializers and segment offsets), and the types
+/// referenced by its typed references.
+/// Components must therefore be added in dependency order (e.g. types before functions),
+/// except that a function body may call the function itself.
+/// Exports and the start function are only checked when added with `try_add_export` and `try_set_start`.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{Export, Function, FunctionType, ModelError, Module, ModuleBuilder, ResultType};
+///
+/// let mut builder = ModuleBuilder::strict();
+///
+/// assert!(matches!(
+///     builder.add_function(Function::new(0, ResultType::empty(), vec![].into())),
+///     Err(ModelError::UnknownType(0))
+/// ));
+///
+/// let kind = builder.add_function_type(FunctionType::runnable()).unwrap();
+/// let function = builder.add_function(Function::new(kind, ResultType::empty(), vec![].into())).unwrap();
+///
+/// builder.try_add_export(Export::function("main".into(), function)).unwrap();
+///
+/// assert!(matches!(
+///     builder.try_add_export(Export::function("main".into(), function)),
+///     Err(ModelError::DuplicateExport(name)) if name == "main"
+/// ));
+/// assert!(matches!(
+///     builder.try_add_export(Export::memory("memory".into(), 0)),
+///     Err(ModelError::UnknownMemory(0))
+/// ));
+/// assert_eq!(builder.build().exports().map(<[Export]>::len), Some(1));
+/// ```
 pub struct ModuleBuilder {
     module: Module,
+    strict: bool,
 }
 
 impl ModuleBuilder {
@@ -21,28 +65,120 @@ impl ModuleBuilder {
     pub fn new() -> Self {
         ModuleBuilder {
             module: Module::empty(),
+            strict: false,
+        }
+    }
+
+    /// Creates a new empty builder of WebAssembly modules that checks
+    /// the indices referenced by each added component and the uniqueness of export names.
+    pub fn strict() -> Self {
+        ModuleBuilder {
+            module: Module::empty(),
+            strict: true,
         }
     }
 
-    /// Sets the function types segment for the WebAssembly module to be built.
-    pub fn set_function_types(&mut self, function_types: Option<Vec<FunctionType>>) {
-        self.module.function_types = function_types;
+    /// Enables or disables the checks performed by the `add_*` and `try_*` methods in strict mode.
+    /// Components already in the builder are not checked when strict mode is enabled.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// True if the builder checks the components added to it.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets the types segment for the WebAssembly module to be built.
+    pub fn set_types(&mut self, types: Option<Vec<RecursiveType>>) {
+        self.module.types = types;
     }
 
-    /// Adds the function type to the module's segment.
+    /// Adds the function type to the module's segment, as a final type in a recursive group of its own.
     /// Returns the index of the type in the module.
     pub fn add_function_type(
         &mut self,
         function_type: FunctionType,
     ) -> Result<TypeIndex, ModelError> {
-        let function_types = self.module.function_types.get_or_insert_with(Vec::new);
-        let index = u32::try_from(function_types.len())?;
+        self.add_recursive_type(function_type.into())
+    }
 
-        function_types.push(function_type);
+    /// Adds the recursive group of types to the module's segment.
+    /// Returns the index of the first type of the group in the module.
+    /// In strict mode, the supertypes of each type must precede it in the index space.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{
+    ///     ArrayType, FieldType, ModuleBuilder, PackedType, RecursiveType, StructType, SubType,
+    /// };
+    ///
+    /// let mut builder = ModuleBuilder::strict();
+    /// let bytes = ArrayType::new(FieldType::mutable(PackedType::I8));
+    /// let point = StructType::new(vec![]);
+    ///
+    /// assert_eq!(builder.add_recursive_type(bytes.into()).unwrap(), 0);
+    /// assert_eq!(
+    ///     builder.add_recursive_type(RecursiveType::new(vec![
+    ///         SubType::new(false, vec![], point.clone().into()),
+    ///         SubType::new(true, vec![1], point.into()),
+    ///     ])).unwrap(),
+    ///     1
+    /// );
+    /// assert!(builder
+    ///     .add_recursive_type(SubType::new(true, vec![3], bytes.into()).into())
+    ///     .is_err());
+    /// assert_eq!(builder.build().sub_types().count(), 3);
+    /// ```
+    pub fn add_recursive_type(&mut self, kind: RecursiveType) -> Result<TypeIndex, ModelError> {
+        let index = u32::try_from(self.module.sub_types().count())?;
+
+        if self.strict {
+            for (current, sub_type) in (index..).zip(kind.types()) {
+                if let Some(supertype) = sub_type.supertypes().iter().find(|s| **s >= current) {
+                    return Err(ModelError::UnknownType(*supertype));
+                }
+            }
+        }
+
+        self.module.types.get_or_insert_with(Vec::new).push(kind);
 
         Ok(index)
     }
 
+    /// Adds the function type to the module's segment, unless an equal type is already present.
+    /// Returns the index of the first equal type in the module, or of the added type.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{FunctionType, Module, RecursiveType, ResultType, ValueType};
+    ///
+    /// let mut builder = Module::builder();
+    /// let unary = FunctionType::side_effect(ResultType::new(vec![ValueType::I32]));
+    ///
+    /// assert_eq!(builder.intern_function_type(FunctionType::runnable()).unwrap(), 0);
+    /// assert_eq!(builder.intern_function_type(unary.clone()).unwrap(), 1);
+    /// assert_eq!(builder.intern_function_type(FunctionType::runnable()).unwrap(), 0);
+    /// assert_eq!(builder.types().map(<[RecursiveType]>::len), Some(2));
+    /// ```
+    pub fn intern_function_type(
+        &mut self,
+        function_type: FunctionType,
+    ) -> Result<TypeIndex, ModelError> {
+        let group = RecursiveType::from(function_type);
+        let mut index = 0;
+
+        for candidate in self.module.types().unwrap_or_default() {
+            if *candidate == group {
+                return Ok(u32::try_from(index)?);
+            }
+
+            index += candidate.len();
+        }
+
+        self.add_recursive_type(group)
+    }
+
     /// Sets the functions segment for the WebAssembly module to be built.
     pub fn set_functions(&mut self, functions: Option<Vec<Function>>) {
         self.module.functions = functions;
@@ -51,20 +187,33 @@ impl ModuleBuilder {
     /// Adds the function to the module's segment.
     /// Returns the index of the function in the module.
     ///
-    /// **Note:** In order for the returned index to be accurate,
-    /// all function imports must be defined prior to adding any functions.
+    /// **Note:** Adding a function import afterwards shifts the index of every defined function by one.
+    /// The builder updates the references within the module, but not the indices already returned.
+    ///
+    /// In strict mode, the type of the function and the types of its locals must be defined,
+    /// and so must the components referenced by its body, except for the function itself.
+    /// A lazily parsed body must then be a valid expression, but is not detached from its bytes.
     pub fn add_function(&mut self, function: Function) -> Result<FunctionIndex, ModelError> {
-        let functions = self.module.functions.get_or_insert_with(Vec::new);
-        let imports = match &self.module.imports {
-            Some(imports) => imports
-                .iter()
-                .filter(|import| matches!(import.description(), ImportDescription::Function(_)))
-                .count(),
-            None => 0,
-        };
-        let index = u32::try_from(functions.len() + imports)?;
+        self.check_type(function.kind())?;
 
-        functions.push(function);
+        for local in function.locals().kinds() {
+            self.check_heap_type(local.heap_type())?;
+        }
+
+        let index = u32::try_from(self.module.function_index_space().len())?;
+
+        if self.strict {
+            let mut body = function_body(&function, index)?.clone();
+
+            self.check_references(Some(Space::Function), |visitor| {
+                visitor.visit_expression(&mut body)
+            })?;
+        }
+
+        self.module
+            .functions
+            .get_or_insert_with(Vec::new)
+            .push(function);
 
         Ok(index)
     }
@@ -77,20 +226,20 @@ impl ModuleBuilder {
     /// Adds the table to the module's segment.
     /// Returns the index of the table in the module.
     ///
-    /// **Note:** In order for the returned index to be accurate,
-    /// all table imports must be defined prior to adding any tables.
-    pub fn add_table(&mut self, table: Table) -> Result<TableIndex, ModelError> {
-        let tables = self.module.tables.get_or_insert_with(Vec::new);
-        let imports = match &self.module.imports {
-            Some(imports) => imports
-                .iter()
-                .filter(|import| matches!(import.description(), ImportDescription::Table(_)))
-                .count(),
-            None => 0,
-        };
-        let index = u32::try_from(tables.len() + imports)?;
+    /// **Note:** Adding a table import afterwards shifts the index of every defined table by one.
+    /// The builder updates the references within the module, but not the indices already returned.
+    ///
+    /// In strict mode, the type referenced by the table's reference type must be defined,
+    /// and so must the components referenced by its initializer.
+    pub fn add_table(&mut self, mut table: Table) -> Result<TableIndex, ModelError> {
+        self.check_heap_type(Some(table.kind().kind().heap_type()))?;
+        self.check_references(None, |visitor| {
+            visitor.visit_table_definition(0, &mut table)
+        })?;
 
-        tables.push(table);
+        let index = u32::try_from(self.module.table_index_space().len())?;
+
+        self.module.tables.get_or_insert_with(Vec::new).push(table);
 
         Ok(index)
     }
@@ -103,20 +252,37 @@ impl ModuleBuilder {
     /// Adds the memory to the module's segment.
     /// Returns the index of the memory in the module.
     ///
-    /// **Note:** In order for the returned index to be accurate,
-    /// all memory imports must be defined prior to adding any memories.
+    /// **Note:** Adding a memory import afterwards shifts the index of every defined memory by one.
+    /// The builder updates the references within the module, but not the indices already returned.
     pub fn add_memory(&mut self, memory: Memory) -> Result<MemoryIndex, ModelError> {
-        let memories = self.module.memories.get_or_insert_with(Vec::new);
-        let imports = match &self.module.imports {
-            Some(imports) => imports
-                .iter()
-                .filter(|import| matches!(import.description(), ImportDescription::Memory(_)))
-                .count(),
-            None => 0,
-        };
-        let index = u32::try_from(memories.len() + imports)?;
+        let index = u32::try_from(self.module.memory_index_space().len())?;
+
+        self.module
+            .memories
+            .get_or_insert_with(Vec::new)
+            .push(memory);
 
-        memories.push(memory);
+        Ok(index)
+    }
+
+    /// Sets the tags segment for the WebAssembly module to be built.
+    pub fn set_tags(&mut self, tags: Option<Vec<Tag>>) {
+        self.module.tags = tags;
+    }
+
+    /// Adds the tag to the module's segment.
+    /// Returns the index of the tag in the module.
+    ///
+    /// **Note:** Adding a tag import afterwards shifts the index of every defined tag by one.
+    /// The builder updates the references within the module, but not the indices already returned.
+    ///
+    /// In strict mode, the type of the tag must be defined.
+    pub fn add_tag(&mut self, tag: Tag) -> Result<TagIndex, ModelError> {
+        self.check_type(tag.kind())?;
+
+        let index = u32::try_from(self.module.tag_index_space().len())?;
+
+        self.module.tags.get_or_insert_with(Vec::new).push(tag);
 
         Ok(index)
     }
@@ -129,20 +295,21 @@ impl ModuleBuilder {
     /// Adds the global to the module's segment.
     /// Returns the index of the global in the module.
     ///
-    /// **Note:** In order for the returned index to be accurate,
-    /// all global imports must be defined prior to adding any globals.
-    pub fn add_global(&mut self, global: Global) -> Result<GlobalIndex, ModelError> {
-        let globals = self.module.globals.get_or_insert_with(Vec::new);
-        let imports = match &self.module.imports {
-            Some(imports) => imports
-                .iter()
-                .filter(|import| matches!(import.description(), ImportDescription::Global(_)))
-                .count(),
-            None => 0,
-        };
-        let index = u32::try_from(globals.len() + imports)?;
+    /// **Note:** Adding a global import afterwards shifts the index of every defined global by one.
+    /// The builder updates the references within the module, but not the indices already returned.
+    ///
+    /// In strict mode, the type referenced by the global's value type must be defined,
+    /// and so must the components referenced by its initializer.
+    pub fn add_global(&mut self, mut global: Global) -> Result<GlobalIndex, ModelError> {
+        self.check_heap_type(global.kind().kind().heap_type())?;
+        self.check_references(None, |visitor| visitor.visit_global(0, &mut global))?;
 
-        globals.push(global);
+        let index = u32::try_from(self.module.global_index_space().len())?;
+
+        self.module
+            .globals
+            .get_or_insert_with(Vec::new)
+            .push(global);
 
         Ok(index)
     }
@@ -154,7 +321,14 @@ impl ModuleBuilder {
 
     /// Adds the element to the module's segment.
     /// Returns the index of the element in the module.
-    pub fn add_element(&mut self, element: Element) -> Result<ElementIndex, ModelError> {
+    ///
+    /// In strict mode, the table of an active element segment must be defined,
+    /// and so must the type referenced by the segment's reference type
+    /// and the components referenced by its offset and initializers.
+    pub fn add_element(&mut self, mut element: Element) -> Result<ElementIndex, ModelError> {
+        self.check_heap_type(Some(element.kind().heap_type()))?;
+        self.check_references(None, |visitor| visitor.visit_element(0, &mut element))?;
+
         let elements = self.module.elements.get_or_insert_with(Vec::new);
         let index = u32::try_from(elements.len())?;
 
@@ -170,7 +344,12 @@ impl ModuleBuilder {
 
     /// Adds the data to the module's segment.
     /// Returns the index of the data in the module.
-    pub fn add_data(&mut self, datum: Data) -> Result<DataIndex, ModelError> {
+    ///
+    /// In strict mode, the memory of an active data segment must be defined,
+    /// and so must the components referenced by its offset.
+    pub fn add_data(&mut self, mut datum: Data) -> Result<DataIndex, ModelError> {
+        self.check_references(None, |visitor| visitor.visit_data(0, &mut datum))?;
+
         let data = self.module.data.get_or_insert_with(Vec::new);
         let index = u32::try_from(data.len())?;
 
@@ -184,24 +363,88 @@ impl ModuleBuilder {
         self.module.start = start;
     }
 
+    /// Sets the start segment for the WebAssembly module to be built.
+    ///
+    /// In strict mode, the start function must be defined.
+    pub fn try_set_start(&mut self, start: Option<Start>) -> Result<(), ModelError> {
+        if let Some(start) = &start {
+            self.check_reference(ExportDescription::Function(start.function()))?;
+        }
+
+        self.set_start(start);
+
+        Ok(())
+    }
+
     /// Sets the imports segment for the WebAssembly module to be built.
     pub fn set_imports(&mut self, imports: Option<Vec<Import>>) {
         self.module.imports = imports;
     }
 
     /// Adds the import to the module's segment.
-    /// Returns the index of the import in the module (i.e function, table, memory, or global index).
+    /// Returns the index of the import in the module (i.e function, table, memory, global or tag index).
+    ///
+    /// Imports precede definitions in their index space, so adding an import after definitions
+    /// of the same kind shifts the index of every definition by one. Every reference to the shifted
+    /// definitions within the module (including the name section) is updated accordingly.
+    ///
+    /// In strict mode, the type of a function or tag import must be defined,
+    /// and so must the type referenced by the reference type of a table or global import.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{ControlInstruction, Export, Function, FunctionType, Import, Module, ResultType};
+    ///
+    /// let mut builder = Module::builder();
+    /// let kind = builder.add_function_type(FunctionType::runnable()).unwrap();
+    /// let main = builder
+    ///     .add_function(Function::new(kind, ResultType::empty(), vec![ControlInstruction::Call(0).into()].into()))
+    ///     .unwrap();
+    ///
+    /// builder.add_export(Export::function("main".into(), main));
+    ///
+    /// let log = builder.add_import(Import::function("env".into(), "log".into(), kind)).unwrap();
+    ///
+    /// assert_eq!((main, log), (0, 0));
+    /// assert_eq!(builder.function_index_space().imports(), 1);
+    /// assert_eq!(builder.function_index_space().definitions(), 1);
+    /// assert_eq!(builder.exports().unwrap()[0], Export::function("main".into(), 1));
+    /// assert_eq!(
+    ///     builder.functions().unwrap()[0].body(),
+    ///     &vec![ControlInstruction::Call(1).into()].into()
+    /// );
+    /// ```
     pub fn add_import(&mut self, import: Import) -> Result<u32, ModelError> {
-        let import_discriminant = discriminant(import.description());
-        let imports = self.module.imports.get_or_insert_with(Vec::new);
-        let index = u32::try_from(
-            imports
-                .iter()
-                .filter(|i| discriminant(i.description()) == import_discriminant)
-                .count(),
-        )?;
+        match import.description() {
+            ImportDescription::Function(kind) | ImportDescription::Tag(kind) => {
+                self.check_type(*kind)?
+            }
+            ImportDescription::Table(kind) => {
+                self.check_heap_type(Some(kind.kind().heap_type()))?
+            }
+            ImportDescription::Global(kind) => self.check_heap_type(kind.kind().heap_type())?,
+            ImportDescription::Memory(_) => {}
+        }
 
-        imports.push(import);
+        let space = Space::of_import(import.description());
+        let index_space = self.module.index_space(space);
+        let index = u32::try_from(index_space.imports())?;
+
+        u32::try_from(index_space.len())?;
+
+        if index_space.definitions() > 0 {
+            self.module.update_references(space, &mut |reference| {
+                if *reference >= index {
+                    *reference += 1;
+                }
+            })?;
+            self.module.insert_names(space, index);
+        }
+
+        self.module
+            .imports
+            .get_or_insert_with(Vec::new)
+            .push(import);
 
         Ok(index)
     }
@@ -212,12 +455,167 @@ impl ModuleBuilder {
     }
 
     /// Adds the export to the module's segment.
-    /// Returns the index of the export in the module.
     pub fn add_export(&mut self, export: Export) {
         let exports = self.module.exports.get_or_insert_with(Vec::new);
         exports.push(export);
     }
 
+    /// Adds the export to the module's segment.
+    ///
+    /// In strict mode, the exported component must be defined
+    /// and no other export may have the same name.
+    pub fn try_add_export(&mut self, export: Export) -> Result<(), ModelError> {
+        self.check_reference(*export.description())?;
+
+        if self.strict
+            && self
+                .module
+                .exports
+                .iter()
+                .flatten()
+                .any(|existing| existing.name() == export.name())
+        {
+            return Err(ModelError::DuplicateExport(export.name().value().clone()));
+        }
+
+        self.add_export(export);
+
+        Ok(())
+    }
+
+    /// Removes the export with the given name from the module's segment.
+    /// Returns the removed export, if any.
+    pub fn remove_export(&mut self, name: &str) -> Option<Export> {
+        let exports = self.module.exports.as_mut()?;
+        let position = exports
+            .iter()
+            .position(|export| export.name().value() == name)?;
+        let export = exports.remove(position);
+
+        if exports.is_empty() {
+            self.module.exports = None;
+        }
+
+        Some(export)
+    }
+
+    /// Removes the function with the given index from the module's segment.
+    /// The indices of the functions defined after the removed one are shifted down by one,
+    /// and every reference to them (i.e. calls, function references, exports, the start function and
+    /// the function and local names of the name section) is updated accordingly.
+    ///
+    /// Only functions defined by the module can be removed, and only once nothing references them.
+    /// Returns the removed function.
+    pub fn remove_function(&mut self, index: FunctionIndex) -> Result<Function, ModelError> {
+        let imports = self.module.function_index_space().imports();
+        let position = (index as usize)
+            .checked_sub(imports)
+            .filter(|position| *position < self.module.functions.as_ref().map_or(0, Vec::len))
+            .ok_or(ModelError::UndefinedFunction(index))?;
+
+        let mut referenced = false;
+
+        self.module
+            .update_references(Space::Function, &mut |function| {
+                referenced |= *function == index
+            })?;
+
+        if referenced {
+            return Err(ModelError::ReferencedFunction(index));
+        }
+
+        let functions = self.module.functions.get_or_insert_with(Vec::new);
+        let function = functions.remove(position);
+
+        if functions.is_empty() {
+            self.module.functions = None;
+        }
+
+        self.module
+            .update_references(Space::Function, &mut |function| {
+                if *function > index {
+                    *function -= 1;
+                }
+            })?;
+        self.module.remove_function_names(index);
+
+        Ok(function)
+    }
+
+    /// In strict mode, checks that the module defines the type with the given index as a function type.
+    fn check_type(&self, index: TypeIndex) -> Result<(), ModelError> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        match self.module.sub_type(index) {
+            None => Err(ModelError::UnknownType(index)),
+            Some(kind) if kind.function_type().is_none() => {
+                Err(ModelError::ExpectedFunctionType(index))
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    /// In strict mode, checks that the module defines the type referenced by a typed reference.
+    fn check_heap_type(&self, kind: Option<HeapType>) -> Result<(), ModelError> {
+        match kind {
+            Some(HeapType::Index(index))
+                if self.strict && self.module.sub_type(index).is_none() =>
+            {
+                Err(ModelError::UnknownType(index))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// In strict mode, checks that the module defines every function, table, memory, global and tag
+    /// referenced by the component that the given function visits.
+    /// The component being added to the given index space, if any, may reference itself.
+    fn check_references(
+        &self,
+        adding: Option<Space>,
+        mut visit: impl FnMut(&mut dyn VisitMut),
+    ) -> Result<(), ModelError> {
+        if !self.strict {
+            return Ok(());
+        }
+
+        for space in Space::ALL {
+            let index_space = self.module.index_space(space);
+            let length = index_space.len() + usize::from(adding == Some(space));
+            let mut unknown = None;
+            let mut check = |index: &mut u32| {
+                if unknown.is_none() && *index as usize >= length {
+                    unknown = Some(*index);
+                }
+            };
+
+            visit(&mut IndexReferences {
+                space,
+                update: &mut check,
+            });
+
+            if let Some(index) = unknown {
+                return Err(space.unknown(index));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// In strict mode, checks that the module defines the referenced component,
+    /// either as an import or in its own segment.
+    fn check_reference(&self, reference: ExportDescription) -> Result<(), ModelError> {
+        let (space, index) = Space::of_export(&reference);
+
+        if self.strict && !self.module.index_space(space).contains(index) {
+            return Err(space.unknown(index));
+        }
+
+        Ok(())
+    }
+
     /// Sets the custom section at the given insertion point for the WebAssembly module to be built.
     /// WebAssembly binary format allows custom sections to be at the start of a module, or after any other section.
     pub fn set_custom_sections(
@@ -249,8 +647,8 @@ impl ModuleBuilder {
     }
 
     /// The 𝗍𝗒𝗉𝖾𝗌 component of the module to be built.
-    pub fn function_types(&self) -> Option<&[FunctionType]> {
-        self.module.function_types()
+    pub fn types(&self) -> Option<&[RecursiveType]> {
+        self.module.types()
     }
 
     /// The 𝖿𝗎𝗇𝖼𝗌 component of the module to be built.
@@ -268,6 +666,11 @@ impl ModuleBuilder {
         self.module.memories()
     }
 
+    /// The 𝗍𝖺𝗀𝗌 component of the module to be built.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.module.tags()
+    }
+
     /// The 𝗀𝗅𝗈𝖻𝖺𝗅𝗌 component of the module to be built.
     pub fn globals(&self) -> Option<&[Global]> {
         self.module.globals()
@@ -298,11 +701,91 @@ impl ModuleBuilder {
         self.module.exports()
     }
 
+    /// Mutable reference to the 𝗍𝗒𝗉𝖾𝗌 component of the module to be built.
+    pub fn types_mut(&mut self) -> Option<&mut [RecursiveType]> {
+        self.module.types_mut()
+    }
+
+    /// Mutable reference to the 𝖿𝗎𝗇𝖼𝗌 component of the module to be built.
+    pub fn functions_mut(&mut self) -> Option<&mut [Function]> {
+        self.module.functions_mut()
+    }
+
+    /// Mutable reference to the 𝗍𝖺𝖻𝗅𝖾𝗌 component of the module to be built.
+    pub fn tables_mut(&mut self) -> Option<&mut [Table]> {
+        self.module.tables_mut()
+    }
+
+    /// Mutable reference to the 𝗆𝖾𝗆𝗌 component of the module to be built.
+    pub fn memories_mut(&mut self) -> Option<&mut [Memory]> {
+        self.module.memories_mut()
+    }
+
+    /// Mutable reference to the 𝗍𝖺𝗀𝗌 component of the module to be built.
+    pub fn tags_mut(&mut self) -> Option<&mut [Tag]> {
+        self.module.tags_mut()
+    }
+
+    /// Mutable reference to the 𝗀𝗅𝗈𝖻𝖺𝗅𝗌 component of the module to be built.
+    pub fn globals_mut(&mut self) -> Option<&mut [Global]> {
+        self.module.globals_mut()
+    }
+
+    /// Mutable reference to the 𝖾𝗅𝖾𝗆𝗌 component of the module to be built.
+    pub fn elements_mut(&mut self) -> Option<&mut [Element]> {
+        self.module.elements_mut()
+    }
+
+    /// Mutable reference to the 𝖽𝖺𝗍𝖺𝗌 component of the module to be built.
+    pub fn data_mut(&mut self) -> Option<&mut [Data]> {
+        self.module.data_mut()
+    }
+
+    /// Mutable reference to the 𝗌𝗍𝖺𝗋𝗍 component of the module to be built.
+    pub fn start_mut(&mut self) -> Option<&mut Start> {
+        self.module.start_mut()
+    }
+
+    /// Mutable reference to the 𝗂𝗆𝗉𝗈𝗋𝗍𝗌 component of the module to be built.
+    pub fn imports_mut(&mut self) -> Option<&mut [Import]> {
+        self.module.imports_mut()
+    }
+
+    /// Mutable reference to the 𝖾𝗑𝗉𝗈𝗋𝗍𝗌 component of the module to be built.
+    pub fn exports_mut(&mut self) -> Option<&mut [Export]> {
+        self.module.exports_mut()
+    }
+
     /// The custom sections of the module to be built.
     pub fn custom_sections_at(&self, insertion_point: ModuleSection) -> Option<&[Custom]> {
         self.module.custom_sections_at(insertion_point)
     }
 
+    /// The function index space of the module to be built.
+    pub fn function_index_space(&self) -> IndexSpace {
+        self.module.function_index_space()
+    }
+
+    /// The table index space of the module to be built.
+    pub fn table_index_space(&self) -> IndexSpace {
+        self.module.table_index_space()
+    }
+
+    /// The memory index space of the module to be built.
+    pub fn memory_index_space(&self) -> IndexSpace {
+        self.module.memory_index_space()
+    }
+
+    /// The global index space of the module to be built.
+    pub fn global_index_space(&self) -> IndexSpace {
+        self.module.global_index_space()
+    }
+
+    /// The tag index space of the module to be built.
+    pub fn tag_index_space(&self) -> IndexSpace {
+        self.module.tag_index_space()
+    }
+
     /// Builds the current segments into a module.
     pub fn build(self) -> Module {
         self.into()
@@ -315,14 +798,21 @@ impl From<ModuleBuilder> for Module {
     }
 }
 
-impl Default for ModuleBuilder {
-    fn default() -> Self {
+impl From<Module> for ModuleBuilder {
+    fn from(module: Module) -> Self {
         ModuleBuilder {
-            module: Module::empty(),
+            module,
+            strict: false,
         }
     }
 }
 
+impl Default for ModuleBuilder {
+    fn default() -> Self {
+        ModuleBuilder::new()
+    }
+}
+
 /// WebAssembly programs are organized into modules, which are the unit of deployment, loading, and compilation.
 /// A module collects definitions for types, functions, tables, memories, and globals.
 /// In addition,
@@ -343,6 +833,7 @@ impl Default for ModuleBuilder {
 /// assert_eq!(module.functions(), None);
 /// assert_eq!(module.tables(), None);
 /// assert_eq!(module.memories(), None);
+/// assert_eq!(module.tags(), None);
 /// assert_eq!(module.globals(), None);
 /// assert_eq!(module.elements(), None);
 /// assert_eq!(module.data(), None);
@@ -363,6 +854,7 @@ impl Default for ModuleBuilder {
 /// assert_eq!(module.functions(), None);
 /// assert_eq!(module.tables(), None);
 /// assert_eq!(module.memories(), None);
+/// assert_eq!(module.tags(), None);
 /// assert_eq!(module.globals(), None);
 /// assert_eq!(module.elements(), None);
 /// assert_eq!(module.data(), None);
@@ -371,12 +863,39 @@ impl Default for ModuleBuilder {
 /// assert_eq!(module.exports(), None);
 /// assert_eq!(module.data_count(), None);
 /// ```
+///
+/// ## Mutation
+/// ```rust
+/// use wasm_ast::{Module, FunctionType, Function, ResultType, ControlInstruction, Export};
+///
+/// let mut builder = Module::builder();
+/// builder.add_function_type(FunctionType::runnable()).unwrap();
+/// builder.add_function(Function::new(0, ResultType::empty(), vec![].into())).unwrap();
+/// builder.add_function(Function::new(0, ResultType::empty(), vec![ControlInstruction::Call(0).into()].into())).unwrap();
+/// builder.add_export(Export::function("main".into(), 1));
+///
+/// let mut module = builder.build();
+///
+/// module.functions_mut().unwrap()[0].set_body(vec![ControlInstruction::Nop.into()].into());
+///
+/// let mut builder = module.into_builder();
+///
+/// assert!(builder.remove_function(0).is_err());
+///
+/// builder.functions_mut().unwrap()[1].set_body(vec![].into());
+///
+/// assert!(builder.remove_function(0).is_ok());
+/// assert_eq!(builder.exports().unwrap()[0], Export::function("main".into(), 0));
+/// assert_eq!(builder.remove_export("main"), Some(Export::function("main".into(), 0)));
+/// assert_eq!(builder.build().exports(), None);
+/// ```
 #[derive(Clone, Debug, PartialEq)]
 pub struct Module {
-    function_types: Option<Vec<FunctionType>>,
+    types: Option<Vec<RecursiveType>>,
     functions: Option<Vec<Function>>,
     tables: Option<Vec<Table>>,
     memories: Option<Vec<Memory>>,
+    tags: Option<Vec<Tag>>,
     globals: Option<Vec<Global>>,
     elements: Option<Vec<Element>>,
     data: Option<Vec<Data>>,
@@ -393,13 +912,19 @@ impl Module {
         ModuleBuilder::new()
     }
 
+    /// Converts the module back into a builder, in order to add, replace or remove its components.
+    pub fn into_builder(self) -> ModuleBuilder {
+        self.into()
+    }
+
     /// Creates a new empty `Module`.
     pub fn empty() -> Self {
         Module {
-            function_types: None,
+            types: None,
             functions: None,
             tables: None,
             memories: None,
+            tags: None,
             globals: None,
             elements: None,
             data: None,
@@ -411,9 +936,28 @@ impl Module {
         }
     }
 
-    /// The 𝗍𝗒𝗉𝖾𝗌 component of a module defines a vector of function types.
-    pub fn function_types(&self) -> Option<&[FunctionType]> {
-        self.function_types.as_deref()
+    /// The 𝗍𝗒𝗉𝖾𝗌 component of a module defines a vector of recursive types.
+    pub fn types(&self) -> Option<&[RecursiveType]> {
+        self.types.as_deref()
+    }
+
+    /// The sub types of the recursive types of the module, in the order of the type index space.
+    pub fn sub_types(&self) -> impl Iterator<Item = &SubType> {
+        self.types
+            .iter()
+            .flatten()
+            .flat_map(|group| group.types().iter())
+    }
+
+    /// The sub type with the given index in the type index space of the module.
+    pub fn sub_type(&self, index: TypeIndex) -> Option<&SubType> {
+        self.sub_types().nth(index as usize)
+    }
+
+    /// The function type with the given index, unless the index is out of bounds or refers to a
+    /// structure or array type.
+    pub fn function_type(&self, index: TypeIndex) -> Option<&FunctionType> {
+        self.sub_type(index)?.function_type()
     }
 
     /// The 𝖿𝗎𝗇𝖼𝗌 component of a module defines a vector of functions.
@@ -432,6 +976,11 @@ impl Module {
         self.memories.as_deref()
     }
 
+    /// The 𝗍𝖺𝗀𝗌 component of a module defines a vector of exception tags.
+    pub fn tags(&self) -> Option<&[Tag]> {
+        self.tags.as_deref()
+    }
+
     /// The 𝗀𝗅𝗈𝖻𝖺𝗅𝗌 component of a module defines a vector of global variables (or globals for short).
     pub fn globals(&self) -> Option<&[Global]> {
         self.globals.as_deref()
@@ -464,15 +1013,959 @@ impl Module {
         self.exports.as_deref()
     }
 
+    /// Mutable reference to the 𝗍𝗒𝗉𝖾𝗌 component of a module.
+    pub fn types_mut(&mut self) -> Option<&mut [RecursiveType]> {
+        self.types.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝖿𝗎𝗇𝖼𝗌 component of a module.
+    pub fn functions_mut(&mut self) -> Option<&mut [Function]> {
+        self.functions.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝗍𝖺𝖻𝗅𝖾𝗌 component of a module.
+    pub fn tables_mut(&mut self) -> Option<&mut [Table]> {
+        self.tables.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝗆𝖾𝗆𝗌 component of a module.
+    pub fn memories_mut(&mut self) -> Option<&mut [Memory]> {
+        self.memories.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝗍𝖺𝗀𝗌 component of a module.
+    pub fn tags_mut(&mut self) -> Option<&mut [Tag]> {
+        self.tags.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝗀𝗅𝗈𝖻𝖺𝗅𝗌 component of a module.
+    pub fn globals_mut(&mut self) -> Option<&mut [Global]> {
+        self.globals.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝖾𝗅𝖾𝗆𝗌 component of a module.
+    pub fn elements_mut(&mut self) -> Option<&mut [Element]> {
+        self.elements.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝖽𝖺𝗍𝖺𝗌 component of a module.
+    pub fn data_mut(&mut self) -> Option<&mut [Data]> {
+        self.data.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝗌𝗍𝖺𝗋𝗍 component of a module.
+    pub fn start_mut(&mut self) -> Option<&mut Start> {
+        self.start.as_mut()
+    }
+
+    /// Mutable reference to the 𝗂𝗆𝗉𝗈𝗋𝗍𝗌 component of a module.
+    pub fn imports_mut(&mut self) -> Option<&mut [Import]> {
+        self.imports.as_deref_mut()
+    }
+
+    /// Mutable reference to the 𝖾𝗑𝗉𝗈𝗋𝗍𝗌 component of a module.
+    pub fn exports_mut(&mut self) -> Option<&mut [Export]> {
+        self.exports.as_deref_mut()
+    }
+
     /// The custom sections of a module for a given insertion point.
     /// Custom sections are allowed at the beginning of a module and after every other section.
     pub fn custom_sections_at(&self, insertion_point: ModuleSection) -> Option<&[Custom]> {
         self.custom_sections.custom_sections_at(insertion_point)
     }
 
-    /// Whether the module includes the data count section or not.
-    pub fn data_count(&self) -> Option<u32> {
-        self.data_count
+    /// Decodes the name section of the module, if the module has a custom section named ‘𝚗𝚊𝚖𝚎’.
+    ///
+    /// See <https://webassembly.github.io/spec/core/appendix/custom.html#name-section>
+    pub fn name_section(&self) -> Result<Option<NameSection>, ModelError> {
+        self.custom_sections
+            .find(NameSection::NAME)
+            .map(NameSection::try_from)
+            .transpose()
+    }
+
+    /// The debug name of the function with the given index, as recorded in the name section.
+    /// Names are only informational, so a malformed name section is treated as if it were absent.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{Custom, Module, ModuleSection, Name, NameSection};
+    ///
+    /// let mut names = NameSection::new();
+    /// names.function_names_mut().push((1, "main".into()));
+    ///
+    /// let mut builder = Module::builder();
+    /// builder.add_custom_section(ModuleSection::Data, Custom::from(&names));
+    ///
+    /// let module = builder.build();
+    ///
+    /// assert_eq!(module.name_section().unwrap(), Some(names));
+    /// assert_eq!(module.function_name(1), Some(Name::from("main")));
+    /// assert_eq!(module.function_name(0), None);
+    /// assert_eq!(Module::empty().function_name(1), None);
+    /// ```
+    pub fn function_name(&self, function: FunctionIndex) -> Option<Name> {
+        let names = self.name_section().ok()??;
+
+        names.function_name(function).cloned()
+    }
+
+    /// Whether the module includes the data count section or not.
+    pub fn data_count(&self) -> Option<u32> {
+        self.data_count
+    }
+
+    /// The function index space of the module, made of the imported functions followed by the defined ones.
+    pub fn function_index_space(&self) -> IndexSpace {
+        self.index_space(Space::Function)
+    }
+
+    /// The table index space of the module, made of the imported tables followed by the defined ones.
+    pub fn table_index_space(&self) -> IndexSpace {
+        self.index_space(Space::Table)
+    }
+
+    /// The memory index space of the module, made of the imported memories followed by the defined ones.
+    pub fn memory_index_space(&self) -> IndexSpace {
+        self.index_space(Space::Memory)
+    }
+
+    /// The global index space of the module, made of the imported globals followed by the defined ones.
+    pub fn global_index_space(&self) -> IndexSpace {
+        self.index_space(Space::Global)
+    }
+
+    /// The tag index space of the module, made of the imported tags followed by the defined ones.
+    pub fn tag_index_space(&self) -> IndexSpace {
+        self.index_space(Space::Tag)
+    }
+
+    /// Maps each type index to the index of the first type that is equivalent to it under iso-recursive canonicalization.
+    /// Recursive groups are equivalent when they are equal once their references to preceding types are canonicalized
+    /// and their references to their own types are made relative to the group.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/conventions.html#rolling-and-unrolling>
+    #[cfg(feature = "validator")]
+    pub(crate) fn canonical_types(&self) -> Vec<TypeIndex> {
+        let mut canonical = Vec::new();
+        let mut unique: Vec<(usize, RecursiveType)> = Vec::new();
+
+        for group in self.types().unwrap_or_default() {
+            let existing = unique.iter().find(|(start, candidate)| {
+                candidate.len() == group.len() && *candidate == relocate(group, &canonical, *start)
+            });
+            let start = match existing {
+                Some((start, _)) => *start,
+                None => {
+                    let start = canonical.len();
+
+                    unique.push((start, relocate(group, &canonical, start)));
+                    start
+                }
+            };
+
+            canonical.extend((start..start + group.len()).map(|index| index as TypeIndex));
+        }
+
+        canonical
+    }
+
+    /// Removes duplicate recursive groups from the type section, keeping the first of each equal group.
+    /// Function types added on their own form a group each, so equal function types are merged.
+    /// Groups are compared after remapping their references to the types kept so far,
+    /// so groups that only become equal once the types they refer to are merged are merged as well.
+    /// Every reference to a type is updated to the index of the kept type,
+    /// including functions, function and tag imports, tags, block types, indirect calls,
+    /// supertypes, reference types and the immediates of GC instructions.
+    /// Type and parameter names of the name section are updated accordingly,
+    /// dropping the names of removed types. Malformed name sections are left untouched.
+    ///
+    /// Returns the number of types removed.
+    /// Fails without changing the module if a lazily parsed function body is not a valid expression.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{
+    ///     BlockType, ControlInstruction, Function, FunctionType, Import, Module, RecursiveType,
+    ///     ResultType, ValueType,
+    /// };
+    ///
+    /// let unary = FunctionType::side_effect(ResultType::new(vec![ValueType::I32]));
+    /// let mut builder = Module::builder();
+    ///
+    /// builder.add_function_type(FunctionType::runnable()).unwrap();
+    /// builder.add_function_type(unary.clone()).unwrap();
+    /// builder.add_function_type(FunctionType::runnable()).unwrap();
+    /// builder.add_function_type(unary.clone()).unwrap();
+    /// builder.add_import(Import::function("env".into(), "log".into(), 3)).unwrap();
+    /// builder
+    ///     .add_function(Function::new(
+    ///         2,
+    ///         ResultType::empty(),
+    ///         vec![ControlInstruction::Block(BlockType::Index(3), vec![].into()).into()].into(),
+    ///     ))
+    ///     .unwrap();
+    ///
+    /// let mut module = builder.build();
+    ///
+    /// assert_eq!(module.deduplicate_types().unwrap(), 2);
+    /// assert_eq!(
+    ///     module.types(),
+    ///     Some(&[RecursiveType::from(FunctionType::runnable()), unary.into()][..])
+    /// );
+    /// assert_eq!(module.imports().unwrap()[0], Import::function("env".into(), "log".into(), 1));
+    /// assert_eq!(
+    ///     module.functions().unwrap()[0],
+    ///     Function::new(
+    ///         0,
+    ///         ResultType::empty(),
+    ///         vec![ControlInstruction::Block(BlockType::Index(1), vec![].into()).into()].into(),
+    ///     )
+    /// );
+    /// ```
+    pub fn deduplicate_types(&mut self) -> Result<usize, ModelError> {
+        self.decode_bodies()?;
+
+        let types = match self.types.take() {
+            Some(types) => types,
+            None => return Ok(0),
+        };
+        let original = types.iter().map(RecursiveType::len).sum::<usize>();
+        let mut unique: Vec<RecursiveType> = Vec::with_capacity(types.len());
+        let mut starts = Vec::with_capacity(types.len());
+        let mut kept = Vec::with_capacity(original);
+        let mut remap = Vec::with_capacity(original);
+        let mut length = 0;
+
+        for group in types {
+            let existing = unique.iter().zip(&starts).position(|(candidate, start)| {
+                candidate.len() == group.len() && *candidate == relocate(&group, &remap, *start)
+            });
+            let start = existing.map_or(length, |index| starts[index]);
+
+            kept.resize(kept.len() + group.len(), existing.is_none());
+
+            if existing.is_none() {
+                unique.push(relocate(&group, &remap, length));
+                starts.push(length);
+                length += group.len();
+            }
+
+            remap.extend((start..start + group.len()).map(|index| index as TypeIndex));
+        }
+
+        let removed = original - length;
+
+        if removed > 0 {
+            TypeReferences(&remap).visit_module(self);
+            self.remap_type_names(&kept, &remap);
+        }
+
+        self.types = Some(unique);
+
+        Ok(removed)
+    }
+
+    /// Evaluates the offsets of the active data segments given the values of the imported globals,
+    /// and returns the range of memory each segment initializes, in the order of the data section.
+    /// Passive segments are skipped.
+    ///
+    /// See <https://webassembly.github.io/spec/core/exec/modules.html#instantiation>
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{
+    ///     Data, GlobalType, Import, Limit, Memory, Module, SegmentLayout, Value, ValueType,
+    ///     VariableInstruction,
+    /// };
+    ///
+    /// let mut builder = Module::builder();
+    ///
+    /// builder
+    ///     .add_import(Import::global(
+    ///         "env".into(),
+    ///         "heap".into(),
+    ///         GlobalType::immutable(ValueType::I32),
+    ///     ))
+    ///     .unwrap();
+    /// builder.add_memory(Memory::from(Limit::unbounded(1))).unwrap();
+    /// builder.add_data(Data::active(0, vec![16i32.into()].into(), vec![1, 2, 3, 4])).unwrap();
+    /// builder.add_data(Data::passive(vec![5])).unwrap();
+    /// builder
+    ///     .add_data(Data::active(
+    ///         0,
+    ///         vec![VariableInstruction::GlobalGet(0).into()].into(),
+    ///         vec![6, 7],
+    ///     ))
+    ///     .unwrap();
+    ///
+    /// let module = builder.build();
+    /// let layout = module.data_layout(&[Value::I32(18)]).unwrap();
+    ///
+    /// assert_eq!(layout, vec![SegmentLayout::new(0, 0, 16, 4), SegmentLayout::new(2, 0, 18, 2)]);
+    /// assert_eq!(layout[0].end(), 20);
+    /// assert!(layout[0].overlaps(&layout[1]));
+    /// assert_eq!(SegmentLayout::overlapping(&layout), vec![(0, 2)]);
+    /// assert!(module.data_layout(&[]).is_err());
+    /// ```
+    pub fn data_layout(&self, globals: &[Value]) -> Result<Vec<SegmentLayout>, ModelError> {
+        let mut layouts = Vec::new();
+
+        for (index, data) in self.data.iter().flatten().enumerate() {
+            if let DataMode::Active(memory, offset) = data.mode() {
+                layouts.push(SegmentLayout::evaluate(
+                    index,
+                    *memory,
+                    offset,
+                    data.initializer().len(),
+                    globals,
+                )?);
+            }
+        }
+
+        Ok(layouts)
+    }
+
+    /// Evaluates the offsets of the active element segments given the values of the imported globals,
+    /// and returns the range of the table each segment initializes, in the order of the element section.
+    /// Passive and declarative segments are skipped.
+    ///
+    /// See <https://webassembly.github.io/spec/core/exec/modules.html#instantiation>
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{
+    ///     Element, ElementInitializer, Limit, Module, ReferenceType, SegmentLayout, Table,
+    ///     TableType,
+    /// };
+    ///
+    /// let mut builder = Module::builder();
+    ///
+    /// builder
+    ///     .add_table(Table::new(TableType::new(ReferenceType::Function, Limit::unbounded(8))))
+    ///     .unwrap();
+    /// builder
+    ///     .add_element(Element::active(
+    ///         0,
+    ///         vec![2i32.into()].into(),
+    ///         ReferenceType::Function,
+    ///         vec![0, 1, 2].to_initializers(),
+    ///     ))
+    ///     .unwrap();
+    ///
+    /// let module = builder.build();
+    ///
+    /// assert_eq!(module.element_layout(&[]).unwrap(), vec![SegmentLayout::new(0, 0, 2, 3)]);
+    /// ```
+    pub fn element_layout(&self, globals: &[Value]) -> Result<Vec<SegmentLayout>, ModelError> {
+        let mut layouts = Vec::new();
+
+        for (index, element) in self.elements.iter().flatten().enumerate() {
+            if let ElementMode::Active(table, offset) = element.mode() {
+                layouts.push(SegmentLayout::evaluate(
+                    index,
+                    *table,
+                    offset,
+                    element.initializers().len(),
+                    globals,
+                )?);
+            }
+        }
+
+        Ok(layouts)
+    }
+
+    /// The extent of the given index space.
+    fn index_space(&self, space: Space) -> IndexSpace {
+        let imports = self
+            .imports
+            .iter()
+            .flatten()
+            .filter(|import| Space::of_import(import.description()) == space)
+            .count();
+        let definitions = match space {
+            Space::Function => self.functions.as_ref().map_or(0, Vec::len),
+            Space::Table => self.tables.as_ref().map_or(0, Vec::len),
+            Space::Memory => self.memories.as_ref().map_or(0, Vec::len),
+            Space::Global => self.globals.as_ref().map_or(0, Vec::len),
+            Space::Tag => self.tags.as_ref().map_or(0, Vec::len),
+        };
+
+        IndexSpace::new(imports, definitions)
+    }
+
+    /// Calls `update` with every index of the given space the module refers to outside of its imports,
+    /// including the instructions of function bodies and constant expressions.
+    /// Fails without calling `update` if a lazily parsed function body is not a valid expression.
+    fn update_references(
+        &mut self,
+        space: Space,
+        update: &mut impl FnMut(&mut u32),
+    ) -> Result<(), ModelError> {
+        self.decode_bodies()?;
+
+        IndexReferences { space, update }.visit_module(self);
+
+        Ok(())
+    }
+
+    /// Checks that every function body is a valid expression,
+    /// so that visiting the bodies mutably cannot fail.
+    fn decode_bodies(&self) -> Result<(), ModelError> {
+        let imports = self.function_index_space().imports();
+
+        for (position, function) in self.functions.iter().flatten().enumerate() {
+            function_body(function, u32::try_from(imports + position)?)?;
+        }
+
+        Ok(())
+    }
+
+    /// Shifts the names of the given index space up by one, starting at the given index.
+    /// Malformed name sections are left untouched.
+    fn insert_names(&mut self, space: Space, index: u32) {
+        let custom = match self.custom_sections.find_mut(NameSection::NAME) {
+            Some(custom) => custom,
+            None => return,
+        };
+        let mut names = match NameSection::try_from(&*custom) {
+            Ok(names) => names,
+            Err(_) => return,
+        };
+
+        match space {
+            Space::Function => {
+                insert_index(names.function_names_mut(), index);
+                insert_index(names.local_names_mut(), index);
+                insert_index(names.label_names_mut(), index);
+            }
+            Space::Table => insert_index(names.table_names_mut(), index),
+            Space::Memory => insert_index(names.memory_names_mut(), index),
+            Space::Global => insert_index(names.global_names_mut(), index),
+            Space::Tag => {
+                insert_index(names.tag_names_mut(), index);
+                insert_index(names.tag_parameter_names_mut(), index);
+            }
+        }
+
+        custom.bytes = names.encode();
+    }
+
+    /// Removes the names of the given function from the name section,
+    /// shifting the names of the functions after it down by one.
+    /// Malformed name sections are left untouched.
+    fn remove_function_names(&mut self, index: FunctionIndex) {
+        let custom = match self.custom_sections.find_mut(NameSection::NAME) {
+            Some(custom) => custom,
+            None => return,
+        };
+        let mut names = match NameSection::try_from(&*custom) {
+            Ok(names) => names,
+            Err(_) => return,
+        };
+
+        remove_index(names.function_names_mut(), index);
+        remove_index(names.local_names_mut(), index);
+        remove_index(names.label_names_mut(), index);
+
+        custom.bytes = names.encode();
+    }
+
+    /// Drops the type, field and parameter names of the types that were not kept,
+    /// and moves the names of the kept types to their new index.
+    /// Malformed name sections are left untouched.
+    fn remap_type_names(&mut self, kept: &[bool], remap: &[TypeIndex]) {
+        let custom = match self.custom_sections.find_mut(NameSection::NAME) {
+            Some(custom) => custom,
+            None => return,
+        };
+        let mut names = match NameSection::try_from(&*custom) {
+            Ok(names) => names,
+            Err(_) => return,
+        };
+
+        remap_indices(names.type_names_mut(), kept, remap);
+        remap_indices(names.field_names_mut(), kept, remap);
+        remap_indices(names.parameter_names_mut(), kept, remap);
+
+        custom.bytes = names.encode();
+    }
+}
+
+/// The recursive type moved to the given start index, with its references to preceding types
+/// remapped to the types kept so far and its references to its own types moved along with it.
+fn relocate(group: &RecursiveType, remap: &[TypeIndex], start: usize) -> RecursiveType {
+    let mut moved = remap.to_vec();
+    let mut group = group.clone();
+
+    moved.extend((start..start + group.len()).map(|index| index as TypeIndex));
+
+    for kind in group.types_mut() {
+        TypeReferences(&moved).visit_sub_type(0, kind);
+    }
+
+    group
+}
+
+/// Maps every reference to a type index to the index it was moved to.
+struct TypeReferences<'remap>(&'remap [TypeIndex]);
+
+impl TypeReferences<'_> {
+    fn update(&self, index: &mut TypeIndex) {
+        if let Some(moved) = self.0.get(*index as usize) {
+            *index = *moved;
+        }
+    }
+
+    fn update_block(&self, kind: &mut BlockType) {
+        match kind {
+            BlockType::Index(index) => self.update(index),
+            BlockType::ValueType(kind) => self.update_value_type(kind),
+            BlockType::None => {}
+        }
+    }
+
+    fn update_heap_type(&self, kind: &mut HeapType) {
+        if let HeapType::Index(index) = kind {
+            self.update(index);
+        }
+    }
+
+    fn update_value_type(&self, kind: &mut ValueType) {
+        if let ValueType::Reference(heap) | ValueType::NullableReference(heap) = kind {
+            self.update_heap_type(heap);
+        }
+    }
+
+    fn update_reference_type(&self, kind: &mut ReferenceType) {
+        if let ReferenceType::Reference(heap) | ReferenceType::NullableReference(heap) = kind {
+            self.update_heap_type(heap);
+        }
+    }
+
+    fn update_table_type(&self, kind: &mut TableType) {
+        let mut reference_type = kind.kind();
+
+        self.update_reference_type(&mut reference_type);
+        *kind = TableType::with_index_type(kind.index_type(), reference_type, *kind.limits());
+    }
+
+    fn update_result_type(&self, kind: &mut ResultType) {
+        let mut kinds = kind.kinds().to_vec();
+
+        kinds
+            .iter_mut()
+            .for_each(|kind| self.update_value_type(kind));
+        *kind = ResultType::new(kinds);
+    }
+
+    fn update_global_type(&self, kind: &mut GlobalType) {
+        let mut value_type = kind.kind();
+
+        self.update_value_type(&mut value_type);
+        *kind = GlobalType::new(value_type, kind.mutability());
+    }
+
+    fn update_field_type(&self, kind: FieldType) -> FieldType {
+        match kind.kind() {
+            StorageType::Value(mut value_type) => {
+                self.update_value_type(&mut value_type);
+                FieldType::new(value_type, kind.mutability())
+            }
+            StorageType::Packed(_) => kind,
+        }
+    }
+
+    fn update_composite_type(&self, kind: &CompositeType) -> CompositeType {
+        match kind {
+            CompositeType::Function(kind) => {
+                let mut parameters = kind.parameters().clone();
+                let mut results = kind.results().clone();
+
+                self.update_result_type(&mut parameters);
+                self.update_result_type(&mut results);
+                FunctionType::new(parameters, results).into()
+            }
+            CompositeType::Struct(kind) => StructType::new(
+                kind.fields()
+                    .iter()
+                    .map(|field| self.update_field_type(*field))
+                    .collect(),
+            )
+            .into(),
+            CompositeType::Array(kind) => {
+                ArrayType::new(self.update_field_type(kind.field())).into()
+            }
+        }
+    }
+}
+
+impl VisitMut for TypeReferences<'_> {
+    fn visit_sub_type(&mut self, _index: TypeIndex, kind: &mut SubType) {
+        let mut supertypes = kind.supertypes().to_vec();
+
+        supertypes.iter_mut().for_each(|index| self.update(index));
+        *kind = SubType::new(
+            kind.is_final(),
+            supertypes,
+            self.update_composite_type(kind.kind()),
+        );
+    }
+
+    fn visit_import(&mut self, import: &mut Import) {
+        match &mut import.description {
+            ImportDescription::Function(kind) | ImportDescription::Tag(kind) => self.update(kind),
+            ImportDescription::Table(kind) => self.update_table_type(kind),
+            ImportDescription::Global(kind) => self.update_global_type(kind),
+            _ => {}
+        }
+    }
+
+    fn visit_function(&mut self, _index: FunctionIndex, function: &mut Function) {
+        self.update(&mut function.kind);
+        self.update_result_type(&mut function.locals);
+        walk_function_mut(self, function);
+    }
+
+    fn visit_table_definition(&mut self, _index: TableIndex, table: &mut Table) {
+        self.update_table_type(&mut table.kind);
+        walk_table_mut(self, table);
+    }
+
+    fn visit_global(&mut self, _index: GlobalIndex, global: &mut Global) {
+        self.update_global_type(&mut global.kind);
+        walk_global_mut(self, global);
+    }
+
+    fn visit_element(&mut self, _index: ElementIndex, element: &mut Element) {
+        self.update_reference_type(&mut element.kind);
+        walk_element_mut(self, element);
+    }
+
+    fn visit_parametric(&mut self, instruction: &mut ParametricInstruction) {
+        if let ParametricInstruction::Select(Some(kinds)) = instruction {
+            kinds
+                .iter_mut()
+                .for_each(|kind| self.update_value_type(kind));
+        }
+    }
+
+    fn visit_tag(&mut self, _index: TagIndex, tag: &mut Tag) {
+        self.update(&mut tag.kind);
+    }
+
+    fn visit_reference(&mut self, instruction: &mut ReferenceInstruction) {
+        if let ReferenceInstruction::Null(kind) = instruction {
+            self.update_heap_type(kind);
+        }
+    }
+
+    fn visit_gc(&mut self, instruction: &mut GcInstruction) {
+        match instruction {
+            GcInstruction::StructNew(kind)
+            | GcInstruction::StructNewDefault(kind)
+            | GcInstruction::StructGet(kind, _, _)
+            | GcInstruction::StructSet(kind, _)
+            | GcInstruction::ArrayNew(kind)
+            | GcInstruction::ArrayNewDefault(kind)
+            | GcInstruction::ArrayNewFixed(kind, _)
+            | GcInstruction::ArrayNewData(kind, _)
+            | GcInstruction::ArrayNewElement(kind, _)
+            | GcInstruction::ArrayGet(kind, _)
+            | GcInstruction::ArraySet(kind)
+            | GcInstruction::ArrayFill(kind)
+            | GcInstruction::ArrayInitData(kind, _)
+            | GcInstruction::ArrayInitElement(kind, _) => self.update(kind),
+            GcInstruction::ArrayCopy(target, source) => {
+                self.update(target);
+                self.update(source);
+            }
+            GcInstruction::Test(kind) | GcInstruction::Cast(kind) => self.update_value_type(kind),
+            GcInstruction::BranchOnCast(_, source, target)
+            | GcInstruction::BranchOnCastFail(_, source, target) => {
+                self.update_value_type(source);
+                self.update_value_type(target);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_control(&mut self, instruction: &mut ControlInstruction) {
+        match instruction {
+            ControlInstruction::Block(kind, _)
+            | ControlInstruction::Loop(kind, _)
+            | ControlInstruction::If(kind, _, _)
+            | ControlInstruction::TryTable(kind, _, _) => self.update_block(kind),
+            ControlInstruction::CallIndirect(kind, _)
+            | ControlInstruction::ReturnCallIndirect(kind, _)
+            | ControlInstruction::CallReference(kind)
+            | ControlInstruction::ReturnCallReference(kind) => self.update(kind),
+            _ => {}
+        }
+
+        walk_control_mut(self, instruction);
+    }
+}
+
+/// The index spaces that include imports.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Space {
+    Function,
+    Table,
+    Memory,
+    Global,
+    Tag,
+}
+
+impl Space {
+    /// Every index space that includes imports.
+    const ALL: [Space; 5] = [
+        Space::Function,
+        Space::Table,
+        Space::Memory,
+        Space::Global,
+        Space::Tag,
+    ];
+
+    /// The index space of an import.
+    fn of_import(description: &ImportDescription) -> Self {
+        match description {
+            ImportDescription::Function(_) => Space::Function,
+            ImportDescription::Table(_) => Space::Table,
+            ImportDescription::Memory(_) => Space::Memory,
+            ImportDescription::Global(_) => Space::Global,
+            ImportDescription::Tag(_) => Space::Tag,
+        }
+    }
+
+    /// The index space and index of an export.
+    fn of_export(description: &ExportDescription) -> (Self, u32) {
+        match *description {
+            ExportDescription::Function(index) => (Space::Function, index),
+            ExportDescription::Table(index) => (Space::Table, index),
+            ExportDescription::Memory(index) => (Space::Memory, index),
+            ExportDescription::Global(index) => (Space::Global, index),
+            ExportDescription::Tag(index) => (Space::Tag, index),
+        }
+    }
+
+    /// The error for an index missing from this index space.
+    fn unknown(self, index: u32) -> ModelError {
+        match self {
+            Space::Function => ModelError::UnknownFunction(index),
+            Space::Table => ModelError::UnknownTable(index),
+            Space::Memory => ModelError::UnknownMemory(index),
+            Space::Global => ModelError::UnknownGlobal(index),
+            Space::Tag => ModelError::UnknownTag(index),
+        }
+    }
+}
+
+/// Calls the wrapped function with every reference to an index of the given index space.
+struct IndexReferences<'update, F> {
+    space: Space,
+    update: &'update mut F,
+}
+
+impl<F: FnMut(&mut u32)> VisitMut for IndexReferences<'_, F> {
+    fn visit_export(&mut self, export: &mut Export) {
+        let index = match (self.space, &mut export.description) {
+            (Space::Function, ExportDescription::Function(index))
+            | (Space::Table, ExportDescription::Table(index))
+            | (Space::Memory, ExportDescription::Memory(index))
+            | (Space::Global, ExportDescription::Global(index))
+            | (Space::Tag, ExportDescription::Tag(index)) => index,
+            _ => return,
+        };
+
+        (self.update)(index);
+    }
+
+    fn visit_start(&mut self, start: &mut Start) {
+        if self.space == Space::Function {
+            (self.update)(&mut start.function);
+        }
+    }
+
+    fn visit_element(&mut self, _index: ElementIndex, element: &mut Element) {
+        if let (Space::Table, ElementMode::Active(table, _)) = (self.space, &mut element.mode) {
+            (self.update)(table);
+        }
+
+        walk_element_mut(self, element);
+    }
+
+    fn visit_data(&mut self, _index: DataIndex, data: &mut Data) {
+        if let (Space::Memory, DataMode::Active(memory, _)) = (self.space, &mut data.mode) {
+            (self.update)(memory);
+        }
+
+        walk_data_mut(self, data);
+    }
+
+    fn visit_reference(&mut self, instruction: &mut ReferenceInstruction) {
+        if let (Space::Function, ReferenceInstruction::Function(function)) =
+            (self.space, instruction)
+        {
+            (self.update)(function);
+        }
+    }
+
+    fn visit_variable(&mut self, instruction: &mut VariableInstruction) {
+        if let (
+            Space::Global,
+            VariableInstruction::GlobalGet(global) | VariableInstruction::GlobalSet(global),
+        ) = (self.space, instruction)
+        {
+            (self.update)(global);
+        }
+    }
+
+    fn visit_table(&mut self, instruction: &mut TableInstruction) {
+        if self.space != Space::Table {
+            return;
+        }
+
+        match instruction {
+            TableInstruction::Get(table)
+            | TableInstruction::Set(table)
+            | TableInstruction::Size(table)
+            | TableInstruction::Grow(table)
+            | TableInstruction::Fill(table)
+            | TableInstruction::Init(_, table) => (self.update)(table),
+            TableInstruction::Copy(destination, source) => {
+                (self.update)(destination);
+                (self.update)(source);
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_memory(&mut self, instruction: &mut MemoryInstruction) {
+        if self.space != Space::Memory {
+            return;
+        }
+
+        match instruction {
+            MemoryInstruction::Load(_, argument)
+            | MemoryInstruction::Store(_, argument)
+            | MemoryInstruction::Load8(_, _, argument)
+            | MemoryInstruction::Load16(_, _, argument)
+            | MemoryInstruction::Load32(_, argument)
+            | MemoryInstruction::Store8(_, argument)
+            | MemoryInstruction::Store16(_, argument)
+            | MemoryInstruction::Store32(argument) => (self.update)(argument.memory_mut()),
+            MemoryInstruction::Size(memory)
+            | MemoryInstruction::Grow(memory)
+            | MemoryInstruction::Fill(memory)
+            | MemoryInstruction::Init(_, memory) => (self.update)(memory),
+            MemoryInstruction::Copy(destination, source) => {
+                (self.update)(destination);
+                (self.update)(source);
+            }
+            MemoryInstruction::DataDrop(_) => {}
+        }
+    }
+
+    fn visit_atomic(&mut self, instruction: &mut AtomicInstruction) {
+        if self.space != Space::Memory {
+            return;
+        }
+
+        match instruction {
+            AtomicInstruction::Notify(argument)
+            | AtomicInstruction::Wait(_, argument)
+            | AtomicInstruction::Load(_, argument)
+            | AtomicInstruction::Load8(_, argument)
+            | AtomicInstruction::Load16(_, argument)
+            | AtomicInstruction::Load32(argument)
+            | AtomicInstruction::Store(_, argument)
+            | AtomicInstruction::Store8(_, argument)
+            | AtomicInstruction::Store16(_, argument)
+            | AtomicInstruction::Store32(argument)
+            | AtomicInstruction::ReadModifyWrite(_, _, argument)
+            | AtomicInstruction::ReadModifyWrite8(_, _, argument)
+            | AtomicInstruction::ReadModifyWrite16(_, _, argument)
+            | AtomicInstruction::ReadModifyWrite32(_, argument) => {
+                (self.update)(argument.memory_mut())
+            }
+            AtomicInstruction::Fence => {}
+        }
+    }
+
+    fn visit_vector(&mut self, instruction: &mut VectorInstruction) {
+        if self.space != Space::Memory {
+            return;
+        }
+
+        match instruction {
+            VectorInstruction::Load(argument)
+            | VectorInstruction::Store(argument)
+            | VectorInstruction::LoadExtend(_, _, argument)
+            | VectorInstruction::LoadSplat(_, argument)
+            | VectorInstruction::LoadZero(_, argument)
+            | VectorInstruction::LoadLane(_, argument, _)
+            | VectorInstruction::StoreLane(_, argument, _) => (self.update)(argument.memory_mut()),
+            _ => {}
+        }
+    }
+
+    fn visit_control(&mut self, instruction: &mut ControlInstruction) {
+        match (self.space, &mut *instruction) {
+            (
+                Space::Function,
+                ControlInstruction::Call(function) | ControlInstruction::ReturnCall(function),
+            ) => (self.update)(function),
+            (
+                Space::Table,
+                ControlInstruction::CallIndirect(_, table)
+                | ControlInstruction::ReturnCallIndirect(_, table),
+            ) => (self.update)(table),
+            (Space::Tag, ControlInstruction::Throw(tag)) => (self.update)(tag),
+            (Space::Tag, ControlInstruction::TryTable(_, catches, _)) => {
+                for catch in catches {
+                    if let Catch::Catch(tag, _) | Catch::CatchReference(tag, _) = catch {
+                        (self.update)(tag);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        walk_control_mut(self, instruction);
+    }
+}
+
+/// Shifts the entries of a map ordered by index up by one, starting at the given index.
+fn insert_index<T>(map: &mut [(u32, T)], index: u32) {
+    for (candidate, _) in map.iter_mut() {
+        if *candidate >= index {
+            *candidate += 1;
+        }
+    }
+}
+
+/// Removes the entry with the given index from a map ordered by index,
+/// shifting the indices of the entries after it down by one.
+fn remove_index<T>(map: &mut Vec<(u32, T)>, index: u32) {
+    map.retain(|(candidate, _)| *candidate != index);
+
+    for (candidate, _) in map.iter_mut() {
+        if *candidate > index {
+            *candidate -= 1;
+        }
+    }
+}
+
+/// Drops the entries of a map ordered by index that were not kept,
+/// and moves the remaining entries to their new index.
+fn remap_indices<T>(map: &mut Vec<(u32, T)>, kept: &[bool], remap: &[u32]) {
+    map.retain(|(index, _)| kept.get(*index as usize).copied().unwrap_or(true));
+
+    for (index, _) in map.iter_mut() {
+        if let Some(moved) = remap.get(*index as usize) {
+            *index = *moved;
+        }
     }
 }
 
@@ -497,6 +1990,22 @@ impl CustomSections {
             .map(Vec::as_slice)
     }
 
+    /// The first custom section with the given name, regardless of its insertion point.
+    pub fn find(&self, name: &str) -> Option<&Custom> {
+        self.custom_sections
+            .values()
+            .flatten()
+            .find(|custom| custom.name().value() == name)
+    }
+
+    /// Mutable reference to the first custom section with the given name.
+    pub fn find_mut(&mut self, name: &str) -> Option<&mut Custom> {
+        self.custom_sections
+            .values_mut()
+            .flatten()
+            .find(|custom| custom.name().value() == name)
+    }
+
     /// Sets the custom section at the given insertion point for the WebAssembly module to be built.
     /// WebAssembly binary format allows custom sections to be at the start of a module, or after any other section.
     pub fn set_custom_sections(
@@ -516,7 +2025,7 @@ impl CustomSections {
         let custom_sections = self
             .custom_sections
             .entry(insertion_point)
-            .or_insert_with(Vec::new);
+            .or_default();
 
         custom_sections.push(custom_section);
     }
@@ -602,11 +2111,99 @@ impl Custom {
 pub struct Function {
     kind: TypeIndex,
     locals: ResultType,
-    body: Expression,
+    body: Body,
+}
+
+/// The code of a function, which is either decoded or waiting to be decoded on first use.
+#[derive(Clone, Debug)]
+pub(crate) enum Body {
+    Decoded(Expression),
+    #[cfg(feature = "parser")]
+    Lazy(crate::parser::LazyExpression),
+}
+
+impl PartialEq for Body {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Body::Decoded(left), Body::Decoded(right)) => left == right,
+            #[cfg(feature = "parser")]
+            (Body::Lazy(left), Body::Lazy(right)) if left == right => true,
+            #[cfg(feature = "parser")]
+            (left, right) => match (left.decode(), right.decode()) {
+                (Some(left), Some(right)) => left == right,
+                _ => false,
+            },
+        }
+    }
+}
+
+impl Body {
+    /// The decoded expression, or `None` if a lazy body fails to decode.
+    fn decode(&self) -> Option<&Expression> {
+        match self {
+            Body::Decoded(expression) => Some(expression),
+            #[cfg(feature = "parser")]
+            Body::Lazy(body) => body.decode().ok(),
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Body::Decoded(Expression::empty())
+    }
+}
+
+impl From<Expression> for Body {
+    fn from(expression: Expression) -> Self {
+        Body::Decoded(expression)
+    }
+}
+
+#[cfg(feature = "parser")]
+impl From<crate::parser::LazyExpression> for Body {
+    fn from(body: crate::parser::LazyExpression) -> Self {
+        Body::Lazy(body)
+    }
+}
+
+/// The body of the function with the given index,
+/// or an error if a lazily parsed body is not a valid expression.
+#[cfg(feature = "parser")]
+fn function_body(function: &Function, index: FunctionIndex) -> Result<&Expression, ModelError> {
+    function
+        .try_body()
+        .map_err(|error| ModelError::MalformedBody(index, error.to_string()))
+}
+
+/// The body of the function, which is always decoded without the parser.
+#[cfg(not(feature = "parser"))]
+fn function_body(function: &Function, _: FunctionIndex) -> Result<&Expression, ModelError> {
+    Ok(function.body())
 }
 
 impl Function {
     pub fn new(kind: TypeIndex, locals: ResultType, body: Expression) -> Self {
+        Function {
+            kind,
+            locals,
+            body: body.into(),
+        }
+    }
+
+    /// Creates a function whose body is decoded the first time it is accessed.
+    #[cfg(feature = "parser")]
+    pub fn lazy(kind: TypeIndex, locals: ResultType, body: crate::parser::LazyExpression) -> Self {
+        Function {
+            kind,
+            locals,
+            body: body.into(),
+        }
+    }
+
+    /// Creates a function from the parts produced by a parser.
+    #[cfg(feature = "parser")]
+    pub(crate) fn from_parts(kind: TypeIndex, locals: ResultType, body: Body) -> Self {
         Function { kind, locals, body }
     }
 
@@ -621,8 +2218,57 @@ impl Function {
     }
 
     /// The code for this `Function`.
+    /// A lazily parsed body is decoded on the first call.
+    ///
+    /// # Panics
+    /// Panics if the body was parsed lazily and is not a valid expression.
+    /// Use `try_body` to handle invalid bodies instead.
     pub fn body(&self) -> &Expression {
-        &self.body
+        self.body
+            .decode()
+            .expect("The function body is not a valid expression.")
+    }
+
+    /// The code for this `Function`, or an error if a lazily parsed body is not a valid expression.
+    #[cfg(feature = "parser")]
+    pub fn try_body(&self) -> Result<&Expression, crate::parser::ParseError> {
+        match &self.body {
+            Body::Decoded(expression) => Ok(expression),
+            Body::Lazy(body) => body.decode(),
+        }
+    }
+
+    /// True if the body of this `Function` is parsed lazily and has not been decoded yet.
+    pub fn is_lazy(&self) -> bool {
+        match &self.body {
+            Body::Decoded(_) => false,
+            #[cfg(feature = "parser")]
+            Body::Lazy(body) => !body.is_decoded(),
+        }
+    }
+
+    /// Mutable reference to the code for this `Function`.
+    /// A lazily parsed body is decoded and detached from the bytes it was parsed from.
+    ///
+    /// # Panics
+    /// Panics if the body was parsed lazily and is not a valid expression.
+    pub fn body_mut(&mut self) -> &mut Expression {
+        #[cfg(feature = "parser")]
+        if let Body::Lazy(_) = &self.body {
+            self.body = match std::mem::take(&mut self.body) {
+                Body::Lazy(body) => body
+                    .into_expression()
+                    .expect("The function body is not a valid expression.")
+                    .into(),
+                body => body,
+            };
+        }
+
+        match &mut self.body {
+            Body::Decoded(expression) => expression,
+            #[cfg(feature = "parser")]
+            Body::Lazy(_) => unreachable!(),
+        }
     }
 
     pub fn set_kind(&mut self,index: TypeIndex) {
@@ -634,13 +2280,15 @@ impl Function {
     }
 
     pub fn set_body(&mut self,body: Expression) {
-        self.body = body;
+        self.body = body.into();
     }
 }
 
 /// A table is a vector of opaque values of a particular reference type.
 /// The 𝗆𝗂𝗇 size in the limits of the table type specifies the initial size of that table, while its 𝗆𝖺𝗑, if present, restricts the size to which it can grow later.
 /// Tables can be initialized through element segments.
+/// A table may also have an initializer, a constant expression whose value every element
+/// of the table starts out with. Tables of non-nullable reference types require one.
 /// Tables are referenced through table indices,
 /// starting with the smallest index not referencing a table import.
 /// Most constructs implicitly reference table index 0.
@@ -657,27 +2305,64 @@ impl Function {
 ///
 /// assert_eq!(table, kind.into());
 /// assert_eq!(table.kind(), &kind);
+/// assert_eq!(table.initializer(), None);
 /// ```
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+///
+/// ## Initializer
+/// ```rust
+/// use wasm_ast::{Table, TableType, Limit, ReferenceType, ReferenceInstruction, HeapType, Expression};
+///
+/// let kind = TableType::new(ReferenceType::Reference(HeapType::Index(0)), Limit::unbounded(1));
+/// let initializer: Expression = vec![ReferenceInstruction::Function(0).into()].into();
+/// let table = Table::with_initializer(kind, initializer.clone());
+///
+/// assert_eq!(table.kind(), &kind);
+/// assert_eq!(table.initializer(), Some(&initializer));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
 pub struct Table {
     kind: TableType,
+    initializer: Option<Expression>,
 }
 
 impl Table {
     /// Creates a new instance of a `Table`.
     pub fn new(kind: TableType) -> Self {
-        Table { kind }
+        Table {
+            kind,
+            initializer: None,
+        }
+    }
+
+    /// Creates a new `Table` whose elements are initialized with the value of the given expression.
+    ///
+    /// See <https://webassembly.github.io/function-references/core/syntax/modules.html#tables>
+    pub fn with_initializer(kind: TableType, initializer: Expression) -> Self {
+        Table {
+            kind,
+            initializer: Some(initializer),
+        }
     }
 
     /// The type descriptor of this `Table`.
     pub fn kind(&self) -> &TableType {
         &self.kind
     }
+
+    /// The expression to initialize the elements of this `Table` with, if any.
+    pub fn initializer(&self) -> Option<&Expression> {
+        self.initializer.as_ref()
+    }
+
+    /// Mutable reference to the expression to initialize the elements of this `Table` with, if any.
+    pub fn initializer_mut(&mut self) -> Option<&mut Expression> {
+        self.initializer.as_mut()
+    }
 }
 
 impl From<TableType> for Table {
     fn from(kind: TableType) -> Self {
-        Table { kind }
+        Table::new(kind)
     }
 }
 
@@ -729,6 +2414,45 @@ where
     }
 }
 
+/// Tags classify exceptions by the types of the values they carry.
+/// The 𝗍𝗒𝗉𝖾 of a tag is a function type with no results, whose parameters are the values of the exception.
+/// Tags are referenced through tag indices,
+/// starting with the smallest index not referencing a tag import.
+///
+/// See <https://webassembly.github.io/exception-handling/core/syntax/modules.html#tags>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::Tag;
+///
+/// let tag = Tag::new(1);
+///
+/// assert_eq!(tag, 1.into());
+/// assert_eq!(tag.kind(), 1);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Tag {
+    kind: TypeIndex,
+}
+
+impl Tag {
+    /// Creates a new `Tag` of the given type.
+    pub fn new(kind: TypeIndex) -> Self {
+        Tag { kind }
+    }
+
+    /// The index of the function type of this tag.
+    pub fn kind(&self) -> TypeIndex {
+        self.kind
+    }
+}
+
+impl From<TypeIndex> for Tag {
+    fn from(kind: TypeIndex) -> Self {
+        Tag { kind }
+    }
+}
+
 /// Each global stores a single value of the given global type.
 /// Its 𝗍𝗒𝗉𝖾 also specifies whether a global is immutable or mutable.
 /// Moreover, each global is initialized with an 𝗂𝗇𝗂𝗍 value given by a constant initializer expression.
@@ -800,6 +2524,11 @@ impl Global {
     pub fn initializer(&self) -> &Expression {
         &self.initializer
     }
+
+    /// Mutable reference to the expression to initialize this `Global` with.
+    pub fn initializer_mut(&mut self) -> &mut Expression {
+        &mut self.initializer
+    }
 }
 
 /// The initial contents of a table is uninitialized.
@@ -931,6 +2660,16 @@ impl Element {
     pub fn mode(&self) -> &ElementMode {
         &self.mode
     }
+
+    /// Mutable reference to the initializer for the element segment.
+    pub fn initializers_mut(&mut self) -> &mut [Expression] {
+        &mut self.initializers
+    }
+
+    /// Mutable reference to the mode of the element segment.
+    pub fn mode_mut(&mut self) -> &mut ElementMode {
+        &mut self.mode
+    }
 }
 
 /// Supported types for initializing an element component.
@@ -1038,6 +2777,11 @@ impl Data {
         &self.mode
     }
 
+    /// Mutable reference to the mode of the data segment.
+    pub fn mode_mut(&mut self) -> &mut DataMode {
+        &mut self.mode
+    }
+
     /// The data to initialize the segment with.
     pub fn initializer(&self) -> &[u8] {
         &self.initializer
@@ -1073,6 +2817,124 @@ pub enum DataMode {
     Active(MemoryIndex, Expression),
 }
 
+/// The range of a memory or table that an active data or element segment initializes,
+/// as determined by evaluating the offset of the segment.
+/// Data segments are measured in bytes and element segments in table entries.
+/// Segments are applied in order during instantiation,
+/// so a later segment overwrites the overlapping range of an earlier one.
+///
+/// See <https://webassembly.github.io/spec/core/exec/modules.html#instantiation>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::SegmentLayout;
+///
+/// let layout = SegmentLayout::new(1, 0, 1024, 16);
+///
+/// assert_eq!(layout.segment(), 1);
+/// assert_eq!(layout.target(), 0);
+/// assert_eq!(layout.start(), 1024);
+/// assert_eq!(layout.len(), 16);
+/// assert_eq!(layout.end(), 1040);
+/// assert!(!layout.is_empty());
+/// assert!(layout.overlaps(&SegmentLayout::new(2, 0, 1039, 1)));
+/// assert!(!layout.overlaps(&SegmentLayout::new(2, 0, 1040, 1)));
+/// assert!(!layout.overlaps(&SegmentLayout::new(2, 1, 1024, 16)));
+/// assert!(!layout.overlaps(&SegmentLayout::new(2, 0, 1024, 0)));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct SegmentLayout {
+    segment: u32,
+    target: u32,
+    start: u64,
+    length: u64,
+}
+
+impl SegmentLayout {
+    /// Creates a new layout for the given segment, covering `length` units of the target from `start`.
+    pub fn new(segment: u32, target: u32, start: u64, length: u64) -> Self {
+        SegmentLayout {
+            segment,
+            target,
+            start,
+            length,
+        }
+    }
+
+    /// The index of the data or element segment.
+    pub fn segment(&self) -> u32 {
+        self.segment
+    }
+
+    /// The index of the memory or table the segment initializes.
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+
+    /// The first address or table entry initialized by the segment.
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    /// The number of bytes or table entries initialized by the segment.
+    pub fn len(&self) -> u64 {
+        self.length
+    }
+
+    /// Returns true if the segment does not initialize anything, false otherwise.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The address or table entry following the last one initialized by the segment.
+    pub fn end(&self) -> u64 {
+        self.start.saturating_add(self.length)
+    }
+
+    /// Returns true if both segments initialize a common part of the same memory or table.
+    pub fn overlaps(&self, other: &SegmentLayout) -> bool {
+        self.target == other.target && self.start < other.end() && other.start < self.end()
+    }
+
+    /// The pairs of segment indices whose layouts overlap, with the earlier segment first.
+    pub fn overlapping(layouts: &[SegmentLayout]) -> Vec<(u32, u32)> {
+        let mut pairs = Vec::new();
+
+        for (position, first) in layouts.iter().enumerate() {
+            for second in &layouts[position + 1..] {
+                if first.overlaps(second) {
+                    pairs.push((first.segment, second.segment));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// Evaluates the offset of the segment at the given index.
+    /// Offsets of type `i32` are unsigned 32-bit addresses.
+    fn evaluate(
+        index: usize,
+        target: u32,
+        offset: &Expression,
+        length: usize,
+        globals: &[Value],
+    ) -> Result<Self, ModelError> {
+        let segment = u32::try_from(index)?;
+        let start = match ConstantExpression::new(offset.clone())?.evaluate(globals)? {
+            Value::I32(offset) => offset as u32 as u64,
+            Value::I64(offset) => offset as u64,
+            _ => return Err(ModelError::InvalidSegmentOffset(segment)),
+        };
+        let length = length as u64;
+
+        match start.checked_add(length) {
+            Some(_) => Ok(SegmentLayout::new(segment, target, start, length)),
+            None => Err(ModelError::InvalidSegmentOffset(segment)),
+        }
+    }
+}
+
 /// The 𝗌𝗍𝖺𝗋𝗍 component of a module declares the function index of a start function that
 /// is automatically invoked when the module is instantiated,
 /// after tables and memories have been initialized.
@@ -1112,7 +2974,7 @@ impl From<u32> for Start {
 /// The 𝖾𝗑𝗉𝗈𝗋𝗍𝗌 component of a module defines a set of exports that become accessible to the
 /// host environment once the module has been instantiated.
 /// Each export is labeled by a unique name.
-/// Exportable definitions are functions, tables, memories, and globals,
+/// Exportable definitions are functions, tables, memories, tags, and globals,
 /// which are referenced through a respective descriptor.
 ///
 /// See <https://webassembly.github.io/spec/core/syntax/modules.html#exports>
@@ -1169,6 +3031,19 @@ impl From<u32> for Start {
 /// assert_eq!(export.name(), &Name::new(String::from(name)));
 /// assert_eq!(export.description(), &description);
 /// ```
+///
+/// ## Tag
+/// ```rust
+/// use wasm_ast::{Export, ExportDescription, Name};
+///
+/// let name = "error";
+/// let description = ExportDescription::Tag(3);
+/// let export = Export::new(name.into(), description.clone());
+///
+/// assert_eq!(export, Export::tag(name.into(), 3));
+/// assert_eq!(export.name(), &Name::new(String::from(name)));
+/// assert_eq!(export.description(), &description);
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Export {
     name: Name,
@@ -1213,6 +3088,14 @@ impl Export {
         }
     }
 
+    /// Create a new instance of an `Export` with the given name and description for a tag.
+    pub fn tag(name: Name, tag: TagIndex) -> Self {
+        Export {
+            name,
+            description: ExportDescription::Tag(tag),
+        }
+    }
+
     /// The name of the export.
     pub fn name(&self) -> &Name {
         &self.name
@@ -1224,7 +3107,7 @@ impl Export {
     }
 }
 
-/// Exportable definitions are functions, tables, memories, and globals,
+/// Exportable definitions are functions, tables, memories, tags, and globals,
 /// which are referenced through a respective descriptor.
 #[derive(Copy, Clone, Debug, Eq, PartialEq,Hash)]
 pub enum ExportDescription {
@@ -1232,11 +3115,12 @@ pub enum ExportDescription {
     Table(TableIndex),
     Memory(MemoryIndex),
     Global(GlobalIndex),
+    Tag(TagIndex),
 }
 
 /// Each import is labeled by a two-level name space,
 /// consisting of a 𝗆𝗈𝖽𝗎𝗅𝖾 name and a 𝗇𝖺𝗆𝖾 for an entity within that module.
-/// Importable definitions are functions, tables, memories, and globals.
+/// Importable definitions are functions, tables, memories, tags, and globals.
 /// Each import is specified by a descriptor with a respective type that a definition provided
 /// during instantiation is required to match.
 /// Every import defines an index in the respective index space.
@@ -1308,6 +3192,21 @@ pub enum ExportDescription {
 /// assert_eq!(import.name(), &Name::new(String::from(name)));
 /// assert_eq!(import.description(), &description);
 /// ```
+///
+/// ## Tag
+/// ```rust
+/// use wasm_ast::{Import, ImportDescription, Name};
+///
+/// let module = "system";
+/// let name = "error";
+/// let description = ImportDescription::Tag(7);
+/// let import = Import::new(module.into(), name.into(), description.clone());
+///
+/// assert_eq!(import, Import::tag(module.into(), name.into(), 7));
+/// assert_eq!(import.module(), &Name::new(String::from(module)));
+/// assert_eq!(import.name(), &Name::new(String::from(name)));
+/// assert_eq!(import.description(), &description);
+/// ```
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Import {
     module: Name,
@@ -1361,6 +3260,15 @@ impl Import {
         }
     }
 
+    /// Create a new instance of an `Import` with the given name and description for a tag.
+    pub fn tag(module: Name, name: Name, tag_kind: TypeIndex) -> Self {
+        Import {
+            module,
+            name,
+            description: ImportDescription::Tag(tag_kind),
+        }
+    }
+
     /// The name of the module (i.e.m namespace).
     pub fn module(&self) -> &Name {
         &self.module
@@ -1385,6 +3293,8 @@ pub enum ImportDescription {
     Table(TableType),
     Memory(MemoryType),
     Global(GlobalType),
+    /// A tag import, described by the index of its function type.
+    Tag(TypeIndex),
 }
 
 /// The binary encoding of modules is organized into sections.
@@ -1442,11 +3352,18 @@ pub enum ModuleSection {
     /// It decodes into an optional u32 that represents the number of data segments in the data section.
     /// If this count does not match the length of the data segment vector, the module is malformed.
     DataCount,
+    /// The tag section has the id 13.
+    /// It decodes into a vector of tags that represent the 𝗍𝖺𝗀𝗌 component of a module.
+    /// Although its id is the largest, the tag section is placed between the memory and global sections.
+    Tag,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::{
+        AtomicOperation, BlockType, IntegerVectorShape, MemoryArgument, NumericInstruction,
+    };
 
     #[test]
     fn module_equality_when_empty() {
@@ -1467,6 +3384,607 @@ mod tests {
         assert_ne!(module, Module::empty());
     }
 
+    #[test]
+    fn remove_function_updates_references() {
+        let mut builder = Module::builder();
+        let call = |function| Expression::new(vec![ControlInstruction::Call(function).into()]);
+        let reference =
+            |function| Expression::new(vec![ReferenceInstruction::Function(function).into()]);
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_import(Import::function("env".into(), "log".into(), 0))
+            .unwrap();
+        builder
+            .add_function(Function::new(0, ResultType::empty(), call(0)))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![ControlInstruction::Block(BlockType::None, call(3)).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(0, ResultType::empty(), Expression::empty()))
+            .unwrap();
+        builder
+            .add_global(Global::immutable(
+                ValueType::FunctionReference,
+                reference(3),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::declarative(
+                ReferenceType::Function,
+                vec![0, 3].to_initializers(),
+            ))
+            .unwrap();
+        builder.add_export(Export::function("run".into(), 2));
+        builder.set_start(Some(Start::new(3)));
+
+        let mut names = NameSection::new();
+        names.function_names_mut().extend([
+            (0, "log".into()),
+            (1, "unused".into()),
+            (3, "last".into()),
+        ]);
+        names.local_names_mut().push((1, vec![(0, "x".into())]));
+        builder.add_custom_section(ModuleSection::Data, Custom::from(&names));
+
+        assert!(matches!(
+            builder.remove_function(0),
+            Err(ModelError::UndefinedFunction(0))
+        ));
+        assert!(matches!(
+            builder.remove_function(4),
+            Err(ModelError::UndefinedFunction(4))
+        ));
+        assert!(matches!(
+            builder.remove_function(2),
+            Err(ModelError::ReferencedFunction(2))
+        ));
+        assert_eq!(
+            builder.remove_function(1).unwrap(),
+            Function::new(0, ResultType::empty(), call(0))
+        );
+
+        let module = builder.build();
+        let functions = module.functions().unwrap();
+        let names = module.name_section().unwrap().unwrap();
+
+        assert_eq!(functions.len(), 2);
+        assert_eq!(
+            functions[0].body(),
+            &vec![ControlInstruction::Block(BlockType::None, call(2)).into()].into()
+        );
+        assert_eq!(module.globals().unwrap()[0].initializer(), &reference(2));
+        assert_eq!(
+            module.elements().unwrap()[0].initializers(),
+            vec![0, 2].to_initializers().as_slice()
+        );
+        assert_eq!(
+            module.exports().unwrap()[0],
+            Export::function("run".into(), 1)
+        );
+        assert_eq!(module.start(), Some(&Start::new(2)));
+        assert_eq!(
+            names.function_names(),
+            &vec![(0, "log".into()), (2, "last".into())]
+        );
+        assert!(names.local_names().is_empty());
+    }
+
+    #[test]
+    fn strict_builder_checks_references() {
+        let mut builder = ModuleBuilder::strict();
+        let memory = MemoryType::new(Limit::new(1, None));
+
+        assert!(matches!(
+            builder.add_import(Import::function("env".into(), "log".into(), 0)),
+            Err(ModelError::UnknownType(0))
+        ));
+        assert!(matches!(
+            builder.add_tag(Tag::new(0)),
+            Err(ModelError::UnknownType(0))
+        ));
+
+        builder
+            .add_recursive_type(StructType::new(vec![]).into())
+            .unwrap();
+
+        assert!(matches!(
+            builder.add_import(Import::function("env".into(), "log".into(), 0)),
+            Err(ModelError::ExpectedFunctionType(0))
+        ));
+        assert!(matches!(
+            builder.add_tag(Tag::new(0)),
+            Err(ModelError::ExpectedFunctionType(0))
+        ));
+        assert!(matches!(
+            builder.add_function(Function::new(0, ResultType::empty(), Expression::empty())),
+            Err(ModelError::ExpectedFunctionType(0))
+        ));
+        assert!(matches!(
+            builder.add_data(Data::active(0, Expression::empty(), vec![])),
+            Err(ModelError::UnknownMemory(0))
+        ));
+        assert!(matches!(
+            builder.add_element(Element::active(
+                0,
+                Expression::empty(),
+                ReferenceType::Function,
+                vec![]
+            )),
+            Err(ModelError::UnknownTable(0))
+        ));
+        assert!(matches!(
+            builder.try_add_export(Export::global("global".into(), 0)),
+            Err(ModelError::UnknownGlobal(0))
+        ));
+
+        builder
+            .add_import(Import::memory("env".into(), "memory".into(), memory))
+            .unwrap();
+        builder
+            .add_data(Data::active(0, Expression::empty(), vec![]))
+            .unwrap();
+        builder.add_memory(memory.into()).unwrap();
+        builder
+            .try_add_export(Export::memory("memory".into(), 1))
+            .unwrap();
+
+        assert!(matches!(
+            builder.try_add_export(Export::memory("other".into(), 2)),
+            Err(ModelError::UnknownMemory(2))
+        ));
+        assert!(matches!(
+            builder.try_add_export(Export::memory("memory".into(), 0)),
+            Err(ModelError::DuplicateExport(name)) if name == "memory"
+        ));
+
+        builder.set_strict(false);
+        builder
+            .try_add_export(Export::memory("memory".into(), 2))
+            .unwrap();
+
+        assert!(!builder.is_strict());
+        assert_eq!(builder.exports().map(<[Export]>::len), Some(2));
+        assert!(!Module::builder().is_strict());
+    }
+
+    #[test]
+    fn strict_builder_checks_expressions_and_typed_references() {
+        let mut builder = ModuleBuilder::strict();
+        let typed = ReferenceType::Reference(HeapType::Index(0));
+        let table = TableType::new(ReferenceType::Function, Limit::unbounded(1));
+        let call = |function| Expression::from(vec![ControlInstruction::Call(function).into()]);
+
+        assert!(matches!(
+            builder.add_import(Import::table(
+                "env".into(),
+                "table".into(),
+                TableType::new(typed, Limit::unbounded(1))
+            )),
+            Err(ModelError::UnknownType(0))
+        ));
+        assert!(matches!(
+            builder.add_import(Import::global(
+                "env".into(),
+                "global".into(),
+                GlobalType::immutable(typed.into())
+            )),
+            Err(ModelError::UnknownType(0))
+        ));
+        assert!(matches!(
+            builder.add_table(Table::new(TableType::new(typed, Limit::unbounded(1)))),
+            Err(ModelError::UnknownType(0))
+        ));
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+
+        assert!(matches!(
+            builder.try_set_start(Some(Start::new(0))),
+            Err(ModelError::UnknownFunction(0))
+        ));
+        assert!(matches!(
+            builder.add_function(Function::new(0, ResultType::empty(), call(1))),
+            Err(ModelError::UnknownFunction(1))
+        ));
+        assert!(matches!(
+            builder.add_function(Function::new(
+                0,
+                ResultType::new(vec![ValueType::Reference(HeapType::Index(1))]),
+                Expression::empty()
+            )),
+            Err(ModelError::UnknownType(1))
+        ));
+        assert!(matches!(
+            builder.add_table(Table::with_initializer(
+                TableType::new(typed, Limit::unbounded(1)),
+                vec![ReferenceInstruction::Function(0).into()].into()
+            )),
+            Err(ModelError::UnknownFunction(0))
+        ));
+        assert!(matches!(
+            builder.add_global(Global::immutable(
+                ValueType::I32,
+                vec![VariableInstruction::GlobalGet(0).into()].into()
+            )),
+            Err(ModelError::UnknownGlobal(0))
+        ));
+
+        builder.add_memory(Limit::unbounded(1).into()).unwrap();
+        builder.add_table(table.into()).unwrap();
+
+        assert!(matches!(
+            builder.add_data(Data::active(
+                0,
+                vec![VariableInstruction::GlobalGet(0).into()].into(),
+                vec![]
+            )),
+            Err(ModelError::UnknownGlobal(0))
+        ));
+
+        assert!(matches!(
+            builder.add_element(Element::active(
+                0,
+                vec![VariableInstruction::GlobalGet(0).into()].into(),
+                ReferenceType::Function,
+                vec![]
+            )),
+            Err(ModelError::UnknownGlobal(0))
+        ));
+        assert!(matches!(
+            builder.add_element(Element::passive(
+                ReferenceType::Function,
+                vec![vec![ReferenceInstruction::Function(0).into()].into()]
+            )),
+            Err(ModelError::UnknownFunction(0))
+        ));
+
+        let function = builder
+            .add_function(Function::new(0, ResultType::empty(), call(0)))
+            .unwrap();
+
+        builder.set_start(Some(Start::new(function)));
+        builder
+            .add_table(Table::with_initializer(
+                TableType::new(typed, Limit::unbounded(1)),
+                vec![ReferenceInstruction::Function(function).into()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(builder.tables().map(<[Table]>::len), Some(2));
+    }
+
+    #[test]
+    fn deduplicate_types_updates_references() {
+        let unary = FunctionType::side_effect(ResultType::new(vec![ValueType::I32]));
+        let boxed = |index| {
+            let field = FieldType::mutable(ValueType::NullableReference(HeapType::Index(index)));
+
+            RecursiveType::from(SubType::new(
+                false,
+                vec![],
+                StructType::new(vec![field]).into(),
+            ))
+        };
+        let mut builder = Module::builder();
+
+        for kind in [
+            unary.clone(),
+            FunctionType::runnable(),
+            unary.clone(),
+            FunctionType::runnable(),
+        ] {
+            builder.add_function_type(kind).unwrap();
+        }
+
+        builder.add_recursive_type(boxed(2)).unwrap();
+        builder.add_recursive_type(boxed(2)).unwrap();
+
+        builder
+            .add_import(Import::tag("env".into(), "error".into(), 2))
+            .unwrap();
+        builder.add_tag(Tag::new(3)).unwrap();
+        builder
+            .add_table(Table::new(TableType::new(
+                ReferenceType::NullableReference(HeapType::Index(3)),
+                Limit::unbounded(0),
+            )))
+            .unwrap();
+        builder
+            .add_element(Element::passive(
+                ReferenceType::Reference(HeapType::Index(2)),
+                Vec::new(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                3,
+                ResultType::new(vec![ValueType::NullableReference(HeapType::Index(5))]),
+                vec![
+                    ControlInstruction::CallIndirect(2, 0).into(),
+                    ControlInstruction::CallReference(2).into(),
+                    ControlInstruction::If(
+                        BlockType::Index(3),
+                        vec![ControlInstruction::ReturnCallIndirect(0, 0).into()].into(),
+                        None,
+                    )
+                    .into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        let mut names = NameSection::new();
+        names.type_names_mut().extend([
+            (1, "void".into()),
+            (2, "duplicate".into()),
+            (3, "last".into()),
+        ]);
+        names.parameter_names_mut().push((2, vec![(0, "x".into())]));
+        names.field_names_mut().push((3, vec![(0, "v".into())]));
+        builder.add_custom_section(ModuleSection::Data, Custom::from(&names));
+
+        let mut module = builder.build();
+
+        assert_eq!(module.deduplicate_types().unwrap(), 3);
+        assert_eq!(module.deduplicate_types().unwrap(), 0);
+        assert_eq!(
+            module.types(),
+            Some(&[unary.into(), FunctionType::runnable().into(), boxed(0)][..])
+        );
+        assert_eq!(
+            module.imports().unwrap()[0].description(),
+            &ImportDescription::Tag(0)
+        );
+        assert_eq!(module.tags(), Some(&[Tag::new(1)][..]));
+        assert_eq!(
+            module.tables().unwrap()[0].kind().kind(),
+            ReferenceType::NullableReference(HeapType::Index(1))
+        );
+        assert_eq!(
+            module.elements().unwrap()[0].kind(),
+            ReferenceType::Reference(HeapType::Index(0))
+        );
+        assert_eq!(
+            module.functions().unwrap()[0],
+            Function::new(
+                1,
+                ResultType::new(vec![ValueType::NullableReference(HeapType::Index(2))]),
+                vec![
+                    ControlInstruction::CallIndirect(0, 0).into(),
+                    ControlInstruction::CallReference(0).into(),
+                    ControlInstruction::If(
+                        BlockType::Index(1),
+                        vec![ControlInstruction::ReturnCallIndirect(0, 0).into()].into(),
+                        None,
+                    )
+                    .into(),
+                ]
+                .into(),
+            )
+        );
+
+        let names = module.name_section().unwrap().unwrap();
+
+        assert_eq!(names.type_names(), &vec![(1, "void".into())]);
+        assert!(names.parameter_names().is_empty());
+        assert!(names.field_names().is_empty());
+    }
+
+    #[test]
+    fn abbreviated_reference_types_are_the_same_type() {
+        let abbreviated = FunctionType::side_effect(ResultType::new(vec![
+            ValueType::FunctionReference,
+            ValueType::ExternalReference,
+        ]));
+        let written_out = FunctionType::side_effect(ResultType::new(vec![
+            ValueType::NullableReference(HeapType::Function),
+            ValueType::NullableReference(HeapType::External),
+        ]));
+        let mut builder = Module::builder();
+
+        assert_eq!(
+            builder.intern_function_type(abbreviated.clone()).unwrap(),
+            0
+        );
+        assert_eq!(
+            builder.intern_function_type(written_out.clone()).unwrap(),
+            0
+        );
+
+        builder.add_function_type(written_out).unwrap();
+
+        let mut module = builder.build();
+
+        assert_eq!(module.deduplicate_types().unwrap(), 1);
+        assert_eq!(module.function_type(0), Some(&abbreviated));
+    }
+
+    #[test]
+    fn deduplicate_types_merges_groups_equal_after_remapping() {
+        let boxed = |index| {
+            let field = FieldType::immutable(ValueType::NullableReference(HeapType::Index(index)));
+
+            SubType::from(StructType::new(vec![field]))
+        };
+        let mut builder = Module::builder();
+
+        builder.set_types(Some(vec![
+            StructType::new(vec![]).into(),
+            StructType::new(vec![]).into(),
+            boxed(0).into(),
+            boxed(1).into(),
+            RecursiveType::new(vec![boxed(5), boxed(2)]),
+            RecursiveType::new(vec![boxed(7), boxed(3)]),
+        ]));
+        builder
+            .add_global(Global::immutable(
+                ValueType::NullableReference(HeapType::Index(7)),
+                vec![ReferenceInstruction::Null(HeapType::Index(3)).into()].into(),
+            ))
+            .unwrap();
+
+        let mut module = builder.build();
+
+        assert_eq!(module.deduplicate_types().unwrap(), 4);
+        assert_eq!(module.deduplicate_types().unwrap(), 0);
+        assert_eq!(
+            module.types(),
+            Some(
+                &[
+                    StructType::new(vec![]).into(),
+                    boxed(0).into(),
+                    RecursiveType::new(vec![boxed(3), boxed(1)]),
+                ][..]
+            )
+        );
+        assert_eq!(
+            module.globals().unwrap()[0],
+            Global::immutable(
+                ValueType::NullableReference(HeapType::Index(3)),
+                vec![ReferenceInstruction::Null(HeapType::Index(1)).into()].into(),
+            )
+        );
+    }
+
+    #[test]
+    fn add_import_after_definitions_shifts_references() {
+        let mut builder = Module::builder();
+        let table = TableType::new(ReferenceType::Function, Limit::new(1, None));
+        let memory = MemoryType::new(Limit::new(1, None));
+        let global = GlobalType::immutable(ValueType::I32);
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder.add_table(table.into()).unwrap();
+        builder.add_memory(memory.into()).unwrap();
+        builder
+            .add_global(Global::new(global, vec![0i32.into()].into()))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    VariableInstruction::GlobalGet(0).into(),
+                    VariableInstruction::GlobalSet(0).into(),
+                    TableInstruction::Copy(0, 0).into(),
+                    TableInstruction::Get(0).into(),
+                    TableInstruction::Init(0, 0).into(),
+                    MemoryInstruction::Size(0).into(),
+                    MemoryInstruction::Copy(0, 0).into(),
+                    MemoryInstruction::Load(NumberType::I32, MemoryArgument::with_memory(0, 2, 4))
+                        .into(),
+                    MemoryInstruction::Store8(
+                        IntegerType::I64,
+                        MemoryArgument::with_memory(0, 0, 0),
+                    )
+                    .into(),
+                    AtomicInstruction::Notify(MemoryArgument::with_memory(0, 2, 0)).into(),
+                    AtomicInstruction::ReadModifyWrite(
+                        AtomicOperation::Add,
+                        IntegerType::I32,
+                        MemoryArgument::with_memory(0, 2, 0),
+                    )
+                    .into(),
+                    VectorInstruction::Load(MemoryArgument::with_memory(0, 4, 0)).into(),
+                    VectorInstruction::StoreLane(
+                        IntegerVectorShape::I8x16,
+                        MemoryArgument::with_memory(0, 0, 0),
+                        3,
+                    )
+                    .into(),
+                    ControlInstruction::CallIndirect(0, 0).into(),
+                    ControlInstruction::ReturnCallIndirect(0, 0).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+        builder
+            .add_data(Data::active(0, vec![0i32.into()].into(), vec![]))
+            .unwrap();
+        builder
+            .add_element(Element::active(
+                0,
+                vec![VariableInstruction::GlobalGet(0).into()].into(),
+                ReferenceType::Function,
+                vec![],
+            ))
+            .unwrap();
+        builder.add_export(Export::global("global".into(), 0));
+
+        let mut names = NameSection::new();
+        names.global_names_mut().push((0, "counter".into()));
+        builder.add_custom_section(ModuleSection::Data, Custom::from(&names));
+
+        let imports = [
+            Import::table("env".into(), "table".into(), table),
+            Import::memory("env".into(), "memory".into(), memory),
+            Import::global("env".into(), "global".into(), global),
+        ];
+
+        for import in imports {
+            assert_eq!(builder.add_import(import).unwrap(), 0);
+        }
+
+        assert_eq!(builder.table_index_space(), IndexSpace::new(1, 1));
+        assert_eq!(builder.memory_index_space(), IndexSpace::new(1, 1));
+        assert_eq!(builder.global_index_space(), IndexSpace::new(1, 1));
+        assert_eq!(builder.function_index_space(), IndexSpace::new(0, 1));
+        assert_eq!(
+            builder.functions().unwrap()[0].body(),
+            &vec![
+                VariableInstruction::GlobalGet(1).into(),
+                VariableInstruction::GlobalSet(1).into(),
+                TableInstruction::Copy(1, 1).into(),
+                TableInstruction::Get(1).into(),
+                TableInstruction::Init(0, 1).into(),
+                MemoryInstruction::Size(1).into(),
+                MemoryInstruction::Copy(1, 1).into(),
+                MemoryInstruction::Load(NumberType::I32, MemoryArgument::with_memory(1, 2, 4))
+                    .into(),
+                MemoryInstruction::Store8(IntegerType::I64, MemoryArgument::with_memory(1, 0, 0))
+                    .into(),
+                AtomicInstruction::Notify(MemoryArgument::with_memory(1, 2, 0)).into(),
+                AtomicInstruction::ReadModifyWrite(
+                    AtomicOperation::Add,
+                    IntegerType::I32,
+                    MemoryArgument::with_memory(1, 2, 0),
+                )
+                .into(),
+                VectorInstruction::Load(MemoryArgument::with_memory(1, 4, 0)).into(),
+                VectorInstruction::StoreLane(
+                    IntegerVectorShape::I8x16,
+                    MemoryArgument::with_memory(1, 0, 0),
+                    3,
+                )
+                .into(),
+                ControlInstruction::CallIndirect(0, 1).into(),
+                ControlInstruction::ReturnCallIndirect(0, 1).into(),
+            ]
+            .into()
+        );
+        assert_eq!(
+            builder.data().unwrap()[0].mode(),
+            &DataMode::Active(1, vec![0i32.into()].into())
+        );
+        assert_eq!(
+            builder.elements().unwrap()[0].mode(),
+            &ElementMode::Active(1, vec![VariableInstruction::GlobalGet(1).into()].into())
+        );
+        assert_eq!(
+            builder.exports().unwrap()[0],
+            Export::global("global".into(), 1)
+        );
+
+        let names = builder.build().name_section().unwrap().unwrap();
+
+        assert_eq!(names.global_names(), &vec![(1, "counter".into())]);
+    }
+
     #[test]
     fn module_equality_not_same_custom_sections() {
         let mut builder = Module::builder();
@@ -1486,4 +4004,95 @@ mod tests {
 
         assert_ne!(module, other_module);
     }
+
+    #[test]
+    fn segment_layouts() {
+        let mut builder = Module::builder();
+        let memory = MemoryType::with_index_type(IndexType::I64, Limit::unbounded(1), false);
+
+        builder
+            .add_import(Import::global(
+                "env".into(),
+                "base".into(),
+                GlobalType::immutable(ValueType::I32),
+            ))
+            .unwrap();
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder.add_memory(memory.into()).unwrap();
+        builder
+            .add_data(Data::active(0, vec![(-1i32).into()].into(), vec![1]))
+            .unwrap();
+        builder
+            .add_data(Data::active(1, vec![8i64.into()].into(), vec![2, 3]))
+            .unwrap();
+        builder
+            .add_data(Data::active(
+                1,
+                vec![
+                    VariableInstruction::GlobalGet(0).into(),
+                    1i32.into(),
+                    NumericInstruction::Add(NumberType::I32).into(),
+                ]
+                .into(),
+                vec![4],
+            ))
+            .unwrap();
+        builder
+            .add_data(Data::active(1, vec![9i64.into()].into(), vec![5, 6]))
+            .unwrap();
+
+        let module = builder.build();
+        let layout = module.data_layout(&[Value::I32(7)]).unwrap();
+
+        assert_eq!(
+            layout,
+            vec![
+                SegmentLayout::new(0, 0, u32::MAX as u64, 1),
+                SegmentLayout::new(1, 1, 8, 2),
+                SegmentLayout::new(2, 1, 8, 1),
+                SegmentLayout::new(3, 1, 9, 2),
+            ]
+        );
+        assert_eq!(SegmentLayout::overlapping(&layout), vec![(1, 2), (1, 3)]);
+        assert!(matches!(
+            module.data_layout(&[Value::F32(7.0)]),
+            Err(ModelError::InvalidConstantOperands(2))
+        ));
+        assert!(matches!(
+            module.data_layout(&[Value::Null(HeapType::Function)]),
+            Err(ModelError::InvalidConstantOperands(2))
+        ));
+    }
+
+    #[test]
+    fn segment_layout_requires_integer_offsets() {
+        let mut builder = Module::builder();
+
+        builder
+            .add_data(Data::active(0, vec![1f32.into()].into(), vec![1]))
+            .unwrap();
+        builder
+            .add_data(Data::active(
+                0,
+                vec![ControlInstruction::Nop.into()].into(),
+                vec![1],
+            ))
+            .unwrap();
+
+        let mut module = builder.build();
+
+        assert!(matches!(
+            module.data_layout(&[]),
+            Err(ModelError::InvalidSegmentOffset(0))
+        ));
+
+        module.data_mut().unwrap()[0] = Data::passive(vec![1]);
+
+        assert!(matches!(
+            module.data_layout(&[]),
+            Err(ModelError::NonConstantInstruction(0))
+        ));
+    }
 }
@@ -0,0 +1,519 @@
+//! Model for the name section, a custom section that associates debug names with the indices of a module.
+
+use crate::leb128::{encode_unsigned, parse_unsigned};
+use crate::model::{Custom, FunctionIndex, LocalIndex, ModelError, Name};
+use std::convert::TryFrom;
+
+/// A map from indices to names, ordered by index.
+pub type NameMap = Vec<(u32, Name)>;
+
+/// A map from indices to name maps, such as the names of the locals of each function.
+pub type IndirectNameMap = Vec<(u32, NameMap)>;
+
+/// The name section is a custom section whose name string is itself ‘𝚗𝚊𝚖𝚎’.
+/// It provides debug names for the module and its definitions, organized into subsections.
+/// Besides the module, function and local names of the core specification,
+/// the subsections of the extended name section proposal are supported as well.
+///
+/// See <https://webassembly.github.io/spec/core/appendix/custom.html#name-section>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{Custom, Name, NameSection};
+/// use std::convert::TryFrom;
+///
+/// let mut names = NameSection::new();
+///
+/// names.set_module_name(Some("math".into()));
+/// names.function_names_mut().push((0, "add".into()));
+/// names.local_names_mut().push((0, vec![(0, "x".into()), (1, "y".into())]));
+///
+/// let custom = Custom::from(&names);
+///
+/// assert_eq!(custom.name(), &Name::from(NameSection::NAME));
+/// assert_eq!(names.module_name(), Some(&Name::from("math")));
+/// assert_eq!(names.function_name(0), Some(&Name::from("add")));
+/// assert_eq!(names.local_name(0, 1), Some(&Name::from("y")));
+/// assert_eq!(names.function_name(1), None);
+/// assert_eq!(NameSection::try_from(&custom).unwrap(), names);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NameSection {
+    module: Option<Name>,
+    functions: NameMap,
+    locals: IndirectNameMap,
+    labels: IndirectNameMap,
+    types: NameMap,
+    tables: NameMap,
+    memories: NameMap,
+    globals: NameMap,
+    elements: NameMap,
+    data: NameMap,
+    fields: IndirectNameMap,
+    tags: NameMap,
+    parameters: IndirectNameMap,
+    tag_parameters: IndirectNameMap,
+}
+
+impl NameSection {
+    /// The name of the custom section that holds the debug names.
+    pub const NAME: &'static str = "name";
+
+    /// Creates a new empty name section.
+    pub fn new() -> Self {
+        NameSection::default()
+    }
+
+    /// Whether the name section does not name anything.
+    pub fn is_empty(&self) -> bool {
+        self.module.is_none()
+            && self.functions.is_empty()
+            && self.locals.is_empty()
+            && self.labels.is_empty()
+            && self.types.is_empty()
+            && self.tables.is_empty()
+            && self.memories.is_empty()
+            && self.globals.is_empty()
+            && self.elements.is_empty()
+            && self.data.is_empty()
+            && self.fields.is_empty()
+            && self.tags.is_empty()
+            && self.parameters.is_empty()
+            && self.tag_parameters.is_empty()
+    }
+
+    /// The name of the module.
+    pub fn module_name(&self) -> Option<&Name> {
+        self.module.as_ref()
+    }
+
+    /// Sets the name of the module.
+    pub fn set_module_name(&mut self, name: Option<Name>) {
+        self.module = name;
+    }
+
+    /// The debug name of the function with the given index, if any.
+    pub fn function_name(&self, function: FunctionIndex) -> Option<&Name> {
+        find(&self.functions, function)
+    }
+
+    /// The debug name of a local of the function with the given index, if any.
+    pub fn local_name(&self, function: FunctionIndex, local: LocalIndex) -> Option<&Name> {
+        find_indirect(&self.locals, function, local)
+    }
+
+    /// The names of the functions, including imported functions.
+    pub fn function_names(&self) -> &NameMap {
+        &self.functions
+    }
+
+    /// Mutable reference to the names of the functions.
+    pub fn function_names_mut(&mut self) -> &mut NameMap {
+        &mut self.functions
+    }
+
+    /// The names of the locals (including parameters) of each function.
+    pub fn local_names(&self) -> &IndirectNameMap {
+        &self.locals
+    }
+
+    /// Mutable reference to the names of the locals of each function.
+    pub fn local_names_mut(&mut self) -> &mut IndirectNameMap {
+        &mut self.locals
+    }
+
+    /// The names of the labels of each function,
+    /// indexed by the order of the structured instructions in the function body.
+    pub fn label_names(&self) -> &IndirectNameMap {
+        &self.labels
+    }
+
+    /// Mutable reference to the names of the labels of each function.
+    pub fn label_names_mut(&mut self) -> &mut IndirectNameMap {
+        &mut self.labels
+    }
+
+    /// The names of the types.
+    pub fn type_names(&self) -> &NameMap {
+        &self.types
+    }
+
+    /// Mutable reference to the names of the types.
+    pub fn type_names_mut(&mut self) -> &mut NameMap {
+        &mut self.types
+    }
+
+    /// The names of the tables, including imported tables.
+    pub fn table_names(&self) -> &NameMap {
+        &self.tables
+    }
+
+    /// Mutable reference to the names of the tables.
+    pub fn table_names_mut(&mut self) -> &mut NameMap {
+        &mut self.tables
+    }
+
+    /// The names of the memories, including imported memories.
+    pub fn memory_names(&self) -> &NameMap {
+        &self.memories
+    }
+
+    /// Mutable reference to the names of the memories.
+    pub fn memory_names_mut(&mut self) -> &mut NameMap {
+        &mut self.memories
+    }
+
+    /// The names of the globals, including imported globals.
+    pub fn global_names(&self) -> &NameMap {
+        &self.globals
+    }
+
+    /// Mutable reference to the names of the globals.
+    pub fn global_names_mut(&mut self) -> &mut NameMap {
+        &mut self.globals
+    }
+
+    /// The names of the element segments.
+    pub fn element_names(&self) -> &NameMap {
+        &self.elements
+    }
+
+    /// Mutable reference to the names of the element segments.
+    pub fn element_names_mut(&mut self) -> &mut NameMap {
+        &mut self.elements
+    }
+
+    /// The names of the data segments.
+    pub fn data_names(&self) -> &NameMap {
+        &self.data
+    }
+
+    /// Mutable reference to the names of the data segments.
+    pub fn data_names_mut(&mut self) -> &mut NameMap {
+        &mut self.data
+    }
+
+    /// The names of the tags, including imported tags.
+    pub fn tag_names(&self) -> &NameMap {
+        &self.tags
+    }
+
+    /// Mutable reference to the names of the tags.
+    pub fn tag_names_mut(&mut self) -> &mut NameMap {
+        &mut self.tags
+    }
+
+    /// The names of the fields of each struct type.
+    pub fn field_names(&self) -> &IndirectNameMap {
+        &self.fields
+    }
+
+    /// Mutable reference to the names of the fields of each struct type.
+    pub fn field_names_mut(&mut self) -> &mut IndirectNameMap {
+        &mut self.fields
+    }
+
+    /// The names of the parameters of each function type.
+    pub fn parameter_names(&self) -> &IndirectNameMap {
+        &self.parameters
+    }
+
+    /// Mutable reference to the names of the parameters of each function type.
+    pub fn parameter_names_mut(&mut self) -> &mut IndirectNameMap {
+        &mut self.parameters
+    }
+
+    /// The names of the parameters of each tag.
+    pub fn tag_parameter_names(&self) -> &IndirectNameMap {
+        &self.tag_parameters
+    }
+
+    /// Mutable reference to the names of the parameters of each tag.
+    pub fn tag_parameter_names_mut(&mut self) -> &mut IndirectNameMap {
+        &mut self.tag_parameters
+    }
+
+    /// Encodes the names as the contents of a name section.
+    /// Subsections are emitted in order of their ids, omitting empty subsections.
+    ///
+    /// See <https://webassembly.github.io/spec/core/appendix/custom.html#subsections>
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+
+        if let Some(module) = &self.module {
+            let mut contents = Vec::new();
+
+            encode_name(module, &mut contents);
+            encode_subsection(0, &contents, &mut bytes);
+        }
+
+        encode_name_map(1, &self.functions, &mut bytes);
+        encode_indirect_name_map(2, &self.locals, &mut bytes);
+        encode_indirect_name_map(3, &self.labels, &mut bytes);
+        encode_name_map(4, &self.types, &mut bytes);
+        encode_name_map(5, &self.tables, &mut bytes);
+        encode_name_map(6, &self.memories, &mut bytes);
+        encode_name_map(7, &self.globals, &mut bytes);
+        encode_name_map(8, &self.elements, &mut bytes);
+        encode_name_map(9, &self.data, &mut bytes);
+        encode_indirect_name_map(10, &self.fields, &mut bytes);
+        encode_name_map(11, &self.tags, &mut bytes);
+        encode_indirect_name_map(12, &self.parameters, &mut bytes);
+        encode_indirect_name_map(13, &self.tag_parameters, &mut bytes);
+
+        bytes
+    }
+
+    /// Decodes the contents of a name section.
+    /// Subsections must appear at most once and in order of their ids.
+    /// Subsections with unknown ids are skipped.
+    ///
+    /// See <https://webassembly.github.io/spec/core/appendix/custom.html#subsections>
+    pub fn decode(bytes: &[u8]) -> Result<Self, ModelError> {
+        let mut names = NameSection::new();
+        let mut reader = Reader { bytes };
+        let mut previous = None;
+
+        while !reader.bytes.is_empty() {
+            let id = reader.byte()?;
+            let size = reader.u32()?;
+            let mut subsection = Reader {
+                bytes: reader.take(size as usize)?,
+            };
+
+            if matches!(previous, Some(previous) if id <= previous) {
+                return Err(ModelError::InvalidNameSection(
+                    "The subsections are out of order.",
+                ));
+            }
+
+            previous = Some(id);
+
+            match id {
+                0 => names.module = Some(subsection.name()?),
+                1 => names.functions = subsection.name_map()?,
+                2 => names.locals = subsection.indirect_name_map()?,
+                3 => names.labels = subsection.indirect_name_map()?,
+                4 => names.types = subsection.name_map()?,
+                5 => names.tables = subsection.name_map()?,
+                6 => names.memories = subsection.name_map()?,
+                7 => names.globals = subsection.name_map()?,
+                8 => names.elements = subsection.name_map()?,
+                9 => names.data = subsection.name_map()?,
+                10 => names.fields = subsection.indirect_name_map()?,
+                11 => names.tags = subsection.name_map()?,
+                12 => names.parameters = subsection.indirect_name_map()?,
+                13 => names.tag_parameters = subsection.indirect_name_map()?,
+                _ => continue,
+            }
+
+            if !subsection.bytes.is_empty() {
+                return Err(ModelError::InvalidNameSection(
+                    "A subsection has unexpected trailing bytes.",
+                ));
+            }
+        }
+
+        Ok(names)
+    }
+}
+
+impl From<&NameSection> for Custom {
+    fn from(names: &NameSection) -> Self {
+        Custom::new(NameSection::NAME.into(), names.encode())
+    }
+}
+
+impl TryFrom<&Custom> for NameSection {
+    type Error = ModelError;
+
+    fn try_from(custom: &Custom) -> Result<Self, Self::Error> {
+        if custom.name().value() != NameSection::NAME {
+            return Err(ModelError::InvalidNameSection(
+                "The custom section is not named 'name'.",
+            ));
+        }
+
+        NameSection::decode(custom.bytes())
+    }
+}
+
+fn find(names: &NameMap, index: u32) -> Option<&Name> {
+    names
+        .iter()
+        .find(|(candidate, _)| *candidate == index)
+        .map(|(_, name)| name)
+}
+
+fn find_indirect(names: &IndirectNameMap, outer: u32, inner: u32) -> Option<&Name> {
+    names
+        .iter()
+        .find(|(candidate, _)| *candidate == outer)
+        .and_then(|(_, names)| find(names, inner))
+}
+
+/// Reads the values of a name section from a byte slice.
+struct Reader<'input> {
+    bytes: &'input [u8],
+}
+
+impl<'input> Reader<'input> {
+    fn byte(&mut self) -> Result<u8, ModelError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, ModelError> {
+        let (remaining, value) = parse_unsigned(self.bytes).map_err(|_| {
+            ModelError::InvalidNameSection("Expected a LEB128-encoded unsigned integer.")
+        })?;
+
+        self.bytes = remaining;
+
+        Ok(value)
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'input [u8], ModelError> {
+        if length > self.bytes.len() {
+            return Err(ModelError::InvalidNameSection("Unexpected end of input."));
+        }
+
+        let (taken, remaining) = self.bytes.split_at(length);
+
+        self.bytes = remaining;
+
+        Ok(taken)
+    }
+
+    fn name(&mut self) -> Result<Name, ModelError> {
+        let length = self.u32()?;
+        let bytes = self.take(length as usize)?;
+        let value = std::str::from_utf8(bytes)
+            .map_err(|_| ModelError::InvalidNameSection("A name is not valid UTF-8."))?;
+
+        Ok(value.into())
+    }
+
+    fn name_map(&mut self) -> Result<NameMap, ModelError> {
+        let length = self.u32()?;
+
+        (0..length)
+            .map(|_| Ok((self.u32()?, self.name()?)))
+            .collect()
+    }
+
+    fn indirect_name_map(&mut self) -> Result<IndirectNameMap, ModelError> {
+        let length = self.u32()?;
+
+        (0..length)
+            .map(|_| Ok((self.u32()?, self.name_map()?)))
+            .collect()
+    }
+}
+
+fn encode_u32(value: u32, bytes: &mut Vec<u8>) {
+    encode_unsigned(value, bytes).expect("Writing to a vector should not fail.");
+}
+
+fn encode_name(name: &Name, bytes: &mut Vec<u8>) {
+    encode_u32(name.len() as u32, bytes);
+    bytes.extend_from_slice(name.as_bytes());
+}
+
+fn encode_subsection(id: u8, contents: &[u8], bytes: &mut Vec<u8>) {
+    bytes.push(id);
+    encode_u32(contents.len() as u32, bytes);
+    bytes.extend_from_slice(contents);
+}
+
+fn encode_names(names: &NameMap, bytes: &mut Vec<u8>) {
+    encode_u32(names.len() as u32, bytes);
+
+    for (index, name) in names {
+        encode_u32(*index, bytes);
+        encode_name(name, bytes);
+    }
+}
+
+fn encode_name_map(id: u8, names: &NameMap, bytes: &mut Vec<u8>) {
+    if names.is_empty() {
+        return;
+    }
+
+    let mut contents = Vec::new();
+
+    encode_names(names, &mut contents);
+    encode_subsection(id, &contents, bytes);
+}
+
+fn encode_indirect_name_map(id: u8, names: &IndirectNameMap, bytes: &mut Vec<u8>) {
+    if names.is_empty() {
+        return;
+    }
+
+    let mut contents = Vec::new();
+
+    encode_u32(names.len() as u32, &mut contents);
+
+    for (index, names) in names {
+        encode_u32(*index, &mut contents);
+        encode_names(names, &mut contents);
+    }
+
+    encode_subsection(id, &contents, bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode() {
+        let mut names = NameSection::new();
+
+        names.set_module_name(Some("m".into()));
+        names.function_names_mut().push((1, "f".into()));
+        names.local_names_mut().push((1, vec![(0, "x".into())]));
+
+        assert!(NameSection::new().is_empty());
+        assert!(!names.is_empty());
+        assert_eq!(
+            names.encode(),
+            vec![
+                0x00, 0x02, 0x01, b'm', //
+                0x01, 0x04, 0x01, 0x01, 0x01, b'f', //
+                0x02, 0x06, 0x01, 0x01, 0x01, 0x00, 0x01, b'x',
+            ]
+        );
+        assert_eq!(NameSection::decode(&names.encode()).unwrap(), names);
+
+        names.field_names_mut().push((2, vec![(1, "v".into())]));
+
+        assert_eq!(
+            names.encode()[18..],
+            [0x0A, 0x06, 0x01, 0x02, 0x01, 0x01, 0x01, b'v']
+        );
+        assert_eq!(NameSection::decode(&names.encode()).unwrap(), names);
+    }
+
+    #[test]
+    fn decode_skips_unknown_subsections() {
+        let bytes = vec![
+            0x01, 0x04, 0x01, 0x00, 0x01, b'f', //
+            0x0B, 0x04, 0x01, 0x02, 0x01, b't', //
+            0x0E, 0x02, 0xFF, 0xFF,
+        ];
+        let names = NameSection::decode(&bytes).unwrap();
+
+        assert_eq!(names.function_name(0), Some(&Name::from("f")));
+        assert_eq!(names.tag_names(), &vec![(2, Name::from("t"))]);
+    }
+
+    #[test]
+    fn decode_invalid() {
+        assert!(NameSection::decode(&[0x01, 0x04, 0x01, 0x00]).is_err());
+        assert!(NameSection::decode(&[0x01, 0x01, 0x00, 0x01, 0x01, 0x00]).is_err());
+        assert!(NameSection::decode(&[0x00, 0x02, 0x01, 0xFF]).is_err());
+        assert!(NameSection::decode(&[0x01, 0x02, 0x00, 0x00]).is_err());
+        assert!(NameSection::try_from(&Custom::new("names".into(), Vec::new())).is_err());
+    }
+}
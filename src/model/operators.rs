@@ -0,0 +1,390 @@
+//! A flat view of expressions, in the order of the binary format.
+
+use crate::model::{BlockType, Catch, ControlInstruction, Expression, Instruction, ModelError};
+
+/// An operator of the flat representation of an expression.
+/// Structured control instructions are split into an opening operator,
+/// an optional 𝖾𝗅𝗌𝖾 operator and a closing 𝖾𝗇𝖽 operator, as in the binary format.
+/// All other instructions are represented as themselves.
+///
+/// See <https://webassembly.github.io/spec/core/binary/instructions.html#control-instructions>
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operator {
+    /// Opens a 𝖻𝗅𝗈𝖼𝗄 instruction.
+    Block(BlockType),
+    /// Opens a 𝗅𝗈𝗈𝗉 instruction.
+    Loop(BlockType),
+    /// Opens an 𝗂𝖿 instruction.
+    If(BlockType),
+    /// Opens a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+    TryTable(BlockType, Vec<Catch>),
+    /// Separates the branches of an 𝗂𝖿 instruction.
+    Else,
+    /// Closes a structured instruction or the expression itself.
+    End,
+    /// Any instruction that is not structured.
+    Instruction(Instruction),
+}
+
+impl From<Instruction> for Operator {
+    fn from(instruction: Instruction) -> Self {
+        Operator::Instruction(instruction)
+    }
+}
+
+/// An operator of a flat expression, along with its position in the operator stream and its
+/// nesting depth. The depth counts the structured instructions enclosing the operator,
+/// so the operators that open, separate and close a structured instruction have the same depth
+/// as the instruction itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlatOperator {
+    position: usize,
+    depth: usize,
+    operator: Operator,
+}
+
+impl FlatOperator {
+    /// The zero-based position of the operator in the operator stream.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The number of structured instructions enclosing the operator.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The operator itself.
+    pub fn operator(&self) -> &Operator {
+        &self.operator
+    }
+}
+
+impl From<FlatOperator> for Operator {
+    fn from(operator: FlatOperator) -> Self {
+        operator.operator
+    }
+}
+
+/// An iterator over the operators of an expression, in the order of the binary format.
+/// Created by `Expression::operators`.
+pub struct Operators<'expression> {
+    frames: Vec<Frame<'expression>>,
+    position: usize,
+}
+
+/// The remaining instructions of an expression being iterated,
+/// along with the 𝖾𝗅𝗌𝖾 branch of an 𝗂𝖿 instruction that is yet to be visited.
+struct Frame<'expression> {
+    instructions: std::slice::Iter<'expression, Instruction>,
+    otherwise: Option<&'expression Expression>,
+}
+
+impl<'expression> Frame<'expression> {
+    fn new(
+        expression: &'expression Expression,
+        otherwise: Option<&'expression Expression>,
+    ) -> Self {
+        Frame {
+            instructions: expression.instructions().iter(),
+            otherwise,
+        }
+    }
+}
+
+impl<'expression> Operators<'expression> {
+    /// The next operator along with its depth.
+    fn next_operator(&mut self) -> Option<(usize, Operator)> {
+        let depth = self.frames.len().checked_sub(1)?;
+        let frame = self.frames.last_mut()?;
+
+        let instruction = match frame.instructions.next() {
+            Some(instruction) => instruction,
+            None => {
+                if let Some(otherwise) = frame.otherwise.take() {
+                    frame.instructions = otherwise.instructions().iter();
+
+                    return Some((depth - 1, Operator::Else));
+                }
+
+                self.frames.pop();
+
+                return Some((depth.saturating_sub(1), Operator::End));
+            }
+        };
+
+        let operator = match instruction {
+            Instruction::Control(ControlInstruction::Block(kind, body)) => {
+                self.frames.push(Frame::new(body, None));
+                Operator::Block(*kind)
+            }
+            Instruction::Control(ControlInstruction::Loop(kind, body)) => {
+                self.frames.push(Frame::new(body, None));
+                Operator::Loop(*kind)
+            }
+            Instruction::Control(ControlInstruction::If(kind, then, otherwise)) => {
+                self.frames.push(Frame::new(then, otherwise.as_ref()));
+                Operator::If(*kind)
+            }
+            Instruction::Control(ControlInstruction::TryTable(kind, catches, body)) => {
+                self.frames.push(Frame::new(body, None));
+                Operator::TryTable(*kind, catches.clone())
+            }
+            instruction => Operator::Instruction(instruction.clone()),
+        };
+
+        Some((depth, operator))
+    }
+}
+
+impl<'expression> Iterator for Operators<'expression> {
+    type Item = FlatOperator;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, operator) = self.next_operator()?;
+        let position = self.position;
+
+        self.position += 1;
+
+        Some(FlatOperator {
+            position,
+            depth,
+            operator,
+        })
+    }
+}
+
+/// A structured instruction whose operators are being collected.
+enum Open {
+    Block(BlockType),
+    Loop(BlockType),
+    If(BlockType),
+    Else(BlockType, Expression),
+    TryTable(BlockType, Vec<Catch>),
+}
+
+impl Open {
+    fn close(self, body: Expression) -> Instruction {
+        match self {
+            Open::Block(kind) => ControlInstruction::Block(kind, body),
+            Open::Loop(kind) => ControlInstruction::Loop(kind, body),
+            Open::If(kind) => ControlInstruction::If(kind, body, None),
+            Open::Else(kind, then) => ControlInstruction::If(kind, then, Some(body)),
+            Open::TryTable(kind, catches) => ControlInstruction::TryTable(kind, catches, body),
+        }
+        .into()
+    }
+}
+
+impl Expression {
+    /// Iterates over the operators of the expression in the order of the binary format.
+    /// Structured instructions are flattened into their opening, 𝖾𝗅𝗌𝖾 and 𝖾𝗇𝖽 operators,
+    /// and the expression itself is terminated by a final 𝖾𝗇𝖽 operator.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use wasm_ast::{BlockType, ControlInstruction, Expression, Operator};
+    ///
+    /// let expression = Expression::new(vec![
+    ///     0i32.into(),
+    ///     ControlInstruction::If(
+    ///         BlockType::None,
+    ///         vec![ControlInstruction::Nop.into()].into(),
+    ///         Some(Expression::empty()),
+    ///     )
+    ///     .into(),
+    /// ]);
+    /// let operators: Vec<(usize, usize, Operator)> = expression
+    ///     .operators()
+    ///     .map(|operator| (operator.position(), operator.depth(), operator.operator().clone()))
+    ///     .collect();
+    ///
+    /// assert_eq!(
+    ///     operators,
+    ///     vec![
+    ///         (0, 0, Operator::Instruction(0i32.into())),
+    ///         (1, 0, Operator::If(BlockType::None)),
+    ///         (2, 1, Operator::Instruction(ControlInstruction::Nop.into())),
+    ///         (3, 0, Operator::Else),
+    ///         (4, 0, Operator::End),
+    ///         (5, 0, Operator::End),
+    ///     ]
+    /// );
+    /// assert_eq!(Expression::from_operators(expression.operators()).unwrap(), expression);
+    /// ```
+    pub fn operators(&self) -> Operators<'_> {
+        Operators {
+            frames: vec![Frame::new(self, None)],
+            position: 0,
+        }
+    }
+
+    /// Rebuilds a nested expression from a flat stream of operators,
+    /// such as the one produced by `Expression::operators`.
+    /// The stream must end with the 𝖾𝗇𝖽 operator that terminates the expression,
+    /// and every structured instruction must be closed by its own 𝖾𝗇𝖽 operator.
+    pub fn from_operators<I>(operators: I) -> Result<Expression, ModelError>
+    where
+        I: IntoIterator,
+        I::Item: Into<Operator>,
+    {
+        let mut open: Vec<(Open, Vec<Instruction>)> = Vec::new();
+        let mut instructions = Vec::new();
+        let mut operators = operators.into_iter().map(Into::into).enumerate();
+
+        for (position, operator) in operators.by_ref() {
+            let opened = match operator {
+                Operator::Instruction(instruction) => {
+                    instructions.push(instruction);
+                    continue;
+                }
+                Operator::Block(kind) => Open::Block(kind),
+                Operator::Loop(kind) => Open::Loop(kind),
+                Operator::If(kind) => Open::If(kind),
+                Operator::TryTable(kind, catches) => Open::TryTable(kind, catches),
+                Operator::Else => match open.pop() {
+                    Some((Open::If(kind), outer)) => {
+                        let then = std::mem::replace(&mut instructions, outer);
+
+                        Open::Else(kind, then.into())
+                    }
+                    _ => return Err(ModelError::UnexpectedElse(position)),
+                },
+                Operator::End => match open.pop() {
+                    Some((opened, outer)) => {
+                        let body = std::mem::replace(&mut instructions, outer);
+
+                        instructions.push(opened.close(body.into()));
+                        continue;
+                    }
+                    None => {
+                        return match operators.next() {
+                            Some((position, _)) => Err(ModelError::UnbalancedEnd(position)),
+                            None => Ok(instructions.into()),
+                        }
+                    }
+                },
+            };
+
+            open.push((opened, std::mem::take(&mut instructions)));
+        }
+
+        Err(ModelError::MissingEnd(open.len() + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ParametricInstruction, ValueType};
+
+    fn nested() -> Expression {
+        vec![
+            ControlInstruction::Block(
+                BlockType::ValueType(ValueType::I32),
+                vec![
+                    ControlInstruction::Loop(
+                        BlockType::None,
+                        vec![ControlInstruction::Branch(0).into()].into(),
+                    )
+                    .into(),
+                    1i32.into(),
+                ]
+                .into(),
+            )
+            .into(),
+            ControlInstruction::If(
+                BlockType::None,
+                vec![ControlInstruction::TryTable(
+                    BlockType::None,
+                    vec![Catch::CatchAll(0)],
+                    Expression::empty(),
+                )
+                .into()]
+                .into(),
+                None,
+            )
+            .into(),
+            ParametricInstruction::Drop.into(),
+        ]
+        .into()
+    }
+
+    #[test]
+    fn operators() {
+        let expression = nested();
+        let operators: Vec<(usize, Operator)> = expression
+            .operators()
+            .map(|operator| (operator.depth(), operator.into()))
+            .collect();
+
+        assert_eq!(
+            operators,
+            vec![
+                (0, Operator::Block(BlockType::ValueType(ValueType::I32))),
+                (1, Operator::Loop(BlockType::None)),
+                (
+                    2,
+                    Operator::Instruction(ControlInstruction::Branch(0).into())
+                ),
+                (1, Operator::End),
+                (1, Operator::Instruction(1i32.into())),
+                (0, Operator::End),
+                (0, Operator::If(BlockType::None)),
+                (
+                    1,
+                    Operator::TryTable(BlockType::None, vec![Catch::CatchAll(0)])
+                ),
+                (1, Operator::End),
+                (0, Operator::End),
+                (0, Operator::Instruction(ParametricInstruction::Drop.into())),
+                (0, Operator::End),
+            ]
+        );
+        assert_eq!(
+            expression
+                .operators()
+                .last()
+                .map(|operator| operator.position()),
+            Some(11)
+        );
+        assert_eq!(
+            Expression::from_operators(expression.operators()).unwrap(),
+            expression
+        );
+        assert_eq!(
+            Expression::from_operators(Expression::empty().operators()).unwrap(),
+            Expression::empty()
+        );
+    }
+
+    #[test]
+    fn unbalanced_operators() {
+        let operators = |operators: Vec<Operator>| Expression::from_operators(operators);
+
+        assert!(matches!(
+            operators(vec![Operator::Block(BlockType::None), Operator::End]),
+            Err(ModelError::MissingEnd(1))
+        ));
+        assert!(matches!(
+            operators(vec![Operator::Loop(BlockType::None)]),
+            Err(ModelError::MissingEnd(2))
+        ));
+        assert!(matches!(
+            operators(vec![Operator::End, Operator::End]),
+            Err(ModelError::UnbalancedEnd(1))
+        ));
+        assert!(matches!(
+            operators(vec![Operator::Block(BlockType::None), Operator::Else]),
+            Err(ModelError::UnexpectedElse(1))
+        ));
+        assert!(matches!(
+            operators(vec![
+                Operator::If(BlockType::None),
+                Operator::Else,
+                Operator::Else
+            ]),
+            Err(ModelError::UnexpectedElse(2))
+        ));
+    }
+}
@@ -1,5 +1,8 @@
 //! Model for types in the WebAssembly syntax.
 
+use crate::model::TypeIndex;
+use std::hash::{Hash, Hasher};
+
 /// Number types classify numeric values.
 /// Number types are transparent, meaning that their bit patterns can be observed.
 /// Values of number type can be stored in memories.
@@ -94,15 +97,202 @@ pub enum FloatType {
 ///
 /// assert_eq!(ValueType::FunctionReference, ReferenceType::Function.into());
 /// assert_eq!(ValueType::ExternalReference, ReferenceType::External.into());
+/// assert_eq!(ValueType::ExceptionReference, ReferenceType::Exception.into());
 /// ```
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+///
+/// ## Typed References
+/// ```rust
+/// use wasm_ast::{ValueType, ReferenceType, HeapType};
+///
+/// assert_eq!(ReferenceType::nullable_reference(HeapType::Function), ReferenceType::Function);
+/// assert_eq!(
+///     ValueType::NullableReference(HeapType::Any),
+///     ReferenceType::nullable_reference(HeapType::Any).into()
+/// );
+/// assert_eq!(
+///     ValueType::Reference(HeapType::Index(1)),
+///     ReferenceType::Reference(HeapType::Index(1)).into()
+/// );
+/// assert_eq!(ReferenceType::External.heap_type(), HeapType::External);
+/// assert!(!ReferenceType::Reference(HeapType::Index(1)).is_nullable());
+/// assert_eq!(ReferenceType::try_from(ValueType::FunctionReference), Ok(ReferenceType::Function));
+/// assert_eq!(ReferenceType::try_from(ValueType::I32), Err(ValueType::I32));
+/// ```
+///
+/// ## Abbreviations
+/// The abbreviated and the written out forms of a nullable reference are the same type.
+/// ```rust
+/// use wasm_ast::{ReferenceType, HeapType};
+///
+/// assert_eq!(ReferenceType::NullableReference(HeapType::Function), ReferenceType::Function);
+/// assert_ne!(ReferenceType::Reference(HeapType::Function), ReferenceType::Function);
+/// ```
+#[derive(Copy, Clone, Debug, Eq)]
 pub enum ReferenceType {
     Function,
     External,
+    /// A reference to an exception package caught by a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+    ///
+    /// See <https://webassembly.github.io/exception-handling/core/syntax/types.html#reference-types>
+    Exception,
+    /// A non-nullable reference to an object of the given heap type.
+    ///
+    /// See <https://webassembly.github.io/function-references/core/syntax/types.html#reference-types>
+    Reference(HeapType),
+    /// A nullable reference to an object of the given heap type.
+    /// Nullable references to the 𝖿𝗎𝗇𝖼, 𝖾𝗑𝗍𝖾𝗋𝗇 and 𝖾𝗑𝗇 heap types are written as
+    /// 𝖿𝗎𝗇𝖼𝗋𝖾𝖿, 𝖾𝗑𝗍𝖾𝗋𝗇𝗋𝖾𝖿 and 𝖾𝗑𝗇𝗋𝖾𝖿 instead.
+    NullableReference(HeapType),
+}
+
+impl ReferenceType {
+    /// The nullable reference type to the given heap type,
+    /// using the abbreviations of the abstract heap types.
+    pub fn nullable_reference(heap: HeapType) -> Self {
+        match heap {
+            HeapType::Function => ReferenceType::Function,
+            HeapType::External => ReferenceType::External,
+            HeapType::Exception => ReferenceType::Exception,
+            heap => ReferenceType::NullableReference(heap),
+        }
+    }
+
+    /// The heap type of the objects this reference type refers to.
+    pub fn heap_type(&self) -> HeapType {
+        match self {
+            ReferenceType::Function => HeapType::Function,
+            ReferenceType::External => HeapType::External,
+            ReferenceType::Exception => HeapType::Exception,
+            ReferenceType::Reference(heap) | ReferenceType::NullableReference(heap) => *heap,
+        }
+    }
+
+    /// Returns true if this reference type admits null values, false otherwise.
+    pub fn is_nullable(&self) -> bool {
+        !matches!(self, ReferenceType::Reference(_))
+    }
+}
+
+impl PartialEq for ReferenceType {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_nullable() == other.is_nullable() && self.heap_type() == other.heap_type()
+    }
+}
+
+impl Hash for ReferenceType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.is_nullable().hash(state);
+        self.heap_type().hash(state);
+    }
+}
+
+impl TryFrom<ValueType> for ReferenceType {
+    type Error = ValueType;
+
+    fn try_from(kind: ValueType) -> Result<Self, Self::Error> {
+        match kind {
+            ValueType::Reference(heap) => Ok(ReferenceType::Reference(heap)),
+            kind => match kind.heap_type() {
+                Some(heap) => Ok(ReferenceType::nullable_reference(heap)),
+                None => Err(kind),
+            },
+        }
+    }
+}
+
+/// Heap types classify the objects that references point to.
+/// The abstract heap types form a hierarchy for each of internal, external, function and exception references,
+/// with a bottom type (e.g., 𝗇𝗈𝗇𝖾 or 𝗇𝗈𝖿𝗎𝗇𝖼) below every other type of the hierarchy.
+/// A type index denotes objects of a specific type defined by the module.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#heap-types>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{HeapType, ReferenceType};
+///
+/// assert_eq!(HeapType::Function, ReferenceType::Function.into());
+/// assert_eq!(HeapType::External, ReferenceType::External.into());
+/// assert_eq!(HeapType::Exception, ReferenceType::Exception.into());
+/// assert!(HeapType::I31.matches(HeapType::Any));
+/// assert!(HeapType::None.matches(HeapType::Struct));
+/// assert!(!HeapType::NoFunction.matches(HeapType::Any));
+/// assert!(!HeapType::Index(0).matches(HeapType::Function));
+/// assert_eq!(HeapType::Struct.top(), Some(HeapType::Any));
+/// assert_eq!(HeapType::Function.bottom(), Some(HeapType::NoFunction));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum HeapType {
+    Function,
+    External,
+    Exception,
+    Any,
+    Eq,
+    I31,
+    Struct,
+    Array,
+    None,
+    NoFunction,
+    NoExtern,
+    NoException,
+    Index(TypeIndex),
+}
+
+impl HeapType {
+    /// Returns true if this heap type is a subtype of the given heap type in the hierarchy of abstract heap types.
+    /// Concrete heap types only match themselves, since relating them to other heap types requires the types of a module.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/matching.html#heap-types>
+    pub fn matches(&self, other: HeapType) -> bool {
+        use HeapType::*;
+
+        *self == other
+            || matches!(
+                (self, other),
+                (Eq | I31 | Struct | Array | None, Any)
+                    | (I31 | Struct | Array | None, Eq)
+                    | (None, I31 | Struct | Array)
+                    | (NoFunction, Function)
+                    | (NoExtern, External)
+                    | (NoException, Exception)
+            )
+    }
+
+    /// The top type of the hierarchy this abstract heap type belongs to, or `None` for type indices.
+    pub fn top(&self) -> Option<HeapType> {
+        match self {
+            HeapType::Function | HeapType::NoFunction => Some(HeapType::Function),
+            HeapType::External | HeapType::NoExtern => Some(HeapType::External),
+            HeapType::Exception | HeapType::NoException => Some(HeapType::Exception),
+            HeapType::Any
+            | HeapType::Eq
+            | HeapType::I31
+            | HeapType::Struct
+            | HeapType::Array
+            | HeapType::None => Some(HeapType::Any),
+            HeapType::Index(_) => Option::None,
+        }
+    }
+
+    /// The bottom type of the hierarchy this abstract heap type belongs to, or `None` for type indices.
+    pub fn bottom(&self) -> Option<HeapType> {
+        match self.top()? {
+            HeapType::Function => Some(HeapType::NoFunction),
+            HeapType::External => Some(HeapType::NoExtern),
+            HeapType::Exception => Some(HeapType::NoException),
+            _ => Some(HeapType::None),
+        }
+    }
+}
+
+impl From<ReferenceType> for HeapType {
+    fn from(kind: ReferenceType) -> Self {
+        kind.heap_type()
+    }
 }
 
 /// Value types classify the individual values that WebAssembly code can compute with and the values that a variable accepts.
-/// They are either number types or reference types.
+/// They are either number types, vector types or reference types.
 ///
 /// See <https://webassembly.github.io/spec/core/syntax/types.html#value-types>
 ///
@@ -120,15 +310,133 @@ pub enum ReferenceType {
 /// assert_eq!(ValueType::F64, NumberType::F64.into());
 /// assert_eq!(ValueType::FunctionReference, ReferenceType::Function.into());
 /// assert_eq!(ValueType::ExternalReference, ReferenceType::External.into());
+/// assert_eq!(ValueType::ExceptionReference, ReferenceType::Exception.into());
 /// ```
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+///
+/// ## Typed References
+/// ```rust
+/// use wasm_ast::{ValueType, HeapType};
+///
+/// assert_eq!(ValueType::nullable_reference(HeapType::Function), ValueType::FunctionReference);
+/// assert_eq!(
+///     ValueType::nullable_reference(HeapType::Index(1)),
+///     ValueType::NullableReference(HeapType::Index(1))
+/// );
+/// assert!(ValueType::Reference(HeapType::Index(1)).matches(ValueType::nullable_reference(HeapType::Index(1))));
+/// assert!(ValueType::Reference(HeapType::NoFunction).matches(ValueType::FunctionReference));
+/// assert!(ValueType::nullable_reference(HeapType::I31).matches(ValueType::nullable_reference(HeapType::Eq)));
+/// assert!(!ValueType::FunctionReference.matches(ValueType::Reference(HeapType::Function)));
+/// assert_eq!(ValueType::Reference(HeapType::External).heap_type(), Some(HeapType::External));
+/// assert_eq!(ValueType::I32.heap_type(), None);
+/// ```
+///
+/// ## Abbreviations
+/// The abbreviated and the written out forms of a nullable reference are the same type.
+/// ```rust
+/// use wasm_ast::{ValueType, HeapType};
+///
+/// assert_eq!(ValueType::NullableReference(HeapType::External), ValueType::ExternalReference);
+/// assert_ne!(ValueType::Reference(HeapType::External), ValueType::ExternalReference);
+/// ```
+#[derive(Copy, Clone, Debug, Eq)]
 pub enum ValueType {
     I32,
     I64,
     F32,
     F64,
+    /// A 128-bit vector of packed integer or floating-point data.
+    ///
+    /// See <https://webassembly.github.io/spec/core/syntax/types.html#vector-types>
+    V128,
     FunctionReference,
     ExternalReference,
+    ExceptionReference,
+    /// A non-nullable reference to an object of the given heap type.
+    ///
+    /// See <https://webassembly.github.io/function-references/core/syntax/types.html#reference-types>
+    Reference(HeapType),
+    /// A nullable reference to an object of the given heap type.
+    /// Nullable references to the 𝖿𝗎𝗇𝖼, 𝖾𝗑𝗍𝖾𝗋𝗇 and 𝖾𝗑𝗇 heap types are written as
+    /// 𝖿𝗎𝗇𝖼𝗋𝖾𝖿, 𝖾𝗑𝗍𝖾𝗋𝗇𝗋𝖾𝖿 and 𝖾𝗑𝗇𝗋𝖾𝖿 instead.
+    NullableReference(HeapType),
+}
+
+impl ValueType {
+    /// The nullable reference type to the given heap type,
+    /// using the abbreviations of the abstract heap types.
+    pub fn nullable_reference(heap: HeapType) -> Self {
+        match heap {
+            HeapType::Function => ValueType::FunctionReference,
+            HeapType::External => ValueType::ExternalReference,
+            HeapType::Exception => ValueType::ExceptionReference,
+            heap => ValueType::NullableReference(heap),
+        }
+    }
+
+    /// The heap type of a reference type, or `None` for number and vector types.
+    pub fn heap_type(&self) -> Option<HeapType> {
+        match self {
+            ValueType::FunctionReference => Some(HeapType::Function),
+            ValueType::ExternalReference => Some(HeapType::External),
+            ValueType::ExceptionReference => Some(HeapType::Exception),
+            ValueType::Reference(heap) | ValueType::NullableReference(heap) => Some(*heap),
+            _ => None,
+        }
+    }
+
+    /// Returns true if this is a reference type, false otherwise.
+    pub fn is_reference(&self) -> bool {
+        self.heap_type().is_some()
+    }
+
+    /// Returns true if this is a reference type that admits null values, false otherwise.
+    pub fn is_nullable(&self) -> bool {
+        self.is_reference() && !matches!(self, ValueType::Reference(_))
+    }
+
+    /// Returns true if this value type is a subtype of the given value type.
+    /// Non-nullable references match nullable ones, and heap types are matched using `HeapType::matches`.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/matching.html#reference-types>
+    pub fn matches(&self, other: ValueType) -> bool {
+        match (self.heap_type(), other.heap_type()) {
+            (Some(heap), Some(other_heap)) => {
+                (!self.is_nullable() || other.is_nullable()) && heap.matches(other_heap)
+            }
+            _ => *self == other,
+        }
+    }
+
+    /// The value type with nullable references to the abstract 𝖿𝗎𝗇𝖼, 𝖾𝗑𝗍𝖾𝗋𝗇 and 𝖾𝗑𝗇 heap types abbreviated.
+    fn abbreviated(&self) -> Self {
+        match self {
+            ValueType::NullableReference(heap) => ValueType::nullable_reference(*heap),
+            kind => *kind,
+        }
+    }
+}
+
+impl PartialEq for ValueType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.abbreviated(), other.abbreviated()) {
+            (ValueType::Reference(heap), ValueType::Reference(other_heap))
+            | (ValueType::NullableReference(heap), ValueType::NullableReference(other_heap)) => {
+                heap == other_heap
+            }
+            (kind, other_kind) => {
+                std::mem::discriminant(&kind) == std::mem::discriminant(&other_kind)
+            }
+        }
+    }
+}
+
+impl Hash for ValueType {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let kind = self.abbreviated();
+
+        std::mem::discriminant(&kind).hash(state);
+        kind.heap_type().hash(state);
+    }
 }
 
 impl<T> From<T> for ValueType
@@ -148,8 +456,8 @@ where
 impl From<ReferenceType> for ValueType {
     fn from(kind: ReferenceType) -> Self {
         match kind {
-            ReferenceType::Function => ValueType::FunctionReference,
-            ReferenceType::External => ValueType::ExternalReference,
+            ReferenceType::Reference(heap) => ValueType::Reference(heap),
+            kind => ValueType::nullable_reference(kind.heap_type()),
         }
     }
 }
@@ -345,6 +653,303 @@ impl FunctionType {
     }
 }
 
+/// Packed types are storage types narrower than any value type, used by the fields of structures and arrays.
+/// Values of packed type are sign- or zero-extended to 𝗂𝟥𝟤 when read.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#aggregate-types>
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum PackedType {
+    I8,
+    I16,
+}
+
+/// Storage types classify the values stored in the fields of structures and the elements of arrays.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#aggregate-types>
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum StorageType {
+    Value(ValueType),
+    Packed(PackedType),
+}
+
+impl StorageType {
+    /// The value type of the values read from storage of this type.
+    pub fn unpacked(&self) -> ValueType {
+        match self {
+            StorageType::Value(kind) => *kind,
+            StorageType::Packed(_) => ValueType::I32,
+        }
+    }
+}
+
+impl From<ValueType> for StorageType {
+    fn from(kind: ValueType) -> Self {
+        StorageType::Value(kind)
+    }
+}
+
+impl From<PackedType> for StorageType {
+    fn from(kind: PackedType) -> Self {
+        StorageType::Packed(kind)
+    }
+}
+
+/// Field types classify the fields of structures and the elements of arrays by their storage type and mutability.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#aggregate-types>
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct FieldType {
+    mutability: Mutability,
+    kind: StorageType,
+}
+
+impl FieldType {
+    /// Creates a new `FieldType` with the given storage type and mutability.
+    pub fn new(kind: impl Into<StorageType>, mutability: Mutability) -> Self {
+        FieldType {
+            mutability,
+            kind: kind.into(),
+        }
+    }
+
+    /// Creates a new mutable `FieldType`.
+    pub fn mutable(kind: impl Into<StorageType>) -> Self {
+        FieldType::new(kind, Mutability::Mutable)
+    }
+
+    /// Creates a new immutable `FieldType`.
+    pub fn immutable(kind: impl Into<StorageType>) -> Self {
+        FieldType::new(kind, Mutability::Immutable)
+    }
+
+    /// The storage type of the field.
+    pub fn kind(&self) -> StorageType {
+        self.kind
+    }
+
+    /// The mutability of the field.
+    pub fn mutability(&self) -> Mutability {
+        self.mutability
+    }
+}
+
+/// Structure types classify heap-allocated structures with a sequence of heterogeneous fields.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#aggregate-types>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StructType {
+    fields: Vec<FieldType>,
+}
+
+impl StructType {
+    /// Creates a new `StructType` with the given fields.
+    pub fn new(fields: Vec<FieldType>) -> Self {
+        StructType { fields }
+    }
+
+    /// The types of the fields of the structure.
+    pub fn fields(&self) -> &[FieldType] {
+        &self.fields
+    }
+}
+
+/// Array types classify heap-allocated arrays of a homogeneous sequence of elements.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#aggregate-types>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ArrayType {
+    field: FieldType,
+}
+
+impl ArrayType {
+    /// Creates a new `ArrayType` with the given element type.
+    pub fn new(field: FieldType) -> Self {
+        ArrayType { field }
+    }
+
+    /// The type of the elements of the array.
+    pub fn field(&self) -> FieldType {
+        self.field
+    }
+}
+
+/// Composite types are the structural definitions of the types in a module:
+/// function, structure or array types.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#composite-types>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CompositeType {
+    Function(FunctionType),
+    Struct(StructType),
+    Array(ArrayType),
+}
+
+impl CompositeType {
+    /// The abstract heap type that references to this composite type are a subtype of.
+    pub fn heap_type(&self) -> HeapType {
+        match self {
+            CompositeType::Function(_) => HeapType::Function,
+            CompositeType::Struct(_) => HeapType::Struct,
+            CompositeType::Array(_) => HeapType::Array,
+        }
+    }
+}
+
+impl From<FunctionType> for CompositeType {
+    fn from(kind: FunctionType) -> Self {
+        CompositeType::Function(kind)
+    }
+}
+
+impl From<StructType> for CompositeType {
+    fn from(kind: StructType) -> Self {
+        CompositeType::Struct(kind)
+    }
+}
+
+impl From<ArrayType> for CompositeType {
+    fn from(kind: ArrayType) -> Self {
+        CompositeType::Array(kind)
+    }
+}
+
+/// Sub types declare a composite type along with its declared supertypes.
+/// A final type cannot be declared as the supertype of another type.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#recursive-types>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{CompositeType, FunctionType, SubType};
+///
+/// let kind = SubType::from(FunctionType::runnable());
+///
+/// assert!(kind.is_final());
+/// assert!(kind.supertypes().is_empty());
+/// assert_eq!(kind.function_type(), Some(&FunctionType::runnable()));
+/// assert_eq!(
+///     SubType::new(false, vec![0], FunctionType::runnable().into()).kind(),
+///     &CompositeType::Function(FunctionType::runnable())
+/// );
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SubType {
+    is_final: bool,
+    supertypes: Vec<TypeIndex>,
+    kind: CompositeType,
+}
+
+impl SubType {
+    /// Creates a new `SubType` of the given composite type.
+    pub fn new(is_final: bool, supertypes: Vec<TypeIndex>, kind: CompositeType) -> Self {
+        SubType {
+            is_final,
+            supertypes,
+            kind,
+        }
+    }
+
+    /// True if the type cannot have subtypes.
+    pub fn is_final(&self) -> bool {
+        self.is_final
+    }
+
+    /// The declared supertypes of the type.
+    pub fn supertypes(&self) -> &[TypeIndex] {
+        &self.supertypes
+    }
+
+    /// The composite type of the type.
+    pub fn kind(&self) -> &CompositeType {
+        &self.kind
+    }
+
+    /// The function type of the type, if it is a function type.
+    pub fn function_type(&self) -> Option<&FunctionType> {
+        match &self.kind {
+            CompositeType::Function(kind) => Some(kind),
+            _ => None,
+        }
+    }
+}
+
+impl<T> From<T> for SubType
+where
+    T: Into<CompositeType>,
+{
+    fn from(kind: T) -> Self {
+        SubType::new(true, Vec::new(), kind.into())
+    }
+}
+
+/// Recursive types group the types of a module that may refer to each other.
+/// Each entry of the type section is a recursive type,
+/// and the sub types of all recursive types form the type index space in order.
+/// Types that are not part of an explicit group are recursive types with a single sub type.
+///
+/// See <https://webassembly.github.io/gc/core/syntax/types.html#recursive-types>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{
+///     ArrayType, FieldType, FunctionType, PackedType, RecursiveType, StructType, SubType,
+///     ValueType, HeapType,
+/// };
+///
+/// let list = StructType::new(vec![
+///     FieldType::immutable(ValueType::I32),
+///     FieldType::immutable(ValueType::NullableReference(HeapType::Index(0))),
+/// ]);
+/// let group = RecursiveType::new(vec![
+///     list.into(),
+///     ArrayType::new(FieldType::mutable(PackedType::I8)).into(),
+/// ]);
+///
+/// assert_eq!(group.len(), 2);
+/// assert!(!group.is_empty());
+/// assert_eq!(RecursiveType::from(FunctionType::runnable()).types(), &[SubType::from(FunctionType::runnable())]);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecursiveType {
+    types: Vec<SubType>,
+}
+
+impl RecursiveType {
+    /// Creates a new `RecursiveType` from the given sub types.
+    pub fn new(types: Vec<SubType>) -> Self {
+        RecursiveType { types }
+    }
+
+    /// The sub types of the group.
+    pub fn types(&self) -> &[SubType] {
+        &self.types
+    }
+
+    /// Mutable reference to the sub types of the group.
+    pub fn types_mut(&mut self) -> &mut [SubType] {
+        &mut self.types
+    }
+
+    /// The number of sub types in the group.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// True if the group has no sub types.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+}
+
+impl<T> From<T> for RecursiveType
+where
+    T: Into<SubType>,
+{
+    fn from(kind: T) -> Self {
+        RecursiveType::new(vec![kind.into()])
+    }
+}
+
 /// Limits classify the size range of resizeable storage associated with memory types and table types.
 ///
 /// See <https://webassembly.github.io/spec/core/syntax/types.html#limits>
@@ -378,23 +983,23 @@ impl FunctionType {
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Limit {
-    min: u32,
-    max: Option<u32>,
+    min: u64,
+    max: Option<u64>,
 }
 
 impl Limit {
     /// Creates a new limit with a required minimum and optional maximum.
-    pub fn new(min: u32, max: Option<u32>) -> Self {
+    pub fn new(min: u64, max: Option<u64>) -> Self {
         Limit { min, max }
     }
 
     /// Creates a new limit with a required minimum and no maximum.
-    pub fn unbounded(min: u32) -> Self {
+    pub fn unbounded(min: u64) -> Self {
         Limit { min, max: None }
     }
 
     /// Creates a new limit with a required minimum and maximum.
-    pub fn bounded(min: u32, max: u32) -> Self {
+    pub fn bounded(min: u64, max: u64) -> Self {
         Limit {
             min,
             max: Some(max),
@@ -402,19 +1007,49 @@ impl Limit {
     }
 
     /// The minimum value of the limit.
-    pub fn min(&self) -> u32 {
+    pub fn min(&self) -> u64 {
         self.min
     }
 
     /// The optional maximum value of the limit.
-    pub fn max(&self) -> Option<u32> {
+    pub fn max(&self) -> Option<u64> {
         self.max
     }
 }
 
+/// The index type of a memory or table, which determines the type of the addresses
+/// of a memory and of the indices into a table.
+/// Memories and tables with a 64-bit index type also allow 64-bit limits.
+///
+/// See <https://webassembly.github.io/memory64/core/syntax/types.html#index-types>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{IndexType, ValueType};
+///
+/// assert_eq!(ValueType::from(IndexType::I32), ValueType::I32);
+/// assert_eq!(ValueType::from(IndexType::I64), ValueType::I64);
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum IndexType {
+    I32,
+    I64,
+}
+
+impl From<IndexType> for ValueType {
+    fn from(kind: IndexType) -> Self {
+        match kind {
+            IndexType::I32 => ValueType::I32,
+            IndexType::I64 => ValueType::I64,
+        }
+    }
+}
+
 /// Memory types classify linear memories and their size range.
 /// The limits constrain the minimum and optionally the maximum size of a memory.
 /// The limits are given in units of page size.
+/// Shared memories may be accessed by multiple threads at once.
+/// The index type determines whether the memory is addressed with 32-bit or 64-bit addresses.
 ///
 /// See <https://webassembly.github.io/spec/core/syntax/types.html#memory-types>
 ///
@@ -426,28 +1061,79 @@ impl Limit {
 /// let memory_type = MemoryType::new(limit.clone());
 ///
 /// assert_eq!(memory_type.limits(), &limit);
+/// assert!(!memory_type.is_shared());
 /// assert_eq!(memory_type, limit.into());
 /// ```
+///
+/// ## Shared
+/// ```rust
+/// use wasm_ast::{Limit, MemoryType};
+///
+/// let limit = Limit::bounded(1, 2);
+/// let memory_type = MemoryType::shared(limit.clone());
+///
+/// assert_eq!(memory_type.limits(), &limit);
+/// assert!(memory_type.is_shared());
+/// assert_ne!(memory_type, limit.into());
+/// ```
+///
+/// ## 64-bit
+/// ```rust
+/// use wasm_ast::{IndexType, Limit, MemoryType};
+///
+/// let limit = Limit::unbounded(1 << 40);
+/// let memory_type = MemoryType::with_index_type(IndexType::I64, limit, false);
+///
+/// assert_eq!(memory_type.index_type(), IndexType::I64);
+/// assert_eq!(MemoryType::new(limit).index_type(), IndexType::I32);
+/// assert_ne!(memory_type, limit.into());
+/// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct MemoryType {
+    index_type: IndexType,
     limits: Limit,
+    shared: bool,
 }
 
 impl MemoryType {
     /// Creates a new memory type from the given limits.
     pub fn new(limit: Limit) -> Self {
-        MemoryType { limits: limit }
+        MemoryType::with_index_type(IndexType::I32, limit, false)
+    }
+
+    /// Creates a new shared memory type from the given limits.
+    pub fn shared(limit: Limit) -> Self {
+        MemoryType::with_index_type(IndexType::I32, limit, true)
+    }
+
+    /// Creates a new memory type with the given index type, limits and sharing.
+    pub fn with_index_type(index_type: IndexType, limit: Limit, shared: bool) -> Self {
+        MemoryType {
+            index_type,
+            limits: limit,
+            shared,
+        }
+    }
+
+    /// The type of the addresses of this `MemoryType`.
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
     }
 
     /// The limits of the number of pages for this `MemoryType`.
     pub fn limits(&self) -> &Limit {
         &self.limits
     }
+
+    /// Whether the memory may be shared between threads.
+    pub fn is_shared(&self) -> bool {
+        self.shared
+    }
 }
 
 impl From<Limit> for MemoryType {
     fn from(limit: Limit) -> Self {
-        MemoryType { limits: limit }
+        MemoryType::new(limit)
     }
 }
 
@@ -459,16 +1145,40 @@ impl From<Limit> for MemoryType {
 ///
 /// # Examples
 /// ```rust
-/// use wasm_ast::{Limit, TableType, ReferenceType};
+/// use wasm_ast::{IndexType, Limit, TableType, ReferenceType};
 ///
 /// let limit = Limit::unbounded(0);
 /// let table_type = TableType::new( ReferenceType::External,limit.clone());
 ///
 /// assert_eq!(table_type.limits(), &limit);
 /// assert_eq!(table_type.kind(), ReferenceType::External);
+/// assert_eq!(table_type.index_type(), IndexType::I32);
+/// ```
+///
+/// ## 64-bit
+/// ```rust
+/// use wasm_ast::{IndexType, Limit, TableType, ReferenceType};
+///
+/// let limit = Limit::unbounded(0);
+/// let table_type = TableType::with_index_type(IndexType::I64, ReferenceType::Function, limit);
+///
+/// assert_eq!(table_type.index_type(), IndexType::I64);
+/// assert_ne!(table_type, TableType::new(ReferenceType::Function, limit));
+/// ```
+///
+/// ## Typed References
+/// ```rust
+/// use wasm_ast::{HeapType, Limit, TableType, ReferenceType};
+///
+/// let kind = ReferenceType::nullable_reference(HeapType::Index(0));
+/// let table_type = TableType::new(kind, Limit::unbounded(0));
+///
+/// assert_eq!(table_type.kind(), ReferenceType::NullableReference(HeapType::Index(0)));
+/// assert!(table_type.kind().is_nullable());
 /// ```
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct TableType {
+    index_type: IndexType,
     limits: Limit,
     kind: ReferenceType,
 }
@@ -476,7 +1186,21 @@ pub struct TableType {
 impl TableType {
     /// Creates a new `TableType` for the given limits and reference type.
     pub fn new(kind: ReferenceType, limits: Limit) -> Self {
-        TableType { limits, kind }
+        TableType::with_index_type(IndexType::I32, kind, limits)
+    }
+
+    /// Creates a new `TableType` with the given index type, reference type and limits.
+    pub fn with_index_type(index_type: IndexType, kind: ReferenceType, limits: Limit) -> Self {
+        TableType {
+            index_type,
+            limits,
+            kind,
+        }
+    }
+
+    /// The type of the indices into this `TableType`.
+    pub fn index_type(&self) -> IndexType {
+        self.index_type
     }
 
     /// The limits of the number of elements for this `TableType`.
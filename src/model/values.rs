@@ -1,5 +1,7 @@
 //! Model for values in the WebAssembly syntax.
 
+use crate::model::{FunctionIndex, HeapType};
+
 /// Names are sequences of characters, which are scalar values as defined by Unicode (Section 2.4).
 /// Due to the limitations of the binary format,
 /// the length of a name is bounded by the length of its UTF-8 encoding.
@@ -64,3 +66,56 @@ impl From<String> for Name {
         Name { value: name }
     }
 }
+
+/// Values are the results of evaluating expressions,
+/// such as the constant expressions initializing globals and offsetting segments.
+/// Reference values are limited to those that can be formed without a store.
+///
+/// See <https://webassembly.github.io/spec/core/exec/runtime.html#values>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{HeapType, Value};
+///
+/// assert_eq!(Value::from(42i32), Value::I32(42));
+/// assert_eq!(Value::from(42i64), Value::I64(42));
+/// assert_eq!(Value::from(0.5f32), Value::F32(0.5));
+/// assert_eq!(Value::from(0.5f64), Value::F64(0.5));
+/// assert_ne!(Value::Null(HeapType::Function), Value::Function(0));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Value {
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+    V128(i128),
+    /// A null reference of the given heap type.
+    Null(HeapType),
+    /// A reference to the function at the given index.
+    Function(FunctionIndex),
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::I32(value)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Value::I64(value)
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Value::F32(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::F64(value)
+    }
+}
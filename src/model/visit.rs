@@ -0,0 +1,652 @@
+//! Traversal of the components and instructions of a module.
+//!
+//! The `Visit` and `VisitMut` traits have a method per component of a module and per category of
+//! instructions. Each method defaults to visiting the nested nodes through the `walk_*` function
+//! of the same name, so an implementation only needs to override the nodes it cares about.
+//! An overriding method can call the `walk_*` function itself to keep descending into nested nodes.
+//!
+//! Indices passed to the visitor are in the index space of the module,
+//! so function, table, memory, tag and global indices account for imports.
+
+use crate::model::{
+    AtomicInstruction, ControlInstruction, Data, DataIndex, DataMode, Element, ElementIndex,
+    ElementMode, Export, Expression, Function, FunctionIndex, GcInstruction, Global, GlobalIndex,
+    Import, ImportDescription, Instruction, Memory, MemoryIndex, MemoryInstruction, Module,
+    NumericInstruction, ParametricInstruction, ReferenceInstruction, Start, SubType, Table,
+    TableIndex, TableInstruction, Tag, TagIndex, TypeIndex, VariableInstruction, VectorInstruction,
+};
+
+/// Visits the components and instructions of a module by reference.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{ControlInstruction, Expression, FunctionIndex, Visit};
+///
+/// #[derive(Default)]
+/// struct Calls(Vec<FunctionIndex>);
+///
+/// impl Visit for Calls {
+///     fn visit_control(&mut self, instruction: &ControlInstruction) {
+///         if let ControlInstruction::Call(function) = instruction {
+///             self.0.push(*function);
+///         }
+///
+///         wasm_ast::visit::walk_control(self, instruction);
+///     }
+/// }
+///
+/// let body = Expression::from(vec![
+///     ControlInstruction::Call(0).into(),
+///     ControlInstruction::Loop(
+///         wasm_ast::BlockType::None,
+///         vec![ControlInstruction::Call(1).into()].into(),
+///     )
+///     .into(),
+/// ]);
+/// let mut calls = Calls::default();
+///
+/// calls.visit_expression(&body);
+///
+/// assert_eq!(calls.0, vec![0, 1]);
+/// ```
+pub trait Visit {
+    /// Visits every component of the module, in the order of the binary format.
+    fn visit_module(&mut self, module: &Module) {
+        walk_module(self, module);
+    }
+
+    fn visit_sub_type(&mut self, _index: TypeIndex, _kind: &SubType) {}
+
+    fn visit_import(&mut self, _import: &Import) {}
+
+    fn visit_function(&mut self, _index: FunctionIndex, function: &Function) {
+        walk_function(self, function);
+    }
+
+    fn visit_table_definition(&mut self, _index: TableIndex, table: &Table) {
+        walk_table(self, table);
+    }
+
+    fn visit_memory_definition(&mut self, _index: MemoryIndex, _memory: &Memory) {}
+
+    fn visit_tag(&mut self, _index: TagIndex, _tag: &Tag) {}
+
+    fn visit_global(&mut self, _index: GlobalIndex, global: &Global) {
+        walk_global(self, global);
+    }
+
+    fn visit_export(&mut self, _export: &Export) {}
+
+    fn visit_start(&mut self, _start: &Start) {}
+
+    fn visit_element(&mut self, _index: ElementIndex, element: &Element) {
+        walk_element(self, element);
+    }
+
+    fn visit_data(&mut self, _index: DataIndex, data: &Data) {
+        walk_data(self, data);
+    }
+
+    /// Visits every instruction of the expression, in order.
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    /// Dispatches the instruction to the method of its category.
+    fn visit_instruction(&mut self, instruction: &Instruction) {
+        walk_instruction(self, instruction);
+    }
+
+    fn visit_numeric(&mut self, _instruction: &NumericInstruction) {}
+
+    fn visit_vector(&mut self, _instruction: &VectorInstruction) {}
+
+    fn visit_reference(&mut self, _instruction: &ReferenceInstruction) {}
+
+    fn visit_parametric(&mut self, _instruction: &ParametricInstruction) {}
+
+    fn visit_variable(&mut self, _instruction: &VariableInstruction) {}
+
+    fn visit_table(&mut self, _instruction: &TableInstruction) {}
+
+    fn visit_memory(&mut self, _instruction: &MemoryInstruction) {}
+
+    fn visit_atomic(&mut self, _instruction: &AtomicInstruction) {}
+
+    fn visit_gc(&mut self, _instruction: &GcInstruction) {}
+
+    /// Visits a control instruction, descending into the expressions of structured instructions.
+    fn visit_control(&mut self, instruction: &ControlInstruction) {
+        walk_control(self, instruction);
+    }
+}
+
+/// Visits the components and instructions of a module by mutable reference.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{BlockType, ControlInstruction, Expression, VisitMut};
+///
+/// struct Offset(u32);
+///
+/// impl VisitMut for Offset {
+///     fn visit_control(&mut self, instruction: &mut ControlInstruction) {
+///         if let ControlInstruction::Call(function) = instruction {
+///             *function += self.0;
+///         }
+///
+///         wasm_ast::visit::walk_control_mut(self, instruction);
+///     }
+/// }
+///
+/// let mut body = Expression::from(vec![ControlInstruction::Block(
+///     BlockType::None,
+///     vec![ControlInstruction::Call(1).into()].into(),
+/// )
+/// .into()]);
+///
+/// Offset(2).visit_expression(&mut body);
+///
+/// assert_eq!(
+///     body,
+///     Expression::from(vec![ControlInstruction::Block(
+///         BlockType::None,
+///         vec![ControlInstruction::Call(3).into()].into(),
+///     )
+///     .into()])
+/// );
+/// ```
+pub trait VisitMut {
+    /// Visits every component of the module, in the order of the binary format.
+    fn visit_module(&mut self, module: &mut Module) {
+        walk_module_mut(self, module);
+    }
+
+    fn visit_sub_type(&mut self, _index: TypeIndex, _kind: &mut SubType) {}
+
+    fn visit_import(&mut self, _import: &mut Import) {}
+
+    fn visit_function(&mut self, _index: FunctionIndex, function: &mut Function) {
+        walk_function_mut(self, function);
+    }
+
+    fn visit_table_definition(&mut self, _index: TableIndex, table: &mut Table) {
+        walk_table_mut(self, table);
+    }
+
+    fn visit_memory_definition(&mut self, _index: MemoryIndex, _memory: &mut Memory) {}
+
+    fn visit_tag(&mut self, _index: TagIndex, _tag: &mut Tag) {}
+
+    fn visit_global(&mut self, _index: GlobalIndex, global: &mut Global) {
+        walk_global_mut(self, global);
+    }
+
+    fn visit_export(&mut self, _export: &mut Export) {}
+
+    fn visit_start(&mut self, _start: &mut Start) {}
+
+    fn visit_element(&mut self, _index: ElementIndex, element: &mut Element) {
+        walk_element_mut(self, element);
+    }
+
+    fn visit_data(&mut self, _index: DataIndex, data: &mut Data) {
+        walk_data_mut(self, data);
+    }
+
+    /// Visits every instruction of the expression, in order.
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression_mut(self, expression);
+    }
+
+    /// Dispatches the instruction to the method of its category.
+    fn visit_instruction(&mut self, instruction: &mut Instruction) {
+        walk_instruction_mut(self, instruction);
+    }
+
+    fn visit_numeric(&mut self, _instruction: &mut NumericInstruction) {}
+
+    fn visit_vector(&mut self, _instruction: &mut VectorInstruction) {}
+
+    fn visit_reference(&mut self, _instruction: &mut ReferenceInstruction) {}
+
+    fn visit_parametric(&mut self, _instruction: &mut ParametricInstruction) {}
+
+    fn visit_variable(&mut self, _instruction: &mut VariableInstruction) {}
+
+    fn visit_table(&mut self, _instruction: &mut TableInstruction) {}
+
+    fn visit_memory(&mut self, _instruction: &mut MemoryInstruction) {}
+
+    fn visit_atomic(&mut self, _instruction: &mut AtomicInstruction) {}
+
+    fn visit_gc(&mut self, _instruction: &mut GcInstruction) {}
+
+    /// Visits a control instruction, descending into the expressions of structured instructions.
+    fn visit_control(&mut self, instruction: &mut ControlInstruction) {
+        walk_control_mut(self, instruction);
+    }
+}
+
+/// The number of imports of a module that match the given predicate.
+fn imported(module: &Module, matches: fn(&ImportDescription) -> bool) -> u32 {
+    module
+        .imports()
+        .unwrap_or_default()
+        .iter()
+        .filter(|import| matches(import.description()))
+        .count() as u32
+}
+
+/// The index of the first definition of each index space, following the imports.
+struct ImportCounts {
+    functions: u32,
+    tables: u32,
+    memories: u32,
+    tags: u32,
+    globals: u32,
+}
+
+impl ImportCounts {
+    fn new(module: &Module) -> Self {
+        ImportCounts {
+            functions: imported(module, |import| {
+                matches!(import, ImportDescription::Function(_))
+            }),
+            tables: imported(module, |import| {
+                matches!(import, ImportDescription::Table(_))
+            }),
+            memories: imported(module, |import| {
+                matches!(import, ImportDescription::Memory(_))
+            }),
+            tags: imported(module, |import| matches!(import, ImportDescription::Tag(_))),
+            globals: imported(module, |import| {
+                matches!(import, ImportDescription::Global(_))
+            }),
+        }
+    }
+}
+
+/// Visits every component of the module, in the order of the binary format.
+pub fn walk_module<V: Visit + ?Sized>(visitor: &mut V, module: &Module) {
+    let imports = ImportCounts::new(module);
+
+    for (index, kind) in module.sub_types().enumerate() {
+        visitor.visit_sub_type(index as u32, kind);
+    }
+
+    for import in module.imports().unwrap_or_default() {
+        visitor.visit_import(import);
+    }
+
+    for (index, table) in module.tables().unwrap_or_default().iter().enumerate() {
+        visitor.visit_table_definition(imports.tables + index as u32, table);
+    }
+
+    for (index, memory) in module.memories().unwrap_or_default().iter().enumerate() {
+        visitor.visit_memory_definition(imports.memories + index as u32, memory);
+    }
+
+    for (index, tag) in module.tags().unwrap_or_default().iter().enumerate() {
+        visitor.visit_tag(imports.tags + index as u32, tag);
+    }
+
+    for (index, global) in module.globals().unwrap_or_default().iter().enumerate() {
+        visitor.visit_global(imports.globals + index as u32, global);
+    }
+
+    for export in module.exports().unwrap_or_default() {
+        visitor.visit_export(export);
+    }
+
+    if let Some(start) = module.start() {
+        visitor.visit_start(start);
+    }
+
+    for (index, element) in module.elements().unwrap_or_default().iter().enumerate() {
+        visitor.visit_element(index as u32, element);
+    }
+
+    for (index, function) in module.functions().unwrap_or_default().iter().enumerate() {
+        visitor.visit_function(imports.functions + index as u32, function);
+    }
+
+    for (index, data) in module.data().unwrap_or_default().iter().enumerate() {
+        visitor.visit_data(index as u32, data);
+    }
+}
+
+/// Visits the body of the function.
+pub fn walk_function<V: Visit + ?Sized>(visitor: &mut V, function: &Function) {
+    visitor.visit_expression(function.body());
+}
+
+/// Visits the initializer of the table, if it has one.
+pub fn walk_table<V: Visit + ?Sized>(visitor: &mut V, table: &Table) {
+    if let Some(initializer) = table.initializer() {
+        visitor.visit_expression(initializer);
+    }
+}
+
+/// Visits the initializer of the global.
+pub fn walk_global<V: Visit + ?Sized>(visitor: &mut V, global: &Global) {
+    visitor.visit_expression(global.initializer());
+}
+
+/// Visits the offset of an active element segment, followed by its initializers.
+pub fn walk_element<V: Visit + ?Sized>(visitor: &mut V, element: &Element) {
+    if let ElementMode::Active(_, offset) = element.mode() {
+        visitor.visit_expression(offset);
+    }
+
+    for initializer in element.initializers() {
+        visitor.visit_expression(initializer);
+    }
+}
+
+/// Visits the offset of an active data segment.
+pub fn walk_data<V: Visit + ?Sized>(visitor: &mut V, data: &Data) {
+    if let DataMode::Active(_, offset) = data.mode() {
+        visitor.visit_expression(offset);
+    }
+}
+
+/// Visits every instruction of the expression, in order.
+pub fn walk_expression<V: Visit + ?Sized>(visitor: &mut V, expression: &Expression) {
+    for instruction in expression.instructions() {
+        visitor.visit_instruction(instruction);
+    }
+}
+
+/// Dispatches the instruction to the method of its category.
+pub fn walk_instruction<V: Visit + ?Sized>(visitor: &mut V, instruction: &Instruction) {
+    match instruction {
+        Instruction::Numeric(instruction) => visitor.visit_numeric(instruction),
+        Instruction::Vector(instruction) => visitor.visit_vector(instruction),
+        Instruction::Reference(instruction) => visitor.visit_reference(instruction),
+        Instruction::Parametric(instruction) => visitor.visit_parametric(instruction),
+        Instruction::Variable(instruction) => visitor.visit_variable(instruction),
+        Instruction::Table(instruction) => visitor.visit_table(instruction),
+        Instruction::Memory(instruction) => visitor.visit_memory(instruction),
+        Instruction::Atomic(instruction) => visitor.visit_atomic(instruction),
+        Instruction::Gc(instruction) => visitor.visit_gc(instruction),
+        Instruction::Control(instruction) => visitor.visit_control(instruction),
+    }
+}
+
+/// Visits the expressions nested in a structured control instruction.
+/// The 𝖾𝗅𝗌𝖾 branch of an 𝗂𝖿 instruction is visited after the 𝗍𝗁𝖾𝗇 branch.
+pub fn walk_control<V: Visit + ?Sized>(visitor: &mut V, instruction: &ControlInstruction) {
+    match instruction {
+        ControlInstruction::Block(_, body)
+        | ControlInstruction::Loop(_, body)
+        | ControlInstruction::TryTable(_, _, body) => visitor.visit_expression(body),
+        ControlInstruction::If(_, then, otherwise) => {
+            visitor.visit_expression(then);
+
+            if let Some(otherwise) = otherwise {
+                visitor.visit_expression(otherwise);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Visits every component of the module, in the order of the binary format.
+pub fn walk_module_mut<V: VisitMut + ?Sized>(visitor: &mut V, module: &mut Module) {
+    let imports = ImportCounts::new(module);
+
+    for (index, kind) in module
+        .types_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .flat_map(|group| group.types_mut().iter_mut())
+        .enumerate()
+    {
+        visitor.visit_sub_type(index as u32, kind);
+    }
+
+    for import in module.imports_mut().unwrap_or_default() {
+        visitor.visit_import(import);
+    }
+
+    for (index, table) in module
+        .tables_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .enumerate()
+    {
+        visitor.visit_table_definition(imports.tables + index as u32, table);
+    }
+
+    for (index, memory) in module
+        .memories_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .enumerate()
+    {
+        visitor.visit_memory_definition(imports.memories + index as u32, memory);
+    }
+
+    for (index, tag) in module.tags_mut().unwrap_or_default().iter_mut().enumerate() {
+        visitor.visit_tag(imports.tags + index as u32, tag);
+    }
+
+    for (index, global) in module
+        .globals_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .enumerate()
+    {
+        visitor.visit_global(imports.globals + index as u32, global);
+    }
+
+    for export in module.exports_mut().unwrap_or_default() {
+        visitor.visit_export(export);
+    }
+
+    if let Some(start) = module.start_mut() {
+        visitor.visit_start(start);
+    }
+
+    for (index, element) in module
+        .elements_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .enumerate()
+    {
+        visitor.visit_element(index as u32, element);
+    }
+
+    for (index, function) in module
+        .functions_mut()
+        .unwrap_or_default()
+        .iter_mut()
+        .enumerate()
+    {
+        visitor.visit_function(imports.functions + index as u32, function);
+    }
+
+    for (index, data) in module.data_mut().unwrap_or_default().iter_mut().enumerate() {
+        visitor.visit_data(index as u32, data);
+    }
+}
+
+/// Visits the body of the function.
+pub fn walk_function_mut<V: VisitMut + ?Sized>(visitor: &mut V, function: &mut Function) {
+    visitor.visit_expression(function.body_mut());
+}
+
+/// Visits the initializer of the table, if it has one.
+pub fn walk_table_mut<V: VisitMut + ?Sized>(visitor: &mut V, table: &mut Table) {
+    if let Some(initializer) = table.initializer_mut() {
+        visitor.visit_expression(initializer);
+    }
+}
+
+/// Visits the initializer of the global.
+pub fn walk_global_mut<V: VisitMut + ?Sized>(visitor: &mut V, global: &mut Global) {
+    visitor.visit_expression(global.initializer_mut());
+}
+
+/// Visits the offset of an active element segment, followed by its initializers.
+pub fn walk_element_mut<V: VisitMut + ?Sized>(visitor: &mut V, element: &mut Element) {
+    if let ElementMode::Active(_, offset) = element.mode_mut() {
+        visitor.visit_expression(offset);
+    }
+
+    for initializer in element.initializers_mut() {
+        visitor.visit_expression(initializer);
+    }
+}
+
+/// Visits the offset of an active data segment.
+pub fn walk_data_mut<V: VisitMut + ?Sized>(visitor: &mut V, data: &mut Data) {
+    if let DataMode::Active(_, offset) = data.mode_mut() {
+        visitor.visit_expression(offset);
+    }
+}
+
+/// Visits every instruction of the expression, in order.
+pub fn walk_expression_mut<V: VisitMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    for instruction in expression.instructions_mut() {
+        visitor.visit_instruction(instruction);
+    }
+}
+
+/// Dispatches the instruction to the method of its category.
+pub fn walk_instruction_mut<V: VisitMut + ?Sized>(visitor: &mut V, instruction: &mut Instruction) {
+    match instruction {
+        Instruction::Numeric(instruction) => visitor.visit_numeric(instruction),
+        Instruction::Vector(instruction) => visitor.visit_vector(instruction),
+        Instruction::Reference(instruction) => visitor.visit_reference(instruction),
+        Instruction::Parametric(instruction) => visitor.visit_parametric(instruction),
+        Instruction::Variable(instruction) => visitor.visit_variable(instruction),
+        Instruction::Table(instruction) => visitor.visit_table(instruction),
+        Instruction::Memory(instruction) => visitor.visit_memory(instruction),
+        Instruction::Atomic(instruction) => visitor.visit_atomic(instruction),
+        Instruction::Gc(instruction) => visitor.visit_gc(instruction),
+        Instruction::Control(instruction) => visitor.visit_control(instruction),
+    }
+}
+
+/// Visits the expressions nested in a structured control instruction.
+/// The 𝖾𝗅𝗌𝖾 branch of an 𝗂𝖿 instruction is visited after the 𝗍𝗁𝖾𝗇 branch.
+pub fn walk_control_mut<V: VisitMut + ?Sized>(
+    visitor: &mut V,
+    instruction: &mut ControlInstruction,
+) {
+    match instruction {
+        ControlInstruction::Block(_, body)
+        | ControlInstruction::Loop(_, body)
+        | ControlInstruction::TryTable(_, _, body) => visitor.visit_expression(body),
+        ControlInstruction::If(_, then, otherwise) => {
+            visitor.visit_expression(then);
+
+            if let Some(otherwise) = otherwise {
+                visitor.visit_expression(otherwise);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{BlockType, FunctionType, Limit, ReferenceType, ResultType, ValueType};
+    use crate::ElementInitializer;
+
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl Visit for Trace {
+        fn visit_function(&mut self, index: FunctionIndex, function: &Function) {
+            self.0.push(format!("function {}", index));
+            walk_function(self, function);
+        }
+
+        fn visit_memory_definition(&mut self, index: MemoryIndex, _memory: &Memory) {
+            self.0.push(format!("memory {}", index));
+        }
+
+        fn visit_global(&mut self, index: GlobalIndex, global: &Global) {
+            self.0.push(format!("global {}", index));
+            walk_global(self, global);
+        }
+
+        fn visit_element(&mut self, index: ElementIndex, element: &Element) {
+            self.0.push(format!("element {}", index));
+            walk_element(self, element);
+        }
+
+        fn visit_numeric(&mut self, instruction: &NumericInstruction) {
+            self.0.push(format!("{:?}", instruction));
+        }
+
+        fn visit_reference(&mut self, instruction: &ReferenceInstruction) {
+            self.0.push(format!("{:?}", instruction));
+        }
+    }
+
+    #[test]
+    fn walk_module_in_binary_order() {
+        let mut builder = Module::builder();
+
+        builder.add_function_type(FunctionType::runnable()).unwrap();
+        builder
+            .add_import(Import::function("env".into(), "f".into(), 0))
+            .unwrap();
+        builder
+            .add_import(Import::memory(
+                "env".into(),
+                "m".into(),
+                Limit::unbounded(1).into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![ControlInstruction::If(
+                    BlockType::None,
+                    vec![1i32.into()].into(),
+                    Some(vec![2i32.into()].into()),
+                )
+                .into()]
+                .into(),
+            ))
+            .unwrap();
+        builder.add_memory(Limit::unbounded(1).into()).unwrap();
+        builder
+            .add_global(Global::immutable(ValueType::I64, vec![3i64.into()].into()))
+            .unwrap();
+        builder
+            .add_element(Element::active(
+                0,
+                vec![4i32.into()].into(),
+                ReferenceType::Function,
+                vec![1].to_initializers(),
+            ))
+            .unwrap();
+
+        let mut trace = Trace::default();
+
+        trace.visit_module(&builder.build());
+
+        assert_eq!(
+            trace.0,
+            vec![
+                "memory 1",
+                "global 0",
+                "I64Constant(3)",
+                "element 0",
+                "I32Constant(4)",
+                "Function(1)",
+                "function 1",
+                "I32Constant(1)",
+                "I32Constant(2)",
+            ]
+        );
+    }
+}
@@ -1,24 +1,260 @@
+#[cfg(feature = "text")]
+use crate::parser::text::TextError;
+use crate::ModuleSection;
+use nom::error::{ContextError, ErrorKind, FromExternalError};
+use nom::{InputLength, Offset};
+use std::fmt::{Display, Formatter};
 use thiserror::Error;
 
 /// An error in parser a WebAssembly module.
 #[derive(Error, Debug)]
 pub enum ParseError {
     #[cfg(feature = "text")]
-    #[error(
-        "The WebAssembly module in text format could not be transformed to the binary format."
-    )]
-    InvalidText(#[from] wat::Error),
-    #[error("The WebAssembly module is not a valid binary format.")]
-    InvalidBinary,
+    #[error("The WebAssembly module in text format is not valid: {0}")]
+    InvalidText(#[from] TextError),
+    #[error("The WebAssembly module is not a valid binary format: {0}")]
+    InvalidBinary(#[from] BinaryError),
     #[error(
         "The module's type and code sections have different lengths (type: {0:?}, code: {1:?})."
     )]
     MismatchedFunctionParts(Option<usize>, Option<usize>),
+    #[error(
+        "The {section:?} section at offset {offset:#x} must come before the {previous:?} section."
+    )]
+    OutOfOrderSection {
+        section: ModuleSection,
+        previous: ModuleSection,
+        offset: usize,
+    },
+    #[error("The {section:?} section at offset {offset:#x} appears more than once.")]
+    DuplicateSection {
+        section: ModuleSection,
+        offset: usize,
+    },
+    #[error("The {section:?} section at offset {offset:#x} declares a size of {declared} bytes, but its contents end after {consumed} bytes.")]
+    SectionSizeMismatch {
+        section: ModuleSection,
+        offset: usize,
+        declared: usize,
+        consumed: usize,
+    },
+    #[error(
+        "The data count section declares {count} data segments, but the data section has {data}."
+    )]
+    DataCountMismatch { count: u32, data: usize },
+    #[error("Unable to read the WebAssembly module: {0}")]
+    Io(#[from] std::io::Error),
 }
 
-/// Create a parse error from a nom error.
-impl<T> From<nom::Err<nom::error::Error<T>>> for ParseError {
-    fn from(_: nom::Err<nom::error::Error<T>>) -> Self {
-        ParseError::InvalidBinary
+impl ParseError {
+    /// Creates an error from a failure of the parsers in this module.
+    /// The failure must have occurred within the given module bytes.
+    pub(crate) fn from_nom(module: &[u8], error: nom::Err<DecodeError<&[u8]>>) -> Self {
+        match &error {
+            nom::Err::Error(decode) | nom::Err::Failure(decode) => {
+                if let (Some(section), Some((declared, consumed))) = (decode.section, decode.size) {
+                    return ParseError::SectionSizeMismatch {
+                        section,
+                        offset: module.offset(decode.input),
+                        declared,
+                        consumed,
+                    };
+                }
+            }
+            nom::Err::Incomplete(_) => {}
+        }
+
+        BinaryError::from_nom(module, error).into()
     }
 }
+
+/// An error in parsing a WebAssembly module in the binary format.
+/// Identifies the byte offset from the start of the module where the error occurred,
+/// along with the section being parsed, the construct the parser expected,
+/// and the byte found instead.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BinaryError {
+    offset: usize,
+    section: Option<ModuleSection>,
+    expected: Option<&'static str>,
+    found: Option<u8>,
+}
+
+impl BinaryError {
+    /// Creates a new error at the given offset of the module.
+    pub fn new(
+        offset: usize,
+        section: Option<ModuleSection>,
+        expected: Option<&'static str>,
+        found: Option<u8>,
+    ) -> Self {
+        BinaryError {
+            offset,
+            section,
+            expected,
+            found,
+        }
+    }
+
+    /// Creates an error from a failure of the parsers in this module.
+    /// The failure must have occurred within the given module bytes.
+    pub(crate) fn from_nom(module: &[u8], error: nom::Err<DecodeError<&[u8]>>) -> Self {
+        match error {
+            nom::Err::Error(error) | nom::Err::Failure(error) => {
+                let offset = module.offset(error.input);
+
+                BinaryError::new(
+                    offset,
+                    error.section,
+                    error.expected,
+                    module.get(offset).copied(),
+                )
+            }
+            nom::Err::Incomplete(_) => BinaryError::new(module.len(), None, None, None),
+        }
+    }
+
+    /// Moves the error by the given number of bytes.
+    /// Used when the parsed bytes start at an offset of the module.
+    pub(crate) fn shift(mut self, offset: usize) -> Self {
+        self.offset += offset;
+        self
+    }
+
+    /// The byte offset from the start of the module where the error occurred.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The section being parsed when the error occurred, if any.
+    pub fn section(&self) -> Option<ModuleSection> {
+        self.section
+    }
+
+    /// The construct the parser expected at the offset (e.g. "opcode" or "limits flag").
+    pub fn expected(&self) -> Option<&'static str> {
+        self.expected
+    }
+
+    /// The byte found at the offset, or `None` if the input ended.
+    pub fn found(&self) -> Option<u8> {
+        self.found
+    }
+}
+
+impl Display for BinaryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.expected {
+            Some(expected) => write!(f, "expected {}", expected)?,
+            None => write!(f, "invalid encoding")?,
+        }
+
+        match self.found {
+            Some(found) => write!(f, ", found byte {:#04x}", found)?,
+            None => write!(f, ", found end of input")?,
+        }
+
+        write!(f, " at offset {:#x}", self.offset)?;
+
+        if let Some(section) = self.section {
+            write!(f, " in the {:?} section", section)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for BinaryError {}
+
+/// The error produced by the nom parsers of the binary format.
+/// Keeps the innermost construct the parser expected and the section being parsed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeError<I> {
+    input: I,
+    expected: Option<&'static str>,
+    section: Option<ModuleSection>,
+    size: Option<(usize, usize)>,
+}
+
+impl<I> DecodeError<I> {
+    /// Creates an error expecting the given construct at the start of the input.
+    pub fn expected(input: I, expected: &'static str) -> Self {
+        DecodeError {
+            input,
+            expected: Some(expected),
+            section: None,
+            size: None,
+        }
+    }
+
+    /// Creates an error for a section starting at the input whose contents
+    /// end before its declared size.
+    pub fn section_size(
+        input: I,
+        section: ModuleSection,
+        declared: usize,
+        consumed: usize,
+    ) -> Self {
+        DecodeError {
+            input,
+            expected: Some("end of section"),
+            section: Some(section),
+            size: Some((declared, consumed)),
+        }
+    }
+
+    /// Attributes the error to the given section, unless it already belongs to one.
+    pub fn in_section(mut self, section: ModuleSection) -> Self {
+        self.section.get_or_insert(section);
+        self
+    }
+}
+
+impl<I: InputLength> nom::error::ParseError<I> for DecodeError<I> {
+    fn from_error_kind(input: I, _: ErrorKind) -> Self {
+        DecodeError {
+            input,
+            expected: None,
+            section: None,
+            size: None,
+        }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    /// Keeps the error of the alternative that made the most progress,
+    /// preferring errors that know which construct was expected.
+    fn or(self, other: Self) -> Self {
+        let (remaining, other_remaining) = (self.input.input_len(), other.input.input_len());
+
+        if other_remaining < remaining || (other_remaining == remaining && self.expected.is_none())
+        {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<I> ContextError<I> for DecodeError<I> {
+    fn add_context(_: I, context: &'static str, mut other: Self) -> Self {
+        other.expected.get_or_insert(context);
+        other
+    }
+}
+
+impl<I, E> FromExternalError<I, E> for DecodeError<I> {
+    fn from_external_error(input: I, _: ErrorKind, _: E) -> Self {
+        DecodeError {
+            input,
+            expected: None,
+            section: None,
+            size: None,
+        }
+    }
+}
+
+/// The result of the nom parsers of the binary format.
+pub type IResult<I, O> = nom::IResult<I, O, DecodeError<I>>;
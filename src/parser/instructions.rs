@@ -1,57 +1,106 @@
-use crate::parser::types::{parse_reference_type, parse_value_type};
-use crate::parser::values::{match_byte, parse_s32, parse_s33, parse_s64, parse_u32, parse_vector};
+use crate::parser::errors::{DecodeError, IResult};
+use crate::parser::types::{parse_heap_type, parse_value_type};
+use crate::parser::values::{
+    match_byte, parse_s32, parse_s33, parse_s64, parse_u32, parse_u64, parse_vector,
+};
 use crate::{
-    BlockType, ControlInstruction, Expression, FloatType, Instruction, IntegerType, MemoryArgument,
-    MemoryInstruction, NumberType, NumericInstruction, ParametricInstruction, ReferenceInstruction,
-    SignExtension, TableInstruction, VariableInstruction,
+    AtomicInstruction, AtomicOperation, BlockType, Catch, ControlInstruction, Expression,
+    FloatType, FloatVectorShape, GcInstruction, HeapType, Instruction, IntegerType,
+    IntegerVectorShape, MemoryArgument, MemoryInstruction, NumberType, NumericInstruction,
+    ParametricInstruction, ReferenceInstruction, SignExtension, TableInstruction, ValueType,
+    VariableInstruction, VectorHalf, VectorInstruction, VectorShape,
 };
 use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::map;
-use nom::multi::fold_many0;
-use nom::number::complete::{le_f32, le_f64};
-use nom::sequence::{delimited, preceded, terminated, tuple};
-use nom::IResult;
+use nom::bytes::complete::{tag, take};
+use nom::combinator::{cut, map};
+use nom::error::context;
+use nom::number::complete::{le_f32, le_f64, le_i128, le_u8};
+use nom::sequence::{preceded, tuple};
 
 /// Marks the end of an expression.
 const EXPRESSION_END: u8 = 0x0B;
 
+/// Separates the branches of an 𝗂𝖿 instruction.
+const ELSE: u8 = 0x05;
+
+/// The read-modify-write operations in the order of their groups of atomic opcodes.
+const ATOMIC_OPERATIONS: [AtomicOperation; 7] = [
+    AtomicOperation::Add,
+    AtomicOperation::Subtract,
+    AtomicOperation::And,
+    AtomicOperation::Or,
+    AtomicOperation::Xor,
+    AtomicOperation::Exchange,
+    AtomicOperation::CompareExchange,
+];
+
+/// Bit 6 of the alignment in a memarg marks the presence of an explicit memory index.
+const MEMORY_INDEX_FLAG: u32 = 0x40;
+
 /// Parses a WebAssembly expression from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/instructions.html#expressions>
 pub fn parse_expression(input: &[u8]) -> IResult<&[u8], Expression> {
-    parse_expression_with_terminal(EXPRESSION_END)(input)
+    map(parse_instructions(&[EXPRESSION_END]), |(expression, _)| {
+        expression
+    })(input)
 }
 
-/// Parses a WebAssembly expression with a given terminating opcode from the input.
-fn parse_expression_with_terminal<'input>(
-    terminal: u8,
-) -> impl FnMut(&'input [u8]) -> IResult<&'input [u8], Expression> {
-    map(
-        terminated(
-            fold_many0(parse_instruction, Vec::new, |mut accumulator, item| {
-                accumulator.push(item);
-                accumulator
-            }),
-            match_byte(terminal),
-        ),
-        Expression::new,
-    )
+/// Parses the instructions of an expression up to one of the given terminating opcodes.
+/// Returns the expression along with the terminating opcode.
+/// Any other byte must start a valid instruction, so errors are reported where they occur
+/// instead of at the start of the enclosing structured instruction.
+fn parse_instructions<'input>(
+    terminals: &'static [u8],
+) -> impl FnMut(&'input [u8]) -> IResult<&'input [u8], (Expression, u8)> {
+    move |mut input| {
+        let mut instructions = Vec::new();
+
+        loop {
+            if let Some(terminal) = input.first().filter(|byte| terminals.contains(byte)) {
+                return Ok((&input[1..], (Expression::new(instructions), *terminal)));
+            }
+
+            let (remaining, instruction) = cut(parse_instruction)(input)?;
+
+            instructions.push(instruction);
+            input = remaining;
+        }
+    }
+}
+
+/// Parses the branches of an 𝗂𝖿 instruction, where the 𝖾𝗅𝗌𝖾 branch is optional.
+fn parse_branches(input: &[u8]) -> IResult<&[u8], (Expression, Option<Expression>)> {
+    let (input, (then, terminal)) = parse_instructions(&[ELSE, EXPRESSION_END])(input)?;
+
+    if terminal == ELSE {
+        let (input, otherwise) = parse_expression(input)?;
+
+        Ok((input, (then, Some(otherwise))))
+    } else {
+        Ok((input, (then, None)))
+    }
 }
 
 /// Parses a WebAssembly instruction from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/instructions.html>
 pub fn parse_instruction(input: &[u8]) -> IResult<&[u8], Instruction> {
-    alt((
-        map(parse_control_instruction, Instruction::from),
-        map(parse_reference_instruction, Instruction::from),
-        map(parse_parametric_instruction, Instruction::from),
-        map(parse_variable_instruction, Instruction::from),
-        map(parse_table_instruction, Instruction::from),
-        map(parse_memory_instruction, Instruction::from),
-        map(parse_numeric_instruction, Instruction::from),
-    ))(input)
+    context(
+        "opcode",
+        alt((
+            map(parse_control_instruction, Instruction::from),
+            map(parse_reference_instruction, Instruction::from),
+            map(parse_parametric_instruction, Instruction::from),
+            map(parse_variable_instruction, Instruction::from),
+            map(parse_table_instruction, Instruction::from),
+            map(parse_memory_instruction, Instruction::from),
+            map(parse_numeric_instruction, Instruction::from),
+            map(parse_vector_instruction, Instruction::from),
+            map(parse_atomic_instruction, Instruction::from),
+            map(parse_gc_instruction, Instruction::from),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly control instruction from the input.
@@ -76,24 +125,8 @@ pub fn parse_control_instruction(input: &[u8]) -> IResult<&[u8], ControlInstruct
             |(kind, expression)| ControlInstruction::Loop(kind, expression),
         ),
         map(
-            preceded(
-                match_byte(0x04),
-                tuple((parse_block_type, parse_expression)),
-            ),
-            |(kind, expression)| ControlInstruction::If(kind, expression, None),
-        ),
-        map(
-            preceded(
-                match_byte(0x04),
-                tuple((
-                    parse_block_type,
-                    parse_expression_with_terminal(0x05),
-                    parse_expression,
-                )),
-            ),
-            |(kind, true_expression, false_expression)| {
-                ControlInstruction::If(kind, true_expression, Some(false_expression))
-            },
+            preceded(match_byte(0x04), tuple((parse_block_type, parse_branches))),
+            |(kind, (then, otherwise))| ControlInstruction::If(kind, then, otherwise),
         ),
         map(
             preceded(match_byte(0x0C), parse_u32),
@@ -119,18 +152,87 @@ pub fn parse_control_instruction(input: &[u8]) -> IResult<&[u8], ControlInstruct
             preceded(match_byte(0x11), tuple((parse_u32, parse_u32))),
             |(type_index, table_index)| ControlInstruction::CallIndirect(type_index, table_index),
         ),
+        map(
+            preceded(match_byte(0x12), parse_u32),
+            ControlInstruction::ReturnCall,
+        ),
+        map(
+            preceded(match_byte(0x13), tuple((parse_u32, parse_u32))),
+            |(type_index, table_index)| {
+                ControlInstruction::ReturnCallIndirect(type_index, table_index)
+            },
+        ),
+        map(
+            preceded(match_byte(0x14), parse_u32),
+            ControlInstruction::CallReference,
+        ),
+        map(
+            preceded(match_byte(0x15), parse_u32),
+            ControlInstruction::ReturnCallReference,
+        ),
+        map(
+            preceded(match_byte(0xD5), parse_u32),
+            ControlInstruction::BranchOnNull,
+        ),
+        map(
+            preceded(match_byte(0xD6), parse_u32),
+            ControlInstruction::BranchOnNonNull,
+        ),
+        map(
+            preceded(
+                match_byte(0x1F),
+                tuple((
+                    parse_block_type,
+                    parse_vector(parse_catch),
+                    parse_expression,
+                )),
+            ),
+            |(kind, catches, expression)| ControlInstruction::TryTable(kind, catches, expression),
+        ),
+        map(
+            preceded(match_byte(0x08), parse_u32),
+            ControlInstruction::Throw,
+        ),
+        map(match_byte(0x0A), |_| ControlInstruction::ThrowReference),
     ))(input)
 }
 
+/// Parses a catch clause of a WebAssembly 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction from the input.
+///
+/// See <https://webassembly.github.io/exception-handling/core/binary/instructions.html#control-instructions>
+fn parse_catch(input: &[u8]) -> IResult<&[u8], Catch> {
+    context(
+        "catch kind",
+        alt((
+            map(
+                preceded(match_byte(0x00), tuple((parse_u32, parse_u32))),
+                |(tag, label)| Catch::Catch(tag, label),
+            ),
+            map(
+                preceded(match_byte(0x01), tuple((parse_u32, parse_u32))),
+                |(tag, label)| Catch::CatchReference(tag, label),
+            ),
+            map(preceded(match_byte(0x02), parse_u32), Catch::CatchAll),
+            map(
+                preceded(match_byte(0x03), parse_u32),
+                Catch::CatchAllReference,
+            ),
+        )),
+    )(input)
+}
+
 /// Parses a WebAssembly control instruction's block type from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/instructions.html#control-instructions>
 pub fn parse_block_type(input: &[u8]) -> IResult<&[u8], BlockType> {
-    alt((
-        map(match_byte(0x40), |_| BlockType::None),
-        map(parse_value_type, BlockType::ValueType),
-        map(parse_s33, |index| BlockType::Index(index as u32)),
-    ))(input)
+    context(
+        "block type",
+        alt((
+            map(match_byte(0x40), |_| BlockType::None),
+            map(parse_value_type, BlockType::ValueType),
+            map(parse_s33, BlockType::Index),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly reference instruction from the input.
@@ -139,7 +241,7 @@ pub fn parse_block_type(input: &[u8]) -> IResult<&[u8], BlockType> {
 pub fn parse_reference_instruction(input: &[u8]) -> IResult<&[u8], ReferenceInstruction> {
     alt((
         map(
-            preceded(match_byte(0xD0), parse_reference_type),
+            preceded(match_byte(0xD0), parse_heap_type),
             ReferenceInstruction::Null,
         ),
         map(match_byte(0xD1), |_| ReferenceInstruction::IsNull),
@@ -147,6 +249,8 @@ pub fn parse_reference_instruction(input: &[u8]) -> IResult<&[u8], ReferenceInst
             preceded(match_byte(0xD2), parse_u32),
             ReferenceInstruction::Function,
         ),
+        map(match_byte(0xD3), |_| ReferenceInstruction::Equal),
+        map(match_byte(0xD4), |_| ReferenceInstruction::AsNonNull),
     ))(input)
 }
 
@@ -335,18 +439,30 @@ pub fn parse_memory_instruction(input: &[u8]) -> IResult<&[u8], MemoryInstructio
                 MemoryInstruction::Store32,
             ),
         )),
-        map(tag([0x3F, 0x00]), |_| MemoryInstruction::Size),
-        map(tag([0x40, 0x00]), |_| MemoryInstruction::Grow),
         map(
-            delimited(tag([0xFC, 8u8]), parse_u32, match_byte(0x00)),
-            MemoryInstruction::Init,
+            preceded(match_byte(0x3F), parse_u32),
+            MemoryInstruction::Size,
+        ),
+        map(
+            preceded(match_byte(0x40), parse_u32),
+            MemoryInstruction::Grow,
+        ),
+        map(
+            preceded(tag([0xFC, 8u8]), tuple((parse_u32, parse_u32))),
+            |(data, memory)| MemoryInstruction::Init(data, memory),
         ),
         map(
             preceded(tag([0xFC, 9u8]), parse_u32),
             MemoryInstruction::DataDrop,
         ),
-        map(tag([0xFC, 10u8, 0x00, 0x00]), |_| MemoryInstruction::Copy),
-        map(tag([0xFC, 11u8, 0x00]), |_| MemoryInstruction::Fill),
+        map(
+            preceded(tag([0xFC, 10u8]), tuple((parse_u32, parse_u32))),
+            |(destination, source)| MemoryInstruction::Copy(destination, source),
+        ),
+        map(
+            preceded(tag([0xFC, 11u8]), parse_u32),
+            MemoryInstruction::Fill,
+        ),
     ))(input)
 }
 
@@ -354,9 +470,18 @@ pub fn parse_memory_instruction(input: &[u8]) -> IResult<&[u8], MemoryInstructio
 ///
 /// See <https://webassembly.github.io/spec/core/binary/instructions.html#memory-instructions>
 pub fn parse_memory_argument(input: &[u8]) -> IResult<&[u8], MemoryArgument> {
-    map(tuple((parse_u32, parse_u32)), |(align, offset)| {
-        MemoryArgument::new(align, offset)
-    })(input)
+    let (input, flags) = parse_u32(input)?;
+    let (input, memory) = if flags & MEMORY_INDEX_FLAG == 0 {
+        (input, 0)
+    } else {
+        parse_u32(input)?
+    };
+    let (input, offset) = parse_u64(input)?;
+
+    Ok((
+        input,
+        MemoryArgument::with_memory(memory, flags & !MEMORY_INDEX_FLAG, offset),
+    ))
 }
 
 /// Parses a WebAssembly numeric instruction from the input.
@@ -915,6 +1040,1126 @@ pub fn parse_numeric_instruction(input: &[u8]) -> IResult<&[u8], NumericInstruct
     ))(input)
 }
 
+/// Parses a WebAssembly vector instruction from the input.
+/// Vector instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+///
+/// See <https://webassembly.github.io/spec/core/binary/instructions.html#vector-instructions>
+pub fn parse_vector_instruction(input: &[u8]) -> IResult<&[u8], VectorInstruction> {
+    let (opcode_input, _) = match_byte(0xFD)(input)?;
+    let (input, opcode) = parse_u32(opcode_input)?;
+
+    match opcode {
+        0 => map(parse_memory_argument, VectorInstruction::Load)(input),
+        1 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I8x16,
+                SignExtension::Signed,
+                argument,
+            )
+        })(input),
+        2 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I8x16,
+                SignExtension::Unsigned,
+                argument,
+            )
+        })(input),
+        3 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I16x8,
+                SignExtension::Signed,
+                argument,
+            )
+        })(input),
+        4 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I16x8,
+                SignExtension::Unsigned,
+                argument,
+            )
+        })(input),
+        5 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I32x4,
+                SignExtension::Signed,
+                argument,
+            )
+        })(input),
+        6 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadExtend(
+                IntegerVectorShape::I32x4,
+                SignExtension::Unsigned,
+                argument,
+            )
+        })(input),
+        7 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadSplat(IntegerVectorShape::I8x16, argument)
+        })(input),
+        8 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadSplat(IntegerVectorShape::I16x8, argument)
+        })(input),
+        9 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadSplat(IntegerVectorShape::I32x4, argument)
+        })(input),
+        10 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadSplat(IntegerVectorShape::I64x2, argument)
+        })(input),
+        11 => map(parse_memory_argument, VectorInstruction::Store)(input),
+        12 => map(le_i128, VectorInstruction::Constant)(input),
+        13 => map(take(16usize), |bytes: &[u8]| {
+            let mut lanes = [0; 16];
+            lanes.copy_from_slice(bytes);
+            VectorInstruction::Shuffle(lanes)
+        })(input),
+        14 => Ok((input, VectorInstruction::Swizzle)),
+        15 => Ok((input, VectorInstruction::Splat(VectorShape::I8x16))),
+        16 => Ok((input, VectorInstruction::Splat(VectorShape::I16x8))),
+        17 => Ok((input, VectorInstruction::Splat(VectorShape::I32x4))),
+        18 => Ok((input, VectorInstruction::Splat(VectorShape::I64x2))),
+        19 => Ok((input, VectorInstruction::Splat(VectorShape::F32x4))),
+        20 => Ok((input, VectorInstruction::Splat(VectorShape::F64x2))),
+        21 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I8x16, Some(SignExtension::Signed), lane)
+        })(input),
+        22 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I8x16, Some(SignExtension::Unsigned), lane)
+        })(input),
+        23 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::I8x16, lane)
+        })(input),
+        24 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I16x8, Some(SignExtension::Signed), lane)
+        })(input),
+        25 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I16x8, Some(SignExtension::Unsigned), lane)
+        })(input),
+        26 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::I16x8, lane)
+        })(input),
+        27 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I32x4, None, lane)
+        })(input),
+        28 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::I32x4, lane)
+        })(input),
+        29 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::I64x2, None, lane)
+        })(input),
+        30 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::I64x2, lane)
+        })(input),
+        31 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::F32x4, None, lane)
+        })(input),
+        32 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::F32x4, lane)
+        })(input),
+        33 => map(le_u8, |lane| {
+            VectorInstruction::ExtractLane(VectorShape::F64x2, None, lane)
+        })(input),
+        34 => map(le_u8, |lane| {
+            VectorInstruction::ReplaceLane(VectorShape::F64x2, lane)
+        })(input),
+        35 => Ok((input, VectorInstruction::Equal(VectorShape::I8x16))),
+        36 => Ok((input, VectorInstruction::NotEqual(VectorShape::I8x16))),
+        37 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        38 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        39 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        40 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(
+                IntegerVectorShape::I8x16,
+                SignExtension::Unsigned,
+            ),
+        )),
+        41 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I8x16,
+                SignExtension::Signed,
+            ),
+        )),
+        42 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I8x16,
+                SignExtension::Unsigned,
+            ),
+        )),
+        43 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I8x16,
+                SignExtension::Signed,
+            ),
+        )),
+        44 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I8x16,
+                SignExtension::Unsigned,
+            ),
+        )),
+        45 => Ok((input, VectorInstruction::Equal(VectorShape::I16x8))),
+        46 => Ok((input, VectorInstruction::NotEqual(VectorShape::I16x8))),
+        47 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        48 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        49 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        50 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(
+                IntegerVectorShape::I16x8,
+                SignExtension::Unsigned,
+            ),
+        )),
+        51 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I16x8,
+                SignExtension::Signed,
+            ),
+        )),
+        52 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I16x8,
+                SignExtension::Unsigned,
+            ),
+        )),
+        53 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I16x8,
+                SignExtension::Signed,
+            ),
+        )),
+        54 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I16x8,
+                SignExtension::Unsigned,
+            ),
+        )),
+        55 => Ok((input, VectorInstruction::Equal(VectorShape::I32x4))),
+        56 => Ok((input, VectorInstruction::NotEqual(VectorShape::I32x4))),
+        57 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I32x4, SignExtension::Signed),
+        )),
+        58 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I32x4, SignExtension::Unsigned),
+        )),
+        59 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(IntegerVectorShape::I32x4, SignExtension::Signed),
+        )),
+        60 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(
+                IntegerVectorShape::I32x4,
+                SignExtension::Unsigned,
+            ),
+        )),
+        61 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I32x4,
+                SignExtension::Signed,
+            ),
+        )),
+        62 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I32x4,
+                SignExtension::Unsigned,
+            ),
+        )),
+        63 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I32x4,
+                SignExtension::Signed,
+            ),
+        )),
+        64 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I32x4,
+                SignExtension::Unsigned,
+            ),
+        )),
+        65 => Ok((input, VectorInstruction::Equal(VectorShape::F32x4))),
+        66 => Ok((input, VectorInstruction::NotEqual(VectorShape::F32x4))),
+        67 => Ok((
+            input,
+            VectorInstruction::LessThanFloat(FloatVectorShape::F32x4),
+        )),
+        68 => Ok((
+            input,
+            VectorInstruction::GreaterThanFloat(FloatVectorShape::F32x4),
+        )),
+        69 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToFloat(FloatVectorShape::F32x4),
+        )),
+        70 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToFloat(FloatVectorShape::F32x4),
+        )),
+        71 => Ok((input, VectorInstruction::Equal(VectorShape::F64x2))),
+        72 => Ok((input, VectorInstruction::NotEqual(VectorShape::F64x2))),
+        73 => Ok((
+            input,
+            VectorInstruction::LessThanFloat(FloatVectorShape::F64x2),
+        )),
+        74 => Ok((
+            input,
+            VectorInstruction::GreaterThanFloat(FloatVectorShape::F64x2),
+        )),
+        75 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToFloat(FloatVectorShape::F64x2),
+        )),
+        76 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToFloat(FloatVectorShape::F64x2),
+        )),
+        77 => Ok((input, VectorInstruction::Not)),
+        78 => Ok((input, VectorInstruction::And)),
+        79 => Ok((input, VectorInstruction::AndNot)),
+        80 => Ok((input, VectorInstruction::Or)),
+        81 => Ok((input, VectorInstruction::Xor)),
+        82 => Ok((input, VectorInstruction::BitSelect)),
+        83 => Ok((input, VectorInstruction::AnyTrue)),
+        84 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::LoadLane(IntegerVectorShape::I8x16, argument, lane)
+        })(input),
+        85 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::LoadLane(IntegerVectorShape::I16x8, argument, lane)
+        })(input),
+        86 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::LoadLane(IntegerVectorShape::I32x4, argument, lane)
+        })(input),
+        87 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::LoadLane(IntegerVectorShape::I64x2, argument, lane)
+        })(input),
+        88 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::StoreLane(IntegerVectorShape::I8x16, argument, lane)
+        })(input),
+        89 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::StoreLane(IntegerVectorShape::I16x8, argument, lane)
+        })(input),
+        90 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::StoreLane(IntegerVectorShape::I32x4, argument, lane)
+        })(input),
+        91 => map(tuple((parse_memory_argument, le_u8)), |(argument, lane)| {
+            VectorInstruction::StoreLane(IntegerVectorShape::I64x2, argument, lane)
+        })(input),
+        92 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadZero(IntegerVectorShape::I32x4, argument)
+        })(input),
+        93 => map(parse_memory_argument, |argument| {
+            VectorInstruction::LoadZero(IntegerVectorShape::I64x2, argument)
+        })(input),
+        94 => Ok((input, VectorInstruction::Demote)),
+        95 => Ok((input, VectorInstruction::Promote)),
+        96 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::I8x16))),
+        97 => Ok((input, VectorInstruction::Negate(VectorShape::I8x16))),
+        98 => Ok((input, VectorInstruction::CountOnes)),
+        99 => Ok((input, VectorInstruction::AllTrue(IntegerVectorShape::I8x16))),
+        100 => Ok((input, VectorInstruction::Bitmask(IntegerVectorShape::I8x16))),
+        101 => Ok((
+            input,
+            VectorInstruction::Narrow(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        102 => Ok((
+            input,
+            VectorInstruction::Narrow(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        103 => Ok((input, VectorInstruction::Ceiling(FloatVectorShape::F32x4))),
+        104 => Ok((input, VectorInstruction::Floor(FloatVectorShape::F32x4))),
+        105 => Ok((input, VectorInstruction::Truncate(FloatVectorShape::F32x4))),
+        106 => Ok((input, VectorInstruction::Nearest(FloatVectorShape::F32x4))),
+        107 => Ok((
+            input,
+            VectorInstruction::ShiftLeft(IntegerVectorShape::I8x16),
+        )),
+        108 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        109 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        110 => Ok((input, VectorInstruction::Add(VectorShape::I8x16))),
+        111 => Ok((
+            input,
+            VectorInstruction::AddSaturate(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        112 => Ok((
+            input,
+            VectorInstruction::AddSaturate(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        113 => Ok((input, VectorInstruction::Subtract(VectorShape::I8x16))),
+        114 => Ok((
+            input,
+            VectorInstruction::SubtractSaturate(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        115 => Ok((
+            input,
+            VectorInstruction::SubtractSaturate(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        116 => Ok((input, VectorInstruction::Ceiling(FloatVectorShape::F64x2))),
+        117 => Ok((input, VectorInstruction::Floor(FloatVectorShape::F64x2))),
+        118 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        119 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        120 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I8x16, SignExtension::Signed),
+        )),
+        121 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I8x16, SignExtension::Unsigned),
+        )),
+        122 => Ok((input, VectorInstruction::Truncate(FloatVectorShape::F64x2))),
+        123 => Ok((
+            input,
+            VectorInstruction::AverageRounded(IntegerVectorShape::I8x16),
+        )),
+        124 => Ok((
+            input,
+            VectorInstruction::ExtendedAddPairwise(
+                IntegerVectorShape::I16x8,
+                SignExtension::Signed,
+            ),
+        )),
+        125 => Ok((
+            input,
+            VectorInstruction::ExtendedAddPairwise(
+                IntegerVectorShape::I16x8,
+                SignExtension::Unsigned,
+            ),
+        )),
+        126 => Ok((
+            input,
+            VectorInstruction::ExtendedAddPairwise(
+                IntegerVectorShape::I32x4,
+                SignExtension::Signed,
+            ),
+        )),
+        127 => Ok((
+            input,
+            VectorInstruction::ExtendedAddPairwise(
+                IntegerVectorShape::I32x4,
+                SignExtension::Unsigned,
+            ),
+        )),
+        128 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::I16x8))),
+        129 => Ok((input, VectorInstruction::Negate(VectorShape::I16x8))),
+        130 => Ok((input, VectorInstruction::Q15MultiplyRoundSaturate)),
+        131 => Ok((input, VectorInstruction::AllTrue(IntegerVectorShape::I16x8))),
+        132 => Ok((input, VectorInstruction::Bitmask(IntegerVectorShape::I16x8))),
+        133 => Ok((
+            input,
+            VectorInstruction::Narrow(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        134 => Ok((
+            input,
+            VectorInstruction::Narrow(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        135 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I16x8,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        136 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I16x8,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        137 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I16x8,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        138 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I16x8,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        139 => Ok((
+            input,
+            VectorInstruction::ShiftLeft(IntegerVectorShape::I16x8),
+        )),
+        140 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        141 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        142 => Ok((input, VectorInstruction::Add(VectorShape::I16x8))),
+        143 => Ok((
+            input,
+            VectorInstruction::AddSaturate(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        144 => Ok((
+            input,
+            VectorInstruction::AddSaturate(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        145 => Ok((input, VectorInstruction::Subtract(VectorShape::I16x8))),
+        146 => Ok((
+            input,
+            VectorInstruction::SubtractSaturate(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        147 => Ok((
+            input,
+            VectorInstruction::SubtractSaturate(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        148 => Ok((input, VectorInstruction::Nearest(FloatVectorShape::F64x2))),
+        149 => Ok((input, VectorInstruction::Multiply(VectorShape::I16x8))),
+        150 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        151 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        152 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I16x8, SignExtension::Signed),
+        )),
+        153 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I16x8, SignExtension::Unsigned),
+        )),
+        155 => Ok((
+            input,
+            VectorInstruction::AverageRounded(IntegerVectorShape::I16x8),
+        )),
+        156 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I16x8,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        157 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I16x8,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        158 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I16x8,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        159 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I16x8,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        160 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::I32x4))),
+        161 => Ok((input, VectorInstruction::Negate(VectorShape::I32x4))),
+        163 => Ok((input, VectorInstruction::AllTrue(IntegerVectorShape::I32x4))),
+        164 => Ok((input, VectorInstruction::Bitmask(IntegerVectorShape::I32x4))),
+        167 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I32x4,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        168 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I32x4,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        169 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I32x4,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        170 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I32x4,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        171 => Ok((
+            input,
+            VectorInstruction::ShiftLeft(IntegerVectorShape::I32x4),
+        )),
+        172 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I32x4, SignExtension::Signed),
+        )),
+        173 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I32x4, SignExtension::Unsigned),
+        )),
+        174 => Ok((input, VectorInstruction::Add(VectorShape::I32x4))),
+        177 => Ok((input, VectorInstruction::Subtract(VectorShape::I32x4))),
+        181 => Ok((input, VectorInstruction::Multiply(VectorShape::I32x4))),
+        182 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I32x4, SignExtension::Signed),
+        )),
+        183 => Ok((
+            input,
+            VectorInstruction::MinimumInteger(IntegerVectorShape::I32x4, SignExtension::Unsigned),
+        )),
+        184 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I32x4, SignExtension::Signed),
+        )),
+        185 => Ok((
+            input,
+            VectorInstruction::MaximumInteger(IntegerVectorShape::I32x4, SignExtension::Unsigned),
+        )),
+        186 => Ok((input, VectorInstruction::DotProduct)),
+        188 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I32x4,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        189 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I32x4,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        190 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I32x4,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        191 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I32x4,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        192 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::I64x2))),
+        193 => Ok((input, VectorInstruction::Negate(VectorShape::I64x2))),
+        195 => Ok((input, VectorInstruction::AllTrue(IntegerVectorShape::I64x2))),
+        196 => Ok((input, VectorInstruction::Bitmask(IntegerVectorShape::I64x2))),
+        199 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I64x2,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        200 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I64x2,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        201 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I64x2,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        202 => Ok((
+            input,
+            VectorInstruction::Extend(
+                IntegerVectorShape::I64x2,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        203 => Ok((
+            input,
+            VectorInstruction::ShiftLeft(IntegerVectorShape::I64x2),
+        )),
+        204 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I64x2, SignExtension::Signed),
+        )),
+        205 => Ok((
+            input,
+            VectorInstruction::ShiftRight(IntegerVectorShape::I64x2, SignExtension::Unsigned),
+        )),
+        206 => Ok((input, VectorInstruction::Add(VectorShape::I64x2))),
+        209 => Ok((input, VectorInstruction::Subtract(VectorShape::I64x2))),
+        213 => Ok((input, VectorInstruction::Multiply(VectorShape::I64x2))),
+        214 => Ok((input, VectorInstruction::Equal(VectorShape::I64x2))),
+        215 => Ok((input, VectorInstruction::NotEqual(VectorShape::I64x2))),
+        216 => Ok((
+            input,
+            VectorInstruction::LessThanInteger(IntegerVectorShape::I64x2, SignExtension::Signed),
+        )),
+        217 => Ok((
+            input,
+            VectorInstruction::GreaterThanInteger(IntegerVectorShape::I64x2, SignExtension::Signed),
+        )),
+        218 => Ok((
+            input,
+            VectorInstruction::LessThanOrEqualToInteger(
+                IntegerVectorShape::I64x2,
+                SignExtension::Signed,
+            ),
+        )),
+        219 => Ok((
+            input,
+            VectorInstruction::GreaterThanOrEqualToInteger(
+                IntegerVectorShape::I64x2,
+                SignExtension::Signed,
+            ),
+        )),
+        220 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I64x2,
+                VectorHalf::Low,
+                SignExtension::Signed,
+            ),
+        )),
+        221 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I64x2,
+                VectorHalf::High,
+                SignExtension::Signed,
+            ),
+        )),
+        222 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I64x2,
+                VectorHalf::Low,
+                SignExtension::Unsigned,
+            ),
+        )),
+        223 => Ok((
+            input,
+            VectorInstruction::ExtendedMultiply(
+                IntegerVectorShape::I64x2,
+                VectorHalf::High,
+                SignExtension::Unsigned,
+            ),
+        )),
+        224 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::F32x4))),
+        225 => Ok((input, VectorInstruction::Negate(VectorShape::F32x4))),
+        227 => Ok((
+            input,
+            VectorInstruction::SquareRoot(FloatVectorShape::F32x4),
+        )),
+        228 => Ok((input, VectorInstruction::Add(VectorShape::F32x4))),
+        229 => Ok((input, VectorInstruction::Subtract(VectorShape::F32x4))),
+        230 => Ok((input, VectorInstruction::Multiply(VectorShape::F32x4))),
+        231 => Ok((
+            input,
+            VectorInstruction::DivideFloat(FloatVectorShape::F32x4),
+        )),
+        232 => Ok((
+            input,
+            VectorInstruction::MinimumFloat(FloatVectorShape::F32x4),
+        )),
+        233 => Ok((
+            input,
+            VectorInstruction::MaximumFloat(FloatVectorShape::F32x4),
+        )),
+        234 => Ok((
+            input,
+            VectorInstruction::PseudoMinimum(FloatVectorShape::F32x4),
+        )),
+        235 => Ok((
+            input,
+            VectorInstruction::PseudoMaximum(FloatVectorShape::F32x4),
+        )),
+        236 => Ok((input, VectorInstruction::AbsoluteValue(VectorShape::F64x2))),
+        237 => Ok((input, VectorInstruction::Negate(VectorShape::F64x2))),
+        239 => Ok((
+            input,
+            VectorInstruction::SquareRoot(FloatVectorShape::F64x2),
+        )),
+        240 => Ok((input, VectorInstruction::Add(VectorShape::F64x2))),
+        241 => Ok((input, VectorInstruction::Subtract(VectorShape::F64x2))),
+        242 => Ok((input, VectorInstruction::Multiply(VectorShape::F64x2))),
+        243 => Ok((
+            input,
+            VectorInstruction::DivideFloat(FloatVectorShape::F64x2),
+        )),
+        244 => Ok((
+            input,
+            VectorInstruction::MinimumFloat(FloatVectorShape::F64x2),
+        )),
+        245 => Ok((
+            input,
+            VectorInstruction::MaximumFloat(FloatVectorShape::F64x2),
+        )),
+        246 => Ok((
+            input,
+            VectorInstruction::PseudoMinimum(FloatVectorShape::F64x2),
+        )),
+        247 => Ok((
+            input,
+            VectorInstruction::PseudoMaximum(FloatVectorShape::F64x2),
+        )),
+        248 => Ok((
+            input,
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F32x4,
+                SignExtension::Signed,
+            ),
+        )),
+        249 => Ok((
+            input,
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F32x4,
+                SignExtension::Unsigned,
+            ),
+        )),
+        250 => Ok((
+            input,
+            VectorInstruction::Convert(FloatVectorShape::F32x4, SignExtension::Signed),
+        )),
+        251 => Ok((
+            input,
+            VectorInstruction::Convert(FloatVectorShape::F32x4, SignExtension::Unsigned),
+        )),
+        252 => Ok((
+            input,
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F64x2,
+                SignExtension::Signed,
+            ),
+        )),
+        253 => Ok((
+            input,
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F64x2,
+                SignExtension::Unsigned,
+            ),
+        )),
+        254 => Ok((
+            input,
+            VectorInstruction::Convert(FloatVectorShape::F64x2, SignExtension::Signed),
+        )),
+        255 => Ok((
+            input,
+            VectorInstruction::Convert(FloatVectorShape::F64x2, SignExtension::Unsigned),
+        )),
+        256 => Ok((input, VectorInstruction::RelaxedSwizzle)),
+        257 => Ok((
+            input,
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F32x4, SignExtension::Signed),
+        )),
+        258 => Ok((
+            input,
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F32x4, SignExtension::Unsigned),
+        )),
+        259 => Ok((
+            input,
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F64x2, SignExtension::Signed),
+        )),
+        260 => Ok((
+            input,
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F64x2, SignExtension::Unsigned),
+        )),
+        261 => Ok((
+            input,
+            VectorInstruction::RelaxedMultiplyAdd(FloatVectorShape::F32x4),
+        )),
+        262 => Ok((
+            input,
+            VectorInstruction::RelaxedNegativeMultiplyAdd(FloatVectorShape::F32x4),
+        )),
+        263 => Ok((
+            input,
+            VectorInstruction::RelaxedMultiplyAdd(FloatVectorShape::F64x2),
+        )),
+        264 => Ok((
+            input,
+            VectorInstruction::RelaxedNegativeMultiplyAdd(FloatVectorShape::F64x2),
+        )),
+        265 => Ok((
+            input,
+            VectorInstruction::RelaxedLaneSelect(IntegerVectorShape::I8x16),
+        )),
+        266 => Ok((
+            input,
+            VectorInstruction::RelaxedLaneSelect(IntegerVectorShape::I16x8),
+        )),
+        267 => Ok((
+            input,
+            VectorInstruction::RelaxedLaneSelect(IntegerVectorShape::I32x4),
+        )),
+        268 => Ok((
+            input,
+            VectorInstruction::RelaxedLaneSelect(IntegerVectorShape::I64x2),
+        )),
+        269 => Ok((
+            input,
+            VectorInstruction::RelaxedMinimum(FloatVectorShape::F32x4),
+        )),
+        270 => Ok((
+            input,
+            VectorInstruction::RelaxedMaximum(FloatVectorShape::F32x4),
+        )),
+        271 => Ok((
+            input,
+            VectorInstruction::RelaxedMinimum(FloatVectorShape::F64x2),
+        )),
+        272 => Ok((
+            input,
+            VectorInstruction::RelaxedMaximum(FloatVectorShape::F64x2),
+        )),
+        273 => Ok((input, VectorInstruction::RelaxedQ15MultiplyRound)),
+        274 => Ok((input, VectorInstruction::RelaxedDotProduct)),
+        275 => Ok((input, VectorInstruction::RelaxedDotProductAdd)),
+        _ => Err(nom::Err::Error(DecodeError::expected(
+            opcode_input,
+            "vector opcode",
+        ))),
+    }
+}
+
+/// Parses a WebAssembly atomic memory instruction from the input.
+/// Atomic instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+///
+/// See <https://webassembly.github.io/threads/core/binary/instructions.html#atomic-memory-instructions>
+pub fn parse_atomic_instruction(input: &[u8]) -> IResult<&[u8], AtomicInstruction> {
+    let (opcode_input, _) = match_byte(0xFE)(input)?;
+    let (input, opcode) = parse_u32(opcode_input)?;
+    let instruction: fn(MemoryArgument) -> AtomicInstruction = match opcode {
+        0x00 => AtomicInstruction::Notify,
+        0x01 => |argument| AtomicInstruction::Wait(IntegerType::I32, argument),
+        0x02 => |argument| AtomicInstruction::Wait(IntegerType::I64, argument),
+        0x03 => return map(match_byte(0x00), |_| AtomicInstruction::Fence)(input),
+        0x10 => |argument| AtomicInstruction::Load(IntegerType::I32, argument),
+        0x11 => |argument| AtomicInstruction::Load(IntegerType::I64, argument),
+        0x12 => |argument| AtomicInstruction::Load8(IntegerType::I32, argument),
+        0x13 => |argument| AtomicInstruction::Load16(IntegerType::I32, argument),
+        0x14 => |argument| AtomicInstruction::Load8(IntegerType::I64, argument),
+        0x15 => |argument| AtomicInstruction::Load16(IntegerType::I64, argument),
+        0x16 => AtomicInstruction::Load32,
+        0x17 => |argument| AtomicInstruction::Store(IntegerType::I32, argument),
+        0x18 => |argument| AtomicInstruction::Store(IntegerType::I64, argument),
+        0x19 => |argument| AtomicInstruction::Store8(IntegerType::I32, argument),
+        0x1A => |argument| AtomicInstruction::Store16(IntegerType::I32, argument),
+        0x1B => |argument| AtomicInstruction::Store8(IntegerType::I64, argument),
+        0x1C => |argument| AtomicInstruction::Store16(IntegerType::I64, argument),
+        0x1D => AtomicInstruction::Store32,
+        0x1E..=0x4E => {
+            let operation = ATOMIC_OPERATIONS[(opcode as usize - 0x1E) / 7];
+            let (input, argument) = parse_memory_argument(input)?;
+            let instruction = match (opcode - 0x1E) % 7 {
+                0 => AtomicInstruction::ReadModifyWrite(operation, IntegerType::I32, argument),
+                1 => AtomicInstruction::ReadModifyWrite(operation, IntegerType::I64, argument),
+                2 => AtomicInstruction::ReadModifyWrite8(operation, IntegerType::I32, argument),
+                3 => AtomicInstruction::ReadModifyWrite16(operation, IntegerType::I32, argument),
+                4 => AtomicInstruction::ReadModifyWrite8(operation, IntegerType::I64, argument),
+                5 => AtomicInstruction::ReadModifyWrite16(operation, IntegerType::I64, argument),
+                _ => AtomicInstruction::ReadModifyWrite32(operation, argument),
+            };
+
+            return Ok((input, instruction));
+        }
+        _ => {
+            return Err(nom::Err::Error(DecodeError::expected(
+                opcode_input,
+                "atomic opcode",
+            )))
+        }
+    };
+
+    map(parse_memory_argument, instruction)(input)
+}
+
+/// Parses a WebAssembly garbage collection instruction from the input.
+/// GC instructions have a shared prefix byte followed by their opcode as an unsigned integer.
+///
+/// See <https://webassembly.github.io/gc/core/binary/instructions.html#aggregate-instructions>
+pub fn parse_gc_instruction(input: &[u8]) -> IResult<&[u8], GcInstruction> {
+    let (opcode_input, _) = match_byte(0xFB)(input)?;
+    let (input, opcode) = parse_u32(opcode_input)?;
+    let signed = Some(SignExtension::Signed);
+    let unsigned = Some(SignExtension::Unsigned);
+
+    match opcode {
+        0x00 => map(parse_u32, GcInstruction::StructNew)(input),
+        0x01 => map(parse_u32, GcInstruction::StructNewDefault)(input),
+        0x02 => map(tuple((parse_u32, parse_u32)), |(kind, field)| {
+            GcInstruction::StructGet(kind, field, None)
+        })(input),
+        0x03 => map(tuple((parse_u32, parse_u32)), |(kind, field)| {
+            GcInstruction::StructGet(kind, field, signed)
+        })(input),
+        0x04 => map(tuple((parse_u32, parse_u32)), |(kind, field)| {
+            GcInstruction::StructGet(kind, field, unsigned)
+        })(input),
+        0x05 => map(tuple((parse_u32, parse_u32)), |(kind, field)| {
+            GcInstruction::StructSet(kind, field)
+        })(input),
+        0x06 => map(parse_u32, GcInstruction::ArrayNew)(input),
+        0x07 => map(parse_u32, GcInstruction::ArrayNewDefault)(input),
+        0x08 => map(tuple((parse_u32, parse_u32)), |(kind, length)| {
+            GcInstruction::ArrayNewFixed(kind, length)
+        })(input),
+        0x09 => map(tuple((parse_u32, parse_u32)), |(kind, data)| {
+            GcInstruction::ArrayNewData(kind, data)
+        })(input),
+        0x0A => map(tuple((parse_u32, parse_u32)), |(kind, element)| {
+            GcInstruction::ArrayNewElement(kind, element)
+        })(input),
+        0x0B => map(parse_u32, |kind| GcInstruction::ArrayGet(kind, None))(input),
+        0x0C => map(parse_u32, |kind| GcInstruction::ArrayGet(kind, signed))(input),
+        0x0D => map(parse_u32, |kind| GcInstruction::ArrayGet(kind, unsigned))(input),
+        0x0E => map(parse_u32, GcInstruction::ArraySet)(input),
+        0x0F => Ok((input, GcInstruction::ArrayLength)),
+        0x10 => map(parse_u32, GcInstruction::ArrayFill)(input),
+        0x11 => map(tuple((parse_u32, parse_u32)), |(target, source)| {
+            GcInstruction::ArrayCopy(target, source)
+        })(input),
+        0x12 => map(tuple((parse_u32, parse_u32)), |(kind, data)| {
+            GcInstruction::ArrayInitData(kind, data)
+        })(input),
+        0x13 => map(tuple((parse_u32, parse_u32)), |(kind, element)| {
+            GcInstruction::ArrayInitElement(kind, element)
+        })(input),
+        0x14 => map(parse_heap_type, |heap| {
+            GcInstruction::Test(ValueType::Reference(heap))
+        })(input),
+        0x15 => map(parse_heap_type, |heap| {
+            GcInstruction::Test(ValueType::nullable_reference(heap))
+        })(input),
+        0x16 => map(parse_heap_type, |heap| {
+            GcInstruction::Cast(ValueType::Reference(heap))
+        })(input),
+        0x17 => map(parse_heap_type, |heap| {
+            GcInstruction::Cast(ValueType::nullable_reference(heap))
+        })(input),
+        0x18 => map(parse_cast_branch, |(label, source, target)| {
+            GcInstruction::BranchOnCast(label, source, target)
+        })(input),
+        0x19 => map(parse_cast_branch, |(label, source, target)| {
+            GcInstruction::BranchOnCastFail(label, source, target)
+        })(input),
+        0x1A => Ok((input, GcInstruction::AnyConvertExtern)),
+        0x1B => Ok((input, GcInstruction::ExternConvertAny)),
+        0x1C => Ok((input, GcInstruction::ReferenceI31)),
+        0x1D => Ok((input, GcInstruction::I31Get(SignExtension::Signed))),
+        0x1E => Ok((input, GcInstruction::I31Get(SignExtension::Unsigned))),
+        _ => Err(nom::Err::Error(DecodeError::expected(
+            opcode_input,
+            "GC opcode",
+        ))),
+    }
+}
+
+/// Parses the immediates of a branch on a cast, whose flags byte marks a nullable source type
+/// in bit 0 and a nullable target type in bit 1.
+///
+/// See <https://webassembly.github.io/gc/core/binary/instructions.html#control-instructions>
+fn parse_cast_branch(input: &[u8]) -> IResult<&[u8], (u32, ValueType, ValueType)> {
+    let reference = |nullable: bool, heap: HeapType| {
+        if nullable {
+            ValueType::nullable_reference(heap)
+        } else {
+            ValueType::Reference(heap)
+        }
+    };
+    let (input, flags) = context(
+        "cast flags",
+        alt((
+            match_byte(0x00),
+            match_byte(0x01),
+            match_byte(0x02),
+            match_byte(0x03),
+        )),
+    )(input)?;
+    let (input, (label, source, target)) =
+        tuple((parse_u32, parse_heap_type, parse_heap_type))(input)?;
+
+    Ok((
+        input,
+        (
+            label,
+            reference(flags & 0x01 != 0, source),
+            reference(flags & 0x02 != 0, target),
+        ),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -950,4 +2195,78 @@ mod tests {
 
         assert!(result.is_err());
     }
+
+    #[test]
+    fn parse_vector_lane_instruction() {
+        let extra = 3;
+        let input = vec![0xFD, 0x55, 0x01, 0x10, 0x07, extra];
+
+        let (remaining, actual) = parse_instruction(input.as_slice()).unwrap();
+        let expected =
+            VectorInstruction::LoadLane(IntegerVectorShape::I16x8, MemoryArgument::new(1, 16), 7);
+
+        assert_eq!(actual, expected.into());
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_memory_argument_with_memory_index() {
+        let extra = 3;
+        let input = vec![0x28, 0x42, 0x01, 0x08, extra];
+
+        let (remaining, actual) = parse_instruction(input.as_slice()).unwrap();
+        let expected =
+            MemoryInstruction::Load(NumberType::I32, MemoryArgument::with_memory(1, 2, 8));
+
+        assert_eq!(actual, expected.into());
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_return_call_indirect() {
+        let extra = 3;
+        let input = vec![0x13, 0x02, 0x01, extra];
+
+        let (remaining, actual) = parse_instruction(input.as_slice()).unwrap();
+
+        assert_eq!(actual, ControlInstruction::ReturnCallIndirect(2, 1).into());
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_vector_constant() {
+        let mut input = vec![0xFD, 0x0C];
+        input.extend_from_slice(&(-2i128).to_le_bytes());
+
+        let (remaining, actual) = parse_instruction(input.as_slice()).unwrap();
+
+        assert_eq!(actual, VectorInstruction::Constant(-2).into());
+        assert!(remaining.is_empty());
+    }
+
+    #[test]
+    fn parse_relaxed_vector_instruction() {
+        let extra = 3;
+        let input = vec![0xFD, 0x85, 0x02, 0xFD, 0x93, 0x02, extra];
+
+        let (remaining, actual) = parse_instruction(input.as_slice()).unwrap();
+
+        assert_eq!(
+            actual,
+            VectorInstruction::RelaxedMultiplyAdd(FloatVectorShape::F32x4).into()
+        );
+
+        let (remaining, actual) = parse_instruction(remaining).unwrap();
+
+        assert_eq!(actual, VectorInstruction::RelaxedDotProductAdd.into());
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_unknown_vector_instruction() {
+        let input = vec![0xFD, 0x9A, 0x01];
+        let result = parse_instruction(input.as_slice());
+
+        assert!(result.is_err());
+    }
 }
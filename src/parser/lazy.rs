@@ -0,0 +1,259 @@
+//! Deferred decoding of function bodies in the binary format.
+
+use crate::parser::errors::{BinaryError, IResult};
+use crate::parser::instructions::parse_expression;
+use crate::parser::module::parse_locals;
+use crate::parser::sections::parse_section;
+use crate::parser::values::{parse_u32, parse_vector};
+use crate::parser::ParseError;
+use crate::{Expression, ModuleSection, ResultType};
+use nom::bytes::complete::take;
+use nom::combinator::{all_consuming, opt};
+use nom::Offset;
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+/// The body of a function parsed by `parse_binary_lazy`.
+/// Holds a range of the module's bytes and decodes it into an expression on first use.
+/// The decoded expression is cached, so each body is decoded at most once.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#code-section>
+#[derive(Clone, Debug)]
+pub struct LazyExpression {
+    bytes: Arc<[u8]>,
+    range: Range<usize>,
+    expression: OnceLock<Result<Expression, BinaryError>>,
+}
+
+impl LazyExpression {
+    /// Creates a lazy expression for the given range of the module's bytes.
+    fn new(bytes: Arc<[u8]>, range: Range<usize>) -> Self {
+        LazyExpression {
+            bytes,
+            range,
+            expression: OnceLock::new(),
+        }
+    }
+
+    /// The range of the module's bytes holding the encoded expression.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The encoded expression.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[self.range.clone()]
+    }
+
+    /// True if the expression has already been decoded.
+    pub fn is_decoded(&self) -> bool {
+        self.expression.get().is_some()
+    }
+
+    /// Decodes the expression, or returns the cached expression if it was already decoded.
+    pub fn decode(&self) -> Result<&Expression, ParseError> {
+        self.expression
+            .get_or_init(|| decode(&self.bytes, self.range.clone()))
+            .as_ref()
+            .map_err(|error| error.clone().into())
+    }
+
+    /// Decodes the expression, taking ownership of the cached expression if there is one.
+    pub fn into_expression(self) -> Result<Expression, ParseError> {
+        let expression = match self.expression.into_inner() {
+            Some(expression) => expression,
+            None => decode(&self.bytes, self.range),
+        };
+
+        Ok(expression?)
+    }
+}
+
+/// Decodes the expression in the given range of the module's bytes.
+fn decode(module: &[u8], range: Range<usize>) -> Result<Expression, BinaryError> {
+    all_consuming(parse_expression)(&module[range])
+        .map(|(_, expression)| expression)
+        .map_err(|error| {
+            BinaryError::from_nom(
+                module,
+                error.map(|error| error.in_section(ModuleSection::Code)),
+            )
+        })
+}
+
+impl PartialEq for LazyExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes() == other.bytes()
+    }
+}
+
+/// Type alias for a code section whose bodies are decoded lazily.
+type LazyCode = Vec<(ResultType, LazyExpression)>;
+
+/// Parses a WebAssembly code section, deferring the decoding of function bodies.
+/// The input must be a sub-slice of the given module bytes.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#code-section>
+pub fn parse_lazy_code_section(
+    module: &Arc<[u8]>,
+) -> impl Fn(&[u8]) -> IResult<&[u8], Option<LazyCode>> + '_ {
+    move |input| {
+        opt(parse_section(
+            ModuleSection::Code,
+            parse_vector(move |input| parse_lazy_code(module, input)),
+        ))(input)
+    }
+}
+
+/// Parses the locals of a code entry, deferring the decoding of its body.
+fn parse_lazy_code<'input>(
+    module: &Arc<[u8]>,
+    input: &'input [u8],
+) -> IResult<&'input [u8], (ResultType, LazyExpression)> {
+    let (input, size) = parse_u32(input)?;
+    let (remaining, code) = take(size as usize)(input)?;
+    let (body, locals) = parse_locals(code)?;
+    let start = module[..].offset(body);
+
+    Ok((
+        remaining,
+        (
+            locals,
+            LazyExpression::new(module.clone(), start..start + body.len()),
+        ),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parse_binary, parse_binary_lazy, Import, ModelError, ModuleBuilder, NumberType,
+        NumericInstruction,
+    };
+
+    fn module() -> Vec<u8> {
+        wat::parse_str(
+            r#"
+            (module
+                (func (param i32 i32) (result i32)
+                    (local i64)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func
+                    (block
+                        i32.const 1
+                        br_if 0)))
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn decode_on_demand() {
+        let bytes = module();
+        let mut module = parse_binary_lazy(&bytes).unwrap();
+        let functions = module.functions().unwrap();
+
+        assert!(functions.iter().all(|function| function.is_lazy()));
+        assert_eq!(functions[0].locals().len(), 1);
+        assert_eq!(functions[0].try_body().unwrap().len(), 3);
+        assert!(!functions[0].is_lazy());
+        assert!(functions[1].is_lazy());
+        assert_eq!(module, parse_binary(&bytes).unwrap());
+
+        let function = &mut module.functions_mut().unwrap()[1];
+
+        function.body_mut().instructions_mut().clear();
+
+        assert!(!function.is_lazy());
+        assert!(function.body().is_empty());
+    }
+
+    #[test]
+    fn invalid_body() {
+        let mut bytes = module();
+        let end = bytes.len() - 1;
+
+        // Replace the final `end` of the last body with an invalid opcode.
+        bytes[end] = 0xFF;
+
+        assert!(parse_binary(&bytes).is_err());
+
+        let module = parse_binary_lazy(&bytes).unwrap();
+        let functions = module.functions().unwrap();
+
+        assert!(matches!(
+            functions[1].try_body(),
+            Err(ParseError::InvalidBinary(error))
+                if error == BinaryError::new(end, Some(ModuleSection::Code), Some("opcode"), Some(0xFF))
+        ));
+        assert_eq!(
+            functions[0].try_body().unwrap().instructions().last(),
+            Some(&NumericInstruction::Add(NumberType::I32).into())
+        );
+    }
+
+    #[test]
+    fn invalid_body_is_reported_by_builder() {
+        let mut bytes = module();
+        let end = bytes.len() - 1;
+
+        bytes[end] = 0xFF;
+
+        let module = parse_binary_lazy(&bytes).unwrap();
+        let mut builder = module.clone().into_builder();
+
+        assert!(matches!(
+            builder.add_import(Import::function("env".into(), "log".into(), 0)),
+            Err(ModelError::MalformedBody(1, _))
+        ));
+        assert!(matches!(
+            builder.remove_function(0),
+            Err(ModelError::MalformedBody(1, _))
+        ));
+        assert_eq!(builder.build(), module);
+
+        let mut module = module;
+
+        assert!(matches!(
+            module.deduplicate_types(),
+            Err(ModelError::MalformedBody(1, _))
+        ));
+
+        let mut builder = ModuleBuilder::strict();
+        let functions = module.functions().unwrap();
+
+        builder.set_types(module.types().map(<[_]>::to_vec));
+        builder.add_function(functions[0].clone()).unwrap();
+
+        assert!(matches!(
+            builder.add_function(functions[1].clone()),
+            Err(ModelError::MalformedBody(1, _))
+        ));
+        assert_eq!(builder.build().functions().unwrap().len(), 1);
+    }
+
+    #[test]
+    #[cfg(all(feature = "validator", feature = "emitter"))]
+    fn invalid_body_is_reported_by_validator_and_emitters() {
+        let mut bytes = module();
+        let end = bytes.len() - 1;
+
+        bytes[end] = 0xFF;
+
+        let module = parse_binary_lazy(&bytes).unwrap();
+
+        assert!(matches!(
+            crate::validate(&module),
+            Err(crate::ValidationError::Function {
+                function: 1,
+                kind: crate::ValidationErrorKind::MalformedBody(_),
+                ..
+            })
+        ));
+        assert!(crate::emit_binary(&module, &mut Vec::new()).is_err());
+        assert!(crate::emit_text(&module, &mut String::new()).is_err());
+    }
+}
@@ -2,22 +2,35 @@
 
 mod errors;
 mod instructions;
+mod lazy;
 mod module;
 mod sections;
+mod stream;
+#[cfg(feature = "text")]
+mod text;
 mod types;
 mod values;
 
+use crate::model::Body;
+use crate::parser::errors::IResult;
+use crate::parser::lazy::parse_lazy_code_section;
 use crate::parser::sections::{
     parse_code_section, parse_custom_section, parse_data_count_section, parse_data_section,
     parse_element_section, parse_export_section, parse_function_section, parse_global_section,
     parse_import_section, parse_memory_section, parse_start_section, parse_table_section,
-    parse_type_section,
+    parse_tag_section, parse_type_section, section_from_id, section_order, SECTION_ORDER,
 };
-use crate::{Expression, Function, Module, ModuleSection, ResultType, TypeIndex};
-pub use errors::ParseError;
-use nom::bytes::complete::tag;
+use crate::parser::values::{match_bytes, parse_u32};
+use crate::{Data, Function, Module, ModuleSection, ResultType, TypeIndex};
+pub use errors::{BinaryError, ParseError};
+pub use lazy::LazyExpression;
 use nom::combinator::all_consuming;
+use nom::error::context;
 use nom::sequence::tuple;
+use std::sync::Arc;
+pub use stream::{parse_reader, FunctionBody, Payload, Payloads, StreamParser};
+#[cfg(feature = "text")]
+pub use text::{TextError, TextErrorKind};
 
 /// A magic constant used to quickly identify WebAssembly binary file contents.
 const PREAMBLE: [u8; 4] = [0x00, 0x61, 0x73, 0x6D];
@@ -31,6 +44,9 @@ const VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
 /// (i.e. valid WebAssembly binary format passed in with trailing data will be treated as invalid).
 ///
 /// Also, the function and code sections must have matching lengths.
+/// Non-custom sections must appear at most once and in the order defined by the specification,
+/// each section's contents must fill its declared size,
+/// and the data count section must agree with the length of the data section.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/index.html>
 ///
@@ -54,108 +70,161 @@ const VERSION: [u8; 4] = [0x01, 0x00, 0x00, 0x00];
 /// assert_eq!(module.data_count(), None);
 /// ```
 pub fn parse_binary(input: &[u8]) -> Result<Module, ParseError> {
+    parse_module(input, parse_code_section)
+}
+
+/// Parses the given bytes into a WebAssembly module, deferring the decoding of function bodies.
+/// The module keeps a shared copy of the bytes, and each function body is decoded
+/// the first time it is accessed. The rest of the module is parsed as in `parse_binary`.
+/// Invalid function bodies are only reported when they are decoded (see `Function::try_body`).
+///
+/// See <https://webassembly.github.io/spec/core/binary/index.html>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{parse_binary, parse_binary_lazy};
+///
+/// let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x01\x04\x01\x60\x00\x00\x03\x02\x01\x00\x0A\x05\x01\x03\x00\x01\x0B";
+/// let module = parse_binary_lazy(bytes).unwrap();
+/// let function = &module.functions().unwrap()[0];
+///
+/// assert!(function.is_lazy());
+/// assert_eq!(function.body(), &vec![wasm_ast::ControlInstruction::Nop.into()].into());
+/// assert!(!function.is_lazy());
+/// assert_eq!(module, parse_binary(bytes).unwrap());
+/// ```
+pub fn parse_binary_lazy(input: &[u8]) -> Result<Module, ParseError> {
+    let bytes: Arc<[u8]> = input.into();
+
+    parse_module(&bytes, parse_lazy_code_section(&bytes))
+}
+
+/// Parses the given bytes into a WebAssembly module,
+/// using the given parser for the code section.
+fn parse_module<'input, C, B>(input: &'input [u8], parse_code: C) -> Result<Module, ParseError>
+where
+    C: Fn(&'input [u8]) -> IResult<&'input [u8], Option<Vec<(ResultType, B)>>>,
+    B: Into<Body>,
+{
+    let module = input;
+    let error = |error| ParseError::from_nom(module, error);
     let mut builder = Module::builder();
 
-    let (input, _) = tuple((tag(PREAMBLE), tag(VERSION)))(input)?;
+    let (input, _) = tuple((
+        context("magic number", match_bytes(&PREAMBLE)),
+        context("version", match_bytes(&VERSION)),
+    ))(input)
+    .map_err(error)?;
+
+    validate_section_order(module, input)?;
 
-    let (input, custom_sections) =
-        parse_custom_section(input).map_err(|_| ParseError::InvalidBinary)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Custom, custom_sections);
 
-    let (input, types) = parse_type_section(input)?;
-    builder.set_function_types(types);
+    let (input, types) = parse_type_section(input).map_err(error)?;
+    builder.set_types(types);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Type, custom_sections);
 
-    let (input, imports) = parse_import_section(input)?;
+    let (input, imports) = parse_import_section(input).map_err(error)?;
     builder.set_imports(imports);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Import, custom_sections);
 
-    let (input, signatures) = parse_function_section(input)?;
+    let (input, signatures) = parse_function_section(input).map_err(error)?;
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Function, custom_sections);
 
-    let (input, tables) = parse_table_section(input)?;
+    let (input, tables) = parse_table_section(input).map_err(error)?;
     builder.set_tables(tables);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Table, custom_sections);
 
-    let (input, memories) = parse_memory_section(input)?;
+    let (input, memories) = parse_memory_section(input).map_err(error)?;
     builder.set_memories(memories);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Memory, custom_sections);
 
-    let (input, globals) = parse_global_section(input)?;
+    let (input, tags) = parse_tag_section(input).map_err(error)?;
+    builder.set_tags(tags);
+
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
+    builder.set_custom_sections(ModuleSection::Tag, custom_sections);
+
+    let (input, globals) = parse_global_section(input).map_err(error)?;
     builder.set_globals(globals);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Global, custom_sections);
 
-    let (input, exports) = parse_export_section(input)?;
+    let (input, exports) = parse_export_section(input).map_err(error)?;
     builder.set_exports(exports);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Export, custom_sections);
 
-    let (input, start) = parse_start_section(input)?;
+    let (input, start) = parse_start_section(input).map_err(error)?;
     builder.set_start(start);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Start, custom_sections);
 
-    let (input, elements) = parse_element_section(input)?;
+    let (input, elements) = parse_element_section(input).map_err(error)?;
     builder.set_elements(elements);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Element, custom_sections);
 
-    let (input, data_count) = parse_data_count_section(input)?;
+    let (input, data_count) = parse_data_count_section(input).map_err(error)?;
     builder.set_data_count(data_count);
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::DataCount, custom_sections);
 
-    let (input, codes) = parse_code_section(input)?;
+    let (input, codes) = parse_code(input).map_err(error)?;
 
     validate_function_counts(codes.as_ref(), signatures.as_ref())?;
 
     builder.set_functions(zip_functions(signatures, codes));
 
-    let (input, custom_sections) = parse_custom_section(input)?;
+    let (input, custom_sections) = parse_custom_section(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Code, custom_sections);
 
-    let (input, data) = parse_data_section(input)?;
+    let (input, data) = parse_data_section(input).map_err(error)?;
+
+    validate_data_count(data_count, data.as_ref())?;
+
     builder.set_data(data);
 
-    let (_, custom_sections) = all_consuming(parse_custom_section)(input)?;
+    let (_, custom_sections) =
+        context("section", all_consuming(parse_custom_section))(input).map_err(error)?;
     builder.set_custom_sections(ModuleSection::Data, custom_sections);
 
     Ok(builder.build())
 }
 
 /// Zips code and function sections into a function syntax type.
-fn zip_functions(
+fn zip_functions<B: Into<Body>>(
     signatures: Option<Vec<TypeIndex>>,
-    codes: Option<Vec<(ResultType, Expression)>>,
+    codes: Option<Vec<(ResultType, B)>>,
 ) -> Option<Vec<Function>> {
     codes.zip(signatures).map(|(codes, signatures)| {
         codes
             .into_iter()
             .zip(signatures)
-            .map(|((locals, body), kind)| Function::new(kind, locals, body))
+            .map(|((locals, body), kind)| Function::from_parts(kind, locals, body.into()))
             .collect()
     })
 }
 
 /// Validates the parsed function and code section lengths match.
-fn validate_function_counts(
-    codes: Option<&Vec<(ResultType, Expression)>>,
+fn validate_function_counts<B>(
+    codes: Option<&Vec<(ResultType, B)>>,
     signatures: Option<&Vec<TypeIndex>>,
 ) -> Result<(), ParseError> {
     if codes.is_none() && signatures.is_none() {
@@ -174,9 +243,71 @@ fn validate_function_counts(
         ))
 }
 
+/// Validates that the non-custom sections of a module appear at most once and in order.
+/// The input must be a sub-slice of the module bytes, starting at the first section.
+/// Stops at the first malformed section header, leaving the error to the section parsers.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#binary-module>
+fn validate_section_order(module: &[u8], mut input: &[u8]) -> Result<(), ParseError> {
+    let mut seen = [false; SECTION_ORDER.len()];
+    let mut previous: Option<(usize, ModuleSection)> = None;
+
+    while let Some((&id, rest)) = input.split_first() {
+        let offset = module.len() - input.len();
+        let (section, (rest, size)) = match (section_from_id(id), parse_u32(rest)) {
+            (Some(section), Ok(header)) => (section, header),
+            _ => break,
+        };
+
+        if let Some(order) = section_order(section) {
+            if seen[order] {
+                return Err(ParseError::DuplicateSection { section, offset });
+            }
+
+            if let Some((_, previous)) = previous.filter(|(last, _)| *last > order) {
+                return Err(ParseError::OutOfOrderSection {
+                    section,
+                    previous,
+                    offset,
+                });
+            }
+
+            seen[order] = true;
+            previous = Some((order, section));
+        }
+
+        match rest.get(size as usize..) {
+            Some(rest) => input = rest,
+            None => break,
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the data count section matches the length of the data section.
+/// A missing data section is treated as having no segments.
+fn validate_data_count(
+    data_count: Option<u32>,
+    data: Option<&Vec<Data>>,
+) -> Result<(), ParseError> {
+    let length = data.map(Vec::len).unwrap_or_default();
+
+    match data_count {
+        Some(count) if count as usize != length => Err(ParseError::DataCountMismatch {
+            count,
+            data: length,
+        }),
+        _ => Ok(()),
+    }
+}
+
 /// Parses the given string into a WebAssembly module.
-/// The string is first converted to WebAssembly binary, then parse.
-/// Some information may be lost in the conversion from text to binary format.
+/// The string is parsed using the WebAssembly text format.
+/// Symbolic identifiers are preserved in a name custom section,
+/// and errors report the line and column at which they occurred.
+///
+/// See <https://webassembly.github.io/spec/core/text/index.html>
 ///
 /// # Examples
 /// ## Empty
@@ -199,33 +330,32 @@ fn validate_function_counts(
 /// ```
 #[cfg(feature = "text")]
 pub fn parse_text(text: &str) -> Result<Module, ParseError> {
-    let binary = wat::parse_str(text)?;
-
-    parse_binary(binary.as_slice())
+    Ok(text::parse_module(text)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::Custom;
+    use crate::model::{Custom, FunctionType, HeapType, ResultType, ValueType};
+    use crate::Expression;
 
     #[test]
     fn validate_functions_no_code() {
-        let result = validate_function_counts(None, Some(vec![]).as_ref());
+        let result = validate_function_counts::<Expression>(None, Some(vec![]).as_ref());
 
         assert!(result.is_err());
     }
 
     #[test]
     fn validate_functions_no_signatures() {
-        let result = validate_function_counts(Some(vec![]).as_ref(), None);
+        let result = validate_function_counts::<Expression>(Some(vec![]).as_ref(), None);
 
         assert!(result.is_err());
     }
 
     #[test]
     fn validate_functions_empty() {
-        let result = validate_function_counts(None, None);
+        let result = validate_function_counts::<Expression>(None, None);
 
         assert!(result.is_ok());
     }
@@ -242,21 +372,21 @@ mod tests {
 
     #[test]
     fn zip_functions_no_code() {
-        let result = zip_functions(Some(vec![]), None);
+        let result = zip_functions::<Expression>(Some(vec![]), None);
 
         assert!(result.is_none());
     }
 
     #[test]
     fn zip_functions_no_signatures() {
-        let result = zip_functions(None, Some(vec![]));
+        let result = zip_functions::<Expression>(None, Some(vec![]));
 
         assert!(result.is_none());
     }
 
     #[test]
     fn zip_functions_empty() {
-        let result = zip_functions(None, None);
+        let result = zip_functions::<Expression>(None, None);
 
         assert!(result.is_none());
     }
@@ -325,4 +455,194 @@ mod tests {
 
         assert_eq!(actual, module);
     }
+
+    #[test]
+    fn abbreviated_reference_types() {
+        let bytes =
+            wat::parse_str("(module (type (func (param (ref null func) (ref null extern)))))")
+                .unwrap();
+        let mut builder = Module::builder();
+
+        builder
+            .add_function_type(FunctionType::side_effect(ResultType::new(vec![
+                ValueType::NullableReference(HeapType::Function),
+                ValueType::NullableReference(HeapType::External),
+            ])))
+            .unwrap();
+
+        assert_eq!(parse_binary(&bytes).unwrap(), builder.build());
+    }
+
+    fn binary_error(bytes: &[u8]) -> BinaryError {
+        match parse_binary(bytes) {
+            Err(ParseError::InvalidBinary(error)) => error,
+            result => panic!("Expected an invalid binary, found {:?}", result),
+        }
+    }
+
+    #[test]
+    fn unknown_opcode() {
+        let mut bytes = wat::parse_str("(module (func (block nop)))").unwrap();
+        let body = [0x02, 0x40, 0x01, 0x0B, 0x0B];
+        let offset = bytes
+            .windows(body.len())
+            .position(|window| window == body)
+            .unwrap()
+            + 2;
+
+        bytes[offset] = 0xFF;
+
+        let error = binary_error(&bytes);
+
+        assert_eq!(
+            error,
+            BinaryError::new(
+                offset,
+                Some(ModuleSection::Code),
+                Some("opcode"),
+                Some(0xFF)
+            )
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "expected opcode, found byte 0xff at offset {:#x} in the Code section",
+                offset
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_limits() {
+        let error = binary_error(b"\x00\x61\x73\x6D\x01\x00\x00\x00\x05\x03\x01\x08\x00");
+
+        assert_eq!(
+            error,
+            BinaryError::new(
+                11,
+                Some(ModuleSection::Memory),
+                Some("limits flag"),
+                Some(0x08)
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_name() {
+        let error = binary_error(b"\x00\x61\x73\x6D\x01\x00\x00\x00\x00\x04\x03a\xFFb");
+
+        assert_eq!(error.offset(), 12);
+        assert_eq!(error.section(), Some(ModuleSection::Custom));
+        assert_eq!(error.expected(), Some("utf-8 name"));
+        assert_eq!(error.found(), Some(0xFF));
+    }
+
+    #[test]
+    fn invalid_magic_number() {
+        assert_eq!(
+            binary_error(b"\x00\x61\x73\x6E\x01\x00\x00\x00"),
+            BinaryError::new(3, None, Some("magic number"), Some(0x6E))
+        );
+        assert_eq!(
+            binary_error(b"\x00\x61\x73\x6D\x02\x00\x00\x00"),
+            BinaryError::new(4, None, Some("version"), Some(0x02))
+        );
+        assert_eq!(
+            binary_error(b"\x00\x61"),
+            BinaryError::new(2, None, Some("magic number"), None)
+        );
+    }
+
+    #[test]
+    fn trailing_bytes() {
+        let error = binary_error(b"\x00\x61\x73\x6D\x01\x00\x00\x00\xFF");
+
+        assert_eq!(
+            error,
+            BinaryError::new(8, None, Some("section"), Some(0xFF))
+        );
+    }
+
+    #[test]
+    fn out_of_order_section() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x05\x01\x00\x01\x01\x00";
+
+        assert!(matches!(
+            parse_binary(bytes),
+            Err(ParseError::OutOfOrderSection {
+                section: ModuleSection::Type,
+                previous: ModuleSection::Memory,
+                offset: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn tag_section_order() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x05\x01\x00\x0D\x01\x00\x06\x01\x00";
+
+        assert!(parse_binary(bytes).is_ok());
+    }
+
+    #[test]
+    fn duplicate_section() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x01\x01\x00\x00\x01\x00\x01\x01\x00";
+
+        assert!(matches!(
+            parse_binary(bytes),
+            Err(ParseError::DuplicateSection {
+                section: ModuleSection::Type,
+                offset: 14
+            })
+        ));
+    }
+
+    #[test]
+    fn section_size_mismatch() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x01\x03\x01\x60\x00\x00";
+        let mut padded = bytes.to_vec();
+
+        padded[9] = 0x05;
+        padded.push(0x00);
+
+        assert!(parse_binary(bytes).is_err());
+        assert!(matches!(
+            parse_binary(&padded),
+            Err(ParseError::SectionSizeMismatch {
+                section: ModuleSection::Type,
+                offset: 8,
+                declared: 5,
+                consumed: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn data_count_mismatch() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x0C\x01\x02\x0B\x01\x00";
+
+        assert!(matches!(
+            parse_binary(bytes),
+            Err(ParseError::DataCountMismatch { count: 2, data: 0 })
+        ));
+        assert!(matches!(
+            parse_binary(&bytes[..11]),
+            Err(ParseError::DataCountMismatch { count: 2, data: 0 })
+        ));
+    }
+
+    #[test]
+    fn truncated_section() {
+        let error = binary_error(b"\x00\x61\x73\x6D\x01\x00\x00\x00\x01\x05\x01");
+
+        assert_eq!(
+            error,
+            BinaryError::new(
+                10,
+                Some(ModuleSection::Type),
+                Some("section contents"),
+                Some(0x01)
+            )
+        );
+    }
 }
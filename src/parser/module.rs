@@ -1,3 +1,4 @@
+use crate::parser::errors::IResult;
 use crate::parser::instructions::parse_expression;
 use crate::parser::types::{
     parse_global_type, parse_memory_type, parse_reference_type, parse_table_type, parse_value_type,
@@ -5,14 +6,14 @@ use crate::parser::types::{
 use crate::parser::values::{match_byte, parse_byte_vector, parse_name, parse_u32, parse_vector};
 use crate::{
     Data, Element, ElementInitializer, Export, ExportDescription, Expression, Global, Import,
-    ImportDescription, Memory, ReferenceType, ResultType, Start, Table,
+    ImportDescription, Memory, ReferenceType, ResultType, Start, Table, Tag, TypeIndex,
 };
 use nom::branch::alt;
 use nom::bytes::complete::take;
 use nom::combinator::{all_consuming, map};
+use nom::error::context;
 use nom::multi::fold_many_m_n;
 use nom::sequence::{preceded, tuple};
-use nom::IResult;
 
 /// Parses a WebAssembly import component from the input.
 ///
@@ -26,31 +27,48 @@ pub fn parse_import(input: &[u8]) -> IResult<&[u8], Import> {
 
 /// Parses an import description.
 fn parse_import_description(input: &[u8]) -> IResult<&[u8], ImportDescription> {
-    alt((
-        map(
-            preceded(match_byte(0x00), parse_u32),
-            ImportDescription::Function,
-        ),
-        map(
-            preceded(match_byte(0x01), parse_table_type),
-            ImportDescription::Table,
-        ),
-        map(
-            preceded(match_byte(0x02), parse_memory_type),
-            ImportDescription::Memory,
-        ),
-        map(
-            preceded(match_byte(0x03), parse_global_type),
-            ImportDescription::Global,
-        ),
-    ))(input)
+    context(
+        "import kind",
+        alt((
+            map(
+                preceded(match_byte(0x00), parse_u32),
+                ImportDescription::Function,
+            ),
+            map(
+                preceded(match_byte(0x01), parse_table_type),
+                ImportDescription::Table,
+            ),
+            map(
+                preceded(match_byte(0x02), parse_memory_type),
+                ImportDescription::Memory,
+            ),
+            map(
+                preceded(match_byte(0x03), parse_global_type),
+                ImportDescription::Global,
+            ),
+            map(
+                preceded(match_byte(0x04), parse_tag_type),
+                ImportDescription::Tag,
+            ),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly table component from the input.
+/// Tables with an initializer are prefixed by the bytes `0x40 0x00`.
 ///
-/// See <https://webassembly.github.io/spec/core/binary/modules.html#memory-section>
+/// See <https://webassembly.github.io/function-references/core/binary/modules.html#table-section>
 pub fn parse_table(input: &[u8]) -> IResult<&[u8], Table> {
-    map(parse_table_type, Table::from)(input)
+    alt((
+        map(
+            preceded(
+                tuple((match_byte(0x40), match_byte(0x00))),
+                tuple((parse_table_type, parse_expression)),
+            ),
+            |(kind, initializer)| Table::with_initializer(kind, initializer),
+        ),
+        map(parse_table_type, Table::from),
+    ))(input)
 }
 
 /// Parses a WebAssembly memory component from the input.
@@ -60,6 +78,20 @@ pub fn parse_memory(input: &[u8]) -> IResult<&[u8], Memory> {
     map(parse_memory_type, Memory::from)(input)
 }
 
+/// Parses a WebAssembly tag component from the input.
+///
+/// See <https://webassembly.github.io/exception-handling/core/binary/modules.html#tag-section>
+pub fn parse_tag(input: &[u8]) -> IResult<&[u8], Tag> {
+    map(parse_tag_type, Tag::from)(input)
+}
+
+/// Parses the type of a tag, which is an exception attribute followed by a type index.
+///
+/// See <https://webassembly.github.io/exception-handling/core/binary/types.html#tag-types>
+fn parse_tag_type(input: &[u8]) -> IResult<&[u8], TypeIndex> {
+    context("tag attribute", preceded(match_byte(0x00), parse_u32))(input)
+}
+
 /// Parses a WebAssembly global component from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#global-section>
@@ -74,25 +106,28 @@ pub fn parse_global(input: &[u8]) -> IResult<&[u8], Global> {
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#data-section>
 pub fn parse_data(input: &[u8]) -> IResult<&[u8], Data> {
-    alt((
-        map(
-            preceded(
-                match_byte(0x00),
-                tuple((parse_expression, parse_byte_vector)),
+    context(
+        "data segment flag",
+        alt((
+            map(
+                preceded(
+                    match_byte(0x00),
+                    tuple((parse_expression, parse_byte_vector)),
+                ),
+                |(offset, bytes)| Data::active(0, offset, bytes.into()),
             ),
-            |(offset, bytes)| Data::active(0, offset, bytes.into()),
-        ),
-        map(preceded(match_byte(0x01), parse_byte_vector), |bytes| {
-            Data::passive(bytes.into())
-        }),
-        map(
-            preceded(
-                match_byte(0x02),
-                tuple((parse_u32, parse_expression, parse_byte_vector)),
+            map(preceded(match_byte(0x01), parse_byte_vector), |bytes| {
+                Data::passive(bytes.into())
+            }),
+            map(
+                preceded(
+                    match_byte(0x02),
+                    tuple((parse_u32, parse_expression, parse_byte_vector)),
+                ),
+                |(memory, offset, bytes)| Data::active(memory, offset, bytes.into()),
             ),
-            |(memory, offset, bytes)| Data::active(memory, offset, bytes.into()),
-        ),
-    ))(input)
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly start component from the input.
@@ -114,115 +149,127 @@ pub fn parse_export(input: &[u8]) -> IResult<&[u8], Export> {
 
 /// Parses an export description.
 fn parse_export_description(input: &[u8]) -> IResult<&[u8], ExportDescription> {
-    alt((
-        map(
-            preceded(match_byte(0x00), parse_u32),
-            ExportDescription::Function,
-        ),
-        map(
-            preceded(match_byte(0x01), parse_u32),
-            ExportDescription::Table,
-        ),
-        map(
-            preceded(match_byte(0x02), parse_u32),
-            ExportDescription::Memory,
-        ),
-        map(
-            preceded(match_byte(0x03), parse_u32),
-            ExportDescription::Global,
-        ),
-    ))(input)
+    context(
+        "export kind",
+        alt((
+            map(
+                preceded(match_byte(0x00), parse_u32),
+                ExportDescription::Function,
+            ),
+            map(
+                preceded(match_byte(0x01), parse_u32),
+                ExportDescription::Table,
+            ),
+            map(
+                preceded(match_byte(0x02), parse_u32),
+                ExportDescription::Memory,
+            ),
+            map(
+                preceded(match_byte(0x03), parse_u32),
+                ExportDescription::Global,
+            ),
+            map(
+                preceded(match_byte(0x04), parse_u32),
+                ExportDescription::Tag,
+            ),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly element component from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#element-section>
 pub fn parse_element(input: &[u8]) -> IResult<&[u8], Element> {
-    alt((
-        map(
-            preceded(
-                match_byte(0x00),
-                tuple((parse_expression, parse_vector(parse_u32))),
-            ),
-            |(offset, functions)| {
-                Element::active(
-                    0,
-                    offset,
-                    ReferenceType::Function,
-                    functions.to_initializers(),
-                )
-            },
-        ),
-        map(
-            preceded(
-                match_byte(0x01),
-                preceded(match_byte(0x00), parse_vector(parse_u32)),
+    context(
+        "element segment flag",
+        alt((
+            map(
+                preceded(
+                    match_byte(0x00),
+                    tuple((parse_expression, parse_vector(parse_u32))),
+                ),
+                |(offset, functions)| {
+                    Element::active(
+                        0,
+                        offset,
+                        ReferenceType::Function,
+                        functions.to_initializers(),
+                    )
+                },
             ),
-            |functions| Element::passive(ReferenceType::Function, functions.to_initializers()),
-        ),
-        map(
-            preceded(
-                match_byte(0x02),
-                tuple((
-                    parse_u32,
-                    parse_expression,
+            map(
+                preceded(
+                    match_byte(0x01),
                     preceded(match_byte(0x00), parse_vector(parse_u32)),
-                )),
+                ),
+                |functions| Element::passive(ReferenceType::Function, functions.to_initializers()),
             ),
-            |(table, offset, functions)| {
-                Element::active(
-                    table,
-                    offset,
-                    ReferenceType::Function,
-                    functions.to_initializers(),
-                )
-            },
-        ),
-        map(
-            preceded(
-                match_byte(0x03),
-                preceded(match_byte(0x00), parse_vector(parse_u32)),
+            map(
+                preceded(
+                    match_byte(0x02),
+                    tuple((
+                        parse_u32,
+                        parse_expression,
+                        preceded(match_byte(0x00), parse_vector(parse_u32)),
+                    )),
+                ),
+                |(table, offset, functions)| {
+                    Element::active(
+                        table,
+                        offset,
+                        ReferenceType::Function,
+                        functions.to_initializers(),
+                    )
+                },
             ),
-            |functions| Element::declarative(ReferenceType::Function, functions.to_initializers()),
-        ),
-        map(
-            preceded(
-                match_byte(0x04),
-                tuple((parse_expression, parse_vector(parse_expression))),
+            map(
+                preceded(
+                    match_byte(0x03),
+                    preceded(match_byte(0x00), parse_vector(parse_u32)),
+                ),
+                |functions| {
+                    Element::declarative(ReferenceType::Function, functions.to_initializers())
+                },
             ),
-            |(offset, initializers)| {
-                Element::active(0, offset, ReferenceType::Function, initializers)
-            },
-        ),
-        map(
-            preceded(
-                match_byte(0x05),
-                tuple((parse_reference_type, parse_vector(parse_expression))),
+            map(
+                preceded(
+                    match_byte(0x04),
+                    tuple((parse_expression, parse_vector(parse_expression))),
+                ),
+                |(offset, initializers)| {
+                    Element::active(0, offset, ReferenceType::Function, initializers)
+                },
             ),
-            |(kind, initializers)| Element::passive(kind, initializers),
-        ),
-        map(
-            preceded(
-                match_byte(0x06),
-                tuple((
-                    parse_u32,
-                    parse_expression,
-                    parse_reference_type,
-                    parse_vector(parse_expression),
-                )),
+            map(
+                preceded(
+                    match_byte(0x05),
+                    tuple((parse_reference_type, parse_vector(parse_expression))),
+                ),
+                |(kind, initializers)| Element::passive(kind, initializers),
             ),
-            |(table, offset, kind, initializers)| {
-                Element::active(table, offset, kind, initializers)
-            },
-        ),
-        map(
-            preceded(
-                match_byte(0x07),
-                tuple((parse_reference_type, parse_vector(parse_expression))),
+            map(
+                preceded(
+                    match_byte(0x06),
+                    tuple((
+                        parse_u32,
+                        parse_expression,
+                        parse_reference_type,
+                        parse_vector(parse_expression),
+                    )),
+                ),
+                |(table, offset, kind, initializers)| {
+                    Element::active(table, offset, kind, initializers)
+                },
             ),
-            |(kind, initializers)| Element::declarative(kind, initializers),
-        ),
-    ))(input)
+            map(
+                preceded(
+                    match_byte(0x07),
+                    tuple((parse_reference_type, parse_vector(parse_expression))),
+                ),
+                |(kind, initializers)| Element::declarative(kind, initializers),
+            ),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly code portion of a function component from the input.
@@ -1,18 +1,40 @@
+use crate::parser::errors::{DecodeError, IResult};
 use crate::parser::module::{
     parse_code, parse_data, parse_element, parse_export, parse_global, parse_import, parse_memory,
-    parse_start, parse_table,
+    parse_start, parse_table, parse_tag,
 };
-use crate::parser::types::parse_function_type;
+use crate::parser::types::parse_recursive_type;
 use crate::parser::values::{match_byte, parse_name, parse_u32, parse_vector};
 use crate::{
-    Custom, Data, Element, Export, Expression, FunctionType, Global, Import, Memory, ModuleSection,
-    ResultType, Start, Table, TypeIndex,
+    Custom, Data, Element, Export, Expression, Global, Import, Memory, ModuleSection,
+    RecursiveType, ResultType, Start, Table, Tag, TypeIndex,
 };
 use nom::bytes::complete::take;
-use nom::combinator::{all_consuming, map, map_parser, opt, rest};
+use nom::combinator::{cut, map, opt, rest};
+use nom::error::context;
 use nom::multi::fold_many1;
 use nom::sequence::tuple;
-use nom::{IResult, Parser};
+use nom::Parser;
+
+/// The order in which non-custom sections must appear in a module.
+/// The tag section has the largest identifier, but is placed between the memory and global sections.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#binary-module>
+pub const SECTION_ORDER: [ModuleSection; 13] = [
+    ModuleSection::Type,
+    ModuleSection::Import,
+    ModuleSection::Function,
+    ModuleSection::Table,
+    ModuleSection::Memory,
+    ModuleSection::Tag,
+    ModuleSection::Global,
+    ModuleSection::Export,
+    ModuleSection::Start,
+    ModuleSection::Element,
+    ModuleSection::DataCount,
+    ModuleSection::Code,
+    ModuleSection::Data,
+];
 
 /// Parses a WebAssembly custom section.
 ///
@@ -38,10 +60,10 @@ fn parse_custom_content(input: &[u8]) -> IResult<&[u8], Custom> {
 /// Parses a WebAssembly type section.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#binary-typesec>
-pub fn parse_type_section(input: &[u8]) -> IResult<&[u8], Option<Vec<FunctionType>>> {
+pub fn parse_type_section(input: &[u8]) -> IResult<&[u8], Option<Vec<RecursiveType>>> {
     opt(parse_section(
         ModuleSection::Type,
-        parse_vector(parse_function_type),
+        parse_vector(parse_recursive_type),
     ))(input)
 }
 
@@ -85,6 +107,13 @@ pub fn parse_memory_section(input: &[u8]) -> IResult<&[u8], Option<Vec<Memory>>>
     ))(input)
 }
 
+/// Parses a WebAssembly tag section.
+///
+/// See <https://webassembly.github.io/exception-handling/core/binary/modules.html#tag-section>
+pub fn parse_tag_section(input: &[u8]) -> IResult<&[u8], Option<Vec<Tag>>> {
+    opt(parse_section(ModuleSection::Tag, parse_vector(parse_tag)))(input)
+}
+
 /// Parses a WebAssembly global section.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#global-section>
@@ -147,16 +176,33 @@ pub fn parse_code_section(input: &[u8]) -> IResult<&[u8], Option<Code>> {
 }
 
 /// Parses a section with the given identifier.
+/// Once the identifier matches, the contents must be valid, and errors are attributed to the section.
+/// The contents must also fill the size declared by the section.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/modules.html#sections>
-fn parse_section<'input, O, P>(
+pub fn parse_section<'input, O, P>(
     section: ModuleSection,
-    parser: P,
+    mut parser: P,
 ) -> impl FnMut(&'input [u8]) -> IResult<&'input [u8], O>
 where
-    P: Parser<&'input [u8], O, nom::error::Error<&'input [u8]>>,
+    P: Parser<&'input [u8], O, DecodeError<&'input [u8]>>,
 {
-    map_parser(parse_section_raw(section), all_consuming(parser))
+    move |input| {
+        let (remaining, contents) = parse_section_raw(section)(input)?;
+        let (rest, output) = cut(|contents| parser.parse(contents))(contents)
+            .map_err(|error| error.map(|error| error.in_section(section)))?;
+
+        if !rest.is_empty() {
+            return Err(nom::Err::Failure(DecodeError::section_size(
+                input,
+                section,
+                contents.len(),
+                contents.len() - rest.len(),
+            )));
+        }
+
+        Ok((remaining, output))
+    }
 }
 
 /// Parses the raw bytes of a section with the given identifier.
@@ -166,8 +212,25 @@ where
 fn parse_section_raw(section: ModuleSection) -> impl Fn(&[u8]) -> IResult<&[u8], &[u8]> {
     move |input| {
         let (input, _) = match_byte(section as u8)(input)?;
-        let (input, length) = parse_u32(input)?;
+        let (input, length) = cut(context("section size", parse_u32))(input)
+            .map_err(|error| error.map(|error| error.in_section(section)))?;
 
-        take(length)(input)
+        cut(context("section contents", take(length)))(input).map_err(
+            |error: nom::Err<DecodeError<&[u8]>>| error.map(|error| error.in_section(section)),
+        )
     }
 }
+
+/// Maps a section identifier to its section.
+pub fn section_from_id(id: u8) -> Option<ModuleSection> {
+    std::iter::once(ModuleSection::Custom)
+        .chain(SECTION_ORDER)
+        .find(|section| *section as u8 == id)
+}
+
+/// The position of a non-custom section in the order sections must appear in a module.
+pub fn section_order(section: ModuleSection) -> Option<usize> {
+    SECTION_ORDER
+        .iter()
+        .position(|candidate| *candidate == section)
+}
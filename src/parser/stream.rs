@@ -0,0 +1,911 @@
+//! Incremental parser of the WebAssembly binary format.
+
+use crate::parser::errors::{BinaryError, IResult};
+use crate::parser::instructions::parse_expression;
+use crate::parser::module::{
+    parse_data, parse_element, parse_export, parse_global, parse_import, parse_locals,
+    parse_memory, parse_start, parse_table, parse_tag,
+};
+use crate::parser::sections::{section_from_id, section_order};
+use crate::parser::types::parse_recursive_type;
+use crate::parser::values::{match_bytes, parse_name, parse_u32};
+use crate::parser::{ParseError, PREAMBLE, VERSION};
+use crate::{
+    Custom, Data, Element, Export, Function, Global, Import, Memory, ModuleSection, RecursiveType,
+    Start, Table, Tag, TypeIndex,
+};
+use nom::combinator::{all_consuming, map, rest};
+use nom::error::context;
+use nom::sequence::tuple;
+use std::io::{ErrorKind, Read};
+use std::ops::Range;
+
+/// The number of bytes read at a time by the `Payloads` iterator.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// An event produced while incrementally parsing a WebAssembly module in the binary format.
+/// Ranges are byte offsets from the start of the module.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#binary-module>
+#[derive(Clone, Debug, PartialEq)]
+pub enum Payload {
+    /// The magic constant and version at the start of the module.
+    Header(Range<usize>),
+    /// The start of a section, along with the range of its contents.
+    /// Sections that hold a vector of components also include the length of the vector.
+    Section {
+        section: ModuleSection,
+        range: Range<usize>,
+        count: Option<u32>,
+    },
+    /// The contents of a custom section.
+    Custom(Custom),
+    /// A recursive type in the type section.
+    Type(RecursiveType),
+    /// An import in the import section.
+    Import(Import),
+    /// The type of a function in the function section.
+    Function(TypeIndex),
+    /// A table in the table section.
+    Table(Table),
+    /// A memory in the memory section.
+    Memory(Memory),
+    /// A tag in the tag section.
+    Tag(Tag),
+    /// A global in the global section.
+    Global(Global),
+    /// An export in the export section.
+    Export(Export),
+    /// The start function in the start section.
+    Start(Start),
+    /// An element segment in the element section.
+    Element(Element),
+    /// The number of data segments in the data count section.
+    DataCount(u32),
+    /// The undecoded locals and body of a function in the code section.
+    Code(FunctionBody),
+    /// A data segment in the data section.
+    Data(Data),
+    /// The end of the module, along with its total length in bytes.
+    End(usize),
+}
+
+/// The locals and body of a function in the code section, in the binary format.
+/// The bytes are decoded on demand, which allows functions to be processed
+/// as soon as their bodies are available.
+///
+/// See <https://webassembly.github.io/spec/core/binary/modules.html#code-section>
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FunctionBody {
+    index: u32,
+    range: Range<usize>,
+    bytes: Vec<u8>,
+}
+
+impl FunctionBody {
+    /// The position of this body in the code section.
+    /// Excludes any imported functions from the function index space.
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// The range of the module's bytes holding this body, excluding its size prefix.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The encoded locals and body.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Decodes this body into a function with the given type.
+    pub fn parse(&self, kind: TypeIndex) -> Result<Function, ParseError> {
+        let (_, (locals, body)) = all_consuming(tuple((parse_locals, parse_expression)))(
+            self.bytes.as_slice(),
+        )
+        .map_err(|error| {
+            let error = error.map(|error| error.in_section(ModuleSection::Code));
+
+            BinaryError::from_nom(&self.bytes, error).shift(self.range.start)
+        })?;
+
+        Ok(Function::new(kind, locals, body))
+    }
+}
+
+/// The part of the module the parser expects next.
+#[derive(Copy, Clone, Debug)]
+enum State {
+    Header,
+    Sections,
+    Single {
+        section: ModuleSection,
+        offset: usize,
+        start: usize,
+        end: usize,
+    },
+    Items {
+        section: ModuleSection,
+        offset: usize,
+        start: usize,
+        end: usize,
+        remaining: u32,
+        index: u32,
+    },
+    Done,
+}
+
+/// A push-based parser of the WebAssembly binary format.
+/// Bytes are fed to the parser as they become available,
+/// and each call to `next_payload` produces the next event the buffered bytes allow.
+/// Only the bytes of the component being parsed are kept in memory.
+///
+/// Unlike `parse_binary`, the parser does not require the whole module up front.
+/// It checks the structure of the module as it goes (section order, section sizes,
+/// matching function and code section lengths, and the data count), but leaves the
+/// interpretation of the payloads to the caller.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{ModuleSection, Payload, StreamParser};
+///
+/// let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x08\x01\x00";
+/// let mut parser = StreamParser::new();
+/// let mut payloads = Vec::new();
+///
+/// for chunk in bytes.chunks(3) {
+///     parser.feed(chunk);
+///
+///     while let Some(payload) = parser.next_payload().unwrap() {
+///         payloads.push(payload);
+///     }
+/// }
+///
+/// parser.finish();
+///
+/// while let Some(payload) = parser.next_payload().unwrap() {
+///     payloads.push(payload);
+/// }
+///
+/// assert!(parser.is_done());
+/// assert_eq!(
+///     payloads,
+///     vec![
+///         Payload::Header(0..8),
+///         Payload::Section { section: ModuleSection::Start, range: 10..11, count: None },
+///         Payload::Start(0.into()),
+///         Payload::End(11),
+///     ]
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct StreamParser {
+    buffer: Vec<u8>,
+    start: usize,
+    position: usize,
+    complete: bool,
+    state: State,
+    last_section: Option<(usize, ModuleSection)>,
+    functions: Option<u32>,
+    codes: Option<u32>,
+    data_count: Option<u32>,
+    data: Option<u32>,
+}
+
+impl Default for StreamParser {
+    fn default() -> Self {
+        StreamParser::new()
+    }
+}
+
+impl StreamParser {
+    /// Creates a new parser expecting the start of a module.
+    pub fn new() -> Self {
+        StreamParser {
+            buffer: Vec::new(),
+            start: 0,
+            position: 0,
+            complete: false,
+            state: State::Header,
+            last_section: None,
+            functions: None,
+            codes: None,
+            data_count: None,
+            data: None,
+        }
+    }
+
+    /// Appends the given bytes to the input of the parser.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.drain(..self.position - self.start);
+        self.start = self.position;
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Marks the input as complete. Once complete, a truncated module is an error
+    /// instead of a request for more bytes.
+    pub fn finish(&mut self) {
+        self.complete = true;
+    }
+
+    /// True if the end of the module has been parsed.
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, State::Done)
+    }
+
+    /// The offset from the start of the module of the next byte to be parsed.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Parses the next payload from the buffered bytes.
+    /// Returns `None` if more bytes are needed to make progress or the module has been parsed.
+    pub fn next_payload(&mut self) -> Result<Option<Payload>, ParseError> {
+        match self.state {
+            State::Header => self.parse_header(),
+            State::Sections => self.parse_section(),
+            State::Single {
+                section,
+                offset,
+                start,
+                end,
+            } => self.parse_single(section, offset, start, end),
+            State::Items {
+                section,
+                offset,
+                start,
+                end,
+                remaining,
+                index,
+            } => self.parse_item(section, offset, start, end, remaining, index),
+            State::Done => Ok(None),
+        }
+    }
+
+    /// Parses the magic constant and version of the module.
+    fn parse_header(&mut self) -> Result<Option<Payload>, ParseError> {
+        let header = PREAMBLE.len() + VERSION.len();
+
+        if self.attempt(None, 0, header, parse_header)?.is_none() {
+            return Ok(None);
+        }
+
+        self.advance(header);
+        self.state = State::Sections;
+
+        Ok(Some(Payload::Header(0..header)))
+    }
+
+    /// Parses the identifier and size of the next section,
+    /// along with the length of its vector of components.
+    fn parse_section(&mut self) -> Result<Option<Payload>, ParseError> {
+        if self.available() == self.position {
+            return if self.complete {
+                self.parse_end()
+            } else {
+                Ok(None)
+            };
+        }
+
+        let (header, (id, size)) =
+            match self.attempt(None, self.position, usize::MAX, parse_section_header)? {
+                Some(parsed) => parsed,
+                None => return Ok(None),
+            };
+        let section =
+            section_from_id(id).ok_or_else(|| self.error(self.position, None, "section id"))?;
+        let offset = self.position;
+        let start = offset + header;
+        let end = start + size as usize;
+        let order = section_order(section);
+
+        if let (Some(order), Some((last, previous))) = (order, self.last_section) {
+            if last == order {
+                return Err(ParseError::DuplicateSection { section, offset });
+            }
+
+            if last > order {
+                return Err(ParseError::OutOfOrderSection {
+                    section,
+                    previous,
+                    offset,
+                });
+            }
+        }
+
+        let count = match section {
+            ModuleSection::Custom | ModuleSection::Start | ModuleSection::DataCount => {
+                self.advance(header);
+                self.state = State::Single {
+                    section,
+                    offset,
+                    start,
+                    end,
+                };
+
+                None
+            }
+            _ => {
+                let (length, count) = match self.attempt(Some(section), start, end, parse_u32)? {
+                    Some(parsed) => parsed,
+                    None => return Ok(None),
+                };
+
+                self.advance(header + length);
+                self.state = State::Items {
+                    section,
+                    offset,
+                    start,
+                    end,
+                    remaining: count,
+                    index: 0,
+                };
+
+                Some(count)
+            }
+        };
+
+        if let Some(order) = order {
+            self.last_section = Some((order, section));
+        }
+
+        match section {
+            ModuleSection::Function => self.functions = count,
+            ModuleSection::Code => self.codes = count,
+            ModuleSection::Data => self.data = count,
+            _ => {}
+        }
+
+        Ok(Some(Payload::Section {
+            section,
+            range: start..end,
+            count,
+        }))
+    }
+
+    /// Parses a section that does not hold a vector of components.
+    fn parse_single(
+        &mut self,
+        section: ModuleSection,
+        offset: usize,
+        start: usize,
+        end: usize,
+    ) -> Result<Option<Payload>, ParseError> {
+        if section == ModuleSection::Custom && self.available() < end {
+            return self.incomplete();
+        }
+
+        let (length, payload) = match self.attempt(Some(section), self.position, end, |input| {
+            parse_single(section, input)
+        })? {
+            Some(parsed) => parsed,
+            None => return Ok(None),
+        };
+
+        if self.position + length != end {
+            return Err(ParseError::SectionSizeMismatch {
+                section,
+                offset,
+                declared: end - start,
+                consumed: self.position + length - start,
+            });
+        }
+
+        if let Payload::DataCount(count) = payload {
+            self.data_count = Some(count);
+        }
+
+        self.advance(length);
+        self.state = State::Sections;
+
+        Ok(Some(payload))
+    }
+
+    /// Parses the next component of a section that holds a vector of components.
+    fn parse_item(
+        &mut self,
+        section: ModuleSection,
+        offset: usize,
+        start: usize,
+        end: usize,
+        remaining: u32,
+        index: u32,
+    ) -> Result<Option<Payload>, ParseError> {
+        if remaining == 0 {
+            if self.position != end {
+                return Err(ParseError::SectionSizeMismatch {
+                    section,
+                    offset,
+                    declared: end - start,
+                    consumed: self.position - start,
+                });
+            }
+
+            self.state = State::Sections;
+
+            return self.next_payload();
+        }
+
+        let (length, payload) = if section == ModuleSection::Code {
+            let (length, size) = match self.attempt(Some(section), self.position, end, parse_u32)? {
+                Some(parsed) => parsed,
+                None => return Ok(None),
+            };
+            let body_start = self.position + length;
+            let body_end = body_start + size as usize;
+
+            if body_end > end {
+                return Err(self.error(self.position, Some(section), "function body size"));
+            }
+
+            if self.available() < body_end {
+                return self.incomplete();
+            }
+
+            let bytes = self.buffer[body_start - self.start..body_end - self.start].to_vec();
+            let body = FunctionBody {
+                index,
+                range: body_start..body_end,
+                bytes,
+            };
+
+            (body_end - self.position, Payload::Code(body))
+        } else {
+            match self.attempt(Some(section), self.position, end, |input| {
+                parse_item(section, input)
+            })? {
+                Some(parsed) => parsed,
+                None => return Ok(None),
+            }
+        };
+
+        self.advance(length);
+        self.state = State::Items {
+            section,
+            offset,
+            start,
+            end,
+            remaining: remaining - 1,
+            index: index + 1,
+        };
+
+        Ok(Some(payload))
+    }
+
+    /// Validates the function and code sections have matching lengths at the end of the module,
+    /// and that the data count section agrees with the length of the data section.
+    fn parse_end(&mut self) -> Result<Option<Payload>, ParseError> {
+        if self.functions.unwrap_or(0) != self.codes.unwrap_or(0) {
+            return Err(ParseError::MismatchedFunctionParts(
+                self.codes.map(|count| count as usize),
+                self.functions.map(|count| count as usize),
+            ));
+        }
+
+        if let Some(count) = self.data_count {
+            let data = self.data.unwrap_or(0);
+
+            if count != data {
+                return Err(ParseError::DataCountMismatch {
+                    count,
+                    data: data as usize,
+                });
+            }
+        }
+
+        self.state = State::Done;
+
+        Ok(Some(Payload::End(self.position)))
+    }
+
+    /// Applies the parser to the buffered bytes between the given offsets.
+    /// Returns the number of bytes consumed along with the parsed value,
+    /// or `None` if the parser failed but more bytes may still arrive before the end.
+    fn attempt<O, P>(
+        &self,
+        section: Option<ModuleSection>,
+        start: usize,
+        end: usize,
+        mut parser: P,
+    ) -> Result<Option<(usize, O)>, ParseError>
+    where
+        P: FnMut(&[u8]) -> IResult<&[u8], O>,
+    {
+        let available = self.available();
+        let bytes = &self.buffer[..end.min(available) - self.start];
+        let input = &bytes[start - self.start..];
+
+        match parser(input) {
+            Ok((remaining, output)) => Ok(Some((input.len() - remaining.len(), output))),
+            Err(error) if available >= end || self.complete => {
+                let error = error.map(|error| match section {
+                    Some(section) => error.in_section(section),
+                    None => error,
+                });
+
+                Err(BinaryError::from_nom(bytes, error).shift(self.start).into())
+            }
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Requests more bytes, unless the input is complete.
+    fn incomplete<O>(&self) -> Result<Option<O>, ParseError> {
+        if self.complete {
+            Err(self.error(self.available(), self.section(), "more bytes"))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Creates an error at the given offset of the module.
+    fn error(
+        &self,
+        offset: usize,
+        section: Option<ModuleSection>,
+        expected: &'static str,
+    ) -> ParseError {
+        let found = offset
+            .checked_sub(self.start)
+            .and_then(|index| self.buffer.get(index))
+            .copied();
+
+        BinaryError::new(offset, section, Some(expected), found).into()
+    }
+
+    /// The section being parsed, if any.
+    fn section(&self) -> Option<ModuleSection> {
+        match self.state {
+            State::Single { section, .. } | State::Items { section, .. } => Some(section),
+            _ => None,
+        }
+    }
+
+    /// The offset from the start of the module of the end of the buffered bytes.
+    fn available(&self) -> usize {
+        self.start + self.buffer.len()
+    }
+
+    /// Moves the current position past the given number of bytes.
+    fn advance(&mut self, length: usize) {
+        self.position += length;
+    }
+}
+
+/// Parses the magic constant and version of a module.
+fn parse_header(input: &[u8]) -> IResult<&[u8], ()> {
+    map(
+        tuple((
+            context("magic number", match_bytes(&PREAMBLE)),
+            context("version", match_bytes(&VERSION)),
+        )),
+        |_| (),
+    )(input)
+}
+
+/// Parses the identifier and size of a section.
+fn parse_section_header(input: &[u8]) -> IResult<&[u8], (u8, u32)> {
+    tuple((nom::number::complete::u8, parse_u32))(input)
+}
+
+/// Parses the contents of a section that does not hold a vector of components.
+fn parse_single(section: ModuleSection, input: &[u8]) -> IResult<&[u8], Payload> {
+    match section {
+        ModuleSection::Start => map(parse_start, Payload::Start)(input),
+        ModuleSection::DataCount => map(parse_u32, Payload::DataCount)(input),
+        _ => map(tuple((parse_name, rest)), |(name, bytes): (_, &[u8])| {
+            Payload::Custom(Custom::new(name, bytes.to_vec()))
+        })(input),
+    }
+}
+
+/// Parses a component of a section that holds a vector of components.
+fn parse_item(section: ModuleSection, input: &[u8]) -> IResult<&[u8], Payload> {
+    match section {
+        ModuleSection::Type => map(parse_recursive_type, Payload::Type)(input),
+        ModuleSection::Import => map(parse_import, Payload::Import)(input),
+        ModuleSection::Function => map(parse_u32, Payload::Function)(input),
+        ModuleSection::Table => map(parse_table, Payload::Table)(input),
+        ModuleSection::Memory => map(parse_memory, Payload::Memory)(input),
+        ModuleSection::Tag => map(parse_tag, Payload::Tag)(input),
+        ModuleSection::Global => map(parse_global, Payload::Global)(input),
+        ModuleSection::Export => map(parse_export, Payload::Export)(input),
+        ModuleSection::Element => map(parse_element, Payload::Element)(input),
+        _ => map(parse_data, Payload::Data)(input),
+    }
+}
+
+/// An iterator over the payloads of a WebAssembly module read from a reader.
+/// Created by `parse_reader`.
+pub struct Payloads<R> {
+    reader: R,
+    parser: StreamParser,
+    chunk: Vec<u8>,
+    failed: bool,
+}
+
+impl<R: Read> Iterator for Payloads<R> {
+    type Item = Result<Payload, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        loop {
+            match self.parser.next_payload() {
+                Ok(Some(payload)) => return Some(Ok(payload)),
+                Ok(None) if self.parser.is_done() => return None,
+                Ok(None) => {}
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                }
+            }
+
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => self.parser.finish(),
+                Ok(length) => self.parser.feed(&self.chunk[..length]),
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error.into()));
+                }
+            }
+        }
+    }
+}
+
+/// Incrementally parses a WebAssembly module in the binary format from the given reader.
+/// Payloads are produced as soon as their bytes have been read,
+/// so a module can be processed while it is still being downloaded.
+/// The iterator stops after the first error.
+///
+/// See <https://webassembly.github.io/spec/core/binary/index.html>
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::{parse_reader, Payload};
+///
+/// let bytes: &[u8] = b"\x00\x61\x73\x6D\x01\x00\x00\x00";
+/// let payloads: Vec<Payload> = parse_reader(bytes).collect::<Result<_, _>>().unwrap();
+///
+/// assert_eq!(payloads, vec![Payload::Header(0..8), Payload::End(8)]);
+/// ```
+pub fn parse_reader<R: Read>(reader: R) -> Payloads<R> {
+    Payloads {
+        reader,
+        parser: StreamParser::new(),
+        chunk: vec![0; CHUNK_SIZE],
+        failed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_binary;
+    use crate::Module;
+
+    /// Rebuilds a module from the payloads of a stream.
+    fn build(payloads: Vec<Payload>) -> Module {
+        let mut builder = Module::builder();
+        let mut section = ModuleSection::Custom;
+        let mut kinds = Vec::new();
+
+        for payload in payloads {
+            match payload {
+                Payload::Header(_) | Payload::End(_) => {}
+                Payload::Section { section: kind, .. } => {
+                    if kind != ModuleSection::Custom {
+                        section = kind;
+                    }
+                }
+                Payload::Custom(custom) => builder.add_custom_section(section, custom),
+                Payload::Type(kind) => {
+                    builder.add_recursive_type(kind).unwrap();
+                }
+                Payload::Import(import) => {
+                    builder.add_import(import).unwrap();
+                }
+                Payload::Function(kind) => kinds.push(kind),
+                Payload::Table(table) => {
+                    builder.add_table(table).unwrap();
+                }
+                Payload::Memory(memory) => {
+                    builder.add_memory(memory).unwrap();
+                }
+                Payload::Tag(tag) => {
+                    builder.add_tag(tag).unwrap();
+                }
+                Payload::Global(global) => {
+                    builder.add_global(global).unwrap();
+                }
+                Payload::Export(export) => {
+                    builder.add_export(export);
+                }
+                Payload::Start(start) => builder.set_start(Some(start)),
+                Payload::Element(element) => {
+                    builder.add_element(element).unwrap();
+                }
+                Payload::DataCount(count) => builder.set_data_count(Some(count)),
+                Payload::Code(body) => {
+                    let kind = kinds[body.index() as usize];
+
+                    builder.add_function(body.parse(kind).unwrap()).unwrap();
+                }
+                Payload::Data(data) => {
+                    builder.add_data(data).unwrap();
+                }
+            }
+        }
+
+        builder.build()
+    }
+
+    fn module() -> Vec<u8> {
+        wat::parse_str(
+            r#"
+            (module $example
+                (import "env" "log" (func $log (param i32)))
+                (memory 1)
+                (global $counter (mut i32) (i32.const 0))
+                (func $add (export "add") (param i32 i32) (result i32)
+                    (local i64)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func $main
+                    i32.const 42
+                    call $log)
+                (table 2 funcref)
+                (elem (i32.const 0) $add $main)
+                (data (i32.const 8) "hello")
+                (start $main))
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_chunks() {
+        let bytes = module();
+        let mut parser = StreamParser::new();
+        let mut payloads = Vec::new();
+
+        for byte in bytes.chunks(1) {
+            parser.feed(byte);
+
+            while let Some(payload) = parser.next_payload().unwrap() {
+                payloads.push(payload);
+            }
+        }
+
+        parser.finish();
+
+        while let Some(payload) = parser.next_payload().unwrap() {
+            payloads.push(payload);
+        }
+
+        let bodies: Vec<Range<usize>> = payloads
+            .iter()
+            .filter_map(|payload| match payload {
+                Payload::Code(body) => Some(body.range()),
+                _ => None,
+            })
+            .collect();
+
+        assert!(parser.is_done());
+        assert_eq!(payloads.last(), Some(&Payload::End(bytes.len())));
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(&bytes[bodies[1].clone()], &[0, 0x41, 42, 0x10, 0, 0x0B]);
+        assert_eq!(build(payloads), parse_binary(&bytes).unwrap());
+    }
+
+    #[test]
+    fn parse_from_reader() {
+        let bytes = module();
+        let payloads = parse_reader(bytes.as_slice())
+            .collect::<Result<Vec<Payload>, ParseError>>()
+            .unwrap();
+
+        assert_eq!(build(payloads), parse_binary(&bytes).unwrap());
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let bytes = module();
+        let mut parser = StreamParser::new();
+
+        parser.feed(&bytes[..bytes.len() - 1]);
+
+        while parser.next_payload().unwrap().is_some() {}
+
+        parser.finish();
+
+        assert!(parser.next_payload().is_err());
+    }
+
+    #[test]
+    fn parse_invalid_magic_number() {
+        let mut parser = StreamParser::new();
+
+        parser.feed(b"\x00\x61\x73\x6E\x01\x00\x00\x00");
+        parser.finish();
+
+        assert!(matches!(
+            parser.next_payload(),
+            Err(ParseError::InvalidBinary(error))
+                if error == BinaryError::new(3, None, Some("magic number"), Some(0x6E))
+        ));
+    }
+
+    #[test]
+    fn parse_out_of_order() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x05\x01\x00\x01\x01\x00";
+        let results: Vec<Result<Payload, ParseError>> = parse_reader(&bytes[..]).collect();
+
+        assert_eq!(results.len(), 3);
+        assert!(matches!(
+            &results[2],
+            Err(ParseError::OutOfOrderSection {
+                section: ModuleSection::Type,
+                previous: ModuleSection::Memory,
+                offset: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_duplicate() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x08\x01\x00\x08\x01\x00";
+        let result = parse_reader(&bytes[..]).last().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::DuplicateSection {
+                section: ModuleSection::Start,
+                offset: 11
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_size_mismatch() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x05\x02\x00\x00";
+        let result = parse_reader(&bytes[..]).last().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::SectionSizeMismatch {
+                section: ModuleSection::Memory,
+                offset: 8,
+                declared: 2,
+                consumed: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_data_count_mismatch() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x0C\x01\x01";
+        let result = parse_reader(&bytes[..]).last().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::DataCountMismatch { count: 1, data: 0 })
+        ));
+    }
+
+    #[test]
+    fn parse_mismatched_functions() {
+        let bytes = b"\x00\x61\x73\x6D\x01\x00\x00\x00\x03\x02\x01\x00";
+        let result = parse_reader(&bytes[..]).last().unwrap();
+
+        assert!(matches!(
+            result,
+            Err(ParseError::MismatchedFunctionParts(None, Some(1)))
+        ));
+    }
+}
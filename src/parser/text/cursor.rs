@@ -0,0 +1,289 @@
+use crate::model::Name;
+use crate::parser::text::errors::{TextError, TextErrorKind};
+use crate::parser::text::lexer::{Position, Token, TokenKind};
+use crate::parser::text::values::{
+    parse_f32, parse_f64, parse_i16, parse_i32, parse_i64, parse_i8, parse_u32, parse_u64,
+};
+
+/// A reference to a definition, either by its numeric index or by its symbolic identifier.
+///
+/// See <https://webassembly.github.io/spec/core/text/modules.html#indices>
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Index<'a> {
+    Numeric(u32),
+    Identifier(&'a str),
+}
+
+/// A cursor over the tokens of a module in the text format.
+pub struct Cursor<'a> {
+    tokens: Vec<Token<'a>>,
+    index: usize,
+    end: Position,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(tokens: Vec<Token<'a>>, end: Position) -> Self {
+        Cursor {
+            tokens,
+            index: 0,
+            end,
+        }
+    }
+
+    /// The index of the next token, used to revisit module fields in a later pass.
+    pub fn checkpoint(&self) -> usize {
+        self.index
+    }
+
+    /// Moves the cursor back to a previous checkpoint.
+    pub fn restore(&mut self, checkpoint: usize) {
+        self.index = checkpoint;
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.index >= self.tokens.len()
+    }
+
+    pub fn peek(&self) -> Option<&TokenKind<'a>> {
+        self.peek_nth(0)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<&TokenKind<'a>> {
+        self.tokens.get(self.index + n).map(|token| &token.kind)
+    }
+
+    /// The position of the next token, or the end of the source text if there are no more tokens.
+    pub fn position(&self) -> Position {
+        self.tokens
+            .get(self.index)
+            .map(|token| token.position)
+            .unwrap_or(self.end)
+    }
+
+    /// Creates an error at the position of the next token.
+    pub fn error(&self, kind: TextErrorKind) -> TextError {
+        self.position().error(kind)
+    }
+
+    /// Creates an error for an unexpected token (or end of input) at the position of the next token.
+    pub fn unexpected(&self, expected: &'static str) -> TextError {
+        match self.peek() {
+            None => self.error(TextErrorKind::UnexpectedEndOfInput),
+            Some(token) => self.error(TextErrorKind::UnexpectedToken {
+                expected,
+                found: token.describe(),
+            }),
+        }
+    }
+
+    pub fn advance(&mut self) {
+        self.index += 1;
+    }
+
+    pub fn expect_left_paren(&mut self) -> Result<(), TextError> {
+        match self.peek() {
+            Some(TokenKind::LeftParen) => {
+                self.advance();
+                Ok(())
+            }
+            _ => Err(self.unexpected("`(`")),
+        }
+    }
+
+    pub fn expect_right_paren(&mut self) -> Result<(), TextError> {
+        match self.peek() {
+            Some(TokenKind::RightParen) => {
+                self.advance();
+                Ok(())
+            }
+            _ => Err(self.unexpected("`)`")),
+        }
+    }
+
+    pub fn peek_keyword(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Keyword(keyword)) => Some(keyword),
+            _ => None,
+        }
+    }
+
+    /// Consumes the given keyword if it is the next token.
+    pub fn take_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword() == Some(keyword) {
+            self.advance();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn expect_keyword(&mut self, keyword: &'static str) -> Result<(), TextError> {
+        if self.take_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.unexpected(keyword))
+        }
+    }
+
+    /// The keyword following the next token, if the next token is an opening parenthesis.
+    pub fn peek_field(&self) -> Option<&'a str> {
+        match (self.peek(), self.peek_nth(1)) {
+            (Some(TokenKind::LeftParen), Some(TokenKind::Keyword(keyword))) => Some(keyword),
+            _ => None,
+        }
+    }
+
+    /// Consumes an opening parenthesis and the given keyword if they are the next tokens.
+    pub fn take_field(&mut self, keyword: &str) -> bool {
+        if self.peek_field() == Some(keyword) {
+            self.index += 2;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn take_identifier(&mut self) -> Option<&'a str> {
+        match self.peek() {
+            Some(TokenKind::Identifier(id)) => {
+                let id = *id;
+
+                self.advance();
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn take_string(&mut self) -> Option<Vec<u8>> {
+        match self.peek() {
+            Some(TokenKind::String(bytes)) => {
+                let bytes = bytes.clone();
+
+                self.advance();
+                Some(bytes)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn expect_string(&mut self) -> Result<Vec<u8>, TextError> {
+        self.take_string()
+            .ok_or_else(|| self.unexpected("a string"))
+    }
+
+    /// Consumes a string that must be valid UTF-8.
+    ///
+    /// See <https://webassembly.github.io/spec/core/text/values.html#names>
+    pub fn expect_name(&mut self) -> Result<Name, TextError> {
+        let position = self.position();
+        let bytes = self.expect_string()?;
+
+        String::from_utf8(bytes)
+            .map(Name::new)
+            .map_err(|_| position.error(TextErrorKind::InvalidUtf8))
+    }
+
+    /// Consumes the remaining tokens of a parenthesized group, including the closing parenthesis.
+    pub fn skip_group(&mut self) -> Result<(), TextError> {
+        let mut depth = 1usize;
+
+        while depth > 0 {
+            match self.peek() {
+                None => return Err(self.error(TextErrorKind::UnexpectedEndOfInput)),
+                Some(TokenKind::LeftParen | TokenKind::Annotation(_)) => depth += 1,
+                Some(TokenKind::RightParen) => depth -= 1,
+                Some(_) => {}
+            }
+
+            self.advance();
+        }
+
+        Ok(())
+    }
+
+    /// Consumes an index if the next token is a number or an identifier.
+    pub fn take_index(&mut self) -> Result<Option<Index<'a>>, TextError> {
+        match self.peek() {
+            Some(TokenKind::Identifier(id)) => {
+                let id = *id;
+
+                self.advance();
+                Ok(Some(Index::Identifier(id)))
+            }
+            Some(TokenKind::Reserved(text)) if text.starts_with(|c: char| c.is_ascii_digit()) => {
+                self.expect_u32().map(|index| Some(Index::Numeric(index)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    pub fn expect_index(&mut self) -> Result<Index<'a>, TextError> {
+        match self.take_index()? {
+            Some(index) => Ok(index),
+            None => Err(self.unexpected("an index")),
+        }
+    }
+
+    /// Consumes a number token, converting it with the given parser.
+    fn expect_number<T>(
+        &mut self,
+        parse: impl Fn(&str) -> Option<T>,
+        keywords: bool,
+    ) -> Result<T, TextError> {
+        let text = match self.peek() {
+            Some(TokenKind::Reserved(text)) => *text,
+            Some(TokenKind::Keyword(text)) if keywords => *text,
+            _ => return Err(self.unexpected("a number")),
+        };
+
+        let value = parse(text)
+            .ok_or_else(|| self.error(TextErrorKind::InvalidNumber(text.to_string())))?;
+
+        self.advance();
+        Ok(value)
+    }
+
+    pub fn expect_u32(&mut self) -> Result<u32, TextError> {
+        self.expect_number(parse_u32, false)
+    }
+
+    pub fn expect_u64(&mut self) -> Result<u64, TextError> {
+        self.expect_number(parse_u64, false)
+    }
+
+    pub fn expect_i8(&mut self) -> Result<i8, TextError> {
+        self.expect_number(parse_i8, false)
+    }
+
+    pub fn expect_i16(&mut self) -> Result<i16, TextError> {
+        self.expect_number(parse_i16, false)
+    }
+
+    pub fn expect_i32(&mut self) -> Result<i32, TextError> {
+        self.expect_number(parse_i32, false)
+    }
+
+    pub fn expect_i64(&mut self) -> Result<i64, TextError> {
+        self.expect_number(parse_i64, false)
+    }
+
+    /// Floating point literals may also be the keywords `inf`, `nan` or `nan:0x` with a payload.
+    pub fn expect_f32(&mut self) -> Result<f32, TextError> {
+        self.expect_number(parse_f32, true)
+    }
+
+    pub fn expect_f64(&mut self) -> Result<f64, TextError> {
+        self.expect_number(parse_f64, true)
+    }
+
+    /// Determines whether the next token is an unsigned integer.
+    pub fn peek_u32(&self) -> bool {
+        matches!(self.peek(), Some(TokenKind::Reserved(text)) if parse_u32(text).is_some())
+    }
+
+    /// Determines whether the next token is an unsigned integer of at most 64 bits.
+    pub fn peek_u64(&self) -> bool {
+        matches!(self.peek(), Some(TokenKind::Reserved(text)) if parse_u64(text).is_some())
+    }
+}
@@ -0,0 +1,74 @@
+use thiserror::Error;
+
+/// An error in parsing a WebAssembly module in the text format.
+/// Identifies the line and column (both starting at 1) of the source text where the error occurred.
+#[derive(Error, Clone, Debug, PartialEq)]
+#[error("{kind} (at line {line}, column {column})")]
+pub struct TextError {
+    line: usize,
+    column: usize,
+    kind: TextErrorKind,
+}
+
+impl TextError {
+    /// Creates a new error at the given line and column of the source text.
+    pub fn new(line: usize, column: usize, kind: TextErrorKind) -> Self {
+        TextError { line, column, kind }
+    }
+
+    /// The line of the source text where the error occurred, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The column of the source text where the error occurred, starting at 1.
+    /// Columns are counted in characters, not bytes.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// The reason the source text could not be parsed.
+    pub fn kind(&self) -> &TextErrorKind {
+        &self.kind
+    }
+}
+
+/// The reason a WebAssembly module in the text format could not be parsed.
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum TextErrorKind {
+    #[error("Unexpected end of input.")]
+    UnexpectedEndOfInput,
+    #[error("Unexpected character {0:?}.")]
+    UnexpectedCharacter(char),
+    #[error("Unterminated string.")]
+    UnterminatedString,
+    #[error("Unterminated block comment.")]
+    UnterminatedComment,
+    #[error("Invalid escape sequence in string.")]
+    InvalidEscape,
+    #[error("The string is not valid UTF-8.")]
+    InvalidUtf8,
+    #[error("Expected {expected}, but found {found}.")]
+    UnexpectedToken {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("Unknown instruction {0}.")]
+    UnknownInstruction(String),
+    #[error("Unknown {0} ${1}.")]
+    UnknownIdentifier(&'static str, String),
+    #[error("Duplicate {0} ${1}.")]
+    DuplicateIdentifier(&'static str, String),
+    #[error("The number {0} is malformed or out of range.")]
+    InvalidNumber(String),
+    #[error("The alignment {0} is not a power of 2.")]
+    InvalidAlignment(String),
+    #[error("Imports must occur before all definitions, but found an import after a {0}.")]
+    ImportAfterDefinition(&'static str),
+    #[error("The inline function type does not match the referenced type {0}.")]
+    MismatchedTypeUse(u32),
+    #[error("The label ${0} does not match the label of the enclosing block.")]
+    MismatchedLabel(String),
+    #[error("A module may have at most one start function.")]
+    MultipleStart,
+}
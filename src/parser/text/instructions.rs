@@ -0,0 +1,1398 @@
+use crate::model::{
+    AtomicInstruction, AtomicOperation, Catch, ControlInstruction, Expression, FloatType,
+    FloatVectorShape, GcInstruction, Instruction, IntegerType, IntegerVectorShape, LaneIndex,
+    MemoryArgument, MemoryInstruction, NameMap, NumberType, NumericInstruction,
+    ParametricInstruction, ReferenceInstruction, SignExtension, TableInstruction,
+    VariableInstruction, VectorHalf, VectorInstruction, VectorShape,
+};
+use crate::parser::text::cursor::{Cursor, Index};
+use crate::parser::text::errors::{TextError, TextErrorKind};
+use crate::parser::text::lexer::{Position, TokenKind};
+use crate::parser::text::module::{ModuleContext, Namespace, TypeUse};
+use crate::parser::text::types::{parse_cast_type, parse_heap_type, take_value_type};
+use crate::parser::text::values::{parse_u32, parse_u64};
+
+/// The local index space and the labels in scope of the function being parsed.
+pub struct FunctionContext<'a> {
+    locals: Namespace<'a>,
+    labels: Vec<Option<&'a str>>,
+    label_names: NameMap,
+    label_count: u32,
+}
+
+impl<'a> FunctionContext<'a> {
+    /// Creates a context with the given locals, where the body of the function is the only label in scope.
+    pub fn new(locals: Namespace<'a>) -> Self {
+        FunctionContext {
+            locals,
+            labels: vec![None],
+            label_names: Vec::new(),
+            label_count: 0,
+        }
+    }
+
+    /// The names of the labels of the function, indexed by the order of their structured instructions.
+    pub fn into_label_names(self) -> NameMap {
+        self.label_names
+    }
+
+    fn push_label(&mut self, label: Option<&'a str>) {
+        if let Some(id) = label {
+            self.label_names.push((self.label_count, id.into()));
+        }
+
+        self.label_count += 1;
+        self.labels.push(label);
+    }
+
+    fn pop_label(&mut self) {
+        self.labels.pop();
+    }
+
+    /// Resolves a label to its relative depth, where the innermost label has a depth of 0.
+    fn resolve_label(&self, index: Index<'a>) -> Result<u32, TextErrorKind> {
+        match index {
+            Index::Numeric(depth) => Ok(depth),
+            Index::Identifier(id) => self
+                .labels
+                .iter()
+                .rev()
+                .position(|label| *label == Some(id))
+                .map(|depth| depth as u32)
+                .ok_or_else(|| TextErrorKind::UnknownIdentifier("label", id.to_string())),
+        }
+    }
+}
+
+/// Parses a sequence of instructions in either the flat or folded syntax,
+/// stopping at the first token that cannot start an instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html>
+pub fn parse_expression<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+    function: &mut FunctionContext<'a>,
+) -> Result<Expression, TextError> {
+    let mut instructions = Vec::new();
+
+    loop {
+        match cursor.peek() {
+            Some(TokenKind::LeftParen) => {
+                parse_folded_instruction(cursor, module, function, &mut instructions)?
+            }
+            Some(TokenKind::Keyword("end" | "else")) => break,
+            Some(TokenKind::Keyword(_)) => {
+                parse_flat_instruction(cursor, module, function, &mut instructions)?
+            }
+            _ => break,
+        }
+    }
+
+    Ok(Expression::new(instructions))
+}
+
+/// Consumes the optional label after the `else` or `end` of a structured instruction,
+/// which must match the label of the instruction.
+fn parse_closing_label(cursor: &mut Cursor, label: Option<&str>) -> Result<(), TextError> {
+    let position = cursor.position();
+
+    match cursor.take_identifier() {
+        Some(id) if label != Some(id) => {
+            Err(position.error(TextErrorKind::MismatchedLabel(id.to_string())))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Parses an instruction in the flat syntax, appending it to the output.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#control-instructions>
+fn parse_flat_instruction<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+    function: &mut FunctionContext<'a>,
+    output: &mut Vec<Instruction>,
+) -> Result<(), TextError> {
+    let position = cursor.position();
+    let keyword = cursor.peek_keyword().unwrap_or_default();
+
+    cursor.advance();
+
+    let instruction = match keyword {
+        "block" | "loop" | "if" => {
+            let label = cursor.take_identifier();
+            let type_use = module.parse_type_use(cursor)?;
+            let kind = module.resolve_block_type(type_use)?;
+
+            function.push_label(label);
+
+            let body = parse_expression(cursor, module, function)?;
+            let instruction = match keyword {
+                "block" => ControlInstruction::Block(kind, body),
+                "loop" => ControlInstruction::Loop(kind, body),
+                _ => {
+                    let alternative = if cursor.take_keyword("else") {
+                        parse_closing_label(cursor, label)?;
+                        Some(parse_expression(cursor, module, function)?)
+                    } else {
+                        None
+                    };
+
+                    ControlInstruction::If(kind, body, alternative)
+                }
+            };
+
+            cursor.expect_keyword("end")?;
+            parse_closing_label(cursor, label)?;
+            function.pop_label();
+
+            instruction.into()
+        }
+        "try_table" => {
+            let label = cursor.take_identifier();
+            let type_use = module.parse_type_use(cursor)?;
+            let kind = module.resolve_block_type(type_use)?;
+            let catches = parse_catches(cursor, module, function)?;
+
+            function.push_label(label);
+
+            let body = parse_expression(cursor, module, function)?;
+
+            cursor.expect_keyword("end")?;
+            parse_closing_label(cursor, label)?;
+            function.pop_label();
+
+            ControlInstruction::TryTable(kind, catches, body).into()
+        }
+        "call_indirect" => {
+            let (table, type_use) = parse_indirect_call(cursor, module)?;
+            let (kind, _) = module.resolve_type_use(type_use)?;
+
+            ControlInstruction::CallIndirect(kind, table).into()
+        }
+        "return_call_indirect" => {
+            let (table, type_use) = parse_indirect_call(cursor, module)?;
+            let (kind, _) = module.resolve_type_use(type_use)?;
+
+            ControlInstruction::ReturnCallIndirect(kind, table).into()
+        }
+        _ => parse_plain_instruction(keyword, position, cursor, module, function)?,
+    };
+
+    output.push(instruction);
+
+    Ok(())
+}
+
+/// Parses an instruction in the folded syntax, appending its operands followed by the instruction to the output.
+/// Indirect calls and the conditions of 𝗂𝖿 instructions are parsed before
+/// any types are implicitly defined for the instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#folded-instructions>
+pub fn parse_folded_instruction<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+    function: &mut FunctionContext<'a>,
+    output: &mut Vec<Instruction>,
+) -> Result<(), TextError> {
+    cursor.expect_left_paren()?;
+
+    let position = cursor.position();
+    let keyword = cursor
+        .peek_keyword()
+        .ok_or_else(|| cursor.unexpected("an instruction"))?;
+
+    cursor.advance();
+
+    let instruction = match keyword {
+        "block" | "loop" => {
+            let label = cursor.take_identifier();
+            let type_use = module.parse_type_use(cursor)?;
+            let kind = module.resolve_block_type(type_use)?;
+
+            function.push_label(label);
+
+            let body = parse_expression(cursor, module, function)?;
+
+            function.pop_label();
+
+            if keyword == "block" {
+                ControlInstruction::Block(kind, body).into()
+            } else {
+                ControlInstruction::Loop(kind, body).into()
+            }
+        }
+        "try_table" => {
+            let label = cursor.take_identifier();
+            let type_use = module.parse_type_use(cursor)?;
+            let kind = module.resolve_block_type(type_use)?;
+            let catches = parse_catches(cursor, module, function)?;
+
+            function.push_label(label);
+
+            let body = parse_expression(cursor, module, function)?;
+
+            function.pop_label();
+
+            ControlInstruction::TryTable(kind, catches, body).into()
+        }
+        "if" => {
+            let label = cursor.take_identifier();
+            let type_use = module.parse_type_use(cursor)?;
+
+            while cursor.peek() == Some(&TokenKind::LeftParen)
+                && cursor.peek_field() != Some("then")
+            {
+                parse_folded_instruction(cursor, module, function, output)?;
+            }
+
+            let kind = module.resolve_block_type(type_use)?;
+
+            function.push_label(label);
+
+            if !cursor.take_field("then") {
+                return Err(cursor.unexpected("`(then`"));
+            }
+
+            let body = parse_expression(cursor, module, function)?;
+
+            cursor.expect_right_paren()?;
+
+            let alternative = if cursor.take_field("else") {
+                let alternative = parse_expression(cursor, module, function)?;
+
+                cursor.expect_right_paren()?;
+
+                Some(alternative)
+            } else {
+                None
+            };
+
+            function.pop_label();
+
+            ControlInstruction::If(kind, body, alternative).into()
+        }
+        "call_indirect" => {
+            let (table, type_use) = parse_indirect_call(cursor, module)?;
+
+            parse_operands(cursor, module, function, output)?;
+
+            let (kind, _) = module.resolve_type_use(type_use)?;
+
+            ControlInstruction::CallIndirect(kind, table).into()
+        }
+        "return_call_indirect" => {
+            let (table, type_use) = parse_indirect_call(cursor, module)?;
+
+            parse_operands(cursor, module, function, output)?;
+
+            let (kind, _) = module.resolve_type_use(type_use)?;
+
+            ControlInstruction::ReturnCallIndirect(kind, table).into()
+        }
+        _ => {
+            let instruction = parse_plain_instruction(keyword, position, cursor, module, function)?;
+
+            parse_operands(cursor, module, function, output)?;
+
+            instruction
+        }
+    };
+
+    cursor.expect_right_paren()?;
+    output.push(instruction);
+
+    Ok(())
+}
+
+/// Parses the folded operands of a folded instruction.
+fn parse_operands<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+    function: &mut FunctionContext<'a>,
+    output: &mut Vec<Instruction>,
+) -> Result<(), TextError> {
+    while cursor.peek() == Some(&TokenKind::LeftParen) {
+        parse_folded_instruction(cursor, module, function, output)?;
+    }
+
+    Ok(())
+}
+
+/// Parses the catch clauses of a 𝗍𝗋𝗒_𝗍𝖺𝖻𝗅𝖾 instruction.
+/// The labels of the clauses are resolved outside of the label introduced by the instruction.
+///
+/// See <https://webassembly.github.io/exception-handling/core/text/instructions.html#control-instructions>
+fn parse_catches<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &ModuleContext<'a>,
+    function: &FunctionContext<'a>,
+) -> Result<Vec<Catch>, TextError> {
+    let mut catches = Vec::new();
+
+    loop {
+        let field = match cursor.peek_field() {
+            Some(field @ ("catch" | "catch_ref" | "catch_all" | "catch_all_ref")) => field,
+            _ => return Ok(catches),
+        };
+
+        cursor.advance();
+        cursor.advance();
+
+        let tag = match field {
+            "catch" | "catch_ref" => Some(module.tags.expect(cursor)?),
+            _ => None,
+        };
+        let position = cursor.position();
+        let index = cursor.expect_index()?;
+        let label = function
+            .resolve_label(index)
+            .map_err(|kind| position.error(kind))?;
+
+        catches.push(match (field, tag) {
+            ("catch", Some(tag)) => Catch::Catch(tag, label),
+            ("catch_ref", Some(tag)) => Catch::CatchReference(tag, label),
+            ("catch_all", _) => Catch::CatchAll(label),
+            _ => Catch::CatchAllReference(label),
+        });
+
+        cursor.expect_right_paren()?;
+    }
+}
+
+/// Parses the optional table and the type use of an indirect call, without resolving the type use.
+fn parse_indirect_call<'a>(
+    cursor: &mut Cursor<'a>,
+    module: &ModuleContext<'a>,
+) -> Result<(u32, TypeUse<'a>), TextError> {
+    let table = module.tables.take(cursor)?.unwrap_or_default();
+    let type_use = module.parse_type_use(cursor)?;
+
+    Ok((table, type_use))
+}
+
+/// Parses the immediates of a plain (i.e. non-structured) instruction after its keyword.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#instructions>
+fn parse_plain_instruction<'a>(
+    keyword: &str,
+    position: Position,
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+    function: &mut FunctionContext<'a>,
+) -> Result<Instruction, TextError> {
+    if let Some(instruction) = numeric_instruction(keyword) {
+        return Ok(instruction.into());
+    }
+
+    if let Some(instruction) = vector_instruction(keyword) {
+        return Ok(instruction.into());
+    }
+
+    let label = |cursor: &mut Cursor<'a>, function: &FunctionContext<'a>| {
+        let position = cursor.position();
+        let index = cursor.expect_index()?;
+
+        function
+            .resolve_label(index)
+            .map_err(|kind| position.error(kind))
+    };
+
+    let instruction: Instruction = match keyword {
+        "unreachable" => ControlInstruction::Unreachable.into(),
+        "nop" => ControlInstruction::Nop.into(),
+        "return" => ControlInstruction::Return.into(),
+        "br" => ControlInstruction::Branch(label(cursor, function)?).into(),
+        "br_if" => ControlInstruction::BranchIf(label(cursor, function)?).into(),
+        "br_on_null" => ControlInstruction::BranchOnNull(label(cursor, function)?).into(),
+        "br_on_non_null" => ControlInstruction::BranchOnNonNull(label(cursor, function)?).into(),
+        "br_on_cast" => GcInstruction::BranchOnCast(
+            label(cursor, function)?,
+            parse_cast_type(cursor, &module.types)?,
+            parse_cast_type(cursor, &module.types)?,
+        )
+        .into(),
+        "br_on_cast_fail" => GcInstruction::BranchOnCastFail(
+            label(cursor, function)?,
+            parse_cast_type(cursor, &module.types)?,
+            parse_cast_type(cursor, &module.types)?,
+        )
+        .into(),
+        "br_table" => {
+            let mut labels = vec![label(cursor, function)?];
+
+            while matches!(
+                cursor.peek(),
+                Some(TokenKind::Identifier(_) | TokenKind::Reserved(_))
+            ) {
+                labels.push(label(cursor, function)?);
+            }
+
+            let default = labels.pop().unwrap_or_default();
+
+            ControlInstruction::BranchTable(labels, default).into()
+        }
+        "call" => ControlInstruction::Call(module.functions.expect(cursor)?).into(),
+        "return_call" => ControlInstruction::ReturnCall(module.functions.expect(cursor)?).into(),
+        "call_ref" => ControlInstruction::CallReference(module.types.expect(cursor)?).into(),
+        "return_call_ref" => {
+            ControlInstruction::ReturnCallReference(module.types.expect(cursor)?).into()
+        }
+        "throw" => ControlInstruction::Throw(module.tags.expect(cursor)?).into(),
+        "throw_ref" => ControlInstruction::ThrowReference.into(),
+        "drop" => ParametricInstruction::Drop.into(),
+        "select" => {
+            let mut results = None;
+
+            while cursor.take_field("result") {
+                let results = results.get_or_insert_with(Vec::new);
+
+                while let Some(kind) = take_value_type(cursor, &module.types)? {
+                    results.push(kind);
+                }
+
+                cursor.expect_right_paren()?;
+            }
+
+            ParametricInstruction::Select(results).into()
+        }
+        "local.get" => VariableInstruction::LocalGet(function.locals.expect(cursor)?).into(),
+        "local.set" => VariableInstruction::LocalSet(function.locals.expect(cursor)?).into(),
+        "local.tee" => VariableInstruction::LocalTee(function.locals.expect(cursor)?).into(),
+        "global.get" => VariableInstruction::GlobalGet(module.globals.expect(cursor)?).into(),
+        "global.set" => VariableInstruction::GlobalSet(module.globals.expect(cursor)?).into(),
+        "table.get" | "table.set" | "table.size" | "table.grow" | "table.fill" => {
+            let table = module.tables.take(cursor)?.unwrap_or_default();
+
+            match keyword {
+                "table.get" => TableInstruction::Get(table),
+                "table.set" => TableInstruction::Set(table),
+                "table.size" => TableInstruction::Size(table),
+                "table.grow" => TableInstruction::Grow(table),
+                _ => TableInstruction::Fill(table),
+            }
+            .into()
+        }
+        "table.copy" => match module.tables.take(cursor)? {
+            Some(destination) => {
+                TableInstruction::Copy(destination, module.tables.expect(cursor)?).into()
+            }
+            None => TableInstruction::Copy(0, 0).into(),
+        },
+        "table.init" => {
+            let first_position = cursor.position();
+            let first = cursor.expect_index()?;
+            let second_position = cursor.position();
+            let (table, element) = match cursor.take_index()? {
+                Some(second) => (
+                    module
+                        .tables
+                        .resolve(first)
+                        .map_err(|kind| first_position.error(kind))?,
+                    module
+                        .elements
+                        .resolve(second)
+                        .map_err(|kind| second_position.error(kind))?,
+                ),
+                None => (
+                    0,
+                    module
+                        .elements
+                        .resolve(first)
+                        .map_err(|kind| first_position.error(kind))?,
+                ),
+            };
+
+            TableInstruction::Init(element, table).into()
+        }
+        "elem.drop" => TableInstruction::ElementDrop(module.elements.expect(cursor)?).into(),
+        "memory.size" | "memory.grow" | "memory.fill" => {
+            let memory = module.memories.take(cursor)?.unwrap_or_default();
+
+            match keyword {
+                "memory.size" => MemoryInstruction::Size(memory),
+                "memory.grow" => MemoryInstruction::Grow(memory),
+                _ => MemoryInstruction::Fill(memory),
+            }
+            .into()
+        }
+        "memory.copy" => match module.memories.take(cursor)? {
+            Some(destination) => {
+                MemoryInstruction::Copy(destination, module.memories.expect(cursor)?).into()
+            }
+            None => MemoryInstruction::Copy(0, 0).into(),
+        },
+        "memory.init" => {
+            let first_position = cursor.position();
+            let first = cursor.expect_index()?;
+            let second_position = cursor.position();
+            let (data, memory) = match cursor.take_index()? {
+                Some(second) => (
+                    module
+                        .data
+                        .resolve(second)
+                        .map_err(|kind| second_position.error(kind))?,
+                    module
+                        .memories
+                        .resolve(first)
+                        .map_err(|kind| first_position.error(kind))?,
+                ),
+                None => (
+                    module
+                        .data
+                        .resolve(first)
+                        .map_err(|kind| first_position.error(kind))?,
+                    0,
+                ),
+            };
+
+            module.uses_data_count = true;
+            MemoryInstruction::Init(data, memory).into()
+        }
+        "data.drop" => {
+            module.uses_data_count = true;
+            MemoryInstruction::DataDrop(module.data.expect(cursor)?).into()
+        }
+        "ref.null" => ReferenceInstruction::Null(parse_heap_type(cursor, &module.types)?).into(),
+        "ref.is_null" => ReferenceInstruction::IsNull.into(),
+        "ref.eq" => ReferenceInstruction::Equal.into(),
+        "ref.as_non_null" => ReferenceInstruction::AsNonNull.into(),
+        "ref.func" => ReferenceInstruction::Function(module.functions.expect(cursor)?).into(),
+        "i32.const" => NumericInstruction::I32Constant(cursor.expect_i32()?).into(),
+        "i64.const" => NumericInstruction::I64Constant(cursor.expect_i64()?).into(),
+        "f32.const" => NumericInstruction::F32Constant(cursor.expect_f32()?).into(),
+        "f64.const" => NumericInstruction::F64Constant(cursor.expect_f64()?).into(),
+        "v128.const" => VectorInstruction::Constant(parse_vector_constant(cursor)?).into(),
+        _ => {
+            if let Some(instruction) = memory_instruction(keyword, cursor, &module.memories)? {
+                instruction.into()
+            } else if let Some(instruction) = vector_lane_instruction(keyword, cursor)? {
+                instruction.into()
+            } else if let Some(instruction) =
+                vector_memory_instruction(keyword, cursor, &module.memories)?
+            {
+                instruction.into()
+            } else if let Some(instruction) = atomic_instruction(keyword, cursor, &module.memories)?
+            {
+                instruction.into()
+            } else if let Some(instruction) = gc_instruction(keyword, cursor, module)? {
+                instruction.into()
+            } else {
+                return Err(position.error(TextErrorKind::UnknownInstruction(keyword.to_string())));
+            }
+        }
+    };
+
+    Ok(instruction)
+}
+
+/// Parses a load or store instruction, along with its memory argument.
+/// Returns `None` if the keyword is not a load or store instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#memory-instructions>
+fn memory_instruction<'a>(
+    keyword: &str,
+    cursor: &mut Cursor<'a>,
+    memories: &Namespace<'a>,
+) -> Result<Option<MemoryInstruction>, TextError> {
+    let mut argument = |natural: u32| parse_memory_argument(cursor, memories, natural, false);
+
+    let instruction = match keyword {
+        "i32.load" => MemoryInstruction::Load(NumberType::I32, argument(2)?),
+        "i64.load" => MemoryInstruction::Load(NumberType::I64, argument(3)?),
+        "f32.load" => MemoryInstruction::Load(NumberType::F32, argument(2)?),
+        "f64.load" => MemoryInstruction::Load(NumberType::F64, argument(3)?),
+        "i32.store" => MemoryInstruction::Store(NumberType::I32, argument(2)?),
+        "i64.store" => MemoryInstruction::Store(NumberType::I64, argument(3)?),
+        "f32.store" => MemoryInstruction::Store(NumberType::F32, argument(2)?),
+        "f64.store" => MemoryInstruction::Store(NumberType::F64, argument(3)?),
+        "i32.load8_s" => {
+            MemoryInstruction::Load8(IntegerType::I32, SignExtension::Signed, argument(0)?)
+        }
+        "i32.load8_u" => {
+            MemoryInstruction::Load8(IntegerType::I32, SignExtension::Unsigned, argument(0)?)
+        }
+        "i64.load8_s" => {
+            MemoryInstruction::Load8(IntegerType::I64, SignExtension::Signed, argument(0)?)
+        }
+        "i64.load8_u" => {
+            MemoryInstruction::Load8(IntegerType::I64, SignExtension::Unsigned, argument(0)?)
+        }
+        "i32.load16_s" => {
+            MemoryInstruction::Load16(IntegerType::I32, SignExtension::Signed, argument(1)?)
+        }
+        "i32.load16_u" => {
+            MemoryInstruction::Load16(IntegerType::I32, SignExtension::Unsigned, argument(1)?)
+        }
+        "i64.load16_s" => {
+            MemoryInstruction::Load16(IntegerType::I64, SignExtension::Signed, argument(1)?)
+        }
+        "i64.load16_u" => {
+            MemoryInstruction::Load16(IntegerType::I64, SignExtension::Unsigned, argument(1)?)
+        }
+        "i64.load32_s" => MemoryInstruction::Load32(SignExtension::Signed, argument(2)?),
+        "i64.load32_u" => MemoryInstruction::Load32(SignExtension::Unsigned, argument(2)?),
+        "i32.store8" => MemoryInstruction::Store8(IntegerType::I32, argument(0)?),
+        "i64.store8" => MemoryInstruction::Store8(IntegerType::I64, argument(0)?),
+        "i32.store16" => MemoryInstruction::Store16(IntegerType::I32, argument(1)?),
+        "i64.store16" => MemoryInstruction::Store16(IntegerType::I64, argument(1)?),
+        "i64.store32" => MemoryInstruction::Store32(argument(2)?),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(instruction))
+}
+
+/// Parses the shape and lanes of a vector constant, returning the bits of the vector.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#vector-instructions>
+fn parse_vector_constant(cursor: &mut Cursor) -> Result<i128, TextError> {
+    let shape = cursor
+        .peek_keyword()
+        .and_then(vector_shape)
+        .ok_or_else(|| cursor.unexpected("a vector shape"))?;
+    let width = match shape {
+        VectorShape::I8x16 => 1,
+        VectorShape::I16x8 => 2,
+        VectorShape::I32x4 | VectorShape::F32x4 => 4,
+        VectorShape::I64x2 | VectorShape::F64x2 => 8,
+    };
+    let mut bytes = [0u8; 16];
+
+    cursor.advance();
+
+    for lane in bytes.chunks_mut(width) {
+        match shape {
+            VectorShape::I8x16 => lane.copy_from_slice(&cursor.expect_i8()?.to_le_bytes()),
+            VectorShape::I16x8 => lane.copy_from_slice(&cursor.expect_i16()?.to_le_bytes()),
+            VectorShape::I32x4 => lane.copy_from_slice(&cursor.expect_i32()?.to_le_bytes()),
+            VectorShape::I64x2 => lane.copy_from_slice(&cursor.expect_i64()?.to_le_bytes()),
+            VectorShape::F32x4 => lane.copy_from_slice(&cursor.expect_f32()?.to_le_bytes()),
+            VectorShape::F64x2 => lane.copy_from_slice(&cursor.expect_f64()?.to_le_bytes()),
+        }
+    }
+
+    Ok(i128::from_le_bytes(bytes))
+}
+
+/// Parses a lane index, which must fit in a single byte.
+fn parse_lane(cursor: &mut Cursor) -> Result<LaneIndex, TextError> {
+    let position = cursor.position();
+    let lane = cursor.expect_u32()?;
+
+    LaneIndex::try_from(lane)
+        .map_err(|_| position.error(TextErrorKind::InvalidNumber(lane.to_string())))
+}
+
+/// Parses a shuffle or a lane access instruction, along with its lane indices.
+/// Returns `None` if the keyword is not a shuffle or lane access instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#vector-instructions>
+fn vector_lane_instruction(
+    keyword: &str,
+    cursor: &mut Cursor,
+) -> Result<Option<VectorInstruction>, TextError> {
+    if keyword == "i8x16.shuffle" {
+        let mut lanes = [0; 16];
+
+        for lane in lanes.iter_mut() {
+            *lane = parse_lane(cursor)?;
+        }
+
+        return Ok(Some(VectorInstruction::Shuffle(lanes)));
+    }
+
+    let (shape, operation) = match keyword.split_once('.') {
+        Some((prefix, operation)) => match vector_shape(prefix) {
+            Some(shape) => (shape, operation),
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+
+    let instruction = match operation {
+        "extract_lane" => VectorInstruction::ExtractLane(shape, None, parse_lane(cursor)?),
+        "extract_lane_s" => {
+            VectorInstruction::ExtractLane(shape, Some(SignExtension::Signed), parse_lane(cursor)?)
+        }
+        "extract_lane_u" => VectorInstruction::ExtractLane(
+            shape,
+            Some(SignExtension::Unsigned),
+            parse_lane(cursor)?,
+        ),
+        "replace_lane" => VectorInstruction::ReplaceLane(shape, parse_lane(cursor)?),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(instruction).filter(VectorInstruction::is_defined))
+}
+
+/// Parses a vector load or store instruction, along with its memory argument and lane index.
+/// Returns `None` if the keyword is not a vector load or store instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#vector-instructions>
+fn vector_memory_instruction<'a>(
+    keyword: &str,
+    cursor: &mut Cursor<'a>,
+    memories: &Namespace<'a>,
+) -> Result<Option<VectorInstruction>, TextError> {
+    use IntegerVectorShape::{I16x8, I32x4, I64x2, I8x16};
+
+    let instruction = match keyword {
+        "v128.load" => VectorInstruction::Load(parse_memory_argument(cursor, memories, 4, false)?),
+        "v128.store" => {
+            VectorInstruction::Store(parse_memory_argument(cursor, memories, 4, false)?)
+        }
+        "v128.load8x8_s" => VectorInstruction::LoadExtend(
+            I8x16,
+            SignExtension::Signed,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load8x8_u" => VectorInstruction::LoadExtend(
+            I8x16,
+            SignExtension::Unsigned,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load16x4_s" => VectorInstruction::LoadExtend(
+            I16x8,
+            SignExtension::Signed,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load16x4_u" => VectorInstruction::LoadExtend(
+            I16x8,
+            SignExtension::Unsigned,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load32x2_s" => VectorInstruction::LoadExtend(
+            I32x4,
+            SignExtension::Signed,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load32x2_u" => VectorInstruction::LoadExtend(
+            I32x4,
+            SignExtension::Unsigned,
+            parse_memory_argument(cursor, memories, 3, false)?,
+        ),
+        "v128.load8_splat" => {
+            VectorInstruction::LoadSplat(I8x16, parse_memory_argument(cursor, memories, 0, false)?)
+        }
+        "v128.load16_splat" => {
+            VectorInstruction::LoadSplat(I16x8, parse_memory_argument(cursor, memories, 1, false)?)
+        }
+        "v128.load32_splat" => {
+            VectorInstruction::LoadSplat(I32x4, parse_memory_argument(cursor, memories, 2, false)?)
+        }
+        "v128.load64_splat" => {
+            VectorInstruction::LoadSplat(I64x2, parse_memory_argument(cursor, memories, 3, false)?)
+        }
+        "v128.load32_zero" => {
+            VectorInstruction::LoadZero(I32x4, parse_memory_argument(cursor, memories, 2, false)?)
+        }
+        "v128.load64_zero" => {
+            VectorInstruction::LoadZero(I64x2, parse_memory_argument(cursor, memories, 3, false)?)
+        }
+        "v128.load8_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 0, true)?;
+
+            VectorInstruction::LoadLane(I8x16, argument, parse_lane(cursor)?)
+        }
+        "v128.load16_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 1, true)?;
+
+            VectorInstruction::LoadLane(I16x8, argument, parse_lane(cursor)?)
+        }
+        "v128.load32_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 2, true)?;
+
+            VectorInstruction::LoadLane(I32x4, argument, parse_lane(cursor)?)
+        }
+        "v128.load64_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 3, true)?;
+
+            VectorInstruction::LoadLane(I64x2, argument, parse_lane(cursor)?)
+        }
+        "v128.store8_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 0, true)?;
+
+            VectorInstruction::StoreLane(I8x16, argument, parse_lane(cursor)?)
+        }
+        "v128.store16_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 1, true)?;
+
+            VectorInstruction::StoreLane(I16x8, argument, parse_lane(cursor)?)
+        }
+        "v128.store32_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 2, true)?;
+
+            VectorInstruction::StoreLane(I32x4, argument, parse_lane(cursor)?)
+        }
+        "v128.store64_lane" => {
+            let argument = parse_memory_argument(cursor, memories, 3, true)?;
+
+            VectorInstruction::StoreLane(I64x2, argument, parse_lane(cursor)?)
+        }
+        _ => return Ok(None),
+    };
+
+    Ok(Some(instruction))
+}
+
+/// Parses a GC instruction other than a branch on a cast, along with its immediates.
+/// Fields are referenced by their numeric indices or by the identifiers declared by their struct type.
+/// Returns `None` if the keyword is not a GC instruction.
+///
+/// See <https://webassembly.github.io/gc/core/text/instructions.html#aggregate-instructions>
+fn gc_instruction<'a>(
+    keyword: &str,
+    cursor: &mut Cursor<'a>,
+    module: &mut ModuleContext<'a>,
+) -> Result<Option<GcInstruction>, TextError> {
+    let signed = Some(SignExtension::Signed);
+    let unsigned = Some(SignExtension::Unsigned);
+    let instruction = match keyword {
+        "struct.new" => GcInstruction::StructNew(module.types.expect(cursor)?),
+        "struct.new_default" => GcInstruction::StructNewDefault(module.types.expect(cursor)?),
+        "struct.get" => {
+            let (kind, field) = module.expect_field(cursor)?;
+
+            GcInstruction::StructGet(kind, field, None)
+        }
+        "struct.get_s" => {
+            let (kind, field) = module.expect_field(cursor)?;
+
+            GcInstruction::StructGet(kind, field, signed)
+        }
+        "struct.get_u" => {
+            let (kind, field) = module.expect_field(cursor)?;
+
+            GcInstruction::StructGet(kind, field, unsigned)
+        }
+        "struct.set" => {
+            let (kind, field) = module.expect_field(cursor)?;
+
+            GcInstruction::StructSet(kind, field)
+        }
+        "array.new" => GcInstruction::ArrayNew(module.types.expect(cursor)?),
+        "array.new_default" => GcInstruction::ArrayNewDefault(module.types.expect(cursor)?),
+        "array.new_fixed" => {
+            GcInstruction::ArrayNewFixed(module.types.expect(cursor)?, cursor.expect_u32()?)
+        }
+        "array.new_data" => {
+            module.uses_data_count = true;
+            GcInstruction::ArrayNewData(module.types.expect(cursor)?, module.data.expect(cursor)?)
+        }
+        "array.new_elem" => GcInstruction::ArrayNewElement(
+            module.types.expect(cursor)?,
+            module.elements.expect(cursor)?,
+        ),
+        "array.get" => GcInstruction::ArrayGet(module.types.expect(cursor)?, None),
+        "array.get_s" => GcInstruction::ArrayGet(module.types.expect(cursor)?, signed),
+        "array.get_u" => GcInstruction::ArrayGet(module.types.expect(cursor)?, unsigned),
+        "array.set" => GcInstruction::ArraySet(module.types.expect(cursor)?),
+        "array.len" => GcInstruction::ArrayLength,
+        "array.fill" => GcInstruction::ArrayFill(module.types.expect(cursor)?),
+        "array.copy" => {
+            GcInstruction::ArrayCopy(module.types.expect(cursor)?, module.types.expect(cursor)?)
+        }
+        "array.init_data" => {
+            module.uses_data_count = true;
+            GcInstruction::ArrayInitData(module.types.expect(cursor)?, module.data.expect(cursor)?)
+        }
+        "array.init_elem" => GcInstruction::ArrayInitElement(
+            module.types.expect(cursor)?,
+            module.elements.expect(cursor)?,
+        ),
+        "ref.test" => GcInstruction::Test(parse_cast_type(cursor, &module.types)?),
+        "ref.cast" => GcInstruction::Cast(parse_cast_type(cursor, &module.types)?),
+        "any.convert_extern" => GcInstruction::AnyConvertExtern,
+        "extern.convert_any" => GcInstruction::ExternConvertAny,
+        "ref.i31" => GcInstruction::ReferenceI31,
+        "i31.get_s" => GcInstruction::I31Get(SignExtension::Signed),
+        "i31.get_u" => GcInstruction::I31Get(SignExtension::Unsigned),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(instruction))
+}
+
+/// Parses an atomic memory instruction, along with its memory argument.
+/// Returns `None` if the keyword is not an atomic memory instruction.
+///
+/// See <https://webassembly.github.io/threads/core/text/instructions.html#atomic-memory-instructions>
+fn atomic_instruction<'a>(
+    keyword: &str,
+    cursor: &mut Cursor<'a>,
+    memories: &Namespace<'a>,
+) -> Result<Option<AtomicInstruction>, TextError> {
+    let mut argument = |natural: u32| parse_memory_argument(cursor, memories, natural, false);
+
+    let instruction = match keyword {
+        "memory.atomic.notify" => AtomicInstruction::Notify(argument(2)?),
+        "memory.atomic.wait32" => AtomicInstruction::Wait(IntegerType::I32, argument(2)?),
+        "memory.atomic.wait64" => AtomicInstruction::Wait(IntegerType::I64, argument(3)?),
+        "atomic.fence" => AtomicInstruction::Fence,
+        _ => {
+            let (kind, natural, access) = match keyword.split_once(".atomic.") {
+                Some(("i32", access)) => (IntegerType::I32, 2, access),
+                Some(("i64", access)) => (IntegerType::I64, 3, access),
+                _ => return Ok(None),
+            };
+
+            match (kind, access) {
+                (_, "load") => AtomicInstruction::Load(kind, argument(natural)?),
+                (_, "load8_u") => AtomicInstruction::Load8(kind, argument(0)?),
+                (_, "load16_u") => AtomicInstruction::Load16(kind, argument(1)?),
+                (IntegerType::I64, "load32_u") => AtomicInstruction::Load32(argument(2)?),
+                (_, "store") => AtomicInstruction::Store(kind, argument(natural)?),
+                (_, "store8") => AtomicInstruction::Store8(kind, argument(0)?),
+                (_, "store16") => AtomicInstruction::Store16(kind, argument(1)?),
+                (IntegerType::I64, "store32") => AtomicInstruction::Store32(argument(2)?),
+                _ => {
+                    let (width, operation) = match access.split_once('.') {
+                        Some((width, operation)) => (width, operation),
+                        None => return Ok(None),
+                    };
+                    let operation = match width {
+                        "rmw" => Some(operation),
+                        _ => operation.strip_suffix("_u"),
+                    }
+                    .and_then(atomic_operation);
+
+                    match (kind, width, operation) {
+                        (_, "rmw", Some(operation)) => {
+                            AtomicInstruction::ReadModifyWrite(operation, kind, argument(natural)?)
+                        }
+                        (_, "rmw8", Some(operation)) => {
+                            AtomicInstruction::ReadModifyWrite8(operation, kind, argument(0)?)
+                        }
+                        (_, "rmw16", Some(operation)) => {
+                            AtomicInstruction::ReadModifyWrite16(operation, kind, argument(1)?)
+                        }
+                        (IntegerType::I64, "rmw32", Some(operation)) => {
+                            AtomicInstruction::ReadModifyWrite32(operation, argument(2)?)
+                        }
+                        _ => return Ok(None),
+                    }
+                }
+            }
+        }
+    };
+
+    Ok(Some(instruction))
+}
+
+/// Maps the name of an atomic read-modify-write operation to the operation.
+fn atomic_operation(name: &str) -> Option<AtomicOperation> {
+    match name {
+        "add" => Some(AtomicOperation::Add),
+        "sub" => Some(AtomicOperation::Subtract),
+        "and" => Some(AtomicOperation::And),
+        "or" => Some(AtomicOperation::Or),
+        "xor" => Some(AtomicOperation::Xor),
+        "xchg" => Some(AtomicOperation::Exchange),
+        "cmpxchg" => Some(AtomicOperation::CompareExchange),
+        _ => None,
+    }
+}
+
+/// Parses the optional memory index, offset and alignment of a memory argument.
+/// The text format expresses alignment in bytes, which must be a power of 2.
+/// For lane instructions, a lone number is the lane index rather than the memory index.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#memory-instructions>
+fn parse_memory_argument<'a>(
+    cursor: &mut Cursor<'a>,
+    memories: &Namespace<'a>,
+    natural: u32,
+    lane: bool,
+) -> Result<MemoryArgument, TextError> {
+    let explicit = match cursor.peek() {
+        Some(TokenKind::Identifier(_)) => true,
+        Some(TokenKind::Reserved(_)) if cursor.peek_u32() => {
+            !lane
+                || match cursor.peek_nth(1) {
+                    Some(TokenKind::Reserved(_)) => true,
+                    Some(TokenKind::Keyword(keyword)) => {
+                        keyword.starts_with("offset=") || keyword.starts_with("align=")
+                    }
+                    _ => false,
+                }
+        }
+        _ => false,
+    };
+    let memory = if explicit {
+        memories.expect(cursor)?
+    } else {
+        0
+    };
+    let mut offset = 0;
+    let mut align = natural;
+
+    if let Some(text) = cursor
+        .peek_keyword()
+        .and_then(|keyword| keyword.strip_prefix("offset="))
+    {
+        offset = parse_u64(text)
+            .ok_or_else(|| cursor.error(TextErrorKind::InvalidNumber(text.to_string())))?;
+        cursor.advance();
+    }
+
+    if let Some(text) = cursor
+        .peek_keyword()
+        .and_then(|keyword| keyword.strip_prefix("align="))
+    {
+        let value = parse_u32(text)
+            .ok_or_else(|| cursor.error(TextErrorKind::InvalidNumber(text.to_string())))?;
+
+        if !value.is_power_of_two() {
+            return Err(cursor.error(TextErrorKind::InvalidAlignment(text.to_string())));
+        }
+
+        align = value.trailing_zeros();
+        cursor.advance();
+    }
+
+    Ok(MemoryArgument::with_memory(memory, align, offset))
+}
+
+/// Maps the keyword of a numeric instruction without immediates to the instruction.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#numeric-instructions>
+fn numeric_instruction(keyword: &str) -> Option<NumericInstruction> {
+    let (prefix, operation) = keyword.split_once('.')?;
+    let (number, integer, float) = match prefix {
+        "i32" => (NumberType::I32, Some(IntegerType::I32), None),
+        "i64" => (NumberType::I64, Some(IntegerType::I64), None),
+        "f32" => (NumberType::F32, None, Some(FloatType::F32)),
+        "f64" => (NumberType::F64, None, Some(FloatType::F64)),
+        _ => return None,
+    };
+
+    let instruction = match operation {
+        "add" => NumericInstruction::Add(number),
+        "sub" => NumericInstruction::Subtract(number),
+        "mul" => NumericInstruction::Multiply(number),
+        "eq" => NumericInstruction::Equal(number),
+        "ne" => NumericInstruction::NotEqual(number),
+        _ => match (integer, float) {
+            (Some(kind), _) => integer_instruction(kind, operation)?,
+            (_, Some(kind)) => float_instruction(kind, operation)?,
+            _ => return None,
+        },
+    };
+
+    Some(instruction)
+}
+
+fn sign_extension(suffix: &str) -> Option<SignExtension> {
+    match suffix {
+        "s" => Some(SignExtension::Signed),
+        "u" => Some(SignExtension::Unsigned),
+        _ => None,
+    }
+}
+
+fn integer_instruction(kind: IntegerType, operation: &str) -> Option<NumericInstruction> {
+    let instruction = match (kind, operation) {
+        (_, "clz") => NumericInstruction::CountLeadingZeros(kind),
+        (_, "ctz") => NumericInstruction::CountTrailingZeros(kind),
+        (_, "popcnt") => NumericInstruction::CountOnes(kind),
+        (_, "and") => NumericInstruction::And(kind),
+        (_, "or") => NumericInstruction::Or(kind),
+        (_, "xor") => NumericInstruction::Xor(kind),
+        (_, "shl") => NumericInstruction::ShiftLeft(kind),
+        (_, "rotl") => NumericInstruction::RotateLeft(kind),
+        (_, "rotr") => NumericInstruction::RotateRight(kind),
+        (_, "eqz") => NumericInstruction::EqualToZero(kind),
+        (_, "extend8_s") => NumericInstruction::ExtendSigned8(kind),
+        (_, "extend16_s") => NumericInstruction::ExtendSigned16(kind),
+        (IntegerType::I64, "extend32_s") => NumericInstruction::ExtendSigned32,
+        (IntegerType::I32, "wrap_i64") => NumericInstruction::Wrap,
+        (IntegerType::I32, "reinterpret_f32") | (IntegerType::I64, "reinterpret_f64") => {
+            NumericInstruction::ReinterpretFloat(kind)
+        }
+        _ => {
+            let (name, suffix) = operation.rsplit_once('_')?;
+            let sign = sign_extension(suffix)?;
+
+            match (kind, name) {
+                (_, "div") => NumericInstruction::DivideInteger(kind, sign),
+                (_, "rem") => NumericInstruction::Remainder(kind, sign),
+                (_, "shr") => NumericInstruction::ShiftRight(kind, sign),
+                (_, "lt") => NumericInstruction::LessThanInteger(kind, sign),
+                (_, "gt") => NumericInstruction::GreaterThanInteger(kind, sign),
+                (_, "le") => NumericInstruction::LessThanOrEqualToInteger(kind, sign),
+                (_, "ge") => NumericInstruction::GreaterThanOrEqualToInteger(kind, sign),
+                (_, "trunc_f32") => {
+                    NumericInstruction::ConvertAndTruncate(kind, FloatType::F32, sign)
+                }
+                (_, "trunc_f64") => {
+                    NumericInstruction::ConvertAndTruncate(kind, FloatType::F64, sign)
+                }
+                (_, "trunc_sat_f32") => {
+                    NumericInstruction::ConvertAndTruncateWithSaturation(kind, FloatType::F32, sign)
+                }
+                (_, "trunc_sat_f64") => {
+                    NumericInstruction::ConvertAndTruncateWithSaturation(kind, FloatType::F64, sign)
+                }
+                (IntegerType::I64, "extend_i32") => {
+                    NumericInstruction::ExtendWithSignExtension(sign)
+                }
+                _ => return None,
+            }
+        }
+    };
+
+    Some(instruction)
+}
+
+fn float_instruction(kind: FloatType, operation: &str) -> Option<NumericInstruction> {
+    let instruction = match (kind, operation) {
+        (_, "abs") => NumericInstruction::AbsoluteValue(kind),
+        (_, "neg") => NumericInstruction::Negate(kind),
+        (_, "sqrt") => NumericInstruction::SquareRoot(kind),
+        (_, "ceil") => NumericInstruction::Ceiling(kind),
+        (_, "floor") => NumericInstruction::Floor(kind),
+        (_, "trunc") => NumericInstruction::Truncate(kind),
+        (_, "nearest") => NumericInstruction::Nearest(kind),
+        (_, "div") => NumericInstruction::DivideFloat(kind),
+        (_, "min") => NumericInstruction::Minimum(kind),
+        (_, "max") => NumericInstruction::Maximum(kind),
+        (_, "copysign") => NumericInstruction::CopySign(kind),
+        (_, "lt") => NumericInstruction::LessThanFloat(kind),
+        (_, "gt") => NumericInstruction::GreaterThanFloat(kind),
+        (_, "le") => NumericInstruction::LessThanOrEqualToFloat(kind),
+        (_, "ge") => NumericInstruction::GreaterThanOrEqualToFloat(kind),
+        (FloatType::F32, "demote_f64") => NumericInstruction::Demote,
+        (FloatType::F64, "promote_f32") => NumericInstruction::Promote,
+        (FloatType::F32, "reinterpret_i32") | (FloatType::F64, "reinterpret_i64") => {
+            NumericInstruction::ReinterpretInteger(kind)
+        }
+        _ => {
+            let (name, suffix) = operation.rsplit_once('_')?;
+            let sign = sign_extension(suffix)?;
+
+            match name {
+                "convert_i32" => NumericInstruction::Convert(kind, IntegerType::I32, sign),
+                "convert_i64" => NumericInstruction::Convert(kind, IntegerType::I64, sign),
+                _ => return None,
+            }
+        }
+    };
+
+    Some(instruction)
+}
+
+/// Maps the keyword of a vector instruction without immediates to the instruction.
+/// Instructions that are not defined for their shape are rejected.
+///
+/// See <https://webassembly.github.io/spec/core/text/instructions.html#vector-instructions>
+fn vector_instruction(keyword: &str) -> Option<VectorInstruction> {
+    let (prefix, operation) = keyword.split_once('.')?;
+
+    if prefix == "v128" {
+        return match operation {
+            "not" => Some(VectorInstruction::Not),
+            "and" => Some(VectorInstruction::And),
+            "andnot" => Some(VectorInstruction::AndNot),
+            "or" => Some(VectorInstruction::Or),
+            "xor" => Some(VectorInstruction::Xor),
+            "bitselect" => Some(VectorInstruction::BitSelect),
+            "any_true" => Some(VectorInstruction::AnyTrue),
+            _ => None,
+        };
+    }
+
+    let shape = vector_shape(prefix)?;
+    let instruction = match operation {
+        "splat" => VectorInstruction::Splat(shape),
+        "eq" => VectorInstruction::Equal(shape),
+        "ne" => VectorInstruction::NotEqual(shape),
+        "abs" => VectorInstruction::AbsoluteValue(shape),
+        "neg" => VectorInstruction::Negate(shape),
+        "add" => VectorInstruction::Add(shape),
+        "sub" => VectorInstruction::Subtract(shape),
+        "mul" => VectorInstruction::Multiply(shape),
+        _ => match shape {
+            VectorShape::I8x16 => integer_vector_instruction(IntegerVectorShape::I8x16, operation)?,
+            VectorShape::I16x8 => integer_vector_instruction(IntegerVectorShape::I16x8, operation)?,
+            VectorShape::I32x4 => integer_vector_instruction(IntegerVectorShape::I32x4, operation)?,
+            VectorShape::I64x2 => integer_vector_instruction(IntegerVectorShape::I64x2, operation)?,
+            VectorShape::F32x4 => float_vector_instruction(FloatVectorShape::F32x4, operation)?,
+            VectorShape::F64x2 => float_vector_instruction(FloatVectorShape::F64x2, operation)?,
+        },
+    };
+
+    Some(instruction).filter(VectorInstruction::is_defined)
+}
+
+fn vector_shape(prefix: &str) -> Option<VectorShape> {
+    match prefix {
+        "i8x16" => Some(VectorShape::I8x16),
+        "i16x8" => Some(VectorShape::I16x8),
+        "i32x4" => Some(VectorShape::I32x4),
+        "i64x2" => Some(VectorShape::I64x2),
+        "f32x4" => Some(VectorShape::F32x4),
+        "f64x2" => Some(VectorShape::F64x2),
+        _ => None,
+    }
+}
+
+/// The integer vector shape with lanes twice as wide as the given shape.
+fn wider_shape(kind: IntegerVectorShape) -> Option<IntegerVectorShape> {
+    match kind {
+        IntegerVectorShape::I8x16 => Some(IntegerVectorShape::I16x8),
+        IntegerVectorShape::I16x8 => Some(IntegerVectorShape::I32x4),
+        IntegerVectorShape::I32x4 => Some(IntegerVectorShape::I64x2),
+        IntegerVectorShape::I64x2 => None,
+    }
+}
+
+fn integer_vector_instruction(
+    kind: IntegerVectorShape,
+    operation: &str,
+) -> Option<VectorInstruction> {
+    let instruction = match (kind, operation) {
+        (IntegerVectorShape::I8x16, "swizzle") => VectorInstruction::Swizzle,
+        (IntegerVectorShape::I8x16, "popcnt") => VectorInstruction::CountOnes,
+        (IntegerVectorShape::I16x8, "q15mulr_sat_s") => VectorInstruction::Q15MultiplyRoundSaturate,
+        (IntegerVectorShape::I32x4, "dot_i16x8_s") => VectorInstruction::DotProduct,
+        (IntegerVectorShape::I32x4, "trunc_sat_f32x4_s") => {
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F32x4,
+                SignExtension::Signed,
+            )
+        }
+        (IntegerVectorShape::I32x4, "trunc_sat_f32x4_u") => {
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F32x4,
+                SignExtension::Unsigned,
+            )
+        }
+        (IntegerVectorShape::I32x4, "trunc_sat_f64x2_s_zero") => {
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F64x2,
+                SignExtension::Signed,
+            )
+        }
+        (IntegerVectorShape::I32x4, "trunc_sat_f64x2_u_zero") => {
+            VectorInstruction::ConvertAndTruncateWithSaturation(
+                FloatVectorShape::F64x2,
+                SignExtension::Unsigned,
+            )
+        }
+        (IntegerVectorShape::I8x16, "relaxed_swizzle") => VectorInstruction::RelaxedSwizzle,
+        (IntegerVectorShape::I16x8, "relaxed_q15mulr_s") => {
+            VectorInstruction::RelaxedQ15MultiplyRound
+        }
+        (IntegerVectorShape::I16x8, "relaxed_dot_i8x16_i7x16_s") => {
+            VectorInstruction::RelaxedDotProduct
+        }
+        (IntegerVectorShape::I32x4, "relaxed_dot_i8x16_i7x16_add_s") => {
+            VectorInstruction::RelaxedDotProductAdd
+        }
+        (IntegerVectorShape::I32x4, "relaxed_trunc_f32x4_s") => {
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F32x4, SignExtension::Signed)
+        }
+        (IntegerVectorShape::I32x4, "relaxed_trunc_f32x4_u") => {
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F32x4, SignExtension::Unsigned)
+        }
+        (IntegerVectorShape::I32x4, "relaxed_trunc_f64x2_s_zero") => {
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F64x2, SignExtension::Signed)
+        }
+        (IntegerVectorShape::I32x4, "relaxed_trunc_f64x2_u_zero") => {
+            VectorInstruction::RelaxedTruncate(FloatVectorShape::F64x2, SignExtension::Unsigned)
+        }
+        (_, "relaxed_laneselect") => VectorInstruction::RelaxedLaneSelect(kind),
+        (_, "all_true") => VectorInstruction::AllTrue(kind),
+        (_, "bitmask") => VectorInstruction::Bitmask(kind),
+        (_, "shl") => VectorInstruction::ShiftLeft(kind),
+        (_, "avgr_u") => VectorInstruction::AverageRounded(kind),
+        _ => {
+            let (name, suffix) = operation.rsplit_once('_')?;
+            let sign = sign_extension(suffix)?;
+
+            match name {
+                "lt" => VectorInstruction::LessThanInteger(kind, sign),
+                "gt" => VectorInstruction::GreaterThanInteger(kind, sign),
+                "le" => VectorInstruction::LessThanOrEqualToInteger(kind, sign),
+                "ge" => VectorInstruction::GreaterThanOrEqualToInteger(kind, sign),
+                "shr" => VectorInstruction::ShiftRight(kind, sign),
+                "add_sat" => VectorInstruction::AddSaturate(kind, sign),
+                "sub_sat" => VectorInstruction::SubtractSaturate(kind, sign),
+                "min" => VectorInstruction::MinimumInteger(kind, sign),
+                "max" => VectorInstruction::MaximumInteger(kind, sign),
+                _ => {
+                    // The remaining instructions name the shape of their operands.
+                    let (name, source) = name.rsplit_once('_')?;
+                    let source = match vector_shape(source)? {
+                        VectorShape::I8x16 => IntegerVectorShape::I8x16,
+                        VectorShape::I16x8 => IntegerVectorShape::I16x8,
+                        VectorShape::I32x4 => IntegerVectorShape::I32x4,
+                        _ => return None,
+                    };
+
+                    if name == "narrow" {
+                        return (wider_shape(kind) == Some(source))
+                            .then_some(VectorInstruction::Narrow(kind, sign));
+                    }
+
+                    if wider_shape(source) != Some(kind) {
+                        return None;
+                    }
+
+                    match name {
+                        "extend_low" => VectorInstruction::Extend(kind, VectorHalf::Low, sign),
+                        "extend_high" => VectorInstruction::Extend(kind, VectorHalf::High, sign),
+                        "extmul_low" => {
+                            VectorInstruction::ExtendedMultiply(kind, VectorHalf::Low, sign)
+                        }
+                        "extmul_high" => {
+                            VectorInstruction::ExtendedMultiply(kind, VectorHalf::High, sign)
+                        }
+                        "extadd_pairwise" => VectorInstruction::ExtendedAddPairwise(kind, sign),
+                        _ => return None,
+                    }
+                }
+            }
+        }
+    };
+
+    Some(instruction)
+}
+
+fn float_vector_instruction(kind: FloatVectorShape, operation: &str) -> Option<VectorInstruction> {
+    let instruction = match (kind, operation) {
+        (_, "sqrt") => VectorInstruction::SquareRoot(kind),
+        (_, "ceil") => VectorInstruction::Ceiling(kind),
+        (_, "floor") => VectorInstruction::Floor(kind),
+        (_, "trunc") => VectorInstruction::Truncate(kind),
+        (_, "nearest") => VectorInstruction::Nearest(kind),
+        (_, "div") => VectorInstruction::DivideFloat(kind),
+        (_, "min") => VectorInstruction::MinimumFloat(kind),
+        (_, "max") => VectorInstruction::MaximumFloat(kind),
+        (_, "pmin") => VectorInstruction::PseudoMinimum(kind),
+        (_, "pmax") => VectorInstruction::PseudoMaximum(kind),
+        (_, "relaxed_madd") => VectorInstruction::RelaxedMultiplyAdd(kind),
+        (_, "relaxed_nmadd") => VectorInstruction::RelaxedNegativeMultiplyAdd(kind),
+        (_, "relaxed_min") => VectorInstruction::RelaxedMinimum(kind),
+        (_, "relaxed_max") => VectorInstruction::RelaxedMaximum(kind),
+        (_, "lt") => VectorInstruction::LessThanFloat(kind),
+        (_, "gt") => VectorInstruction::GreaterThanFloat(kind),
+        (_, "le") => VectorInstruction::LessThanOrEqualToFloat(kind),
+        (_, "ge") => VectorInstruction::GreaterThanOrEqualToFloat(kind),
+        (FloatVectorShape::F32x4, "demote_f64x2_zero") => VectorInstruction::Demote,
+        (FloatVectorShape::F64x2, "promote_low_f32x4") => VectorInstruction::Promote,
+        (FloatVectorShape::F32x4, "convert_i32x4_s")
+        | (FloatVectorShape::F64x2, "convert_low_i32x4_s") => {
+            VectorInstruction::Convert(kind, SignExtension::Signed)
+        }
+        (FloatVectorShape::F32x4, "convert_i32x4_u")
+        | (FloatVectorShape::F64x2, "convert_low_i32x4_u") => {
+            VectorInstruction::Convert(kind, SignExtension::Unsigned)
+        }
+        _ => return None,
+    };
+
+    Some(instruction)
+}
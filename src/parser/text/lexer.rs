@@ -0,0 +1,436 @@
+use crate::parser::text::errors::{TextError, TextErrorKind};
+
+/// A location in the source text. Lines and columns start at 1.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Creates an error at this position.
+    pub fn error(self, kind: TextErrorKind) -> TextError {
+        TextError::new(self.line, self.column, kind)
+    }
+}
+
+/// The lexical tokens of the text format.
+///
+/// See <https://webassembly.github.io/spec/core/text/lexical.html#tokens>
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind<'a> {
+    LeftParen,
+    RightParen,
+    /// A keyword, including keywords with a `=` immediate such as `offset=8`.
+    Keyword(&'a str),
+    /// An identifier without its leading `$`.
+    Identifier(&'a str),
+    /// Any other sequence of identifier characters, such as a number.
+    Reserved(&'a str),
+    /// The decoded bytes of a string literal.
+    String(Vec<u8>),
+    /// The opening of an annotation (i.e. `(@name`), with the name of the annotation.
+    Annotation(&'a str),
+}
+
+impl TokenKind<'_> {
+    /// Describes the token for use in error messages.
+    pub fn describe(&self) -> String {
+        match self {
+            TokenKind::LeftParen => "`(`".to_string(),
+            TokenKind::RightParen => "`)`".to_string(),
+            TokenKind::Keyword(keyword) => format!("`{}`", keyword),
+            TokenKind::Identifier(id) => format!("`${}`", id),
+            TokenKind::Reserved(text) => format!("`{}`", text),
+            TokenKind::String(_) => "a string".to_string(),
+            TokenKind::Annotation(name) => format!("`(@{}`", name),
+        }
+    }
+}
+
+/// A token along with its position in the source text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    pub position: Position,
+}
+
+/// Splits the source text into tokens, skipping white space and comments.
+/// Annotations other than custom section annotations are ignored, along with their contents.
+/// Returns the tokens along with the position of the end of the source text.
+///
+/// See <https://webassembly.github.io/spec/core/text/lexical.html>
+pub fn tokenize(text: &str) -> Result<(Vec<Token<'_>>, Position), TextError> {
+    let mut lexer = Lexer {
+        text,
+        offset: 0,
+        line: 1,
+        column: 1,
+    };
+    let mut tokens = Vec::new();
+
+    while let Some(token) = lexer.next_token()? {
+        tokens.push(token);
+    }
+
+    let end = lexer.position();
+
+    Ok((skip_annotations(tokens, end)?, end))
+}
+
+/// Removes all annotations other than `@custom` from the tokens.
+///
+/// See <https://webassembly.github.io/annotations/core/text/lexical.html#annotations>
+fn skip_annotations(tokens: Vec<Token>, end: Position) -> Result<Vec<Token>, TextError> {
+    let mut kept = Vec::with_capacity(tokens.len());
+    let mut skipped_depth = 0usize;
+
+    for token in tokens {
+        if skipped_depth > 0 {
+            match token.kind {
+                TokenKind::LeftParen | TokenKind::Annotation(_) => skipped_depth += 1,
+                TokenKind::RightParen => skipped_depth -= 1,
+                _ => {}
+            }
+        } else if matches!(token.kind, TokenKind::Annotation(name) if name != "custom") {
+            skipped_depth = 1;
+        } else {
+            kept.push(token);
+        }
+    }
+
+    if skipped_depth > 0 {
+        return Err(end.error(TextErrorKind::UnexpectedEndOfInput));
+    }
+
+    Ok(kept)
+}
+
+/// Determines whether the given byte may be part of a keyword, identifier or reserved token.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#text-idchar>
+fn is_identifier_character(byte: u8) -> bool {
+    matches!(byte,
+        b'0'..=b'9'
+        | b'A'..=b'Z'
+        | b'a'..=b'z'
+        | b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'/'
+        | b':' | b'<' | b'=' | b'>' | b'?' | b'@' | b'\\' | b'^' | b'_' | b'`' | b'|' | b'~')
+}
+
+struct Lexer<'a> {
+    text: &'a str,
+    offset: usize,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn position(&self) -> Position {
+        Position {
+            line: self.line,
+            column: self.column,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn peek_second(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset + 1).copied()
+    }
+
+    /// Consumes a single byte, tracking the line and column.
+    /// Columns are only advanced on the first byte of a UTF-8 encoded character.
+    fn advance(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+
+        self.offset += 1;
+
+        if byte == b'\n' {
+            self.line += 1;
+            self.column = 1;
+        } else if byte & 0xC0 != 0x80 {
+            self.column += 1;
+        }
+
+        Some(byte)
+    }
+
+    fn unexpected_character(&self) -> TextError {
+        let character = self.text[self.offset..].chars().next().unwrap_or_default();
+
+        self.position()
+            .error(TextErrorKind::UnexpectedCharacter(character))
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, TextError> {
+        self.skip_white_space()?;
+
+        let position = self.position();
+        let kind = match self.peek() {
+            None => return Ok(None),
+            Some(b'(') if self.peek_second() == Some(b'@') => {
+                self.advance();
+                self.advance();
+
+                let name = self.identifier_characters();
+
+                if name.is_empty() {
+                    return Err(self.unexpected_character());
+                }
+
+                TokenKind::Annotation(name)
+            }
+            Some(b'(') => {
+                self.advance();
+                TokenKind::LeftParen
+            }
+            Some(b')') => {
+                self.advance();
+                TokenKind::RightParen
+            }
+            Some(b'"') => TokenKind::String(self.string(position)?),
+            Some(byte) if is_identifier_character(byte) => {
+                let text = self.identifier_characters();
+
+                match text.as_bytes()[0] {
+                    b'$' if text.len() > 1 => TokenKind::Identifier(&text[1..]),
+                    b'a'..=b'z' => TokenKind::Keyword(text),
+                    _ => TokenKind::Reserved(text),
+                }
+            }
+            Some(_) => return Err(self.unexpected_character()),
+        };
+
+        Ok(Some(Token { kind, position }))
+    }
+
+    fn identifier_characters(&mut self) -> &'a str {
+        let start = self.offset;
+
+        while self.peek().map(is_identifier_character).unwrap_or(false) {
+            self.advance();
+        }
+
+        &self.text[start..self.offset]
+    }
+
+    /// Skips white space, line comments and (nested) block comments.
+    ///
+    /// See <https://webassembly.github.io/spec/core/text/lexical.html#white-space>
+    fn skip_white_space(&mut self) -> Result<(), TextError> {
+        loop {
+            match (self.peek(), self.peek_second()) {
+                (Some(b' ' | b'\t' | b'\n' | b'\r'), _) => {
+                    self.advance();
+                }
+                (Some(b';'), Some(b';')) => {
+                    while !matches!(self.peek(), None | Some(b'\n')) {
+                        self.advance();
+                    }
+                }
+                (Some(b'('), Some(b';')) => self.skip_block_comment()?,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn skip_block_comment(&mut self) -> Result<(), TextError> {
+        let start = self.position();
+        let mut depth = 0usize;
+
+        loop {
+            match (self.peek(), self.peek_second()) {
+                (None, _) => return Err(start.error(TextErrorKind::UnterminatedComment)),
+                (Some(b'('), Some(b';')) => {
+                    self.advance();
+                    self.advance();
+                    depth += 1;
+                }
+                (Some(b';'), Some(b')')) => {
+                    self.advance();
+                    self.advance();
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+
+    /// Decodes a string literal into its bytes.
+    ///
+    /// See <https://webassembly.github.io/spec/core/text/values.html#strings>
+    fn string(&mut self, start: Position) -> Result<Vec<u8>, TextError> {
+        let mut bytes = Vec::new();
+
+        self.advance();
+
+        loop {
+            match self.peek() {
+                None => return Err(start.error(TextErrorKind::UnterminatedString)),
+                Some(b'"') => {
+                    self.advance();
+                    return Ok(bytes);
+                }
+                Some(b'\\') => {
+                    let escape = self.position();
+
+                    self.advance();
+                    self.escape(&mut bytes)
+                        .ok_or_else(|| escape.error(TextErrorKind::InvalidEscape))?;
+                }
+                Some(byte) if byte < 0x20 || byte == 0x7F => {
+                    return Err(self.unexpected_character())
+                }
+                Some(byte) => {
+                    self.advance();
+                    bytes.push(byte);
+                }
+            }
+        }
+    }
+
+    fn escape(&mut self, bytes: &mut Vec<u8>) -> Option<()> {
+        let byte = match self.advance()? {
+            b't' => b'\t',
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b'"' => b'"',
+            b'\'' => b'\'',
+            b'\\' => b'\\',
+            b'u' => {
+                if self.advance()? != b'{' {
+                    return None;
+                }
+
+                let start = self.offset;
+
+                while self.peek()? != b'}' {
+                    self.advance();
+                }
+
+                let digits = self.text[start..self.offset].replace('_', "");
+                let value = u32::from_str_radix(&digits, 16).ok()?;
+                let character = char::from_u32(value)?;
+
+                self.advance();
+
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(character.encode_utf8(&mut buffer).as_bytes());
+
+                return Some(());
+            }
+            high => {
+                let low = self.advance()?;
+                let digits = [high, low];
+                let digits = std::str::from_utf8(&digits).ok()?;
+
+                u8::from_str_radix(digits, 16).ok()?
+            }
+        };
+
+        bytes.push(byte);
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<TokenKind<'_>> {
+        tokenize(text)
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|token| token.kind)
+            .collect()
+    }
+
+    #[test]
+    fn tokens() {
+        assert_eq!(
+            kinds("(module $m (func i32.const -0x1_0 offset=4))"),
+            vec![
+                TokenKind::LeftParen,
+                TokenKind::Keyword("module"),
+                TokenKind::Identifier("m"),
+                TokenKind::LeftParen,
+                TokenKind::Keyword("func"),
+                TokenKind::Keyword("i32.const"),
+                TokenKind::Reserved("-0x1_0"),
+                TokenKind::Keyword("offset=4"),
+                TokenKind::RightParen,
+                TokenKind::RightParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn comments_and_annotations() {
+        assert_eq!(
+            kinds("(; outer (; inner ;) ;) ;; line\n(@name \"x\" (nested)) (@custom \"c\")"),
+            vec![
+                TokenKind::Annotation("custom"),
+                TokenKind::String(b"c".to_vec()),
+                TokenKind::RightParen,
+            ]
+        );
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            kinds(r#""a\t\n\"\\\00\ff\u{1F600}""#),
+            vec![TokenKind::String(
+                [&b"a\t\n\"\\\x00\xff"[..], "😀".as_bytes()].concat()
+            )]
+        );
+    }
+
+    #[test]
+    fn positions() {
+        let (tokens, end) = tokenize("(module\n  \"é\" $x)").unwrap();
+        let positions: Vec<(usize, usize)> = tokens
+            .iter()
+            .map(|token| (token.position.line, token.position.column))
+            .collect();
+
+        assert_eq!(positions, vec![(1, 1), (1, 2), (2, 3), (2, 7), (2, 9)]);
+        assert_eq!(
+            end,
+            Position {
+                line: 2,
+                column: 10
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            tokenize("(module\n \"abc").unwrap_err(),
+            TextError::new(2, 2, TextErrorKind::UnterminatedString)
+        );
+        assert_eq!(
+            tokenize("(; (; ;)").unwrap_err(),
+            TextError::new(1, 1, TextErrorKind::UnterminatedComment)
+        );
+        assert_eq!(
+            tokenize("\"\\q\"").unwrap_err(),
+            TextError::new(1, 2, TextErrorKind::InvalidEscape)
+        );
+        assert_eq!(
+            tokenize("(module {)").unwrap_err(),
+            TextError::new(1, 9, TextErrorKind::UnexpectedCharacter('{'))
+        );
+    }
+}
@@ -0,0 +1,372 @@
+//! Parser of the WebAssembly text format.
+//!
+//! Modules are parsed directly into the syntax model, without an intermediate binary encoding.
+//! Symbolic identifiers are preserved as a name custom section.
+//!
+//! See <https://webassembly.github.io/spec/core/text/index.html>
+
+mod cursor;
+mod errors;
+mod instructions;
+mod lexer;
+mod module;
+mod types;
+mod values;
+
+pub use errors::{TextError, TextErrorKind};
+pub use module::parse_module;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{Custom, Instruction, ModuleSection, NumericInstruction};
+    use crate::parser::parse_binary;
+
+    /// Asserts that parsing the text directly produces the same module as parsing its binary encoding.
+    fn assert_equivalent(text: &str) {
+        let binary = wat::parse_str(text).unwrap();
+        let expected = parse_binary(binary.as_slice()).unwrap();
+        let actual = parse_module(text).unwrap();
+
+        assert_eq!(actual, expected, "{}", text);
+    }
+
+    fn error(text: &str) -> (usize, usize, TextErrorKind) {
+        let error = parse_module(text).unwrap_err();
+
+        (error.line(), error.column(), error.kind().clone())
+    }
+
+    #[test]
+    fn empty() {
+        assert_equivalent("(module)");
+        assert_equivalent("(module $m)");
+        assert_eq!(parse_module("").unwrap(), parse_module("(module)").unwrap());
+    }
+
+    #[test]
+    fn types_and_imports() {
+        assert_equivalent(
+            r#"(module
+                (type $t (func (param $a i32) (param i64 f32) (result f64)))
+                (type (func))
+                (import "env" "f" (func $f (type $t)))
+                (import "env" "g" (func (param $x i32) (result i32)))
+                (import "env" "t" (table $t 1 2 funcref))
+                (import "env" "m" (memory $m 1))
+                (import "env" "g" (global $g (mut externref)))
+                (func (import "env" "h") (param i32 i32))
+                (global (import "env" "i") f32)
+            )"#,
+        );
+    }
+
+    #[test]
+    fn functions() {
+        assert_equivalent(
+            r#"(module
+                (func $add (export "add") (export "plus") (param $a i32) (param $b i32) (result i32)
+                    (local $c i32) (local i64 f64)
+                    local.get $a
+                    local.get $b
+                    i32.add
+                    local.tee $c)
+                (func $folded (param i32) (result i32)
+                    (i32.mul (local.get 0) (call $add (i32.const -1) (i32.const 0x10))))
+                (func (type 0) (param i32 i32) (result i32) unreachable)
+                (func (result f32 f64) f32.const -0x1.8p3 f64.const -0x0.1p-2)
+                (func (result f32) f32.const inf)
+                (func (result f64) f64.const 1_000.5e-3)
+                (start $start)
+                (func $start)
+            )"#,
+        );
+    }
+
+    #[test]
+    fn control() {
+        assert_equivalent(
+            r#"(module
+                (type (func (param i32) (result i32)))
+                (table 1 funcref)
+                (func $f (param $x i32) (result i32)
+                    (block $outer (result i32)
+                        (loop $inner
+                            (br_if $inner (local.get $x))
+                            (br_table $inner $outer 0 (i32.const 1) (local.get $x))
+                        )
+                        (if (result i32) (local.get $x)
+                            (then (i32.const 1))
+                            (else (i32.const 2)))
+                    )
+                    block (param i32) (result i32 i32)
+                        local.get $x
+                        if $l (param i32) (result i32)
+                            br $l
+                        else $l
+                            i32.const 3
+                            i32.add
+                        end $l
+                        i32.const 7
+                    end
+                    drop
+                    (call_indirect (type 0) (i32.const 4) (i32.const 5))
+                    (call_indirect 0 (param i64) (result i64) (i64.const 4) (i32.const 5))
+                    drop
+                    select
+                    (select (result i32) (i32.const 1) (i32.const 2) (i32.const 3))
+                    nop
+                    return)
+            )"#,
+        );
+    }
+
+    #[test]
+    fn memory_and_tables() {
+        assert_equivalent(
+            r#"(module
+                (memory $m 1 2)
+                (table $t 2 externref)
+                (table $u funcref (elem $f $f))
+                (func $f (param i32)
+                    (i32.store offset=4 align=2 (i32.const 0) (i32.load8_u offset=0x10 (i32.const 1)))
+                    (i64.store32 align=1 (i32.const 0) (i64.load (i32.const 8)))
+                    (drop (memory.grow (memory.size)))
+                    (memory.fill (i32.const 0) (i32.const 0) (i32.const 0))
+                    (memory.copy (i32.const 0) (i32.const 0) (i32.const 0))
+                    (memory.init $d (i32.const 0) (i32.const 0) (i32.const 0))
+                    (data.drop 0)
+                    (table.set $t (i32.const 0) (table.get 0 (i32.const 1)))
+                    (drop (table.grow $t (ref.null extern) (i32.const 1)))
+                    (drop (table.size $u))
+                    (table.fill $t (i32.const 0) (ref.null extern) (i32.const 0))
+                    (table.copy $u $u (i32.const 0) (i32.const 0) (i32.const 0))
+                    (table.copy (i32.const 0) (i32.const 0) (i32.const 0))
+                    (table.init $u $e (i32.const 0) (i32.const 0) (i32.const 0))
+                    (table.init $e (i32.const 0) (i32.const 0) (i32.const 0))
+                    (elem.drop $e)
+                    (drop (ref.is_null (ref.func $f))))
+                (elem $e func $f)
+                (elem declare func 0)
+                (elem (table $u) (offset (i32.const 1)) func $f)
+                (elem (i32.const 0) $f)
+                (elem funcref (ref.func $f) (item ref.null func))
+                (data $d "hello" "\00\ff")
+                (data (memory $m) (offset (i32.const 8)) "\u{1F600}")
+                (data (i32.const 16) "world")
+            )"#,
+        );
+    }
+
+    #[test]
+    fn typed_tables() {
+        assert_equivalent(
+            r#"(module
+                (type (func))
+                (import "env" "t" (table 1 (ref 0)))
+                (table 1 (ref null 0))
+                (table i64 2 3 anyref)
+                (table (ref null 0) (elem (item ref.func 0)))
+                (func (type 0))
+                (elem (table 1) (i32.const 0) (ref 0) (item ref.func 0))
+                (elem (ref null 0) (item ref.null 0) (ref.func 0))
+                (elem declare (ref 0) (ref.func 0))
+            )"#,
+        );
+    }
+
+    #[test]
+    fn named_types_and_fields() {
+        assert_equivalent(
+            r#"(module
+                (rec
+                    (type $node (struct (field $value (mut i32)) (field $next (ref null $node)) (field i8 f32)))
+                    (type $list (sub (struct (field $head (ref $node)) (field $small (mut i16))))))
+                (type $get (func (param $n (ref $node)) (result (ref null $list))))
+                (table $t 1 (ref null $node))
+                (global $g (mut (ref null $list)) (ref.null $list))
+                (func $f (type $get) (param $n (ref $node)) (result (ref null $list))
+                    (local $l (ref null $node))
+                    (struct.set $node $value (local.get $n) (i32.const 1))
+                    (local.set $l (struct.get $node $next (local.get $n)))
+                    (drop (struct.get_s $node 2 (local.get $n)))
+                    (struct.set $list $small (global.get $g) (struct.get_u $list $small (global.get $g)))
+                    (drop (ref.test (ref $node) (local.get $n)))
+                    (drop (block $b (result (ref $node))
+                        (br_on_cast $b (ref null $node) (ref $node) (local.get $l))
+                        (br_on_cast_fail $b (ref null $node) (ref $node))
+                        (ref.cast (ref $node))))
+                    (select (result (ref null $list)) (ref.null $list) (global.get $g) (i32.const 0)))
+                (elem (ref null $node) (item ref.null $node))
+            )"#,
+        );
+    }
+
+    #[test]
+    fn inline_data() {
+        assert_equivalent(r#"(module (memory (export "m") (data "a" "b")))"#);
+    }
+
+    #[test]
+    fn globals_and_exports() {
+        assert_equivalent(
+            r#"(module
+                (global $a i32 (i32.const 1))
+                (global $b (mut i64) i64.const -9223372036854775808)
+                (global (export "c") f64 (global.get $a) (drop) (f64.const 0x1p-1074))
+                (export "a" (global $a))
+                (export "b" (global 1))
+                (func $f (global.set $b (i64.extend_i32_s (global.get $a))))
+                (export "f" (func $f))
+            )"#,
+        );
+    }
+
+    #[test]
+    fn numeric() {
+        assert_equivalent(
+            r#"(module
+                (func (param i32 i64 f32 f64)
+                    (drop (i32.clz (i32.popcnt (i32.ctz (local.get 0)))))
+                    (drop (i64.rotr (i64.shr_u (local.get 1) (i64.const 1)) (i64.const 2)))
+                    (drop (i32.wrap_i64 (i64.trunc_sat_f64_s (local.get 3))))
+                    (drop (f32.demote_f64 (f64.promote_f32 (f32.copysign (local.get 2) (local.get 2)))))
+                    (drop (f64.convert_i64_u (i64.reinterpret_f64 (local.get 3))))
+                    (drop (f32.reinterpret_i32 (i32.extend8_s (i32.const 0))))
+                    (drop (i64.extend32_s (i64.extend_i32_u (i32.ge_u (i32.const 0) (i32.const 0)))))
+                    (drop (f64.nearest (f64.sqrt (f64.neg (f64.abs (local.get 3))))))
+                    (drop (i32.trunc_f32_u (f32.max (local.get 2) (f32.min (local.get 2) (local.get 2)))))
+                    (drop (f32.le (local.get 2) (local.get 2)))
+                    (drop (i64.eqz (i64.rem_s (local.get 1) (local.get 1)))))
+            )"#,
+        );
+    }
+
+    #[test]
+    fn not_a_number() {
+        let module = parse_module("(module (func (result f64) f64.const -nan:0x4))").unwrap();
+        let body = module.functions().unwrap()[0].body();
+
+        match body.instructions() {
+            [Instruction::Numeric(NumericInstruction::F64Constant(value))] => {
+                assert_eq!(value.to_bits(), 0xFFF0_0000_0000_0004)
+            }
+            instructions => panic!("unexpected instructions {:?}", instructions),
+        }
+    }
+
+    #[test]
+    fn custom_sections() {
+        let module = parse_module(
+            r#"(module
+                (@custom "a" (before first) "1")
+                (@custom "b" "2")
+                (func)
+                (@custom "c" (after func) "3")
+            )"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            module.custom_sections_at(ModuleSection::Custom),
+            Some(&[Custom::new("a".into(), b"1".to_vec())][..])
+        );
+        assert_eq!(
+            module.custom_sections_at(ModuleSection::Function),
+            Some(&[Custom::new("c".into(), b"3".to_vec())][..])
+        );
+        assert_eq!(
+            module.custom_sections_at(ModuleSection::Code),
+            Some(&[Custom::new("b".into(), b"2".to_vec())][..])
+        );
+    }
+
+    #[test]
+    fn names() {
+        let text = r#"(module $m
+            (type $t (func (param $p i32)))
+            (func $f (param $x i32) (local $y i32)
+                block $b end)
+            (memory $mem 1)
+        )"#;
+        let module = parse_module(text).unwrap();
+        let sections = module.custom_sections_at(ModuleSection::Code).unwrap();
+        let names = module.name_section().unwrap().unwrap();
+        let reference = parse_binary(&wat::parse_str(text).unwrap())
+            .unwrap()
+            .name_section()
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].name().value(), "name");
+        assert_eq!(names, reference);
+        assert_eq!(names.module_name(), Some(&"m".into()));
+        assert_eq!(module.function_name(0), Some("f".into()));
+        assert_eq!(names.local_name(0, 1), Some(&"y".into()));
+        assert_eq!(names.label_names(), &vec![(0, vec![(0, "b".into())])]);
+        assert_eq!(names.type_names(), &vec![(0, "t".into())]);
+        assert_eq!(names.memory_names(), &vec![(0, "mem".into())]);
+        assert_eq!(names.parameter_names(), &vec![(0, vec![(0, "p".into())])]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error("(module\n  (func call $missing))"),
+            (
+                2,
+                14,
+                TextErrorKind::UnknownIdentifier("function", "missing".to_string())
+            )
+        );
+        assert_eq!(
+            error("(module (func $f) (func $f))"),
+            (
+                1,
+                25,
+                TextErrorKind::DuplicateIdentifier("function", "f".to_string())
+            )
+        );
+        assert_eq!(
+            error("(module (memory 1)\n(import \"a\" \"b\" (func)))"),
+            (2, 1, TextErrorKind::ImportAfterDefinition("memory"))
+        );
+        assert_eq!(
+            error("(module (func block $a end $b))"),
+            (1, 28, TextErrorKind::MismatchedLabel("b".to_string()))
+        );
+        assert_eq!(
+            error("(module (func\n\ti32.frobnicate))"),
+            (
+                2,
+                2,
+                TextErrorKind::UnknownInstruction("i32.frobnicate".to_string())
+            )
+        );
+        assert_eq!(
+            error("(module (func (i32.load align=3 (i32.const 0))))"),
+            (1, 25, TextErrorKind::InvalidAlignment("3".to_string()))
+        );
+        assert_eq!(
+            error("(module (type $t (struct (field $v i32))) (func struct.get $t $w))"),
+            (
+                1,
+                63,
+                TextErrorKind::UnknownIdentifier("field", "w".to_string())
+            )
+        );
+        assert_eq!(
+            error("(module (type $t (struct (field $v i32) (field $v i64))))"),
+            (
+                1,
+                15,
+                TextErrorKind::DuplicateIdentifier("field", "v".to_string())
+            )
+        );
+        assert_eq!(
+            error("(module"),
+            (1, 8, TextErrorKind::UnexpectedEndOfInput)
+        );
+    }
+}
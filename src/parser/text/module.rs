@@ -0,0 +1,1402 @@
+use crate::model::{
+    BlockType, CompositeType, Custom, Data, DataMode, Element, ElementInitializer, ElementMode,
+    Export, ExportDescription, Expression, Function, FunctionType, Global, Import, IndexType,
+    Instruction, Limit, Memory, MemoryType, Module, ModuleSection, Name, NameMap, NameSection,
+    RecursiveType, ReferenceType, ResultType, Start, SubType, Table, TableType, Tag, TypeIndex,
+    ValueType,
+};
+use crate::parser::text::cursor::{Cursor, Index};
+use crate::parser::text::errors::{TextError, TextErrorKind};
+use crate::parser::text::instructions::{
+    parse_expression, parse_folded_instruction, FunctionContext,
+};
+use crate::parser::text::lexer::{tokenize, Position, TokenKind};
+use crate::parser::text::types::{
+    parse_composite_type, parse_global_type, parse_index_type, parse_memory_type, parse_parameters,
+    parse_results, parse_table_type, parse_value_type, take_reference_type, take_value_type,
+};
+use std::collections::HashMap;
+
+/// The number of bytes in a page of memory.
+const PAGE_SIZE: usize = 65536;
+
+/// The sections of a module in the order they occur in the binary format.
+/// The order of the ids of the sections differs for the data count and tag sections.
+const SECTION_ORDER: [ModuleSection; 14] = [
+    ModuleSection::Custom,
+    ModuleSection::Type,
+    ModuleSection::Import,
+    ModuleSection::Function,
+    ModuleSection::Table,
+    ModuleSection::Memory,
+    ModuleSection::Tag,
+    ModuleSection::Global,
+    ModuleSection::Export,
+    ModuleSection::Start,
+    ModuleSection::Element,
+    ModuleSection::DataCount,
+    ModuleSection::Code,
+    ModuleSection::Data,
+];
+
+/// The position of a section in the binary format.
+fn section_rank(section: ModuleSection) -> usize {
+    SECTION_ORDER
+        .iter()
+        .position(|other| *other == section)
+        .unwrap_or_default()
+}
+
+/// An index space of a module or function, mapping identifiers to indices.
+///
+/// See <https://webassembly.github.io/spec/core/text/modules.html#indices>
+pub struct Namespace<'a> {
+    kind: &'static str,
+    ids: HashMap<&'a str, u32>,
+    count: u32,
+}
+
+impl<'a> Namespace<'a> {
+    pub fn new(kind: &'static str) -> Self {
+        Namespace {
+            kind,
+            ids: HashMap::new(),
+            count: 0,
+        }
+    }
+
+    /// Allocates the next index in the space, binding it to the given identifier.
+    pub fn define(&mut self, id: Option<&'a str>) -> Result<u32, TextErrorKind> {
+        let index = self.count;
+
+        if let Some(id) = id {
+            if self.ids.insert(id, index).is_some() {
+                return Err(TextErrorKind::DuplicateIdentifier(
+                    self.kind,
+                    id.to_string(),
+                ));
+            }
+        }
+
+        self.count += 1;
+
+        Ok(index)
+    }
+
+    pub fn resolve(&self, index: Index<'a>) -> Result<u32, TextErrorKind> {
+        match index {
+            Index::Numeric(index) => Ok(index),
+            Index::Identifier(id) => self
+                .ids
+                .get(id)
+                .copied()
+                .ok_or_else(|| TextErrorKind::UnknownIdentifier(self.kind, id.to_string())),
+        }
+    }
+
+    /// Consumes and resolves an index, if the next token is an index.
+    pub fn take(&self, cursor: &mut Cursor<'a>) -> Result<Option<u32>, TextError> {
+        let position = cursor.position();
+
+        match cursor.take_index()? {
+            Some(index) => self
+                .resolve(index)
+                .map(Some)
+                .map_err(|kind| position.error(kind)),
+            None => Ok(None),
+        }
+    }
+
+    /// Consumes and resolves an index.
+    pub fn expect(&self, cursor: &mut Cursor<'a>) -> Result<u32, TextError> {
+        match self.take(cursor)? {
+            Some(index) => Ok(index),
+            None => Err(cursor.unexpected("an index")),
+        }
+    }
+}
+
+/// The identifiers of the parameters of an inline function type.
+type ParameterIds<'a> = Vec<Option<&'a str>>;
+
+/// The reference to a function type of an import, function, block or indirect call,
+/// either by index, by an inline declaration of its parameters and results, or both.
+///
+/// See <https://webassembly.github.io/spec/core/text/modules.html#type-uses>
+pub struct TypeUse<'a> {
+    index: Option<(TypeIndex, Position)>,
+    parameters: Vec<(Option<&'a str>, ValueType)>,
+    results: Vec<ValueType>,
+}
+
+impl TypeUse<'_> {
+    fn is_inline(&self) -> bool {
+        !self.parameters.is_empty() || !self.results.is_empty()
+    }
+
+    fn function_type(&self) -> FunctionType {
+        let parameters: Vec<ValueType> = self.parameters.iter().map(|(_, kind)| *kind).collect();
+
+        FunctionType::new(
+            ResultType::new(parameters),
+            ResultType::new(self.results.clone()),
+        )
+    }
+}
+
+/// The index spaces and types of a module, shared by the parsers of module fields and instructions.
+pub struct ModuleContext<'a> {
+    pub types: Namespace<'a>,
+    /// The field index spaces of the struct types, keyed by type index.
+    pub fields: HashMap<TypeIndex, Namespace<'a>>,
+    pub recursive_types: Vec<RecursiveType>,
+    pub functions: Namespace<'a>,
+    pub tables: Namespace<'a>,
+    pub memories: Namespace<'a>,
+    pub tags: Namespace<'a>,
+    pub globals: Namespace<'a>,
+    pub elements: Namespace<'a>,
+    pub data: Namespace<'a>,
+    /// Whether any function uses an instruction that requires the data count section.
+    pub uses_data_count: bool,
+}
+
+impl<'a> ModuleContext<'a> {
+    fn new() -> Self {
+        ModuleContext {
+            types: Namespace::new("type"),
+            fields: HashMap::new(),
+            recursive_types: Vec::new(),
+            functions: Namespace::new("function"),
+            tables: Namespace::new("table"),
+            memories: Namespace::new("memory"),
+            tags: Namespace::new("tag"),
+            globals: Namespace::new("global"),
+            elements: Namespace::new("element segment"),
+            data: Namespace::new("data segment"),
+            uses_data_count: false,
+        }
+    }
+
+    /// Parses a type use, without resolving its inline type.
+    pub fn parse_type_use(&self, cursor: &mut Cursor<'a>) -> Result<TypeUse<'a>, TextError> {
+        let index = if cursor.take_field("type") {
+            let position = cursor.position();
+            let index = self.types.expect(cursor)?;
+
+            cursor.expect_right_paren()?;
+
+            Some((index, position))
+        } else {
+            None
+        };
+
+        let mut parameters = Vec::new();
+        let mut results = Vec::new();
+
+        parse_parameters(cursor, &self.types, &mut parameters)?;
+        parse_results(cursor, &self.types, &mut results)?;
+
+        Ok(TypeUse {
+            index,
+            parameters,
+            results,
+        })
+    }
+
+    /// Consumes and resolves a type index, followed by the index of a field of that type.
+    pub fn expect_field(&self, cursor: &mut Cursor<'a>) -> Result<(TypeIndex, u32), TextError> {
+        let kind = self.types.expect(cursor)?;
+        let field = match self.fields.get(&kind) {
+            Some(fields) => fields.expect(cursor)?,
+            None => Namespace::new("field").expect(cursor)?,
+        };
+
+        Ok((kind, field))
+    }
+
+    /// The sub type at the given index of the type index space, if any.
+    pub fn sub_type(&self, index: TypeIndex) -> Option<&SubType> {
+        self.recursive_types
+            .iter()
+            .flat_map(|group| group.types())
+            .nth(index as usize)
+    }
+
+    /// The function type at the given index of the type index space, if any.
+    pub fn function_type(&self, index: TypeIndex) -> Option<&FunctionType> {
+        self.sub_type(index)?.function_type()
+    }
+
+    /// Finds the first function type equal to the given type that forms a recursive group of its own,
+    /// appending the type to the module if no such type exists.
+    pub fn intern(&mut self, kind: FunctionType) -> TypeIndex {
+        let group = RecursiveType::from(kind);
+        let mut index = 0;
+
+        for other in &self.recursive_types {
+            if other == &group {
+                return index as TypeIndex;
+            }
+
+            index += other.len();
+        }
+
+        self.recursive_types.push(group);
+        index as TypeIndex
+    }
+
+    /// Resolves a type use to a type index.
+    /// An inline type must match the referenced type, if both are present.
+    /// Otherwise, the inline type is used to find or append a matching type.
+    ///
+    /// Returns the type index along with the identifiers of the inline parameters, if any.
+    pub fn resolve_type_use(
+        &mut self,
+        type_use: TypeUse<'a>,
+    ) -> Result<(TypeIndex, Option<ParameterIds<'a>>), TextError> {
+        let inline = type_use.is_inline();
+        let kind = type_use.function_type();
+        let index = match type_use.index {
+            Some((index, position)) => {
+                let mismatched = self
+                    .function_type(index)
+                    .map(|other| other != &kind)
+                    .unwrap_or(true);
+
+                if inline && mismatched {
+                    return Err(position.error(TextErrorKind::MismatchedTypeUse(index)));
+                }
+
+                index
+            }
+            None => self.intern(kind),
+        };
+        let ids = inline.then(|| type_use.parameters.iter().map(|(id, _)| *id).collect());
+
+        Ok((index, ids))
+    }
+
+    /// Resolves the type use of a block to a block type.
+    /// Inline types with no parameters and at most one result do not require a type index.
+    ///
+    /// See <https://webassembly.github.io/spec/core/text/instructions.html#control-instructions>
+    pub fn resolve_block_type(&mut self, type_use: TypeUse<'a>) -> Result<BlockType, TextError> {
+        if type_use.index.is_some() {
+            return self
+                .resolve_type_use(type_use)
+                .map(|(index, _)| BlockType::Index(index));
+        }
+
+        Ok(
+            match (type_use.parameters.is_empty(), type_use.results.as_slice()) {
+                (true, []) => BlockType::None,
+                (true, [kind]) => BlockType::ValueType(*kind),
+                _ => BlockType::Index(self.intern(type_use.function_type())),
+            },
+        )
+    }
+
+    /// The number of parameters of the given type, or 0 for an unknown type.
+    fn parameter_count(&self, kind: TypeIndex) -> usize {
+        self.function_type(kind)
+            .map(|kind| kind.parameters().len())
+            .unwrap_or_default()
+    }
+}
+
+/// Binds the identifiers of the type definitions of a module to their indices, before any type is parsed.
+/// This allows types to refer to the types that follow them, such as the other types of their recursion group.
+fn define_type_identifiers<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &mut Namespace<'a>,
+) -> Result<(), TextError> {
+    let mut define = |cursor: &mut Cursor<'a>| {
+        let id_position = cursor.position();
+
+        types
+            .define(cursor.take_identifier())
+            .map_err(|kind| id_position.error(kind))?;
+        cursor.skip_group()
+    };
+
+    loop {
+        if cursor.take_field("type") {
+            define(cursor)?;
+        } else if cursor.take_field("rec") {
+            while cursor.take_field("type") {
+                define(cursor)?;
+            }
+
+            cursor.skip_group()?;
+        } else if matches!(
+            cursor.peek(),
+            Some(TokenKind::LeftParen | TokenKind::Annotation(_))
+        ) {
+            cursor.advance();
+            cursor.skip_group()?;
+        } else {
+            return Ok(());
+        }
+    }
+}
+
+/// Defines the type with the given index, starting after its `type` keyword, through its closing parenthesis.
+/// The type is either a sub type, or a composite type that is final and has no supertypes.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#recursive-types>
+fn define_type<'a>(
+    cursor: &mut Cursor<'a>,
+    context: &mut ModuleContext<'a>,
+    names: &mut NameSection,
+    index: TypeIndex,
+) -> Result<SubType, TextError> {
+    let id_position = cursor.position();
+    let id = cursor.take_identifier();
+    let (kind, ids) = if cursor.take_field("sub") {
+        let is_final = cursor.take_keyword("final");
+        let mut supertypes = Vec::new();
+
+        while let Some(supertype) = context.types.take(cursor)? {
+            supertypes.push(supertype);
+        }
+
+        let (kind, ids) = parse_composite_type(cursor, &context.types)?;
+
+        cursor.expect_right_paren()?;
+
+        (SubType::new(is_final, supertypes, kind), ids)
+    } else {
+        let (kind, ids) = parse_composite_type(cursor, &context.types)?;
+
+        (SubType::from(kind), ids)
+    };
+
+    cursor.expect_right_paren()?;
+
+    if let Some(id) = id {
+        names.type_names_mut().push((index, id.into()));
+    }
+
+    let is_struct = matches!(kind.kind(), CompositeType::Struct(_));
+
+    if is_struct {
+        let mut fields = Namespace::new("field");
+
+        for id in &ids {
+            fields.define(*id).map_err(|kind| id_position.error(kind))?;
+        }
+
+        context.fields.insert(index, fields);
+    }
+
+    let ids = named(ids);
+
+    if !ids.is_empty() {
+        if is_struct {
+            names.field_names_mut().push((index, ids));
+        } else {
+            names.parameter_names_mut().push((index, ids));
+        }
+    }
+
+    Ok(kind)
+}
+
+/// Skips a reference type if the next token starts one, without resolving its heap type.
+fn skip_reference_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<bool, TextError> {
+    if cursor.take_field("ref") {
+        cursor.skip_group()?;
+        Ok(true)
+    } else {
+        Ok(take_reference_type(cursor, types)?.is_some())
+    }
+}
+
+/// Parses a module in the text format.
+/// The module may either be wrapped in a `module` field, or consist of its fields alone.
+///
+/// See <https://webassembly.github.io/spec/core/text/modules.html#modules>
+pub fn parse_module(text: &str) -> Result<Module, TextError> {
+    let (tokens, end) = tokenize(text)?;
+    let mut cursor = Cursor::new(tokens, end);
+    let wrapped = cursor.take_field("module");
+    let id = if wrapped {
+        cursor.take_identifier()
+    } else {
+        None
+    };
+
+    let start = cursor.checkpoint();
+    let mut context = ModuleContext::new();
+    let mut names = NameSection::new();
+
+    names.set_module_name(id.map(Name::from));
+
+    define_type_identifiers(&mut cursor, &mut context.types)?;
+    cursor.restore(start);
+    define_fields(&mut cursor, &mut context, &mut names)?;
+    cursor.restore(start);
+
+    let mut parser = ModuleParser::new(cursor, context, names);
+
+    parser.parse_fields()?;
+
+    if wrapped {
+        parser.cursor.expect_right_paren()?;
+    }
+
+    if !parser.cursor.is_at_end() {
+        return Err(parser.cursor.unexpected("the end of the module"));
+    }
+
+    Ok(parser.build())
+}
+
+/// Binds the identifiers of every module field to its index, before any field is parsed.
+/// This allows fields to refer to definitions that occur later in the module.
+/// Type definitions are parsed in full, as they precede any implicitly defined types.
+///
+/// Imports must occur before any function, table, memory, tag or global definitions.
+fn define_fields<'a>(
+    cursor: &mut Cursor<'a>,
+    context: &mut ModuleContext<'a>,
+    names: &mut NameSection,
+) -> Result<(), TextError> {
+    let mut last_definition = None;
+    let mut next_type = 0;
+
+    loop {
+        let position = cursor.position();
+
+        if let Some(TokenKind::Annotation(_)) = cursor.peek() {
+            cursor.advance();
+            cursor.skip_group()?;
+            continue;
+        }
+
+        let field = match cursor.peek_field() {
+            Some(field) => field,
+            None if cursor.peek() == Some(&TokenKind::LeftParen) => {
+                cursor.advance();
+                return Err(cursor.unexpected("a module field"));
+            }
+            None => return Ok(()),
+        };
+
+        cursor.advance();
+        cursor.advance();
+
+        let import_error = |last_definition: Option<&'static str>| match last_definition {
+            Some(definition) => {
+                Err(position.error(TextErrorKind::ImportAfterDefinition(definition)))
+            }
+            None => Ok(()),
+        };
+
+        match field {
+            "type" => {
+                let kind = define_type(cursor, context, names, next_type)?;
+
+                next_type += 1;
+                context.recursive_types.push(kind.into());
+            }
+            "rec" => {
+                let mut types = Vec::new();
+
+                while cursor.take_field("type") {
+                    types.push(define_type(cursor, context, names, next_type)?);
+                    next_type += 1;
+                }
+
+                context.recursive_types.push(RecursiveType::new(types));
+                cursor.expect_right_paren()?;
+            }
+            "import" => {
+                import_error(last_definition)?;
+
+                cursor.expect_name()?;
+                cursor.expect_name()?;
+
+                let namespace = match cursor.peek_field() {
+                    Some("func") => &mut context.functions,
+                    Some("table") => &mut context.tables,
+                    Some("memory") => &mut context.memories,
+                    Some("global") => &mut context.globals,
+                    Some("tag") => &mut context.tags,
+                    _ => {
+                        cursor.expect_left_paren()?;
+                        return Err(cursor.unexpected("an import description"));
+                    }
+                };
+
+                cursor.advance();
+                cursor.advance();
+
+                let id_position = cursor.position();
+                let id = cursor.take_identifier();
+
+                namespace
+                    .define(id)
+                    .map_err(|kind| id_position.error(kind))?;
+                cursor.skip_group()?;
+                cursor.expect_right_paren()?;
+            }
+            "func" | "table" | "memory" | "global" | "tag" => {
+                let id_position = cursor.position();
+                let id = cursor.take_identifier();
+
+                while cursor.take_field("export") {
+                    cursor.skip_group()?;
+                }
+
+                let (namespace, definition) = match field {
+                    "func" => (&mut context.functions, "function"),
+                    "table" => (&mut context.tables, "table"),
+                    "memory" => (&mut context.memories, "memory"),
+                    "tag" => (&mut context.tags, "tag"),
+                    _ => (&mut context.globals, "global"),
+                };
+
+                namespace
+                    .define(id)
+                    .map_err(|kind| id_position.error(kind))?;
+
+                if cursor.peek_field() == Some("import") {
+                    import_error(last_definition)?;
+                } else {
+                    last_definition = Some(definition);
+                }
+
+                if matches!(field, "table" | "memory") {
+                    parse_index_type(cursor);
+                }
+
+                match field {
+                    "table"
+                        if skip_reference_type(cursor, &context.types)?
+                            && cursor.peek_field() == Some("elem") =>
+                    {
+                        context.elements.define(None).ok();
+                    }
+                    "memory" if cursor.peek_field() == Some("data") => {
+                        context.data.define(None).ok();
+                    }
+                    _ => {}
+                }
+
+                cursor.skip_group()?;
+            }
+            "elem" | "data" => {
+                let id_position = cursor.position();
+                let id = cursor.take_identifier();
+                let namespace = if field == "elem" {
+                    &mut context.elements
+                } else {
+                    &mut context.data
+                };
+
+                namespace
+                    .define(id)
+                    .map_err(|kind| id_position.error(kind))?;
+                cursor.skip_group()?;
+            }
+            "export" | "start" => cursor.skip_group()?,
+            _ => {
+                cursor.restore(cursor.checkpoint() - 1);
+                return Err(cursor.unexpected("a module field"));
+            }
+        }
+    }
+}
+
+/// Collects the named entries of a list of optional identifiers.
+fn named<'a>(ids: impl IntoIterator<Item = Option<&'a str>>) -> NameMap {
+    ids.into_iter()
+        .enumerate()
+        .filter_map(|(index, id)| id.map(|id| (index as u32, id.into())))
+        .collect()
+}
+
+/// Wraps a list of module components in an option, where empty lists are omitted.
+fn non_empty<T>(items: Vec<T>) -> Option<Vec<T>> {
+    if items.is_empty() {
+        None
+    } else {
+        Some(items)
+    }
+}
+
+/// An expression consisting of a single integer constant of 0 for the given index type,
+/// used as the offset of abbreviated inline element and data segments.
+fn zero_offset(index_type: IndexType) -> Expression {
+    match index_type {
+        IndexType::I32 => Expression::new(vec![Instruction::from(0i32)]),
+        IndexType::I64 => Expression::new(vec![Instruction::from(0i64)]),
+    }
+}
+
+/// Parses the fields of a module once all of the identifiers have been bound to indices.
+struct ModuleParser<'a> {
+    cursor: Cursor<'a>,
+    context: ModuleContext<'a>,
+    names: NameSection,
+    imports: Vec<Import>,
+    functions: Vec<Function>,
+    tables: Vec<Table>,
+    memories: Vec<Memory>,
+    tags: Vec<Tag>,
+    globals: Vec<Global>,
+    exports: Vec<Export>,
+    start: Option<Start>,
+    elements: Vec<Element>,
+    data: Vec<Data>,
+    custom_sections: Vec<((ModuleSection, u8), Custom)>,
+    next_function: u32,
+    next_table: u32,
+    next_memory: u32,
+    next_tag: u32,
+    next_global: u32,
+}
+
+impl<'a> ModuleParser<'a> {
+    fn new(cursor: Cursor<'a>, context: ModuleContext<'a>, names: NameSection) -> Self {
+        ModuleParser {
+            cursor,
+            context,
+            names,
+            imports: Vec::new(),
+            functions: Vec::new(),
+            tables: Vec::new(),
+            memories: Vec::new(),
+            tags: Vec::new(),
+            globals: Vec::new(),
+            exports: Vec::new(),
+            start: None,
+            elements: Vec::new(),
+            data: Vec::new(),
+            custom_sections: Vec::new(),
+            next_function: 0,
+            next_table: 0,
+            next_memory: 0,
+            next_tag: 0,
+            next_global: 0,
+        }
+    }
+
+    /// Builds the module from the parsed fields.
+    /// The identifiers of the module are stored in a name section after all other custom sections,
+    /// unless the module already has a custom section with the same name.
+    fn build(mut self) -> Module {
+        let mut builder = Module::builder();
+        let present = [
+            (
+                ModuleSection::Type,
+                !self.context.recursive_types.is_empty(),
+            ),
+            (ModuleSection::Import, !self.imports.is_empty()),
+            (ModuleSection::Function, !self.functions.is_empty()),
+            (ModuleSection::Table, !self.tables.is_empty()),
+            (ModuleSection::Memory, !self.memories.is_empty()),
+            (ModuleSection::Tag, !self.tags.is_empty()),
+            (ModuleSection::Global, !self.globals.is_empty()),
+            (ModuleSection::Export, !self.exports.is_empty()),
+            (ModuleSection::Start, self.start.is_some()),
+            (ModuleSection::Element, !self.elements.is_empty()),
+            (ModuleSection::DataCount, self.context.uses_data_count),
+            (ModuleSection::Code, !self.functions.is_empty()),
+            (ModuleSection::Data, !self.data.is_empty()),
+        ];
+
+        builder.set_types(non_empty(self.context.recursive_types));
+        builder.set_imports(non_empty(self.imports));
+        builder.set_functions(non_empty(self.functions));
+        builder.set_tables(non_empty(self.tables));
+        builder.set_memories(non_empty(self.memories));
+        builder.set_tags(non_empty(self.tags));
+        builder.set_globals(non_empty(self.globals));
+        builder.set_exports(non_empty(self.exports));
+        builder.set_start(self.start);
+        builder.set_elements(non_empty(self.elements));
+
+        if self.context.uses_data_count {
+            builder.set_data_count(Some(self.data.len() as u32));
+        }
+
+        builder.set_data(non_empty(self.data));
+
+        let has_name_section = self
+            .custom_sections
+            .iter()
+            .any(|(_, custom)| custom.name().value() == NameSection::NAME);
+
+        if !self.names.is_empty() && !has_name_section {
+            self.custom_sections
+                .push(((ModuleSection::Data, u8::MAX), Custom::from(&self.names)));
+        }
+
+        self.custom_sections
+            .sort_by_key(|((section, order), _)| (section_rank(*section), *order));
+
+        // Custom sections are placed after the closest section that is present in the binary format,
+        // matching the result of parsing the binary encoding of the module.
+        for ((placement, _), custom) in self.custom_sections {
+            let insertion_point = present
+                .iter()
+                .rev()
+                .find(|(section, present)| {
+                    *present && section_rank(*section) <= section_rank(placement)
+                })
+                .map(|(section, _)| *section)
+                .unwrap_or(ModuleSection::Custom);
+
+            builder.add_custom_section(insertion_point, custom);
+        }
+
+        builder.build()
+    }
+
+    fn parse_fields(&mut self) -> Result<(), TextError> {
+        loop {
+            if let Some(TokenKind::Annotation(_)) = self.cursor.peek() {
+                self.cursor.advance();
+                self.parse_custom_section()?;
+                continue;
+            }
+
+            let field = match self.cursor.peek_field() {
+                Some(field) => field,
+                None => return Ok(()),
+            };
+
+            self.cursor.advance();
+            self.cursor.advance();
+
+            match field {
+                "type" | "rec" => self.cursor.skip_group()?,
+                "import" => self.parse_import()?,
+                "func" => self.parse_function()?,
+                "table" => self.parse_table()?,
+                "memory" => self.parse_memory()?,
+                "tag" => self.parse_tag()?,
+                "global" => self.parse_global()?,
+                "export" => self.parse_export()?,
+                "start" => self.parse_start()?,
+                "elem" => self.parse_element()?,
+                _ => self.parse_data()?,
+            }
+        }
+    }
+
+    /// Parses a custom section annotation, starting after the `(@custom` token.
+    /// Custom sections without an explicit placement are placed after the last section.
+    ///
+    /// See <https://webassembly.github.io/annotations/core/text/custom.html>
+    fn parse_custom_section(&mut self) -> Result<(), TextError> {
+        let name = self.cursor.expect_name()?;
+        let placement = if self.cursor.peek() == Some(&TokenKind::LeftParen) {
+            self.parse_placement()?
+        } else {
+            (ModuleSection::Data, 1)
+        };
+
+        let mut bytes = Vec::new();
+
+        while let Some(string) = self.cursor.take_string() {
+            bytes.extend(string);
+        }
+
+        self.cursor.expect_right_paren()?;
+        self.custom_sections
+            .push((placement, Custom::new(name, bytes)));
+
+        Ok(())
+    }
+
+    /// Parses the placement of a custom section as the insertion point of the section.
+    /// Sections placed after a section precede sections placed before the following section.
+    fn parse_placement(&mut self) -> Result<(ModuleSection, u8), TextError> {
+        self.cursor.expect_left_paren()?;
+
+        let before = if self.cursor.take_keyword("before") {
+            true
+        } else {
+            self.cursor.expect_keyword("after")?;
+            false
+        };
+
+        let placement = match (before, self.cursor.peek_keyword()) {
+            (true, Some("first")) => (ModuleSection::Custom, 0),
+            (false, Some("last")) => (ModuleSection::Data, 1),
+            (_, Some(section)) => {
+                let (after, previous) = match section {
+                    "type" => (ModuleSection::Type, ModuleSection::Custom),
+                    "import" => (ModuleSection::Import, ModuleSection::Type),
+                    "func" => (ModuleSection::Function, ModuleSection::Import),
+                    "table" => (ModuleSection::Table, ModuleSection::Function),
+                    "memory" => (ModuleSection::Memory, ModuleSection::Table),
+                    "tag" => (ModuleSection::Tag, ModuleSection::Memory),
+                    "global" => (ModuleSection::Global, ModuleSection::Tag),
+                    "export" => (ModuleSection::Export, ModuleSection::Global),
+                    "start" => (ModuleSection::Start, ModuleSection::Export),
+                    "elem" => (ModuleSection::Element, ModuleSection::Start),
+                    "datacount" => (ModuleSection::DataCount, ModuleSection::Element),
+                    "code" => (ModuleSection::Code, ModuleSection::DataCount),
+                    "data" => (ModuleSection::Data, ModuleSection::Code),
+                    _ => return Err(self.cursor.unexpected("a section name")),
+                };
+
+                if before {
+                    (previous, 1)
+                } else {
+                    (after, 0)
+                }
+            }
+            _ => return Err(self.cursor.unexpected("a section name")),
+        };
+
+        self.cursor.advance();
+        self.cursor.expect_right_paren()?;
+
+        Ok(placement)
+    }
+
+    /// Parses any number of inline exports of the definition with the given description.
+    ///
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#text-func-abbrev>
+    fn parse_inline_exports(&mut self, description: ExportDescription) -> Result<(), TextError> {
+        while self.cursor.take_field("export") {
+            let name = self.cursor.expect_name()?;
+
+            self.cursor.expect_right_paren()?;
+            self.exports.push(Export::new(name, description));
+        }
+
+        Ok(())
+    }
+
+    /// Parses an inline import, returning the module and name of the import, if present.
+    fn take_inline_import(&mut self) -> Result<Option<(Name, Name)>, TextError> {
+        if !self.cursor.take_field("import") {
+            return Ok(None);
+        }
+
+        let module = self.cursor.expect_name()?;
+        let name = self.cursor.expect_name()?;
+
+        self.cursor.expect_right_paren()?;
+
+        Ok(Some((module, name)))
+    }
+
+    fn define_local_names(&mut self, function: u32, names: NameMap) {
+        if !names.is_empty() {
+            self.names.local_names_mut().push((function, names));
+        }
+    }
+
+    /// Parses an identifier and records it as the name of the definition with the given index.
+    fn parse_name(&mut self, index: u32, select: fn(&mut NameSection) -> &mut NameMap) {
+        if let Some(id) = self.cursor.take_identifier() {
+            select(&mut self.names).push((index, id.into()));
+        }
+    }
+
+    /// Parses the type use of an imported function.
+    fn parse_function_import(&mut self, index: u32) -> Result<TypeIndex, TextError> {
+        let type_use = self.context.parse_type_use(&mut self.cursor)?;
+        let (kind, parameters) = self.context.resolve_type_use(type_use)?;
+
+        self.define_local_names(index, named(parameters.unwrap_or_default()));
+
+        Ok(kind)
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#imports>
+    fn parse_import(&mut self) -> Result<(), TextError> {
+        let module = self.cursor.expect_name()?;
+        let name = self.cursor.expect_name()?;
+        let field = self.cursor.peek_field();
+
+        self.cursor.advance();
+        self.cursor.advance();
+
+        let import = match field {
+            Some("func") => {
+                let index = self.next_function;
+
+                self.next_function += 1;
+                self.parse_name(index, NameSection::function_names_mut);
+
+                let kind = self.parse_function_import(index)?;
+
+                Import::function(module, name, kind)
+            }
+            Some("table") => {
+                self.parse_name(self.next_table, NameSection::table_names_mut);
+                self.next_table += 1;
+
+                Import::table(
+                    module,
+                    name,
+                    parse_table_type(&mut self.cursor, &self.context.types)?,
+                )
+            }
+            Some("memory") => {
+                self.parse_name(self.next_memory, NameSection::memory_names_mut);
+                self.next_memory += 1;
+
+                Import::memory(module, name, parse_memory_type(&mut self.cursor)?)
+            }
+            Some("tag") => {
+                let index = self.next_tag;
+
+                self.next_tag += 1;
+                self.parse_name(index, NameSection::tag_names_mut);
+
+                Import::tag(module, name, self.parse_tag_type(index)?)
+            }
+            _ => {
+                self.parse_name(self.next_global, NameSection::global_names_mut);
+                self.next_global += 1;
+
+                Import::global(
+                    module,
+                    name,
+                    parse_global_type(&mut self.cursor, &self.context.types)?,
+                )
+            }
+        };
+
+        self.cursor.expect_right_paren()?;
+        self.cursor.expect_right_paren()?;
+        self.imports.push(import);
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#functions>
+    fn parse_function(&mut self) -> Result<(), TextError> {
+        let index = self.next_function;
+
+        self.next_function += 1;
+        self.parse_name(index, NameSection::function_names_mut);
+        self.parse_inline_exports(ExportDescription::Function(index))?;
+
+        if let Some((module, name)) = self.take_inline_import()? {
+            let kind = self.parse_function_import(index)?;
+
+            self.cursor.expect_right_paren()?;
+            self.imports.push(Import::function(module, name, kind));
+
+            return Ok(());
+        }
+
+        let type_use = self.context.parse_type_use(&mut self.cursor)?;
+        let (kind, parameters) = self.context.resolve_type_use(type_use)?;
+        let parameters =
+            parameters.unwrap_or_else(|| vec![None; self.context.parameter_count(kind)]);
+
+        let mut namespace = Namespace::new("local");
+        let mut local_names = Vec::new();
+        let mut locals = Vec::new();
+
+        for id in parameters {
+            let position = self.cursor.position();
+            let local = namespace.define(id).map_err(|kind| position.error(kind))?;
+
+            if let Some(id) = id {
+                local_names.push((local, id.into()));
+            }
+        }
+
+        while self.cursor.take_field("local") {
+            let position = self.cursor.position();
+
+            match self.cursor.take_identifier() {
+                Some(id) => {
+                    let local = namespace
+                        .define(Some(id))
+                        .map_err(|kind| position.error(kind))?;
+
+                    local_names.push((local, id.into()));
+                    locals.push(parse_value_type(&mut self.cursor, &self.context.types)?);
+                }
+                None => {
+                    while let Some(kind) = take_value_type(&mut self.cursor, &self.context.types)? {
+                        namespace.define(None).ok();
+                        locals.push(kind);
+                    }
+                }
+            }
+
+            self.cursor.expect_right_paren()?;
+        }
+
+        let mut function = FunctionContext::new(namespace);
+        let body = parse_expression(&mut self.cursor, &mut self.context, &mut function)?;
+
+        self.cursor.expect_right_paren()?;
+        self.define_local_names(index, local_names);
+
+        let labels = function.into_label_names();
+
+        if !labels.is_empty() {
+            self.names.label_names_mut().push((index, labels));
+        }
+
+        self.functions
+            .push(Function::new(kind, ResultType::new(locals), body));
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/function-references/core/text/modules.html#tables>
+    fn parse_table(&mut self) -> Result<(), TextError> {
+        let index = self.next_table;
+
+        self.next_table += 1;
+        self.parse_name(index, NameSection::table_names_mut);
+        self.parse_inline_exports(ExportDescription::Table(index))?;
+
+        let import = self.take_inline_import()?;
+        let checkpoint = self.cursor.checkpoint();
+        let index_type = parse_index_type(&mut self.cursor);
+
+        match (
+            import,
+            take_reference_type(&mut self.cursor, &self.context.types)?,
+        ) {
+            (Some((module, name)), _) => {
+                self.cursor.restore(checkpoint);
+
+                let kind = parse_table_type(&mut self.cursor, &self.context.types)?;
+
+                self.imports.push(Import::table(module, name, kind));
+            }
+            (None, Some(kind)) => {
+                if !self.cursor.take_field("elem") {
+                    return Err(self.cursor.unexpected("`(elem`"));
+                }
+
+                let initializers = if self.cursor.peek() == Some(&TokenKind::LeftParen) {
+                    self.parse_element_expressions()?
+                } else {
+                    self.parse_function_indices()?
+                };
+                let length = initializers.len() as u64;
+                let limits = Limit::bounded(length, length);
+
+                self.cursor.expect_right_paren()?;
+                self.tables
+                    .push(TableType::with_index_type(index_type, kind, limits).into());
+                self.elements.push(Element::active(
+                    index,
+                    zero_offset(index_type),
+                    kind,
+                    initializers,
+                ));
+            }
+            (None, None) => {
+                self.cursor.restore(checkpoint);
+
+                let kind = parse_table_type(&mut self.cursor, &self.context.types)?;
+
+                if self.cursor.peek() == Some(&TokenKind::RightParen) {
+                    self.tables.push(kind.into());
+                } else {
+                    let initializer = self.parse_constant_expression()?;
+
+                    self.tables.push(Table::with_initializer(kind, initializer));
+                }
+            }
+        }
+
+        self.cursor.expect_right_paren()
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#memories>
+    fn parse_memory(&mut self) -> Result<(), TextError> {
+        let index = self.next_memory;
+
+        self.next_memory += 1;
+        self.parse_name(index, NameSection::memory_names_mut);
+        self.parse_inline_exports(ExportDescription::Memory(index))?;
+
+        if let Some((module, name)) = self.take_inline_import()? {
+            let kind = parse_memory_type(&mut self.cursor)?;
+
+            self.imports.push(Import::memory(module, name, kind));
+            return self.cursor.expect_right_paren();
+        }
+
+        let checkpoint = self.cursor.checkpoint();
+        let index_type = parse_index_type(&mut self.cursor);
+
+        if self.cursor.take_field("data") {
+            let mut bytes = Vec::new();
+
+            while let Some(string) = self.cursor.take_string() {
+                bytes.extend(string);
+            }
+
+            self.cursor.expect_right_paren()?;
+
+            let pages = bytes.len().div_ceil(PAGE_SIZE) as u64;
+            let limits = Limit::bounded(pages, pages);
+
+            self.memories
+                .push(MemoryType::with_index_type(index_type, limits, false).into());
+            self.data
+                .push(Data::active(index, zero_offset(index_type), bytes));
+        } else {
+            self.cursor.restore(checkpoint);
+
+            let kind = parse_memory_type(&mut self.cursor)?;
+
+            self.memories.push(kind.into());
+        }
+
+        self.cursor.expect_right_paren()
+    }
+
+    /// Parses the type use of a tag, recording the identifiers of its inline parameters.
+    fn parse_tag_type(&mut self, index: u32) -> Result<TypeIndex, TextError> {
+        let type_use = self.context.parse_type_use(&mut self.cursor)?;
+        let (kind, parameters) = self.context.resolve_type_use(type_use)?;
+        let parameters = named(parameters.unwrap_or_default());
+
+        if !parameters.is_empty() {
+            self.names
+                .tag_parameter_names_mut()
+                .push((index, parameters));
+        }
+
+        Ok(kind)
+    }
+
+    /// See <https://webassembly.github.io/exception-handling/core/text/modules.html#tags>
+    fn parse_tag(&mut self) -> Result<(), TextError> {
+        let index = self.next_tag;
+
+        self.next_tag += 1;
+        self.parse_name(index, NameSection::tag_names_mut);
+        self.parse_inline_exports(ExportDescription::Tag(index))?;
+
+        if let Some((module, name)) = self.take_inline_import()? {
+            let kind = self.parse_tag_type(index)?;
+
+            self.imports.push(Import::tag(module, name, kind));
+        } else {
+            let kind = self.parse_tag_type(index)?;
+
+            self.tags.push(Tag::new(kind));
+        }
+
+        self.cursor.expect_right_paren()
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#globals>
+    fn parse_global(&mut self) -> Result<(), TextError> {
+        let index = self.next_global;
+
+        self.next_global += 1;
+        self.parse_name(index, NameSection::global_names_mut);
+        self.parse_inline_exports(ExportDescription::Global(index))?;
+
+        if let Some((module, name)) = self.take_inline_import()? {
+            let kind = parse_global_type(&mut self.cursor, &self.context.types)?;
+
+            self.imports.push(Import::global(module, name, kind));
+        } else {
+            let kind = parse_global_type(&mut self.cursor, &self.context.types)?;
+            let initializer = self.parse_constant_expression()?;
+
+            self.globals.push(Global::new(kind, initializer));
+        }
+
+        self.cursor.expect_right_paren()
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#exports>
+    fn parse_export(&mut self) -> Result<(), TextError> {
+        let name = self.cursor.expect_name()?;
+        let field = self.cursor.peek_field();
+
+        self.cursor.expect_left_paren()?;
+
+        let description = match field {
+            Some("func") => {
+                self.cursor.advance();
+                ExportDescription::Function(self.context.functions.expect(&mut self.cursor)?)
+            }
+            Some("table") => {
+                self.cursor.advance();
+                ExportDescription::Table(self.context.tables.expect(&mut self.cursor)?)
+            }
+            Some("memory") => {
+                self.cursor.advance();
+                ExportDescription::Memory(self.context.memories.expect(&mut self.cursor)?)
+            }
+            Some("global") => {
+                self.cursor.advance();
+                ExportDescription::Global(self.context.globals.expect(&mut self.cursor)?)
+            }
+            Some("tag") => {
+                self.cursor.advance();
+                ExportDescription::Tag(self.context.tags.expect(&mut self.cursor)?)
+            }
+            _ => return Err(self.cursor.unexpected("an export description")),
+        };
+
+        self.cursor.expect_right_paren()?;
+        self.cursor.expect_right_paren()?;
+        self.exports.push(Export::new(name, description));
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#start-function>
+    fn parse_start(&mut self) -> Result<(), TextError> {
+        if self.start.is_some() {
+            return Err(self.cursor.error(TextErrorKind::MultipleStart));
+        }
+
+        let function = self.context.functions.expect(&mut self.cursor)?;
+
+        self.start = Some(Start::new(function));
+        self.cursor.expect_right_paren()
+    }
+
+    /// Parses an expression of constant instructions, such as the initializer of a global.
+    fn parse_constant_expression(&mut self) -> Result<Expression, TextError> {
+        let mut function = FunctionContext::new(Namespace::new("local"));
+
+        parse_expression(&mut self.cursor, &mut self.context, &mut function)
+    }
+
+    /// Parses an expression consisting of a single folded instruction.
+    fn parse_folded_expression(&mut self) -> Result<Expression, TextError> {
+        let mut function = FunctionContext::new(Namespace::new("local"));
+        let mut instructions = Vec::new();
+
+        parse_folded_instruction(
+            &mut self.cursor,
+            &mut self.context,
+            &mut function,
+            &mut instructions,
+        )?;
+
+        Ok(Expression::new(instructions))
+    }
+
+    /// Parses the offset of an active element or data segment,
+    /// either as an `offset` field or as a single folded instruction.
+    fn parse_offset(&mut self) -> Result<Expression, TextError> {
+        if self.cursor.take_field("offset") {
+            let offset = self.parse_constant_expression()?;
+
+            self.cursor.expect_right_paren()?;
+
+            Ok(offset)
+        } else {
+            self.parse_folded_expression()
+        }
+    }
+
+    /// Parses a list of function indices as element initializers.
+    fn parse_function_indices(&mut self) -> Result<Vec<Expression>, TextError> {
+        let mut functions = Vec::new();
+
+        while let Some(function) = self.context.functions.take(&mut self.cursor)? {
+            functions.push(function);
+        }
+
+        Ok(functions.to_initializers())
+    }
+
+    /// Parses a list of element expressions, each either an `item` field or a single folded instruction.
+    fn parse_element_expressions(&mut self) -> Result<Vec<Expression>, TextError> {
+        let mut initializers = Vec::new();
+
+        while self.cursor.peek() == Some(&TokenKind::LeftParen) {
+            if self.cursor.take_field("item") {
+                initializers.push(self.parse_constant_expression()?);
+                self.cursor.expect_right_paren()?;
+            } else {
+                initializers.push(self.parse_folded_expression()?);
+            }
+        }
+
+        Ok(initializers)
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#element-segments>
+    fn parse_element(&mut self) -> Result<(), TextError> {
+        let index = self.elements.len() as u32;
+
+        self.parse_name(index, NameSection::element_names_mut);
+
+        let mode = if self.cursor.take_keyword("declare") {
+            ElementMode::Declarative
+        } else if self.cursor.take_field("table") {
+            let table = self.context.tables.expect(&mut self.cursor)?;
+
+            self.cursor.expect_right_paren()?;
+
+            ElementMode::Active(table, self.parse_offset()?)
+        } else if self.cursor.peek() == Some(&TokenKind::LeftParen)
+            && self.cursor.peek_field() != Some("ref")
+        {
+            ElementMode::Active(0, self.parse_offset()?)
+        } else {
+            ElementMode::Passive
+        };
+
+        let (kind, initializers) = if self.cursor.take_keyword("func") {
+            (ReferenceType::Function, self.parse_function_indices()?)
+        } else if let Some(kind) = take_reference_type(&mut self.cursor, &self.context.types)? {
+            (kind, self.parse_element_expressions()?)
+        } else {
+            (ReferenceType::Function, self.parse_function_indices()?)
+        };
+
+        self.cursor.expect_right_paren()?;
+        self.elements.push(Element::new(kind, mode, initializers));
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/text/modules.html#data-segments>
+    fn parse_data(&mut self) -> Result<(), TextError> {
+        let index = self.data.len() as u32;
+
+        self.parse_name(index, NameSection::data_names_mut);
+
+        let mode = if self.cursor.take_field("memory") {
+            let memory = self.context.memories.expect(&mut self.cursor)?;
+
+            self.cursor.expect_right_paren()?;
+
+            DataMode::Active(memory, self.parse_offset()?)
+        } else if self.cursor.peek() == Some(&TokenKind::LeftParen) {
+            DataMode::Active(0, self.parse_offset()?)
+        } else {
+            DataMode::Passive
+        };
+
+        let mut bytes = Vec::new();
+
+        while let Some(string) = self.cursor.take_string() {
+            bytes.extend(string);
+        }
+
+        self.cursor.expect_right_paren()?;
+        self.data.push(Data::new(mode, bytes));
+
+        Ok(())
+    }
+}
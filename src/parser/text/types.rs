@@ -0,0 +1,346 @@
+use crate::model::{
+    ArrayType, CompositeType, FieldType, FunctionType, GlobalType, HeapType, IndexType, Limit,
+    MemoryType, Mutability, PackedType, ReferenceType, ResultType, StorageType, StructType,
+    TableType, ValueType,
+};
+use crate::parser::text::cursor::Cursor;
+use crate::parser::text::errors::TextError;
+use crate::parser::text::module::Namespace;
+
+/// Parses a value type if the next token is one.
+///
+/// See <https://webassembly.github.io/spec/core/text/types.html#value-types>
+pub fn take_value_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<Option<ValueType>, TextError> {
+    let kind = match cursor.peek_keyword() {
+        Some("i32") => ValueType::I32,
+        Some("i64") => ValueType::I64,
+        Some("f32") => ValueType::F32,
+        Some("f64") => ValueType::F64,
+        Some("v128") => ValueType::V128,
+        _ => return Ok(take_reference_type(cursor, types)?.map(ValueType::from)),
+    };
+
+    cursor.advance();
+    Ok(Some(kind))
+}
+
+/// See <https://webassembly.github.io/spec/core/text/types.html#value-types>
+pub fn parse_value_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<ValueType, TextError> {
+    take_value_type(cursor, types)?.ok_or_else(|| cursor.unexpected("a value type"))
+}
+
+/// Parses a reference type if the next token is one.
+/// Typed references are written as `(ref null? heaptype)`, with concrete heap types given as type indices.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#reference-types>
+pub fn take_reference_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<Option<ReferenceType>, TextError> {
+    if cursor.take_field("ref") {
+        let nullable = cursor.take_keyword("null");
+        let heap = parse_heap_type(cursor, types)?;
+
+        cursor.expect_right_paren()?;
+
+        return Ok(Some(if nullable {
+            ReferenceType::nullable_reference(heap)
+        } else {
+            ReferenceType::Reference(heap)
+        }));
+    }
+
+    let heap = match cursor.peek_keyword() {
+        Some("funcref") => HeapType::Function,
+        Some("externref") => HeapType::External,
+        Some("exnref") => HeapType::Exception,
+        Some("anyref") => HeapType::Any,
+        Some("eqref") => HeapType::Eq,
+        Some("i31ref") => HeapType::I31,
+        Some("structref") => HeapType::Struct,
+        Some("arrayref") => HeapType::Array,
+        Some("nullref") => HeapType::None,
+        Some("nullexternref") => HeapType::NoExtern,
+        Some("nullfuncref") => HeapType::NoFunction,
+        Some("nullexnref") => HeapType::NoException,
+        _ => return Ok(None),
+    };
+
+    cursor.advance();
+    Ok(Some(ReferenceType::nullable_reference(heap)))
+}
+
+/// See <https://webassembly.github.io/gc/core/text/types.html#reference-types>
+pub fn parse_reference_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<ReferenceType, TextError> {
+    take_reference_type(cursor, types)?.ok_or_else(|| cursor.unexpected("a reference type"))
+}
+
+/// Parses an abstract heap type, or a concrete heap type given as a numeric or symbolic type index.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#heap-types>
+pub fn parse_heap_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<HeapType, TextError> {
+    if let Some(index) = types.take(cursor)? {
+        return Ok(HeapType::Index(index));
+    }
+
+    let kind = match cursor.peek_keyword() {
+        Some("func") => HeapType::Function,
+        Some("extern") => HeapType::External,
+        Some("exn") => HeapType::Exception,
+        Some("any") => HeapType::Any,
+        Some("eq") => HeapType::Eq,
+        Some("i31") => HeapType::I31,
+        Some("struct") => HeapType::Struct,
+        Some("array") => HeapType::Array,
+        Some("none") => HeapType::None,
+        Some("noextern") => HeapType::NoExtern,
+        Some("nofunc") => HeapType::NoFunction,
+        Some("noexn") => HeapType::NoException,
+        _ => return Err(cursor.unexpected("a heap type")),
+    };
+
+    cursor.advance();
+    Ok(kind)
+}
+
+/// Parses a reference type for the immediates of casts, which must be a reference type.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#reference-types>
+pub fn parse_cast_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<ValueType, TextError> {
+    parse_reference_type(cursor, types).map(ValueType::from)
+}
+
+/// Parses any number of parameter declarations, including their optional identifiers.
+/// A parameter with an identifier declares a single parameter.
+///
+/// See <https://webassembly.github.io/spec/core/text/types.html#function-types>
+pub fn parse_parameters<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+    parameters: &mut Vec<(Option<&'a str>, ValueType)>,
+) -> Result<(), TextError> {
+    while cursor.take_field("param") {
+        match cursor.take_identifier() {
+            Some(id) => parameters.push((Some(id), parse_value_type(cursor, types)?)),
+            None => {
+                while let Some(kind) = take_value_type(cursor, types)? {
+                    parameters.push((None, kind));
+                }
+            }
+        }
+
+        cursor.expect_right_paren()?;
+    }
+
+    Ok(())
+}
+
+/// Parses any number of result declarations.
+///
+/// See <https://webassembly.github.io/spec/core/text/types.html#function-types>
+pub fn parse_results<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+    results: &mut Vec<ValueType>,
+) -> Result<(), TextError> {
+    while cursor.take_field("result") {
+        while let Some(kind) = take_value_type(cursor, types)? {
+            results.push(kind);
+        }
+
+        cursor.expect_right_paren()?;
+    }
+
+    Ok(())
+}
+
+/// Parses a function type, starting after its `func` keyword, through its closing parenthesis.
+/// Returns the type along with the identifiers of its parameters.
+///
+/// See <https://webassembly.github.io/spec/core/text/types.html#function-types>
+pub fn parse_function_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<(FunctionType, Vec<Option<&'a str>>), TextError> {
+    let mut parameters = Vec::new();
+    let mut results = Vec::new();
+
+    parse_parameters(cursor, types, &mut parameters)?;
+    parse_results(cursor, types, &mut results)?;
+    cursor.expect_right_paren()?;
+
+    let (ids, kinds): (Vec<Option<&str>>, Vec<ValueType>) = parameters.into_iter().unzip();
+
+    Ok((
+        FunctionType::new(ResultType::new(kinds), ResultType::new(results)),
+        ids,
+    ))
+}
+
+/// Parses a composite type, including its enclosing parentheses.
+/// Returns the type along with the identifiers of the parameters of a function type,
+/// or the identifiers of the fields of a struct type.
+/// A field with an identifier declares a single field.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#composite-types>
+pub fn parse_composite_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<(CompositeType, Vec<Option<&'a str>>), TextError> {
+    if cursor.take_field("func") {
+        let (kind, parameters) = parse_function_type(cursor, types)?;
+
+        return Ok((kind.into(), parameters));
+    }
+
+    if cursor.take_field("struct") {
+        let mut fields = Vec::new();
+        let mut ids = Vec::new();
+
+        while cursor.take_field("field") {
+            match cursor.take_identifier() {
+                Some(id) => {
+                    let field = take_field_type(cursor, types)?
+                        .ok_or_else(|| cursor.unexpected("a field type"))?;
+
+                    fields.push(field);
+                    ids.push(Some(id));
+                }
+                None => {
+                    while let Some(field) = take_field_type(cursor, types)? {
+                        fields.push(field);
+                        ids.push(None);
+                    }
+                }
+            }
+
+            cursor.expect_right_paren()?;
+        }
+
+        cursor.expect_right_paren()?;
+
+        return Ok((StructType::new(fields).into(), ids));
+    }
+
+    if cursor.take_field("array") {
+        let field =
+            take_field_type(cursor, types)?.ok_or_else(|| cursor.unexpected("a field type"))?;
+
+        cursor.expect_right_paren()?;
+
+        return Ok((ArrayType::new(field).into(), Vec::new()));
+    }
+
+    cursor.expect_left_paren()?;
+    Err(cursor.unexpected("a composite type"))
+}
+
+/// Parses a field type if the next token starts one.
+///
+/// See <https://webassembly.github.io/gc/core/text/types.html#composite-types>
+fn take_field_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<Option<FieldType>, TextError> {
+    if cursor.take_field("mut") {
+        let kind =
+            take_storage_type(cursor, types)?.ok_or_else(|| cursor.unexpected("a storage type"))?;
+
+        cursor.expect_right_paren()?;
+
+        return Ok(Some(FieldType::new(kind, Mutability::Mutable)));
+    }
+
+    Ok(take_storage_type(cursor, types)?.map(FieldType::immutable))
+}
+
+/// Parses a storage type if the next token starts one.
+fn take_storage_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<Option<StorageType>, TextError> {
+    let kind = match cursor.peek_keyword() {
+        Some("i8") => PackedType::I8,
+        Some("i16") => PackedType::I16,
+        _ => return Ok(take_value_type(cursor, types)?.map(StorageType::from)),
+    };
+
+    cursor.advance();
+    Ok(Some(kind.into()))
+}
+
+/// See <https://webassembly.github.io/spec/core/text/types.html#limits>
+pub fn parse_limits(cursor: &mut Cursor) -> Result<Limit, TextError> {
+    let min = cursor.expect_u64()?;
+    let max = if cursor.peek_u64() {
+        Some(cursor.expect_u64()?)
+    } else {
+        None
+    };
+
+    Ok(Limit::new(min, max))
+}
+
+/// Parses the optional index type of a memory or table type, which defaults to `i32`.
+///
+/// See <https://webassembly.github.io/memory64/core/text/types.html#index-types>
+pub fn parse_index_type(cursor: &mut Cursor) -> IndexType {
+    if cursor.take_keyword("i64") {
+        IndexType::I64
+    } else {
+        cursor.take_keyword("i32");
+        IndexType::I32
+    }
+}
+
+/// See <https://webassembly.github.io/spec/core/text/types.html#memory-types>
+pub fn parse_memory_type(cursor: &mut Cursor) -> Result<MemoryType, TextError> {
+    let index_type = parse_index_type(cursor);
+    let limits = parse_limits(cursor)?;
+    let shared = cursor.take_keyword("shared");
+
+    Ok(MemoryType::with_index_type(index_type, limits, shared))
+}
+
+/// See <https://webassembly.github.io/spec/core/text/types.html#table-types>
+pub fn parse_table_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<TableType, TextError> {
+    let index_type = parse_index_type(cursor);
+    let limits = parse_limits(cursor)?;
+    let kind = parse_reference_type(cursor, types)?;
+
+    Ok(TableType::with_index_type(index_type, kind, limits))
+}
+
+/// See <https://webassembly.github.io/spec/core/text/types.html#global-types>
+pub fn parse_global_type<'a>(
+    cursor: &mut Cursor<'a>,
+    types: &Namespace<'a>,
+) -> Result<GlobalType, TextError> {
+    if cursor.take_field("mut") {
+        let kind = parse_value_type(cursor, types)?;
+
+        cursor.expect_right_paren()?;
+
+        Ok(GlobalType::mutable(kind))
+    } else {
+        parse_value_type(cursor, types).map(GlobalType::immutable)
+    }
+}
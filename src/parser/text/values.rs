@@ -0,0 +1,394 @@
+/// Removes the underscores separating the digits of a number.
+/// Returns `None` if an underscore is not surrounded by digits of the given radix.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+fn digits(text: &str, radix: u32) -> Option<String> {
+    let mut previous_digit = false;
+    let mut digits = String::with_capacity(text.len());
+
+    for character in text.chars() {
+        if character == '_' {
+            if !previous_digit {
+                return None;
+            }
+
+            previous_digit = false;
+        } else if character.is_digit(radix) {
+            previous_digit = true;
+            digits.push(character);
+        } else {
+            return None;
+        }
+    }
+
+    if previous_digit {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+/// Splits the optional sign off of a number.
+/// Returns whether the number is negative along with the remaining text.
+fn sign(text: &str) -> (bool, &str) {
+    if let Some(rest) = text.strip_prefix('-') {
+        (true, rest)
+    } else if let Some(rest) = text.strip_prefix('+') {
+        (false, rest)
+    } else {
+        (false, text)
+    }
+}
+
+/// Parses the magnitude of a decimal or hexadecimal integer without a sign.
+fn magnitude(text: &str) -> Option<u64> {
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(&digits(hex, 16)?, 16).ok(),
+        None => digits(text, 10)?.parse().ok(),
+    }
+}
+
+/// Parses an unsigned integer of at most 32 bits.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_u32(text: &str) -> Option<u32> {
+    u32::try_from(magnitude(text)?).ok()
+}
+
+/// Parses an unsigned integer of at most 64 bits.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_u64(text: &str) -> Option<u64> {
+    magnitude(text)
+}
+
+/// Parses an uninterpreted integer of the given number of bits (at most 64) as its two's complement bits.
+/// Both signed and unsigned literals are allowed, as long as they fit in the given number of bits.
+fn parse_uninterpreted(text: &str, bits: u32) -> Option<u64> {
+    let (negative, rest) = sign(text);
+    let magnitude = magnitude(rest)?;
+    let mask = u64::MAX >> (64 - bits);
+
+    if negative {
+        if magnitude > 1 << (bits - 1) {
+            return None;
+        }
+
+        Some(magnitude.wrapping_neg() & mask)
+    } else if magnitude <= mask {
+        Some(magnitude)
+    } else {
+        None
+    }
+}
+
+/// Parses an uninterpreted 8-bit integer, as used by the lanes of vector constants.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_i8(text: &str) -> Option<i8> {
+    parse_uninterpreted(text, 8).map(|bits| bits as i8)
+}
+
+/// Parses an uninterpreted 16-bit integer, as used by the lanes of vector constants.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_i16(text: &str) -> Option<i16> {
+    parse_uninterpreted(text, 16).map(|bits| bits as i16)
+}
+
+/// Parses an uninterpreted 32-bit integer.
+/// Both signed and unsigned literals are allowed, as long as they fit in 32 bits.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_i32(text: &str) -> Option<i32> {
+    let (negative, rest) = sign(text);
+    let magnitude = magnitude(rest)?;
+
+    if negative {
+        if magnitude > 1 << 31 {
+            return None;
+        }
+
+        Some((magnitude as i64).wrapping_neg() as i32)
+    } else {
+        u32::try_from(magnitude).ok().map(|value| value as i32)
+    }
+}
+
+/// Parses an uninterpreted 64-bit integer.
+/// Both signed and unsigned literals are allowed, as long as they fit in 64 bits.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#integers>
+pub fn parse_i64(text: &str) -> Option<i64> {
+    let (negative, rest) = sign(text);
+    let magnitude = magnitude(rest)?;
+
+    if negative {
+        if magnitude > 1 << 63 {
+            return None;
+        }
+
+        Some((magnitude as i64).wrapping_neg())
+    } else {
+        Some(magnitude as i64)
+    }
+}
+
+/// Parses a 32-bit floating point literal.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#floating-point>
+pub fn parse_f32(text: &str) -> Option<f32> {
+    let bits = parse_float(text, 23, 8, |decimal| {
+        let value: f32 = decimal.parse().ok()?;
+
+        Some(value.to_bits() as u64)
+    })?;
+
+    Some(f32::from_bits(bits as u32))
+}
+
+/// Parses a 64-bit floating point literal.
+///
+/// See <https://webassembly.github.io/spec/core/text/values.html#floating-point>
+pub fn parse_f64(text: &str) -> Option<f64> {
+    let bits = parse_float(text, 52, 11, |decimal| {
+        let value: f64 = decimal.parse().ok()?;
+
+        Some(value.to_bits())
+    })?;
+
+    Some(f64::from_bits(bits))
+}
+
+/// Parses the bits of a floating point literal with the given number of mantissa and exponent bits.
+/// Decimal literals are converted by the given function once their underscores are removed.
+/// Literals whose value rounds to infinity are rejected.
+fn parse_float(
+    text: &str,
+    mantissa_bits: u32,
+    exponent_bits: u32,
+    decimal: impl Fn(&str) -> Option<u64>,
+) -> Option<u64> {
+    let (negative, rest) = sign(text);
+    let sign_bit = (negative as u64) << (mantissa_bits + exponent_bits);
+    let exponent_mask = (1u64 << exponent_bits) - 1;
+    let infinity = exponent_mask << mantissa_bits;
+
+    let bits = if rest == "inf" {
+        infinity
+    } else if rest == "nan" {
+        infinity | 1 << (mantissa_bits - 1)
+    } else if let Some(payload) = rest.strip_prefix("nan:0x") {
+        let payload = u64::from_str_radix(&digits(payload, 16)?, 16).ok()?;
+
+        if payload == 0 || payload >> mantissa_bits != 0 {
+            return None;
+        }
+
+        infinity | payload
+    } else if let Some(hex) = rest.strip_prefix("0x") {
+        parse_hexadecimal_float(hex, mantissa_bits, exponent_bits)?
+    } else {
+        parse_decimal_float(rest, &decimal)?
+    };
+
+    if bits & infinity == infinity && rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    Some(bits | sign_bit)
+}
+
+/// Parses an unsigned decimal floating point literal.
+fn parse_decimal_float(text: &str, decimal: &impl Fn(&str) -> Option<u64>) -> Option<u64> {
+    let (mantissa, exponent) = match text.find(['e', 'E']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (mantissa, None),
+    };
+
+    let mut normalized = digits(integer, 10)?;
+
+    if let Some(fraction) = fraction.filter(|fraction| !fraction.is_empty()) {
+        normalized.push('.');
+        normalized.push_str(&digits(fraction, 10)?);
+    }
+
+    if let Some(exponent) = exponent {
+        let (negative, exponent) = sign(exponent);
+
+        normalized.push('e');
+
+        if negative {
+            normalized.push('-');
+        }
+
+        normalized.push_str(&digits(exponent, 10)?);
+    }
+
+    decimal(&normalized)
+}
+
+/// Parses an unsigned hexadecimal floating point literal (without its `0x` prefix) into the bits
+/// of a float with the given number of mantissa and exponent bits, rounding to nearest, ties to even.
+fn parse_hexadecimal_float(text: &str, mantissa_bits: u32, exponent_bits: u32) -> Option<u64> {
+    let (mantissa, exponent) = match text.find(['p', 'P']) {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (text, None),
+    };
+    let (integer, fraction) = match mantissa.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (mantissa, ""),
+    };
+
+    let integer = digits(integer, 16)?;
+    let fraction = if fraction.is_empty() {
+        String::new()
+    } else {
+        digits(fraction, 16)?
+    };
+    let exponent: i64 = match exponent {
+        Some(exponent) => {
+            let (negative, exponent) = sign(exponent);
+            let magnitude = digits(exponent, 10)?.parse::<i64>().unwrap_or(i64::MAX / 2);
+
+            if negative {
+                -magnitude
+            } else {
+                magnitude
+            }
+        }
+        None => 0,
+    };
+
+    // Accumulate up to 60 significant bits; any remaining non-zero digits only affect rounding.
+    let mut significand = 0u64;
+    let mut scale = exponent;
+    let mut sticky = false;
+
+    for (index, digit) in integer.chars().chain(fraction.chars()).enumerate() {
+        let digit = digit.to_digit(16)? as u64;
+        let is_fraction = index >= integer.len();
+
+        if significand >> 60 == 0 {
+            significand = significand << 4 | digit;
+
+            if is_fraction {
+                scale -= 4;
+            }
+        } else {
+            sticky |= digit != 0;
+
+            if !is_fraction {
+                scale += 4;
+            }
+        }
+    }
+
+    if significand == 0 {
+        return Some(0);
+    }
+
+    let bias = (1i64 << (exponent_bits - 1)) - 1;
+    let mantissa_bits = mantissa_bits as i64;
+    let highest_bit = 63 - significand.leading_zeros() as i64;
+    let minimum_exponent = 1 - bias - mantissa_bits;
+    let mut unit_exponent = (highest_bit + scale - mantissa_bits).max(minimum_exponent);
+    let shift = unit_exponent - scale;
+
+    let mut quotient = if shift <= 0 {
+        (significand as u128) << (-shift).min(127)
+    } else if shift > 64 {
+        0
+    } else {
+        let significand = significand as u128;
+        let quotient = significand >> shift;
+        let remainder = significand & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let round_up = remainder > half || (remainder == half && (sticky || quotient & 1 == 1));
+
+        quotient + round_up as u128
+    };
+
+    if quotient == 1 << (mantissa_bits + 1) {
+        quotient >>= 1;
+        unit_exponent += 1;
+    }
+
+    if quotient >> mantissa_bits == 0 {
+        return Some(quotient as u64);
+    }
+
+    let biased_exponent = unit_exponent + mantissa_bits + bias;
+
+    if biased_exponent >= (1 << exponent_bits) - 1 {
+        return None;
+    }
+
+    Some((biased_exponent as u64) << mantissa_bits | (quotient as u64 & ((1 << mantissa_bits) - 1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        assert_eq!(parse_u32("0"), Some(0));
+        assert_eq!(parse_u32("4_294_967_295"), Some(u32::MAX));
+        assert_eq!(parse_u32("0xFFFF_FFFF"), Some(u32::MAX));
+        assert_eq!(parse_u32("4294967296"), None);
+        assert_eq!(parse_u32("+1"), None);
+        assert_eq!(parse_u32("1__0"), None);
+        assert_eq!(parse_u32("_1"), None);
+        assert_eq!(parse_u32("1_"), None);
+        assert_eq!(parse_i32("-0x8000_0000"), Some(i32::MIN));
+        assert_eq!(parse_i32("0xFFFFFFFF"), Some(-1));
+        assert_eq!(parse_i32("-2147483649"), None);
+        assert_eq!(parse_i32("+42"), Some(42));
+        assert_eq!(parse_i64("-9223372036854775808"), Some(i64::MIN));
+        assert_eq!(parse_i64("18446744073709551615"), Some(-1));
+        assert_eq!(parse_i64("18446744073709551616"), None);
+        assert_eq!(parse_i8("-128"), Some(i8::MIN));
+        assert_eq!(parse_i8("0xFF"), Some(-1));
+        assert_eq!(parse_i8("256"), None);
+        assert_eq!(parse_i8("-129"), None);
+        assert_eq!(parse_i16("65_535"), Some(-1));
+        assert_eq!(parse_i16("-0x8000"), Some(i16::MIN));
+        assert_eq!(parse_i16("65536"), None);
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(parse_f32("1.5"), Some(1.5));
+        assert_eq!(parse_f32("-0x1.8p1"), Some(-3.0));
+        assert_eq!(parse_f32("0x1p-149"), Some(f32::from_bits(1)));
+        assert_eq!(parse_f32("0x1p-150"), Some(0.0));
+        assert_eq!(parse_f32("0x1.000001p0"), Some(1.0));
+        assert_eq!(parse_f32("0x1.000003p0"), Some(f32::from_bits(0x3F80_0002)));
+        assert_eq!(parse_f32("0x1.fffffe7p127"), Some(f32::MAX));
+        assert_eq!(parse_f32("0x1p128"), None);
+        assert_eq!(parse_f32("1e39"), None);
+        assert_eq!(parse_f32("1_000.000_1e-0_1"), Some(100.00001));
+        assert_eq!(parse_f32("inf"), Some(f32::INFINITY));
+        assert_eq!(parse_f32("-inf"), Some(f32::NEG_INFINITY));
+        assert_eq!(parse_f32("nan").map(f32::to_bits), Some(0x7FC0_0000));
+        assert_eq!(parse_f32("-nan:0x1").map(f32::to_bits), Some(0xFF80_0001));
+        assert_eq!(parse_f32("nan:0x0"), None);
+        assert_eq!(parse_f32("nan:0x800000"), None);
+        assert_eq!(parse_f64("0x1.999999999999ap-4"), Some(0.1));
+        assert_eq!(
+            parse_f64("0x0.0000000000001p-1022"),
+            Some(f64::from_bits(1))
+        );
+        assert_eq!(parse_f64("0x1.fffffffffffff8p1023"), None);
+        assert_eq!(parse_f64("0x.8"), None);
+        assert_eq!(parse_f64("1."), Some(1.0));
+        assert_eq!(parse_f64("-0"), Some(-0.0));
+        assert!(parse_f64("-0").unwrap().is_sign_negative());
+        assert_eq!(parse_f64("1e"), None);
+    }
+}
@@ -2,15 +2,18 @@
 //!
 //! See <https://webassembly.github.io/spec/core/binary/types.html>
 
-use crate::parser::values::{match_byte, parse_u32, parse_vector};
+use crate::parser::errors::IResult;
+use crate::parser::values::{match_byte, parse_s33, parse_u32, parse_u64, parse_vector};
 use crate::{
-    FloatType, FunctionType, GlobalType, IntegerType, Limit, MemoryType, Mutability, NumberType,
-    ReferenceType, ResultType, TableType, ValueType,
+    ArrayType, CompositeType, FieldType, FloatType, FunctionType, GlobalType, HeapType, IndexType,
+    IntegerType, Limit, MemoryType, Mutability, NumberType, PackedType, RecursiveType,
+    ReferenceType, ResultType, StorageType, StructType, SubType, TableType, ValueType,
 };
 use nom::branch::alt;
-use nom::combinator::map;
+use nom::combinator::{map, verify};
+use nom::error::context;
+use nom::number::complete::u8;
 use nom::sequence::{preceded, tuple};
-use nom::IResult;
 
 /// Parses a WebAssembly integer type from the input.
 ///
@@ -43,22 +46,69 @@ pub fn parse_number_type(input: &[u8]) -> IResult<&[u8], NumberType> {
 }
 
 /// Parses a WebAssembly reference type from the input.
+/// Nullable references to abstract heap types are encoded as the byte of the heap type alone.
 ///
-/// See <https://webassembly.github.io/spec/core/binary/types.html#reference-types>
+/// See <https://webassembly.github.io/gc/core/binary/types.html#reference-types>
 pub fn parse_reference_type(input: &[u8]) -> IResult<&[u8], ReferenceType> {
-    alt((
-        map(match_byte(0x70), |_| ReferenceType::Function),
-        map(match_byte(0x6F), |_| ReferenceType::External),
-    ))(input)
+    context(
+        "reference type",
+        alt((
+            map(parse_abstract_heap_type, ReferenceType::nullable_reference),
+            map(
+                preceded(match_byte(0x63), parse_heap_type),
+                ReferenceType::nullable_reference,
+            ),
+            map(
+                preceded(match_byte(0x64), parse_heap_type),
+                ReferenceType::Reference,
+            ),
+        )),
+    )(input)
 }
 
 /// Parses a WebAssembly value type from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/types.html#value-types>
 pub fn parse_value_type(input: &[u8]) -> IResult<&[u8], ValueType> {
+    context(
+        "value type",
+        alt((
+            map(parse_number_type, ValueType::from),
+            map(match_byte(0x7B), |_| ValueType::V128),
+            map(parse_reference_type, ValueType::from),
+        )),
+    )(input)
+}
+
+/// Parses a WebAssembly heap type from the input.
+/// Concrete heap types are encoded as non-negative signed 33-bit type indices.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#heap-types>
+pub fn parse_heap_type(input: &[u8]) -> IResult<&[u8], HeapType> {
+    context(
+        "heap type",
+        alt((parse_abstract_heap_type, map(parse_s33, HeapType::Index))),
+    )(input)
+}
+
+/// Parses a WebAssembly abstract heap type from the input.
+/// The same bytes encode the nullable reference types to the abstract heap types.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#heap-types>
+fn parse_abstract_heap_type(input: &[u8]) -> IResult<&[u8], HeapType> {
     alt((
-        map(parse_number_type, ValueType::from),
-        map(parse_reference_type, ValueType::from),
+        map(match_byte(0x70), |_| HeapType::Function),
+        map(match_byte(0x6F), |_| HeapType::External),
+        map(match_byte(0x69), |_| HeapType::Exception),
+        map(match_byte(0x6E), |_| HeapType::Any),
+        map(match_byte(0x6D), |_| HeapType::Eq),
+        map(match_byte(0x6C), |_| HeapType::I31),
+        map(match_byte(0x6B), |_| HeapType::Struct),
+        map(match_byte(0x6A), |_| HeapType::Array),
+        map(match_byte(0x71), |_| HeapType::None),
+        map(match_byte(0x72), |_| HeapType::NoExtern),
+        map(match_byte(0x73), |_| HeapType::NoFunction),
+        map(match_byte(0x74), |_| HeapType::NoException),
     ))(input)
 }
 
@@ -73,35 +123,141 @@ pub fn parse_result_type(input: &[u8]) -> IResult<&[u8], ResultType> {
 ///
 /// See <https://webassembly.github.io/spec/core/binary/types.html#function-types>
 pub fn parse_function_type(input: &[u8]) -> IResult<&[u8], FunctionType> {
-    map(
-        preceded(
-            match_byte(0x60),
-            tuple((parse_result_type, parse_result_type)),
+    context(
+        "function type",
+        map(
+            preceded(
+                match_byte(0x60),
+                tuple((parse_result_type, parse_result_type)),
+            ),
+            |(parameters, results)| FunctionType::new(parameters, results),
         ),
-        |(parameters, results)| FunctionType::new(parameters, results),
     )(input)
 }
 
-/// Parses a WebAssembly limit from the input.
+/// Parses a WebAssembly field type from the input.
 ///
-/// See <https://webassembly.github.io/spec/core/binary/types.html#limits>
-pub fn parse_limit(input: &[u8]) -> IResult<&[u8], Limit> {
-    alt((
-        map(preceded(match_byte(0x00), parse_u32), Limit::unbounded),
+/// See <https://webassembly.github.io/gc/core/binary/types.html#composite-types>
+pub fn parse_field_type(input: &[u8]) -> IResult<&[u8], FieldType> {
+    context(
+        "field type",
         map(
-            preceded(match_byte(0x01), tuple((parse_u32, parse_u32))),
-            |(min, max)| Limit::bounded(min, max),
+            tuple((parse_storage_type, parse_mutability)),
+            |(kind, mutability)| FieldType::new(kind, mutability),
         ),
+    )(input)
+}
+
+/// Parses a WebAssembly storage type from the input.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#composite-types>
+fn parse_storage_type(input: &[u8]) -> IResult<&[u8], StorageType> {
+    alt((
+        map(parse_value_type, StorageType::from),
+        map(match_byte(0x78), |_| PackedType::I8.into()),
+        map(match_byte(0x77), |_| PackedType::I16.into()),
     ))(input)
 }
 
+/// Parses a WebAssembly composite type from the input.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#composite-types>
+pub fn parse_composite_type(input: &[u8]) -> IResult<&[u8], CompositeType> {
+    context(
+        "composite type",
+        alt((
+            map(parse_function_type, CompositeType::from),
+            map(
+                preceded(match_byte(0x5F), parse_vector(parse_field_type)),
+                |fields| StructType::new(fields).into(),
+            ),
+            map(preceded(match_byte(0x5E), parse_field_type), |field| {
+                ArrayType::new(field).into()
+            }),
+        )),
+    )(input)
+}
+
+/// Parses a WebAssembly sub type from the input.
+/// A bare composite type is a final type without supertypes.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#recursive-types>
+pub fn parse_sub_type(input: &[u8]) -> IResult<&[u8], SubType> {
+    context(
+        "sub type",
+        alt((
+            map(
+                preceded(
+                    match_byte(0x50),
+                    tuple((parse_vector(parse_u32), parse_composite_type)),
+                ),
+                |(supertypes, kind)| SubType::new(false, supertypes, kind),
+            ),
+            map(
+                preceded(
+                    match_byte(0x4F),
+                    tuple((parse_vector(parse_u32), parse_composite_type)),
+                ),
+                |(supertypes, kind)| SubType::new(true, supertypes, kind),
+            ),
+            map(parse_composite_type, SubType::from),
+        )),
+    )(input)
+}
+
+/// Parses a WebAssembly recursive type from the input.
+/// A bare sub type is a recursive group of one type.
+///
+/// See <https://webassembly.github.io/gc/core/binary/types.html#recursive-types>
+pub fn parse_recursive_type(input: &[u8]) -> IResult<&[u8], RecursiveType> {
+    context(
+        "recursive type",
+        alt((
+            map(
+                preceded(match_byte(0x4E), parse_vector(parse_sub_type)),
+                RecursiveType::new,
+            ),
+            map(parse_sub_type, |kind| RecursiveType::new(vec![kind])),
+        )),
+    )(input)
+}
+
+/// Parses WebAssembly limits from the input, along with the shared and index type flags.
+/// The flags byte marks the presence of a maximum in bit 0,
+/// a shared memory in bit 1 and a 64-bit index type in bit 2.
+///
+/// See <https://webassembly.github.io/memory64/core/binary/types.html#limits>
+pub fn parse_limit(input: &[u8]) -> IResult<&[u8], (Limit, bool, IndexType)> {
+    let (input, flags) = context("limits flag", verify(u8, |flags| *flags <= 0x07))(input)?;
+    let index_type = if flags & 0x04 == 0 {
+        IndexType::I32
+    } else {
+        IndexType::I64
+    };
+    let parse_bound = |input| match index_type {
+        IndexType::I32 => map(parse_u32, u64::from)(input),
+        IndexType::I64 => parse_u64(input),
+    };
+    let (input, min) = parse_bound(input)?;
+    let (input, max) = if flags & 0x01 == 0 {
+        (input, None)
+    } else {
+        map(parse_bound, Some)(input)?
+    };
+
+    Ok((input, (Limit::new(min, max), flags & 0x02 != 0, index_type)))
+}
+
 /// Parses a WebAssembly table type from the input.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/types.html#table-types>
 pub fn parse_table_type(input: &[u8]) -> IResult<&[u8], TableType> {
     map(
-        tuple((parse_reference_type, parse_limit)),
-        |(kind, limit)| TableType::new(kind, limit),
+        tuple((
+            parse_reference_type,
+            verify(parse_limit, |(_, shared, _)| !*shared),
+        )),
+        |(kind, (limit, _, index_type))| TableType::with_index_type(index_type, kind, limit),
     )(input)
 }
 
@@ -109,7 +265,9 @@ pub fn parse_table_type(input: &[u8]) -> IResult<&[u8], TableType> {
 ///
 /// See <https://webassembly.github.io/spec/core/binary/types.html#memory-types>
 pub fn parse_memory_type(input: &[u8]) -> IResult<&[u8], MemoryType> {
-    map(parse_limit, MemoryType::new)(input)
+    map(parse_limit, |(limit, shared, index_type)| {
+        MemoryType::with_index_type(index_type, limit, shared)
+    })(input)
 }
 
 /// Parses a WebAssembly global type from the input.
@@ -117,17 +275,22 @@ pub fn parse_memory_type(input: &[u8]) -> IResult<&[u8], MemoryType> {
 /// See <https://webassembly.github.io/spec/core/binary/types.html#global-types>
 pub fn parse_global_type(input: &[u8]) -> IResult<&[u8], GlobalType> {
     map(
-        tuple((
-            parse_value_type,
-            alt((
-                map(match_byte(0x00), |_| Mutability::Immutable),
-                map(match_byte(0x01), |_| Mutability::Mutable),
-            )),
-        )),
+        tuple((parse_value_type, parse_mutability)),
         |(kind, mutability)| GlobalType::new(kind, mutability),
     )(input)
 }
 
+/// Parses the mutability flag of a global or field type from the input.
+fn parse_mutability(input: &[u8]) -> IResult<&[u8], Mutability> {
+    context(
+        "mutability flag",
+        alt((
+            map(match_byte(0x00), |_| Mutability::Immutable),
+            map(match_byte(0x01), |_| Mutability::Mutable),
+        )),
+    )(input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -135,7 +298,7 @@ mod tests {
     #[test]
     fn parse_result_type_with_all_types() {
         let extra = 0x01;
-        let mut input = vec![0x7F, 0x7E, 0x7D, 0x7C, 0x70, 0x6F];
+        let mut input = vec![0x7F, 0x7E, 0x7D, 0x7C, 0x7B, 0x70, 0x6F];
         input.insert(0, input.len() as u8);
         input.push(extra);
 
@@ -145,6 +308,7 @@ mod tests {
             ValueType::I64,
             ValueType::F32,
             ValueType::F64,
+            ValueType::V128,
             ValueType::FunctionReference,
             ValueType::ExternalReference,
         ]
@@ -154,6 +318,27 @@ mod tests {
         assert_eq!(remaining, &[extra]);
     }
 
+    #[test]
+    fn parse_typed_references() {
+        let extra = 0x01;
+        let input = vec![
+            4, 0x63, 0x70, 0x63, 0x02, 0x64, 0x6F, 0x64, 0x81, 0x01, extra,
+        ];
+
+        let (remaining, types) = parse_result_type(input.as_slice()).unwrap();
+        let expected = vec![
+            ValueType::FunctionReference,
+            ValueType::NullableReference(HeapType::Index(2)),
+            ValueType::Reference(HeapType::External),
+            ValueType::Reference(HeapType::Index(129)),
+        ]
+        .into();
+
+        assert_eq!(types, expected);
+        assert_eq!(remaining, &[extra]);
+        assert!(parse_value_type(&[0x64, 0x7F]).is_err());
+    }
+
     #[test]
     fn parse_simple_function_type() {
         let extra = 0x01;
@@ -165,13 +350,47 @@ mod tests {
         assert_eq!(remaining, &[extra]);
     }
 
+    #[test]
+    fn parse_recursive_group() {
+        let extra = 0x01;
+        let input = vec![
+            0x4E, 2, 0x50, 0, 0x5F, 2, 0x78, 0x00, 0x63, 0x01, 0x01, 0x4F, 1, 0x00, 0x5E, 0x77,
+            0x01, extra,
+        ];
+        let (remaining, actual) = parse_recursive_type(input.as_slice()).unwrap();
+        let expected = RecursiveType::new(vec![
+            SubType::new(
+                false,
+                vec![],
+                StructType::new(vec![
+                    FieldType::immutable(PackedType::I8),
+                    FieldType::mutable(ValueType::NullableReference(HeapType::Index(1))),
+                ])
+                .into(),
+            ),
+            SubType::new(
+                true,
+                vec![0],
+                ArrayType::new(FieldType::mutable(PackedType::I16)).into(),
+            ),
+        ]);
+
+        assert_eq!(actual, expected);
+        assert_eq!(remaining, &[extra]);
+        assert_eq!(
+            parse_recursive_type(&[0x60, 0, 0]).unwrap().1,
+            FunctionType::runnable().into()
+        );
+        assert!(parse_recursive_type(&[0x5E, 0x77, 0x02]).is_err());
+    }
+
     #[test]
     fn parse_unbounded_limit() {
         let extra = 0x01;
         let min = 42;
         let input = vec![0x00, min, extra];
         let (remaining, actual) = parse_limit(input.as_slice()).unwrap();
-        let expected = Limit::unbounded(min as u32);
+        let expected = (Limit::unbounded(min as u64), false, IndexType::I32);
 
         assert_eq!(actual, expected);
         assert_eq!(remaining, &[extra]);
@@ -184,7 +403,11 @@ mod tests {
         let max = 42;
         let input = vec![0x01, min, max, extra];
         let (remaining, actual) = parse_limit(input.as_slice()).unwrap();
-        let expected = Limit::bounded(min as u32, max as u32);
+        let expected = (
+            Limit::bounded(min as u64, max as u64),
+            false,
+            IndexType::I32,
+        );
 
         assert_eq!(actual, expected);
         assert_eq!(remaining, &[extra]);
@@ -196,24 +419,105 @@ mod tests {
         let min = 42;
         let input = vec![0x00, min, extra];
         let (remaining, actual) = parse_memory_type(input.as_slice()).unwrap();
-        let expected = Limit::unbounded(min as u32).into();
+        let expected = Limit::unbounded(min as u64).into();
+
+        assert_eq!(actual, expected);
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_shared_memory_type() {
+        let extra = 0x01;
+        let min = 1;
+        let max = 42;
+        let input = vec![0x03, min, max, extra];
+        let (remaining, actual) = parse_memory_type(input.as_slice()).unwrap();
+        let expected = MemoryType::shared(Limit::bounded(min as u64, max as u64));
 
         assert_eq!(actual, expected);
         assert_eq!(remaining, &[extra]);
     }
 
+    #[test]
+    fn parse_64_bit_memory_type() {
+        let extra = 0x01;
+        let input = vec![0x05, 0x01, 0x80, 0x80, 0x80, 0x80, 0x40, extra];
+        let (remaining, actual) = parse_memory_type(input.as_slice()).unwrap();
+        let expected =
+            MemoryType::with_index_type(IndexType::I64, Limit::bounded(1, 1 << 34), false);
+
+        assert_eq!(actual, expected);
+        assert_eq!(remaining, &[extra]);
+    }
+
+    #[test]
+    fn parse_shared_64_bit_memory_type() {
+        let input = vec![0x07, 0x01, 0x02];
+        let (_, actual) = parse_memory_type(input.as_slice()).unwrap();
+        let expected = MemoryType::with_index_type(IndexType::I64, Limit::bounded(1, 2), true);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn parse_invalid_limits_flags() {
+        assert!(parse_memory_type(&[0x08, 0x01]).is_err());
+        assert!(parse_table_type(&[0x70, 0x02, 0x01]).is_err());
+    }
+
+    #[test]
+    fn parse_64_bit_table_type() {
+        let input = vec![0x70, 0x04, 0x01];
+        let (_, actual) = parse_table_type(input.as_slice()).unwrap();
+        let expected = TableType::with_index_type(
+            IndexType::I64,
+            ReferenceType::Function,
+            Limit::unbounded(1),
+        );
+
+        assert_eq!(actual, expected);
+    }
+
     #[test]
     fn parse_table_type_unbounded() {
         let extra = 0x01;
         let min = 42;
         let input = vec![0x6F, 0x00, min, extra];
         let (remaining, actual) = parse_table_type(input.as_slice()).unwrap();
-        let expected = TableType::new(ReferenceType::External, Limit::unbounded(min as u32));
+        let expected = TableType::new(ReferenceType::External, Limit::unbounded(min as u64));
 
         assert_eq!(actual, expected);
         assert_eq!(remaining, &[extra]);
     }
 
+    #[test]
+    fn parse_typed_table_type() {
+        let (_, actual) = parse_table_type(&[0x63, 0x02, 0x00, 0x01]).unwrap();
+        let expected = TableType::new(
+            ReferenceType::NullableReference(HeapType::Index(2)),
+            Limit::unbounded(1),
+        );
+
+        assert_eq!(actual, expected);
+
+        let (_, actual) = parse_table_type(&[0x6E, 0x00, 0x01]).unwrap();
+        let expected = TableType::new(
+            ReferenceType::NullableReference(HeapType::Any),
+            Limit::unbounded(1),
+        );
+
+        assert_eq!(actual, expected);
+
+        let (_, actual) = parse_table_type(&[0x64, 0x70, 0x00, 0x01]).unwrap();
+        let expected = TableType::new(
+            ReferenceType::Reference(HeapType::Function),
+            Limit::unbounded(1),
+        );
+
+        assert_eq!(actual, expected);
+        assert!(parse_table_type(&[0x7F, 0x00, 0x01]).is_err());
+    }
+
     #[test]
     fn parse_mutable_global_type() {
         let extra = 0x01;
@@ -1,9 +1,11 @@
 use crate::leb128::{parse_signed, parse_unsigned, LEB128Error};
+use crate::parser::errors::{DecodeError, IResult};
 use crate::Name;
 use nom::bytes::complete::{tag, take};
 use nom::combinator::{map, map_res};
+use nom::error::{context, ErrorKind, ParseError};
 use nom::multi::fold_many_m_n;
-use nom::{IResult, Parser};
+use nom::Parser;
 use std::convert::TryFrom;
 
 /// Parses a single byte and verified the parsed byte matches the given byte.
@@ -11,6 +13,28 @@ pub fn match_byte<'input>(byte: u8) -> impl FnMut(&'input [u8]) -> IResult<&'inp
     map(tag([byte]), |bytes: &'input [u8]| bytes[0])
 }
 
+/// Parses the given bytes, failing at the first byte of the input that differs from them.
+pub fn match_bytes<'input>(
+    bytes: &'static [u8],
+) -> impl FnMut(&'input [u8]) -> IResult<&'input [u8], &'input [u8]> {
+    move |input: &'input [u8]| {
+        let matched = bytes
+            .iter()
+            .zip(input)
+            .take_while(|(expected, actual)| expected == actual)
+            .count();
+
+        if matched < bytes.len() {
+            return Err(nom::Err::Error(DecodeError::from_error_kind(
+                &input[matched..],
+                ErrorKind::Tag,
+            )));
+        }
+
+        Ok((&input[matched..], &input[..matched]))
+    }
+}
+
 /// Parses an unsigned 32-bit integer using LEB128 (Little-Endian Base 128) encoding.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/values.html#integers>
@@ -18,6 +42,13 @@ pub fn parse_u32(input: &[u8]) -> IResult<&[u8], u32> {
     nomify(input, parse_unsigned::<u32>(input))
 }
 
+/// Parses an unsigned 64-bit integer using LEB128 (Little-Endian Base 128) encoding.
+///
+/// See <https://webassembly.github.io/spec/core/binary/values.html#integers>
+pub fn parse_u64(input: &[u8]) -> IResult<&[u8], u64> {
+    nomify(input, parse_unsigned::<u64>(input))
+}
+
 /// Parses a signed 33-bit integer using LEB128 (Little-Endian Base 128) encoding.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/values.html#integers>
@@ -46,9 +77,9 @@ fn nomify<'input, T>(
 ) -> IResult<&'input [u8], T> {
     match result {
         Ok(value) => Ok(value),
-        Err(_) => Err(nom::Err::Failure(nom::error::Error::new(
+        Err(_) => Err(nom::Err::Failure(DecodeError::expected(
             input,
-            nom::error::ErrorKind::MapRes,
+            "leb128 integer",
         ))),
     }
 }
@@ -56,8 +87,19 @@ fn nomify<'input, T>(
 /// Parses a WebAssembly name value.
 ///
 /// See <https://webassembly.github.io/spec/core/binary/values.html#names>
+/// An invalid name fails at its first byte that is not part of a valid UTF-8 sequence.
 pub fn parse_name(input: &[u8]) -> IResult<&[u8], Name> {
-    map(map_res(parse_byte_vector, std::str::from_utf8), Name::from)(input)
+    context("utf-8 name", |input| {
+        let (input, bytes) = parse_byte_vector(input)?;
+
+        match std::str::from_utf8(bytes) {
+            Ok(name) => Ok((input, Name::from(name))),
+            Err(error) => Err(nom::Err::Error(DecodeError::from_error_kind(
+                &bytes[error.valid_up_to()..],
+                ErrorKind::MapRes,
+            ))),
+        }
+    })(input)
 }
 
 /// Parses a WebAssembly byte vector.
@@ -77,7 +119,7 @@ pub fn parse_vector<'input, O, P>(
     parser: P,
 ) -> impl Fn(&'input [u8]) -> IResult<&'input [u8], Vec<O>>
 where
-    P: Copy + Parser<&'input [u8], O, nom::error::Error<&'input [u8]>>,
+    P: Copy + Parser<&'input [u8], O, DecodeError<&'input [u8]>>,
 {
     move |input| {
         let (input, length) = parse_u32(input)?;
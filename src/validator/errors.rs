@@ -0,0 +1,220 @@
+use crate::model::{
+    DataIndex, ElementIndex, FieldIndex, FunctionIndex, GlobalIndex, LabelIndex, LaneIndex,
+    LocalIndex, MemoryIndex, Name, TableIndex, TagIndex, TypeIndex, ValueType, VectorInstruction,
+};
+use std::fmt::{Display, Formatter};
+use thiserror::Error;
+
+/// An error in validating a WebAssembly module.
+/// Each variant identifies the component of the module that failed validation.
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    #[error("The module is not valid: {0}")]
+    Module(ValidationErrorKind),
+    #[error("The type {0} is not valid: {1}")]
+    Type(TypeIndex, ValidationErrorKind),
+    #[error("The import at position {0} is not valid: {1}")]
+    Import(usize, ValidationErrorKind),
+    #[error("The table {0} is not valid: {1}")]
+    Table(TableIndex, ValidationErrorKind),
+    #[error("The memory {0} is not valid: {1}")]
+    Memory(MemoryIndex, ValidationErrorKind),
+    #[error("The tag {0} is not valid: {1}")]
+    Tag(TagIndex, ValidationErrorKind),
+    #[error("The global {0} is not valid: {1}")]
+    Global(GlobalIndex, ValidationErrorKind),
+    #[error("The function {function} is not valid at instruction {path}: {kind}")]
+    Function {
+        function: FunctionIndex,
+        path: InstructionPath,
+        kind: ValidationErrorKind,
+    },
+    #[error("The element segment {0} is not valid: {1}")]
+    Element(ElementIndex, ValidationErrorKind),
+    #[error("The data segment {0} is not valid: {1}")]
+    Data(DataIndex, ValidationErrorKind),
+    #[error("The start function is not valid: {0}")]
+    Start(ValidationErrorKind),
+    #[error("The export at position {0} is not valid: {1}")]
+    Export(usize, ValidationErrorKind),
+}
+
+impl ValidationError {
+    /// The reason the module component failed validation.
+    pub fn kind(&self) -> &ValidationErrorKind {
+        match self {
+            ValidationError::Module(kind)
+            | ValidationError::Type(_, kind)
+            | ValidationError::Import(_, kind)
+            | ValidationError::Table(_, kind)
+            | ValidationError::Memory(_, kind)
+            | ValidationError::Tag(_, kind)
+            | ValidationError::Global(_, kind)
+            | ValidationError::Function { kind, .. }
+            | ValidationError::Element(_, kind)
+            | ValidationError::Data(_, kind)
+            | ValidationError::Start(kind)
+            | ValidationError::Export(_, kind) => kind,
+        }
+    }
+}
+
+/// The reason a component of a WebAssembly module failed validation.
+#[derive(Error, Clone, Debug, PartialEq)]
+pub enum ValidationErrorKind {
+    #[error("Unknown type {0}.")]
+    UnknownType(TypeIndex),
+    #[error("Unknown function {0}.")]
+    UnknownFunction(FunctionIndex),
+    #[error("Unknown table {0}.")]
+    UnknownTable(TableIndex),
+    #[error("Unknown memory {0}.")]
+    UnknownMemory(MemoryIndex),
+    #[error("Unknown global {0}.")]
+    UnknownGlobal(GlobalIndex),
+    #[error("Unknown tag {0}.")]
+    UnknownTag(TagIndex),
+    #[error("Unknown element segment {0}.")]
+    UnknownElement(ElementIndex),
+    #[error("Unknown data segment {0}.")]
+    UnknownData(DataIndex),
+    #[error("Unknown local {0}.")]
+    UnknownLocal(LocalIndex),
+    #[error("Local {0} is read before it is initialized.")]
+    UninitializedLocal(LocalIndex),
+    #[error("Unknown label {0}.")]
+    UnknownLabel(LabelIndex),
+    #[error("Unknown field {field} of type {kind}.")]
+    UnknownField { kind: TypeIndex, field: FieldIndex },
+    #[error("Expected the type {0} to be a function type.")]
+    ExpectedFunctionType(TypeIndex),
+    #[error("Expected the type {0} to be a structure type.")]
+    ExpectedStructType(TypeIndex),
+    #[error("Expected the type {0} to be an array type.")]
+    ExpectedArrayType(TypeIndex),
+    #[error("Expected an operand of type {expected:?}, but found {actual:?}.")]
+    TypeMismatch {
+        expected: ValueType,
+        actual: ValueType,
+    },
+    #[error("Expected an operand of numeric type, but found {0:?}.")]
+    ExpectedNumeric(ValueType),
+    #[error("Expected an operand of reference type, but found {0:?}.")]
+    ExpectedReference(ValueType),
+    #[error("Expected an operand, but the operand stack is empty.")]
+    OperandStackUnderflow,
+    #[error("Expected {expected} operand(s) at the end of the block, but found {actual}.")]
+    OperandStackMismatch { expected: usize, actual: usize },
+    #[error("The targets of a branch table have different arities ({expected} and {actual}).")]
+    BranchTableArityMismatch { expected: usize, actual: usize },
+    #[error("The label of a catch clause expects {expected} value(s), but the clause provides {actual}.")]
+    CatchArityMismatch { expected: usize, actual: usize },
+    #[error("A tail call must return the results {expected:?} of the calling function, but returns {actual:?}.")]
+    TailCallMismatch {
+        expected: Vec<ValueType>,
+        actual: Vec<ValueType>,
+    },
+    #[error(
+        "An if instruction without an else branch must have the same parameter and result types."
+    )]
+    MissingElseBranch,
+    #[error(
+        "The label {0} of a br_on_non_null instruction must expect a reference as its last value."
+    )]
+    InvalidBranchOnNonNull(LabelIndex),
+    #[error("The label {0} of a cast branch must expect a reference as its last value.")]
+    InvalidBranchOnCast(LabelIndex),
+    #[error("A typed select instruction must have exactly one value type, but found {0}.")]
+    InvalidSelectArity(usize),
+    #[error("The global {0} is immutable.")]
+    ImmutableGlobal(GlobalIndex),
+    #[error("The instruction writes to an immutable field of type {0}.")]
+    ImmutableField(TypeIndex),
+    #[error("A sign extension must be given exactly when reading a packed field of type {0}.")]
+    InvalidFieldExtension(TypeIndex),
+    #[error("The type {0} has fields without a default value.")]
+    NonDefaultableType(TypeIndex),
+    #[error("The function body is not a valid expression: {0}")]
+    MalformedBody(String),
+    #[error("Tables without an initializer must have a nullable reference type.")]
+    NonDefaultableTable,
+    #[error("The alignment 2^{align} exceeds the natural alignment 2^{maximum}.")]
+    InvalidAlignment { align: u32, maximum: u32 },
+    #[error("The offset {0} exceeds the range of a memory with a 32-bit index type.")]
+    InvalidOffset(u64),
+    #[error("The alignment 2^{align} of an atomic instruction must equal its natural alignment 2^{natural}.")]
+    InvalidAtomicAlignment { align: u32, natural: u32 },
+    #[error("The lane index {lane} is out of bounds for a vector with {lanes} lanes.")]
+    InvalidLaneIndex { lane: LaneIndex, lanes: LaneIndex },
+    #[error("The vector instruction {0:?} is not defined for its shape.")]
+    UndefinedVectorInstruction(VectorInstruction),
+    #[error("The function {0} is referenced in code without being declared in the module.")]
+    UndeclaredFunctionReference(FunctionIndex),
+    #[error("The instruction requires a data count section.")]
+    MissingDataCount,
+    #[error("The instruction is not allowed in a constant expression.")]
+    NonConstantInstruction,
+    #[error("The minimum {min} of the limits exceeds the maximum {max}.")]
+    InvalidLimits { min: u64, max: u64 },
+    #[error("The limits exceed the allowed range of {0}.")]
+    LimitsOutOfRange(u64),
+    #[error("A shared memory must declare a maximum size.")]
+    UnboundedSharedMemory,
+    #[error("A sub type may declare at most one supertype, but found {0}.")]
+    InvalidSupertypeCount(usize),
+    #[error("The type does not match its supertype {0}, or the supertype is final.")]
+    InvalidSupertype(TypeIndex),
+    #[error("The type of a tag must not have any results.")]
+    InvalidTagType,
+    #[error("The data count {declared} does not match the number of data segments {actual}.")]
+    DataCountMismatch { declared: u32, actual: usize },
+    #[error("The start function must not take parameters or return results.")]
+    InvalidStartFunction,
+    #[error("The export name {0:?} is not unique.")]
+    DuplicateExportName(Name),
+}
+
+/// The position of an instruction inside of a (possibly nested) expression.
+/// Each segment is the index of an instruction in its enclosing expression, outermost first.
+/// Instructions in the 𝖾𝗅𝗌𝖾 branch of an 𝗂𝖿 instruction are numbered after those of the 𝗍𝗁𝖾𝗇 branch,
+/// matching the order of the binary format.
+///
+/// # Examples
+/// ```rust
+/// use wasm_ast::InstructionPath;
+///
+/// let path = InstructionPath::new(vec![3, 0, 1]);
+///
+/// assert_eq!(path.segments(), &[3, 0, 1]);
+/// assert_eq!(path.to_string(), "3.0.1");
+/// assert_eq!(InstructionPath::default().to_string(), "<end>");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct InstructionPath {
+    segments: Vec<usize>,
+}
+
+impl InstructionPath {
+    /// Creates a new path from the given instruction indices, outermost first.
+    pub fn new(segments: Vec<usize>) -> Self {
+        InstructionPath { segments }
+    }
+
+    /// The index of the instruction in each enclosing expression, outermost first.
+    /// An empty path refers to the end of the function body.
+    pub fn segments(&self) -> &[usize] {
+        &self.segments
+    }
+}
+
+impl Display for InstructionPath {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.segments.is_empty() {
+            return write!(f, "<end>");
+        }
+
+        let segments: Vec<String> = self.segments.iter().map(usize::to_string).collect();
+
+        write!(f, "{}", segments.join("."))
+    }
+}
@@ -0,0 +1,1561 @@
+use crate::model::{
+    AtomicInstruction, AtomicOperation, Catch, ControlInstruction, Expression, FieldType,
+    FloatType, GcInstruction, HeapType, IndexType, Instruction, IntegerType, IntegerVectorShape,
+    LaneIndex, MemoryArgument, MemoryInstruction, Mutability, NumberType, NumericInstruction,
+    ParametricInstruction, ReferenceInstruction, ReferenceType, SignExtension, StorageType,
+    TableInstruction, TypeIndex, ValueType, VariableInstruction, VectorInstruction, VectorShape,
+};
+use crate::validator::types::{resolve_block_type, validate_value_type};
+use crate::validator::{Context, InstructionPath, ValidationErrorKind};
+
+/// A validation error inside of an expression, along with the path to the offending instruction.
+pub type ExpressionError = (InstructionPath, ValidationErrorKind);
+
+/// The kind of structured control instruction that introduced a control frame.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum FrameKind {
+    Block,
+    Loop,
+    If,
+    Else,
+}
+
+/// A control frame of the validation algorithm.
+///
+/// See <https://webassembly.github.io/spec/core/appendix/algorithm.html#data-structures>
+struct Frame {
+    kind: FrameKind,
+    parameters: Vec<ValueType>,
+    results: Vec<ValueType>,
+    height: usize,
+    initializations: usize,
+    unreachable: bool,
+}
+
+impl Frame {
+    /// The types of the values a branch to this frame's label expects.
+    fn label_types(&self) -> &[ValueType] {
+        match self.kind {
+            FrameKind::Loop => &self.parameters,
+            _ => &self.results,
+        }
+    }
+}
+
+/// Type checks expressions using the operand and control stacks of the validation algorithm.
+/// Operands of an unknown type (i.e., in unreachable code) are represented as `None`.
+///
+/// See <https://webassembly.github.io/spec/core/appendix/algorithm.html>
+pub struct ExpressionValidator<'context> {
+    context: &'context Context<'context>,
+    locals: Vec<ValueType>,
+    initialized: Vec<bool>,
+    initializations: Vec<u32>,
+    operands: Vec<Option<ValueType>>,
+    frames: Vec<Frame>,
+    path: Vec<usize>,
+}
+
+impl<'context> ExpressionValidator<'context> {
+    /// Creates a new validator for expressions with the given parameters and locals in the given context.
+    /// Parameters and locals with a default value start out initialized, all other locals must be set before use.
+    pub fn new(
+        context: &'context Context<'context>,
+        parameters: &[ValueType],
+        locals: &[ValueType],
+    ) -> Self {
+        let initialized = parameters
+            .iter()
+            .map(|_| true)
+            .chain(
+                locals
+                    .iter()
+                    .map(|local| !local.is_reference() || local.is_nullable()),
+            )
+            .collect();
+
+        ExpressionValidator {
+            context,
+            locals: parameters.iter().chain(locals).copied().collect(),
+            initialized,
+            initializations: Vec::new(),
+            operands: Vec::new(),
+            frames: Vec::new(),
+            path: Vec::new(),
+        }
+    }
+
+    /// Validates that the expression leaves values of the given result types on the operand stack.
+    pub fn validate(
+        mut self,
+        expression: &Expression,
+        results: &[ValueType],
+    ) -> Result<(), ExpressionError> {
+        self.push_frame(FrameKind::Block, Vec::new(), results.to_vec());
+        self.validate_sequence(expression.instructions(), 0)?;
+        self.pop_frame().map_err(|kind| self.error(kind))?;
+
+        Ok(())
+    }
+
+    /// Creates an error for the instruction currently being validated.
+    fn error(&self, kind: ValidationErrorKind) -> ExpressionError {
+        (InstructionPath::new(self.path.clone()), kind)
+    }
+
+    /// Validates a sequence of instructions, numbering them starting from the given offset.
+    fn validate_sequence(
+        &mut self,
+        instructions: &[Instruction],
+        offset: usize,
+    ) -> Result<(), ExpressionError> {
+        for (index, instruction) in instructions.iter().enumerate() {
+            self.path.push(offset + index);
+            self.validate_instruction(instruction)?;
+            self.path.pop();
+        }
+
+        Ok(())
+    }
+
+    /// Validates a single instruction.
+    fn validate_instruction(&mut self, instruction: &Instruction) -> Result<(), ExpressionError> {
+        match instruction {
+            Instruction::Numeric(instruction) => self.validate_numeric(instruction),
+            Instruction::Vector(instruction) => self.validate_vector(instruction),
+            Instruction::Reference(instruction) => self.validate_reference(instruction),
+            Instruction::Parametric(instruction) => self.validate_parametric(instruction),
+            Instruction::Variable(instruction) => self.validate_variable(instruction),
+            Instruction::Table(instruction) => self.validate_table(instruction),
+            Instruction::Memory(instruction) => self.validate_memory(instruction),
+            Instruction::Atomic(instruction) => self.validate_atomic(instruction),
+            Instruction::Gc(instruction) => self.validate_gc(instruction),
+            Instruction::Control(instruction) => return self.validate_control(instruction),
+        }
+        .map_err(|kind| self.error(kind))
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#numeric-instructions>
+    fn validate_numeric(
+        &mut self,
+        instruction: &NumericInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        let (parameters, result) = numeric_signature(instruction);
+
+        self.pop_operands(&parameters)?;
+        self.push_operand(result);
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#vector-instructions>
+    fn validate_vector(
+        &mut self,
+        instruction: &VectorInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        if !instruction.is_defined() {
+            return Err(ValidationErrorKind::UndefinedVectorInstruction(
+                *instruction,
+            ));
+        }
+
+        let mut address = ValueType::I32;
+
+        match *instruction {
+            VectorInstruction::Shuffle(lanes) => {
+                for lane in lanes {
+                    validate_lane(lane, 32)?;
+                }
+            }
+            VectorInstruction::ExtractLane(shape, _, lane)
+            | VectorInstruction::ReplaceLane(shape, lane) => {
+                validate_lane(lane, lane_count(shape))?;
+            }
+            VectorInstruction::Load(argument) | VectorInstruction::Store(argument) => {
+                address = self.address_type(&argument)?;
+                validate_alignment(&argument, 4)?;
+            }
+            VectorInstruction::LoadExtend(_, _, argument) => {
+                address = self.address_type(&argument)?;
+                validate_alignment(&argument, 3)?;
+            }
+            VectorInstruction::LoadSplat(shape, argument)
+            | VectorInstruction::LoadZero(shape, argument) => {
+                address = self.address_type(&argument)?;
+                validate_alignment(&argument, lane_alignment(shape))?;
+            }
+            VectorInstruction::LoadLane(shape, argument, lane)
+            | VectorInstruction::StoreLane(shape, argument, lane) => {
+                address = self.address_type(&argument)?;
+                validate_alignment(&argument, lane_alignment(shape))?;
+                validate_lane(lane, lane_count(shape.into()))?;
+            }
+            _ => {}
+        }
+
+        let (parameters, results) = vector_signature(instruction, address);
+
+        self.pop_operands(&parameters)?;
+        self.push_operands(&results);
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#reference-instructions>
+    fn validate_reference(
+        &mut self,
+        instruction: &ReferenceInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        match instruction {
+            ReferenceInstruction::Null(heap) => {
+                if let HeapType::Index(index) = heap {
+                    self.context.sub_type(*index)?;
+                }
+
+                self.push_operand(ValueType::nullable_reference(*heap));
+            }
+            ReferenceInstruction::IsNull => {
+                self.pop_reference()?;
+                self.push_operand(ValueType::I32);
+            }
+            ReferenceInstruction::Function(function) => {
+                let kind = self.context.function_type_index(*function)?;
+
+                self.context.reference(*function)?;
+                self.push_operand(ValueType::Reference(HeapType::Index(kind)));
+            }
+            ReferenceInstruction::AsNonNull => {
+                let heap = self.pop_reference()?;
+
+                self.operands.push(heap.map(ValueType::Reference));
+            }
+            ReferenceInstruction::Equal => {
+                let kind = ValueType::nullable_reference(HeapType::Eq);
+
+                self.pop_operands(&[kind, kind])?;
+                self.push_operand(ValueType::I32);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#parametric-instructions>
+    fn validate_parametric(
+        &mut self,
+        instruction: &ParametricInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        match instruction {
+            ParametricInstruction::Drop => {
+                self.pop_operand()?;
+            }
+            ParametricInstruction::Select(None) => {
+                self.pop_expected(ValueType::I32)?;
+
+                let first = self.pop_operand()?;
+                let second = self.pop_operand()?;
+
+                for kind in [first, second].into_iter().flatten() {
+                    if kind.is_reference() {
+                        return Err(ValidationErrorKind::ExpectedNumeric(kind));
+                    }
+                }
+
+                match (first, second) {
+                    (Some(expected), Some(actual)) if expected != actual => {
+                        return Err(ValidationErrorKind::TypeMismatch { expected, actual });
+                    }
+                    _ => self.operands.push(first.or(second)),
+                }
+            }
+            ParametricInstruction::Select(Some(kinds)) => {
+                if kinds.len() != 1 {
+                    return Err(ValidationErrorKind::InvalidSelectArity(kinds.len()));
+                }
+
+                self.pop_expected(ValueType::I32)?;
+                self.pop_expected(kinds[0])?;
+                self.pop_expected(kinds[0])?;
+                self.push_operand(kinds[0]);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#variable-instructions>
+    fn validate_variable(
+        &mut self,
+        instruction: &VariableInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        match *instruction {
+            VariableInstruction::LocalGet(local) => {
+                let kind = self.local(local)?;
+
+                if !self.initialized[local as usize] {
+                    return Err(ValidationErrorKind::UninitializedLocal(local));
+                }
+
+                self.push_operand(kind);
+            }
+            VariableInstruction::LocalSet(local) => {
+                let kind = self.local(local)?;
+                self.pop_expected(kind)?;
+                self.initialize(local);
+            }
+            VariableInstruction::LocalTee(local) => {
+                let kind = self.local(local)?;
+                self.pop_expected(kind)?;
+                self.initialize(local);
+                self.push_operand(kind);
+            }
+            VariableInstruction::GlobalGet(global) => {
+                let kind = self.context.global(global)?.kind();
+                self.push_operand(kind);
+            }
+            VariableInstruction::GlobalSet(global) => {
+                let kind = self.context.global(global)?;
+
+                if kind.mutability() != Mutability::Mutable {
+                    return Err(ValidationErrorKind::ImmutableGlobal(global));
+                }
+
+                self.pop_expected(kind.kind())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#table-instructions>
+    fn validate_table(
+        &mut self,
+        instruction: &TableInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        match *instruction {
+            TableInstruction::Get(table) => {
+                let table = self.context.table(table)?;
+
+                self.pop_expected(table.index_type().into())?;
+                self.push_operand(table.kind().into());
+            }
+            TableInstruction::Set(table) => {
+                let table = self.context.table(table)?;
+
+                self.pop_expected(table.kind().into())?;
+                self.pop_expected(table.index_type().into())?;
+            }
+            TableInstruction::Size(table) => {
+                let table = self.context.table(table)?;
+
+                self.push_operand(table.index_type().into());
+            }
+            TableInstruction::Grow(table) => {
+                let table = self.context.table(table)?;
+
+                self.pop_expected(table.index_type().into())?;
+                self.pop_expected(table.kind().into())?;
+                self.push_operand(table.index_type().into());
+            }
+            TableInstruction::Fill(table) => {
+                let table = self.context.table(table)?;
+                let address = table.index_type().into();
+
+                self.pop_expected(address)?;
+                self.pop_expected(table.kind().into())?;
+                self.pop_expected(address)?;
+            }
+            TableInstruction::Copy(destination, source) => {
+                let destination = self.context.table(destination)?;
+                let source = self.context.table(source)?;
+                let length = minimum_index_type(destination.index_type(), source.index_type());
+
+                self.matching_references(destination.kind(), source.kind())?;
+                self.pop_operands(&[
+                    destination.index_type().into(),
+                    source.index_type().into(),
+                    length.into(),
+                ])?;
+            }
+            TableInstruction::Init(element, table) => {
+                let table = self.context.table(table)?;
+                let actual = self.context.element(element)?;
+
+                self.matching_references(table.kind(), actual)?;
+                self.pop_operands(&[table.index_type().into(), ValueType::I32, ValueType::I32])?;
+            }
+            TableInstruction::ElementDrop(element) => {
+                self.context.element(element)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#memory-instructions>
+    fn validate_memory(
+        &mut self,
+        instruction: &MemoryInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        let address = match *instruction {
+            MemoryInstruction::Load(_, argument)
+            | MemoryInstruction::Store(_, argument)
+            | MemoryInstruction::Load8(_, _, argument)
+            | MemoryInstruction::Load16(_, _, argument)
+            | MemoryInstruction::Load32(_, argument)
+            | MemoryInstruction::Store8(_, argument)
+            | MemoryInstruction::Store16(_, argument)
+            | MemoryInstruction::Store32(argument) => self.address_type(&argument)?,
+            MemoryInstruction::Size(memory)
+            | MemoryInstruction::Grow(memory)
+            | MemoryInstruction::Fill(memory)
+            | MemoryInstruction::Init(_, memory) => {
+                self.context.memory(memory)?.index_type().into()
+            }
+            MemoryInstruction::Copy(destination, _) => {
+                self.context.memory(destination)?.index_type().into()
+            }
+            MemoryInstruction::DataDrop(_) => ValueType::I32,
+        };
+
+        match *instruction {
+            MemoryInstruction::Load(kind, argument) => {
+                validate_alignment(&argument, natural_alignment(kind))?;
+                self.pop_expected(address)?;
+                self.push_operand(kind.into());
+            }
+            MemoryInstruction::Store(kind, argument) => {
+                validate_alignment(&argument, natural_alignment(kind))?;
+                self.pop_expected(kind.into())?;
+                self.pop_expected(address)?;
+            }
+            MemoryInstruction::Load8(kind, _, argument) => {
+                validate_alignment(&argument, 0)?;
+                self.pop_expected(address)?;
+                self.push_operand(kind.into());
+            }
+            MemoryInstruction::Load16(kind, _, argument) => {
+                validate_alignment(&argument, 1)?;
+                self.pop_expected(address)?;
+                self.push_operand(kind.into());
+            }
+            MemoryInstruction::Load32(_, argument) => {
+                validate_alignment(&argument, 2)?;
+                self.pop_expected(address)?;
+                self.push_operand(ValueType::I64);
+            }
+            MemoryInstruction::Store8(kind, argument) => {
+                validate_alignment(&argument, 0)?;
+                self.pop_expected(kind.into())?;
+                self.pop_expected(address)?;
+            }
+            MemoryInstruction::Store16(kind, argument) => {
+                validate_alignment(&argument, 1)?;
+                self.pop_expected(kind.into())?;
+                self.pop_expected(address)?;
+            }
+            MemoryInstruction::Store32(argument) => {
+                validate_alignment(&argument, 2)?;
+                self.pop_expected(ValueType::I64)?;
+                self.pop_expected(address)?;
+            }
+            MemoryInstruction::Size(_) => {
+                self.push_operand(address);
+            }
+            MemoryInstruction::Grow(_) => {
+                self.pop_expected(address)?;
+                self.push_operand(address);
+            }
+            MemoryInstruction::Fill(_) => {
+                self.pop_operands(&[address, ValueType::I32, address])?;
+            }
+            MemoryInstruction::Copy(destination, source) => {
+                let destination = self.context.memory(destination)?.index_type();
+                let source = self.context.memory(source)?.index_type();
+                let length = minimum_index_type(destination, source);
+
+                self.pop_operands(&[destination.into(), source.into(), length.into()])?;
+            }
+            MemoryInstruction::Init(data, _) => {
+                self.context.data_count()?;
+                self.context.data(data)?;
+                self.pop_operands(&[address, ValueType::I32, ValueType::I32])?;
+            }
+            MemoryInstruction::DataDrop(data) => {
+                self.context.data_count()?;
+                self.context.data(data)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the type of the address operand of an instruction accessing memory,
+    /// which must be able to represent the offset of the memory argument.
+    ///
+    /// See <https://webassembly.github.io/memory64/core/valid/instructions.html#memory-instructions>
+    fn address_type(&self, argument: &MemoryArgument) -> Result<ValueType, ValidationErrorKind> {
+        let index_type = self.context.memory(argument.memory())?.index_type();
+
+        if index_type == IndexType::I32 && argument.offset() > u32::MAX as u64 {
+            Err(ValidationErrorKind::InvalidOffset(argument.offset()))
+        } else {
+            Ok(index_type.into())
+        }
+    }
+
+    /// See <https://webassembly.github.io/threads/core/valid/instructions.html#atomic-memory-instructions>
+    fn validate_atomic(
+        &mut self,
+        instruction: &AtomicInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        let (argument, natural, kind) = match *instruction {
+            AtomicInstruction::Fence => return Ok(()),
+            AtomicInstruction::Notify(argument) => (argument, 2, IntegerType::I32),
+            AtomicInstruction::Wait(kind, argument)
+            | AtomicInstruction::Load(kind, argument)
+            | AtomicInstruction::Store(kind, argument)
+            | AtomicInstruction::ReadModifyWrite(_, kind, argument) => {
+                (argument, natural_alignment(kind.into()), kind)
+            }
+            AtomicInstruction::Load8(kind, argument)
+            | AtomicInstruction::Store8(kind, argument)
+            | AtomicInstruction::ReadModifyWrite8(_, kind, argument) => (argument, 0, kind),
+            AtomicInstruction::Load16(kind, argument)
+            | AtomicInstruction::Store16(kind, argument)
+            | AtomicInstruction::ReadModifyWrite16(_, kind, argument) => (argument, 1, kind),
+            AtomicInstruction::Load32(argument)
+            | AtomicInstruction::Store32(argument)
+            | AtomicInstruction::ReadModifyWrite32(_, argument) => (argument, 2, IntegerType::I64),
+        };
+
+        let address = self.address_type(&argument)?;
+
+        if argument.align() != natural {
+            return Err(ValidationErrorKind::InvalidAtomicAlignment {
+                align: argument.align(),
+                natural,
+            });
+        }
+
+        let kind = ValueType::from(kind);
+
+        match *instruction {
+            AtomicInstruction::Notify(_) => {
+                self.pop_operands(&[address, ValueType::I32])?;
+                self.push_operand(ValueType::I32);
+            }
+            AtomicInstruction::Wait(..) => {
+                self.pop_operands(&[address, kind, ValueType::I64])?;
+                self.push_operand(ValueType::I32);
+            }
+            AtomicInstruction::Load(..)
+            | AtomicInstruction::Load8(..)
+            | AtomicInstruction::Load16(..)
+            | AtomicInstruction::Load32(_) => {
+                self.pop_expected(address)?;
+                self.push_operand(kind);
+            }
+            AtomicInstruction::Store(..)
+            | AtomicInstruction::Store8(..)
+            | AtomicInstruction::Store16(..)
+            | AtomicInstruction::Store32(_) => {
+                self.pop_operands(&[address, kind])?;
+            }
+            AtomicInstruction::ReadModifyWrite(AtomicOperation::CompareExchange, ..)
+            | AtomicInstruction::ReadModifyWrite8(AtomicOperation::CompareExchange, ..)
+            | AtomicInstruction::ReadModifyWrite16(AtomicOperation::CompareExchange, ..)
+            | AtomicInstruction::ReadModifyWrite32(AtomicOperation::CompareExchange, _) => {
+                self.pop_operands(&[address, kind, kind])?;
+                self.push_operand(kind);
+            }
+            _ => {
+                self.pop_operands(&[address, kind])?;
+                self.push_operand(kind);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// See <https://webassembly.github.io/gc/core/valid/instructions.html#aggregate-reference-instructions>
+    fn validate_gc(&mut self, instruction: &GcInstruction) -> Result<(), ValidationErrorKind> {
+        let reference = |index| ValueType::Reference(HeapType::Index(index));
+        let nullable = |index| ValueType::NullableReference(HeapType::Index(index));
+
+        match *instruction {
+            GcInstruction::StructNew(index) => {
+                let kinds: Vec<ValueType> = self
+                    .context
+                    .struct_type(index)?
+                    .fields()
+                    .iter()
+                    .map(|field| field.kind().unpacked())
+                    .collect();
+
+                self.pop_operands(&kinds)?;
+                self.push_operand(reference(index));
+            }
+            GcInstruction::StructNewDefault(index) => {
+                let fields = self.context.struct_type(index)?.fields();
+
+                if !fields.iter().all(is_defaultable) {
+                    return Err(ValidationErrorKind::NonDefaultableType(index));
+                }
+
+                self.push_operand(reference(index));
+            }
+            GcInstruction::StructGet(index, field, extension) => {
+                let kind = validate_extension(index, self.context.field(index, field)?, extension)?;
+
+                self.pop_expected(nullable(index))?;
+                self.push_operand(kind);
+            }
+            GcInstruction::StructSet(index, field) => {
+                let kind = validate_mutable(index, self.context.field(index, field)?)?;
+
+                self.pop_operands(&[nullable(index), kind])?;
+            }
+            GcInstruction::ArrayNew(index) => {
+                let kind = self.context.array_type(index)?.field().kind().unpacked();
+
+                self.pop_operands(&[kind, ValueType::I32])?;
+                self.push_operand(reference(index));
+            }
+            GcInstruction::ArrayNewDefault(index) => {
+                if !is_defaultable(&self.context.array_type(index)?.field()) {
+                    return Err(ValidationErrorKind::NonDefaultableType(index));
+                }
+
+                self.pop_expected(ValueType::I32)?;
+                self.push_operand(reference(index));
+            }
+            GcInstruction::ArrayNewFixed(index, length) => {
+                let kind = self.context.array_type(index)?.field().kind().unpacked();
+
+                for _ in 0..length {
+                    let frame = self
+                        .frames
+                        .last()
+                        .expect("The control stack must not be empty.");
+
+                    if frame.unreachable && self.operands.len() == frame.height {
+                        break;
+                    }
+
+                    self.pop_expected(kind)?;
+                }
+
+                self.push_operand(reference(index));
+            }
+            GcInstruction::ArrayNewData(index, data) => {
+                validate_numeric_field(self.context.array_type(index)?.field())?;
+                self.context.data_count()?;
+                self.context.data(data)?;
+                self.pop_operands(&[ValueType::I32, ValueType::I32])?;
+                self.push_operand(reference(index));
+            }
+            GcInstruction::ArrayNewElement(index, element) => {
+                let expected = self.context.array_type(index)?.field().kind().unpacked();
+
+                self.validate_element_type(element, expected)?;
+                self.pop_operands(&[ValueType::I32, ValueType::I32])?;
+                self.push_operand(reference(index));
+            }
+            GcInstruction::ArrayGet(index, extension) => {
+                let field = self.context.array_type(index)?.field();
+                let kind = validate_extension(index, field, extension)?;
+
+                self.pop_operands(&[nullable(index), ValueType::I32])?;
+                self.push_operand(kind);
+            }
+            GcInstruction::ArraySet(index) => {
+                let kind = validate_mutable(index, self.context.array_type(index)?.field())?;
+
+                self.pop_operands(&[nullable(index), ValueType::I32, kind])?;
+            }
+            GcInstruction::ArrayLength => {
+                self.pop_expected(ValueType::nullable_reference(HeapType::Array))?;
+                self.push_operand(ValueType::I32);
+            }
+            GcInstruction::ArrayFill(index) => {
+                let kind = validate_mutable(index, self.context.array_type(index)?.field())?;
+
+                self.pop_operands(&[nullable(index), ValueType::I32, kind, ValueType::I32])?;
+            }
+            GcInstruction::ArrayCopy(destination, source) => {
+                let expected = self.context.array_type(destination)?.field();
+                let actual = self.context.array_type(source)?.field();
+
+                validate_mutable(destination, expected)?;
+
+                let matching = match (actual.kind(), expected.kind()) {
+                    (StorageType::Value(actual), StorageType::Value(expected)) => {
+                        self.context.matches(actual, expected)
+                    }
+                    (actual, expected) => actual == expected,
+                };
+
+                if !matching {
+                    return Err(ValidationErrorKind::TypeMismatch {
+                        expected: expected.kind().unpacked(),
+                        actual: actual.kind().unpacked(),
+                    });
+                }
+
+                self.pop_operands(&[
+                    nullable(destination),
+                    ValueType::I32,
+                    nullable(source),
+                    ValueType::I32,
+                    ValueType::I32,
+                ])?;
+            }
+            GcInstruction::ArrayInitData(index, data) => {
+                let field = self.context.array_type(index)?.field();
+
+                validate_mutable(index, field)?;
+                validate_numeric_field(field)?;
+                self.context.data_count()?;
+                self.context.data(data)?;
+                self.pop_operands(&[
+                    nullable(index),
+                    ValueType::I32,
+                    ValueType::I32,
+                    ValueType::I32,
+                ])?;
+            }
+            GcInstruction::ArrayInitElement(index, element) => {
+                let expected = validate_mutable(index, self.context.array_type(index)?.field())?;
+
+                self.validate_element_type(element, expected)?;
+                self.pop_operands(&[
+                    nullable(index),
+                    ValueType::I32,
+                    ValueType::I32,
+                    ValueType::I32,
+                ])?;
+            }
+            GcInstruction::Test(kind) => {
+                self.pop_cast_operand(kind)?;
+                self.push_operand(ValueType::I32);
+            }
+            GcInstruction::Cast(kind) => {
+                self.pop_cast_operand(kind)?;
+                self.push_operand(kind);
+            }
+            GcInstruction::BranchOnCast(label, source, target)
+            | GcInstruction::BranchOnCastFail(label, source, target) => {
+                for kind in [source, target] {
+                    if !kind.is_reference() {
+                        return Err(ValidationErrorKind::ExpectedReference(kind));
+                    }
+
+                    validate_value_type(kind, self.context)?;
+                }
+
+                if !self.context.matches(target, source) {
+                    return Err(ValidationErrorKind::TypeMismatch {
+                        expected: source,
+                        actual: target,
+                    });
+                }
+
+                let difference = match (target.is_nullable(), source.heap_type()) {
+                    (true, Some(heap)) => ValueType::Reference(heap),
+                    _ => source,
+                };
+                let (branch, fallthrough) = match instruction {
+                    GcInstruction::BranchOnCast(..) => (target, difference),
+                    _ => (difference, target),
+                };
+                let mut kinds = self.label_types(label)?;
+                let expected = kinds
+                    .pop()
+                    .filter(ValueType::is_reference)
+                    .ok_or(ValidationErrorKind::InvalidBranchOnCast(label))?;
+
+                if !self.context.matches(branch, expected) {
+                    return Err(ValidationErrorKind::TypeMismatch {
+                        expected,
+                        actual: branch,
+                    });
+                }
+
+                self.pop_expected(source)?;
+                self.pop_operands(&kinds)?;
+                self.push_operands(&kinds);
+                self.push_operand(fallthrough);
+            }
+            GcInstruction::AnyConvertExtern => {
+                let kind = self.pop_expected(ValueType::ExternalReference)?;
+
+                self.push_operand(convert_reference(kind, HeapType::Any));
+            }
+            GcInstruction::ExternConvertAny => {
+                let kind = self.pop_expected(ValueType::nullable_reference(HeapType::Any))?;
+
+                self.push_operand(convert_reference(kind, HeapType::External));
+            }
+            GcInstruction::ReferenceI31 => {
+                self.pop_expected(ValueType::I32)?;
+                self.push_operand(ValueType::Reference(HeapType::I31));
+            }
+            GcInstruction::I31Get(_) => {
+                self.pop_expected(ValueType::nullable_reference(HeapType::I31))?;
+                self.push_operand(ValueType::I32);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pops the operand of a cast to the given reference type,
+    /// which must belong to the same type hierarchy as the target of the cast.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/instructions.html#reference-instructions>
+    fn pop_cast_operand(&mut self, kind: ValueType) -> Result<(), ValidationErrorKind> {
+        let heap = kind
+            .heap_type()
+            .ok_or(ValidationErrorKind::ExpectedReference(kind))?;
+        let top = self.context.top(heap)?;
+
+        self.pop_expected(ValueType::nullable_reference(top))?;
+
+        Ok(())
+    }
+
+    /// Validates that the references of the element segment can be stored as the given type.
+    fn validate_element_type(
+        &self,
+        element: u32,
+        expected: ValueType,
+    ) -> Result<(), ValidationErrorKind> {
+        let actual = self.context.element(element)?.into();
+
+        if self.context.matches(actual, expected) {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::TypeMismatch { expected, actual })
+        }
+    }
+
+    /// See <https://webassembly.github.io/spec/core/valid/instructions.html#control-instructions>
+    fn validate_control(
+        &mut self,
+        instruction: &ControlInstruction,
+    ) -> Result<(), ExpressionError> {
+        match instruction {
+            ControlInstruction::Block(kind, expression) => {
+                self.validate_block(FrameKind::Block, kind, expression)
+            }
+            ControlInstruction::Loop(kind, expression) => {
+                self.validate_block(FrameKind::Loop, kind, expression)
+            }
+            ControlInstruction::TryTable(kind, catches, expression) => {
+                for catch in catches {
+                    self.validate_catch(catch)
+                        .map_err(|kind| self.error(kind))?;
+                }
+
+                self.validate_block(FrameKind::Block, kind, expression)
+            }
+            ControlInstruction::If(kind, then, otherwise) => {
+                let kind =
+                    resolve_block_type(kind, self.context).map_err(|kind| self.error(kind))?;
+                let parameters = kind.parameters().kinds().to_vec();
+                let results = kind.results().kinds().to_vec();
+
+                self.pop_expected(ValueType::I32)
+                    .and_then(|_| self.pop_operands(&parameters))
+                    .map_err(|kind| self.error(kind))?;
+
+                self.push_frame(FrameKind::If, parameters.clone(), results.clone());
+                self.validate_sequence(then.instructions(), 0)?;
+                self.pop_frame().map_err(|kind| self.error(kind))?;
+
+                match otherwise {
+                    Some(otherwise) => {
+                        self.push_frame(FrameKind::Else, parameters, results.clone());
+                        self.validate_sequence(otherwise.instructions(), then.len())?;
+                        self.pop_frame().map_err(|kind| self.error(kind))?;
+                    }
+                    None if parameters != results => {
+                        return Err(self.error(ValidationErrorKind::MissingElseBranch));
+                    }
+                    None => {}
+                }
+
+                self.push_operands(&results);
+
+                Ok(())
+            }
+            instruction => self
+                .validate_branch(instruction)
+                .map_err(|kind| self.error(kind)),
+        }
+    }
+
+    /// Validates a block or loop instruction.
+    fn validate_block(
+        &mut self,
+        frame: FrameKind,
+        kind: &crate::model::BlockType,
+        expression: &Expression,
+    ) -> Result<(), ExpressionError> {
+        let kind = resolve_block_type(kind, self.context).map_err(|kind| self.error(kind))?;
+        let parameters = kind.parameters().kinds().to_vec();
+        let results = kind.results().kinds().to_vec();
+
+        self.pop_operands(&parameters)
+            .map_err(|kind| self.error(kind))?;
+        self.push_frame(frame, parameters, results.clone());
+        self.validate_sequence(expression.instructions(), 0)?;
+        self.pop_frame().map_err(|kind| self.error(kind))?;
+        self.push_operands(&results);
+
+        Ok(())
+    }
+
+    /// Validates that a catch clause provides the values expected by its label.
+    ///
+    /// See <https://webassembly.github.io/exception-handling/core/valid/instructions.html#control-instructions>
+    fn validate_catch(&self, catch: &Catch) -> Result<(), ValidationErrorKind> {
+        let mut kinds = match catch {
+            Catch::Catch(tag, _) | Catch::CatchReference(tag, _) => {
+                self.context.tag(*tag)?.parameters().kinds().to_vec()
+            }
+            Catch::CatchAll(_) | Catch::CatchAllReference(_) => Vec::new(),
+        };
+
+        if let Catch::CatchReference(..) | Catch::CatchAllReference(_) = catch {
+            kinds.push(ValueType::ExceptionReference);
+        }
+
+        let expected = self.label_types(catch.label())?;
+
+        if expected.len() != kinds.len() {
+            return Err(ValidationErrorKind::CatchArityMismatch {
+                expected: expected.len(),
+                actual: kinds.len(),
+            });
+        }
+
+        for (expected, actual) in expected.into_iter().zip(kinds) {
+            if !self.context.matches(actual, expected) {
+                return Err(ValidationErrorKind::TypeMismatch { expected, actual });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Validates that the callee of a tail call returns the same results as the current function.
+    ///
+    /// See <https://webassembly.github.io/tail-call/core/valid/instructions.html#control-instructions>
+    fn validate_tail_call(&self, results: &[ValueType]) -> Result<(), ValidationErrorKind> {
+        let expected = &self.frames[0].results;
+
+        if expected.as_slice() != results {
+            return Err(ValidationErrorKind::TailCallMismatch {
+                expected: expected.clone(),
+                actual: results.to_vec(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validates the non-structured control instructions.
+    fn validate_branch(
+        &mut self,
+        instruction: &ControlInstruction,
+    ) -> Result<(), ValidationErrorKind> {
+        match instruction {
+            ControlInstruction::Nop => {}
+            ControlInstruction::Unreachable => self.mark_unreachable(),
+            ControlInstruction::Branch(label) => {
+                let kinds = self.label_types(*label)?;
+
+                self.pop_operands(&kinds)?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::BranchIf(label) => {
+                let kinds = self.label_types(*label)?;
+
+                self.pop_expected(ValueType::I32)?;
+                self.pop_operands(&kinds)?;
+                self.push_operands(&kinds);
+            }
+            ControlInstruction::BranchTable(labels, default) => {
+                self.pop_expected(ValueType::I32)?;
+
+                let default_kinds = self.label_types(*default)?;
+
+                for label in labels {
+                    let kinds = self.label_types(*label)?;
+
+                    if kinds.len() != default_kinds.len() {
+                        return Err(ValidationErrorKind::BranchTableArityMismatch {
+                            expected: default_kinds.len(),
+                            actual: kinds.len(),
+                        });
+                    }
+
+                    let operands = self.pop_operands(&kinds)?;
+                    self.operands.extend(operands);
+                }
+
+                self.pop_operands(&default_kinds)?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::Return => {
+                let kinds = self.frames[0].results.clone();
+
+                self.pop_operands(&kinds)?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::Call(function) => {
+                let kind = self.context.function(*function)?;
+
+                self.pop_operands(kind.parameters().kinds())?;
+                self.push_operands(kind.results().kinds());
+            }
+            ControlInstruction::CallIndirect(kind, table) => {
+                let table = self.context.table(*table)?;
+                let kind = self.context.function_type(*kind)?;
+
+                self.matching_references(ReferenceType::Function, table.kind())?;
+                self.pop_expected(table.index_type().into())?;
+                self.pop_operands(kind.parameters().kinds())?;
+                self.push_operands(kind.results().kinds());
+            }
+            ControlInstruction::ReturnCall(function) => {
+                let kind = self.context.function(*function)?;
+
+                self.validate_tail_call(kind.results().kinds())?;
+                self.pop_operands(kind.parameters().kinds())?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::ReturnCallIndirect(kind, table) => {
+                let table = self.context.table(*table)?;
+                let kind = self.context.function_type(*kind)?;
+
+                self.matching_references(ReferenceType::Function, table.kind())?;
+                self.validate_tail_call(kind.results().kinds())?;
+                self.pop_expected(table.index_type().into())?;
+                self.pop_operands(kind.parameters().kinds())?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::CallReference(index) => {
+                let kind = self.context.function_type(*index)?;
+
+                self.pop_expected(ValueType::NullableReference(HeapType::Index(*index)))?;
+                self.pop_operands(kind.parameters().kinds())?;
+                self.push_operands(kind.results().kinds());
+            }
+            ControlInstruction::ReturnCallReference(index) => {
+                let kind = self.context.function_type(*index)?;
+
+                self.validate_tail_call(kind.results().kinds())?;
+                self.pop_expected(ValueType::NullableReference(HeapType::Index(*index)))?;
+                self.pop_operands(kind.parameters().kinds())?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::BranchOnNull(label) => {
+                let kinds = self.label_types(*label)?;
+                let heap = self.pop_reference()?;
+
+                self.pop_operands(&kinds)?;
+                self.push_operands(&kinds);
+                self.operands.push(heap.map(ValueType::Reference));
+            }
+            ControlInstruction::BranchOnNonNull(label) => {
+                let mut kinds = self.label_types(*label)?;
+                let expected = kinds
+                    .pop()
+                    .filter(ValueType::is_reference)
+                    .ok_or(ValidationErrorKind::InvalidBranchOnNonNull(*label))?;
+
+                if let Some(heap) = self.pop_reference()? {
+                    let actual = ValueType::Reference(heap);
+
+                    if !self.context.matches(actual, expected) {
+                        return Err(ValidationErrorKind::TypeMismatch { expected, actual });
+                    }
+                }
+
+                self.pop_operands(&kinds)?;
+                self.push_operands(&kinds);
+            }
+            ControlInstruction::Throw(tag) => {
+                let kind = self.context.tag(*tag)?;
+
+                self.pop_operands(kind.parameters().kinds())?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::ThrowReference => {
+                self.pop_expected(ValueType::ExceptionReference)?;
+                self.mark_unreachable();
+            }
+            ControlInstruction::Block(..)
+            | ControlInstruction::Loop(..)
+            | ControlInstruction::If(..)
+            | ControlInstruction::TryTable(..) => {
+                unreachable!("Structured instructions are validated separately.")
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The type of the local at the given index.
+    fn local(&self, local: u32) -> Result<ValueType, ValidationErrorKind> {
+        self.locals
+            .get(local as usize)
+            .copied()
+            .ok_or(ValidationErrorKind::UnknownLocal(local))
+    }
+
+    /// Verifies that the reference type `actual` matches the `expected` one.
+    fn matching_references(
+        &self,
+        expected: ReferenceType,
+        actual: ReferenceType,
+    ) -> Result<(), ValidationErrorKind> {
+        if self.context.matches(actual.into(), expected.into()) {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::TypeMismatch {
+                expected: expected.into(),
+                actual: actual.into(),
+            })
+        }
+    }
+
+    /// Marks the local at the given index as initialized until the end of the current block.
+    fn initialize(&mut self, local: u32) {
+        if !self.initialized[local as usize] {
+            self.initialized[local as usize] = true;
+            self.initializations.push(local);
+        }
+    }
+
+    /// The types expected by a branch to the given label.
+    fn label_types(&self, label: u32) -> Result<Vec<ValueType>, ValidationErrorKind> {
+        let depth = label as usize;
+
+        if depth >= self.frames.len() {
+            return Err(ValidationErrorKind::UnknownLabel(label));
+        }
+
+        Ok(self.frames[self.frames.len() - 1 - depth]
+            .label_types()
+            .to_vec())
+    }
+
+    fn push_operand(&mut self, kind: ValueType) {
+        self.operands.push(Some(kind));
+    }
+
+    fn push_operands(&mut self, kinds: &[ValueType]) {
+        self.operands.extend(kinds.iter().copied().map(Some));
+    }
+
+    /// Pops an operand of any type, returning `None` if the type is unknown due to unreachable code.
+    fn pop_operand(&mut self) -> Result<Option<ValueType>, ValidationErrorKind> {
+        let frame = self
+            .frames
+            .last()
+            .expect("The control stack must not be empty.");
+
+        if self.operands.len() == frame.height {
+            return if frame.unreachable {
+                Ok(None)
+            } else {
+                Err(ValidationErrorKind::OperandStackUnderflow)
+            };
+        }
+
+        Ok(self.operands.pop().flatten())
+    }
+
+    fn pop_expected(
+        &mut self,
+        expected: ValueType,
+    ) -> Result<Option<ValueType>, ValidationErrorKind> {
+        match self.pop_operand()? {
+            Some(actual) if !self.context.matches(actual, expected) => {
+                Err(ValidationErrorKind::TypeMismatch { expected, actual })
+            }
+            actual => Ok(actual),
+        }
+    }
+
+    /// Pops an operand of any reference type, returning its heap type if known.
+    fn pop_reference(&mut self) -> Result<Option<HeapType>, ValidationErrorKind> {
+        match self.pop_operand()? {
+            Some(kind) => kind
+                .heap_type()
+                .map(Some)
+                .ok_or(ValidationErrorKind::ExpectedReference(kind)),
+            None => Ok(None),
+        }
+    }
+
+    /// Pops operands of the given types in reverse order, returning the popped operands in order.
+    fn pop_operands(
+        &mut self,
+        kinds: &[ValueType],
+    ) -> Result<Vec<Option<ValueType>>, ValidationErrorKind> {
+        let mut operands = Vec::with_capacity(kinds.len());
+
+        for kind in kinds.iter().rev() {
+            operands.push(self.pop_expected(*kind)?);
+        }
+
+        operands.reverse();
+
+        Ok(operands)
+    }
+
+    fn push_frame(&mut self, kind: FrameKind, parameters: Vec<ValueType>, results: Vec<ValueType>) {
+        self.frames.push(Frame {
+            kind,
+            height: self.operands.len(),
+            initializations: self.initializations.len(),
+            unreachable: false,
+            parameters: parameters.clone(),
+            results,
+        });
+        self.push_operands(&parameters);
+    }
+
+    /// Pops the current control frame, verifying the operand stack matches its result types.
+    fn pop_frame(&mut self) -> Result<Frame, ValidationErrorKind> {
+        let results = self
+            .frames
+            .last()
+            .expect("The control stack must not be empty.")
+            .results
+            .clone();
+
+        self.pop_operands(&results)?;
+
+        let frame = self
+            .frames
+            .pop()
+            .expect("The control stack must not be empty.");
+
+        if self.operands.len() != frame.height {
+            return Err(ValidationErrorKind::OperandStackMismatch {
+                expected: results.len(),
+                actual: results.len() + self.operands.len() - frame.height,
+            });
+        }
+
+        for local in self.initializations.drain(frame.initializations..) {
+            self.initialized[local as usize] = false;
+        }
+
+        Ok(frame)
+    }
+
+    /// Marks the remainder of the current block as unreachable, allowing operands of any type.
+    fn mark_unreachable(&mut self) {
+        let frame = self
+            .frames
+            .last_mut()
+            .expect("The control stack must not be empty.");
+
+        self.operands.truncate(frame.height);
+        frame.unreachable = true;
+    }
+}
+
+/// Validates that the instructions of the expression are constant and the expression evaluates
+/// to a single value of the given type.
+/// Only imported immutable globals may be read by constant expressions,
+/// which may also combine integers with addition, subtraction and multiplication.
+///
+/// See <https://webassembly.github.io/extended-const/core/valid/instructions.html#constant-expressions>
+pub fn validate_constant_expression(
+    expression: &Expression,
+    expected: ValueType,
+    context: &Context,
+) -> Result<(), ValidationErrorKind> {
+    for instruction in expression.instructions() {
+        if !instruction.is_constant() {
+            return Err(ValidationErrorKind::NonConstantInstruction);
+        }
+
+        if let Instruction::Variable(VariableInstruction::GlobalGet(global)) = instruction {
+            if *global >= context.imported_globals {
+                return Err(ValidationErrorKind::UnknownGlobal(*global));
+            }
+
+            if context.global(*global)?.mutability() != Mutability::Immutable {
+                return Err(ValidationErrorKind::NonConstantInstruction);
+            }
+        }
+    }
+
+    ExpressionValidator::new(context, &[], &[])
+        .validate(expression, &[expected])
+        .map_err(|(_, kind)| kind)
+}
+
+/// The type signature of a numeric instruction as its parameters and result.
+///
+/// See <https://webassembly.github.io/spec/core/valid/instructions.html#numeric-instructions>
+fn numeric_signature(instruction: &NumericInstruction) -> (Vec<ValueType>, ValueType) {
+    let unary = |kind: ValueType| (vec![kind], kind);
+    let binary = |kind: ValueType| (vec![kind, kind], kind);
+    let compare = |kind: ValueType| (vec![kind, kind], ValueType::I32);
+
+    match *instruction {
+        NumericInstruction::I32Constant(_) => (Vec::new(), ValueType::I32),
+        NumericInstruction::I64Constant(_) => (Vec::new(), ValueType::I64),
+        NumericInstruction::F32Constant(_) => (Vec::new(), ValueType::F32),
+        NumericInstruction::F64Constant(_) => (Vec::new(), ValueType::F64),
+        NumericInstruction::CountLeadingZeros(kind)
+        | NumericInstruction::CountTrailingZeros(kind)
+        | NumericInstruction::CountOnes(kind)
+        | NumericInstruction::ExtendSigned8(kind)
+        | NumericInstruction::ExtendSigned16(kind) => unary(kind.into()),
+        NumericInstruction::AbsoluteValue(kind)
+        | NumericInstruction::Negate(kind)
+        | NumericInstruction::SquareRoot(kind)
+        | NumericInstruction::Ceiling(kind)
+        | NumericInstruction::Floor(kind)
+        | NumericInstruction::Truncate(kind)
+        | NumericInstruction::Nearest(kind) => unary(kind.into()),
+        NumericInstruction::ExtendSigned32 => unary(ValueType::I64),
+        NumericInstruction::Add(kind)
+        | NumericInstruction::Subtract(kind)
+        | NumericInstruction::Multiply(kind) => binary(kind.into()),
+        NumericInstruction::DivideInteger(kind, _)
+        | NumericInstruction::Remainder(kind, _)
+        | NumericInstruction::ShiftRight(kind, _)
+        | NumericInstruction::And(kind)
+        | NumericInstruction::Or(kind)
+        | NumericInstruction::Xor(kind)
+        | NumericInstruction::ShiftLeft(kind)
+        | NumericInstruction::RotateLeft(kind)
+        | NumericInstruction::RotateRight(kind) => binary(kind.into()),
+        NumericInstruction::DivideFloat(kind)
+        | NumericInstruction::Minimum(kind)
+        | NumericInstruction::Maximum(kind)
+        | NumericInstruction::CopySign(kind) => binary(kind.into()),
+        NumericInstruction::EqualToZero(kind) => (vec![kind.into()], ValueType::I32),
+        NumericInstruction::Equal(kind) | NumericInstruction::NotEqual(kind) => {
+            compare(kind.into())
+        }
+        NumericInstruction::LessThanInteger(kind, _)
+        | NumericInstruction::GreaterThanInteger(kind, _)
+        | NumericInstruction::LessThanOrEqualToInteger(kind, _)
+        | NumericInstruction::GreaterThanOrEqualToInteger(kind, _) => compare(kind.into()),
+        NumericInstruction::LessThanFloat(kind)
+        | NumericInstruction::GreaterThanFloat(kind)
+        | NumericInstruction::LessThanOrEqualToFloat(kind)
+        | NumericInstruction::GreaterThanOrEqualToFloat(kind) => compare(kind.into()),
+        NumericInstruction::Wrap => (vec![ValueType::I64], ValueType::I32),
+        NumericInstruction::ExtendWithSignExtension(_) => (vec![ValueType::I32], ValueType::I64),
+        NumericInstruction::ConvertAndTruncate(integer, float, _)
+        | NumericInstruction::ConvertAndTruncateWithSaturation(integer, float, _) => {
+            (vec![float.into()], integer.into())
+        }
+        NumericInstruction::Demote => (vec![ValueType::F64], ValueType::F32),
+        NumericInstruction::Promote => (vec![ValueType::F32], ValueType::F64),
+        NumericInstruction::Convert(float, integer, _) => (vec![integer.into()], float.into()),
+        NumericInstruction::ReinterpretFloat(IntegerType::I32) => {
+            (vec![ValueType::F32], ValueType::I32)
+        }
+        NumericInstruction::ReinterpretFloat(IntegerType::I64) => {
+            (vec![ValueType::F64], ValueType::I64)
+        }
+        NumericInstruction::ReinterpretInteger(FloatType::F32) => {
+            (vec![ValueType::I32], ValueType::F32)
+        }
+        NumericInstruction::ReinterpretInteger(FloatType::F64) => {
+            (vec![ValueType::I64], ValueType::F64)
+        }
+    }
+}
+
+/// The type signature of a vector instruction as its parameters and results,
+/// where memory instructions take an address of the given type.
+///
+/// See <https://webassembly.github.io/spec/core/valid/instructions.html#vector-instructions>
+fn vector_signature(
+    instruction: &VectorInstruction,
+    address: ValueType,
+) -> (Vec<ValueType>, Vec<ValueType>) {
+    let vector = ValueType::V128;
+
+    match *instruction {
+        VectorInstruction::Constant(_) => (vec![], vec![vector]),
+        VectorInstruction::AnyTrue
+        | VectorInstruction::AllTrue(_)
+        | VectorInstruction::Bitmask(_) => (vec![vector], vec![ValueType::I32]),
+        VectorInstruction::BitSelect
+        | VectorInstruction::RelaxedMultiplyAdd(_)
+        | VectorInstruction::RelaxedNegativeMultiplyAdd(_)
+        | VectorInstruction::RelaxedLaneSelect(_)
+        | VectorInstruction::RelaxedDotProductAdd => (vec![vector, vector, vector], vec![vector]),
+        VectorInstruction::Splat(shape) => (vec![lane_type(shape)], vec![vector]),
+        VectorInstruction::ExtractLane(shape, ..) => (vec![vector], vec![lane_type(shape)]),
+        VectorInstruction::ReplaceLane(shape, _) => (vec![vector, lane_type(shape)], vec![vector]),
+        VectorInstruction::ShiftLeft(_) | VectorInstruction::ShiftRight(..) => {
+            (vec![vector, ValueType::I32], vec![vector])
+        }
+        VectorInstruction::Load(_)
+        | VectorInstruction::LoadExtend(..)
+        | VectorInstruction::LoadSplat(..)
+        | VectorInstruction::LoadZero(..) => (vec![address], vec![vector]),
+        VectorInstruction::Store(_) | VectorInstruction::StoreLane(..) => {
+            (vec![address, vector], vec![])
+        }
+        VectorInstruction::LoadLane(..) => (vec![address, vector], vec![vector]),
+        VectorInstruction::Not
+        | VectorInstruction::AbsoluteValue(_)
+        | VectorInstruction::Negate(_)
+        | VectorInstruction::CountOnes
+        | VectorInstruction::SquareRoot(_)
+        | VectorInstruction::Ceiling(_)
+        | VectorInstruction::Floor(_)
+        | VectorInstruction::Truncate(_)
+        | VectorInstruction::Nearest(_)
+        | VectorInstruction::Extend(..)
+        | VectorInstruction::ExtendedAddPairwise(..)
+        | VectorInstruction::ConvertAndTruncateWithSaturation(..)
+        | VectorInstruction::Convert(..)
+        | VectorInstruction::RelaxedTruncate(..)
+        | VectorInstruction::Demote
+        | VectorInstruction::Promote => (vec![vector], vec![vector]),
+        _ => (vec![vector, vector], vec![vector]),
+    }
+}
+
+/// The type of the lanes of a vector shape when extracted from a vector.
+fn lane_type(shape: VectorShape) -> ValueType {
+    match shape {
+        VectorShape::I8x16 | VectorShape::I16x8 | VectorShape::I32x4 => ValueType::I32,
+        VectorShape::I64x2 => ValueType::I64,
+        VectorShape::F32x4 => ValueType::F32,
+        VectorShape::F64x2 => ValueType::F64,
+    }
+}
+
+fn lane_count(shape: VectorShape) -> LaneIndex {
+    match shape {
+        VectorShape::I8x16 => 16,
+        VectorShape::I16x8 => 8,
+        VectorShape::I32x4 | VectorShape::F32x4 => 4,
+        VectorShape::I64x2 | VectorShape::F64x2 => 2,
+    }
+}
+
+/// The natural alignment of a lane of an integer vector shape as the exponent of a power of 2.
+fn lane_alignment(shape: IntegerVectorShape) -> u32 {
+    match shape {
+        IntegerVectorShape::I8x16 => 0,
+        IntegerVectorShape::I16x8 => 1,
+        IntegerVectorShape::I32x4 => 2,
+        IntegerVectorShape::I64x2 => 3,
+    }
+}
+
+/// Validates the lane index is smaller than the given number of lanes.
+fn validate_lane(lane: LaneIndex, lanes: LaneIndex) -> Result<(), ValidationErrorKind> {
+    if lane < lanes {
+        Ok(())
+    } else {
+        Err(ValidationErrorKind::InvalidLaneIndex { lane, lanes })
+    }
+}
+
+/// The natural alignment of a number type as the exponent of a power of 2.
+fn natural_alignment(kind: NumberType) -> u32 {
+    match kind {
+        NumberType::I32 | NumberType::F32 => 2,
+        NumberType::I64 | NumberType::F64 => 3,
+    }
+}
+
+/// The smaller of two index types, used for the length operand of a copy between
+/// memories or tables with different index types.
+///
+/// See <https://webassembly.github.io/memory64/core/valid/instructions.html#memory-instructions>
+fn minimum_index_type(first: IndexType, second: IndexType) -> IndexType {
+    match (first, second) {
+        (IndexType::I64, IndexType::I64) => IndexType::I64,
+        _ => IndexType::I32,
+    }
+}
+
+/// Validates the alignment of a memory argument does not exceed the given maximum.
+///
+/// See <https://webassembly.github.io/spec/core/valid/instructions.html#memory-instructions>
+fn validate_alignment(argument: &MemoryArgument, maximum: u32) -> Result<(), ValidationErrorKind> {
+    if argument.align() > maximum {
+        Err(ValidationErrorKind::InvalidAlignment {
+            align: argument.align(),
+            maximum,
+        })
+    } else {
+        Ok(())
+    }
+}
+
+/// Validates the two reference types are the same.
+/// Returns true if the field has a default value, i.e. it does not store non-nullable references.
+///
+/// See <https://webassembly.github.io/gc/core/valid/types.html#defaultable-types>
+fn is_defaultable(field: &FieldType) -> bool {
+    let kind = field.kind().unpacked();
+
+    !kind.is_reference() || kind.is_nullable()
+}
+
+/// Validates that a sign extension is given exactly when reading a packed field of the given type,
+/// returning the type of the value read.
+fn validate_extension(
+    index: TypeIndex,
+    field: FieldType,
+    extension: Option<SignExtension>,
+) -> Result<ValueType, ValidationErrorKind> {
+    match (field.kind(), extension) {
+        (StorageType::Packed(_), Some(_)) | (StorageType::Value(_), None) => {
+            Ok(field.kind().unpacked())
+        }
+        _ => Err(ValidationErrorKind::InvalidFieldExtension(index)),
+    }
+}
+
+/// Validates that a field of the given type is mutable, returning the type of the values it stores.
+fn validate_mutable(index: TypeIndex, field: FieldType) -> Result<ValueType, ValidationErrorKind> {
+    match field.mutability() {
+        Mutability::Mutable => Ok(field.kind().unpacked()),
+        Mutability::Immutable => Err(ValidationErrorKind::ImmutableField(index)),
+    }
+}
+
+/// Validates that a field stores numbers or vectors, as required to initialize it from a data segment.
+fn validate_numeric_field(field: FieldType) -> Result<(), ValidationErrorKind> {
+    let kind = field.kind().unpacked();
+
+    if kind.is_reference() {
+        Err(ValidationErrorKind::ExpectedNumeric(kind))
+    } else {
+        Ok(())
+    }
+}
+
+/// The type of a reference converted to the given abstract heap type, preserving its nullability.
+fn convert_reference(kind: Option<ValueType>, heap: HeapType) -> ValueType {
+    match kind {
+        Some(ValueType::Reference(_)) => ValueType::Reference(heap),
+        _ => ValueType::nullable_reference(heap),
+    }
+}
@@ -0,0 +1,1817 @@
+//! Validation of WebAssembly modules.
+//!
+//! See <https://webassembly.github.io/spec/core/valid/index.html>
+
+mod errors;
+mod instructions;
+mod module;
+mod types;
+
+pub use errors::{InstructionPath, ValidationError, ValidationErrorKind};
+
+use crate::model::{
+    ArrayType, CompositeType, ElementMode, ExportDescription, Expression, FieldIndex, FieldType,
+    FunctionIndex, FunctionType, GlobalType, HeapType, ImportDescription, Instruction, MemoryType,
+    Module, ReferenceInstruction, ReferenceType, StructType, SubType, TableType, TagIndex,
+    TypeIndex, ValueType,
+};
+use crate::validator::module::{
+    validate_data, validate_element, validate_exports, validate_function, validate_global,
+    validate_import, validate_memory, validate_start, validate_table, validate_tag,
+};
+use crate::validator::types::validate_sub_type;
+use std::collections::HashSet;
+
+/// Validates the given WebAssembly module against the rules of the specification.
+/// Validation type checks the body of every function, checks the indices referenced by every
+/// component of the module and requires initializers and offsets to be constant expressions.
+/// Returns the first error encountered.
+///
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#modules>
+///
+/// # Examples
+/// ## Valid
+/// ```rust
+/// use wasm_ast::{validate, Function, FunctionType, Module, NumericInstruction, NumberType, ResultType, ValueType};
+///
+/// let mut builder = Module::builder();
+/// let kind = builder
+///     .add_function_type(FunctionType::nullary(vec![ValueType::I32].into()))
+///     .unwrap();
+///
+/// builder
+///     .add_function(Function::new(
+///         kind,
+///         ResultType::empty(),
+///         vec![1i32.into(), 2i32.into(), NumericInstruction::Add(NumberType::I32).into()].into(),
+///     ))
+///     .unwrap();
+///
+/// assert!(validate(&builder.build()).is_ok());
+/// ```
+///
+/// ## Invalid
+/// ```rust
+/// use wasm_ast::{validate, Function, FunctionType, InstructionPath, Module, ResultType, ValidationError, ValidationErrorKind, ValueType};
+///
+/// let mut builder = Module::builder();
+/// let kind = builder
+///     .add_function_type(FunctionType::nullary(vec![ValueType::I32].into()))
+///     .unwrap();
+///
+/// builder
+///     .add_function(Function::new(kind, ResultType::empty(), vec![1i64.into()].into()))
+///     .unwrap();
+///
+/// assert_eq!(
+///     validate(&builder.build()),
+///     Err(ValidationError::Function {
+///         function: 0,
+///         path: InstructionPath::default(),
+///         kind: ValidationErrorKind::TypeMismatch {
+///             expected: ValueType::I32,
+///             actual: ValueType::I64,
+///         },
+///     })
+/// );
+/// ```
+pub fn validate(module: &Module) -> Result<(), ValidationError> {
+    let context = Context::new(module);
+
+    let mut index = 0;
+
+    for group in module.types().unwrap_or_default() {
+        let end = index + group.len() as u32;
+
+        for kind in group.types() {
+            validate_sub_type(index, end, kind, &context)
+                .map_err(|kind| ValidationError::Type(index, kind))?;
+            index += 1;
+        }
+    }
+
+    for (index, import) in module.imports().unwrap_or_default().iter().enumerate() {
+        validate_import(import, &context).map_err(|kind| ValidationError::Import(index, kind))?;
+    }
+
+    for (index, table) in module.tables().unwrap_or_default().iter().enumerate() {
+        let index = context.imported_tables + index as u32;
+
+        validate_table(table, &context).map_err(|kind| ValidationError::Table(index, kind))?;
+    }
+
+    for (index, memory) in module.memories().unwrap_or_default().iter().enumerate() {
+        let index = context.imported_memories + index as u32;
+
+        validate_memory(memory).map_err(|kind| ValidationError::Memory(index, kind))?;
+    }
+
+    for (index, tag) in module.tags().unwrap_or_default().iter().enumerate() {
+        let index = context.imported_tags + index as u32;
+
+        validate_tag(tag, &context).map_err(|kind| ValidationError::Tag(index, kind))?;
+    }
+
+    for (index, global) in module.globals().unwrap_or_default().iter().enumerate() {
+        let index = context.imported_globals + index as u32;
+
+        validate_global(global, &context).map_err(|kind| ValidationError::Global(index, kind))?;
+    }
+
+    for (index, function) in module.functions().unwrap_or_default().iter().enumerate() {
+        let index = context.imported_functions + index as u32;
+
+        validate_function(function, &context).map_err(|(path, kind)| {
+            ValidationError::Function {
+                function: index,
+                path,
+                kind,
+            }
+        })?;
+    }
+
+    for (index, element) in module.elements().unwrap_or_default().iter().enumerate() {
+        validate_element(element, &context)
+            .map_err(|kind| ValidationError::Element(index as u32, kind))?;
+    }
+
+    for (index, data) in module.data().unwrap_or_default().iter().enumerate() {
+        validate_data(data, &context).map_err(|kind| ValidationError::Data(index as u32, kind))?;
+    }
+
+    if let Some(start) = module.start() {
+        validate_start(start, &context).map_err(ValidationError::Start)?;
+    }
+
+    validate_exports(module.exports().unwrap_or_default(), &context)?;
+
+    if let Some(declared) = module.data_count() {
+        let actual = context.data;
+
+        if declared as usize != actual {
+            return Err(ValidationError::Module(
+                ValidationErrorKind::DataCountMismatch { declared, actual },
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// The validation context of a module.
+/// Holds the types of every definition in each index space, imports first.
+///
+/// See <https://webassembly.github.io/spec/core/valid/conventions.html#contexts>
+pub(crate) struct Context<'module> {
+    types: Vec<&'module SubType>,
+    canonical: Vec<TypeIndex>,
+    functions: Vec<TypeIndex>,
+    tables: Vec<TableType>,
+    memories: Vec<MemoryType>,
+    tags: Vec<TypeIndex>,
+    globals: Vec<GlobalType>,
+    elements: Vec<ReferenceType>,
+    data: usize,
+    data_count: Option<u32>,
+    references: HashSet<FunctionIndex>,
+    imported_functions: u32,
+    imported_tables: u32,
+    imported_memories: u32,
+    imported_tags: u32,
+    imported_globals: u32,
+}
+
+impl<'module> Context<'module> {
+    /// Creates the validation context for the given module.
+    pub fn new(module: &'module Module) -> Self {
+        let imports = module.imports().unwrap_or_default();
+        let mut functions = Vec::new();
+        let mut tables = Vec::new();
+        let mut memories = Vec::new();
+        let mut tags = Vec::new();
+        let mut globals = Vec::new();
+
+        for import in imports {
+            match import.description() {
+                ImportDescription::Function(kind) => functions.push(*kind),
+                ImportDescription::Table(kind) => tables.push(*kind),
+                ImportDescription::Memory(kind) => memories.push(*kind),
+                ImportDescription::Global(kind) => globals.push(*kind),
+                ImportDescription::Tag(kind) => tags.push(*kind),
+            }
+        }
+
+        let imported_functions = functions.len() as u32;
+        let imported_tables = tables.len() as u32;
+        let imported_memories = memories.len() as u32;
+        let imported_tags = tags.len() as u32;
+        let imported_globals = globals.len() as u32;
+
+        functions.extend(
+            module
+                .functions()
+                .unwrap_or_default()
+                .iter()
+                .map(|f| f.kind()),
+        );
+        tables.extend(
+            module
+                .tables()
+                .unwrap_or_default()
+                .iter()
+                .map(|t| *t.kind()),
+        );
+        memories.extend(
+            module
+                .memories()
+                .unwrap_or_default()
+                .iter()
+                .map(|m| *m.kind()),
+        );
+        tags.extend(module.tags().unwrap_or_default().iter().map(|t| t.kind()));
+        globals.extend(
+            module
+                .globals()
+                .unwrap_or_default()
+                .iter()
+                .map(|g| *g.kind()),
+        );
+
+        Context {
+            types: module.sub_types().collect(),
+            canonical: module.canonical_types(),
+            functions,
+            tables,
+            memories,
+            tags,
+            globals,
+            elements: module
+                .elements()
+                .unwrap_or_default()
+                .iter()
+                .map(|e| e.kind())
+                .collect(),
+            data: module.data().unwrap_or_default().len(),
+            data_count: module.data_count(),
+            references: declared_references(module),
+            imported_functions,
+            imported_tables,
+            imported_memories,
+            imported_tags,
+            imported_globals,
+        }
+    }
+
+    /// The sub type at the given index.
+    pub fn sub_type(&self, index: TypeIndex) -> Result<&'module SubType, ValidationErrorKind> {
+        self.types
+            .get(index as usize)
+            .copied()
+            .ok_or(ValidationErrorKind::UnknownType(index))
+    }
+
+    /// The function type at the given index.
+    pub fn function_type(
+        &self,
+        index: TypeIndex,
+    ) -> Result<&'module FunctionType, ValidationErrorKind> {
+        self.sub_type(index)?
+            .function_type()
+            .ok_or(ValidationErrorKind::ExpectedFunctionType(index))
+    }
+
+    /// The structure type at the given index.
+    pub fn struct_type(
+        &self,
+        index: TypeIndex,
+    ) -> Result<&'module StructType, ValidationErrorKind> {
+        match self.sub_type(index)?.kind() {
+            CompositeType::Struct(kind) => Ok(kind),
+            _ => Err(ValidationErrorKind::ExpectedStructType(index)),
+        }
+    }
+
+    /// The array type at the given index.
+    pub fn array_type(&self, index: TypeIndex) -> Result<ArrayType, ValidationErrorKind> {
+        match self.sub_type(index)?.kind() {
+            CompositeType::Array(kind) => Ok(*kind),
+            _ => Err(ValidationErrorKind::ExpectedArrayType(index)),
+        }
+    }
+
+    /// The field type of the given field of the structure type at the given index.
+    pub fn field(
+        &self,
+        index: TypeIndex,
+        field: FieldIndex,
+    ) -> Result<FieldType, ValidationErrorKind> {
+        self.struct_type(index)?
+            .fields()
+            .get(field as usize)
+            .copied()
+            .ok_or(ValidationErrorKind::UnknownField { kind: index, field })
+    }
+
+    /// The top type of the hierarchy the given heap type belongs to.
+    pub fn top(&self, heap: HeapType) -> Result<HeapType, ValidationErrorKind> {
+        match heap {
+            HeapType::Index(index) => Ok(self.sub_type(index)?.kind().heap_type()),
+            heap => Ok(heap),
+        }
+        .map(|heap| heap.top().unwrap_or(heap))
+    }
+
+    /// Returns true if the value type `actual` is a subtype of `expected`.
+    /// Unlike `ValueType::matches`, concrete heap types are resolved against the types of the module.
+    /// Concrete heap types match when they are equivalent or through declared supertypes.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/matching.html#value-types>
+    pub fn matches(&self, actual: ValueType, expected: ValueType) -> bool {
+        match (actual.heap_type(), expected.heap_type()) {
+            (Some(heap), Some(expected_heap)) => {
+                (!actual.is_nullable() || expected.is_nullable())
+                    && self.heap_matches(heap, expected_heap)
+            }
+            _ => actual == expected,
+        }
+    }
+
+    /// Returns true if the heap type `actual` is a subtype of `expected`.
+    ///
+    /// See <https://webassembly.github.io/gc/core/valid/matching.html#heap-types>
+    pub fn heap_matches(&self, actual: HeapType, expected: HeapType) -> bool {
+        match (actual, expected) {
+            (HeapType::Index(actual), HeapType::Index(expected)) => {
+                self.declares_supertype(actual, expected)
+            }
+            (HeapType::Index(actual), expected) => matches!(
+                self.types.get(actual as usize),
+                Some(kind) if kind.kind().heap_type().matches(expected)
+            ),
+            (actual, HeapType::Index(expected)) => matches!(
+                self.types.get(expected as usize),
+                Some(kind) if kind.kind().heap_type().bottom() == Some(actual)
+            ),
+            (actual, expected) => actual.matches(expected),
+        }
+    }
+
+    /// Returns true if the value types are subtypes of each other,
+    /// i.e. they are the same type once their concrete heap types are canonicalized.
+    pub fn equivalent(&self, first: ValueType, second: ValueType) -> bool {
+        self.matches(first, second) && self.matches(second, first)
+    }
+
+    /// The index of the first type that is equivalent to the type at the given index.
+    fn canonical(&self, index: TypeIndex) -> TypeIndex {
+        self.canonical.get(index as usize).copied().unwrap_or(index)
+    }
+
+    /// Returns true if a type equivalent to `expected` is reachable from `actual` through declared supertypes.
+    fn declares_supertype(&self, mut actual: TypeIndex, expected: TypeIndex) -> bool {
+        let expected = self.canonical(expected);
+
+        loop {
+            if self.canonical(actual) == expected {
+                return true;
+            }
+
+            match self
+                .types
+                .get(actual as usize)
+                .and_then(|kind| kind.supertypes().first())
+            {
+                Some(&supertype) if supertype < actual => actual = supertype,
+                _ => return false,
+            }
+        }
+    }
+
+    /// The function type of the function at the given index.
+    pub fn function(&self, index: FunctionIndex) -> Result<&FunctionType, ValidationErrorKind> {
+        self.function_type(self.function_type_index(index)?)
+    }
+
+    /// The index of the function type of the function at the given index.
+    pub fn function_type_index(
+        &self,
+        index: FunctionIndex,
+    ) -> Result<TypeIndex, ValidationErrorKind> {
+        self.functions
+            .get(index as usize)
+            .copied()
+            .ok_or(ValidationErrorKind::UnknownFunction(index))
+    }
+
+    /// The table type of the table at the given index.
+    pub fn table(&self, index: u32) -> Result<&TableType, ValidationErrorKind> {
+        self.tables
+            .get(index as usize)
+            .ok_or(ValidationErrorKind::UnknownTable(index))
+    }
+
+    /// The memory type of the memory at the given index.
+    pub fn memory(&self, index: u32) -> Result<&MemoryType, ValidationErrorKind> {
+        self.memories
+            .get(index as usize)
+            .ok_or(ValidationErrorKind::UnknownMemory(index))
+    }
+
+    /// The function type of the tag at the given index.
+    pub fn tag(&self, index: TagIndex) -> Result<&FunctionType, ValidationErrorKind> {
+        let kind = self
+            .tags
+            .get(index as usize)
+            .ok_or(ValidationErrorKind::UnknownTag(index))?;
+
+        self.function_type(*kind)
+    }
+
+    /// The global type of the global at the given index.
+    pub fn global(&self, index: u32) -> Result<&GlobalType, ValidationErrorKind> {
+        self.globals
+            .get(index as usize)
+            .ok_or(ValidationErrorKind::UnknownGlobal(index))
+    }
+
+    /// The reference type of the element segment at the given index.
+    pub fn element(&self, index: u32) -> Result<ReferenceType, ValidationErrorKind> {
+        self.elements
+            .get(index as usize)
+            .copied()
+            .ok_or(ValidationErrorKind::UnknownElement(index))
+    }
+
+    /// Verifies a data segment exists at the given index.
+    pub fn data(&self, index: u32) -> Result<(), ValidationErrorKind> {
+        if (index as usize) < self.data {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::UnknownData(index))
+        }
+    }
+
+    /// Verifies the module declares a data count section.
+    pub fn data_count(&self) -> Result<(), ValidationErrorKind> {
+        self.data_count
+            .map(|_| ())
+            .ok_or(ValidationErrorKind::MissingDataCount)
+    }
+
+    /// Verifies the given function is declared outside of function bodies, allowing code to reference it.
+    pub fn reference(&self, index: FunctionIndex) -> Result<(), ValidationErrorKind> {
+        self.function(index)?;
+
+        if self.references.contains(&index) {
+            Ok(())
+        } else {
+            Err(ValidationErrorKind::UndeclaredFunctionReference(index))
+        }
+    }
+}
+
+/// Collects the function indices referenced by the module outside of function bodies and the start function.
+///
+/// See <https://webassembly.github.io/spec/core/valid/conventions.html#contexts>
+fn declared_references(module: &Module) -> HashSet<FunctionIndex> {
+    let mut references = HashSet::new();
+    let mut collect = |expression: &Expression| {
+        for instruction in expression.instructions() {
+            if let Instruction::Reference(ReferenceInstruction::Function(index)) = instruction {
+                references.insert(*index);
+            }
+        }
+    };
+
+    for table in module.tables().unwrap_or_default() {
+        table.initializer().into_iter().for_each(&mut collect);
+    }
+
+    for global in module.globals().unwrap_or_default() {
+        collect(global.initializer());
+    }
+
+    for element in module.elements().unwrap_or_default() {
+        if let ElementMode::Active(_, offset) = element.mode() {
+            collect(offset);
+        }
+
+        element.initializers().iter().for_each(&mut collect);
+    }
+
+    for export in module.exports().unwrap_or_default() {
+        if let ExportDescription::Function(index) = export.description() {
+            references.insert(*index);
+        }
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{
+        AtomicInstruction, AtomicOperation, BlockType, Catch, ConstantExpression,
+        ControlInstruction, Data, Element, ElementInitializer, Export, Function, GcInstruction,
+        Global, GlobalIndex, Import, IndexType, IntegerType, IntegerVectorShape, Limit, Memory,
+        MemoryArgument, MemoryInstruction, MemoryType, NumberType, NumericInstruction, PackedType,
+        RecursiveType, ResultType, SignExtension, Start, StructType, Table, Tag, Value, ValueType,
+        VariableInstruction, VectorInstruction, VectorShape,
+    };
+
+    fn builder_with_type(kind: FunctionType) -> crate::model::ModuleBuilder {
+        let mut builder = Module::builder();
+        builder.add_function_type(kind).unwrap();
+        builder
+    }
+
+    fn function_error(module: &Module) -> (FunctionIndex, Vec<usize>, ValidationErrorKind) {
+        match validate(module) {
+            Err(ValidationError::Function {
+                function,
+                path,
+                kind,
+            }) => (function, path.segments().to_vec(), kind),
+            result => panic!("Expected a function error, but got {:?}.", result),
+        }
+    }
+
+    #[test]
+    fn valid_empty_module() {
+        assert_eq!(validate(&Module::empty()), Ok(()));
+    }
+
+    #[test]
+    fn valid_nested_blocks() {
+        let mut builder = builder_with_type(FunctionType::new(
+            vec![ValueType::I32].into(),
+            vec![ValueType::I32].into(),
+        ));
+        let body = vec![
+            ControlInstruction::Block(
+                BlockType::ValueType(ValueType::I32),
+                vec![
+                    VariableInstruction::LocalGet(0).into(),
+                    ControlInstruction::If(
+                        BlockType::ValueType(ValueType::I32),
+                        vec![1i32.into()].into(),
+                        Some(vec![2i32.into(), ControlInstruction::Branch(1).into()].into()),
+                    )
+                    .into(),
+                ]
+                .into(),
+            )
+            .into(),
+            ControlInstruction::Loop(
+                BlockType::None,
+                vec![ControlInstruction::Branch(0).into()].into(),
+            )
+            .into(),
+        ];
+
+        builder
+            .add_function(Function::new(0, ResultType::empty(), body.into()))
+            .unwrap();
+
+        assert_eq!(validate(&builder.build()), Ok(()));
+    }
+
+    #[test]
+    fn invalid_branch_depth_points_at_instruction() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        let body = vec![
+            ControlInstruction::Nop.into(),
+            ControlInstruction::Block(
+                BlockType::None,
+                vec![
+                    ControlInstruction::Nop.into(),
+                    ControlInstruction::Branch(2).into(),
+                ]
+                .into(),
+            )
+            .into(),
+        ];
+
+        builder
+            .add_function(Function::new(0, ResultType::empty(), body.into()))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (0, vec![1, 1], ValidationErrorKind::UnknownLabel(2))
+        );
+    }
+
+    #[test]
+    fn invalid_else_branch_path() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        let body = vec![
+            1i32.into(),
+            ControlInstruction::If(
+                BlockType::None,
+                vec![ControlInstruction::Nop.into()].into(),
+                Some(vec![ControlInstruction::Call(7).into()].into()),
+            )
+            .into(),
+        ];
+
+        builder
+            .add_function(Function::new(0, ResultType::empty(), body.into()))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (0, vec![1, 1], ValidationErrorKind::UnknownFunction(7))
+        );
+    }
+
+    #[test]
+    fn invalid_function_index_includes_imports() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_import(Import::function("env".into(), "f".into(), 0))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![ParametricDrop::drop()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (1, vec![0], ValidationErrorKind::OperandStackUnderflow)
+        );
+    }
+
+    #[test]
+    fn invalid_unknown_type() {
+        let mut builder = Module::builder();
+        builder
+            .add_function(Function::new(3, ResultType::empty(), Expression::empty()))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (0, vec![], ValidationErrorKind::UnknownType(3))
+        );
+    }
+
+    #[test]
+    fn invalid_unconsumed_values() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![1i32.into()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (
+                0,
+                vec![],
+                ValidationErrorKind::OperandStackMismatch {
+                    expected: 0,
+                    actual: 1
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn valid_unreachable_code() {
+        let mut builder = builder_with_type(FunctionType::nullary(vec![ValueType::I32].into()));
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    ControlInstruction::Unreachable.into(),
+                    NumericInstruction::Add(NumberType::I32).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        assert_eq!(validate(&builder.build()), Ok(()));
+    }
+
+    #[test]
+    fn invalid_alignment() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    0i32.into(),
+                    MemoryInstruction::Load(NumberType::I32, MemoryArgument::default_offset(3))
+                        .into(),
+                    ParametricDrop::drop(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::InvalidAlignment {
+                    align: 3,
+                    maximum: 2
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn multiple_memories() {
+        let module = |memories: usize| {
+            let mut builder = builder_with_type(FunctionType::runnable());
+
+            for _ in 0..memories {
+                builder
+                    .add_memory(Memory::from(Limit::unbounded(1)))
+                    .unwrap();
+            }
+
+            builder
+                .add_function(Function::new(
+                    0,
+                    ResultType::empty(),
+                    vec![
+                        0i32.into(),
+                        0i32.into(),
+                        0i32.into(),
+                        MemoryInstruction::Copy(0, 1).into(),
+                    ]
+                    .into(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(validate(&module(2)), Ok(()));
+        assert_eq!(
+            function_error(&module(1)),
+            (0, vec![3], ValidationErrorKind::UnknownMemory(1))
+        );
+    }
+
+    #[test]
+    fn invalid_undeclared_function_reference() {
+        let module = |exported: bool| {
+            let mut builder = builder_with_type(FunctionType::runnable());
+            builder
+                .add_function(Function::new(
+                    0,
+                    ResultType::empty(),
+                    vec![
+                        ReferenceInstruction::Function(0).into(),
+                        ParametricDrop::drop(),
+                    ]
+                    .into(),
+                ))
+                .unwrap();
+
+            if exported {
+                builder.add_export(Export::function("f".into(), 0));
+            }
+
+            builder.build()
+        };
+
+        assert_eq!(
+            function_error(&module(false)),
+            (
+                0,
+                vec![0],
+                ValidationErrorKind::UndeclaredFunctionReference(0)
+            )
+        );
+        assert_eq!(validate(&module(true)), Ok(()));
+    }
+
+    #[test]
+    fn invalid_global_initializer() {
+        let mut builder = Module::builder();
+        builder
+            .add_global(Global::immutable(
+                ValueType::F32,
+                vec![
+                    1f32.into(),
+                    2f32.into(),
+                    NumericInstruction::Add(NumberType::F32).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Global(
+                0,
+                ValidationErrorKind::NonConstantInstruction
+            ))
+        );
+    }
+
+    #[test]
+    fn extended_constant_expressions() {
+        let offset = |global: GlobalIndex| -> Expression {
+            vec![
+                VariableInstruction::GlobalGet(global).into(),
+                4i32.into(),
+                NumericInstruction::Multiply(NumberType::I32).into(),
+                8i32.into(),
+                NumericInstruction::Subtract(NumberType::I32).into(),
+            ]
+            .into()
+        };
+        let module = |global: GlobalIndex| {
+            let mut builder = Module::builder();
+            builder
+                .add_import(Import::global(
+                    "env".into(),
+                    "base".into(),
+                    GlobalType::immutable(ValueType::I32),
+                ))
+                .unwrap();
+            builder
+                .add_global(Global::immutable(ValueType::I32, offset(0)))
+                .unwrap();
+            builder
+                .add_memory(Memory::from(Limit::unbounded(1)))
+                .unwrap();
+            builder
+                .add_data(Data::active(0, offset(global), vec![1, 2, 3]))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(validate(&module(0)), Ok(()));
+        assert_eq!(
+            validate(&module(1)),
+            Err(ValidationError::Data(
+                0,
+                ValidationErrorKind::UnknownGlobal(1)
+            ))
+        );
+        assert_eq!(
+            ConstantExpression::new(offset(0))
+                .unwrap()
+                .evaluate(&[Value::I32(3)])
+                .unwrap(),
+            Value::I32(4)
+        );
+    }
+
+    #[test]
+    fn invalid_global_initializer_type() {
+        let mut builder = Module::builder();
+        builder
+            .add_global(Global::immutable(ValueType::I32, vec![1i64.into()].into()))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Global(
+                0,
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::I32,
+                    actual: ValueType::I64
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_element_table() {
+        let mut builder = Module::builder();
+        builder
+            .add_table(Table::new(TableType::new(
+                ReferenceType::External,
+                Limit::unbounded(1),
+            )))
+            .unwrap();
+        builder
+            .add_element(Element::active(
+                1,
+                vec![0i32.into()].into(),
+                ReferenceType::Function,
+                Vec::<Expression>::new().to_initializers(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Element(
+                0,
+                ValidationErrorKind::UnknownTable(1)
+            ))
+        );
+    }
+
+    #[test]
+    fn table_initializers() {
+        let kind = TableType::new(
+            ReferenceType::Reference(HeapType::Index(0)),
+            Limit::unbounded(1),
+        );
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_function(Function::new(0, ResultType::empty(), Expression::empty()))
+            .unwrap();
+        builder
+            .add_table(Table::with_initializer(
+                kind,
+                vec![ReferenceInstruction::Function(0).into()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(validate(&builder.build()), Ok(()));
+
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_table(Table::with_initializer(
+                kind,
+                vec![ReferenceInstruction::Null(HeapType::Index(0)).into()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Table(
+                0,
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::Reference(HeapType::Index(0)),
+                    actual: ValueType::NullableReference(HeapType::Index(0))
+                }
+            ))
+        );
+
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_table(Table::with_initializer(kind, vec![0i32.into()].into()))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Table(
+                0,
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::Reference(HeapType::Index(0)),
+                    actual: ValueType::I32
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_typed_tables() {
+        let table = |kind: ReferenceType| Table::new(TableType::new(kind, Limit::unbounded(1)));
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_table(table(ReferenceType::Reference(HeapType::Index(0))))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Table(
+                0,
+                ValidationErrorKind::NonDefaultableTable
+            ))
+        );
+
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_table(table(ReferenceType::NullableReference(HeapType::Index(1))))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Table(
+                0,
+                ValidationErrorKind::UnknownType(1)
+            ))
+        );
+
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_table(table(ReferenceType::NullableReference(HeapType::Index(0))))
+            .unwrap();
+        builder
+            .add_element(Element::active(
+                0,
+                vec![0i32.into()].into(),
+                ReferenceType::Function,
+                Vec::<Expression>::new().to_initializers(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Element(
+                0,
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::NullableReference(HeapType::Index(0)),
+                    actual: ValueType::FunctionReference
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_data_memory() {
+        let mut builder = Module::builder();
+        builder
+            .add_data(Data::active(0, vec![0i32.into()].into(), vec![1]))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Data(
+                0,
+                ValidationErrorKind::UnknownMemory(0)
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_start_signature() {
+        let mut builder = builder_with_type(FunctionType::nullary(vec![ValueType::I32].into()));
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![0i32.into()].into(),
+            ))
+            .unwrap();
+        builder.set_start(Some(Start::new(0)));
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Start(
+                ValidationErrorKind::InvalidStartFunction
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_duplicate_export() {
+        let mut builder = Module::builder();
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder.add_export(Export::memory("memory".into(), 0));
+        builder.add_export(Export::memory("memory".into(), 0));
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Export(
+                1,
+                ValidationErrorKind::DuplicateExportName("memory".into())
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_memory_limits() {
+        let mut builder = Module::builder();
+        builder
+            .add_memory(Memory::from(Limit::bounded(2, 1)))
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Memory(
+                0,
+                ValidationErrorKind::InvalidLimits { min: 2, max: 1 }
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_unbounded_shared_memory() {
+        let mut builder = Module::builder();
+        builder
+            .add_memory(MemoryType::shared(Limit::unbounded(1)).into())
+            .unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Memory(
+                0,
+                ValidationErrorKind::UnboundedSharedMemory
+            ))
+        );
+    }
+
+    #[test]
+    fn memory64() {
+        let module = |index_type: IndexType, body: Vec<Instruction>| {
+            let mut builder = builder_with_type(FunctionType::runnable());
+            builder
+                .add_memory(
+                    MemoryType::with_index_type(index_type, Limit::unbounded(1), false).into(),
+                )
+                .unwrap();
+            builder
+                .add_function(Function::new(0, ResultType::empty(), body.into()))
+                .unwrap();
+            builder.build()
+        };
+        let load = |address: Instruction, offset: u64| {
+            vec![
+                address,
+                MemoryInstruction::Load(NumberType::I32, MemoryArgument::new(2, offset)).into(),
+                ParametricDrop::drop(),
+            ]
+        };
+
+        assert_eq!(
+            validate(&module(IndexType::I64, load(0i64.into(), 1 << 32))),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&module(
+                IndexType::I64,
+                vec![
+                    MemoryInstruction::Size(0).into(),
+                    MemoryInstruction::Grow(0).into(),
+                    0i32.into(),
+                    0i64.into(),
+                    MemoryInstruction::Fill(0).into(),
+                ]
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            function_error(&module(IndexType::I64, load(0i32.into(), 0))),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::I64,
+                    actual: ValueType::I32
+                }
+            )
+        );
+        assert_eq!(
+            function_error(&module(IndexType::I32, load(0i32.into(), 1 << 32))),
+            (0, vec![1], ValidationErrorKind::InvalidOffset(1 << 32))
+        );
+    }
+
+    #[test]
+    fn atomic_instructions() {
+        let module = |align: u32| {
+            let mut builder = builder_with_type(FunctionType::nullary(vec![ValueType::I64].into()));
+            builder
+                .add_memory(MemoryType::shared(Limit::bounded(1, 1)).into())
+                .unwrap();
+            builder
+                .add_function(Function::new(
+                    0,
+                    ResultType::empty(),
+                    vec![
+                        0i32.into(),
+                        0i64.into(),
+                        1i64.into(),
+                        AtomicInstruction::ReadModifyWrite(
+                            AtomicOperation::CompareExchange,
+                            IntegerType::I64,
+                            MemoryArgument::default_offset(align),
+                        )
+                        .into(),
+                        AtomicInstruction::Fence.into(),
+                    ]
+                    .into(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(validate(&module(3)), Ok(()));
+        assert_eq!(
+            function_error(&module(2)),
+            (
+                0,
+                vec![3],
+                ValidationErrorKind::InvalidAtomicAlignment {
+                    align: 2,
+                    natural: 3
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_tag_type() {
+        let mut builder = builder_with_type(FunctionType::nullary(vec![ValueType::I32].into()));
+        builder.add_tag(Tag::new(0)).unwrap();
+
+        assert_eq!(
+            validate(&builder.build()),
+            Err(ValidationError::Tag(0, ValidationErrorKind::InvalidTagType))
+        );
+    }
+
+    #[test]
+    fn exception_handling() {
+        let module = |label: u32| {
+            let mut builder =
+                builder_with_type(FunctionType::side_effect(vec![ValueType::I32].into()));
+            builder.add_function_type(FunctionType::runnable()).unwrap();
+            builder.add_tag(Tag::new(0)).unwrap();
+            builder
+                .add_function(Function::new(
+                    1,
+                    ResultType::empty(),
+                    vec![
+                        ControlInstruction::Block(
+                            BlockType::ValueType(ValueType::I32),
+                            vec![
+                                ControlInstruction::Block(
+                                    BlockType::None,
+                                    vec![ControlInstruction::TryTable(
+                                        BlockType::None,
+                                        vec![Catch::Catch(0, label)],
+                                        vec![0i32.into(), ControlInstruction::Throw(0).into()]
+                                            .into(),
+                                    )
+                                    .into()]
+                                    .into(),
+                                )
+                                .into(),
+                                0i32.into(),
+                            ]
+                            .into(),
+                        )
+                        .into(),
+                        crate::model::ParametricInstruction::Drop.into(),
+                    ]
+                    .into(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(validate(&module(1)), Ok(()));
+        assert_eq!(
+            function_error(&module(0)),
+            (
+                0,
+                vec![0, 0, 0],
+                ValidationErrorKind::CatchArityMismatch {
+                    expected: 0,
+                    actual: 1
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_tail_call_results() {
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_function_type(FunctionType::nullary(vec![ValueType::I32].into()))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![ControlInstruction::ReturnCall(1).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![ControlInstruction::ReturnCall(1).into()].into(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (
+                0,
+                vec![0],
+                ValidationErrorKind::TailCallMismatch {
+                    expected: vec![],
+                    actual: vec![ValueType::I32]
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_typed_function_references() {
+        let module_with_body = |local: ValueType, body: Vec<Instruction>| {
+            let mut builder = builder_with_type(FunctionType::runnable());
+            builder
+                .add_function(Function::new(0, vec![local].into(), body.into()))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(
+            function_error(&module_with_body(
+                ValueType::FunctionReference,
+                vec![
+                    VariableInstruction::LocalGet(0).into(),
+                    ControlInstruction::CallReference(0).into(),
+                ]
+            )),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::NullableReference(HeapType::Index(0)),
+                    actual: ValueType::FunctionReference
+                }
+            )
+        );
+        assert_eq!(
+            function_error(&module_with_body(
+                ValueType::NullableReference(HeapType::Index(0)),
+                vec![ControlInstruction::Block(
+                    BlockType::None,
+                    vec![
+                        VariableInstruction::LocalGet(0).into(),
+                        ControlInstruction::BranchOnNonNull(0).into(),
+                    ]
+                    .into(),
+                )
+                .into()]
+            )),
+            (
+                0,
+                vec![0, 1],
+                ValidationErrorKind::InvalidBranchOnNonNull(0)
+            )
+        );
+    }
+
+    #[test]
+    fn function_references_have_concrete_types() {
+        let unary = FunctionType::new(
+            ResultType::empty(),
+            vec![ValueType::Reference(HeapType::Index(0))].into(),
+        );
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder.add_function_type(unary).unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    ReferenceInstruction::Function(0).into(),
+                    ControlInstruction::CallReference(0).into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![ReferenceInstruction::Function(0).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::declarative(
+                ReferenceType::Function,
+                vec![0].to_initializers(),
+            ))
+            .unwrap();
+
+        assert_eq!(validate(&builder.build()), Ok(()));
+
+        let mut builder = builder_with_type(FunctionType::runnable());
+        builder
+            .add_function_type(FunctionType::new(
+                ResultType::empty(),
+                vec![ValueType::Reference(HeapType::Index(0))].into(),
+            ))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                1,
+                ResultType::empty(),
+                vec![ReferenceInstruction::Function(0).into()].into(),
+            ))
+            .unwrap();
+        builder
+            .add_element(Element::declarative(
+                ReferenceType::Function,
+                vec![0].to_initializers(),
+            ))
+            .unwrap();
+
+        assert_eq!(
+            function_error(&builder.build()),
+            (
+                0,
+                vec![],
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::Reference(HeapType::Index(0)),
+                    actual: ValueType::Reference(HeapType::Index(1))
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn non_defaultable_locals_must_be_initialized() {
+        let module = |body: Vec<Instruction>| {
+            let mut builder = builder_with_type(FunctionType::runnable());
+            builder
+                .add_function_type(FunctionType::new(
+                    vec![ValueType::Reference(HeapType::Index(0))].into(),
+                    ResultType::empty(),
+                ))
+                .unwrap();
+            builder
+                .add_function(Function::new(
+                    1,
+                    vec![ValueType::Reference(HeapType::Index(1))].into(),
+                    body.into(),
+                ))
+                .unwrap();
+            builder
+                .add_element(Element::declarative(
+                    ReferenceType::Function,
+                    vec![0].to_initializers(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+        let set = || -> Vec<Instruction> {
+            vec![
+                ReferenceInstruction::Function(0).into(),
+                VariableInstruction::LocalSet(1).into(),
+            ]
+        };
+        let get = || -> Instruction {
+            ControlInstruction::Block(
+                BlockType::None,
+                vec![
+                    VariableInstruction::LocalGet(1).into(),
+                    ParametricDrop::drop(),
+                ]
+                .into(),
+            )
+            .into()
+        };
+
+        let mut body = vec![
+            VariableInstruction::LocalGet(0).into(),
+            ParametricDrop::drop(),
+        ];
+        body.extend(set());
+        body.push(get());
+        assert_eq!(validate(&module(body)), Ok(()));
+
+        assert_eq!(
+            function_error(&module(vec![get()])),
+            (0, vec![0, 0], ValidationErrorKind::UninitializedLocal(1))
+        );
+        assert_eq!(
+            function_error(&module(vec![
+                ControlInstruction::Block(BlockType::None, set().into()).into(),
+                get(),
+            ])),
+            (0, vec![1, 0], ValidationErrorKind::UninitializedLocal(1))
+        );
+        assert_eq!(
+            function_error(&module(vec![
+                1i32.into(),
+                ControlInstruction::If(BlockType::None, set().into(), Some(vec![get()].into()))
+                    .into(),
+            ])),
+            (0, vec![1, 2, 0], ValidationErrorKind::UninitializedLocal(1))
+        );
+    }
+
+    #[test]
+    fn invalid_sub_types() {
+        let fields = |kind: ValueType| StructType::new(vec![FieldType::mutable(kind)]);
+        let module = |types: Vec<RecursiveType>| {
+            let mut builder = Module::builder();
+            builder.set_types(Some(types));
+            builder.build()
+        };
+
+        assert_eq!(
+            validate(&module(vec![
+                fields(ValueType::I32).into(),
+                SubType::new(true, vec![0], fields(ValueType::I32).into()).into(),
+            ])),
+            Err(ValidationError::Type(
+                1,
+                ValidationErrorKind::InvalidSupertype(0)
+            ))
+        );
+        assert_eq!(
+            validate(&module(vec![
+                SubType::new(false, vec![], fields(ValueType::I32).into()).into(),
+                SubType::new(true, vec![0], fields(ValueType::I64).into()).into(),
+            ])),
+            Err(ValidationError::Type(
+                1,
+                ValidationErrorKind::InvalidSupertype(0)
+            ))
+        );
+        assert_eq!(
+            validate(&module(vec![RecursiveType::new(vec![
+                SubType::new(false, vec![1], fields(ValueType::I32).into()),
+                SubType::new(false, vec![], fields(ValueType::I32).into()),
+            ])])),
+            Err(ValidationError::Type(
+                0,
+                ValidationErrorKind::UnknownType(1)
+            ))
+        );
+        assert_eq!(
+            validate(&module(vec![fields(ValueType::NullableReference(
+                HeapType::Index(1)
+            ))
+            .into()])),
+            Err(ValidationError::Type(
+                0,
+                ValidationErrorKind::UnknownType(1)
+            ))
+        );
+
+        let forward = fields(ValueType::NullableReference(HeapType::Index(1)));
+
+        assert_eq!(
+            validate(&module(vec![
+                forward.clone().into(),
+                StructType::new(vec![]).into(),
+            ])),
+            Err(ValidationError::Type(
+                0,
+                ValidationErrorKind::UnknownType(1)
+            ))
+        );
+        assert_eq!(
+            validate(&module(vec![RecursiveType::new(vec![
+                forward.into(),
+                StructType::new(vec![]).into(),
+            ])])),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn equivalent_recursive_types() {
+        let reference = |index| ValueType::Reference(HeapType::Index(index));
+        let group = |start| {
+            RecursiveType::new(vec![
+                FunctionType::new(vec![reference(start + 1)].into(), ResultType::empty()).into(),
+                FunctionType::new(vec![reference(start)].into(), ResultType::empty()).into(),
+            ])
+        };
+        let module = |result| {
+            let field =
+                |index| FieldType::mutable(ValueType::NullableReference(HeapType::Index(index)));
+            let mut builder = Module::builder();
+            builder.set_types(Some(vec![
+                group(0),
+                group(2),
+                FunctionType::new(vec![reference(0)].into(), vec![reference(result)].into()).into(),
+                SubType::new(false, vec![], StructType::new(vec![field(0)]).into()).into(),
+                SubType::new(true, vec![5], StructType::new(vec![field(2)]).into()).into(),
+            ]));
+            builder
+                .add_function(Function::new(
+                    4,
+                    ResultType::empty(),
+                    vec![VariableInstruction::LocalGet(0).into()].into(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(validate(&module(2)), Ok(()));
+        assert_eq!(
+            function_error(&module(3)),
+            (
+                0,
+                vec![],
+                ValidationErrorKind::TypeMismatch {
+                    expected: reference(3),
+                    actual: reference(0),
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_gc_instructions() {
+        let reference = ValueType::NullableReference(HeapType::Index(0));
+        let module_with_body = |body: Vec<Instruction>| {
+            let mut builder = Module::builder();
+            builder
+                .add_recursive_type(
+                    StructType::new(vec![
+                        FieldType::immutable(PackedType::I8),
+                        FieldType::mutable(ValueType::I32),
+                    ])
+                    .into(),
+                )
+                .unwrap();
+            builder
+                .add_function_type(FunctionType::side_effect(vec![reference].into()))
+                .unwrap();
+            builder
+                .add_function(Function::new(1, ResultType::empty(), body.into()))
+                .unwrap();
+            builder.build()
+        };
+        let read = |instruction: GcInstruction| {
+            module_with_body(vec![
+                VariableInstruction::LocalGet(0).into(),
+                instruction.into(),
+                ParametricDrop::drop(),
+            ])
+        };
+
+        assert_eq!(
+            validate(&read(GcInstruction::StructGet(0, 1, None))),
+            Ok(())
+        );
+        assert_eq!(
+            function_error(&read(GcInstruction::StructGet(0, 0, None))),
+            (0, vec![1], ValidationErrorKind::InvalidFieldExtension(0))
+        );
+        assert_eq!(
+            function_error(&read(GcInstruction::StructGet(0, 2, None))),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::UnknownField { kind: 0, field: 2 }
+            )
+        );
+        assert_eq!(
+            function_error(&read(GcInstruction::ArrayLength)),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::NullableReference(HeapType::Array),
+                    actual: reference
+                }
+            )
+        );
+        assert_eq!(
+            function_error(&read(GcInstruction::Cast(ValueType::Reference(
+                HeapType::External
+            )))),
+            (
+                0,
+                vec![1],
+                ValidationErrorKind::TypeMismatch {
+                    expected: ValueType::ExternalReference,
+                    actual: reference
+                }
+            )
+        );
+        assert_eq!(
+            function_error(&module_with_body(vec![
+                VariableInstruction::LocalGet(0).into(),
+                1i32.into(),
+                GcInstruction::StructSet(0, 0).into(),
+            ])),
+            (0, vec![2], ValidationErrorKind::ImmutableField(0))
+        );
+        assert_eq!(
+            function_error(&module_with_body(vec![
+                GcInstruction::StructNew(1).into(),
+                ParametricDrop::drop(),
+            ])),
+            (0, vec![0], ValidationErrorKind::ExpectedStructType(1))
+        );
+    }
+
+    #[test]
+    fn valid_vector_instructions() {
+        let mut builder = builder_with_type(FunctionType::new(
+            vec![ValueType::V128].into(),
+            vec![ValueType::I32].into(),
+        ));
+        builder
+            .add_memory(Memory::from(Limit::unbounded(1)))
+            .unwrap();
+        builder
+            .add_function(Function::new(
+                0,
+                ResultType::empty(),
+                vec![
+                    0i32.into(),
+                    VariableInstruction::LocalGet(0).into(),
+                    VectorInstruction::LoadLane(
+                        IntegerVectorShape::I16x8,
+                        MemoryArgument::default_offset(1),
+                        7,
+                    )
+                    .into(),
+                    VectorInstruction::Constant(1).into(),
+                    VectorInstruction::Add(VectorShape::I16x8).into(),
+                    VectorInstruction::ExtractLane(
+                        VectorShape::I16x8,
+                        Some(SignExtension::Unsigned),
+                        7,
+                    )
+                    .into(),
+                ]
+                .into(),
+            ))
+            .unwrap();
+
+        assert_eq!(validate(&builder.build()), Ok(()));
+    }
+
+    #[test]
+    fn invalid_vector_instructions() {
+        let module = |instruction: VectorInstruction| {
+            let mut builder = builder_with_type(FunctionType::new(
+                vec![ValueType::V128].into(),
+                vec![ValueType::V128].into(),
+            ));
+            builder
+                .add_function(Function::new(
+                    0,
+                    ResultType::empty(),
+                    vec![
+                        VariableInstruction::LocalGet(0).into(),
+                        VariableInstruction::LocalGet(0).into(),
+                        instruction.into(),
+                    ]
+                    .into(),
+                ))
+                .unwrap();
+            builder.build()
+        };
+
+        assert_eq!(
+            function_error(&module(VectorInstruction::Multiply(VectorShape::I8x16))),
+            (
+                0,
+                vec![2],
+                ValidationErrorKind::UndefinedVectorInstruction(VectorInstruction::Multiply(
+                    VectorShape::I8x16
+                ))
+            )
+        );
+        assert_eq!(
+            function_error(&module(VectorInstruction::Shuffle([32; 16]))),
+            (
+                0,
+                vec![2],
+                ValidationErrorKind::InvalidLaneIndex {
+                    lane: 32,
+                    lanes: 32
+                }
+            )
+        );
+        assert_eq!(
+            function_error(&module(VectorInstruction::ReplaceLane(
+                VectorShape::F64x2,
+                2
+            ))),
+            (
+                0,
+                vec![2],
+                ValidationErrorKind::InvalidLaneIndex { lane: 2, lanes: 2 }
+            )
+        );
+        assert_eq!(
+            function_error(&module(VectorInstruction::Store(
+                MemoryArgument::default_offset(4)
+            ))),
+            (0, vec![2], ValidationErrorKind::UnknownMemory(0))
+        );
+    }
+
+    /// Shorthand for a drop instruction.
+    struct ParametricDrop;
+
+    impl ParametricDrop {
+        fn drop() -> Instruction {
+            crate::model::ParametricInstruction::Drop.into()
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::model::{
+    Data, DataMode, Element, ElementMode, Export, ExportDescription, Expression, Function, Global,
+    Import, ImportDescription, Memory, Start, Table, Tag, TypeIndex,
+};
+use crate::validator::instructions::{
+    validate_constant_expression, ExpressionError, ExpressionValidator,
+};
+use crate::validator::types::{validate_memory_type, validate_table_type, validate_value_type};
+use crate::validator::{Context, ValidationError, ValidationErrorKind};
+use std::collections::HashSet;
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#imports>
+pub fn validate_import(import: &Import, context: &Context) -> Result<(), ValidationErrorKind> {
+    match import.description() {
+        ImportDescription::Function(kind) => context.function_type(*kind).map(|_| ()),
+        ImportDescription::Table(kind) => validate_table_type(kind, context),
+        ImportDescription::Memory(kind) => validate_memory_type(kind),
+        ImportDescription::Global(kind) => validate_value_type(kind.kind(), context),
+        ImportDescription::Tag(kind) => validate_tag_type(*kind, context),
+    }
+}
+
+/// The initializer of a table must be a constant expression of the table's reference type.
+/// Tables without an initializer start out filled with null references,
+/// so their reference type must be nullable.
+///
+/// See <https://webassembly.github.io/gc/core/valid/modules.html#tables>
+pub fn validate_table(table: &Table, context: &Context) -> Result<(), ValidationErrorKind> {
+    validate_table_type(table.kind(), context)?;
+
+    match table.initializer() {
+        Some(initializer) => {
+            validate_constant_expression(initializer, table.kind().kind().into(), context)
+        }
+        None if table.kind().kind().is_nullable() => Ok(()),
+        None => Err(ValidationErrorKind::NonDefaultableTable),
+    }
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#memories>
+pub fn validate_memory(memory: &Memory) -> Result<(), ValidationErrorKind> {
+    validate_memory_type(memory.kind())
+}
+
+/// See <https://webassembly.github.io/exception-handling/core/valid/modules.html#tags>
+pub fn validate_tag(tag: &Tag, context: &Context) -> Result<(), ValidationErrorKind> {
+    validate_tag_type(tag.kind(), context)
+}
+
+/// The type of a tag must be a function type without results.
+///
+/// See <https://webassembly.github.io/exception-handling/core/valid/types.html#tag-types>
+fn validate_tag_type(kind: TypeIndex, context: &Context) -> Result<(), ValidationErrorKind> {
+    if context.function_type(kind)?.results().is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationErrorKind::InvalidTagType)
+    }
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#globals>
+pub fn validate_global(global: &Global, context: &Context) -> Result<(), ValidationErrorKind> {
+    validate_value_type(global.kind().kind(), context)?;
+    validate_constant_expression(global.initializer(), global.kind().kind(), context)
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#functions>
+pub fn validate_function(function: &Function, context: &Context) -> Result<(), ExpressionError> {
+    let kind = context
+        .function_type(function.kind())
+        .map_err(|kind| (Default::default(), kind))?;
+
+    for local in function.locals().kinds() {
+        validate_value_type(*local, context).map_err(|kind| (Default::default(), kind))?;
+    }
+
+    ExpressionValidator::new(
+        context,
+        kind.parameters().kinds(),
+        function.locals().kinds(),
+    )
+    .validate(function_body(function)?, kind.results().kinds())
+}
+
+/// The body of the function, or an error if a lazily parsed body is not a valid expression.
+#[cfg(feature = "parser")]
+fn function_body(function: &Function) -> Result<&Expression, ExpressionError> {
+    function.try_body().map_err(|error| {
+        (
+            Default::default(),
+            ValidationErrorKind::MalformedBody(error.to_string()),
+        )
+    })
+}
+
+/// The body of the function, which is always decoded without the parser.
+#[cfg(not(feature = "parser"))]
+fn function_body(function: &Function) -> Result<&Expression, ExpressionError> {
+    Ok(function.body())
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#element-segments>
+pub fn validate_element(element: &Element, context: &Context) -> Result<(), ValidationErrorKind> {
+    if let ElementMode::Active(table, offset) = element.mode() {
+        let table = context.table(*table)?;
+
+        if !context.matches(element.kind().into(), table.kind().into()) {
+            return Err(ValidationErrorKind::TypeMismatch {
+                expected: table.kind().into(),
+                actual: element.kind().into(),
+            });
+        }
+
+        validate_constant_expression(offset, table.index_type().into(), context)?;
+    }
+
+    validate_value_type(element.kind().into(), context)?;
+
+    for initializer in element.initializers() {
+        validate_constant_expression(initializer, element.kind().into(), context)?;
+    }
+
+    Ok(())
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#data-segments>
+pub fn validate_data(data: &Data, context: &Context) -> Result<(), ValidationErrorKind> {
+    if let DataMode::Active(memory, offset) = data.mode() {
+        let memory = context.memory(*memory)?;
+
+        validate_constant_expression(offset, memory.index_type().into(), context)?;
+    }
+
+    Ok(())
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#start-function>
+pub fn validate_start(start: &Start, context: &Context) -> Result<(), ValidationErrorKind> {
+    let kind = context.function(start.function())?;
+
+    if kind.parameters().is_empty() && kind.results().is_empty() {
+        Ok(())
+    } else {
+        Err(ValidationErrorKind::InvalidStartFunction)
+    }
+}
+
+/// Validates the descriptions of the exports and verifies the export names are unique.
+///
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#exports>
+pub fn validate_exports(exports: &[Export], context: &Context) -> Result<(), ValidationError> {
+    let mut names = HashSet::new();
+
+    for (index, export) in exports.iter().enumerate() {
+        validate_export(export, context).map_err(|kind| ValidationError::Export(index, kind))?;
+
+        if !names.insert(export.name().value()) {
+            return Err(ValidationError::Export(
+                index,
+                ValidationErrorKind::DuplicateExportName(export.name().clone()),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// See <https://webassembly.github.io/spec/core/valid/modules.html#exports>
+fn validate_export(export: &Export, context: &Context) -> Result<(), ValidationErrorKind> {
+    match *export.description() {
+        ExportDescription::Function(function) => context.function(function).map(|_| ()),
+        ExportDescription::Table(table) => context.table(table).map(|_| ()),
+        ExportDescription::Memory(memory) => context.memory(memory).map(|_| ()),
+        ExportDescription::Global(global) => context.global(global).map(|_| ()),
+        ExportDescription::Tag(tag) => context.tag(tag).map(|_| ()),
+    }
+}
@@ -0,0 +1,204 @@
+use crate::model::{
+    BlockType, CompositeType, FieldType, FunctionType, HeapType, IndexType, Limit, MemoryType,
+    Mutability, ResultType, StorageType, SubType, TableType, TypeIndex, ValueType,
+};
+use crate::validator::{Context, ValidationErrorKind};
+
+/// The maximum number of pages a memory with a 32-bit index type may have.
+const MAX_PAGES: u64 = 1 << 16;
+
+/// The maximum number of pages a memory with a 64-bit index type may have.
+const MAX_PAGES_64: u64 = 1 << 48;
+
+/// Validates that the limits are within the given range.
+///
+/// See <https://webassembly.github.io/spec/core/valid/types.html#limits>
+pub fn validate_limits(limits: &Limit, range: u64) -> Result<(), ValidationErrorKind> {
+    if limits.min() > range {
+        return Err(ValidationErrorKind::LimitsOutOfRange(range));
+    }
+
+    match limits.max() {
+        Some(max) if max > range => Err(ValidationErrorKind::LimitsOutOfRange(range)),
+        Some(max) if max < limits.min() => Err(ValidationErrorKind::InvalidLimits {
+            min: limits.min(),
+            max,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Validates the limits and the reference type of a table type.
+///
+/// See <https://webassembly.github.io/spec/core/valid/types.html#table-types>
+pub fn validate_table_type(kind: &TableType, context: &Context) -> Result<(), ValidationErrorKind> {
+    match kind.index_type() {
+        IndexType::I32 => validate_limits(kind.limits(), u32::MAX as u64)?,
+        IndexType::I64 => validate_limits(kind.limits(), u64::MAX)?,
+    }
+
+    validate_value_type(kind.kind().into(), context)
+}
+
+/// Validates the limits of a memory type, which must be bounded if the memory is shared.
+///
+/// See <https://webassembly.github.io/spec/core/valid/types.html#memory-types>
+pub fn validate_memory_type(kind: &MemoryType) -> Result<(), ValidationErrorKind> {
+    match kind.index_type() {
+        IndexType::I32 => validate_limits(kind.limits(), MAX_PAGES)?,
+        IndexType::I64 => validate_limits(kind.limits(), MAX_PAGES_64)?,
+    }
+
+    if kind.is_shared() && kind.limits().max().is_none() {
+        Err(ValidationErrorKind::UnboundedSharedMemory)
+    } else {
+        Ok(())
+    }
+}
+
+/// Resolves the function type of a block type.
+///
+/// See <https://webassembly.github.io/spec/core/valid/types.html#block-types>
+pub fn resolve_block_type(
+    kind: &BlockType,
+    context: &Context,
+) -> Result<FunctionType, ValidationErrorKind> {
+    match kind {
+        BlockType::None => Ok(FunctionType::runnable()),
+        BlockType::Index(index) => context.function_type(*index).cloned(),
+        BlockType::ValueType(kind) => Ok(FunctionType::nullary(ResultType::new(vec![*kind]))),
+    }
+}
+
+/// Validates a sub type of the type section at the given index,
+/// in a recursive type whose types end before the index `end`.
+/// A sub type declares at most one supertype, which must precede it, must not be final,
+/// and must be matched by the composite type of the sub type.
+///
+/// See <https://webassembly.github.io/gc/core/valid/types.html#sub-types>
+pub fn validate_sub_type(
+    index: TypeIndex,
+    end: TypeIndex,
+    kind: &SubType,
+    context: &Context,
+) -> Result<(), ValidationErrorKind> {
+    validate_composite_type(kind.kind(), end, context)?;
+
+    match *kind.supertypes() {
+        [] => Ok(()),
+        [supertype] if supertype >= index => Err(ValidationErrorKind::UnknownType(supertype)),
+        [supertype] => {
+            let expected = context.sub_type(supertype)?;
+
+            if expected.is_final() || !composite_matches(kind.kind(), expected.kind(), context) {
+                Err(ValidationErrorKind::InvalidSupertype(supertype))
+            } else {
+                Ok(())
+            }
+        }
+        ref supertypes => Err(ValidationErrorKind::InvalidSupertypeCount(supertypes.len())),
+    }
+}
+
+/// Validates that the concrete heap types referenced by a composite type are defined
+/// before the index `end`, i.e. either by a preceding recursive type or by its own recursive type.
+///
+/// See <https://webassembly.github.io/gc/core/valid/types.html#composite-types>
+fn validate_composite_type(
+    kind: &CompositeType,
+    end: TypeIndex,
+    context: &Context,
+) -> Result<(), ValidationErrorKind> {
+    let kinds: Vec<ValueType> = match kind {
+        CompositeType::Function(kind) => kind
+            .parameters()
+            .kinds()
+            .iter()
+            .chain(kind.results().kinds())
+            .copied()
+            .collect(),
+        CompositeType::Struct(kind) => kind
+            .fields()
+            .iter()
+            .map(|field| field.kind().unpacked())
+            .collect(),
+        CompositeType::Array(kind) => vec![kind.field().kind().unpacked()],
+    };
+
+    for kind in kinds {
+        if let Some(HeapType::Index(index)) = kind.heap_type() {
+            if index >= end {
+                return Err(ValidationErrorKind::UnknownType(index));
+            }
+        }
+
+        validate_value_type(kind, context)?;
+    }
+
+    Ok(())
+}
+
+/// Validates that the heap type of a reference type is defined.
+///
+/// See <https://webassembly.github.io/gc/core/valid/types.html#value-types>
+pub fn validate_value_type(kind: ValueType, context: &Context) -> Result<(), ValidationErrorKind> {
+    if let Some(heap) = kind.heap_type() {
+        context.top(heap)?;
+    }
+
+    Ok(())
+}
+
+/// Returns true if the composite type `actual` matches `expected`.
+/// Function types match contravariantly in their parameters and covariantly in their results,
+/// while structure types may append fields to those of the expected type.
+///
+/// See <https://webassembly.github.io/gc/core/valid/matching.html#composite-types>
+fn composite_matches(actual: &CompositeType, expected: &CompositeType, context: &Context) -> bool {
+    let all_match = |actual: &[ValueType], expected: &[ValueType]| {
+        actual.len() == expected.len()
+            && actual
+                .iter()
+                .zip(expected)
+                .all(|(actual, expected)| context.matches(*actual, *expected))
+    };
+
+    match (actual, expected) {
+        (CompositeType::Function(actual), CompositeType::Function(expected)) => {
+            all_match(expected.parameters().kinds(), actual.parameters().kinds())
+                && all_match(actual.results().kinds(), expected.results().kinds())
+        }
+        (CompositeType::Struct(actual), CompositeType::Struct(expected)) => {
+            actual.fields().len() >= expected.fields().len()
+                && actual
+                    .fields()
+                    .iter()
+                    .zip(expected.fields())
+                    .all(|(actual, expected)| field_matches(*actual, *expected, context))
+        }
+        (CompositeType::Array(actual), CompositeType::Array(expected)) => {
+            field_matches(actual.field(), expected.field(), context)
+        }
+        _ => false,
+    }
+}
+
+/// Returns true if the field type `actual` matches `expected`.
+/// Mutable fields are invariant, while immutable fields are covariant.
+///
+/// See <https://webassembly.github.io/gc/core/valid/matching.html#field-types>
+fn field_matches(actual: FieldType, expected: FieldType, context: &Context) -> bool {
+    if actual.mutability() != expected.mutability() {
+        return false;
+    }
+
+    match (actual.kind(), expected.kind(), expected.mutability()) {
+        (StorageType::Value(actual), StorageType::Value(expected), Mutability::Immutable) => {
+            context.matches(actual, expected)
+        }
+        (StorageType::Value(actual), StorageType::Value(expected), Mutability::Mutable) => {
+            context.equivalent(actual, expected)
+        }
+        (actual, expected, _) => actual == expected,
+    }
+}